@@ -0,0 +1,13 @@
+//! Fuzz AtlasManifest deserialization and validation
+
+#![no_main]
+
+use cra_core::atlas::{AtlasManifest, AtlasValidator};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(manifest) = serde_json::from_slice::<AtlasManifest>(data) {
+        let _ = manifest.validate();
+        let _ = AtlasValidator::new().validate(&manifest);
+    }
+});
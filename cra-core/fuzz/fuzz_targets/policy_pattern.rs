@@ -0,0 +1,15 @@
+//! Fuzz policy pattern matching
+
+#![no_main]
+
+use cra_core::carp::PolicyEvaluator;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &str)| {
+    let (pattern, action_id) = input;
+    let evaluator = PolicyEvaluator::new();
+
+    let _ = evaluator.pattern_matches(pattern, action_id);
+    assert!(evaluator.pattern_matches("*", action_id));
+    assert!(evaluator.pattern_matches(action_id, action_id));
+});
@@ -0,0 +1,17 @@
+//! Fuzz TRACE JSONL import and chain verification (core and standalone)
+
+#![no_main]
+
+use cra_core::trace::{ChainVerifier, TraceCollector};
+use cra_trace_verify::{verify_jsonl, GENESIS_HASH};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let mut collector = TraceCollector::new();
+    if collector.import_jsonl("fuzz", data).is_ok() {
+        let events = collector.get_events("fuzz").unwrap_or_default();
+        let _ = ChainVerifier::verify(&events);
+    }
+
+    let _ = verify_jsonl(data, GENESIS_HASH);
+});
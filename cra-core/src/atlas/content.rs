@@ -0,0 +1,257 @@
+//! Content Policies
+//!
+//! A `content` policy governs the text flowing into and out of an agent
+//! rather than the actions it takes: PII, profanity, restricted topics.
+//! Its rules live in the policy's `parameters`:
+//!
+//! ```json
+//! {
+//!   "policy_id": "no-pii",
+//!   "type": "content",
+//!   "reason": "Personal data must not leave the session",
+//!   "parameters": {
+//!     "stages": ["output"],
+//!     "action": "redact",
+//!     "detectors": [
+//!       { "type": "regex", "name": "email", "patterns": ["[\\w.+-]+@[\\w-]+\\.[\\w.]+"] },
+//!       { "type": "wordlist", "name": "profanity", "words": ["darn"] },
+//!       { "type": "classifier", "name": "medical", "classifier": "topic.medical", "threshold": 0.8 }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! The resolver only parses and validates these policies; the wrapper
+//! enforces them in its input and output hooks, where classifiers are
+//! registered by name.
+
+use serde::{Deserialize, Serialize};
+
+use super::manifest::{AtlasPolicy, PolicyType};
+use crate::error::{CRAError, Result};
+
+/// Text a content policy applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentStage {
+    /// User input, before it reaches the agent
+    Input,
+    /// Agent output, before it reaches the user
+    Output,
+}
+
+/// What happens when a detector fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentAction {
+    /// Reject the text
+    Block,
+    /// Replace the matched spans
+    Redact,
+    /// Let the text through and record the detection
+    Flag,
+}
+
+/// A detector within a content policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentDetector {
+    /// Fires when any regular expression matches
+    Regex { name: String, patterns: Vec<String> },
+    /// Fires on any listed word or phrase (case-insensitive, whole words)
+    Wordlist { name: String, words: Vec<String> },
+    /// Fires when a named classifier scores the text at or above `threshold`
+    Classifier {
+        name: String,
+        classifier: String,
+        #[serde(default = "default_threshold")]
+        threshold: f64,
+    },
+}
+
+fn default_threshold() -> f64 {
+    0.5
+}
+
+impl ContentDetector {
+    /// Name recorded with detections
+    pub fn name(&self) -> &str {
+        match self {
+            ContentDetector::Regex { name, .. }
+            | ContentDetector::Wordlist { name, .. }
+            | ContentDetector::Classifier { name, .. } => name,
+        }
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        if self.name().is_empty() {
+            return Err("detector name cannot be empty".to_string());
+        }
+        match self {
+            ContentDetector::Regex { name, patterns } => {
+                if patterns.is_empty() {
+                    return Err(format!("regex detector '{}' has no patterns", name));
+                }
+                for pattern in patterns {
+                    regex::Regex::new(pattern)
+                        .map_err(|e| format!("detector '{}': invalid pattern: {}", name, e))?;
+                }
+            }
+            ContentDetector::Wordlist { name, words } => {
+                if words.iter().all(|w| w.trim().is_empty()) {
+                    return Err(format!("wordlist detector '{}' has no words", name));
+                }
+            }
+            ContentDetector::Classifier { name, classifier, threshold } => {
+                if classifier.is_empty() {
+                    return Err(format!("classifier detector '{}' names no classifier", name));
+                }
+                if !(0.0..=1.0).contains(threshold) {
+                    return Err(format!("detector '{}': threshold must be between 0 and 1", name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameters of a `content` policy
+#[derive(Debug, Clone, Deserialize)]
+struct ContentParameters {
+    #[serde(default = "all_stages")]
+    stages: Vec<ContentStage>,
+    action: ContentAction,
+    detectors: Vec<ContentDetector>,
+}
+
+fn all_stages() -> Vec<ContentStage> {
+    vec![ContentStage::Input, ContentStage::Output]
+}
+
+/// A parsed `content` policy, in the shape sent to wrappers at bootstrap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentPolicy {
+    /// ID of the atlas policy
+    pub policy_id: String,
+    /// Stages the policy applies to
+    pub stages: Vec<ContentStage>,
+    /// What to do on detection
+    pub action: ContentAction,
+    /// Detectors, any of which triggers the policy
+    pub detectors: Vec<ContentDetector>,
+    /// Reason shown when text is blocked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ContentPolicy {
+    /// Parse and validate a `content` atlas policy
+    pub fn from_policy(policy: &AtlasPolicy) -> Result<Self> {
+        let invalid = |reason: String| CRAError::InvalidAtlasManifest {
+            reason: format!("content policy '{}': {}", policy.policy_id, reason),
+        };
+
+        if policy.policy_type != PolicyType::Content {
+            return Err(invalid(format!("policy type is '{}'", policy.policy_type)));
+        }
+        let parameters = policy
+            .parameters
+            .clone()
+            .ok_or_else(|| invalid("missing parameters".to_string()))?;
+        let parameters: ContentParameters =
+            serde_json::from_value(parameters).map_err(|e| invalid(e.to_string()))?;
+
+        if parameters.stages.is_empty() {
+            return Err(invalid("no stages".to_string()));
+        }
+        if parameters.detectors.is_empty() {
+            return Err(invalid("no detectors".to_string()));
+        }
+        for detector in &parameters.detectors {
+            detector.validate().map_err(invalid)?;
+        }
+
+        Ok(Self {
+            policy_id: policy.policy_id.clone(),
+            stages: parameters.stages,
+            action: parameters.action,
+            detectors: parameters.detectors,
+            reason: policy.reason.clone(),
+        })
+    }
+
+    /// Whether the policy applies to a stage
+    pub fn applies_to(&self, stage: ContentStage) -> bool {
+        self.stages.contains(&stage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn content_policy(parameters: serde_json::Value) -> AtlasPolicy {
+        AtlasPolicy {
+            policy_id: "no-pii".to_string(),
+            policy_type: PolicyType::Content,
+            actions: vec![],
+            reason: Some("No personal data".to_string()),
+            parameters: Some(parameters),
+        }
+    }
+
+    #[test]
+    fn test_parse_content_policy() {
+        let policy = ContentPolicy::from_policy(&content_policy(json!({
+            "stages": ["output"],
+            "action": "redact",
+            "detectors": [
+                { "type": "regex", "name": "email", "patterns": ["[a-z]+@[a-z]+\\.com"] },
+                { "type": "wordlist", "name": "profanity", "words": ["darn"] },
+                { "type": "classifier", "name": "medical", "classifier": "topic.medical" }
+            ]
+        })))
+        .unwrap();
+
+        assert_eq!(policy.action, ContentAction::Redact);
+        assert!(policy.applies_to(ContentStage::Output));
+        assert!(!policy.applies_to(ContentStage::Input));
+        assert_eq!(policy.detectors[2].name(), "medical");
+        assert!(matches!(
+            policy.detectors[2],
+            ContentDetector::Classifier { threshold, .. } if threshold == 0.5
+        ));
+    }
+
+    #[test]
+    fn test_stages_default_to_both() {
+        let policy = ContentPolicy::from_policy(&content_policy(json!({
+            "action": "flag",
+            "detectors": [{ "type": "wordlist", "name": "w", "words": ["x"] }]
+        })))
+        .unwrap();
+        assert!(policy.applies_to(ContentStage::Input));
+        assert!(policy.applies_to(ContentStage::Output));
+    }
+
+    #[test]
+    fn test_invalid_content_policies() {
+        let cases = [
+            json!({ "action": "block", "detectors": [] }),
+            json!({ "action": "shred", "detectors": [{ "type": "wordlist", "name": "w", "words": ["x"] }] }),
+            json!({ "action": "block", "detectors": [{ "type": "regex", "name": "r", "patterns": ["("] }] }),
+            json!({ "action": "block", "detectors": [{ "type": "wordlist", "name": "w", "words": [" "] }] }),
+            json!({ "action": "block", "detectors": [{ "type": "classifier", "name": "c", "classifier": "t", "threshold": 2.0 }] }),
+            json!({ "action": "block", "stages": [], "detectors": [{ "type": "wordlist", "name": "w", "words": ["x"] }] }),
+        ];
+        for parameters in cases {
+            let err = ContentPolicy::from_policy(&content_policy(parameters.clone())).unwrap_err();
+            assert!(err.to_string().contains("no-pii"), "{}: {}", parameters, err);
+        }
+
+        let mut missing = content_policy(json!({}));
+        missing.parameters = None;
+        assert!(ContentPolicy::from_policy(&missing).is_err());
+    }
+}
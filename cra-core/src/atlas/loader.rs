@@ -12,6 +12,7 @@ use std::path::{Path, PathBuf};
 use crate::error::{CRAError, Result};
 
 use super::manifest::AtlasManifest;
+use super::testing::AtlasTest;
 
 /// Atlas loader for loading atlases from various sources
 pub struct AtlasLoader {
@@ -132,6 +133,7 @@ impl AtlasLoader {
     /// ```text
     /// atlas-name/
     /// ├── atlas.json          # Manifest (required)
+    /// ├── tests.json          # Test scenarios (optional)
     /// ├── context/            # Context documents
     /// │   └── *.md
     /// └── adapters/           # Platform-specific configs
@@ -163,11 +165,27 @@ impl AtlasLoader {
             }
         })?;
 
-        let manifest: AtlasManifest =
+        let mut manifest: AtlasManifest =
             serde_json::from_str(&manifest_content).map_err(|e| CRAError::InvalidAtlasManifest {
                 reason: format!("{}: {}", manifest_path.display(), e),
             })?;
 
+        // Tests shipped beside the manifest run after any declared inline
+        let tests_path = path.join("tests.json");
+        if tests_path.is_file() {
+            let tests_content = fs::read_to_string(&tests_path).map_err(|e| {
+                CRAError::AtlasLoadError {
+                    path: tests_path.display().to_string(),
+                    reason: e.to_string(),
+                }
+            })?;
+            let tests: Vec<AtlasTest> =
+                serde_json::from_str(&tests_content).map_err(|e| CRAError::InvalidAtlasManifest {
+                    reason: format!("{}: {}", tests_path.display(), e),
+                })?;
+            manifest.tests.extend(tests);
+        }
+
         if self.validate_on_load {
             manifest.validate().map_err(|errors| {
                 CRAError::InvalidAtlasManifest {
@@ -415,6 +433,40 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_load_with_checkpoints() {
+        let json = r#"{
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.checkpoints",
+            "version": "1.0.0",
+            "name": "Checkpoints",
+            "description": "",
+            "capabilities": [
+                { "capability_id": "write", "name": "Write", "actions": [] }
+            ],
+            "checkpoints": [
+                {
+                    "checkpoint_id": "onboarding",
+                    "name": "Onboarding",
+                    "trigger": { "type": "session_start" },
+                    "unlock_capabilities": ["write"]
+                }
+            ]
+        }"#;
+
+        let mut loader = AtlasLoader::new();
+        let atlas_id = loader.load_from_json(json).unwrap();
+        let manifest = loader.get_manifest(&atlas_id).unwrap();
+        assert_eq!(manifest.checkpoints.len(), 1);
+        assert_eq!(manifest.get_session_start_checkpoints().len(), 1);
+
+        // Same manifest but unlocking a capability that doesn't exist
+        let invalid = json.replace(r#""unlock_capabilities": ["write"]"#, r#""unlock_capabilities": ["admin"]"#);
+        let mut loader = AtlasLoader::new();
+        let err = loader.load_from_json(&invalid).unwrap_err();
+        assert!(err.to_string().contains("unknown capability to unlock: admin"));
+    }
+
     #[test]
     fn test_list_and_unload() {
         let mut loader = AtlasLoader::new();
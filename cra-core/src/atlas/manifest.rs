@@ -7,7 +7,8 @@ use serde_json::Value;
 
 use super::VERSION;
 use super::steward::StewardConfig;
-use crate::carp::{StewardCheckpointDef, CheckpointTrigger};
+use super::testing::AtlasTest;
+use crate::carp::{StewardCheckpointDef, CheckpointTrigger, MatchMode};
 
 /// The main Atlas manifest structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +64,10 @@ pub struct AtlasManifest {
     #[serde(default)]
     pub policies: Vec<AtlasPolicy>,
 
+    /// Namespace defaults (e.g. a risk tier for everything under `admin`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<AtlasNamespace>,
+
     /// Action definitions
     #[serde(default)]
     pub actions: Vec<AtlasAction>,
@@ -74,6 +79,10 @@ pub struct AtlasManifest {
     /// External sources (repositories, documentation, demos)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sources: Option<AtlasSources>,
+
+    /// Scenarios checked by `AtlasValidator::run_tests`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<AtlasTest>,
 }
 
 impl AtlasManifest {
@@ -87,6 +96,55 @@ impl AtlasManifest {
         self.actions.iter().find(|a| a.action_id == action_id)
     }
 
+    /// Get all actions under a namespace (see [`super::namespace`])
+    pub fn actions_in_namespace(&self, namespace: &str) -> Vec<&AtlasAction> {
+        self.actions
+            .iter()
+            .filter(|a| super::namespace::in_namespace(namespace, &a.action_id))
+            .collect()
+    }
+
+    /// Risk tier an action inherits from its closest namespace with a default
+    ///
+    /// Returns `None` if no enclosing namespace declares a risk tier.
+    pub fn namespace_risk_tier(&self, action_id: &str) -> Option<&str> {
+        self.namespaces
+            .iter()
+            .filter(|ns| ns.risk_tier.is_some() && super::namespace::in_namespace(&ns.namespace, action_id))
+            .max_by_key(|ns| ns.namespace.trim_end_matches('.').len())
+            .and_then(|ns| ns.risk_tier.as_deref())
+    }
+
+    /// Fill in risk tiers of actions that do not declare one
+    ///
+    /// Actions take the tier of their closest namespace default, or `low`.
+    /// Called by the resolver when the atlas is loaded.
+    pub fn apply_namespace_defaults(&mut self) {
+        let inherited: Vec<Option<String>> = self
+            .actions
+            .iter()
+            .map(|a| {
+                a.risk_tier
+                    .is_empty()
+                    .then(|| self.namespace_risk_tier(&a.action_id).unwrap_or("low").to_string())
+            })
+            .collect();
+        for (action, tier) in self.actions.iter_mut().zip(inherited) {
+            if let Some(tier) = tier {
+                action.risk_tier = tier;
+            }
+        }
+    }
+
+    /// Parse the atlas's `content` policies (see [`super::content`])
+    pub fn content_policies(&self) -> crate::error::Result<Vec<super::content::ContentPolicy>> {
+        self.policies
+            .iter()
+            .filter(|p| p.policy_type == PolicyType::Content)
+            .map(super::content::ContentPolicy::from_policy)
+            .collect()
+    }
+
     /// Get a policy by ID
     pub fn get_policy(&self, policy_id: &str) -> Option<&AtlasPolicy> {
         self.policies.iter().find(|p| p.policy_id == policy_id)
@@ -99,15 +157,10 @@ impl AtlasManifest {
             .find(|c| c.capability_id == capability_id)
     }
 
-    /// Get all actions for a capability
+    /// Get all actions for a capability, expanding namespace patterns
     pub fn get_capability_actions(&self, capability_id: &str) -> Vec<&AtlasAction> {
         self.get_capability(capability_id)
-            .map(|cap| {
-                cap.actions
-                    .iter()
-                    .filter_map(|action_id| self.get_action(action_id))
-                    .collect()
-            })
+            .map(|cap| self.actions.iter().filter(|a| cap.includes(&a.action_id)).collect())
             .unwrap_or_default()
     }
 
@@ -126,6 +179,14 @@ impl AtlasManifest {
             .collect()
     }
 
+    /// Get all checkpoints with a custom trigger
+    pub fn get_custom_checkpoints(&self) -> Vec<&StewardCheckpointDef> {
+        self.checkpoints
+            .iter()
+            .filter(|c| matches!(c.trigger, CheckpointTrigger::Custom { .. }))
+            .collect()
+    }
+
     /// Get all checkpoints for a given action pattern
     pub fn get_action_checkpoints(&self, action_id: &str) -> Vec<&StewardCheckpointDef> {
         self.checkpoints
@@ -221,10 +282,10 @@ impl AtlasManifest {
             }
         }
 
-        // Validate capability actions exist
+        // Validate capability actions exist (patterns must match at least one)
         for capability in &self.capabilities {
             for action_id in &capability.actions {
-                if self.get_action(action_id).is_none() {
+                if !self.actions.iter().any(|a| super::namespace::pattern_matches(action_id, &a.action_id)) {
                     errors.push(format!(
                         "Capability {} references unknown action: {}",
                         capability.capability_id, action_id
@@ -272,6 +333,54 @@ impl AtlasManifest {
                     ));
                 }
             }
+
+            // Validate trigger-specific references
+            match &checkpoint.trigger {
+                CheckpointTrigger::Keyword { patterns, match_mode: MatchMode::Regex, .. } => {
+                    for pattern in patterns {
+                        if let Err(e) = regex::Regex::new(pattern) {
+                            errors.push(format!(
+                                "Checkpoint {} has invalid keyword regex '{}': {}",
+                                checkpoint.checkpoint_id, pattern, e
+                            ));
+                        }
+                    }
+                }
+                CheckpointTrigger::CapabilityAccess { capability_ids } => {
+                    for cap_id in capability_ids {
+                        if self.get_capability(cap_id).is_none() {
+                            errors.push(format!(
+                                "Checkpoint {} is triggered by unknown capability: {}",
+                                checkpoint.checkpoint_id, cap_id
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            // Validate questions have unique IDs and valid answer patterns
+            let mut question_ids: Vec<&str> = checkpoint.questions.iter().map(|q| q.question_id.as_str()).collect();
+            question_ids.sort();
+            for window in question_ids.windows(2) {
+                if window[0] == window[1] {
+                    errors.push(format!(
+                        "Checkpoint {} has duplicate question_id: {}",
+                        checkpoint.checkpoint_id, window[0]
+                    ));
+                }
+            }
+            for question in &checkpoint.questions {
+                let pattern = question.validation.as_ref().and_then(|v| v.pattern.as_ref());
+                if let Some(pattern) = pattern {
+                    if let Err(e) = regex::Regex::new(pattern) {
+                        errors.push(format!(
+                            "Checkpoint {} question {} has invalid answer pattern '{}': {}",
+                            checkpoint.checkpoint_id, question.question_id, pattern, e
+                        ));
+                    }
+                }
+            }
         }
 
         if errors.is_empty() {
@@ -306,9 +415,11 @@ impl AtlasManifestBuilder {
                 context_packs: vec![],
                 context_blocks: vec![],
                 policies: vec![],
+                namespaces: vec![],
                 actions: vec![],
                 dependencies: None,
                 sources: None,
+                tests: vec![],
             },
         }
     }
@@ -358,11 +469,21 @@ impl AtlasManifestBuilder {
         self
     }
 
+    pub fn add_namespace(mut self, namespace: AtlasNamespace) -> Self {
+        self.manifest.namespaces.push(namespace);
+        self
+    }
+
     pub fn add_action(mut self, action: AtlasAction) -> Self {
         self.manifest.actions.push(action);
         self
     }
 
+    pub fn add_test(mut self, test: AtlasTest) -> Self {
+        self.manifest.tests.push(test);
+        self
+    }
+
     pub fn add_checkpoint(mut self, checkpoint: StewardCheckpointDef) -> Self {
         self.manifest.checkpoints.push(checkpoint);
         self
@@ -391,7 +512,7 @@ pub struct AtlasCapability {
     #[serde(default)]
     pub description: String,
 
-    /// Action IDs included in this capability
+    /// Action IDs or namespace patterns (`ticket.*`, `ticket.**`) included in this capability
     pub actions: Vec<String>,
 }
 
@@ -411,6 +532,43 @@ impl AtlasCapability {
         self.description = description;
         self
     }
+
+    /// Whether the capability includes an action, directly or by pattern
+    pub fn includes(&self, action_id: &str) -> bool {
+        self.actions.iter().any(|p| super::namespace::pattern_matches(p, action_id))
+    }
+}
+
+/// Defaults for every action under a namespace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasNamespace {
+    /// Namespace prefix (e.g. "ticket" or "ticket.admin")
+    pub namespace: String,
+
+    /// Description of the namespace
+    #[serde(default)]
+    pub description: String,
+
+    /// Risk tier for actions that do not declare one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_tier: Option<String>,
+}
+
+impl AtlasNamespace {
+    /// Create a namespace without defaults
+    pub fn new(namespace: String) -> Self {
+        Self {
+            namespace,
+            description: String::new(),
+            risk_tier: None,
+        }
+    }
+
+    /// Set the default risk tier
+    pub fn with_risk_tier(mut self, tier: RiskTier) -> Self {
+        self.risk_tier = Some(tier.to_string());
+        self
+    }
 }
 
 /// A context pack containing related content (file-based)
@@ -493,7 +651,8 @@ pub struct AtlasPolicy {
     #[serde(rename = "type")]
     pub policy_type: PolicyType,
 
-    /// Action patterns this policy applies to
+    /// Action patterns this policy applies to (empty for content policies)
+    #[serde(default)]
     pub actions: Vec<String>,
 
     /// Reason for this policy (shown when triggered)
@@ -573,6 +732,10 @@ pub enum PolicyType {
     RequiresApproval,
     /// Budget/cost limit
     Budget,
+    /// Input/output text rules, enforced by the wrapper
+    Content,
+    /// Decision delegated to an external policy engine (e.g. OPA) over HTTP
+    External,
 }
 
 impl std::fmt::Display for PolicyType {
@@ -583,6 +746,8 @@ impl std::fmt::Display for PolicyType {
             PolicyType::RateLimit => write!(f, "rate_limit"),
             PolicyType::RequiresApproval => write!(f, "requires_approval"),
             PolicyType::Budget => write!(f, "budget"),
+            PolicyType::Content => write!(f, "content"),
+            PolicyType::External => write!(f, "external"),
         }
     }
 }
@@ -607,7 +772,10 @@ pub struct AtlasAction {
     pub returns_schema: Option<Value>,
 
     /// Risk tier classification
-    #[serde(default = "default_risk_tier")]
+    ///
+    /// Empty when not declared: the action then inherits its namespace's
+    /// default (see [`AtlasManifest::apply_namespace_defaults`]), or `low`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub risk_tier: String,
 
     /// Whether this action is idempotent
@@ -619,10 +787,6 @@ pub struct AtlasAction {
     pub executor: Option<String>,
 }
 
-fn default_risk_tier() -> String {
-    "low".to_string()
-}
-
 impl AtlasAction {
     /// Create a new action
     pub fn new(action_id: String, name: String, description: String) -> Self {
@@ -996,6 +1160,146 @@ mod tests {
         assert!(manifest.validate().is_ok());
     }
 
+    #[test]
+    fn test_checkpoints_serialization_roundtrip() {
+        let json = json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.roundtrip",
+            "version": "1.0.0",
+            "name": "Roundtrip",
+            "description": "",
+            "capabilities": [
+                { "capability_id": "admin", "name": "Admin", "actions": [] }
+            ],
+            "checkpoints": [
+                {
+                    "checkpoint_id": "admin-gate",
+                    "name": "Admin Gate",
+                    "trigger": { "type": "capability_access", "capability_ids": ["admin"] },
+                    "mode": "blocking",
+                    "questions": [
+                        {
+                            "question_id": "reason",
+                            "question": "Why do you need admin access?",
+                            "validation": { "pattern": "^[A-Za-z ]+$", "min_length": 10 }
+                        }
+                    ],
+                    "guidance": { "content": "Be careful", "expires_after": "admin-gate" },
+                    "unlock_capabilities": ["admin"],
+                    "priority": 900
+                },
+                {
+                    "checkpoint_id": "secrets",
+                    "name": "Secrets Mentioned",
+                    "trigger": {
+                        "type": "keyword",
+                        "patterns": ["pass(word)?", "api[_-]?key"],
+                        "match_mode": "regex"
+                    }
+                }
+            ]
+        });
+
+        let manifest: AtlasManifest = serde_json::from_value(json).unwrap();
+        assert!(manifest.validate().is_ok());
+
+        let serialized = serde_json::to_string(&manifest).unwrap();
+        let parsed: AtlasManifest = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(parsed.checkpoints.len(), 2);
+        let gate = parsed.get_checkpoint("admin-gate").unwrap();
+        assert!(gate.requires_response());
+        assert_eq!(gate.priority, 900);
+        assert_eq!(gate.unlock_capabilities, vec!["admin".to_string()]);
+        assert_eq!(
+            gate.guidance.as_ref().and_then(|g| g.expires_after.as_deref()),
+            Some("admin-gate")
+        );
+        assert_eq!(parsed.get_capability_checkpoints("admin").len(), 1);
+        assert!(matches!(
+            parsed.get_checkpoint("secrets").unwrap().trigger,
+            CheckpointTrigger::Keyword { match_mode: MatchMode::Regex, .. }
+        ));
+
+        // Round-tripping again must be stable
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), serialized);
+    }
+
+    #[test]
+    fn test_checkpoint_trigger_validation_errors() {
+        use crate::carp::{AnswerValidation, CheckpointQuestion, StewardCheckpointDef};
+
+        let manifest = AtlasManifest::builder(
+            "com.test.invalid".to_string(),
+            "Invalid Triggers".to_string(),
+        )
+        .add_checkpoint(
+            StewardCheckpointDef::new(
+                "bad-regex",
+                "Bad Regex",
+                CheckpointTrigger::Keyword {
+                    patterns: vec!["[unclosed".to_string()],
+                    case_sensitive: false,
+                    match_mode: MatchMode::Regex,
+                },
+            )
+            .with_question(CheckpointQuestion::text("q1", "First"))
+            .with_question(
+                CheckpointQuestion::text("q1", "Duplicate").with_validation(AnswerValidation {
+                    pattern: Some("(".to_string()),
+                    min_length: None,
+                    max_length: None,
+                    must_contain: vec![],
+                    must_not_contain: vec![],
+                    custom_validator: None,
+                }),
+            ),
+        )
+        .add_checkpoint(StewardCheckpointDef::new(
+            "bad-capability",
+            "Bad Capability",
+            CheckpointTrigger::CapabilityAccess {
+                capability_ids: vec!["missing".to_string()],
+            },
+        ))
+        .add_checkpoint(StewardCheckpointDef::new(
+            "bad-regex",
+            "Duplicate Id",
+            CheckpointTrigger::SessionStart,
+        ))
+        .build();
+
+        let errors = manifest.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("Duplicate checkpoint_id: bad-regex")));
+        assert!(errors.iter().any(|e| e.contains("invalid keyword regex")));
+        assert!(errors.iter().any(|e| e.contains("duplicate question_id: q1")));
+        assert!(errors.iter().any(|e| e.contains("invalid answer pattern")));
+        assert!(errors.iter().any(|e| e.contains("triggered by unknown capability: missing")));
+    }
+
+    #[test]
+    fn test_keyword_trigger_plain_patterns_not_compiled() {
+        use crate::carp::StewardCheckpointDef;
+
+        // Non-regex keyword patterns are plain substrings and need not compile
+        let manifest = AtlasManifest::builder(
+            "com.test.keywords".to_string(),
+            "Keywords".to_string(),
+        )
+        .add_checkpoint(StewardCheckpointDef::new(
+            "brackets",
+            "Brackets",
+            CheckpointTrigger::Keyword {
+                patterns: vec!["[draft".to_string()],
+                case_sensitive: false,
+                match_mode: MatchMode::Any,
+            },
+        ))
+        .build();
+
+        assert!(manifest.validate().is_ok());
+    }
+
     #[test]
     fn test_checkpoint_validation_errors() {
         use crate::carp::{StewardCheckpointDef, CheckpointTrigger};
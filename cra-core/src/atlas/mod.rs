@@ -4,7 +4,7 @@
 //! agent behavior in a domain:
 //!
 //! - Context documents (knowledge, policies, procedures)
-//! - Policy definitions (deny, allow, rate limit, approval)
+//! - Policy definitions (deny, allow, rate limit, approval, content, external)
 //! - Action definitions (tools available to agents)
 //! - Capability groupings
 //! - Platform-specific adapters
@@ -13,13 +13,18 @@ mod manifest;
 mod loader;
 mod validator;
 mod steward;
+mod testing;
+pub mod namespace;
+pub mod content;
 
 pub use manifest::{
-    AtlasManifest, AtlasAction, AtlasPolicy, AtlasCapability, AtlasContextPack,
-    AtlasContextBlock, PolicyType, RiskTier, InjectMode, AtlasSources,
+    AtlasManifest, AtlasManifestBuilder, AtlasAction, AtlasPolicy, AtlasCapability, AtlasContextPack,
+    AtlasContextBlock, AtlasNamespace, PolicyType, RiskTier, InjectMode, AtlasSources,
 };
+pub use content::{ContentAction, ContentDetector, ContentPolicy, ContentStage};
 pub use loader::AtlasLoader;
 pub use validator::AtlasValidator;
+pub use testing::{AtlasTest, AtlasTestReport, AtlasTestResult, TestExpectation, TestFailure};
 pub use steward::{
     StewardConfig, AccessConfig, AccessType, RateLimitConfig,
     DeliveryConfig, DeliveryMode, DeliveryEndpoints, FallbackConfig, CachingConfig,
@@ -54,9 +59,11 @@ mod tests {
             context_packs: vec![],
             context_blocks: vec![],
             policies: vec![],
+            namespaces: vec![],
             actions: vec![],
             dependencies: None,
             sources: None,
+            tests: vec![],
         };
 
         let json = serde_json::to_string(&manifest).unwrap();
@@ -0,0 +1,98 @@
+//! Hierarchical action namespaces
+//!
+//! Action IDs are dot-separated paths: `ticket.comment.create` lives in the
+//! `ticket` and `ticket.comment` namespaces. Policy and capability patterns
+//! match segment by segment:
+//!
+//! | Pattern       | Matches                                        |
+//! |---------------|------------------------------------------------|
+//! | `ticket.get`  | exactly `ticket.get`                           |
+//! | `ticket.*`    | one level under `ticket` (`ticket.get`)        |
+//! | `ticket.**`   | anything under `ticket` (`ticket.comment.add`) |
+//! | `*.delete`    | `delete` one level down (`user.delete`)        |
+//! | `**.delete`   | `delete` at any depth (`ticket.comment.delete`) |
+//! | `*`, `**`     | every action                                   |
+//!
+//! `*` stands for exactly one segment and `**` for one or more.
+
+/// Whether a policy or capability pattern matches an action ID
+pub fn pattern_matches(pattern: &str, action_id: &str) -> bool {
+    if pattern == action_id || pattern == "*" || pattern == "**" {
+        return true;
+    }
+    if !pattern.contains('*') {
+        return false;
+    }
+
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let segments: Vec<&str> = action_id.split('.').collect();
+    segments_match(&pattern, &segments)
+}
+
+fn segments_match(pattern: &[&str], segments: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => segments.is_empty(),
+        Some((&"**", rest)) => (1..=segments.len()).any(|n| segments_match(rest, &segments[n..])),
+        Some((&head, rest)) => match segments.split_first() {
+            Some((segment, remaining)) => (head == "*" || head == *segment) && segments_match(rest, remaining),
+            None => false,
+        },
+    }
+}
+
+/// Whether an action ID lies anywhere under a namespace
+///
+/// `ticket` contains `ticket.get` and `ticket.comment.add`, but not
+/// `ticket` itself or `tickets.get`. The empty namespace contains everything.
+pub fn in_namespace(namespace: &str, action_id: &str) -> bool {
+    let namespace = namespace.trim_end_matches('.');
+    namespace.is_empty()
+        || action_id
+            .strip_prefix(namespace)
+            .is_some_and(|rest| rest.starts_with('.') && rest.len() > 1)
+}
+
+/// Namespace an action ID belongs to directly (`ticket.comment` for `ticket.comment.add`)
+pub fn parent_namespace(action_id: &str) -> Option<&str> {
+    action_id.rsplit_once('.').map(|(parent, _)| parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_and_deep_wildcards() {
+        assert!(pattern_matches("ticket.*", "ticket.get"));
+        assert!(!pattern_matches("ticket.*", "ticket.comment.add"));
+        assert!(!pattern_matches("ticket.*", "ticket"));
+        assert!(pattern_matches("ticket.**", "ticket.get"));
+        assert!(pattern_matches("ticket.**", "ticket.comment.add"));
+        assert!(!pattern_matches("ticket.**", "ticket"));
+        assert!(!pattern_matches("ticket.**", "tickets.get"));
+
+        assert!(pattern_matches("*.delete", "user.delete"));
+        assert!(!pattern_matches("*.delete", "ticket.comment.delete"));
+        assert!(pattern_matches("**.delete", "ticket.comment.delete"));
+        assert!(pattern_matches("ticket.*.add", "ticket.comment.add"));
+        assert!(!pattern_matches("ticket.*.add", "ticket.comment.reply.add"));
+        assert!(pattern_matches("ticket.**.add", "ticket.comment.reply.add"));
+
+        assert!(pattern_matches("*", "ticket.comment.add"));
+        assert!(pattern_matches("**", "ticket"));
+        assert!(!pattern_matches("ticket.get", "ticket.list"));
+    }
+
+    #[test]
+    fn test_namespaces() {
+        assert!(in_namespace("ticket", "ticket.get"));
+        assert!(in_namespace("ticket.", "ticket.comment.add"));
+        assert!(in_namespace("ticket.comment", "ticket.comment.add"));
+        assert!(!in_namespace("ticket", "ticket"));
+        assert!(!in_namespace("ticket", "tickets.get"));
+        assert!(in_namespace("", "anything"));
+
+        assert_eq!(parent_namespace("ticket.comment.add"), Some("ticket.comment"));
+        assert_eq!(parent_namespace("ticket"), None);
+    }
+}
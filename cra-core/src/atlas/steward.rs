@@ -6,6 +6,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::trace::TraceKey;
+
 /// Complete steward configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StewardConfig {
@@ -40,6 +42,10 @@ pub struct StewardConfig {
     /// Branding configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branding: Option<BrandingConfig>,
+
+    /// Public keys of runtimes trusted to sign this atlas's TRACE events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace_keys: Vec<TraceKey>,
 }
 
 impl StewardConfig {
@@ -80,6 +86,12 @@ impl StewardConfig {
         self.notifications = Some(notifications);
         self
     }
+
+    /// Trust a runtime key for TRACE signatures
+    pub fn with_trace_key(mut self, key: TraceKey) -> Self {
+        self.trace_keys.push(key);
+        self
+    }
 }
 
 /// Access control configuration
@@ -0,0 +1,416 @@
+//! Declarative atlas tests
+//!
+//! Stewards ship scenarios alongside an atlas, either in the manifest's
+//! `tests` section or in a `tests.json` array next to `atlas.json` in a
+//! bundle directory. Each names a goal (and optionally an action to
+//! execute) and what the governance outcome must be:
+//!
+//! ```json
+//! {
+//!   "name": "deletes are refused",
+//!   "goal": "clean up old files",
+//!   "action": "file.delete",
+//!   "expect": { "decision": "deny", "denied_policy": "no-delete" }
+//! }
+//! ```
+//!
+//! [`AtlasValidator::run_tests`](super::AtlasValidator::run_tests) runs
+//! each scenario against a fresh resolver holding only that atlas, and
+//! reports every mismatch as an expected/actual pair.
+//!
+//! Without an `action` the outcome is the resolution's: `decision` is the
+//! resolution decision and `denied_policy` must be one of the policies that
+//! denied an action. With one, the action is executed and `decision` is
+//! `allow`, `deny`, `requires_approval`, `rate_limited` or `error`.
+//! List expectations are subsets: every listed ID must be present.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::manifest::AtlasManifest;
+use crate::carp::{CARPRequest, Resolver};
+use crate::error::{CRAError, Result};
+
+/// A scenario shipped with an atlas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AtlasTest {
+    /// Name shown in reports
+    pub name: String,
+
+    /// Goal the session is created with and resolved for
+    pub goal: String,
+
+    /// Agent making the request
+    #[serde(default = "default_agent_id")]
+    pub agent_id: String,
+
+    /// Action to execute after resolving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+
+    /// Parameters for the action
+    #[serde(default = "default_params")]
+    pub params: Value,
+
+    /// The required outcome
+    pub expect: TestExpectation,
+}
+
+fn default_agent_id() -> String {
+    "test-agent".to_string()
+}
+
+fn default_params() -> Value {
+    Value::Object(Default::default())
+}
+
+impl AtlasTest {
+    /// Create a test resolving `goal`
+    pub fn new(name: impl Into<String>, goal: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            goal: goal.into(),
+            agent_id: default_agent_id(),
+            action: None,
+            params: default_params(),
+            expect: TestExpectation::default(),
+        }
+    }
+
+    /// Execute `action` with `params` after resolving
+    pub fn with_action(mut self, action: impl Into<String>, params: Value) -> Self {
+        self.action = Some(action.into());
+        self.params = params;
+        self
+    }
+
+    /// Set the required outcome
+    pub fn expect(mut self, expect: TestExpectation) -> Self {
+        self.expect = expect;
+        self
+    }
+}
+
+/// What an [`AtlasTest`] requires; unset fields aren't checked
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestExpectation {
+    /// Decision, e.g. `allow`, `deny` or `requires_approval`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+
+    /// Policy that must have denied the action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denied_policy: Option<String>,
+
+    /// Actions the resolution must allow
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_actions: Vec<String>,
+
+    /// Actions the resolution must deny
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_actions: Vec<String>,
+
+    /// Constraint IDs the resolution must carry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<String>,
+}
+
+impl TestExpectation {
+    /// Expect a decision
+    pub fn decision(mut self, decision: impl Into<String>) -> Self {
+        self.decision = Some(decision.into());
+        self
+    }
+
+    /// Expect a denial by `policy_id`
+    pub fn denied_by(mut self, policy_id: impl Into<String>) -> Self {
+        self.denied_policy = Some(policy_id.into());
+        self
+    }
+
+    /// Expect these actions to be allowed
+    pub fn allowed_actions(mut self, actions: Vec<String>) -> Self {
+        self.allowed_actions = actions;
+        self
+    }
+
+    /// Expect these actions to be denied
+    pub fn denied_actions(mut self, actions: Vec<String>) -> Self {
+        self.denied_actions = actions;
+        self
+    }
+
+    /// Expect these constraints
+    pub fn constraints(mut self, constraint_ids: Vec<String>) -> Self {
+        self.constraints = constraint_ids;
+        self
+    }
+}
+
+/// One mismatch between expected and actual outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    /// Expectation field that didn't hold
+    pub field: String,
+    /// What the test required
+    pub expected: String,
+    /// What the resolver did
+    pub actual: String,
+}
+
+impl fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {}:", self.field)?;
+        writeln!(f, "    - expected: {}", self.expected)?;
+        writeln!(f, "    + actual:   {}", self.actual)
+    }
+}
+
+/// Outcome of one test
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasTestResult {
+    /// Test name
+    pub name: String,
+    /// Mismatches; empty when the test passed
+    pub failures: Vec<TestFailure>,
+}
+
+impl AtlasTestResult {
+    /// Whether every expectation held
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for AtlasTestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed() {
+            return writeln!(f, "ok   {}", self.name);
+        }
+        writeln!(f, "FAIL {}", self.name)?;
+        self.failures.iter().try_for_each(|failure| write!(f, "{}", failure))
+    }
+}
+
+/// Outcome of an atlas's tests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasTestReport {
+    /// Atlas under test
+    pub atlas_id: String,
+    /// One result per test, in declaration order
+    pub results: Vec<AtlasTestResult>,
+}
+
+impl AtlasTestReport {
+    /// Number of passing tests
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+
+    /// Number of failing tests
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// Whether every test passed
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// Get a summary string
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} tests, {} passed, {} failed",
+            self.atlas_id,
+            self.results.len(),
+            self.passed(),
+            self.failed()
+        )
+    }
+}
+
+impl fmt::Display for AtlasTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.results.iter().try_for_each(|result| write!(f, "{}", result))?;
+        writeln!(f)?;
+        writeln!(f, "{}", self.summary())
+    }
+}
+
+/// What the resolver did for a test
+struct Outcome {
+    decision: String,
+    denied_policies: Vec<String>,
+    allowed_actions: Vec<String>,
+    denied_actions: Vec<String>,
+    constraints: Vec<String>,
+}
+
+/// Run one test against a fresh resolver holding only `manifest`
+pub(crate) fn run_test(manifest: &AtlasManifest, test: &AtlasTest) -> AtlasTestResult {
+    let failures = match observe(manifest, test) {
+        Ok(outcome) => compare(&test.expect, &outcome),
+        Err(e) => vec![TestFailure {
+            field: "run".to_string(),
+            expected: "test runs to completion".to_string(),
+            actual: e.to_string(),
+        }],
+    };
+    AtlasTestResult {
+        name: test.name.clone(),
+        failures,
+    }
+}
+
+fn observe(manifest: &AtlasManifest, test: &AtlasTest) -> Result<Outcome> {
+    let mut resolver = Resolver::new();
+    resolver.load_atlas(manifest.clone())?;
+    let session_id = resolver.create_session(&test.agent_id, &test.goal)?;
+    let request = CARPRequest::new(session_id.clone(), test.agent_id.clone(), test.goal.clone());
+    let resolution = resolver.resolve(&request)?;
+
+    let mut outcome = Outcome {
+        decision: resolution.decision.to_string(),
+        denied_policies: resolution.denied_actions.iter().map(|d| d.policy_id.clone()).collect(),
+        allowed_actions: resolution.allowed_actions.iter().map(|a| a.action_id.clone()).collect(),
+        denied_actions: resolution.denied_actions.iter().map(|d| d.action_id.clone()).collect(),
+        constraints: resolution.constraints.iter().map(|c| c.constraint_id.clone()).collect(),
+    };
+
+    if let Some(action) = &test.action {
+        let result = resolver.execute(&session_id, &resolution.trace_id, action, test.params.clone());
+        outcome.denied_policies.clear();
+        outcome.decision = match result {
+            Ok(_) => "allow".to_string(),
+            Err(CRAError::ActionDenied { policy_id, .. }) => {
+                outcome.denied_policies.push(policy_id);
+                "deny".to_string()
+            }
+            Err(CRAError::ActionRequiresApproval { .. }) => "requires_approval".to_string(),
+            Err(CRAError::RateLimitExceeded { .. }) => "rate_limited".to_string(),
+            Err(e) => format!("error ({})", e),
+        };
+    }
+
+    Ok(outcome)
+}
+
+fn compare(expect: &TestExpectation, outcome: &Outcome) -> Vec<TestFailure> {
+    let mut failures = Vec::new();
+
+    if let Some(decision) = &expect.decision {
+        if *decision != outcome.decision {
+            failures.push(TestFailure {
+                field: "decision".to_string(),
+                expected: decision.clone(),
+                actual: outcome.decision.clone(),
+            });
+        }
+    }
+
+    if let Some(policy) = &expect.denied_policy {
+        if !outcome.denied_policies.contains(policy) {
+            failures.push(TestFailure {
+                field: "denied_policy".to_string(),
+                expected: policy.clone(),
+                actual: list_or_none(&outcome.denied_policies),
+            });
+        }
+    }
+
+    let lists = [
+        ("allowed_actions", &expect.allowed_actions, &outcome.allowed_actions),
+        ("denied_actions", &expect.denied_actions, &outcome.denied_actions),
+        ("constraints", &expect.constraints, &outcome.constraints),
+    ];
+    for (field, expected, actual) in lists {
+        if expected.iter().any(|id| !actual.contains(id)) {
+            failures.push(TestFailure {
+                field: field.to_string(),
+                expected: format!("includes {}", expected.join(", ")),
+                actual: list_or_none(actual),
+            });
+        }
+    }
+
+    failures
+}
+
+fn list_or_none(ids: &[String]) -> String {
+    if ids.is_empty() {
+        "(none)".to_string()
+    } else {
+        ids.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atlas::AtlasValidator;
+    use serde_json::json;
+
+    fn atlas() -> AtlasManifest {
+        serde_json::from_value(json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.testing",
+            "version": "1.0.0",
+            "name": "Tested Atlas",
+            "description": "Atlas with shipped tests",
+            "domains": ["test"],
+            "capabilities": [],
+            "policies": [
+                {"policy_id": "no-delete", "type": "deny", "actions": ["*.delete"], "reason": "No deletes"}
+            ],
+            "actions": [
+                {"action_id": "file.read", "name": "Read", "description": "Read a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "low"},
+                {"action_id": "file.delete", "name": "Delete", "description": "Delete a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "high"}
+            ],
+            "tests": [
+                {"name": "reads are allowed", "goal": "read files", "action": "file.read",
+                 "expect": {"decision": "allow"}},
+                {"name": "deletes are refused", "goal": "delete files",
+                 "expect": {"denied_policy": "no-delete", "allowed_actions": ["file.read"],
+                            "denied_actions": ["file.delete"]}}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_shipped_tests_pass() {
+        let manifest = atlas();
+        let report = AtlasValidator::new().run_tests(&manifest);
+
+        assert_eq!(report.results.len(), 2);
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(report.summary(), "com.test.testing: 2 tests, 2 passed, 0 failed");
+    }
+
+    #[test]
+    fn test_failures_report_expected_and_actual() {
+        let mut manifest = atlas();
+        manifest.tests = vec![AtlasTest::new("deletes are allowed", "delete files")
+            .with_action("file.delete", json!({}))
+            .expect(TestExpectation::default().decision("allow").denied_by("other-policy"))];
+
+        let report = AtlasValidator::new().run_tests(&manifest);
+        assert_eq!(report.failed(), 1);
+
+        let failures = &report.results[0].failures;
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].field, "decision");
+        assert_eq!(failures[0].actual, "deny");
+        assert_eq!(failures[1].expected, "other-policy");
+        assert_eq!(failures[1].actual, "no-delete");
+
+        let rendered = report.to_string();
+        assert!(rendered.contains("FAIL deletes are allowed"));
+        assert!(rendered.contains("- expected: allow"));
+        assert!(rendered.contains("+ actual:   deny"));
+    }
+}
@@ -7,6 +7,10 @@
 //! - Cross-reference checking
 
 use super::manifest::{AtlasManifest, AtlasPolicy, PolicyType};
+use super::content::ContentPolicy;
+use super::namespace;
+use super::testing::{self, AtlasTestReport};
+use crate::carp::{CheckpointTrigger, ExternalPolicy, MatchMode};
 
 /// Validation result with detailed findings
 #[derive(Debug, Clone, Default)]
@@ -154,6 +158,8 @@ impl AtlasValidator {
         self.validate_policies(&manifest, &mut result);
         self.validate_capabilities(&manifest, &mut result);
         self.validate_context_packs(&manifest, &mut result);
+        self.validate_checkpoints(manifest, &mut result);
+        self.validate_tests(manifest, &mut result);
 
         // Recommendations
         if self.check_recommendations {
@@ -163,6 +169,16 @@ impl AtlasValidator {
         result
     }
 
+    /// Run the atlas's shipped tests, each against a fresh resolver
+    ///
+    /// See [`AtlasTest`](super::AtlasTest) for the scenario format.
+    pub fn run_tests(&self, manifest: &AtlasManifest) -> AtlasTestReport {
+        AtlasTestReport {
+            atlas_id: manifest.atlas_id.clone(),
+            results: manifest.tests.iter().map(|test| testing::run_test(manifest, test)).collect(),
+        }
+    }
+
     fn validate_version(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
         if manifest.atlas_version != super::VERSION {
             result.add_error(
@@ -259,8 +275,10 @@ impl AtlasValidator {
                 );
             }
 
-            // Validate risk tier
-            if !["low", "medium", "high", "critical"].contains(&action.risk_tier.as_str()) {
+            // Validate risk tier (empty inherits the namespace default)
+            if !action.risk_tier.is_empty()
+                && !["low", "medium", "high", "critical"].contains(&action.risk_tier.as_str())
+            {
                 result.add_warning(
                     ValidationIssue::new(
                         "W003",
@@ -271,6 +289,18 @@ impl AtlasValidator {
                 );
             }
         }
+
+        for (i, ns) in manifest.namespaces.iter().enumerate() {
+            if let Some(tier) = &ns.risk_tier {
+                if !["low", "medium", "high", "critical"].contains(&tier.as_str()) {
+                    result.add_warning(
+                        ValidationIssue::new("W003", format!("Unknown risk_tier: {}", tier))
+                            .with_path(format!("namespaces[{}].risk_tier", i))
+                            .with_suggestion("Use one of: low, medium, high, critical"),
+                    );
+                }
+            }
+        }
     }
 
     fn validate_policies(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
@@ -290,6 +320,12 @@ impl AtlasValidator {
                 );
             }
 
+            // Content policies govern text, not actions
+            if policy.policy_type == PolicyType::Content {
+                self.validate_content_policy(policy, &path, result);
+                continue;
+            }
+
             // Validate policy has actions
             if policy.actions.is_empty() {
                 result.add_error(
@@ -303,6 +339,16 @@ impl AtlasValidator {
                 self.validate_rate_limit_params(policy, &path, result);
             }
 
+            // Validate external policy parameters
+            if policy.policy_type == PolicyType::External {
+                if let Err(e) = ExternalPolicy::from_policy(policy) {
+                    result.add_error(
+                        ValidationIssue::new("E018", e.to_string())
+                            .with_path(format!("{}.parameters", path)),
+                    );
+                }
+            }
+
             // Validate action patterns
             for (j, pattern) in policy.actions.iter().enumerate() {
                 if !is_valid_action_pattern(pattern) {
@@ -357,19 +403,33 @@ impl AtlasValidator {
         }
     }
 
-    fn validate_capabilities(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
-        let action_ids: std::collections::HashSet<&str> = manifest
-            .actions
-            .iter()
-            .map(|a| a.action_id.as_str())
-            .collect();
+    fn validate_content_policy(
+        &self,
+        policy: &AtlasPolicy,
+        path: &str,
+        result: &mut ValidationResult,
+    ) {
+        if let Err(e) = ContentPolicy::from_policy(policy) {
+            result.add_error(
+                ValidationIssue::new("E016", e.to_string())
+                    .with_path(format!("{}.parameters", path)),
+            );
+        }
+        if !policy.actions.is_empty() {
+            result.add_warning(
+                ValidationIssue::new("W007", "Content policies ignore action patterns")
+                    .with_path(format!("{}.actions", path)),
+            );
+        }
+    }
 
+    fn validate_capabilities(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
         for (i, capability) in manifest.capabilities.iter().enumerate() {
             let path = format!("capabilities[{}]", i);
 
             // Check referenced actions exist
             for (j, action_id) in capability.actions.iter().enumerate() {
-                if !action_ids.contains(action_id.as_str()) {
+                if !manifest.actions.iter().any(|a| namespace::pattern_matches(action_id, &a.action_id)) {
                     result.add_error(
                         ValidationIssue::new(
                             "E012",
@@ -403,6 +463,108 @@ impl AtlasValidator {
         }
     }
 
+    fn validate_checkpoints(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
+        let mut seen_ids = std::collections::HashSet::new();
+
+        for (i, checkpoint) in manifest.checkpoints.iter().enumerate() {
+            let path = format!("checkpoints[{}]", i);
+
+            // Check for duplicates
+            if !seen_ids.insert(&checkpoint.checkpoint_id) {
+                result.add_error(
+                    ValidationIssue::new(
+                        "E013",
+                        format!("Duplicate checkpoint_id: {}", checkpoint.checkpoint_id),
+                    )
+                    .with_path(format!("{}.checkpoint_id", path)),
+                );
+            }
+
+            // Check referenced capabilities exist
+            let trigger_caps = match &checkpoint.trigger {
+                CheckpointTrigger::CapabilityAccess { capability_ids } => capability_ids.as_slice(),
+                _ => &[],
+            };
+            let referenced = [
+                ("unlock_capabilities", checkpoint.unlock_capabilities.as_slice()),
+                ("lock_capabilities", checkpoint.lock_capabilities.as_slice()),
+                ("trigger.capability_ids", trigger_caps),
+            ];
+            for (field, cap_ids) in referenced {
+                for (j, cap_id) in cap_ids.iter().enumerate() {
+                    if manifest.get_capability(cap_id).is_none() {
+                        result.add_error(
+                            ValidationIssue::new(
+                                "E014",
+                                format!("Checkpoint references unknown capability: {}", cap_id),
+                            )
+                            .with_path(format!("{}.{}[{}]", path, field, j)),
+                        );
+                    }
+                }
+            }
+
+            // Check keyword regexes compile
+            if let CheckpointTrigger::Keyword { patterns, match_mode: MatchMode::Regex, .. } =
+                &checkpoint.trigger
+            {
+                for (j, pattern) in patterns.iter().enumerate() {
+                    if let Err(e) = regex::Regex::new(pattern) {
+                        result.add_error(
+                            ValidationIssue::new(
+                                "E015",
+                                format!("Invalid keyword regex '{}': {}", pattern, e),
+                            )
+                            .with_path(format!("{}.trigger.patterns[{}]", path, j)),
+                        );
+                    }
+                }
+            }
+
+            // Check session variable names set by answers
+            for (j, question) in checkpoint.questions.iter().enumerate() {
+                if let Some(name) = &question.sets_variable {
+                    if !crate::carp::template::is_valid_name(name) {
+                        result.add_error(
+                            ValidationIssue::new(
+                                "E017",
+                                format!("Invalid session variable name: '{}'", name),
+                            )
+                            .with_path(format!("{}.questions[{}].sets_variable", path, j)),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_tests(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
+        let mut seen_names = std::collections::HashSet::new();
+
+        for (i, test) in manifest.tests.iter().enumerate() {
+            let path = format!("tests[{}]", i);
+
+            if !seen_names.insert(&test.name) {
+                result.add_error(
+                    ValidationIssue::new("E019", format!("Duplicate test name: {}", test.name))
+                        .with_path(format!("{}.name", path)),
+                );
+            }
+
+            if let Some(action) = &test.action {
+                if manifest.get_action(action).is_none() {
+                    result.add_warning(
+                        ValidationIssue::new(
+                            "W008",
+                            format!("Test executes unknown action: {}", action),
+                        )
+                        .with_path(format!("{}.action", path)),
+                    );
+                }
+            }
+        }
+    }
+
     fn check_recommendations(&self, manifest: &AtlasManifest, result: &mut ValidationResult) {
         // License
         if manifest.license.is_none() {
@@ -548,9 +710,11 @@ mod tests {
             context_packs: vec![],
             context_blocks: vec![],
             policies: vec![],
+            namespaces: vec![],
             actions: vec![],
             dependencies: None,
             sources: None,
+            tests: vec![],
         };
 
         let validator = AtlasValidator::new();
@@ -561,6 +725,20 @@ mod tests {
         assert!(result.errors.iter().any(|e| e.code == "E004"));
     }
 
+    #[test]
+    fn test_validate_tests() {
+        let mut manifest = create_valid_manifest();
+        let test = crate::atlas::AtlasTest::new("reads", "read")
+            .with_action("file.read", serde_json::json!({}));
+        manifest.tests = vec![test.clone(), test];
+
+        let result = AtlasValidator::new().validate(&manifest);
+
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.code == "E019"));
+        assert!(result.warnings.iter().any(|w| w.code == "W008"));
+    }
+
     #[test]
     fn test_validate_duplicate_action_ids() {
         let mut manifest = create_valid_manifest();
@@ -611,6 +789,112 @@ mod tests {
         assert!(result.errors.iter().any(|e| e.code == "E011"));
     }
 
+    #[test]
+    fn test_validate_external_policy_params() {
+        let mut manifest = create_valid_manifest();
+        manifest.policies.push(AtlasPolicy {
+            policy_id: "opa".to_string(),
+            policy_type: PolicyType::External,
+            actions: vec!["api.*".to_string()],
+            reason: None,
+            parameters: Some(serde_json::json!({ "url": "https://opa.example.com/v1/data/cra/allow", "timeout_ms": 0 })),
+        });
+
+        let validator = AtlasValidator::new();
+        let result = validator.validate(&manifest);
+
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.code == "E018"));
+    }
+
+    #[test]
+    fn test_validate_content_policies() {
+        let mut manifest = create_valid_manifest();
+        manifest.policies.push(AtlasPolicy {
+            policy_id: "no-pii".to_string(),
+            policy_type: PolicyType::Content,
+            actions: vec![],
+            reason: None,
+            parameters: Some(serde_json::json!({
+                "action": "redact",
+                "detectors": [{ "type": "regex", "name": "ssn", "patterns": ["\\d{3}-\\d{2}-\\d{4}"] }]
+            })),
+        });
+
+        let validator = AtlasValidator::new();
+        let result = validator.validate(&manifest);
+        assert!(result.is_valid, "{:?}", result.errors);
+
+        manifest.policies.push(AtlasPolicy {
+            policy_id: "broken".to_string(),
+            policy_type: PolicyType::Content,
+            actions: vec!["test.*".to_string()],
+            reason: None,
+            parameters: Some(serde_json::json!({
+                "action": "block",
+                "detectors": [{ "type": "regex", "name": "bad", "patterns": ["(unclosed"] }]
+            })),
+        });
+
+        let result = validator.validate(&manifest);
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.code == "E016"
+            && e.path.as_deref() == Some("policies[2].parameters")));
+        assert!(result.warnings.iter().any(|w| w.code == "W007"));
+    }
+
+    #[test]
+    fn test_validate_checkpoints() {
+        use crate::carp::StewardCheckpointDef;
+
+        let mut manifest = create_valid_manifest();
+        manifest.checkpoints.push(
+            StewardCheckpointDef::new(
+                "gate",
+                "Gate",
+                CheckpointTrigger::CapabilityAccess {
+                    capability_ids: vec!["missing.cap".to_string()],
+                },
+            ),
+        );
+        manifest.checkpoints.push(StewardCheckpointDef::new(
+            "gate",
+            "Gate Again",
+            CheckpointTrigger::Keyword {
+                patterns: vec!["(unclosed".to_string()],
+                case_sensitive: false,
+                match_mode: MatchMode::Regex,
+            },
+        ));
+
+        let validator = AtlasValidator::new();
+        let result = validator.validate(&manifest);
+
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.code == "E013"));
+        assert!(result.errors.iter().any(|e| e.code == "E014"
+            && e.path.as_deref() == Some("checkpoints[0].trigger.capability_ids[0]")));
+        assert!(result.errors.iter().any(|e| e.code == "E015"));
+    }
+
+    #[test]
+    fn test_validate_checkpoint_variables() {
+        use crate::carp::{CheckpointQuestion, StewardCheckpointDef};
+
+        let mut manifest = create_valid_manifest();
+        let mut checkpoint = StewardCheckpointDef::new("tenant", "Tenant", CheckpointTrigger::SessionStart);
+        checkpoint.questions = vec![
+            CheckpointQuestion::text("tenant", "Which tenant?").sets_variable("tenant_id"),
+            CheckpointQuestion::text("env", "Which environment?").sets_variable("${env}"),
+        ];
+        manifest.checkpoints.push(checkpoint);
+
+        let result = AtlasValidator::new().validate(&manifest);
+        let errors: Vec<_> = result.errors.iter().filter(|e| e.code == "E017").collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path.as_deref(), Some("checkpoints[0].questions[1].sets_variable"));
+    }
+
     #[test]
     fn test_helper_functions() {
         assert!(is_valid_semver("1.0.0"));
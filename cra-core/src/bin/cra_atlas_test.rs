@@ -0,0 +1,78 @@
+//! CRA Atlas Test - Run the scenarios shipped with an atlas
+//!
+//! Loads an atlas (a manifest file, or a bundle directory whose
+//! `tests.json` is merged into the manifest's `tests` section), runs each
+//! scenario against an ephemeral resolver and prints the failures as
+//! expected/actual diffs. Exits with status 2 when any test fails so
+//! stewards can gate publishing on it.
+//!
+//! Usage:
+//!     cra-atlas-test atlases/cra-development.json
+//!     cra-atlas-test path/to/atlas-bundle/
+//!     cra-atlas-test --json path/to/atlas-bundle/
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use cra_core::atlas::{AtlasLoader, AtlasValidator};
+
+#[derive(Parser, Debug)]
+#[command(name = "cra-atlas-test")]
+#[command(about = "Run the tests shipped with a CRA atlas")]
+#[command(version)]
+struct Args {
+    /// Atlas manifest file or bundle directory
+    atlas: PathBuf,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Only print failing tests
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let mut loader = AtlasLoader::new();
+    let loaded = if args.atlas.is_dir() {
+        loader.load_from_directory(&args.atlas)
+    } else {
+        loader.load_from_file(&args.atlas)
+    };
+    let manifest = match loaded {
+        Ok(atlas_id) => loader.get_manifest(&atlas_id).cloned().expect("atlas was just loaded"),
+        Err(e) => {
+            eprintln!("Error loading atlas: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if manifest.tests.is_empty() {
+        eprintln!("{} declares no tests", manifest.atlas_id);
+        return ExitCode::SUCCESS;
+    }
+
+    let report = AtlasValidator::new().run_tests(&manifest);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else if args.quiet {
+        for result in report.results.iter().filter(|r| !r.passed()) {
+            print!("{}", result);
+        }
+        println!("{}", report.summary());
+    } else {
+        print!("{}", report);
+    }
+
+    if report.is_success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    }
+}
@@ -0,0 +1,347 @@
+//! CRA Bench - Load and performance harness for the resolver
+//!
+//! Drives a configurable workload against an embedded resolver: N sessions,
+//! each making M resolutions (optionally throttled to a target rate) and
+//! executing allowed actions, against a generated atlas of a given size.
+//! Reports latency percentiles and throughput, and exits with status 2 when
+//! a regression threshold is exceeded so release pipelines can gate on it.
+//!
+//! Usage:
+//!     cra-bench --sessions 20 --resolutions 200 --profile medium
+//!     cra-bench --rate 500 --max-resolve-p99-us 2000 --min-throughput 1000
+//!     cra-bench --atlas atlases/cra-development.json --deferred --json
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{Parser, ValueEnum};
+use serde_json::json;
+
+use cra_core::metrics::Histogram;
+use cra_core::{atlas::AtlasManifest, CARPRequest, DeferredConfig, Resolver};
+
+#[derive(Parser, Debug)]
+#[command(name = "cra-bench")]
+#[command(about = "Load and performance harness for the CRA resolver")]
+#[command(version)]
+struct Args {
+    /// Number of sessions
+    #[arg(long, default_value_t = 10)]
+    sessions: usize,
+
+    /// Resolutions per session
+    #[arg(long, default_value_t = 100)]
+    resolutions: usize,
+
+    /// Actions executed after each resolution
+    #[arg(long, default_value_t = 1)]
+    executions: usize,
+
+    /// Target resolutions per second across all sessions (0 = unthrottled)
+    #[arg(long, default_value_t = 0.0)]
+    rate: f64,
+
+    /// Size of the generated atlas
+    #[arg(long, value_enum, default_value_t = Profile::Small)]
+    profile: Profile,
+
+    /// Use an atlas from a file instead of generating one
+    #[arg(long)]
+    atlas: Option<PathBuf>,
+
+    /// Use deferred tracing
+    #[arg(long)]
+    deferred: bool,
+
+    /// Fail if resolve p99 latency exceeds this many microseconds
+    #[arg(long)]
+    max_resolve_p99_us: Option<u64>,
+
+    /// Fail if execute p99 latency exceeds this many microseconds
+    #[arg(long)]
+    max_execute_p99_us: Option<u64>,
+
+    /// Fail if throughput falls below this many operations per second
+    #[arg(long)]
+    min_throughput: Option<f64>,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+/// Generated atlas sizes
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Profile {
+    /// 10 actions in 2 namespaces
+    Small,
+    /// 100 actions in 10 namespaces
+    Medium,
+    /// 1000 actions in 50 namespaces
+    Large,
+}
+
+impl Profile {
+    /// (namespaces, actions per namespace)
+    fn shape(self) -> (usize, usize) {
+        match self {
+            Profile::Small => (2, 5),
+            Profile::Medium => (10, 10),
+            Profile::Large => (50, 20),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let atlas = match &args.atlas {
+        Some(path) => match load_atlas(path) {
+            Ok(atlas) => atlas,
+            Err(e) => {
+                eprintln!("Error loading atlas: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => generate_atlas(args.profile),
+    };
+
+    let report = match run(&args, atlas) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let violations = check_thresholds(&args, &report);
+    if args.json {
+        let mut value = report.to_json();
+        value["violations"] = json!(violations);
+        println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+    } else {
+        report.print();
+        for violation in &violations {
+            eprintln!("REGRESSION: {}", violation);
+        }
+    }
+
+    if violations.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    }
+}
+
+/// Measurements from one run
+struct Report {
+    sessions: usize,
+    actions: usize,
+    elapsed: Duration,
+    resolve: Histogram,
+    execute: Histogram,
+    errors: u64,
+}
+
+impl Report {
+    fn operations(&self) -> u64 {
+        self.resolve.count() + self.execute.count()
+    }
+
+    fn throughput(&self) -> f64 {
+        self.operations() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    fn print(&self) {
+        println!("Sessions:    {}", self.sessions);
+        println!("Atlas:       {} actions", self.actions);
+        println!("Operations:  {} ({} errors)", self.operations(), self.errors);
+        println!("Elapsed:     {:.3}s", self.elapsed.as_secs_f64());
+        println!("Throughput:  {:.0} ops/s", self.throughput());
+        println!();
+        println!("{:<10} {:>8} {:>10} {:>10} {:>10} {:>10}", "latency", "count", "p50 µs", "p90 µs", "p99 µs", "max µs");
+        for (name, histogram) in [("resolve", &self.resolve), ("execute", &self.execute)] {
+            println!(
+                "{:<10} {:>8} {:>10} {:>10} {:>10} {:>10}",
+                name,
+                histogram.count(),
+                histogram.value_at_quantile(0.5),
+                histogram.value_at_quantile(0.9),
+                histogram.value_at_quantile(0.99),
+                histogram.max()
+            );
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let latency = |histogram: &Histogram| {
+            json!({
+                "count": histogram.count(),
+                "p50_us": histogram.value_at_quantile(0.5),
+                "p90_us": histogram.value_at_quantile(0.9),
+                "p99_us": histogram.value_at_quantile(0.99),
+                "max_us": histogram.max(),
+            })
+        };
+        json!({
+            "sessions": self.sessions,
+            "actions": self.actions,
+            "operations": self.operations(),
+            "errors": self.errors,
+            "elapsed_ms": self.elapsed.as_millis() as u64,
+            "throughput_ops_per_sec": self.throughput(),
+            "resolve": latency(&self.resolve),
+            "execute": latency(&self.execute),
+        })
+    }
+}
+
+fn run(args: &Args, atlas: AtlasManifest) -> Result<Report, String> {
+    let actions = atlas.actions.len();
+    let mut resolver = if args.deferred {
+        Resolver::new().with_deferred_tracing(DeferredConfig::default())
+    } else {
+        Resolver::new()
+    };
+    resolver.load_atlas(atlas).map_err(|e| e.to_string())?;
+
+    let session_ids = (0..args.sessions)
+        .map(|i| resolver.create_session(&format!("bench-agent-{}", i), "Benchmark workload"))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let interval = (args.rate > 0.0).then(|| Duration::from_secs_f64(1.0 / args.rate));
+    let mut resolve = Histogram::new();
+    let mut execute = Histogram::new();
+    let mut errors = 0;
+
+    let start = Instant::now();
+    for round in 0..args.resolutions {
+        for (index, session_id) in session_ids.iter().enumerate() {
+            if let Some(interval) = interval {
+                let due = interval.mul_f64((round * session_ids.len() + index) as f64);
+                if let Some(wait) = due.checked_sub(start.elapsed()) {
+                    thread::sleep(wait);
+                }
+            }
+
+            let request = CARPRequest::new(
+                session_id.clone(),
+                format!("bench-agent-{}", index),
+                format!("Benchmark goal {}", round),
+            );
+            let began = Instant::now();
+            let resolution = resolver.resolve(&request);
+            resolve.record(began.elapsed().as_micros() as u64);
+            let resolution = match resolution {
+                Ok(resolution) => resolution,
+                Err(_) => {
+                    errors += 1;
+                    continue;
+                }
+            };
+
+            let allowed = &resolution.allowed_actions;
+            for n in 0..args.executions.min(allowed.len()) {
+                let action = &allowed[(round + n) % allowed.len()];
+                let began = Instant::now();
+                let result = resolver.execute(session_id, &resolution.trace_id, &action.action_id, json!({}));
+                execute.record(began.elapsed().as_micros() as u64);
+                if result.is_err() {
+                    errors += 1;
+                }
+            }
+        }
+    }
+    resolver.flush_traces().map_err(|e| e.to_string())?;
+    let elapsed = start.elapsed();
+
+    Ok(Report {
+        sessions: session_ids.len(),
+        actions,
+        elapsed,
+        resolve,
+        execute,
+        errors,
+    })
+}
+
+fn check_thresholds(args: &Args, report: &Report) -> Vec<String> {
+    let mut violations = Vec::new();
+    if let Some(max) = args.max_resolve_p99_us {
+        let p99 = report.resolve.value_at_quantile(0.99);
+        if p99 > max {
+            violations.push(format!("resolve p99 {}µs exceeds {}µs", p99, max));
+        }
+    }
+    if let Some(max) = args.max_execute_p99_us {
+        let p99 = report.execute.value_at_quantile(0.99);
+        if p99 > max {
+            violations.push(format!("execute p99 {}µs exceeds {}µs", p99, max));
+        }
+    }
+    if let Some(min) = args.min_throughput {
+        let throughput = report.throughput();
+        if throughput < min {
+            violations.push(format!("throughput {:.0} ops/s is below {:.0} ops/s", throughput, min));
+        }
+    }
+    violations
+}
+
+fn load_atlas(path: &PathBuf) -> Result<AtlasManifest, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read atlas file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse atlas JSON: {}", e))
+}
+
+/// Atlas with a deny policy for every fifth namespace and an allow policy for the rest
+fn generate_atlas(profile: Profile) -> AtlasManifest {
+    let (namespaces, per_namespace) = profile.shape();
+    let risk_tiers = ["low", "medium", "high"];
+
+    let actions: Vec<_> = (0..namespaces)
+        .flat_map(|ns| {
+            (0..per_namespace).map(move |i| {
+                json!({
+                    "action_id": format!("bench.ns{}.action{}", ns, i),
+                    "name": format!("Action {}.{}", ns, i),
+                    "description": "Generated benchmark action",
+                    "parameters_schema": { "type": "object" },
+                    "risk_tier": risk_tiers[i % risk_tiers.len()],
+                })
+            })
+        })
+        .collect();
+
+    let mut policies: Vec<_> = (0..namespaces)
+        .step_by(5)
+        .map(|ns| {
+            json!({
+                "policy_id": format!("deny-ns{}", ns),
+                "type": "deny",
+                "actions": [format!("bench.ns{}.*", ns)],
+                "reason": "Generated deny policy",
+            })
+        })
+        .collect();
+    policies.push(json!({
+        "policy_id": "allow-bench",
+        "type": "allow",
+        "actions": ["bench.**"],
+    }));
+
+    serde_json::from_value(json!({
+        "atlas_version": "1.0",
+        "atlas_id": "com.cra.bench",
+        "version": "1.0.0",
+        "name": "Benchmark Atlas",
+        "description": "Generated atlas for cra-bench",
+        "domains": ["bench"],
+        "capabilities": [],
+        "policies": policies,
+        "actions": actions,
+    }))
+    .expect("generated atlas is valid")
+}
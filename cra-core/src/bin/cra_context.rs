@@ -7,9 +7,11 @@
 //!     cra-context "I need to modify the hash computation"
 //!     cra-context --atlas path/to/atlas.json "Add a new event type"
 //!     cra-context --json "Working on trace module"
+//!     cra-context --report "Working on trace module"
+//!     cra-context --graph mermaid "Working on trace module"
 
 use clap::Parser;
-use cra_core::{Resolver, CARPRequest, atlas::AtlasManifest};
+use cra_core::{Resolver, CARPRequest, GraphFormat, atlas::AtlasManifest};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -32,6 +34,16 @@ struct Args {
     #[arg(long)]
     list_only: bool,
 
+    /// Print the session's audit report instead of the context
+    /// (Markdown, or JSON with --json)
+    #[arg(long)]
+    report: bool,
+
+    /// Print a graph of the session's activity instead of the context
+    /// (dot or mermaid)
+    #[arg(long, value_name = "FORMAT")]
+    graph: Option<GraphFormat>,
+
     /// Agent ID for session tracking
     #[arg(long, default_value = "cli-agent")]
     agent_id: String,
@@ -78,7 +90,7 @@ fn main() {
 
     // Create request and resolve
     let request = CARPRequest::new(
-        session_id,
+        session_id.clone(),
         args.agent_id,
         args.goal,
     );
@@ -92,7 +104,11 @@ fn main() {
     };
 
     // Output based on format
-    if args.json {
+    if args.report {
+        output_report(&mut resolver, &session_id, args.json);
+    } else if let Some(format) = args.graph {
+        output_graph(&mut resolver, &session_id, format);
+    } else if args.json {
         output_json(&resolution);
     } else if args.list_only {
         output_list(&resolution);
@@ -101,6 +117,33 @@ fn main() {
     }
 }
 
+fn output_report(resolver: &mut Resolver, session_id: &str, json: bool) {
+    let report = resolver
+        .end_session(session_id)
+        .and_then(|_| resolver.session_report(session_id));
+    match report {
+        Ok(report) if json => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+        Ok(report) => print!("{}", report.to_markdown()),
+        Err(e) => {
+            eprintln!("Error building session report: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn output_graph(resolver: &mut Resolver, session_id: &str, format: GraphFormat) {
+    let graph = resolver
+        .end_session(session_id)
+        .and_then(|_| resolver.visualize_trace(session_id, format));
+    match graph {
+        Ok(graph) => print!("{}", graph),
+        Err(e) => {
+            eprintln!("Error building session graph: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn load_atlas(path: &Option<PathBuf>, verbose: bool) -> Result<AtlasManifest, String> {
     // If path provided, use it
     if let Some(p) = path {
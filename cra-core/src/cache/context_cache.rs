@@ -5,10 +5,11 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use sha2::{Digest, Sha256};
 
+use crate::clock::Instant;
 use super::DEFAULT_CONTEXT_TTL;
 
 /// Configuration for context cache
@@ -299,6 +300,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "minimal"))] // needs a moving clock
     fn test_ttl_expiration() {
         let config = ContextCacheConfig::default()
             .with_ttl(Duration::from_millis(50));
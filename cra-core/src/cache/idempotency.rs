@@ -0,0 +1,239 @@
+//! Idempotency key cache
+//!
+//! Stores the first successful response to a resolve or execute call per
+//! (session, idempotency key), so a retried call returns that response
+//! instead of running again. Failed calls aren't stored and may be retried
+//! with the same key.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::{CRAError, Result};
+use crate::trace::canonical_json;
+
+/// Default time a stored response is replayed for (24 hours)
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Configuration for the idempotency cache
+#[derive(Debug, Clone)]
+pub struct IdempotencyConfig {
+    /// How long a stored response is replayed for
+    pub ttl: Duration,
+    /// Maximum number of stored responses; the oldest are dropped first
+    pub max_entries: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl: DEFAULT_IDEMPOTENCY_TTL,
+            max_entries: 10_000,
+        }
+    }
+}
+
+impl IdempotencyConfig {
+    /// Set the TTL
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set max entries
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+}
+
+/// A stored response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Session the call was made in
+    pub session_id: String,
+    /// Client-supplied idempotency key
+    pub key: String,
+    /// Operation the key was first used for (`resolve` or `execute`)
+    pub operation: String,
+    /// Hash of the canonical request, to detect a key reused for another request
+    pub request_hash: String,
+    /// The response returned by the first call
+    pub response: Value,
+    /// When the response was stored
+    pub created_at: DateTime<Utc>,
+    /// When the response stops being replayed
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Responses stored by (session, idempotency key)
+#[derive(Debug, Default)]
+pub struct IdempotencyCache {
+    entries: HashMap<(String, String), IdempotencyRecord>,
+    config: IdempotencyConfig,
+}
+
+impl IdempotencyCache {
+    /// Create a cache with default config
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create with custom config
+    pub fn with_config(config: IdempotencyConfig) -> Self {
+        Self {
+            entries: HashMap::new(),
+            config,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &IdempotencyConfig {
+        &self.config
+    }
+
+    /// Find the stored response for a key, if it hasn't expired
+    ///
+    /// Fails with `IdempotencyKeyConflict` if the key was first used for a
+    /// different operation or request.
+    pub fn lookup(
+        &self,
+        session_id: &str,
+        key: &str,
+        operation: &str,
+        request_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<&IdempotencyRecord>> {
+        let Some(record) = self
+            .entries
+            .get(&(session_id.to_string(), key.to_string()))
+            .filter(|r| r.expires_at > now)
+        else {
+            return Ok(None);
+        };
+
+        if record.operation != operation || record.request_hash != request_hash {
+            return Err(CRAError::IdempotencyKeyConflict {
+                key: key.to_string(),
+                reason: format!("key was first used for a different {} request", record.operation),
+            });
+        }
+        Ok(Some(record))
+    }
+
+    /// Store the first response for a key
+    pub fn store(
+        &mut self,
+        session_id: &str,
+        key: &str,
+        operation: &str,
+        request_hash: &str,
+        response: Value,
+        now: DateTime<Utc>,
+    ) {
+        self.purge_expired(now);
+        if self.entries.len() >= self.config.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, r)| r.created_at)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let ttl = chrono::Duration::from_std(self.config.ttl).unwrap_or(chrono::Duration::MAX);
+        self.entries.insert(
+            (session_id.to_string(), key.to_string()),
+            IdempotencyRecord {
+                session_id: session_id.to_string(),
+                key: key.to_string(),
+                operation: operation.to_string(),
+                request_hash: request_hash.to_string(),
+                response,
+                created_at: now,
+                expires_at: now.checked_add_signed(ttl).unwrap_or(DateTime::<Utc>::MAX_UTC),
+            },
+        );
+    }
+
+    /// Responses that have expired but not been dropped yet
+    pub fn expired(&self, now: DateTime<Utc>) -> Vec<&IdempotencyRecord> {
+        self.entries.values().filter(|r| r.expires_at <= now).collect()
+    }
+
+    /// Drop expired responses
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, r| r.expires_at > now);
+        before - self.entries.len()
+    }
+
+    /// Drop a session's stored responses
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.entries.retain(|(session, _), _| session != session_id);
+    }
+
+    /// Number of stored responses
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Hash a request for conflict detection
+///
+/// Uses canonical JSON, so field order doesn't matter.
+pub fn hash_request(request: &Value) -> String {
+    hex::encode(Sha256::digest(canonical_json(request).as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_replay_until_expired() {
+        let mut cache = IdempotencyCache::with_config(IdempotencyConfig::default().with_ttl(Duration::from_secs(60)));
+        let now = Utc::now();
+        let hash = hash_request(&json!({"action_id": "ticket.create", "parameters": {"a": 1, "b": 2}}));
+        assert_eq!(hash, hash_request(&json!({"parameters": {"b": 2, "a": 1}, "action_id": "ticket.create"})));
+
+        assert!(cache.lookup("s1", "key-1", "execute", &hash, now).unwrap().is_none());
+        cache.store("s1", "key-1", "execute", &hash, json!({"ok": true}), now);
+
+        let record = cache.lookup("s1", "key-1", "execute", &hash, now).unwrap().unwrap();
+        assert_eq!(record.response, json!({"ok": true}));
+        // Keys are scoped to a session
+        assert!(cache.lookup("s2", "key-1", "execute", &hash, now).unwrap().is_none());
+
+        let later = now + chrono::Duration::seconds(61);
+        assert!(cache.lookup("s1", "key-1", "execute", &hash, later).unwrap().is_none());
+        assert_eq!(cache.purge_expired(later), 1);
+    }
+
+    #[test]
+    fn test_key_reused_for_other_request() {
+        let mut cache = IdempotencyCache::with_config(IdempotencyConfig::default().with_max_entries(1));
+        let now = Utc::now();
+        cache.store("s1", "key-1", "execute", "hash-a", json!({}), now);
+
+        let err = cache.lookup("s1", "key-1", "execute", "hash-b", now).unwrap_err();
+        assert_eq!(err.error_code(), "IDEMPOTENCY_KEY_CONFLICT");
+        assert!(cache.lookup("s1", "key-1", "resolve", "hash-a", now).is_err());
+
+        cache.store("s1", "key-2", "execute", "hash-b", json!({}), now + chrono::Duration::seconds(1));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.lookup("s1", "key-1", "execute", "hash-a", now).unwrap().is_none());
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides caching for:
 //! - Context blocks (avoid re-fetching)
 //! - Policy decisions (avoid re-evaluating)
+//! - Responses to idempotent resolve/execute calls (avoid running twice)
 //!
 //! Features:
 //! - TTL (time-to-live) support
@@ -10,10 +11,14 @@
 //! - Statistics tracking
 
 mod context_cache;
+mod idempotency;
 mod policy_cache;
 
 pub use context_cache::{ContextCache, CachedContext, ContextCacheConfig};
-pub use policy_cache::{PolicyCache, CachedPolicy, PolicyCacheConfig};
+pub use idempotency::{
+    hash_request, IdempotencyCache, IdempotencyConfig, IdempotencyRecord, DEFAULT_IDEMPOTENCY_TTL,
+};
+pub use policy_cache::{hash_params, PolicyCache, CachedPolicy, PolicyCacheConfig, PolicyDecision};
 
 use std::time::Duration;
 
@@ -0,0 +1,619 @@
+//! Human-in-the-loop approvals
+//!
+//! Executing an action matched by a `requires_approval` policy opens an
+//! [`ApprovalRequest`] instead of running the action. The [`ApprovalManager`]
+//! escalates it through every registered [`EscalationChannel`] and records a
+//! [`DeliveryStatus`] per channel. Once a human decides, the decision (and
+//! who made it) is recorded; an approved request lets exactly one execution
+//! of the same action with the same parameters through.
+//!
+//! Two channels are built in. Both only build payloads and hand them to an
+//! embedder-provided [`HttpPostFn`], so the core stays free of an HTTP
+//! client:
+//!
+//! - [`WebhookChannel`] posts a plain JSON approval request
+//! - [`SlackChannel`] posts a Slack incoming-webhook message with
+//!   approve/reject buttons, and parses the resulting interaction payload
+//!
+//! Decisions arrive either pushed by the embedder (`Resolver::decide_approval`)
+//! or pulled from channels that implement [`EscalationChannel::receive`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{CRAError, Result};
+
+/// Slack `action_id` of the approve button
+pub const SLACK_APPROVE_ACTION: &str = "cra_approve";
+
+/// Slack `action_id` of the reject button
+pub const SLACK_REJECT_ACTION: &str = "cra_reject";
+
+/// Callback that POSTs a JSON body to a URL
+///
+/// Returns `Err(message)` when delivery fails.
+pub type HttpPostFn = Arc<dyn Fn(&str, &Value) -> std::result::Result<(), String> + Send + Sync>;
+
+/// A request for a human to approve an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Unique approval ID
+    pub approval_id: String,
+    /// Session the action was requested in
+    pub session_id: String,
+    /// Action awaiting approval
+    pub action_id: String,
+    /// Policy that requires the approval
+    pub policy_id: String,
+    /// Policy reason, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Hash of the action parameters the approval covers
+    pub parameters_hash: String,
+    /// When the approval was requested
+    pub requested_at: DateTime<Utc>,
+}
+
+/// A human's decision on an approval request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    /// Whether the action was approved
+    pub approved: bool,
+    /// Identity of the human who decided
+    pub decided_by: String,
+    /// Channel the decision arrived through, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Reason given with the decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the decision was made
+    pub decided_at: DateTime<Utc>,
+}
+
+impl ApprovalDecision {
+    /// Approve, as `decided_by`
+    pub fn approve(decided_by: impl Into<String>) -> Self {
+        Self::new(true, decided_by)
+    }
+
+    /// Reject, as `decided_by`
+    pub fn reject(decided_by: impl Into<String>) -> Self {
+        Self::new(false, decided_by)
+    }
+
+    fn new(approved: bool, decided_by: impl Into<String>) -> Self {
+        Self {
+            approved,
+            decided_by: decided_by.into(),
+            channel: None,
+            reason: None,
+            decided_at: crate::clock::now(),
+        }
+    }
+
+    /// Set the channel the decision arrived through
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
+
+    /// Set the reason given with the decision
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// Where an approval request stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    /// Waiting for a human
+    Pending,
+    /// Approved and not yet used
+    Approved,
+    /// Rejected
+    Rejected,
+    /// Approved and used by an execution
+    Executed,
+}
+
+impl std::fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalStatus::Pending => write!(f, "pending"),
+            ApprovalStatus::Approved => write!(f, "approved"),
+            ApprovalStatus::Rejected => write!(f, "rejected"),
+            ApprovalStatus::Executed => write!(f, "executed"),
+        }
+    }
+}
+
+/// Outcome of sending an approval request through one channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryStatus {
+    /// Channel name
+    pub channel: String,
+    /// Whether the channel accepted the request
+    pub delivered: bool,
+    /// Delivery error, if it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A way of reaching a human with approval requests
+pub trait EscalationChannel: Send + Sync {
+    /// Channel name, recorded with deliveries and decisions
+    fn name(&self) -> &str;
+
+    /// Send an approval request
+    ///
+    /// Returns `Err(message)` when delivery fails.
+    fn send(&self, request: &ApprovalRequest) -> std::result::Result<(), String>;
+
+    /// Check for a decision on an approval request
+    ///
+    /// Channels that receive decisions out of band (e.g. through an HTTP
+    /// callback handled by the embedder) keep the default, which never has one.
+    fn receive(&self, _approval_id: &str) -> Option<ApprovalDecision> {
+        None
+    }
+}
+
+/// Posts approval requests as JSON to a webhook
+pub struct WebhookChannel {
+    name: String,
+    url: String,
+    post: HttpPostFn,
+}
+
+impl WebhookChannel {
+    /// Create a channel posting to `url` through `post`
+    pub fn new(url: impl Into<String>, post: HttpPostFn) -> Self {
+        Self {
+            name: "webhook".to_string(),
+            url: url.into(),
+            post,
+        }
+    }
+
+    /// Set the channel name (default: "webhook")
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Body posted for an approval request
+    pub fn payload(request: &ApprovalRequest) -> Value {
+        json!({
+            "type": "approval.requested",
+            "approval_id": request.approval_id,
+            "session_id": request.session_id,
+            "action_id": request.action_id,
+            "policy_id": request.policy_id,
+            "reason": request.reason,
+            "parameters_hash": request.parameters_hash,
+            "requested_at": request.requested_at,
+        })
+    }
+
+    /// Parse a decision posted back to the embedder
+    ///
+    /// Expects `{"approval_id", "approved", "decided_by", "reason"?}` and
+    /// returns the approval ID with the decision.
+    pub fn parse_decision(&self, body: &Value) -> Option<(String, ApprovalDecision)> {
+        let approval_id = body.get("approval_id")?.as_str()?;
+        let approved = body.get("approved")?.as_bool()?;
+        let decided_by = body.get("decided_by")?.as_str().filter(|s| !s.is_empty())?;
+
+        let mut decision = ApprovalDecision::new(approved, decided_by).with_channel(self.name.clone());
+        if let Some(reason) = body.get("reason").and_then(Value::as_str) {
+            decision = decision.with_reason(reason);
+        }
+        Some((approval_id.to_string(), decision))
+    }
+}
+
+impl EscalationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&self, request: &ApprovalRequest) -> std::result::Result<(), String> {
+        (self.post)(&self.url, &Self::payload(request))
+    }
+}
+
+/// Posts approval requests to a Slack incoming webhook
+///
+/// Messages carry approve and reject buttons whose value is the approval ID.
+/// Point the Slack app's interactivity URL at the embedder and pass the
+/// decoded `payload` to [`SlackChannel::parse_interaction`].
+pub struct SlackChannel {
+    name: String,
+    webhook_url: String,
+    post: HttpPostFn,
+}
+
+impl SlackChannel {
+    /// Create a channel posting to a Slack incoming webhook through `post`
+    pub fn new(webhook_url: impl Into<String>, post: HttpPostFn) -> Self {
+        Self {
+            name: "slack".to_string(),
+            webhook_url: webhook_url.into(),
+            post,
+        }
+    }
+
+    /// Set the channel name (default: "slack")
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Slack message posted for an approval request
+    pub fn payload(request: &ApprovalRequest) -> Value {
+        let text = format!(
+            "Approval needed: `{}` in session `{}` (policy `{}`)",
+            request.action_id, request.session_id, request.policy_id
+        );
+        let mut blocks = vec![json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text },
+        })];
+        if let Some(reason) = &request.reason {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": reason }],
+            }));
+        }
+        blocks.push(json!({
+            "type": "actions",
+            "block_id": request.approval_id,
+            "elements": [
+                {
+                    "type": "button",
+                    "action_id": SLACK_APPROVE_ACTION,
+                    "style": "primary",
+                    "text": { "type": "plain_text", "text": "Approve" },
+                    "value": request.approval_id,
+                },
+                {
+                    "type": "button",
+                    "action_id": SLACK_REJECT_ACTION,
+                    "style": "danger",
+                    "text": { "type": "plain_text", "text": "Reject" },
+                    "value": request.approval_id,
+                },
+            ],
+        }));
+
+        json!({ "text": text, "blocks": blocks })
+    }
+
+    /// Parse a Slack `block_actions` interaction into a decision
+    ///
+    /// The decider is recorded as `slack:<username>` (or the user ID when
+    /// there is no username).
+    pub fn parse_interaction(&self, payload: &Value) -> Option<(String, ApprovalDecision)> {
+        if payload.get("type")?.as_str()? != "block_actions" {
+            return None;
+        }
+        let action = payload.get("actions")?.as_array()?.iter().find(|a| {
+            matches!(
+                a.get("action_id").and_then(Value::as_str),
+                Some(SLACK_APPROVE_ACTION | SLACK_REJECT_ACTION)
+            )
+        })?;
+        let approved = action.get("action_id")?.as_str()? == SLACK_APPROVE_ACTION;
+        let approval_id = action.get("value")?.as_str()?;
+
+        let user = payload.get("user")?;
+        let identity = user
+            .get("username")
+            .and_then(Value::as_str)
+            .or_else(|| user.get("id").and_then(Value::as_str))?;
+
+        let decision = ApprovalDecision::new(approved, format!("slack:{}", identity)).with_channel(self.name.clone());
+        Some((approval_id.to_string(), decision))
+    }
+}
+
+impl EscalationChannel for SlackChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&self, request: &ApprovalRequest) -> std::result::Result<(), String> {
+        (self.post)(&self.webhook_url, &Self::payload(request))
+    }
+}
+
+/// An approval request with its deliveries and outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    /// The request
+    pub request: ApprovalRequest,
+    /// Current status
+    pub status: ApprovalStatus,
+    /// Delivery outcome per channel
+    pub deliveries: Vec<DeliveryStatus>,
+    /// The human decision, once made
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ApprovalDecision>,
+}
+
+/// Tracks approval requests and escalates them to humans
+#[derive(Default)]
+pub struct ApprovalManager {
+    channels: Vec<Arc<dyn EscalationChannel>>,
+    records: HashMap<String, ApprovalRecord>,
+}
+
+impl std::fmt::Debug for ApprovalManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApprovalManager")
+            .field("channels", &self.channel_names())
+            .field("records", &self.records.len())
+            .finish()
+    }
+}
+
+impl ApprovalManager {
+    /// Create a manager with no channels
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an escalation channel
+    pub fn add_channel(&mut self, channel: Arc<dyn EscalationChannel>) {
+        self.channels.push(channel);
+    }
+
+    /// Names of the registered channels
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.channels.iter().map(|c| c.name()).collect()
+    }
+
+    /// Record a new request and send it through every channel
+    pub fn open(&mut self, request: ApprovalRequest) -> &ApprovalRecord {
+        let deliveries = self
+            .channels
+            .iter()
+            .map(|channel| {
+                let result = channel.send(&request);
+                DeliveryStatus {
+                    channel: channel.name().to_string(),
+                    delivered: result.is_ok(),
+                    error: result.err(),
+                }
+            })
+            .collect();
+
+        let approval_id = request.approval_id.clone();
+        self.records.insert(
+            approval_id.clone(),
+            ApprovalRecord {
+                request,
+                status: ApprovalStatus::Pending,
+                deliveries,
+                decision: None,
+            },
+        );
+        &self.records[&approval_id]
+    }
+
+    /// Record a human decision on a pending request
+    pub fn decide(&mut self, approval_id: &str, decision: ApprovalDecision) -> Result<&ApprovalRecord> {
+        let record = self.records.get_mut(approval_id).ok_or_else(|| CRAError::ApprovalNotFound {
+            approval_id: approval_id.to_string(),
+        })?;
+        if record.status != ApprovalStatus::Pending {
+            return Err(CRAError::ApprovalAlreadyDecided {
+                approval_id: approval_id.to_string(),
+                status: record.status.to_string(),
+            });
+        }
+
+        record.status = if decision.approved {
+            ApprovalStatus::Approved
+        } else {
+            ApprovalStatus::Rejected
+        };
+        record.decision = Some(decision);
+        Ok(record)
+    }
+
+    /// Ask the channels for a decision on a pending request
+    pub fn poll(&self, approval_id: &str) -> Option<ApprovalDecision> {
+        if self.records.get(approval_id)?.status != ApprovalStatus::Pending {
+            return None;
+        }
+        self.channels.iter().find_map(|channel| {
+            channel.receive(approval_id).map(|mut decision| {
+                decision.channel.get_or_insert_with(|| channel.name().to_string());
+                decision
+            })
+        })
+    }
+
+    /// Get a request by ID
+    pub fn get(&self, approval_id: &str) -> Option<&ApprovalRecord> {
+        self.records.get(approval_id)
+    }
+
+    /// All requests, oldest first
+    pub fn records(&self) -> Vec<&ApprovalRecord> {
+        let mut records: Vec<&ApprovalRecord> = self.records.values().collect();
+        records.sort_by(|a, b| {
+            (a.request.requested_at, &a.request.approval_id).cmp(&(b.request.requested_at, &b.request.approval_id))
+        });
+        records
+    }
+
+    /// Pending requests in a session, oldest first
+    pub fn pending(&self, session_id: &str) -> Vec<&ApprovalRecord> {
+        let mut pending: Vec<&ApprovalRecord> = self
+            .records
+            .values()
+            .filter(|r| r.request.session_id == session_id && r.status == ApprovalStatus::Pending)
+            .collect();
+        pending.sort_by(|a, b| {
+            (a.request.requested_at, &a.request.approval_id).cmp(&(b.request.requested_at, &b.request.approval_id))
+        });
+        pending
+    }
+
+    /// Find an open (pending or approved) request covering an execution
+    pub fn find_open(&self, session_id: &str, action_id: &str, parameters_hash: &str) -> Option<&ApprovalRecord> {
+        self.records.values().find(|r| {
+            r.request.session_id == session_id
+                && r.request.action_id == action_id
+                && r.request.parameters_hash == parameters_hash
+                && matches!(r.status, ApprovalStatus::Pending | ApprovalStatus::Approved)
+        })
+    }
+
+    /// Mark an approved request as used by an execution
+    pub fn mark_executed(&mut self, approval_id: &str) {
+        if let Some(record) = self.records.get_mut(approval_id) {
+            if record.status == ApprovalStatus::Approved {
+                record.status = ApprovalStatus::Executed;
+            }
+        }
+    }
+
+    /// Drop one request
+    pub fn remove(&mut self, approval_id: &str) -> Option<ApprovalRecord> {
+        self.records.remove(approval_id)
+    }
+
+    /// Drop all requests in a session
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.records.retain(|_, r| r.request.session_id != session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Every (url, body) a recorder was asked to POST
+    type SentLog = Arc<Mutex<Vec<(String, Value)>>>;
+
+    fn request(approval_id: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            approval_id: approval_id.to_string(),
+            session_id: "s1".to_string(),
+            action_id: "git.commit".to_string(),
+            policy_id: "require-review".to_string(),
+            reason: Some("Commits need a reviewer".to_string()),
+            parameters_hash: "abc".to_string(),
+            requested_at: crate::clock::now(),
+        }
+    }
+
+    fn recorder() -> (HttpPostFn, SentLog) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let post: HttpPostFn = Arc::new(move |url: &str, body: &Value| {
+            log.lock().unwrap().push((url.to_string(), body.clone()));
+            Ok(())
+        });
+        (post, sent)
+    }
+
+    #[test]
+    fn test_webhook_channel() {
+        let (post, sent) = recorder();
+        let channel = WebhookChannel::new("https://hooks.example.com/approvals", post);
+        channel.send(&request("ap-1")).unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[0].0, "https://hooks.example.com/approvals");
+        assert_eq!(sent[0].1["type"], "approval.requested");
+        assert_eq!(sent[0].1["approval_id"], "ap-1");
+        assert_eq!(sent[0].1["action_id"], "git.commit");
+
+        let (id, decision) = channel
+            .parse_decision(&json!({ "approval_id": "ap-1", "approved": false, "decided_by": "dana", "reason": "Tests are red" }))
+            .unwrap();
+        assert_eq!(id, "ap-1");
+        assert!(!decision.approved);
+        assert_eq!(decision.decided_by, "dana");
+        assert_eq!(decision.channel.as_deref(), Some("webhook"));
+        assert_eq!(decision.reason.as_deref(), Some("Tests are red"));
+
+        assert!(channel.parse_decision(&json!({ "approval_id": "ap-1", "approved": true })).is_none());
+    }
+
+    #[test]
+    fn test_slack_channel() {
+        let (post, sent) = recorder();
+        let channel = SlackChannel::new("https://hooks.slack.com/services/T/B/X", post);
+        channel.send(&request("ap-1")).unwrap();
+
+        let body = sent.lock().unwrap()[0].1.clone();
+        assert!(body["text"].as_str().unwrap().contains("git.commit"));
+        let actions = body["blocks"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(actions["elements"][0]["action_id"], SLACK_APPROVE_ACTION);
+        assert_eq!(actions["elements"][1]["value"], "ap-1");
+
+        let interaction = json!({
+            "type": "block_actions",
+            "user": { "id": "U123", "username": "dana" },
+            "actions": [{ "action_id": SLACK_APPROVE_ACTION, "value": "ap-1" }]
+        });
+        let (id, decision) = channel.parse_interaction(&interaction).unwrap();
+        assert_eq!(id, "ap-1");
+        assert!(decision.approved);
+        assert_eq!(decision.decided_by, "slack:dana");
+        assert_eq!(decision.channel.as_deref(), Some("slack"));
+
+        assert!(channel.parse_interaction(&json!({ "type": "view_submission" })).is_none());
+    }
+
+    #[test]
+    fn test_manager_lifecycle() {
+        let (post, _) = recorder();
+        let failing: HttpPostFn = Arc::new(|_: &str, _: &Value| Err("connection refused".to_string()));
+
+        let mut manager = ApprovalManager::new();
+        manager.add_channel(Arc::new(WebhookChannel::new("https://ok.example.com", post)));
+        manager.add_channel(Arc::new(SlackChannel::new("https://down.example.com", failing)));
+        assert_eq!(manager.channel_names(), vec!["webhook", "slack"]);
+
+        let record = manager.open(request("ap-1"));
+        assert_eq!(record.status, ApprovalStatus::Pending);
+        assert!(record.deliveries[0].delivered);
+        assert_eq!(record.deliveries[1].error.as_deref(), Some("connection refused"));
+        assert_eq!(manager.pending("s1").len(), 1);
+        assert!(manager.find_open("s1", "git.commit", "abc").is_some());
+        assert!(manager.find_open("s1", "git.commit", "other").is_none());
+
+        manager.decide("ap-1", ApprovalDecision::approve("dana")).unwrap();
+        assert_eq!(manager.get("ap-1").unwrap().status, ApprovalStatus::Approved);
+        assert!(manager.pending("s1").is_empty());
+        assert!(matches!(
+            manager.decide("ap-1", ApprovalDecision::reject("sam")),
+            Err(CRAError::ApprovalAlreadyDecided { .. })
+        ));
+        assert!(matches!(
+            manager.decide("ap-missing", ApprovalDecision::approve("dana")),
+            Err(CRAError::ApprovalNotFound { .. })
+        ));
+
+        manager.mark_executed("ap-1");
+        assert_eq!(manager.get("ap-1").unwrap().status, ApprovalStatus::Executed);
+        assert!(manager.find_open("s1", "git.commit", "abc").is_none());
+
+        manager.remove_session("s1");
+        assert!(manager.get("ap-1").is_none());
+    }
+}
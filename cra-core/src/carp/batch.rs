@@ -0,0 +1,114 @@
+//! Batch resolution
+//!
+//! Swarm controllers resolve many goals at once. A batch carries up to
+//! [`MAX_BATCH_SIZE`] CARP requests and answers with one result per request,
+//! in request order. Requests are resolved independently: one failing
+//! (unknown session, invalid request, ...) doesn't affect the others, and
+//! its error is reported in its slot.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CRAError, ErrorDetail, Result};
+
+use super::request::CARPRequest;
+use super::resolution::CARPResolution;
+
+/// Most requests accepted in one batch
+pub const MAX_BATCH_SIZE: usize = 500;
+
+/// Outcome of one request in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItem {
+    /// The request was resolved
+    Ok {
+        /// Position of the request in the batch
+        index: usize,
+        /// The resolution
+        resolution: CARPResolution,
+    },
+    /// The request failed
+    Error {
+        /// Position of the request in the batch
+        index: usize,
+        /// Session the request was for
+        session_id: String,
+        /// What went wrong
+        error: ErrorDetail,
+        /// HTTP status the request would have failed with on its own
+        http_status: u16,
+    },
+}
+
+impl BatchItem {
+    /// Position of the request in the batch
+    pub fn index(&self) -> usize {
+        match self {
+            BatchItem::Ok { index, .. } | BatchItem::Error { index, .. } => *index,
+        }
+    }
+
+    /// Whether the request was resolved
+    pub fn is_ok(&self) -> bool {
+        matches!(self, BatchItem::Ok { .. })
+    }
+}
+
+/// Results of a batch, in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResolveResponse {
+    /// One result per request
+    pub results: Vec<BatchItem>,
+    /// Requests resolved
+    pub succeeded: usize,
+    /// Requests that failed
+    pub failed: usize,
+}
+
+impl BatchResolveResponse {
+    /// Collect per-request outcomes
+    ///
+    /// `outcomes` pairs each request with its result, in request order.
+    pub fn from_results<'a>(
+        outcomes: impl IntoIterator<Item = (&'a CARPRequest, Result<CARPResolution>)>,
+    ) -> Self {
+        let results: Vec<BatchItem> = outcomes
+            .into_iter()
+            .enumerate()
+            .map(|(index, (request, result))| match result {
+                Ok(resolution) => BatchItem::Ok { index, resolution },
+                Err(e) => BatchItem::Error {
+                    index,
+                    session_id: request.session_id.clone(),
+                    error: e.to_error_response().error,
+                    http_status: e.http_status_code(),
+                },
+            })
+            .collect();
+        let succeeded = results.iter().filter(|r| r.is_ok()).count();
+        Self {
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+        }
+    }
+}
+
+/// Reject empty and oversized batches
+pub(crate) fn validate_batch(requests: &[CARPRequest]) -> Result<()> {
+    if requests.is_empty() {
+        return Err(CRAError::InvalidCARPRequest {
+            reason: "batch contains no requests".to_string(),
+        });
+    }
+    if requests.len() > MAX_BATCH_SIZE {
+        return Err(CRAError::InvalidCARPRequest {
+            reason: format!(
+                "batch of {} requests exceeds the limit of {}",
+                requests.len(),
+                MAX_BATCH_SIZE
+            ),
+        });
+    }
+    Ok(())
+}
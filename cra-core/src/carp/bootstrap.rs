@@ -0,0 +1,450 @@
+//! Bootstrap protocol
+//!
+//! Bootstrap is how an agent joins CRA governance in one round trip: it
+//! sends a [`BootstrapRequest`] with its identity and intent, and the
+//! resolver ([`Resolver::bootstrap`]) answers with a [`BootstrapResponse`]
+//! carrying
+//!
+//! - the new session and its genesis hash, the start of the session's
+//!   TRACE chain, plus the current chain head
+//! - the governance rules and policies the agent works under
+//! - the context blocks of an initial resolution of its intent, inlined
+//! - the actions and constraints of that resolution, and the content
+//!   policies the agent side must enforce on text
+//!
+//! These are the wire types shared by every CRA front end (the MCP server's
+//! `cra_bootstrap` tool, HTTP transports) and by the wrapper's client, so
+//! what an agent is told at bootstrap is the same whichever way it connects.
+//!
+//! ## Versioning
+//!
+//! Both messages carry `protocol_version` ([`BOOTSTRAP_PROTOCOL_VERSION`]).
+//! Fields may be added within a major version, and readers ignore fields
+//! they don't know; a different major version is rejected with
+//! `InvalidBootstrap`. Messages without a version are read as `1.0`.
+//!
+//! ```rust
+//! use cra_core::carp::BootstrapRequest;
+//! use cra_core::Resolver;
+//!
+//! let mut resolver = Resolver::new();
+//! let response = resolver
+//!     .bootstrap(&BootstrapRequest::new("agent-1", "Tidy the repository"))
+//!     .unwrap();
+//!
+//! assert_eq!(response.genesis_hash, resolver.get_trace(&response.session_id).unwrap()[0].event_hash);
+//! assert!(response.hard_rules().any(|rule| rule.rule_id == "trace.required"));
+//! ```
+//!
+//! [`Resolver::bootstrap`]: super::Resolver::bootstrap
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::resolution::{Constraint, ContextBlock};
+use crate::atlas::{AtlasPolicy, ContentPolicy};
+use crate::context::content_address;
+use crate::error::{CRAError, Result};
+
+/// Version of the bootstrap wire schema
+pub const BOOTSTRAP_PROTOCOL_VERSION: &str = "1.0";
+
+fn default_version() -> String {
+    BOOTSTRAP_PROTOCOL_VERSION.to_string()
+}
+
+/// Check that a peer's bootstrap protocol version can be read
+///
+/// Versions are compatible when their major versions match.
+pub fn check_protocol_version(version: &str) -> Result<()> {
+    let major = |v: &str| v.split('.').next().map(str::to_string);
+    if major(version) == major(BOOTSTRAP_PROTOCOL_VERSION) {
+        Ok(())
+    } else {
+        Err(CRAError::InvalidBootstrap {
+            reason: format!(
+                "protocol version '{}' is not supported (expected {})",
+                version, BOOTSTRAP_PROTOCOL_VERSION
+            ),
+        })
+    }
+}
+
+/// Agent → CRA: join governance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapRequest {
+    /// Wire schema version
+    #[serde(default = "default_version")]
+    pub protocol_version: String,
+
+    /// Agent identifier
+    pub agent_id: String,
+
+    /// What the agent intends to do; becomes the session goal
+    pub intent: String,
+
+    /// Tools and abilities the agent declares
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
+
+    /// Hints for the initial context resolution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_hints: Vec<String>,
+}
+
+impl BootstrapRequest {
+    /// Create a request at the current protocol version
+    pub fn new(agent_id: impl Into<String>, intent: impl Into<String>) -> Self {
+        Self {
+            protocol_version: default_version(),
+            agent_id: agent_id.into(),
+            intent: intent.into(),
+            capabilities: Vec::new(),
+            context_hints: Vec::new(),
+        }
+    }
+
+    /// Declare the agent's capabilities
+    pub fn with_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Add hints for the initial context resolution
+    pub fn with_context_hints(mut self, hints: Vec<String>) -> Self {
+        self.context_hints = hints;
+        self
+    }
+}
+
+/// CRA → Agent: the governance the agent now works under
+///
+/// `P` is the type content policies are read into. It defaults to the
+/// atlas [`ContentPolicy`]; agent-side readers can use their own type for
+/// the same wire shape.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapResponse<P = ContentPolicy> {
+    /// Wire schema version
+    #[serde(default = "default_version")]
+    pub protocol_version: String,
+
+    /// Session created for the agent
+    pub session_id: String,
+
+    /// Hash of the session's first TRACE event
+    pub genesis_hash: String,
+
+    /// Hash of the session's latest TRACE event when the response was built
+    pub current_hash: String,
+
+    /// Sequence number of that event
+    #[serde(default)]
+    pub sequence: u64,
+
+    /// ID of the initial resolution, for executing its actions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution_id: Option<String>,
+
+    /// Governance rules the agent must follow
+    #[serde(default)]
+    pub rules: Vec<GovernanceRule>,
+
+    /// Policies of the loaded atlases
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<PolicySummary>,
+
+    /// Context of the initial resolution, highest priority first
+    #[serde(default)]
+    pub contexts: Vec<BootstrapContext>,
+
+    /// Actions the initial resolution allows
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_actions: Vec<String>,
+
+    /// Session constraints from the initial resolution (e.g. `budget_limit`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<SessionConstraint>,
+
+    /// Server X25519 public key (hex) for sealing TRACE payloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_encryption_key: Option<String>,
+
+    /// Content policies the agent side enforces on input and output text
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub content_policies: Vec<P>,
+}
+
+impl<P> BootstrapResponse<P> {
+    /// Create a response for a session whose chain starts at `genesis_hash`
+    pub fn new(session_id: impl Into<String>, genesis_hash: impl Into<String>) -> Self {
+        let genesis_hash = genesis_hash.into();
+        Self {
+            protocol_version: default_version(),
+            session_id: session_id.into(),
+            current_hash: genesis_hash.clone(),
+            genesis_hash,
+            sequence: 0,
+            resolution_id: None,
+            rules: Vec::new(),
+            policies: Vec::new(),
+            contexts: Vec::new(),
+            allowed_actions: Vec::new(),
+            constraints: Vec::new(),
+            trace_encryption_key: None,
+            content_policies: Vec::new(),
+        }
+    }
+
+    /// Set the governance rules
+    pub fn with_rules(mut self, rules: Vec<GovernanceRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Add a context block
+    pub fn with_context(mut self, context: BootstrapContext) -> Self {
+        self.contexts.push(context);
+        self
+    }
+
+    /// Add a session constraint
+    pub fn with_constraint(mut self, constraint: SessionConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Set the content policies
+    pub fn with_content_policies(mut self, policies: Vec<P>) -> Self {
+        self.content_policies = policies;
+        self
+    }
+
+    /// IDs of the context blocks, in order
+    pub fn context_ids(&self) -> Vec<String> {
+        self.contexts.iter().map(|c| c.context_id.clone()).collect()
+    }
+
+    /// Rules with hard enforcement
+    pub fn hard_rules(&self) -> impl Iterator<Item = &GovernanceRule> {
+        self.rules.iter().filter(|rule| rule.is_hard())
+    }
+
+    /// Check that this response's protocol version can be read
+    pub fn check_version(&self) -> Result<()> {
+        check_protocol_version(&self.protocol_version)
+    }
+}
+
+/// A governance rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GovernanceRule {
+    /// Rule identifier
+    pub rule_id: String,
+
+    /// Human-readable description
+    pub description: String,
+
+    /// Enforcement level: `hard` (enforced) or `soft` (encouraged)
+    #[serde(default = "default_enforcement")]
+    pub enforcement: String,
+}
+
+fn default_enforcement() -> String {
+    "soft".to_string()
+}
+
+impl GovernanceRule {
+    /// A rule CRA enforces
+    pub fn hard(rule_id: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule_id.into(),
+            description: description.into(),
+            enforcement: "hard".to_string(),
+        }
+    }
+
+    /// A rule CRA encourages
+    pub fn soft(rule_id: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule_id.into(),
+            description: description.into(),
+            enforcement: default_enforcement(),
+        }
+    }
+
+    /// Whether the rule is enforced
+    pub fn is_hard(&self) -> bool {
+        self.enforcement == "hard"
+    }
+
+    /// The rules every bootstrapped session works under
+    pub fn standard() -> Vec<Self> {
+        vec![
+            Self::hard("trace.required", "All actions must be reported to CRA before they are taken"),
+            Self::soft("context.must_request", "Request context before making domain-specific decisions"),
+            Self::soft("feedback.expected", "Provide feedback on context usefulness"),
+        ]
+    }
+}
+
+/// Summary of an atlas policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicySummary {
+    /// Policy identifier
+    pub policy_id: String,
+
+    /// Policy type (`deny`, `requires_approval`, ...)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub policy_type: String,
+
+    /// Description of what the policy does
+    pub description: String,
+
+    /// Action patterns the policy applies to
+    #[serde(default)]
+    pub actions_affected: Vec<String>,
+}
+
+impl From<&AtlasPolicy> for PolicySummary {
+    fn from(policy: &AtlasPolicy) -> Self {
+        Self {
+            policy_id: policy.policy_id.clone(),
+            policy_type: policy.policy_type.to_string(),
+            description: policy
+                .reason
+                .clone()
+                .unwrap_or_else(|| format!("{} policy", policy.policy_type)),
+            actions_affected: policy.actions.clone(),
+        }
+    }
+}
+
+/// A context block delivered at bootstrap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapContext {
+    /// Context identifier
+    pub context_id: String,
+
+    /// The content
+    pub content: String,
+
+    /// Priority (higher = more important)
+    pub priority: i32,
+
+    /// Content address of `content` (`sha256:<hex>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    /// Server-assigned cache lifetime (the agent's default if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
+impl BootstrapContext {
+    /// Create a context block, addressing its content
+    pub fn new(context_id: impl Into<String>, content: impl Into<String>, priority: i32) -> Self {
+        let content = content.into();
+        Self {
+            context_id: context_id.into(),
+            digest: Some(content_address(&content)),
+            content,
+            priority,
+            ttl_seconds: None,
+        }
+    }
+
+    /// Set the cache lifetime
+    pub fn with_ttl(mut self, ttl_seconds: u64) -> Self {
+        self.ttl_seconds = Some(ttl_seconds);
+        self
+    }
+
+    /// Whether the content matches its digest (true when there is none)
+    pub fn is_intact(&self) -> bool {
+        self.digest
+            .as_deref()
+            .is_none_or(|digest| digest == content_address(&self.content))
+    }
+}
+
+impl From<&ContextBlock> for BootstrapContext {
+    fn from(block: &ContextBlock) -> Self {
+        Self::new(block.block_id.clone(), block.content.clone(), block.priority)
+    }
+}
+
+/// Constraint applied to the whole session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionConstraint {
+    /// Constraint identifier
+    pub constraint_id: String,
+
+    /// Constraint type (`budget_limit`, `rate_limit`, ...)
+    pub constraint_type: String,
+
+    /// Human-readable description
+    pub description: String,
+
+    /// Constraint parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+impl From<&Constraint> for SessionConstraint {
+    fn from(constraint: &Constraint) -> Self {
+        Self {
+            constraint_id: constraint.constraint_id.clone(),
+            constraint_type: serde_json::to_value(constraint.constraint_type)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            description: constraint.description.clone(),
+            parameters: constraint.parameters.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_protocol_version() {
+        assert!(check_protocol_version("1.0").is_ok());
+        assert!(check_protocol_version("1.3").is_ok());
+        assert!(matches!(
+            check_protocol_version("2.0"),
+            Err(CRAError::InvalidBootstrap { .. })
+        ));
+
+        // Unversioned messages are read as 1.0
+        let request: BootstrapRequest = serde_json::from_value(json!({
+            "agent_id": "agent-1",
+            "intent": "Tidy files"
+        }))
+        .unwrap();
+        assert_eq!(request.protocol_version, BOOTSTRAP_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn test_response_wire_shape() {
+        let response: BootstrapResponse = BootstrapResponse::new("session-1", "genesis")
+            .with_rules(GovernanceRule::standard())
+            .with_context(BootstrapContext::new("ctx-1", "Be careful", 10).with_ttl(600));
+        let wire = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(wire["protocol_version"], "1.0");
+        assert_eq!(wire["current_hash"], "genesis");
+        assert!(wire.get("content_policies").is_none());
+        assert_eq!(wire["contexts"][0]["ttl_seconds"], 600);
+
+        // Readers ignore fields they don't know
+        let mut wire = wire;
+        wire["added_in_1_1"] = json!(true);
+        let parsed: BootstrapResponse<Value> = serde_json::from_value(wire).unwrap();
+        assert_eq!(parsed.context_ids(), vec!["ctx-1"]);
+        assert!(parsed.contexts[0].is_intact());
+        assert_eq!(parsed.hard_rules().count(), 1);
+    }
+}
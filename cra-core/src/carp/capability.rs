@@ -0,0 +1,205 @@
+//! Session Capability State
+//!
+//! Tracks which atlas capabilities a session may use. Capabilities that are
+//! gated by a checkpoint start as `pending_gate`; checkpoint outcomes and
+//! admin calls move them between states:
+//!
+//! ```text
+//! pending_gate ──unlock──▶ granted ◀──grant── locked
+//!       │                     │                  ▲
+//!       └────────lock─────────┴───────lock───────┘
+//! ```
+//!
+//! Capabilities with no tracked state are ungated. Every transition is
+//! emitted to TRACE as `capability.state_changed`, so the state of a session
+//! can be rebuilt from its trace with [`CapabilityState::replay`].
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::trace::{EventType, TRACEEvent};
+
+/// Status of a capability within a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityStatus {
+    /// The session may use the capability
+    Granted,
+    /// The capability has been locked
+    Locked,
+    /// The capability is waiting on a checkpoint gate
+    PendingGate,
+}
+
+impl CapabilityStatus {
+    /// Get the string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CapabilityStatus::Granted => "granted",
+            CapabilityStatus::Locked => "locked",
+            CapabilityStatus::PendingGate => "pending_gate",
+        }
+    }
+
+    /// Check if actions in this capability may be used
+    pub fn is_usable(&self) -> bool {
+        matches!(self, CapabilityStatus::Granted)
+    }
+}
+
+impl std::fmt::Display for CapabilityStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// What caused a capability transition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum CapabilityChangeSource {
+    /// Capability gated at session start
+    SessionStart,
+    /// A checkpoint outcome
+    Checkpoint { checkpoint_id: String },
+    /// An administrative API call
+    Admin { actor: String },
+    /// Delegation from the parent of a child session
+    Delegation { parent_session_id: String },
+}
+
+/// A single capability state transition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityTransition {
+    /// Capability that changed
+    pub capability_id: String,
+    /// Previous status (None if previously untracked)
+    pub from: Option<CapabilityStatus>,
+    /// New status
+    pub to: CapabilityStatus,
+    /// What caused the change
+    #[serde(flatten)]
+    pub source: CapabilityChangeSource,
+}
+
+/// Per-session capability state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityState {
+    statuses: BTreeMap<String, CapabilityStatus>,
+}
+
+impl CapabilityState {
+    /// Create an empty state (all capabilities ungated)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the tracked status of a capability
+    pub fn status(&self, capability_id: &str) -> Option<CapabilityStatus> {
+        self.statuses.get(capability_id).copied()
+    }
+
+    /// Check if a capability may be used (granted or ungated)
+    pub fn is_usable(&self, capability_id: &str) -> bool {
+        self.status(capability_id).is_none_or(|s| s.is_usable())
+    }
+
+    /// Capabilities currently granted
+    pub fn granted(&self) -> Vec<String> {
+        self.with_status(CapabilityStatus::Granted)
+    }
+
+    /// Capabilities with the given status
+    pub fn with_status(&self, status: CapabilityStatus) -> Vec<String> {
+        self.statuses
+            .iter()
+            .filter(|(_, s)| **s == status)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// All tracked capabilities and their status
+    pub fn statuses(&self) -> &BTreeMap<String, CapabilityStatus> {
+        &self.statuses
+    }
+
+    /// Move a capability to a new status
+    ///
+    /// Returns the transition, or `None` if the capability was already in that status.
+    pub fn transition(
+        &mut self,
+        capability_id: &str,
+        to: CapabilityStatus,
+        source: CapabilityChangeSource,
+    ) -> Option<CapabilityTransition> {
+        let from = self.statuses.insert(capability_id.to_string(), to);
+        if from == Some(to) {
+            return None;
+        }
+
+        Some(CapabilityTransition {
+            capability_id: capability_id.to_string(),
+            from,
+            to,
+            source,
+        })
+    }
+
+    /// Rebuild state from a session's `capability.state_changed` events
+    pub fn replay(events: &[TRACEEvent]) -> Self {
+        let mut state = Self::new();
+        for event in events {
+            if event.event_type != EventType::CapabilityStateChanged {
+                continue;
+            }
+            if let Ok(transition) = serde_json::from_value::<CapabilityTransition>(event.payload.clone()) {
+                state.statuses.insert(transition.capability_id, transition.to);
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions() {
+        let mut state = CapabilityState::new();
+        assert!(state.is_usable("admin"));
+
+        let t = state
+            .transition("admin", CapabilityStatus::PendingGate, CapabilityChangeSource::SessionStart)
+            .unwrap();
+        assert_eq!(t.from, None);
+        assert!(!state.is_usable("admin"));
+
+        let source = CapabilityChangeSource::Checkpoint { checkpoint_id: "gate".to_string() };
+        let t = state.transition("admin", CapabilityStatus::Granted, source.clone()).unwrap();
+        assert_eq!(t.from, Some(CapabilityStatus::PendingGate));
+        assert!(state.is_usable("admin"));
+        assert_eq!(state.granted(), vec!["admin".to_string()]);
+
+        // Repeating a transition is a no-op
+        assert!(state.transition("admin", CapabilityStatus::Granted, source).is_none());
+    }
+
+    #[test]
+    fn test_transition_payload_roundtrip() {
+        let transition = CapabilityTransition {
+            capability_id: "admin".to_string(),
+            from: Some(CapabilityStatus::Granted),
+            to: CapabilityStatus::Locked,
+            source: CapabilityChangeSource::Admin { actor: "ops".to_string() },
+        };
+
+        let value = serde_json::to_value(&transition).unwrap();
+        assert_eq!(value["to"], "locked");
+        assert_eq!(value["source"], "admin");
+        assert_eq!(value["actor"], "ops");
+
+        let parsed: CapabilityTransition = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, transition);
+    }
+}
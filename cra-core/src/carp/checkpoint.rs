@@ -43,11 +43,14 @@
 //! - `interactive` - Steward-defined interactive gate
 
 use std::collections::{HashMap, HashSet};
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::clock::Instant;
+use crate::trace::TraceVerbosity;
 use super::RiskTier;
 
 /// Checkpoint types
@@ -144,6 +147,10 @@ pub struct StewardCheckpointDef {
     #[serde(default)]
     pub force_sync_trace: bool,
 
+    /// Trace verbosity the session switches to once this checkpoint completes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_verbosity: Option<TraceVerbosity>,
+
     /// Priority (higher = evaluated first)
     #[serde(default = "default_priority")]
     pub priority: u32,
@@ -239,6 +246,10 @@ pub struct CheckpointQuestion {
     /// What happens if validation fails
     #[serde(default)]
     pub on_invalid: InvalidAnswerAction,
+
+    /// Session variable set to the answer once the checkpoint passes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sets_variable: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -382,6 +393,24 @@ pub enum AnswerValue {
     Acknowledged,
 }
 
+impl AnswerValue {
+    /// The answer as a session variable value
+    ///
+    /// Whole numbers drop their fraction (`3`, not `3.0`) and JSON answers
+    /// are serialized compactly.
+    pub fn to_variable_value(&self) -> String {
+        match self {
+            AnswerValue::Text(s) | AnswerValue::Choice(s) => s.clone(),
+            AnswerValue::Boolean(b) => b.to_string(),
+            AnswerValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            AnswerValue::Number(n) => n.to_string(),
+            AnswerValue::Json(Value::String(s)) => s.clone(),
+            AnswerValue::Json(v) => v.to_string(),
+            AnswerValue::Acknowledged => "acknowledged".to_string(),
+        }
+    }
+}
+
 /// Result of validating checkpoint responses
 #[derive(Debug, Clone)]
 pub struct CheckpointValidation {
@@ -501,6 +530,7 @@ impl StewardCheckpointDef {
             allow_actions: vec![],
             deny_actions: vec![],
             force_sync_trace: false,
+            trace_verbosity: None,
             priority: 500,
         }
     }
@@ -541,6 +571,12 @@ impl StewardCheckpointDef {
         self
     }
 
+    /// Switch the session's trace verbosity when this checkpoint completes
+    pub fn with_trace_verbosity(mut self, verbosity: TraceVerbosity) -> Self {
+        self.trace_verbosity = Some(verbosity);
+        self
+    }
+
     /// Set priority
     pub fn with_priority(mut self, priority: u32) -> Self {
         self.priority = priority;
@@ -567,6 +603,7 @@ impl CheckpointQuestion {
             validation: None,
             hint: None,
             on_invalid: InvalidAnswerAction::Retry,
+            sets_variable: None,
         }
     }
 
@@ -583,6 +620,7 @@ impl CheckpointQuestion {
             validation: None,
             hint: None,
             on_invalid: InvalidAnswerAction::Retry,
+            sets_variable: None,
         }
     }
 
@@ -599,6 +637,7 @@ impl CheckpointQuestion {
             validation: None,
             hint: Some("Respond with 'acknowledged' or 'understood'".to_string()),
             on_invalid: InvalidAnswerAction::Retry,
+            sets_variable: None,
         }
     }
 
@@ -616,6 +655,7 @@ impl CheckpointQuestion {
             validation: None,
             hint: None,
             on_invalid: InvalidAnswerAction::Retry,
+            sets_variable: None,
         }
     }
 
@@ -642,6 +682,12 @@ impl CheckpointQuestion {
         self.required = false;
         self
     }
+
+    /// Store the answer in a session variable
+    pub fn sets_variable(mut self, name: impl Into<String>) -> Self {
+        self.sets_variable = Some(name.into());
+        self
+    }
 }
 
 impl GuidanceBlock {
@@ -701,7 +747,7 @@ impl GuidanceBlock {
 }
 
 /// Checkpoint trigger result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggeredCheckpoint {
     /// The checkpoint type that triggered
     pub checkpoint_type: CheckpointType,
@@ -746,21 +792,90 @@ impl TriggeredCheckpoint {
     }
 }
 
+/// Callback implementing an `AnswerValidation::custom_validator`
+///
+/// Returns `Err(message)` to reject the answer.
+pub type CustomAnswerValidatorFn =
+    Arc<dyn Fn(&CheckpointQuestion, &AnswerValue) -> std::result::Result<(), String> + Send + Sync>;
+
 /// Validates checkpoint responses against questions
-pub struct CheckpointValidator;
+///
+/// Built-in rules cover response types, numeric bounds, JSON schemas, length,
+/// keyword and regex checks. Questions referencing a `custom_validator` are
+/// checked by the callback registered under that name; if none is registered
+/// the answer is rejected.
+#[derive(Clone, Default)]
+pub struct CheckpointValidator {
+    custom_validators: HashMap<String, CustomAnswerValidatorFn>,
+}
+
+impl std::fmt::Debug for CheckpointValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&str> = self.custom_validators.keys().map(|s| s.as_str()).collect();
+        names.sort_unstable();
+        f.debug_struct("CheckpointValidator")
+            .field("custom_validators", &names)
+            .finish()
+    }
+}
 
 impl CheckpointValidator {
-    /// Validate a response against a checkpoint definition
+    /// Create a validator with no custom validators
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom validator, replacing any existing one with the same name
+    pub fn register_validator<F>(&mut self, name: impl Into<String>, validator: F)
+    where
+        F: Fn(&CheckpointQuestion, &AnswerValue) -> std::result::Result<(), String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.custom_validators.insert(name.into(), Arc::new(validator));
+    }
+
+    /// Check if a custom validator is registered
+    pub fn has_validator(&self, name: &str) -> bool {
+        self.custom_validators.contains_key(name)
+    }
+
+    /// Validate a response using built-in rules only
     pub fn validate(
         checkpoint: &TriggeredCheckpoint,
         response: &CheckpointResponse,
     ) -> CheckpointValidation {
+        Self::new().validate_response(checkpoint, response)
+    }
+
+    /// Validate a response against a checkpoint definition
+    pub fn validate_response(
+        &self,
+        checkpoint: &TriggeredCheckpoint,
+        response: &CheckpointResponse,
+    ) -> CheckpointValidation {
+        self.validate_response_within(checkpoint, response, None)
+    }
+
+    /// Validate a response, giving custom validators at most `budget` in total
+    ///
+    /// Validators are not interrupted. Once they have used up the budget,
+    /// the remaining custom validators are not called and their answers are
+    /// rejected.
+    pub fn validate_response_within(
+        &self,
+        checkpoint: &TriggeredCheckpoint,
+        response: &CheckpointResponse,
+        budget: Option<Duration>,
+    ) -> CheckpointValidation {
+        let limit = budget.map(|budget| (Instant::now(), budget));
         let mut question_results = HashMap::new();
         let mut is_valid = true;
         let mut actions = vec![];
 
         for question in &checkpoint.questions {
-            let result = Self::validate_question(question, response.answers.get(&question.question_id));
+            let result = self.answer_result(question, response.answers.get(&question.question_id), limit);
 
             if question.required && !result.is_valid {
                 is_valid = false;
@@ -832,125 +947,166 @@ impl CheckpointValidator {
         }
     }
 
-    fn validate_question(
+    /// Validate a single answer against its question
+    pub fn validate_answer(
+        &self,
         question: &CheckpointQuestion,
         answer: Option<&AnswerValue>,
     ) -> QuestionValidationResult {
-        let Some(answer) = answer else {
-            return QuestionValidationResult {
-                question_id: question.question_id.clone(),
-                is_valid: false,
-                error_message: Some("No answer provided".to_string()),
-                action: question.on_invalid.clone(),
-            };
+        self.answer_result(question, answer, None)
+    }
+
+    fn answer_result(
+        &self,
+        question: &CheckpointQuestion,
+        answer: Option<&AnswerValue>,
+        limit: Option<(Instant, Duration)>,
+    ) -> QuestionValidationResult {
+        let outcome = match answer {
+            Some(answer) => self.check_answer(question, answer, limit),
+            None => Err("No answer provided".to_string()),
         };
 
-        // Type validation
-        let type_valid = match (&question.response_type, answer) {
-            (ResponseType::Text, AnswerValue::Text(_)) => true,
-            (ResponseType::Boolean, AnswerValue::Boolean(_)) => true,
-            (ResponseType::Number { min, max }, AnswerValue::Number(n)) => {
-                let in_min = min.map_or(true, |m| *n >= m);
-                let in_max = max.map_or(true, |m| *n <= m);
-                in_min && in_max
-            }
-            (ResponseType::Choice { options }, AnswerValue::Choice(c)) => {
-                options.contains(c)
-            }
-            (ResponseType::Json { .. }, AnswerValue::Json(_)) => true,
-            (ResponseType::Acknowledgment, AnswerValue::Acknowledged) => true,
-            (ResponseType::Acknowledgment, AnswerValue::Text(t)) => {
-                let t_lower = t.to_lowercase();
-                t_lower.contains("acknowledged") || t_lower.contains("understood")
-            }
-            _ => false,
+        QuestionValidationResult {
+            question_id: question.question_id.clone(),
+            is_valid: outcome.is_ok(),
+            error_message: outcome.err(),
+            action: question.on_invalid.clone(),
+        }
+    }
+
+    fn check_answer(
+        &self,
+        question: &CheckpointQuestion,
+        answer: &AnswerValue,
+        limit: Option<(Instant, Duration)>,
+    ) -> std::result::Result<(), String> {
+        Self::check_type(&question.response_type, answer)?;
+
+        let Some(validation) = &question.validation else {
+            return Ok(());
         };
 
-        if !type_valid {
-            return QuestionValidationResult {
-                question_id: question.question_id.clone(),
-                is_valid: false,
-                error_message: Some("Answer type does not match expected type".to_string()),
-                action: question.on_invalid.clone(),
-            };
+        // Text rules apply to any answer carried as a string
+        if let AnswerValue::Text(text) | AnswerValue::Choice(text) = answer {
+            Self::check_text(validation, text)?;
         }
 
-        // Custom validation
-        if let Some(validation) = &question.validation {
-            if let AnswerValue::Text(text) = answer {
-                // Length validation
-                if let Some(min) = validation.min_length {
-                    if text.len() < min {
-                        return QuestionValidationResult {
-                            question_id: question.question_id.clone(),
-                            is_valid: false,
-                            error_message: Some(format!("Answer too short (min {} chars)", min)),
-                            action: question.on_invalid.clone(),
-                        };
+        if let Some(name) = &validation.custom_validator {
+            let validator = self
+                .custom_validators
+                .get(name)
+                .ok_or_else(|| format!("Unknown custom validator: {}", name))?;
+            if limit.is_some_and(|(start, budget)| start.elapsed() >= budget) {
+                return Err(format!("Custom validator {} not run: latency budget exhausted", name));
+            }
+            validator(question, answer)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_type(
+        response_type: &ResponseType,
+        answer: &AnswerValue,
+    ) -> std::result::Result<(), String> {
+        match (response_type, answer) {
+            (ResponseType::Text, AnswerValue::Text(_)) => Ok(()),
+            (ResponseType::Boolean, AnswerValue::Boolean(_)) => Ok(()),
+            (ResponseType::Number { min, max }, AnswerValue::Number(n)) => {
+                if let Some(min) = min {
+                    if n < min {
+                        return Err(format!("Answer {} is below minimum {}", n, min));
                     }
                 }
-                if let Some(max) = validation.max_length {
-                    if text.len() > max {
-                        return QuestionValidationResult {
-                            question_id: question.question_id.clone(),
-                            is_valid: false,
-                            error_message: Some(format!("Answer too long (max {} chars)", max)),
-                            action: question.on_invalid.clone(),
-                        };
+                if let Some(max) = max {
+                    if n > max {
+                        return Err(format!("Answer {} is above maximum {}", n, max));
                     }
                 }
-
-                // Must contain
-                for keyword in &validation.must_contain {
-                    if !text.to_lowercase().contains(&keyword.to_lowercase()) {
-                        return QuestionValidationResult {
-                            question_id: question.question_id.clone(),
-                            is_valid: false,
-                            error_message: Some(format!("Answer must contain: {}", keyword)),
-                            action: question.on_invalid.clone(),
-                        };
-                    }
+                Ok(())
+            }
+            // Untagged answers deserialize strings as Text, so accept both
+            (ResponseType::Choice { options }, AnswerValue::Choice(c) | AnswerValue::Text(c)) => {
+                if options.contains(c) {
+                    Ok(())
+                } else {
+                    Err(format!("Answer must be one of: {}", options.join(", ")))
                 }
-
-                // Must not contain
-                for keyword in &validation.must_not_contain {
-                    if text.to_lowercase().contains(&keyword.to_lowercase()) {
-                        return QuestionValidationResult {
-                            question_id: question.question_id.clone(),
-                            is_valid: false,
-                            error_message: Some(format!("Answer must not contain: {}", keyword)),
-                            action: question.on_invalid.clone(),
-                        };
-                    }
+            }
+            (ResponseType::Json { schema }, AnswerValue::Json(value)) => match schema {
+                Some(schema) => Self::check_schema(schema, value),
+                None => Ok(()),
+            },
+            (ResponseType::Acknowledgment, AnswerValue::Acknowledged) => Ok(()),
+            (ResponseType::Acknowledgment, AnswerValue::Text(t)) => {
+                let t_lower = t.to_lowercase();
+                if t_lower.contains("acknowledged") || t_lower.contains("understood") {
+                    Ok(())
+                } else {
+                    Err("Answer must acknowledge the statement".to_string())
                 }
+            }
+            _ => Err("Answer type does not match expected type".to_string()),
+        }
+    }
 
-                // Pattern validation
-                if let Some(pattern) = &validation.pattern {
-                    if let Ok(re) = regex::Regex::new(pattern) {
-                        if !re.is_match(text) {
-                            return QuestionValidationResult {
-                                question_id: question.question_id.clone(),
-                                is_valid: false,
-                                error_message: Some("Answer does not match required pattern".to_string()),
-                                action: question.on_invalid.clone(),
-                            };
-                        }
-                    }
-                }
+    fn check_schema(schema: &Value, value: &Value) -> std::result::Result<(), String> {
+        let compiled = jsonschema::JSONSchema::compile(schema)
+            .map_err(|e| format!("Invalid answer schema: {}", e))?;
+
+        let result = compiled.validate(value).map_err(|errors| {
+            let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
+            format!("Answer does not match schema: {}", messages.join("; "))
+        });
+        result
+    }
+
+    fn check_text(validation: &AnswerValidation, text: &str) -> std::result::Result<(), String> {
+        // Length validation (in characters, not bytes)
+        let length = text.chars().count();
+        if let Some(min) = validation.min_length {
+            if length < min {
+                return Err(format!("Answer too short (min {} chars)", min));
+            }
+        }
+        if let Some(max) = validation.max_length {
+            if length > max {
+                return Err(format!("Answer too long (max {} chars)", max));
             }
         }
 
-        QuestionValidationResult {
-            question_id: question.question_id.clone(),
-            is_valid: true,
-            error_message: None,
-            action: question.on_invalid.clone(),
+        let text_lower = text.to_lowercase();
+
+        // Must contain
+        for keyword in &validation.must_contain {
+            if !text_lower.contains(&keyword.to_lowercase()) {
+                return Err(format!("Answer must contain: {}", keyword));
+            }
+        }
+
+        // Must not contain
+        for keyword in &validation.must_not_contain {
+            if text_lower.contains(&keyword.to_lowercase()) {
+                return Err(format!("Answer must not contain: {}", keyword));
+            }
         }
+
+        // Pattern validation - an invalid pattern rejects rather than passes
+        if let Some(pattern) = &validation.pattern {
+            let re = regex::Regex::new(pattern)
+                .map_err(|e| format!("Invalid answer pattern: {}", e))?;
+            if !re.is_match(text) {
+                return Err("Answer does not match required pattern".to_string());
+            }
+        }
+
+        Ok(())
     }
 }
 
 /// Additional data from checkpoint triggers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TriggerData {
     /// Keywords that matched
     Keywords(Vec<String>),
@@ -974,6 +1130,117 @@ pub enum TriggerData {
         error_type: String,
         message: String,
     },
+    /// Custom trigger that fired
+    Custom {
+        trigger_id: String,
+        params: HashMap<String, Value>,
+    },
+}
+
+/// Session state handed to custom trigger callbacks
+#[derive(Debug, Clone)]
+pub struct CustomTriggerContext {
+    /// Session being evaluated
+    pub session_id: String,
+    /// Agent that owns the session
+    pub agent_id: String,
+    /// Session goal
+    pub goal: String,
+    /// Action being performed (if evaluated before an action)
+    pub action_id: Option<String>,
+    /// Total actions executed in this session
+    pub total_actions: u64,
+    /// Actions since the last checkpoint
+    pub actions_since_checkpoint: u64,
+    /// Capabilities currently unlocked for the session
+    pub unlocked_capabilities: Vec<String>,
+}
+
+/// Decision returned by a custom trigger callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomTriggerDecision {
+    /// The checkpoint should fire
+    Fire,
+    /// The checkpoint should be skipped
+    Skip,
+}
+
+impl CustomTriggerDecision {
+    /// Get the string representation (used in TRACE payloads)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CustomTriggerDecision::Fire => "fire",
+            CustomTriggerDecision::Skip => "skip",
+        }
+    }
+}
+
+/// Callback evaluating a `CheckpointTrigger::Custom` trigger
+pub type CustomTriggerFn =
+    Arc<dyn Fn(&CustomTriggerContext, &HashMap<String, Value>) -> CustomTriggerDecision + Send + Sync>;
+
+/// Registry of embedder-provided callbacks for custom checkpoint triggers
+///
+/// Callbacks are keyed by the `trigger_id` used in `CheckpointTrigger::Custom`.
+/// A custom trigger with no registered callback never fires.
+#[derive(Default)]
+pub struct CustomTriggerEvaluator {
+    triggers: HashMap<String, CustomTriggerFn>,
+}
+
+impl std::fmt::Debug for CustomTriggerEvaluator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomTriggerEvaluator")
+            .field("trigger_ids", &self.registered_ids())
+            .finish()
+    }
+}
+
+impl CustomTriggerEvaluator {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback for a trigger ID, replacing any existing one
+    pub fn register<F>(&mut self, trigger_id: impl Into<String>, callback: F)
+    where
+        F: Fn(&CustomTriggerContext, &HashMap<String, Value>) -> CustomTriggerDecision
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.triggers.insert(trigger_id.into(), Arc::new(callback));
+    }
+
+    /// Remove the callback for a trigger ID
+    pub fn unregister(&mut self, trigger_id: &str) -> bool {
+        self.triggers.remove(trigger_id).is_some()
+    }
+
+    /// Check if a callback is registered for a trigger ID
+    pub fn is_registered(&self, trigger_id: &str) -> bool {
+        self.triggers.contains_key(trigger_id)
+    }
+
+    /// List registered trigger IDs (sorted)
+    pub fn registered_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.triggers.keys().map(|s| s.as_str()).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Evaluate a trigger
+    ///
+    /// Returns `None` if no callback is registered for `trigger_id`.
+    pub fn evaluate(
+        &self,
+        trigger_id: &str,
+        context: &CustomTriggerContext,
+        params: &HashMap<String, Value>,
+    ) -> Option<CustomTriggerDecision> {
+        self.triggers.get(trigger_id).map(|callback| callback(context, params))
+    }
 }
 
 /// Checkpoint configuration from atlas
@@ -1948,6 +2215,209 @@ mod tests {
         assert!(!validation.is_valid);
     }
 
+    fn interactive_checkpoint(questions: Vec<CheckpointQuestion>) -> TriggeredCheckpoint {
+        TriggeredCheckpoint {
+            checkpoint_type: CheckpointType::Interactive,
+            priority: 500,
+            inject_contexts: vec![],
+            is_sync: true,
+            trigger_data: None,
+            steward_def: None,
+            questions,
+            guidance: None,
+            mode: CheckpointMode::Blocking,
+        }
+    }
+
+    fn response_with(answers: Vec<(&str, AnswerValue)>) -> CheckpointResponse {
+        CheckpointResponse {
+            checkpoint_id: "test".to_string(),
+            answers: answers.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            guidance_acknowledged: false,
+            responded_at: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "session-1".to_string(),
+        }
+    }
+
+    fn text_rules() -> AnswerValidation {
+        AnswerValidation {
+            pattern: None,
+            min_length: None,
+            max_length: None,
+            must_contain: vec![],
+            must_not_contain: vec![],
+            custom_validator: None,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_validator_numeric_bounds() {
+        let question = CheckpointQuestion {
+            response_type: ResponseType::Number { min: Some(1.0), max: Some(10.0) },
+            ..CheckpointQuestion::text("count", "How many records?")
+        };
+        let checkpoint = interactive_checkpoint(vec![question]);
+
+        let validation = CheckpointValidator::validate(
+            &checkpoint,
+            &response_with(vec![("count", AnswerValue::Number(5.0))]),
+        );
+        assert!(validation.is_valid);
+
+        let validation = CheckpointValidator::validate(
+            &checkpoint,
+            &response_with(vec![("count", AnswerValue::Number(11.0))]),
+        );
+        assert!(!validation.is_valid);
+        let error = validation.question_results["count"].error_message.clone().unwrap();
+        assert!(error.contains("above maximum"));
+    }
+
+    #[test]
+    fn test_checkpoint_validator_json_schema() {
+        let question = CheckpointQuestion {
+            response_type: ResponseType::Json {
+                schema: Some(serde_json::json!({
+                    "type": "object",
+                    "required": ["ticket_id"],
+                    "properties": { "ticket_id": { "type": "string" } }
+                })),
+            },
+            ..CheckpointQuestion::text("plan", "Which ticket?")
+        };
+        let checkpoint = interactive_checkpoint(vec![question]);
+
+        let valid: CheckpointResponse = serde_json::from_value(serde_json::json!({
+            "checkpoint_id": "test",
+            "answers": { "plan": { "ticket_id": "T-1" } },
+            "responded_at": "2024-01-01T00:00:00Z",
+            "session_id": "session-1"
+        }))
+        .unwrap();
+        assert!(CheckpointValidator::validate(&checkpoint, &valid).is_valid);
+
+        let invalid = response_with(vec![("plan", AnswerValue::Json(serde_json::json!({ "ticket_id": 7 })))]);
+        let validation = CheckpointValidator::validate(&checkpoint, &invalid);
+        assert!(!validation.is_valid);
+        assert!(validation.question_results["plan"]
+            .error_message
+            .as_ref()
+            .unwrap()
+            .contains("does not match schema"));
+    }
+
+    #[test]
+    fn test_checkpoint_validator_choice_and_text_rules() {
+        let checkpoint = interactive_checkpoint(vec![
+            CheckpointQuestion::choice("env", "Which environment?", vec!["staging".to_string(), "prod".to_string()]),
+            CheckpointQuestion::text("name", "Your name").with_validation(AnswerValidation {
+                min_length: Some(3),
+                max_length: Some(4),
+                ..text_rules()
+            }),
+            CheckpointQuestion::text("bad-pattern", "Anything").with_validation(AnswerValidation {
+                pattern: Some("(".to_string()),
+                ..text_rules()
+            }),
+        ]);
+
+        // Choices arrive as Text through untagged deserialization; length is in chars
+        let validation = CheckpointValidator::validate(
+            &checkpoint,
+            &response_with(vec![
+                ("env", AnswerValue::Text("staging".to_string())),
+                ("name", AnswerValue::Text("Zoë".to_string())),
+                ("bad-pattern", AnswerValue::Text("x".to_string())),
+            ]),
+        );
+        assert!(validation.question_results["env"].is_valid);
+        assert!(validation.question_results["name"].is_valid);
+        // An uncompilable pattern rejects rather than silently passing
+        assert!(!validation.question_results["bad-pattern"].is_valid);
+
+        let validation = CheckpointValidator::validate(
+            &checkpoint,
+            &response_with(vec![("env", AnswerValue::Choice("dev".to_string()))]),
+        );
+        assert!(!validation.question_results["env"].is_valid);
+    }
+
+    #[test]
+    fn test_checkpoint_validator_custom_validator() {
+        let question = CheckpointQuestion::text("ticket", "Ticket reference").with_validation(
+            AnswerValidation {
+                custom_validator: Some("ticket-exists".to_string()),
+                ..text_rules()
+            },
+        );
+        let checkpoint = interactive_checkpoint(vec![question]);
+        let answer = |t: &str| response_with(vec![("ticket", AnswerValue::Text(t.to_string()))]);
+
+        // Unregistered validator fails closed
+        let validation = CheckpointValidator::validate(&checkpoint, &answer("T-1"));
+        assert!(!validation.is_valid);
+        assert!(validation.question_results["ticket"]
+            .error_message
+            .as_ref()
+            .unwrap()
+            .contains("Unknown custom validator"));
+
+        let mut validator = CheckpointValidator::new();
+        validator.register_validator("ticket-exists", |_question: &CheckpointQuestion, answer: &AnswerValue| match answer {
+            AnswerValue::Text(t) if t.starts_with("T-") => Ok(()),
+            _ => Err("No such ticket".to_string()),
+        });
+        assert!(validator.has_validator("ticket-exists"));
+
+        assert!(validator.validate_response(&checkpoint, &answer("T-1")).is_valid);
+        let validation = validator.validate_response(&checkpoint, &answer("X-1"));
+        assert!(!validation.is_valid);
+        assert_eq!(
+            validation.question_results["ticket"].error_message.as_deref(),
+            Some("No such ticket")
+        );
+    }
+
+    #[test]
+    fn test_custom_trigger_evaluator() {
+        let mut evaluator = CustomTriggerEvaluator::new();
+        evaluator.register("after-two-actions", |ctx: &CustomTriggerContext, params: &HashMap<String, Value>| {
+            let threshold = params.get("threshold").and_then(|v| v.as_u64()).unwrap_or(2);
+            if ctx.total_actions >= threshold {
+                CustomTriggerDecision::Fire
+            } else {
+                CustomTriggerDecision::Skip
+            }
+        });
+
+        let mut ctx = CustomTriggerContext {
+            session_id: "s1".to_string(),
+            agent_id: "agent".to_string(),
+            goal: "goal".to_string(),
+            action_id: None,
+            total_actions: 1,
+            actions_since_checkpoint: 1,
+            unlocked_capabilities: vec![],
+        };
+        let params = HashMap::new();
+
+        assert!(evaluator.is_registered("after-two-actions"));
+        assert_eq!(
+            evaluator.evaluate("after-two-actions", &ctx, &params),
+            Some(CustomTriggerDecision::Skip)
+        );
+
+        ctx.total_actions = 2;
+        assert_eq!(
+            evaluator.evaluate("after-two-actions", &ctx, &params),
+            Some(CustomTriggerDecision::Fire)
+        );
+
+        assert_eq!(evaluator.evaluate("unknown", &ctx, &params), None);
+        assert!(evaluator.unregister("after-two-actions"));
+        assert!(evaluator.registered_ids().is_empty());
+    }
+
     #[test]
     fn test_steward_checkpoint_evaluation() {
         let checkpoint_def = StewardCheckpointDef::new(
@@ -0,0 +1,299 @@
+//! External policy engines
+//!
+//! An `external` policy hands the decision for its actions to a policy
+//! engine such as OPA. For every matching action the resolver POSTs an
+//! OPA-style decision request:
+//!
+//! ```json
+//! {"input": {"policy_id": "...", "operation": "execute", "action_id": "...",
+//!            "parameters": {...}, "session": {"session_id": "...", "agent_id": "...", "goal": "...",
+//!                                             "principal": {"subject": "...", "tenant": "...", "roles": [...]}}}}
+//! ```
+//!
+//! and reads back either `{"result": true}` or
+//! `{"result": {"allow": false, "reason": "..."}}`. Like the approval
+//! channels, the call goes through an embedder-provided [`PolicyDecisionFn`],
+//! so the core stays free of an HTTP client.
+//!
+//! Policy parameters:
+//!
+//! - `url` (required): decision endpoint
+//! - `timeout_ms` (default 1000): slower answers count as timeouts
+//! - `fail_open` (default false): allow instead of deny when the engine
+//!   errors, times out or isn't configured
+//! - `cache_ttl_seconds` (default: the policy cache's TTL): how long a
+//!   decision is reused for the same input; `0` disables caching
+//!
+//! External policies are consulted only for actions the built-in policies
+//! would allow, so they can narrow but never widen what an atlas permits.
+//!
+//! A resolver may also set a latency budget for all the external decisions
+//! one resolution or execution waits for
+//! ([`PolicyEvaluator::with_latency_budget`](super::PolicyEvaluator::with_latency_budget)).
+//! Each call's timeout is cut to what is left of the budget, and once it is
+//! spent the remaining engines aren't called at all. Either kind of timeout
+//! falls back to the policy's `fail_open`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::atlas::{AtlasPolicy, PolicyType};
+use crate::cache::{hash_params, PolicyCache, PolicyDecision};
+use crate::clock::Instant;
+use crate::error::{CRAError, Result};
+
+/// Default time an external decision may take
+pub const DEFAULT_EXTERNAL_TIMEOUT_MS: u64 = 1000;
+
+/// Callback that POSTs a decision request and returns the response body
+///
+/// Called with the endpoint URL, the request body and the policy's timeout.
+/// Returns `Err(message)` when the call fails or times out.
+pub type PolicyDecisionFn =
+    Arc<dyn Fn(&str, &Value, Duration) -> std::result::Result<Value, String> + Send + Sync>;
+
+/// Parsed parameters of an `external` policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalPolicy {
+    /// Policy ID
+    pub policy_id: String,
+    /// Decision endpoint
+    pub url: String,
+    /// Longest a decision may take
+    pub timeout: Duration,
+    /// Allow when no decision could be obtained
+    pub fail_open: bool,
+    /// How long decisions are cached; `None` uses the cache default
+    pub cache_ttl: Option<Duration>,
+    /// Reason reported for denials that don't carry one
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExternalParameters {
+    url: String,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    fail_open: bool,
+    #[serde(default)]
+    cache_ttl_seconds: Option<u64>,
+}
+
+impl ExternalPolicy {
+    /// Parse an `external` atlas policy
+    pub fn from_policy(policy: &AtlasPolicy) -> Result<Self> {
+        let invalid = |reason: String| CRAError::InvalidAtlasManifest {
+            reason: format!("external policy '{}': {}", policy.policy_id, reason),
+        };
+
+        if policy.policy_type != PolicyType::External {
+            return Err(invalid(format!("policy type is '{}'", policy.policy_type)));
+        }
+        let parameters = policy
+            .parameters
+            .clone()
+            .ok_or_else(|| invalid("missing parameters".to_string()))?;
+        let parameters: ExternalParameters =
+            serde_json::from_value(parameters).map_err(|e| invalid(e.to_string()))?;
+
+        if !parameters.url.starts_with("http://") && !parameters.url.starts_with("https://") {
+            return Err(invalid(format!("url '{}' is not an http(s) URL", parameters.url)));
+        }
+        let timeout_ms = parameters.timeout_ms.unwrap_or(DEFAULT_EXTERNAL_TIMEOUT_MS);
+        if timeout_ms == 0 {
+            return Err(invalid("timeout_ms must be positive".to_string()));
+        }
+
+        Ok(Self {
+            policy_id: policy.policy_id.clone(),
+            url: parameters.url,
+            timeout: Duration::from_millis(timeout_ms),
+            fail_open: parameters.fail_open,
+            cache_ttl: parameters.cache_ttl_seconds.map(Duration::from_secs),
+            reason: policy.reason.clone(),
+        })
+    }
+}
+
+/// How an external decision was reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExternalDecision {
+    /// Whether the action may proceed
+    pub allowed: bool,
+    /// Reason for a denial
+    pub reason: String,
+    /// `allow`, `deny` or `error`
+    pub outcome: &'static str,
+    /// Why no decision could be obtained
+    pub error: Option<String>,
+    /// Time spent waiting for the engine
+    pub latency_ms: u64,
+    /// Whether the decision came from the cache
+    pub cached: bool,
+    /// Set when no decision arrived in time
+    pub timeout: Option<EvaluationTimeout>,
+}
+
+/// Why an external decision timed out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EvaluationTimeout {
+    /// `policy_timeout` (the policy's `timeout_ms`) or `latency_budget`
+    pub cause: &'static str,
+    /// Time the call was given
+    pub timeout_ms: u64,
+}
+
+/// Calls external policy engines and caches their decisions
+#[derive(Default)]
+pub(crate) struct ExternalPolicies {
+    client: Option<PolicyDecisionFn>,
+    cache: PolicyCache,
+}
+
+impl std::fmt::Debug for ExternalPolicies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalPolicies")
+            .field("client", &self.client.is_some())
+            .field("cache", &self.cache.len())
+            .finish()
+    }
+}
+
+impl ExternalPolicies {
+    /// Set the callback decision requests are sent through
+    pub fn set_client(&mut self, client: PolicyDecisionFn) {
+        self.client = Some(client);
+    }
+
+    /// Drop every cached decision
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Get a decision for one action
+    ///
+    /// `input` is the `input` document of the decision request. `budget` is
+    /// what is left of the caller's latency budget, if it has one.
+    pub fn decide(&self, policy: &AtlasPolicy, action_id: &str, input: &Value, budget: Option<Duration>) -> ExternalDecision {
+        let config = match ExternalPolicy::from_policy(policy) {
+            Ok(config) => config,
+            // Invalid parameters fail closed; the validator reports them
+            Err(e) => return failed(false, e.to_string(), 0),
+        };
+        let params_hash = hash_params(input);
+        if let Some(hit) = self.cache.get(&config.policy_id, action_id, &params_hash) {
+            let allowed = hit.decision.is_allowed();
+            return ExternalDecision {
+                allowed,
+                reason: hit.reason.unwrap_or_default(),
+                outcome: if allowed { "allow" } else { "deny" },
+                error: None,
+                latency_ms: 0,
+                cached: true,
+                timeout: None,
+            };
+        }
+
+        let Some(client) = &self.client else {
+            return failed(config.fail_open, "no external policy client configured".to_string(), 0);
+        };
+        let (timeout, cause) = match budget {
+            Some(left) if left.is_zero() => {
+                let mut decision = failed(config.fail_open, "latency budget exhausted".to_string(), 0);
+                decision.timeout = Some(EvaluationTimeout { cause: "latency_budget", timeout_ms: 0 });
+                return decision;
+            }
+            Some(left) if left < config.timeout => (left, "latency_budget"),
+            _ => (config.timeout, "policy_timeout"),
+        };
+        let start = Instant::now();
+        let response = client(&config.url, &json!({ "input": input }), timeout);
+        let elapsed = start.elapsed();
+        let latency_ms = elapsed.as_millis() as u64;
+
+        // A late answer is discarded, and so is an error from a call the
+        // client gave up on at the timeout
+        if elapsed >= timeout {
+            let timeout_ms = timeout.as_millis() as u64;
+            let mut decision = failed(config.fail_open, format!("timed out after {} ms", timeout_ms), latency_ms);
+            decision.timeout = Some(EvaluationTimeout { cause, timeout_ms });
+            return decision;
+        }
+        let (allowed, reason) = match response.and_then(|body| parse_decision(&body)) {
+            Ok(decision) => decision,
+            Err(e) => return failed(config.fail_open, e, latency_ms),
+        };
+        let reason = reason
+            .or(config.reason)
+            .unwrap_or_else(|| "Denied by external policy".to_string());
+
+        if config.cache_ttl != Some(Duration::ZERO) {
+            self.cache.set_full(
+                &config.policy_id,
+                action_id,
+                &params_hash,
+                if allowed { PolicyDecision::Allow } else { PolicyDecision::Deny },
+                Some(reason.clone()),
+                Some(config.policy_id.clone()),
+                config.cache_ttl,
+            );
+        }
+
+        ExternalDecision {
+            allowed,
+            reason,
+            outcome: if allowed { "allow" } else { "deny" },
+            error: None,
+            latency_ms,
+            cached: false,
+            timeout: None,
+        }
+    }
+}
+
+fn failed(fail_open: bool, error: String, latency_ms: u64) -> ExternalDecision {
+    ExternalDecision {
+        allowed: fail_open,
+        reason: format!("External policy engine unavailable: {}", error),
+        outcome: "error",
+        error: Some(error),
+        latency_ms,
+        cached: false,
+        timeout: None,
+    }
+}
+
+/// Read `allow` and `reason` from an OPA-style response
+fn parse_decision(body: &Value) -> std::result::Result<(bool, Option<String>), String> {
+    let result = body.get("result").unwrap_or(body);
+    if let Some(allowed) = result.as_bool() {
+        return Ok((allowed, None));
+    }
+    let allowed = result
+        .get("allow")
+        .and_then(Value::as_bool)
+        .ok_or_else(|| "response has no boolean result or result.allow".to_string())?;
+    let reason = result.get("reason").and_then(Value::as_str).map(String::from);
+    Ok((allowed, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decision_shapes() {
+        assert_eq!(parse_decision(&json!({"result": true})), Ok((true, None)));
+        assert_eq!(
+            parse_decision(&json!({"result": {"allow": false, "reason": "after hours"}})),
+            Ok((false, Some("after hours".to_string())))
+        );
+        // OPA returns no result when the rule is undefined
+        assert!(parse_decision(&json!({})).is_err());
+        assert!(parse_decision(&json!({"result": {"reason": "x"}})).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Garbage collection of expired and orphaned resolver data
+//!
+//! A long-running resolver accumulates state nothing will read again:
+//! pins of resolutions past their TTL, approval requests in sessions that
+//! are gone or that nobody answered, expired idempotent responses, context
+//! content no trace refers to, and delivered webhook records.
+//! `Resolver::collect_garbage` reclaims it according to a [`GcPolicy`] and
+//! reports a [`GcStats`]; runs that reclaim something (and every dry run)
+//! are recorded as `gc.completed` in the
+//! [`ADMIN_AUDIT_SESSION`](crate::trace::ADMIN_AUDIT_SESSION) trace.
+//!
+//! Runs are usually driven by a `TimerManager` with a GC interval, whose
+//! handler calls `collect_garbage` from `on_garbage_collect`:
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use cra_core::carp::GcPolicy;
+//! use cra_core::Resolver;
+//!
+//! let mut resolver = Resolver::new().with_gc_policy(
+//!     GcPolicy::new()
+//!         .resolution_grace(Duration::from_secs(60))
+//!         .approval_max_age(Duration::from_secs(24 * 3600)),
+//! );
+//! let stats = resolver.collect_garbage().unwrap();
+//! assert_eq!(stats.items_reclaimed(), 0);
+//! ```
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What a garbage collection run reclaims
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcPolicy {
+    /// How long a resolution is kept after its TTL runs out
+    pub resolution_grace: Duration,
+    /// Age after which approval requests are dropped, whatever their status
+    ///
+    /// Requests in sessions that are no longer active are always dropped.
+    pub approval_max_age: Option<Duration>,
+    /// Drop expired idempotent responses
+    pub idempotency: bool,
+    /// Drop context content no retained TRACE event refers to
+    pub orphaned_content: bool,
+    /// Drop successful deliveries from the webhook history
+    pub webhook_history: bool,
+    /// Count what would be reclaimed without reclaiming it
+    pub dry_run: bool,
+}
+
+impl Default for GcPolicy {
+    fn default() -> Self {
+        Self {
+            resolution_grace: Duration::ZERO,
+            approval_max_age: Some(Duration::from_secs(7 * 24 * 3600)),
+            idempotency: true,
+            orphaned_content: true,
+            webhook_history: false,
+            dry_run: false,
+        }
+    }
+}
+
+impl GcPolicy {
+    /// Create the default policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep resolutions this long after their TTL
+    pub fn resolution_grace(mut self, grace: Duration) -> Self {
+        self.resolution_grace = grace;
+        self
+    }
+
+    /// Drop approval requests older than this
+    pub fn approval_max_age(mut self, max_age: Duration) -> Self {
+        self.approval_max_age = Some(max_age);
+        self
+    }
+
+    /// Keep approval requests in active sessions, however old
+    pub fn keep_approvals(mut self) -> Self {
+        self.approval_max_age = None;
+        self
+    }
+
+    /// Whether to drop expired idempotent responses
+    pub fn idempotency(mut self, enabled: bool) -> Self {
+        self.idempotency = enabled;
+        self
+    }
+
+    /// Whether to drop unreferenced context content
+    pub fn orphaned_content(mut self, enabled: bool) -> Self {
+        self.orphaned_content = enabled;
+        self
+    }
+
+    /// Whether to drop delivered webhook records
+    pub fn webhook_history(mut self, enabled: bool) -> Self {
+        self.webhook_history = enabled;
+        self
+    }
+
+    /// Only count what would be reclaimed
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+}
+
+/// What a garbage collection run reclaimed (or, in a dry run, would have)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcStats {
+    /// Expired resolution pins
+    pub resolutions: usize,
+    /// Approval requests
+    pub approvals: usize,
+    /// Expired idempotent responses
+    pub idempotency_entries: usize,
+    /// Unreferenced context contents
+    pub contents: usize,
+    /// Delivered webhook records
+    pub webhook_deliveries: usize,
+    /// Approximate bytes freed: content bytes, plus the serialized size of
+    /// approval, idempotency and webhook records
+    pub bytes_freed: u64,
+    /// Whether nothing was actually reclaimed
+    pub dry_run: bool,
+    /// How long the run took
+    pub duration_us: u64,
+}
+
+impl GcStats {
+    /// Total items reclaimed
+    pub fn items_reclaimed(&self) -> usize {
+        self.resolutions + self.approvals + self.idempotency_entries + self.contents + self.webhook_deliveries
+    }
+}
+
+/// Approximate in-memory size of a record, by its JSON size
+pub(crate) fn approximate_size<T: Serialize>(record: &T) -> u64 {
+    serde_json::to_vec(record).map_or(0, |bytes| bytes.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carp::CARPRequest;
+    use crate::context::ContentStore;
+    use crate::trace::{EventType, ADMIN_AUDIT_SESSION};
+    use crate::{AtlasManifest, CRAError, Resolver};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn atlas() -> AtlasManifest {
+        serde_json::from_value(json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.gc",
+            "version": "1.0.0",
+            "name": "GC Atlas",
+            "description": "Atlas for garbage collection tests",
+            "domains": ["test"],
+            "capabilities": [],
+            "policies": [
+                {"policy_id": "review-deletes", "type": "requires_approval", "actions": ["*.delete"]}
+            ],
+            "actions": [
+                {"action_id": "file.read", "name": "Read", "description": "Read a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "low"},
+                {"action_id": "file.delete", "name": "Delete", "description": "Delete a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "high"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collect_expired_and_orphaned() {
+        let store = Arc::new(ContentStore::new());
+        let policy = GcPolicy::new().approval_max_age(Duration::ZERO);
+        let mut resolver = Resolver::new()
+            .with_default_ttl(0)
+            .with_content_store(store.clone())
+            .with_gc_policy(policy.clone());
+        resolver.load_atlas(atlas()).unwrap();
+
+        let session_id = resolver.create_session("agent-1", "Tidy files").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "agent-1".to_string(), "read files".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        let err = resolver
+            .execute(&session_id, &resolution.trace_id, "file.delete", json!({}))
+            .unwrap_err();
+        assert!(matches!(err, CRAError::ActionRequiresApproval { .. }));
+        store.put("nobody refers to this");
+
+        // A dry run counts without reclaiming
+        let dry = resolver.collect_garbage_with(&policy.clone().dry_run(true)).unwrap();
+        assert!(dry.dry_run);
+        assert_eq!(dry.resolutions, 1);
+        assert_eq!(dry.approvals, 1);
+        assert_eq!(dry.contents, 1);
+        assert!(dry.bytes_freed >= "nobody refers to this".len() as u64);
+        assert_eq!(store.stats().blocks, 1);
+
+        let stats = resolver.collect_garbage().unwrap();
+        assert!(!stats.dry_run);
+        assert_eq!(stats.items_reclaimed(), dry.items_reclaimed());
+        assert_eq!(store.stats().blocks, 0);
+        assert_eq!(resolver.collect_garbage().unwrap().items_reclaimed(), 0);
+
+        // Both runs are audited; the empty one is not
+        let audited: Vec<_> = resolver
+            .get_trace(ADMIN_AUDIT_SESSION)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == EventType::GcCompleted)
+            .collect();
+        assert_eq!(audited.len(), 2);
+        assert_eq!(audited[0].payload["dry_run"], json!(true));
+        assert_eq!(audited[1].payload["approvals"], json!(1));
+    }
+}
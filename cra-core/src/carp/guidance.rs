@@ -0,0 +1,197 @@
+//! Guidance Injection
+//!
+//! Tracks the guidance blocks that checkpoints have injected into a session
+//! and assembles them into a single context block for resolutions.
+//!
+//! ## Merge Rules
+//!
+//! - Blocks are ordered by priority (highest first), then by injection order
+//! - An `append: false` block replaces every active block of the same format
+//! - A block with `expires_after` is removed once that checkpoint completes
+
+use serde::{Deserialize, Serialize};
+
+use super::checkpoint::{GuidanceBlock, GuidanceFormat};
+use super::ContextBlock;
+
+/// Block ID used for assembled guidance in resolutions
+pub const GUIDANCE_BLOCK_ID: &str = "checkpoint-guidance";
+
+/// A guidance block currently active in a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveGuidance {
+    /// Checkpoint that injected this guidance
+    pub checkpoint_id: String,
+    /// The guidance block
+    pub block: GuidanceBlock,
+    /// Injection order within the session (for deterministic tie-breaking)
+    pub injected_seq: u64,
+}
+
+/// Why a guidance block stopped being active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuidanceRemoval {
+    /// Its `expires_after` checkpoint completed
+    Expired,
+    /// A non-appending block of the same format replaced it
+    Replaced,
+}
+
+impl GuidanceRemoval {
+    /// Get the string representation (used in TRACE payloads)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuidanceRemoval::Expired => "expired",
+            GuidanceRemoval::Replaced => "replaced",
+        }
+    }
+}
+
+/// Per-session guidance state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuidanceManager {
+    active: Vec<ActiveGuidance>,
+    next_seq: u64,
+}
+
+impl GuidanceManager {
+    /// Create an empty manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject a guidance block from a checkpoint
+    ///
+    /// Returns the blocks that were replaced by this injection.
+    pub fn inject(&mut self, checkpoint_id: &str, block: GuidanceBlock) -> Vec<ActiveGuidance> {
+        let mut replaced = Vec::new();
+        if !block.append {
+            let (removed, kept) = std::mem::take(&mut self.active)
+                .into_iter()
+                .partition(|g| g.block.format == block.format);
+            self.active = kept;
+            replaced = removed;
+        }
+
+        self.active.push(ActiveGuidance {
+            checkpoint_id: checkpoint_id.to_string(),
+            block,
+            injected_seq: self.next_seq,
+        });
+        self.next_seq += 1;
+
+        replaced
+    }
+
+    /// Mark a checkpoint as complete, expiring guidance bound to it
+    ///
+    /// Returns the expired blocks.
+    pub fn complete_checkpoint(&mut self, checkpoint_id: &str) -> Vec<ActiveGuidance> {
+        let (expired, kept) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|g| g.block.expires_after.as_deref() == Some(checkpoint_id));
+        self.active = kept;
+        expired
+    }
+
+    /// Active guidance in merge order (priority desc, then injection order)
+    pub fn active(&self) -> Vec<&ActiveGuidance> {
+        let mut active: Vec<&ActiveGuidance> = self.active.iter().collect();
+        active.sort_by_key(|g| (std::cmp::Reverse(g.block.priority), g.injected_seq));
+        active
+    }
+
+    /// Check if any guidance is active
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    /// Assemble active guidance into a single text
+    pub fn assemble(&self) -> Option<String> {
+        if self.active.is_empty() {
+            return None;
+        }
+
+        let sections: Vec<&str> = self.active().iter().map(|g| g.block.content.as_str()).collect();
+        Some(sections.join("\n\n"))
+    }
+
+    /// Assemble active guidance into a resolution context block
+    pub fn to_context_block(&self) -> Option<ContextBlock> {
+        let content = self.assemble()?;
+        let active = self.active();
+        let priority = active.first().map(|g| g.block.priority).unwrap_or_default();
+        let content_type = if active.iter().all(|g| g.block.format == GuidanceFormat::Json) {
+            "application/json"
+        } else {
+            "text/markdown"
+        };
+
+        Some(
+            ContextBlock::new(
+                GUIDANCE_BLOCK_ID.to_string(),
+                "Checkpoint Guidance".to_string(),
+                content,
+            )
+            .with_priority(priority)
+            .with_content_type(content_type.to_string()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_order_is_deterministic() {
+        let mut manager = GuidanceManager::new();
+        manager.inject("a", GuidanceBlock::text("low first"));
+        manager.inject("b", GuidanceBlock::text("high").with_priority(10));
+        manager.inject("c", GuidanceBlock::text("low second"));
+
+        assert_eq!(
+            manager.assemble().unwrap(),
+            "high\n\nlow first\n\nlow second"
+        );
+    }
+
+    #[test]
+    fn test_replace_same_format() {
+        let mut manager = GuidanceManager::new();
+        manager.inject("a", GuidanceBlock::system_instruction("old rules"));
+        manager.inject("b", GuidanceBlock::markdown("notes"));
+
+        let replaced = manager.inject("c", GuidanceBlock::system_instruction("new rules"));
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(replaced[0].checkpoint_id, "a");
+
+        // System instructions outrank the markdown notes
+        assert_eq!(manager.assemble().unwrap(), "new rules\n\nnotes");
+    }
+
+    #[test]
+    fn test_expiry_after_checkpoint() {
+        let mut manager = GuidanceManager::new();
+        manager.inject("a", GuidanceBlock::text("temporary").expires_after("review"));
+        manager.inject("a", GuidanceBlock::text("permanent"));
+
+        assert!(manager.complete_checkpoint("other").is_empty());
+
+        let expired = manager.complete_checkpoint("review");
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].block.content, "temporary");
+
+        let block = manager.to_context_block().unwrap();
+        assert_eq!(block.block_id, GUIDANCE_BLOCK_ID);
+        assert_eq!(block.content, "permanent");
+    }
+
+    #[test]
+    fn test_empty_manager() {
+        let manager = GuidanceManager::new();
+        assert!(manager.is_empty());
+        assert!(manager.to_context_block().is_none());
+    }
+}
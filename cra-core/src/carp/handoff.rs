@@ -0,0 +1,111 @@
+//! Session Handoff
+//!
+//! Moves a live session between resolvers, e.g. from an edge WASM resolver
+//! to a central server mid-task. The source ends its copy of the session with
+//! a `session.handoff_out` event and exports a [`SessionSnapshot`]: session
+//! state plus the full TRACE chain, signed by the source's event signer. The
+//! destination verifies the chain and signature, then continues the same
+//! chain with `session.handoff_in`.
+//!
+//! ```text
+//! source                                   destination
+//! ──────                                   ───────────
+//! ... → session.handoff_out ─ snapshot ──► verify → session.handoff_in → ...
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::trace::{canonical_json, EventSignature, EventType, TRACEEvent};
+
+use super::template::SessionVariables;
+use super::{CapabilityState, GuidanceManager};
+
+/// Handoff snapshot format version
+pub const SNAPSHOT_VERSION: &str = "1.0";
+
+/// Last event of an exported chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHead {
+    /// Sequence number of the last event
+    pub sequence: u64,
+    /// Hash of the last event
+    pub event_hash: String,
+}
+
+/// Everything a resolver needs to continue a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Snapshot format version
+    pub snapshot_version: String,
+    /// Session being handed off
+    pub session_id: String,
+    /// Agent that owns the session
+    pub agent_id: String,
+    /// Initial goal for the session
+    pub goal: String,
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+    /// Resolutions so far
+    pub resolution_count: u64,
+    /// Actions executed so far
+    pub action_count: u64,
+    /// Atlases the session was using; the destination must have them loaded
+    pub atlas_ids: Vec<String>,
+    /// Capability gates and grants
+    pub capability_state: CapabilityState,
+    /// Active checkpoint guidance
+    pub guidance: GuidanceManager,
+    /// Actions since the last checkpoint (for count-interval triggers)
+    pub actions_since_checkpoint: u64,
+    /// Keywords that already fired keyword checkpoints
+    pub matched_keywords: Vec<String>,
+    /// Session variables
+    #[serde(default, skip_serializing_if = "SessionVariables::is_empty")]
+    pub variables: SessionVariables,
+    /// Where the session is going (informational)
+    pub destination: String,
+    /// When the snapshot was exported
+    pub exported_at: DateTime<Utc>,
+    /// The last event, `session.handoff_out`
+    pub chain_head: ChainHead,
+    /// The session's TRACE, genesis through `session.handoff_out`
+    pub events: Vec<TRACEEvent>,
+    /// Source signature over [`SessionSnapshot::digest`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<EventSignature>,
+}
+
+impl SessionSnapshot {
+    /// SHA-256 of the canonical JSON of the snapshot without its signature
+    pub fn digest(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("signature");
+        }
+        hex::encode(Sha256::digest(canonical_json(&value).as_bytes()))
+    }
+
+    /// Check the snapshot's internal consistency
+    ///
+    /// The chain must belong to this session, end in `session.handoff_out`
+    /// and match `chain_head`. Chain integrity and signatures are checked by
+    /// the importing resolver.
+    pub fn check_continuity(&self) -> std::result::Result<(), String> {
+        if self.snapshot_version != SNAPSHOT_VERSION {
+            return Err(format!("unsupported snapshot version '{}'", self.snapshot_version));
+        }
+        let last = self.events.last().ok_or("snapshot has no events")?;
+        if let Some(event) = self.events.iter().find(|e| e.session_id != self.session_id) {
+            return Err(format!("event {} belongs to session '{}'", event.event_id, event.session_id));
+        }
+        if last.event_type != EventType::SessionHandoffOut {
+            return Err(format!("chain ends with {}, not session.handoff_out", last.event_type));
+        }
+        if last.sequence != self.chain_head.sequence || last.event_hash != self.chain_head.event_hash {
+            return Err("chain head does not match the last event".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+//! Point-in-time session state
+//!
+//! [`SessionStateSnapshot::replay`] rebuilds what a session looked like
+//! just after one of its TRACE events: capability statuses, the latest
+//! resolution's action decisions, constraints and injected context, and
+//! the checkpoint guidance in effect. Everything is derived from the
+//! chain, so the answer is the same on any resolver holding the trace.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{CapabilityState, CapabilityStatus};
+use crate::error::{CRAError, Result};
+use crate::trace::{ChainVerification, ChainVerifier, EventType, TRACEEvent};
+
+/// A session's state as of one TRACE event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStateSnapshot {
+    /// Session the state belongs to
+    pub session_id: String,
+    /// Sequence of the last event replayed
+    pub at_sequence: u64,
+    /// Timestamp of that event
+    pub at_timestamp: DateTime<Utc>,
+    /// Type of that event
+    pub at_event_type: EventType,
+    /// Whether the session had not ended yet
+    pub is_active: bool,
+    /// Resolutions completed so far
+    pub resolution_count: u64,
+    /// Actions executed so far
+    pub action_count: u64,
+    /// Tracked capability statuses (untracked capabilities are ungated)
+    pub capabilities: BTreeMap<String, CapabilityStatus>,
+    /// The most recent resolution, possibly still in progress
+    pub latest_resolution: Option<ResolutionState>,
+    /// Checkpoints whose guidance is in effect, in injection order
+    pub active_guidance: Vec<String>,
+    /// Verification of the chain up to and including this event
+    pub chain: ChainVerification,
+}
+
+/// A resolution as recorded in the trace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionState {
+    /// Resolution (trace) ID
+    pub resolution_id: String,
+    /// Goal the agent resolved for
+    pub goal: Option<String>,
+    /// Whether `carp.resolution.completed` had been recorded
+    pub completed: bool,
+    /// Overall decision, once completed
+    pub decision: Option<String>,
+    /// Actions the policies allowed
+    pub allowed_actions: Vec<String>,
+    /// Actions the policies denied, with the policy result
+    pub denied_actions: BTreeMap<String, String>,
+    /// Constraint IDs attached to allowed actions
+    pub constraints: Vec<String>,
+    /// Context blocks injected
+    pub contexts: Vec<InjectedContext>,
+}
+
+/// A context block injected into a resolution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InjectedContext {
+    pub context_id: String,
+    pub source_atlas: Option<String>,
+    pub token_estimate: u64,
+}
+
+impl SessionStateSnapshot {
+    /// Replay a session's chain up to and including event `sequence`
+    ///
+    /// Action decisions cover actions evaluated by policy; actions denied
+    /// by a capability gate or strict mode leave no policy event.
+    pub fn replay(session_id: &str, events: &[TRACEEvent], sequence: u64) -> Result<Self> {
+        let end = events.iter().position(|e| e.sequence == sequence).ok_or_else(|| CRAError::InvalidQuery {
+            reason: format!(
+                "session '{}' has no event with sequence {} ({} events)",
+                session_id,
+                sequence,
+                events.len()
+            ),
+        })?;
+        let prefix = &events[..=end];
+        let at = &prefix[end];
+
+        let mut is_active = true;
+        let mut resolution_count = 0;
+        let mut action_count = 0;
+        let mut latest: Option<ResolutionState> = None;
+        let mut guidance: Vec<String> = Vec::new();
+
+        for event in prefix {
+            let payload = &event.payload;
+            let text = |field: &str| payload[field].as_str().map(String::from);
+            match event.event_type {
+                EventType::SessionEnded | EventType::SessionHandoffOut => is_active = false,
+                EventType::CARPRequestReceived => {
+                    latest = Some(ResolutionState {
+                        resolution_id: text("request_id").unwrap_or_default(),
+                        goal: text("goal"),
+                        ..Default::default()
+                    });
+                }
+                EventType::PolicyEvaluated => {
+                    let (Some(resolution), Some(action_id), Some(result)) =
+                        (latest.as_mut(), text("action_id"), text("result"))
+                    else {
+                        continue;
+                    };
+                    if result.starts_with("Allow") || result == "NoMatch" {
+                        if result.starts_with("AllowWithConstraints") {
+                            resolution.constraints.extend(quoted(&result));
+                        }
+                        resolution.allowed_actions.push(action_id);
+                    } else {
+                        resolution.denied_actions.insert(action_id, result);
+                    }
+                }
+                EventType::ContextInjected => {
+                    if let Some(resolution) = latest.as_mut() {
+                        resolution.contexts.push(InjectedContext {
+                            context_id: text("context_id").unwrap_or_default(),
+                            source_atlas: text("source_atlas"),
+                            token_estimate: payload["token_estimate"].as_u64().unwrap_or(0),
+                        });
+                    }
+                }
+                EventType::CARPResolutionCompleted => {
+                    resolution_count += 1;
+                    if let Some(resolution) = latest.as_mut() {
+                        resolution.completed = true;
+                        resolution.decision = text("decision_type");
+                        // Actions narrowed out of the resolution were allowed by policy only
+                        if let Some(excluded) = payload["narrowing"]["excluded_actions"].as_array() {
+                            resolution.allowed_actions.retain(|id| !excluded.iter().any(|e| e == id.as_str()));
+                        }
+                    }
+                }
+                EventType::ActionExecuted => action_count += 1,
+                EventType::CheckpointGuidanceInjected => {
+                    if let Some(checkpoint_id) = text("checkpoint_id") {
+                        guidance.retain(|id| *id != checkpoint_id);
+                        guidance.push(checkpoint_id);
+                    }
+                }
+                EventType::CheckpointGuidanceExpired => {
+                    if let Some(checkpoint_id) = text("checkpoint_id") {
+                        guidance.retain(|id| *id != checkpoint_id);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            session_id: session_id.to_string(),
+            at_sequence: at.sequence,
+            at_timestamp: at.timestamp,
+            at_event_type: at.event_type,
+            is_active,
+            resolution_count,
+            action_count,
+            capabilities: CapabilityState::replay(prefix).statuses().clone(),
+            latest_resolution: latest,
+            active_guidance: guidance,
+            chain: ChainVerifier::verify(prefix),
+        })
+    }
+}
+
+/// Quoted strings in a policy result, e.g. the IDs in `AllowWithConstraints(["a", "b"])`
+fn quoted(result: &str) -> Vec<String> {
+    result.split('"').skip(1).step_by(2).map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint_ids_from_policy_result() {
+        assert_eq!(quoted(r#"AllowWithConstraints(["pii-redaction", "business-hours"])"#), vec!["pii-redaction", "business-hours"]);
+        assert!(quoted("AllowWithConstraints([])").is_empty());
+    }
+}
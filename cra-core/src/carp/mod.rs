@@ -25,11 +25,30 @@ mod resolution;
 mod policy;
 mod resolver;
 mod checkpoint;
+mod guidance;
+mod capability;
+mod handoff;
+mod snapshot;
+mod approval;
+mod report;
+mod history;
+mod token;
+mod external;
+mod batch;
+mod bootstrap;
+mod gc;
+mod observer;
+pub mod template;
 
 pub use request::{CARPRequest, RiskTier};
-pub use resolution::{CARPResolution, Decision, AllowedAction, DeniedAction, Constraint, ConstraintType, ContextBlock};
-pub use policy::{PolicyEvaluator, PolicyResult};
-pub use resolver::Resolver;
+pub use resolution::{CARPResolution, Decision, AllowedAction, DeniedAction, Constraint, ConstraintType, ContextBlock, DegradedEvaluation, Narrowing, NARROWING_POLICY_ID};
+pub use policy::{
+    PolicyEvaluator, PolicyResult, RateLimitCounter,
+    PolicyCoverage, ActionCoverage, STRICT_MODE_POLICY_ID,
+};
+pub use resolver::{Resolver, Session};
+pub use history::{SessionStateSnapshot, ResolutionState, InjectedContext};
+pub use report::{SessionReport, ActionSummary, ActionTally, ContextSummary, CheckpointSummary, CostTotals};
 pub use checkpoint::{
     // Core checkpoint types
     CheckpointType, CheckpointMode, CheckpointConfig, CheckpointEvaluator,
@@ -40,13 +59,37 @@ pub use checkpoint::{
     // Guidance
     GuidanceBlock, GuidanceFormat,
     // Response validation
-    CheckpointResponse, AnswerValue, CheckpointValidator,
+    CheckpointResponse, AnswerValue, CheckpointValidator, CustomAnswerValidatorFn,
     CheckpointValidation, QuestionValidationResult, CheckpointAction,
+    // Custom triggers
+    CustomTriggerEvaluator, CustomTriggerContext, CustomTriggerDecision, CustomTriggerFn,
     // Config types
     SessionStartConfig, SessionEndConfig, KeywordMatchConfig,
     ActionPreConfig, ActionCheckpointConfig, RiskThresholdConfig,
     TimeIntervalConfig, CountIntervalConfig, MatchMode,
 };
+pub use guidance::{GuidanceManager, ActiveGuidance, GuidanceRemoval, GUIDANCE_BLOCK_ID};
+pub use capability::{CapabilityState, CapabilityStatus, CapabilityChangeSource, CapabilityTransition};
+pub use token::{CapabilityToken, CAPABILITY_TOKEN_POLICY_ID};
+pub use observer::ResolverObserver;
+pub use gc::{GcPolicy, GcStats};
+pub use bootstrap::{
+    check_protocol_version, BootstrapContext, BootstrapRequest, BootstrapResponse, GovernanceRule, PolicySummary,
+    SessionConstraint, BOOTSTRAP_PROTOCOL_VERSION,
+};
+pub use batch::{BatchItem, BatchResolveResponse, MAX_BATCH_SIZE};
+#[cfg(feature = "async-runtime")]
+pub(crate) use batch::validate_batch;
+pub use external::{ExternalPolicy, PolicyDecisionFn, DEFAULT_EXTERNAL_TIMEOUT_MS};
+pub use handoff::{SessionSnapshot, ChainHead, SNAPSHOT_VERSION};
+pub use approval::{
+    ApprovalManager, ApprovalRequest, ApprovalDecision, ApprovalStatus, ApprovalRecord, DeliveryStatus,
+    EscalationChannel, WebhookChannel, SlackChannel, HttpPostFn, SLACK_APPROVE_ACTION, SLACK_REJECT_ACTION,
+};
+pub use snapshot::{
+    ResolverState, SessionSnapshotState, TraceChainSnapshot,
+    RESOLVER_SNAPSHOT_MAGIC, RESOLVER_SNAPSHOT_VERSION,
+};
 
 /// CARP protocol version
 pub const VERSION: &str = "1.0";
@@ -85,12 +128,16 @@ mod tests {
                 description: Some("Retrieve a ticket".to_string()),
                 parameters_schema: json!({}),
                 risk_tier: "low".to_string(),
+                executor: None,
             }],
             denied_actions: vec![],
             context_blocks: vec![],
             constraints: vec![],
             ttl_seconds: 300,
             timestamp: chrono::Utc::now(),
+            atlas_versions: Default::default(),
+            narrowing: None,
+            degraded_evaluations: vec![],
         };
 
         let json = serde_json::to_string(&resolution).unwrap();
@@ -0,0 +1,236 @@
+//! Resolver observers
+//!
+//! A [`ResolverObserver`] hears about session lifecycle, resolutions,
+//! executions and policy denials as the synchronous [`Resolver`] handles
+//! them, for embedders wiring up metrics or logging without the async
+//! runtime:
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use cra_core::carp::{CARPRequest, CARPResolution, ResolverObserver};
+//! use cra_core::Resolver;
+//!
+//! #[derive(Default)]
+//! struct CountResolutions(AtomicUsize);
+//!
+//! impl ResolverObserver for CountResolutions {
+//!     fn on_resolution(&self, _request: &CARPRequest, _resolution: &CARPResolution) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(CountResolutions::default());
+//! let mut resolver = Resolver::new();
+//! resolver.add_observer(counter.clone());
+//! ```
+//!
+//! ## Contract
+//!
+//! Callbacks run inline on the caller's thread, after the TRACE event for
+//! what they report has been recorded, and while the resolver is mutably
+//! borrowed. They must return quickly and must not block: hand anything
+//! slow (network, disk) to a channel or queue. They can't call back into
+//! the resolver, and a panic in one propagates to the resolver's caller.
+//!
+//! [`Resolver`]: super::Resolver
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::request::CARPRequest;
+use super::resolution::CARPResolution;
+use super::resolver::Session;
+use crate::error::Result;
+
+/// Receives resolver activity; every callback defaults to doing nothing
+///
+/// See the [module docs](self) for the calling contract.
+pub trait ResolverObserver: Send + Sync {
+    /// A session was created
+    fn on_session_started(&self, _session: &Session) {}
+
+    /// A session ended; `reason` is `completed`, `parent_ended` or similar
+    fn on_session_ended(&self, _session: &Session, _reason: &str) {}
+
+    /// A resolution completed
+    fn on_resolution(&self, _request: &CARPRequest, _resolution: &CARPResolution) {}
+
+    /// An action was executed, or failed to execute
+    fn on_execution(&self, _session_id: &str, _action_id: &str, _result: &Result<Value>) {}
+
+    /// A policy denied an action, in a resolution or at execution
+    fn on_policy_denied(&self, _session_id: &str, _action_id: &str, _policy_id: &str, _reason: &str) {}
+}
+
+impl<T: ResolverObserver + ?Sized> ResolverObserver for Arc<T> {
+    fn on_session_started(&self, session: &Session) {
+        (**self).on_session_started(session)
+    }
+
+    fn on_session_ended(&self, session: &Session, reason: &str) {
+        (**self).on_session_ended(session, reason)
+    }
+
+    fn on_resolution(&self, request: &CARPRequest, resolution: &CARPResolution) {
+        (**self).on_resolution(request, resolution)
+    }
+
+    fn on_execution(&self, session_id: &str, action_id: &str, result: &Result<Value>) {
+        (**self).on_execution(session_id, action_id, result)
+    }
+
+    fn on_policy_denied(&self, session_id: &str, action_id: &str, policy_id: &str, reason: &str) {
+        (**self).on_policy_denied(session_id, action_id, policy_id, reason)
+    }
+}
+
+/// The observers registered with a resolver
+#[derive(Default)]
+pub(crate) struct Observers {
+    observers: Vec<Arc<dyn ResolverObserver>>,
+}
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observers")
+            .field("count", &self.observers.len())
+            .finish()
+    }
+}
+
+impl Observers {
+    pub(crate) fn add(&mut self, observer: Arc<dyn ResolverObserver>) {
+        self.observers.push(observer);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub(crate) fn session_started(&self, session: &Session) {
+        self.observers.iter().for_each(|o| o.on_session_started(session));
+    }
+
+    pub(crate) fn session_ended(&self, session: &Session, reason: &str) {
+        self.observers.iter().for_each(|o| o.on_session_ended(session, reason));
+    }
+
+    /// Report a resolution, then each action it denied
+    pub(crate) fn resolved(&self, request: &CARPRequest, resolution: &CARPResolution) {
+        for observer in &self.observers {
+            observer.on_resolution(request, resolution);
+            for denied in &resolution.denied_actions {
+                observer.on_policy_denied(&request.session_id, &denied.action_id, &denied.policy_id, &denied.reason);
+            }
+        }
+    }
+
+    /// Report an execution, and its denial if a policy refused it
+    pub(crate) fn executed(&self, session_id: &str, action_id: &str, result: &Result<Value>) {
+        for observer in &self.observers {
+            observer.on_execution(session_id, action_id, result);
+            if let Err(crate::CRAError::ActionDenied { policy_id, reason }) = result {
+                observer.on_policy_denied(session_id, action_id, policy_id, reason);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtlasManifest, Resolver};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Recorder {
+        fn push(&self, entry: String) {
+            self.0.lock().unwrap().push(entry);
+        }
+
+        fn entries(&self) -> Vec<String> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl ResolverObserver for Recorder {
+        fn on_session_started(&self, session: &Session) {
+            self.push(format!("started {}", session.agent_id));
+        }
+
+        fn on_session_ended(&self, _session: &Session, reason: &str) {
+            self.push(format!("ended {}", reason));
+        }
+
+        fn on_resolution(&self, _request: &CARPRequest, resolution: &CARPResolution) {
+            self.push(format!("resolved {}", resolution.allowed_actions.len()));
+        }
+
+        fn on_execution(&self, _session_id: &str, action_id: &str, result: &Result<Value>) {
+            self.push(format!("executed {} {}", action_id, result.is_ok()));
+        }
+
+        fn on_policy_denied(&self, _session_id: &str, action_id: &str, policy_id: &str, _reason: &str) {
+            self.push(format!("denied {} by {}", action_id, policy_id));
+        }
+    }
+
+    fn atlas() -> AtlasManifest {
+        serde_json::from_value(json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.observer",
+            "version": "1.0.0",
+            "name": "Observer Atlas",
+            "description": "Atlas for observer tests",
+            "domains": ["test"],
+            "capabilities": [],
+            "policies": [
+                {"policy_id": "no-delete", "type": "deny", "actions": ["*.delete"], "reason": "No deletes"}
+            ],
+            "actions": [
+                {"action_id": "file.read", "name": "Read", "description": "Read a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "low"},
+                {"action_id": "file.delete", "name": "Delete", "description": "Delete a file",
+                 "parameters_schema": {"type": "object"}, "risk_tier": "high"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_observer_sees_session_activity() {
+        let recorder = Arc::new(Recorder::default());
+        let mut resolver = Resolver::new().with_observer(recorder.clone());
+        resolver.load_atlas(atlas()).unwrap();
+
+        let session_id = resolver.create_session("agent-1", "Tidy files").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "agent-1".to_string(), "read and delete files".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        resolver
+            .execute(&session_id, &resolution.trace_id, "file.read", json!({}))
+            .unwrap();
+        assert!(resolver
+            .execute(&session_id, &resolution.trace_id, "file.delete", json!({}))
+            .is_err());
+        resolver.end_session(&session_id).unwrap();
+
+        assert_eq!(
+            recorder.entries(),
+            vec![
+                "started agent-1",
+                "resolved 1",
+                "denied file.delete by no-delete",
+                "executed file.read true",
+                "executed file.delete false",
+                "denied file.delete by no-delete",
+                "ended completed",
+            ]
+        );
+        assert_eq!(resolver.observer_count(), 1);
+    }
+}
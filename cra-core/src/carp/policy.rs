@@ -7,12 +7,17 @@
 //! 4. Allow policies (explicit allowance)
 //!
 //! If no policy matches, the default behavior is to allow the action.
+//!
+//! Actions that pass are then put to any `external` policies (see
+//! [`super::external`]), within the evaluator's optional latency budget.
 
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::clock::Instant;
+use crate::atlas::namespace::pattern_matches;
 use crate::atlas::{AtlasPolicy, PolicyType};
 
 /// Result of evaluating a policy against an action
@@ -52,6 +57,9 @@ pub struct PolicyEvaluator {
 
     /// Rate limit state (action_id -> (count, window_start))
     rate_limit_state: HashMap<String, RateLimitState>,
+
+    /// Longest one resolution or execution waits for external decisions
+    latency_budget: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,47 +70,102 @@ struct RateLimitState {
     window_seconds: u64,
 }
 
-/// Check if an action matches any of the policy patterns
-fn matches_action(patterns: &[String], action_id: &str) -> bool {
-    patterns.iter().any(|pattern| pattern_matches(pattern, action_id))
-}
+/// Policy ID reported for actions denied by the resolver's strict mode
+pub const STRICT_MODE_POLICY_ID: &str = "strict_mode";
 
-/// Match a pattern against an action ID
-///
-/// Supports:
-/// - Exact match: "ticket.get"
-/// - Wildcard suffix: "ticket.*"
-/// - Wildcard prefix: "*.delete"
-/// - Full wildcard: "*"
-fn pattern_matches(pattern: &str, action_id: &str) -> bool {
-    if pattern == "*" {
-        return true;
-    }
+/// Which policies and capabilities apply to one action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionCoverage {
+    /// The action
+    pub action_id: String,
+    /// Atlas that defines the action
+    pub atlas_id: String,
+    /// Policies whose patterns match the action
+    pub policies: Vec<String>,
+    /// Capabilities that include the action
+    pub capabilities: Vec<String>,
+}
 
-    if pattern == action_id {
-        return true;
-    }
+/// Least-privilege report from [`Resolver::policy_coverage`](super::Resolver::policy_coverage)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyCoverage {
+    /// Whether the resolver denies actions no capability grants
+    pub strict_mode: bool,
+    /// Every action of every loaded atlas, sorted by action ID
+    pub actions: Vec<ActionCoverage>,
+    /// Actions no policy applies to
+    pub uncovered_actions: Vec<String>,
+    /// Actions no capability includes
+    pub ungranted_actions: Vec<String>,
+    /// Actions allowed only because nothing matched them
+    ///
+    /// Empty in strict mode, where such actions are either granted by a
+    /// capability or denied.
+    pub default_allowed_actions: Vec<String>,
+}
 
-    if let Some(prefix) = pattern.strip_suffix(".*") {
-        return action_id.starts_with(prefix) && action_id[prefix.len()..].starts_with('.');
+impl PolicyCoverage {
+    /// Whether every allowed action is allowed explicitly
+    pub fn is_least_privilege(&self) -> bool {
+        self.default_allowed_actions.is_empty()
     }
+}
 
-    if let Some(suffix) = pattern.strip_prefix("*.") {
-        return action_id.ends_with(suffix) && action_id[..action_id.len() - suffix.len()].ends_with('.');
-    }
+/// Rate limit counter in portable form, for resolver snapshots
+///
+/// The window start is stored relative to the moment the counter was
+/// exported, since `Instant`s do not survive a process restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitCounter {
+    /// Counter key (`policy_id:action_id`)
+    pub key: String,
+    /// Calls in the current window
+    pub count: u64,
+    /// Milliseconds between the window start and the export
+    pub window_elapsed_ms: u64,
+    /// Calls allowed per window
+    pub max_calls: u64,
+    /// Window length in seconds
+    pub window_seconds: u64,
+}
 
-    false
+/// Check if an action matches any of the policy patterns
+fn matches_action(patterns: &[String], action_id: &str) -> bool {
+    patterns.iter().any(|pattern| pattern_matches(pattern, action_id))
 }
 
+
 impl PolicyEvaluator {
     /// Create a new policy evaluator
     pub fn new() -> Self {
         Self {
             policies: Vec::new(),
             rate_limit_state: HashMap::new(),
+            latency_budget: None,
         }
     }
 
+    /// Cap the total time external policy engines may take per resolution
+    /// or execution
+    ///
+    /// Calls are given at most what is left of the budget; once it is
+    /// spent, remaining external policies time out without being called
+    /// and fall back to their `fail_open` setting.
+    pub fn with_latency_budget(mut self, budget: Duration) -> Self {
+        self.latency_budget = Some(budget);
+        self
+    }
+
+    /// Set or clear the latency budget
+    pub fn set_latency_budget(&mut self, budget: Option<Duration>) {
+        self.latency_budget = budget;
+    }
+
+    /// The latency budget, if any
+    pub fn latency_budget(&self) -> Option<Duration> {
+        self.latency_budget
+    }
+
     /// Add policies from an atlas
     pub fn add_policies(&mut self, policies: Vec<AtlasPolicy>) {
         self.policies.extend(policies);
@@ -169,9 +232,11 @@ impl PolicyEvaluator {
     ///
     /// Supports:
     /// - Exact match: "ticket.get"
-    /// - Wildcard suffix: "ticket.*"
-    /// - Wildcard prefix: "*.delete"
+    /// - One level: "ticket.*", "*.delete"
+    /// - Any depth: "ticket.**", "**.delete"
     /// - Full wildcard: "*"
+    ///
+    /// See [`crate::atlas::namespace`].
     pub fn pattern_matches(&self, pattern: &str, action_id: &str) -> bool {
         pattern_matches(pattern, action_id)
     }
@@ -218,6 +283,26 @@ impl PolicyEvaluator {
         None
     }
 
+    /// IDs of the policies whose patterns match an action, of any type but `content`
+    pub fn matching_policies(&self, action_id: &str) -> Vec<&str> {
+        self.policies
+            .iter()
+            .filter(|p| p.policy_type != PolicyType::Content && matches_action(&p.actions, action_id))
+            .map(|p| p.policy_id.as_str())
+            .collect()
+    }
+
+    /// `external` policies whose patterns match an action
+    ///
+    /// [`evaluate`](Self::evaluate) ignores these; the resolver asks their
+    /// engines about actions the other policies allow.
+    pub fn external_policies(&self, action_id: &str) -> Vec<&AtlasPolicy> {
+        self.policies
+            .iter()
+            .filter(|p| p.policy_type == PolicyType::External && matches_action(&p.actions, action_id))
+            .collect()
+    }
+
     /// Reset rate limit state for testing or session end
     pub fn reset_rate_limits(&mut self) {
         self.rate_limit_state.clear();
@@ -228,6 +313,41 @@ impl PolicyEvaluator {
         let key = format!("{}:{}", policy_id, action_id);
         self.rate_limit_state.get(&key).map(|s| s.count)
     }
+
+    /// Export the rate limit counters, sorted by key
+    pub fn rate_limit_counters(&self) -> Vec<RateLimitCounter> {
+        let mut counters: Vec<RateLimitCounter> = self
+            .rate_limit_state
+            .iter()
+            .map(|(key, state)| RateLimitCounter {
+                key: key.clone(),
+                count: state.count,
+                window_elapsed_ms: state.window_start.elapsed().as_millis() as u64,
+                max_calls: state.max_calls,
+                window_seconds: state.window_seconds,
+            })
+            .collect();
+        counters.sort_by(|a, b| a.key.cmp(&b.key));
+        counters
+    }
+
+    /// Replace the rate limit counters with exported ones
+    pub fn restore_rate_limit_counters(&mut self, counters: Vec<RateLimitCounter>) {
+        let now = Instant::now();
+        self.rate_limit_state = counters
+            .into_iter()
+            .map(|counter| {
+                let elapsed = Duration::from_millis(counter.window_elapsed_ms);
+                let state = RateLimitState {
+                    count: counter.count,
+                    window_start: now.checked_sub(elapsed).unwrap_or(now),
+                    max_calls: counter.max_calls,
+                    window_seconds: counter.window_seconds,
+                };
+                (counter.key, state)
+            })
+            .collect();
+    }
 }
 
 impl Default for PolicyEvaluator {
@@ -356,6 +476,11 @@ mod tests {
         assert!(evaluator.pattern_matches("ticket.*", "ticket.get"));
         assert!(evaluator.pattern_matches("ticket.*", "ticket.delete"));
         assert!(!evaluator.pattern_matches("ticket.*", "user.get"));
+        assert!(!evaluator.pattern_matches("ticket.*", "ticket.comment.add"));
+
+        // Any depth
+        assert!(evaluator.pattern_matches("ticket.**", "ticket.comment.add"));
+        assert!(evaluator.pattern_matches("**.delete", "ticket.comment.delete"));
 
         // Wildcard prefix
         assert!(evaluator.pattern_matches("*.delete", "ticket.delete"));
@@ -0,0 +1,312 @@
+//! Per-session audit reports
+//!
+//! A [`SessionReport`] summarizes one session from its TRACE events: how
+//! long it ran, which actions were attempted, allowed and denied (and by
+//! which policy), what context was injected, how checkpoints went, whether
+//! the hash chain verifies and what the session cost. Reports render as
+//! JSON (via serde) or Markdown ([`SessionReport::to_markdown`]).
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Session;
+use crate::trace::{ChainVerification, EventType, TRACEEvent};
+
+/// Audit summary of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    /// Session the report covers
+    pub session_id: String,
+    /// Agent that owns the session
+    pub agent_id: String,
+    /// Initial goal of the session
+    pub goal: String,
+    /// When the session was created
+    pub started_at: DateTime<Utc>,
+    /// When the session ended (if it has)
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Time from start to end, or to report generation for active sessions
+    pub duration_ms: i64,
+    /// Whether the session is still active
+    pub is_active: bool,
+    /// Whether the session is quarantined after an integrity violation
+    pub quarantined: bool,
+    /// Completed resolutions
+    pub resolutions: usize,
+    /// Action outcomes
+    pub actions: ActionSummary,
+    /// Injected context
+    pub contexts: ContextSummary,
+    /// Checkpoint outcomes
+    pub checkpoints: CheckpointSummary,
+    /// Hash chain verification result
+    pub chain: ChainVerification,
+    /// Usage reported in event payloads
+    pub cost: CostTotals,
+    /// Number of TRACE events in the session
+    pub event_count: usize,
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Action outcomes in a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionSummary {
+    /// Execution requests (`action.requested`)
+    pub attempted: usize,
+    /// Executions that completed (`action.executed`)
+    pub allowed: usize,
+    /// Executions refused by policy (`action.denied`)
+    pub denied: usize,
+    /// Executions that failed (`action.failed`)
+    pub failed: usize,
+    /// Outcomes per action ID
+    pub by_action: BTreeMap<String, ActionTally>,
+    /// Denials per policy ID
+    pub denied_by_policy: BTreeMap<String, usize>,
+}
+
+/// Outcomes of one action
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionTally {
+    pub attempted: usize,
+    pub allowed: usize,
+    pub denied: usize,
+    pub failed: usize,
+}
+
+/// Context injected into a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextSummary {
+    /// Context blocks injected, counting repeats
+    pub injected: usize,
+    /// Sum of the blocks' token estimates
+    pub token_estimate: u64,
+    /// Injections per context block ID
+    pub by_block: BTreeMap<String, usize>,
+}
+
+/// Checkpoint outcomes in a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub triggered: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// IDs of checkpoints that failed, in order
+    pub failed_ids: Vec<String>,
+}
+
+/// Token and cost totals
+///
+/// Summed from `input_tokens`, `output_tokens` and `cost_usd` fields in
+/// event payloads, such as usage events recorded by agent wrappers. The
+/// resolver itself doesn't meter model usage, so these are zero unless
+/// something reports it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl SessionReport {
+    /// Build a report from a session, its events and its chain verification
+    pub fn build(
+        session: &Session,
+        events: &[TRACEEvent],
+        chain: ChainVerification,
+        quarantined: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut actions = ActionSummary::default();
+        let mut contexts = ContextSummary::default();
+        let mut checkpoints = CheckpointSummary::default();
+        let mut cost = CostTotals::default();
+        let mut resolutions = 0;
+
+        for event in events {
+            let payload = &event.payload;
+            let text = |field: &str| payload[field].as_str().unwrap_or("unknown").to_string();
+            match event.event_type {
+                EventType::CARPResolutionCompleted => resolutions += 1,
+                EventType::ActionRequested => {
+                    actions.attempted += 1;
+                    actions.by_action.entry(text("action_id")).or_default().attempted += 1;
+                }
+                EventType::ActionExecuted => {
+                    actions.allowed += 1;
+                    actions.by_action.entry(text("action_id")).or_default().allowed += 1;
+                }
+                EventType::ActionDenied => {
+                    actions.denied += 1;
+                    actions.by_action.entry(text("action_id")).or_default().denied += 1;
+                    *actions.denied_by_policy.entry(text("policy_id")).or_default() += 1;
+                }
+                EventType::ActionFailed => {
+                    actions.failed += 1;
+                    actions.by_action.entry(text("action_id")).or_default().failed += 1;
+                }
+                EventType::ContextInjected => {
+                    contexts.injected += 1;
+                    contexts.token_estimate += payload["token_estimate"].as_u64().unwrap_or(0);
+                    *contexts.by_block.entry(text("context_id")).or_default() += 1;
+                }
+                EventType::CheckpointTriggered => checkpoints.triggered += 1,
+                EventType::CheckpointPassed => checkpoints.passed += 1,
+                EventType::CheckpointFailed => {
+                    checkpoints.failed += 1;
+                    checkpoints.failed_ids.push(text("checkpoint_id"));
+                }
+                EventType::CheckpointSkipped => checkpoints.skipped += 1,
+                _ => {}
+            }
+
+            cost.input_tokens += payload["input_tokens"].as_u64().unwrap_or(0);
+            cost.output_tokens += payload["output_tokens"].as_u64().unwrap_or(0);
+            cost.cost_usd += payload["cost_usd"].as_f64().unwrap_or(0.0);
+        }
+
+        let end = session.ended_at.unwrap_or(now);
+        Self {
+            session_id: session.session_id.clone(),
+            agent_id: session.agent_id.clone(),
+            goal: session.goal.clone(),
+            started_at: session.created_at,
+            ended_at: session.ended_at,
+            duration_ms: (end - session.created_at).num_milliseconds().max(0),
+            is_active: session.is_active,
+            quarantined,
+            resolutions,
+            actions,
+            contexts,
+            checkpoints,
+            chain,
+            cost,
+            event_count: events.len(),
+            generated_at: now,
+        }
+    }
+
+    /// Render the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let status = match (self.quarantined, self.is_active) {
+            (true, _) => "quarantined",
+            (false, true) => "active",
+            (false, false) => "ended",
+        };
+
+        // Writing to a String can't fail
+        let _ = writeln!(out, "# Session report: {}\n", self.session_id);
+        let _ = writeln!(out, "- **Agent:** {}", self.agent_id);
+        let _ = writeln!(out, "- **Goal:** {}", self.goal);
+        let _ = writeln!(out, "- **Status:** {}", status);
+        let _ = writeln!(out, "- **Started:** {}", self.started_at.to_rfc3339());
+        if let Some(ended_at) = self.ended_at {
+            let _ = writeln!(out, "- **Ended:** {}", ended_at.to_rfc3339());
+        }
+        let _ = writeln!(out, "- **Duration:** {} ms", self.duration_ms);
+        let _ = writeln!(out, "- **Resolutions:** {}", self.resolutions);
+        let _ = writeln!(out, "- **Events:** {}", self.event_count);
+
+        let a = &self.actions;
+        let _ = writeln!(out, "\n## Actions\n");
+        let _ = writeln!(
+            out,
+            "{} attempted, {} allowed, {} denied, {} failed\n",
+            a.attempted, a.allowed, a.denied, a.failed
+        );
+        if !a.by_action.is_empty() {
+            let _ = writeln!(out, "| Action | Attempted | Allowed | Denied | Failed |");
+            let _ = writeln!(out, "|---|---|---|---|---|");
+            for (action_id, t) in &a.by_action {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    action_id, t.attempted, t.allowed, t.denied, t.failed
+                );
+            }
+        }
+        if !a.denied_by_policy.is_empty() {
+            let _ = writeln!(out, "\n| Policy | Denials |");
+            let _ = writeln!(out, "|---|---|");
+            for (policy_id, count) in &a.denied_by_policy {
+                let _ = writeln!(out, "| {} | {} |", policy_id, count);
+            }
+        }
+
+        let c = &self.contexts;
+        let _ = writeln!(out, "\n## Context\n");
+        let _ = writeln!(
+            out,
+            "{} blocks injected (~{} tokens)",
+            c.injected, c.token_estimate
+        );
+        for (block_id, count) in &c.by_block {
+            let _ = writeln!(out, "- {} ({}x)", block_id, count);
+        }
+
+        let k = &self.checkpoints;
+        let _ = writeln!(out, "\n## Checkpoints\n");
+        let _ = writeln!(
+            out,
+            "{} triggered, {} passed, {} failed, {} skipped",
+            k.triggered, k.passed, k.failed, k.skipped
+        );
+        if !k.failed_ids.is_empty() {
+            let _ = writeln!(out, "\nFailed: {}", k.failed_ids.join(", "));
+        }
+
+        let _ = writeln!(out, "\n## Chain\n");
+        if self.chain.is_valid {
+            let _ = writeln!(out, "Valid ({} events)", self.chain.event_count);
+        } else {
+            let _ = writeln!(
+                out,
+                "**Invalid** at event {}: {}",
+                self.chain.first_invalid_index.map_or("?".to_string(), |i| i.to_string()),
+                self.chain.error_message.as_deref().unwrap_or("unknown error")
+            );
+        }
+
+        let _ = writeln!(out, "\n## Cost\n");
+        let _ = writeln!(
+            out,
+            "{} input tokens, {} output tokens, ${:.4}",
+            self.cost.input_tokens, self.cost.output_tokens, self.cost.cost_usd
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::ChainVerifier;
+    use serde_json::json;
+
+    #[test]
+    fn test_usage_totals() {
+        let session = Session::new("s1".to_string(), "agent".to_string(), "goal".to_string());
+        let usage = |input: u64, output: u64, cost: f64| {
+            TRACEEvent::new(
+                "s1".to_string(),
+                "t1".to_string(),
+                EventType::ActionExecuted,
+                json!({"action_id": "llm.call", "input_tokens": input, "output_tokens": output, "cost_usd": cost}),
+            )
+        };
+        let events = vec![usage(100, 20, 0.25), usage(50, 10, 0.5)];
+        let chain = ChainVerifier::verify(&events);
+        let report = SessionReport::build(&session, &events, chain, false, session.created_at);
+
+        assert_eq!((report.cost.input_tokens, report.cost.output_tokens), (150, 30));
+        assert!((report.cost.cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(report.duration_ms, 0);
+        assert!(report.to_markdown().contains("150 input tokens, 30 output tokens, $0.7500"));
+    }
+}
@@ -34,9 +34,18 @@ pub struct CARPRequest {
     pub requested_capabilities: Option<Vec<String>>,
 
     /// Optional specific actions the agent wants to use
+    ///
+    /// Entries may be action IDs or namespace patterns (`ticket.*`). When
+    /// set, the resolution only allows actions matching one of them.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub requested_actions: Option<Vec<String>>,
 
+    /// Optional highest risk tier the agent wants to be allowed
+    ///
+    /// When set, the resolution only allows actions at or below this tier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_risk_tier: Option<RiskTier>,
+
     /// Optional metadata attached to the request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Value>,
@@ -57,8 +66,9 @@ impl CARPRequest {
             context_hints: None,
             requested_capabilities: None,
             requested_actions: None,
+            max_risk_tier: None,
             metadata: None,
-            timestamp: Utc::now(),
+            timestamp: crate::clock::now(),
         }
     }
 
@@ -126,6 +136,12 @@ impl CARPRequestBuilder {
         self
     }
 
+    /// Set the highest risk tier to allow
+    pub fn max_risk_tier(mut self, tier: RiskTier) -> Self {
+        self.request.max_risk_tier = Some(tier);
+        self
+    }
+
     /// Add metadata
     pub fn metadata(mut self, metadata: Value) -> Self {
         self.request.metadata = Some(metadata);
@@ -227,9 +243,11 @@ mod tests {
         .risk_tier(RiskTier::High)
         .context_hints(vec!["support".to_string()])
         .requested_capabilities(vec!["ticket.read".to_string()])
+        .max_risk_tier(RiskTier::Medium)
         .build();
 
         assert_eq!(request.risk_tier, Some(RiskTier::High));
+        assert_eq!(request.max_risk_tier, Some(RiskTier::Medium));
         assert_eq!(
             request.context_hints,
             Some(vec!["support".to_string()])
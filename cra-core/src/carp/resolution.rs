@@ -1,10 +1,12 @@
 //! CARP Resolution types
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::VERSION;
+use super::{RiskTier, VERSION};
 
 /// A CARP resolution containing what the agent is allowed to do
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +40,25 @@ pub struct CARPResolution {
 
     /// When this resolution was created
     pub timestamp: DateTime<Utc>,
+
+    /// Versions of the atlases loaded when this resolution was made, by atlas ID
+    ///
+    /// Executing against the resolution fails once any of them is unloaded
+    /// or replaced by another version.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub atlas_versions: BTreeMap<String, String>,
+
+    /// How the request's `requested_actions` and `max_risk_tier` narrowed
+    /// the allowed actions, if it set either
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narrowing: Option<Narrowing>,
+
+    /// Policies decided by fallback because their engine timed out
+    ///
+    /// Each action listed here was allowed or denied by the policy's
+    /// `fail_open` setting rather than by the engine.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_evaluations: Vec<DegradedEvaluation>,
 }
 
 impl CARPResolution {
@@ -49,7 +70,7 @@ impl CARPResolution {
     /// Check if the resolution has expired
     pub fn is_expired(&self) -> bool {
         let expiry = self.timestamp + chrono::Duration::seconds(self.ttl_seconds as i64);
-        Utc::now() > expiry
+        crate::clock::now() > expiry
     }
 
     /// Get the expiry time
@@ -67,6 +88,11 @@ impl CARPResolution {
         self.allowed_actions.iter().find(|a| a.action_id == action_id)
     }
 
+    /// Whether any policy decision was a timeout fallback
+    pub fn is_degraded(&self) -> bool {
+        !self.degraded_evaluations.is_empty()
+    }
+
     /// Get the denial reason for a specific action
     pub fn get_denial_reason(&self, action_id: &str) -> Option<&str> {
         self.denied_actions
@@ -131,7 +157,10 @@ impl CARPResolutionBuilder {
                 context_blocks: vec![],
                 constraints: vec![],
                 ttl_seconds: 300, // 5 minutes default
-                timestamp: Utc::now(),
+                timestamp: crate::clock::now(),
+                atlas_versions: BTreeMap::new(),
+                narrowing: None,
+                degraded_evaluations: vec![],
             },
         }
     }
@@ -191,11 +220,77 @@ impl CARPResolutionBuilder {
         self
     }
 
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.resolution.timestamp = timestamp;
+        self
+    }
+
+    pub fn atlas_versions(mut self, versions: BTreeMap<String, String>) -> Self {
+        self.resolution.atlas_versions = versions;
+        self
+    }
+
+    pub fn narrowing(mut self, narrowing: Narrowing) -> Self {
+        self.resolution.narrowing = Some(narrowing);
+        self
+    }
+
+    pub fn degraded_evaluations(mut self, degraded: Vec<DegradedEvaluation>) -> Self {
+        self.resolution.degraded_evaluations = degraded;
+        self
+    }
+
     pub fn build(self) -> CARPResolution {
         self.resolution
     }
 }
 
+/// A policy decision made by fallback after its engine timed out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedEvaluation {
+    /// Action the decision was for
+    pub action_id: String,
+    /// Policy whose engine timed out
+    pub policy_id: String,
+    /// `policy_timeout` or `latency_budget`
+    pub cause: String,
+    /// `allow` (fail-open) or `deny` (fail-closed)
+    pub fallback: String,
+}
+
+/// Policy ID reported for actions executed outside a resolution's narrowing
+pub const NARROWING_POLICY_ID: &str = "narrowing";
+
+/// Least-privilege narrowing applied to a resolution
+///
+/// Actions that policy allowed but the request didn't ask for are left
+/// out of `allowed_actions` and listed here instead; executing them
+/// against the resolution is denied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Narrowing {
+    /// Action IDs or patterns the request asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_actions: Option<Vec<String>>,
+    /// Highest risk tier the request asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_risk_tier: Option<RiskTier>,
+    /// Allowed actions left out of the resolution
+    pub excluded_actions: Vec<String>,
+}
+
+impl Narrowing {
+    /// Whether the request's narrowing admits an action at a risk tier
+    pub fn admits(&self, action_id: &str, risk_tier: &str) -> bool {
+        let requested = self.requested_actions.as_ref().is_none_or(|patterns| {
+            patterns.iter().any(|p| crate::atlas::namespace::pattern_matches(p, action_id))
+        });
+        // Unknown tiers are treated as critical
+        let level = risk_tier.parse::<RiskTier>().map_or(RiskTier::Critical.level(), |t| t.level());
+        let within_tier = self.max_risk_tier.is_none_or(|max| level <= max.level());
+        requested && within_tier
+    }
+}
+
 /// Decision outcome for a CARP resolution
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -210,12 +305,14 @@ pub enum Decision {
     AllowWithConstraints,
     /// Actions require human approval before execution
     RequiresApproval,
+    /// The session is quarantined after a TRACE integrity violation
+    Quarantined,
 }
 
 impl Decision {
     /// Check if this decision permits any action
     pub fn permits_action(&self) -> bool {
-        !matches!(self, Decision::Deny)
+        !matches!(self, Decision::Deny | Decision::Quarantined)
     }
 
     /// Check if this decision requires human intervention
@@ -232,6 +329,7 @@ impl std::fmt::Display for Decision {
             Decision::Partial => write!(f, "partial"),
             Decision::AllowWithConstraints => write!(f, "allow_with_constraints"),
             Decision::RequiresApproval => write!(f, "requires_approval"),
+            Decision::Quarantined => write!(f, "quarantined"),
         }
     }
 }
@@ -254,6 +352,10 @@ pub struct AllowedAction {
 
     /// Risk tier of this action
     pub risk_tier: String,
+
+    /// Executor identifier, with session variables substituted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor: Option<String>,
 }
 
 impl AllowedAction {
@@ -265,6 +367,7 @@ impl AllowedAction {
             description: None,
             parameters_schema,
             risk_tier: "low".to_string(),
+            executor: None,
         }
     }
 
@@ -335,6 +438,13 @@ pub struct ContextBlock {
 
     /// Source atlas that provided this context
     pub source_atlas: String,
+
+    /// Content address of `content` (`sha256:<hex>`)
+    ///
+    /// When the resolver references context instead of inlining it,
+    /// `content` is empty and this is the key into its content store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
 impl ContextBlock {
@@ -347,6 +457,7 @@ impl ContextBlock {
             priority: 0,
             content_type: "text/plain".to_string(),
             source_atlas: String::new(),
+            content_hash: None,
         }
     }
 
@@ -367,6 +478,11 @@ impl ContextBlock {
         self.source_atlas = atlas;
         self
     }
+
+    /// Whether the content was left out in favour of `content_hash`
+    pub fn is_reference(&self) -> bool {
+        self.content.is_empty() && self.content_hash.is_some()
+    }
 }
 
 /// A constraint on agent behavior
@@ -451,6 +567,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "minimal"))] // needs a moving clock
     fn test_resolution_expiry() {
         let resolution = CARPResolution::builder("session-1".to_string())
             .ttl_seconds(1)
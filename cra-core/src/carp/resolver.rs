@@ -7,28 +7,61 @@
 //! - Executes actions and tracks results
 //! - Emits TRACE events for all operations
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use uuid::Uuid;
 
-use crate::atlas::{AtlasAction, AtlasManifest};
-use crate::context::{ContextRegistry, ContextMatcher, LoadedContext, ContextSource};
+use crate::atlas::{AtlasAction, AtlasManifest, AtlasPolicy, ContentPolicy, InjectMode, PolicyType};
+use crate::cache::{hash_request, IdempotencyCache, IdempotencyConfig};
+use crate::clock::{Clock, GlobalClock, Instant};
+use crate::context::{ContentStore, ContextRegistry, ContextMatcher, LoadedContext, ContextSource};
 use crate::error::{CRAError, Result};
-use crate::trace::{DeferredConfig, EventType, TraceCollector, TRACEEvent};
+use crate::id::{IdGen, UuidGen};
+use crate::identity::{IdentityProvider, Principal};
+use crate::metrics::{self, InMemoryMetrics, MetricsSink, MetricsSnapshot};
+#[cfg(not(feature = "minimal"))]
+use crate::notify::{NotificationClass, WebhookNotifier};
+#[cfg(not(feature = "minimal"))]
+use crate::replication::{ReplicationBatch, ReplicationCursor};
+use crate::storage::{EventPage, EventQuery, SessionStore, StorageBackend, MAX_SEARCH_LIMIT};
+use crate::trace::{
+    DeferredConfig, EventType, GraphFormat, IntegrityViolationPayload, KeyRotatedPayload, ADMIN_AUDIT_SESSION, SessionHandoffInPayload, SessionHandoffOutPayload,
+    ContentRecord, TraceCollector, TraceExport, TraceKey, TraceSigner, TraceVerbosity, TRACEEvent,
+};
 
 use super::{
     AllowedAction, CARPRequest, CARPResolution, ContextBlock, Constraint, Decision, DeniedAction,
-    PolicyEvaluator, PolicyResult,
+    DegradedEvaluation, Narrowing, NARROWING_POLICY_ID, CapabilityToken, CAPABILITY_TOKEN_POLICY_ID,
+    ExternalPolicy, PolicyDecisionFn,
+    PolicyEvaluator, PolicyResult, PolicyCoverage, ActionCoverage, STRICT_MODE_POLICY_ID,
     // Checkpoint types
     CheckpointEvaluator, CheckpointConfig, CheckpointResponse,
     CheckpointValidator, CheckpointValidation, TriggeredCheckpoint,
     SessionCheckpointState, TriggerData,
+    CheckpointTrigger, CustomTriggerContext, CustomTriggerDecision, CustomTriggerEvaluator,
+    CheckpointQuestion, AnswerValue,
+    ActiveGuidance, GuidanceManager, GuidanceRemoval,
+    CapabilityChangeSource, CapabilityState, CapabilityStatus, CapabilityTransition,
+    ChainHead, SessionSnapshot, SNAPSHOT_VERSION,
+    ApprovalDecision, ApprovalManager, ApprovalRecord, ApprovalRequest, ApprovalStatus, EscalationChannel,
+    ResolverState, SessionSnapshotState, TraceChainSnapshot, SessionReport, SessionStateSnapshot,
+};
+use super::template::{self, SessionVariables};
+use super::batch::{self, BatchResolveResponse};
+use super::observer::{Observers, ResolverObserver};
+use super::gc::{self, GcPolicy, GcStats};
+use super::bootstrap::{
+    check_protocol_version, BootstrapContext, BootstrapRequest, BootstrapResponse, GovernanceRule, PolicySummary,
+    SessionConstraint,
 };
+use super::external::ExternalPolicies;
+use super::token::TokenIssuer;
 
 /// Session state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     /// Unique session identifier
     pub session_id: String,
@@ -46,6 +79,18 @@ pub struct Session {
     pub resolution_count: u64,
     /// Number of actions executed in this session
     pub action_count: u64,
+    /// Variables substituted into `${name}` action templates
+    #[serde(default, skip_serializing_if = "SessionVariables::is_empty")]
+    pub variables: SessionVariables,
+    /// Session that spawned this one (see [`Resolver::create_child_session`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_session_id: Option<String>,
+    /// Authenticated caller the session was created for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<Principal>,
+    /// Detail recorded in the session's TRACE
+    #[serde(default, skip_serializing_if = "TraceVerbosity::is_standard")]
+    pub trace_verbosity: TraceVerbosity,
 }
 
 impl Session {
@@ -55,27 +100,49 @@ impl Session {
             session_id,
             agent_id,
             goal,
-            created_at: Utc::now(),
+            created_at: crate::clock::now(),
             ended_at: None,
             is_active: true,
             resolution_count: 0,
             action_count: 0,
+            variables: SessionVariables::new(),
+            parent_session_id: None,
+            principal: None,
+            trace_verbosity: TraceVerbosity::default(),
         }
     }
 
     /// End the session
     pub fn end(&mut self) {
-        self.ended_at = Some(Utc::now());
+        self.end_at(crate::clock::now());
+    }
+
+    /// End the session at a specific time
+    pub fn end_at(&mut self, ended_at: chrono::DateTime<Utc>) {
+        self.ended_at = Some(ended_at);
         self.is_active = false;
     }
 
     /// Get session duration in milliseconds
     pub fn duration_ms(&self) -> i64 {
-        let end = self.ended_at.unwrap_or_else(Utc::now);
+        let end = self.ended_at.unwrap_or_else(crate::clock::now);
         (end - self.created_at).num_milliseconds()
     }
 }
 
+/// Atlas versions a resolution was made against
+#[derive(Debug, Clone)]
+struct ResolutionPin {
+    session_id: String,
+    atlas_versions: BTreeMap<String, String>,
+    /// Why the resolution can no longer be executed against
+    invalidated: Option<String>,
+    /// Narrowing the request asked for, checked again on execute
+    narrowing: Option<Narrowing>,
+    /// When the resolution's TTL runs out
+    expires_at: chrono::DateTime<Utc>,
+}
+
 /// The main CRA Resolver
 ///
 /// Manages atlases, sessions, and provides CARP resolution.
@@ -87,21 +154,36 @@ pub struct Resolver {
     /// Active sessions by ID
     sessions: HashMap<String, Session>,
 
+    /// Atlas versions pinned by each resolution, by resolution ID
+    resolution_pins: HashMap<String, ResolutionPin>,
+
     /// Checkpoint state per session
     checkpoint_states: HashMap<String, SessionCheckpointState>,
 
     /// Pending checkpoints awaiting response
     pending_checkpoints: HashMap<String, Vec<TriggeredCheckpoint>>,
 
-    /// Unlocked capabilities per session
-    unlocked_capabilities: HashMap<String, std::collections::HashSet<String>>,
+    /// Capability state per session
+    capability_states: HashMap<String, CapabilityState>,
+
+    /// Active checkpoint guidance per session
+    guidance: HashMap<String, GuidanceManager>,
 
     /// Policy evaluator
     policy_evaluator: PolicyEvaluator,
 
+    /// Approval requests and the channels they are escalated through
+    approvals: ApprovalManager,
+
     /// Checkpoint evaluator
     checkpoint_evaluator: CheckpointEvaluator,
 
+    /// Embedder callbacks for custom checkpoint triggers
+    custom_triggers: CustomTriggerEvaluator,
+
+    /// Validator for checkpoint responses (with custom validators)
+    answer_validator: CheckpointValidator,
+
     /// Context registry for context injection
     context_registry: ContextRegistry,
 
@@ -111,25 +193,105 @@ pub struct Resolver {
     /// TRACE collector for audit events
     trace_collector: TraceCollector,
 
+    /// Signing keys replaced by `rotate_event_signer`, with their expiry
+    retired_trace_keys: Vec<TraceKey>,
+
+    /// Broken TRACE chains found by chain monitoring, by session
+    integrity_violations: HashMap<String, IntegrityViolationPayload>,
+
+    /// Verify chains as `flush_traces` chains deferred events
+    verify_on_flush: bool,
+
+    /// Where the next `sweep_chains` starts in the sorted session list
+    sweep_cursor: usize,
+
+    /// Responses to idempotent resolve and execute calls
+    idempotency: IdempotencyCache,
+
+    /// Capability tokens minted for sessions
+    tokens: TokenIssuer,
+
+    /// Client and decision cache for `external` policies
+    external_policies: ExternalPolicies,
+
     /// Default TTL for resolutions in seconds
     default_ttl: u64,
+
+    /// Deny actions that no capability grants
+    strict_mode: bool,
+
+    /// Clock for session and resolution timestamps
+    clock: Arc<dyn Clock>,
+
+    /// Generator for session, trace, and execution IDs
+    ids: Arc<dyn IdGen>,
+
+    /// Sink for decision counters and latency histograms
+    metrics: Arc<dyn MetricsSink>,
+
+    /// Webhooks notified of governance events
+    #[cfg(not(feature = "minimal"))]
+    notifier: Option<Arc<WebhookNotifier>>,
+
+    /// Write-through persistence of session state, for crash recovery
+    session_store: Option<Arc<dyn SessionStore>>,
+
+    /// Providers that validate caller credentials, tried in order
+    identity_providers: Vec<Arc<dyn IdentityProvider>>,
+
+    /// Observers of sessions, resolutions and executions
+    observers: Observers,
+
+    /// Context block contents by content address
+    content_store: Arc<ContentStore>,
+
+    /// Whether resolutions carry context by reference instead of inline
+    reference_context: bool,
+
+    /// What `collect_garbage` reclaims
+    gc_policy: GcPolicy,
 }
 
 impl Resolver {
     /// Create a new resolver
     pub fn new() -> Self {
+        let metrics: Arc<dyn MetricsSink> = Arc::new(InMemoryMetrics::new());
         Self {
             atlases: HashMap::new(),
             sessions: HashMap::new(),
+            resolution_pins: HashMap::new(),
             checkpoint_states: HashMap::new(),
             pending_checkpoints: HashMap::new(),
-            unlocked_capabilities: HashMap::new(),
+            capability_states: HashMap::new(),
+            guidance: HashMap::new(),
             policy_evaluator: PolicyEvaluator::new(),
+            approvals: ApprovalManager::new(),
             checkpoint_evaluator: CheckpointEvaluator::with_defaults(),
+            custom_triggers: CustomTriggerEvaluator::new(),
+            answer_validator: CheckpointValidator::new(),
             context_registry: ContextRegistry::new(),
             context_matcher: ContextMatcher::new(),
-            trace_collector: TraceCollector::new(),
+            trace_collector: TraceCollector::new().with_metrics(metrics.clone()),
+            retired_trace_keys: Vec::new(),
+            integrity_violations: HashMap::new(),
+            verify_on_flush: false,
+            sweep_cursor: 0,
+            idempotency: IdempotencyCache::new(),
+            tokens: TokenIssuer::default(),
+            external_policies: ExternalPolicies::default(),
             default_ttl: 300, // 5 minutes
+            strict_mode: false,
+            clock: Arc::new(GlobalClock),
+            ids: Arc::new(UuidGen),
+            metrics,
+            #[cfg(not(feature = "minimal"))]
+            notifier: None,
+            session_store: None,
+            identity_providers: Vec::new(),
+            observers: Observers::default(),
+            content_store: Arc::new(ContentStore::new()),
+            reference_context: false,
+            gc_policy: GcPolicy::default(),
         }
     }
 
@@ -145,6 +307,290 @@ impl Resolver {
         self
     }
 
+    /// Keep context contents in a shared store (e.g. one per process)
+    pub fn with_content_store(mut self, store: Arc<ContentStore>) -> Self {
+        self.content_store = store;
+        self
+    }
+
+    /// Send context blocks by reference
+    ///
+    /// Resolutions then carry each block's `content_hash` with empty
+    /// `content`; fetch the content with [`Resolver::context_content`] or
+    /// [`Resolver::inline_context`]. Blocks are content-addressed either way.
+    pub fn with_context_references(mut self, enabled: bool) -> Self {
+        self.reference_context = enabled;
+        self
+    }
+
+    /// The store holding context contents
+    pub fn content_store(&self) -> &Arc<ContentStore> {
+        &self.content_store
+    }
+
+    /// Context content by content address
+    pub fn context_content(&self, content_hash: &str) -> Result<String> {
+        self.content_store.get(content_hash).ok_or_else(|| CRAError::ContentNotFound {
+            content_hash: content_hash.to_string(),
+        })
+    }
+
+    /// Fill in the content of a resolution's referenced context blocks
+    pub fn inline_context(&self, resolution: &mut CARPResolution) -> Result<()> {
+        for block in resolution.context_blocks.iter_mut().filter(|b| b.is_reference()) {
+            if let Some(content_hash) = &block.content_hash {
+                block.content = self.context_content(content_hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set how long, and for how many calls, idempotent responses are kept
+    pub fn with_idempotency_config(mut self, config: IdempotencyConfig) -> Self {
+        self.idempotency = IdempotencyCache::with_config(config);
+        self
+    }
+
+    /// Persist session state to a store
+    ///
+    /// After every call that changes a session, its state (and the rate
+    /// limit counters) is written to the store. A restarted resolver gets
+    /// live sessions back with [`Resolver::recover_sessions`].
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.session_store = Some(store);
+        self
+    }
+
+    /// Get the session store, if any
+    pub fn session_store(&self) -> Option<&Arc<dyn SessionStore>> {
+        self.session_store.as_ref()
+    }
+
+    /// Deny by default: actions not included in any atlas capability are denied
+    ///
+    /// Denials report the [`STRICT_MODE_POLICY_ID`] policy. Explicit deny,
+    /// approval and rate limit policies still apply to granted actions.
+    pub fn with_strict_mode(mut self, strict: bool) -> Self {
+        self.strict_mode = strict;
+        self
+    }
+
+    /// Whether strict (deny-by-default) mode is enabled
+    pub fn is_strict(&self) -> bool {
+        self.strict_mode
+    }
+
+    /// Use a specific clock for session, resolution, and TRACE timestamps
+    ///
+    /// Combined with [`Resolver::with_id_generator`], this makes traces
+    /// reproducible (tests, replay, golden traces).
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_clock(self.clock.clone());
+        self
+    }
+
+    /// Use a specific generator for session, trace, event, and span IDs
+    pub fn with_id_generator(mut self, ids: impl IdGen + 'static) -> Self {
+        self.ids = Arc::new(ids);
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_id_generator(self.ids.clone());
+        self
+    }
+
+    /// Report metrics to a specific sink instead of the built-in aggregator
+    ///
+    /// [`Resolver::metrics_snapshot`] returns whatever the sink's
+    /// `snapshot()` does.
+    pub fn with_metrics_sink(mut self, sink: impl MetricsSink + 'static) -> Self {
+        self.metrics = Arc::new(sink);
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_metrics(self.metrics.clone());
+        self
+    }
+
+    /// Aggregated decision counters and latency histograms
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Sign TRACE events with a runtime key (see [`crate::trace::TraceSigner`])
+    ///
+    /// The signer's public key is trusted by [`Resolver::verify_chain`], along
+    /// with the `trace_keys` of loaded atlas stewards.
+    pub fn with_event_signer(mut self, signer: impl TraceSigner + 'static) -> Self {
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_signer(Arc::new(signer));
+        self
+    }
+
+    /// Write TRACE events to a storage backend as they are chained
+    ///
+    /// See [`TraceCollector::with_storage`]; [`StorageFactory`](crate::storage::StorageFactory)
+    /// builds a backend from configuration.
+    pub fn with_trace_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_storage(storage);
+        self
+    }
+
+    /// Notify subscribed webhooks of governance events
+    ///
+    /// Policy denials, approval requests and session ends are delivered as
+    /// their TRACE events are chained; failed [`Resolver::verify_chain`]
+    /// results are delivered as `chain.invalid`.
+    #[cfg(not(feature = "minimal"))]
+    pub fn with_webhook_notifier(mut self, notifier: Arc<WebhookNotifier>) -> Self {
+        let observer = notifier.clone();
+        self.trace_collector = std::mem::take(&mut self.trace_collector).with_callback(move |event| {
+            if let Err(e) = observer.observe(event) {
+                eprintln!("Error queueing webhook for {}: {}", event.event_id, e);
+            }
+        });
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// The webhook notifier, if any
+    #[cfg(not(feature = "minimal"))]
+    pub fn webhook_notifier(&self) -> Option<&Arc<WebhookNotifier>> {
+        self.notifier.as_ref()
+    }
+
+    /// Switch TRACE signing to a new key
+    ///
+    /// The old key stays in [`Resolver::trace_keys`] for `overlap`, and the
+    /// rotation is recorded as `key.rotated` in the
+    /// [`ADMIN_AUDIT_SESSION`](crate::trace::ADMIN_AUDIT_SESSION) trace.
+    pub fn rotate_event_signer(
+        &mut self,
+        signer: impl TraceSigner + 'static,
+        overlap: chrono::Duration,
+    ) -> Result<KeyRotatedPayload> {
+        let expires_at = self.clock.now() + overlap;
+        if let Some(current) = self.trace_collector.signer() {
+            let mut retired = current.public_key();
+            retired.expires_at = Some(expires_at);
+            self.retired_trace_keys.push(retired);
+        }
+        self.trace_collector.rotate_signer(Arc::new(signer), Some(expires_at))
+    }
+
+    /// Send decision requests of `external` policies through `client`
+    ///
+    /// Without a client, every external policy decides as if its engine
+    /// were down: denied unless the policy sets `fail_open`.
+    pub fn with_external_policy_client(mut self, client: PolicyDecisionFn) -> Self {
+        self.external_policies.set_client(client);
+        self
+    }
+
+    /// Cap the time external policies may take per resolution or execution
+    ///
+    /// See [`PolicyEvaluator::with_latency_budget`]. Decisions that fall
+    /// back after a timeout are listed in the resolution's
+    /// `degraded_evaluations` and recorded as `policy.evaluation_timeout`.
+    ///
+    /// The same budget applies to custom checkpoint triggers per
+    /// [`Resolver::evaluate_custom_checkpoints`] call, and to custom answer
+    /// validators per [`Resolver::respond_to_checkpoint`] call. Once it is
+    /// spent, remaining triggers skip (`checkpoint.skipped` with reason
+    /// `latency_budget`) and remaining validators reject their answers.
+    pub fn with_policy_latency_budget(mut self, budget: std::time::Duration) -> Self {
+        self.policy_evaluator.set_latency_budget(Some(budget));
+        self
+    }
+
+    /// Validate caller credentials with `provider` too
+    ///
+    /// Providers are tried in the order they were added.
+    pub fn with_identity_provider(mut self, provider: impl IdentityProvider + 'static) -> Self {
+        self.identity_providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Validate a caller's credential (an API key or bearer token)
+    ///
+    /// Returns the principal from the first provider that accepts it, or
+    /// `AuthenticationFailed` with every provider's reason.
+    pub fn authenticate(&self, credential: &str) -> Result<Principal> {
+        if self.identity_providers.is_empty() {
+            return Err(CRAError::AuthenticationFailed {
+                reason: "no identity provider configured".to_string(),
+            });
+        }
+        let now = self.clock.now();
+        let mut reasons = Vec::new();
+        for provider in &self.identity_providers {
+            match provider.authenticate(credential, now) {
+                Ok(principal) => return Ok(principal),
+                Err(e) => reasons.push(format!("{}: {}", provider.name(), e)),
+            }
+        }
+        Err(CRAError::AuthenticationFailed {
+            reason: reasons.join("; "),
+        })
+    }
+
+    /// Escalate approval requests through `channel` as well
+    pub fn with_escalation_channel(mut self, channel: impl EscalationChannel + 'static) -> Self {
+        self.add_escalation_channel(channel);
+        self
+    }
+
+    /// Add a channel that approval requests are escalated through
+    pub fn add_escalation_channel(&mut self, channel: impl EscalationChannel + 'static) {
+        self.approvals.add_channel(Arc::new(channel));
+    }
+
+    /// Report sessions, resolutions, executions and denials to `observer`
+    pub fn with_observer(mut self, observer: impl ResolverObserver + 'static) -> Self {
+        self.add_observer(observer);
+        self
+    }
+
+    /// Add an observer of sessions, resolutions, executions and denials
+    ///
+    /// Observers are called synchronously; see [`ResolverObserver`] for what
+    /// they may do.
+    pub fn add_observer(&mut self, observer: impl ResolverObserver + 'static) {
+        self.observers.add(Arc::new(observer));
+    }
+
+    /// Number of registered observers
+    pub fn observer_count(&self) -> usize {
+        self.observers.len()
+    }
+
+    /// Register a callback for `CheckpointTrigger::Custom` triggers with this `trigger_id`
+    ///
+    /// The callback receives a snapshot of the session state and the trigger's
+    /// params from the atlas, and decides whether the checkpoint fires.
+    pub fn register_custom_trigger<F>(&mut self, trigger_id: impl Into<String>, callback: F)
+    where
+        F: Fn(&CustomTriggerContext, &HashMap<String, Value>) -> CustomTriggerDecision
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.custom_triggers.register(trigger_id, callback);
+    }
+
+    /// Remove a custom trigger callback
+    pub fn unregister_custom_trigger(&mut self, trigger_id: &str) -> bool {
+        self.custom_triggers.unregister(trigger_id)
+    }
+
+    /// Register a validator for questions whose `validation.custom_validator` is `name`
+    ///
+    /// Returning `Err(message)` rejects the answer. Answers referencing a
+    /// validator that isn't registered are rejected.
+    pub fn register_answer_validator<F>(&mut self, name: impl Into<String>, validator: F)
+    where
+        F: Fn(&CheckpointQuestion, &AnswerValue) -> std::result::Result<(), String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.answer_validator.register_validator(name, validator);
+    }
+
     /// Enable deferred tracing mode
     ///
     /// In deferred mode, trace events are queued without computing hashes,
@@ -153,7 +599,22 @@ impl Resolver {
     ///
     /// This is recommended for high-throughput scenarios (agent swarms, benchmarks).
     pub fn with_deferred_tracing(mut self, config: DeferredConfig) -> Self {
-        self.trace_collector = TraceCollector::with_deferred(config);
+        let signer = self.trace_collector.signer().cloned();
+        let storage = self.trace_collector.storage().cloned();
+        let callback = self.trace_collector.callback().cloned();
+        self.trace_collector = TraceCollector::with_deferred(config)
+            .with_clock(self.clock.clone())
+            .with_id_generator(self.ids.clone())
+            .with_metrics(self.metrics.clone());
+        if let Some(signer) = signer {
+            self.trace_collector = std::mem::take(&mut self.trace_collector).with_signer(signer);
+        }
+        if let Some(storage) = storage {
+            self.trace_collector = std::mem::take(&mut self.trace_collector).with_storage(storage);
+        }
+        if let Some(callback) = callback {
+            self.trace_collector = std::mem::take(&mut self.trace_collector).with_callback(move |e| callback(e));
+        }
         self
     }
 
@@ -188,7 +649,237 @@ impl Resolver {
     /// let trace = resolver.get_trace(&session_id)?;
     /// ```
     pub fn flush_traces(&mut self) -> Result<()> {
-        self.trace_collector.flush()
+        if !self.verify_on_flush {
+            return self.trace_collector.flush();
+        }
+
+        let mut flushed: Vec<String> =
+            self.trace_collector.unchained_sessions().into_iter().map(String::from).collect();
+        flushed.sort();
+        self.trace_collector.flush()?;
+        for session_id in flushed {
+            self.monitor_chain(&session_id, "flush")?;
+        }
+        Ok(())
+    }
+
+    /// Verify each chain that `flush_traces` extends
+    ///
+    /// A chain that fails verification is handled as in
+    /// [`Resolver::sweep_chains`].
+    pub fn with_verify_on_flush(mut self) -> Self {
+        self.verify_on_flush = true;
+        self
+    }
+
+    /// Verify up to `max_sessions` TRACE chains, continuing where the last
+    /// sweep stopped
+    ///
+    /// Call periodically to sample chains in the background. When a chain
+    /// fails verification its session is quarantined: resolutions return
+    /// [`Decision::Quarantined`] and executions fail with
+    /// [`CRAError::SessionQuarantined`]. The violation is recorded as
+    /// `trace.integrity_violation` in the
+    /// [`ADMIN_AUDIT_SESSION`](crate::trace::ADMIN_AUDIT_SESSION) trace,
+    /// counted in `cra_integrity_violations_total`, and sent to webhooks
+    /// subscribed to `chain.invalid`.
+    ///
+    /// Returns the sessions found broken by this sweep.
+    pub fn sweep_chains(&mut self, max_sessions: usize) -> Result<Vec<String>> {
+        // Sessions with unflushed deferred events are checked once chained
+        let unchained = self.trace_collector.unchained_sessions();
+        let mut session_ids: Vec<String> = self
+            .trace_collector
+            .session_ids()
+            .into_iter()
+            .filter(|id| !self.integrity_violations.contains_key(*id) && !unchained.contains(id))
+            .map(String::from)
+            .collect();
+        session_ids.sort();
+        if session_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let start = self.sweep_cursor % session_ids.len();
+        let count = max_sessions.min(session_ids.len());
+        self.sweep_cursor = start + count;
+
+        let mut broken = Vec::new();
+        for session_id in session_ids.iter().cycle().skip(start).take(count) {
+            if self.monitor_chain(session_id, "sweep")? {
+                broken.push(session_id.clone());
+            }
+        }
+        Ok(broken)
+    }
+
+    /// Whether a session is quarantined after a TRACE integrity violation
+    pub fn is_quarantined(&self, session_id: &str) -> bool {
+        self.integrity_violations.get(session_id).is_some_and(|v| v.quarantined)
+    }
+
+    /// Broken TRACE chains found by chain monitoring, by session ID
+    pub fn integrity_violations(&self) -> Vec<&IntegrityViolationPayload> {
+        let mut violations: Vec<_> = self.integrity_violations.values().collect();
+        violations.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        violations
+    }
+
+    /// Lift a session's quarantine once its chain has been investigated
+    ///
+    /// Monitoring checks the session again on later sweeps.
+    pub fn release_quarantine(&mut self, session_id: &str) -> Option<IntegrityViolationPayload> {
+        self.integrity_violations.remove(session_id)
+    }
+
+    /// Verify one chain, recording a violation if it is broken
+    fn monitor_chain(&mut self, session_id: &str, detected_by: &str) -> Result<bool> {
+        let verification = self.check_chain(session_id)?;
+        if verification.is_valid {
+            return Ok(false);
+        }
+
+        let violation = IntegrityViolationPayload {
+            session_id: session_id.to_string(),
+            detected_by: detected_by.to_string(),
+            first_invalid_index: verification.first_invalid_index,
+            error: verification.error_message.clone().unwrap_or_default(),
+            // The admin audit chain has no resolver session to quarantine
+            quarantined: self.sessions.contains_key(session_id),
+            detected_at: self.clock.now(),
+        };
+        self.trace_collector.record(
+            ADMIN_AUDIT_SESSION,
+            EventType::IntegrityViolation,
+            serde_json::to_value(&violation)?,
+        )?;
+        self.metrics.increment(metrics::INTEGRITY_VIOLATIONS_TOTAL, &[("detected_by", detected_by)], 1);
+        #[cfg(not(feature = "minimal"))]
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(NotificationClass::ChainInvalid, session_id, serde_json::to_value(&verification)?)?;
+        }
+        self.integrity_violations.insert(session_id.to_string(), violation);
+        Ok(true)
+    }
+
+    /// Set what [`Resolver::collect_garbage`] reclaims
+    pub fn with_gc_policy(mut self, policy: GcPolicy) -> Self {
+        self.gc_policy = policy;
+        self
+    }
+
+    /// Reclaim expired and orphaned data under the configured [`GcPolicy`]
+    ///
+    /// See [`Resolver::collect_garbage_with`].
+    pub fn collect_garbage(&mut self) -> Result<GcStats> {
+        let policy = self.gc_policy.clone();
+        self.collect_garbage_with(&policy)
+    }
+
+    /// Reclaim expired and orphaned data under `policy`
+    ///
+    /// Runs that reclaim anything, and all dry runs, are recorded as
+    /// `gc.completed` in the
+    /// [`ADMIN_AUDIT_SESSION`](crate::trace::ADMIN_AUDIT_SESSION) trace.
+    /// Orphaned content is only collected once deferred events are flushed,
+    /// since unflushed events may still refer to it.
+    pub fn collect_garbage_with(&mut self, policy: &GcPolicy) -> Result<GcStats> {
+        let start = Instant::now();
+        let now = self.clock.now();
+        let mut stats = GcStats {
+            dry_run: policy.dry_run,
+            ..GcStats::default()
+        };
+
+        // Resolutions past their TTL and grace period
+        let grace = chrono::Duration::from_std(policy.resolution_grace).unwrap_or(chrono::Duration::MAX);
+        let expired: Vec<String> = self
+            .resolution_pins
+            .iter()
+            .filter(|(_, pin)| pin.expires_at.checked_add_signed(grace).is_some_and(|t| t <= now))
+            .map(|(id, _)| id.clone())
+            .collect();
+        stats.resolutions = expired.len();
+        if !policy.dry_run {
+            expired.iter().for_each(|id| {
+                self.resolution_pins.remove(id);
+            });
+        }
+
+        // Approvals in inactive sessions, or older than the maximum age
+        let max_age = policy.approval_max_age.and_then(|age| chrono::Duration::from_std(age).ok());
+        let stale: Vec<String> = self
+            .approvals
+            .records()
+            .into_iter()
+            .filter(|r| {
+                !self.sessions.get(&r.request.session_id).is_some_and(|s| s.is_active)
+                    || max_age.is_some_and(|age| r.request.requested_at + age <= now)
+            })
+            .map(|r| {
+                stats.bytes_freed += gc::approximate_size(r);
+                r.request.approval_id.clone()
+            })
+            .collect();
+        stats.approvals = stale.len();
+        if !policy.dry_run {
+            stale.iter().for_each(|id| {
+                self.approvals.remove(id);
+            });
+        }
+
+        // Expired idempotent responses
+        if policy.idempotency {
+            let expired = self.idempotency.expired(now);
+            stats.idempotency_entries = expired.len();
+            stats.bytes_freed += expired.iter().map(|r| gc::approximate_size(*r)).sum::<u64>();
+            if !policy.dry_run {
+                self.idempotency.purge_expired(now);
+            }
+        }
+
+        // Context content no retained event refers to
+        if policy.orphaned_content && self.trace_collector.is_flushed() {
+            let mut referenced = HashSet::new();
+            for session_id in self.trace_collector.session_ids() {
+                for event in self.trace_collector.get_events(session_id)? {
+                    if let Some(hash) = event.payload.get("content_hash").and_then(Value::as_str) {
+                        referenced.insert(hash.to_string());
+                    }
+                }
+            }
+            for content_hash in self.content_store.hashes() {
+                if referenced.contains(&content_hash) {
+                    continue;
+                }
+                let freed = if policy.dry_run {
+                    self.content_store.get(&content_hash).map(|c| c.len())
+                } else {
+                    self.content_store.remove(&content_hash)
+                };
+                if let Some(freed) = freed {
+                    stats.contents += 1;
+                    stats.bytes_freed += freed as u64;
+                }
+            }
+        }
+
+        // Delivered webhooks
+        #[cfg(not(feature = "minimal"))]
+        if policy.webhook_history {
+            if let Some(notifier) = &self.notifier {
+                let purged = notifier.purge_delivered(policy.dry_run);
+                stats.webhook_deliveries = purged.len();
+                stats.bytes_freed += purged.iter().map(gc::approximate_size).sum::<u64>();
+            }
+        }
+
+        stats.duration_us = start.elapsed().as_micros() as u64;
+        if stats.items_reclaimed() > 0 || policy.dry_run {
+            self.trace_collector
+                .record(ADMIN_AUDIT_SESSION, EventType::GcCompleted, serde_json::to_value(&stats)?)?;
+        }
+        Ok(stats)
     }
 
     /// Check if all trace events have been processed
@@ -197,7 +888,7 @@ impl Resolver {
     }
 
     /// Load an atlas into the resolver
-    pub fn load_atlas(&mut self, atlas: AtlasManifest) -> Result<String> {
+    pub fn load_atlas(&mut self, mut atlas: AtlasManifest) -> Result<String> {
         let atlas_id = atlas.atlas_id.clone();
 
         if self.atlases.contains_key(&atlas_id) {
@@ -206,8 +897,18 @@ impl Resolver {
             });
         }
 
+        // Content policies are enforced by wrappers, so reject broken ones up front
+        atlas.content_policies()?;
+        for policy in atlas.policies.iter().filter(|p| p.policy_type == PolicyType::External) {
+            ExternalPolicy::from_policy(policy)?;
+        }
+
+        // Actions without a risk tier inherit their namespace's default
+        atlas.apply_namespace_defaults();
+
         // Add policies from the atlas to the evaluator
         self.policy_evaluator.add_policies(atlas.policies.clone());
+        self.external_policies.clear_cache();
 
         // Load inline context_blocks into the registry
         for block in &atlas.context_blocks {
@@ -254,59 +955,437 @@ impl Resolver {
             });
         }
 
-        self.atlases.remove(atlas_id);
+        let removed = self.atlases.remove(atlas_id);
         // Note: policies remain - in production you'd want to rebuild
-        Ok(())
-    }
 
-    /// Get a loaded atlas by ID
-    pub fn get_atlas(&self, atlas_id: &str) -> Option<&AtlasManifest> {
-        self.atlases.get(atlas_id)
+        // Resolutions made against this atlas must be re-resolved
+        if let Some(atlas) = removed {
+            let reason = format!("atlas '{}' version {} was unloaded", atlas_id, atlas.version);
+            let mut resolution_ids: Vec<String> = self
+                .resolution_pins
+                .iter()
+                .filter(|(_, pin)| {
+                    pin.invalidated.is_none()
+                        && pin.atlas_versions.get(atlas_id) == Some(&atlas.version)
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            resolution_ids.sort();
+            for resolution_id in resolution_ids {
+                self.invalidate_resolution(&resolution_id, reason.clone())?;
+            }
+        }
+        Ok(())
     }
 
-    /// List all loaded atlas IDs
-    pub fn list_atlases(&self) -> Vec<&str> {
-        self.atlases.keys().map(|s| s.as_str()).collect()
+    /// Versions of the loaded atlases, by atlas ID
+    pub fn atlas_versions(&self) -> BTreeMap<String, String> {
+        self.atlases
+            .iter()
+            .map(|(id, atlas)| (id.clone(), atlas.version.clone()))
+            .collect()
     }
 
-    /// Create a new session
+    /// Whether a resolution can still be executed against
     ///
-    /// Returns the session ID and any triggered session start checkpoints.
-    pub fn create_session(&mut self, agent_id: &str, goal: &str) -> Result<String> {
-        let session_id = Uuid::new_v4().to_string();
-
-        if self.sessions.contains_key(&session_id) {
-            return Err(CRAError::SessionAlreadyExists {
-                session_id: session_id.clone(),
-            });
-        }
+    /// Returns `None` for resolutions this resolver did not make (or that
+    /// belong to ended sessions).
+    pub fn is_resolution_valid(&self, resolution_id: &str) -> Option<bool> {
+        self.resolution_pins
+            .get(resolution_id)
+            .map(|pin| pin.invalidated.is_none() && self.pin_mismatch(pin).is_none())
+    }
 
-        let session = Session::new(session_id.clone(), agent_id.to_string(), goal.to_string());
+    /// First pinned atlas that is no longer loaded at its pinned version
+    fn pin_mismatch(&self, pin: &ResolutionPin) -> Option<String> {
+        pin.atlas_versions.iter().find_map(|(atlas_id, version)| {
+            match self.atlases.get(atlas_id) {
+                None => Some(format!("atlas '{}' version {} is not loaded", atlas_id, version)),
+                Some(atlas) if &atlas.version != version => Some(format!(
+                    "atlas '{}' changed from version {} to {}",
+                    atlas_id, version, atlas.version
+                )),
+                Some(_) => None,
+            }
+        })
+    }
 
-        // Initialize checkpoint state for this session
-        self.checkpoint_states.insert(session_id.clone(), SessionCheckpointState::new());
-        self.unlocked_capabilities.insert(session_id.clone(), std::collections::HashSet::new());
+    /// Mark a pinned resolution invalid and record why in its session's TRACE
+    fn invalidate_resolution(&mut self, resolution_id: &str, reason: String) -> Result<()> {
+        let Some(pin) = self.resolution_pins.get_mut(resolution_id) else {
+            return Ok(());
+        };
+        pin.invalidated = Some(reason.clone());
+        let session_id = pin.session_id.clone();
+        let atlas_versions = pin.atlas_versions.clone();
 
-        // Emit session.started event
-        self.trace_collector.emit(
+        self.trace_collector.record(
             &session_id,
-            EventType::SessionStarted,
+            EventType::ResolutionInvalidated,
             serde_json::json!({
-                "agent_id": agent_id,
-                "goal": goal,
-                "atlas_ids": self.list_atlases(),
+                "resolution_id": resolution_id,
+                "atlas_versions": atlas_versions,
+                "reason": reason,
             }),
         )?;
+        Ok(())
+    }
 
-        // Evaluate session start checkpoints from atlases
-        let session_start_checkpoints = self.evaluate_session_start_checkpoints(&session_id)?;
+    /// Fail if a resolution's pinned atlases are gone or have changed version
+    fn check_resolution_pin(&mut self, session_id: &str, resolution_id: &str) -> Result<()> {
+        let Some(pin) = self.resolution_pins.get(resolution_id) else {
+            return Ok(());
+        };
+        if pin.session_id != session_id {
+            return Ok(());
+        }
 
-        if !session_start_checkpoints.is_empty() {
-            // Store pending checkpoints if any require response
-            let pending: Vec<_> = session_start_checkpoints
-                .iter()
-                .filter(|c| c.requires_response())
-                .cloned()
+        let reason = match &pin.invalidated {
+            Some(reason) => reason.clone(),
+            None => match self.pin_mismatch(pin) {
+                Some(reason) => {
+                    self.invalidate_resolution(resolution_id, reason.clone())?;
+                    reason
+                }
+                None => return Ok(()),
+            },
+        };
+        Err(CRAError::ResolutionInvalidated {
+            resolution_id: resolution_id.to_string(),
+            reason,
+        })
+    }
+
+    /// Get a loaded atlas by ID
+    pub fn get_atlas(&self, atlas_id: &str) -> Option<&AtlasManifest> {
+        self.atlases.get(atlas_id)
+    }
+
+    /// List all loaded atlas IDs
+    pub fn list_atlases(&self) -> Vec<&str> {
+        self.atlases.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// All loaded actions under a namespace, sorted by action ID
+    ///
+    /// `ticket` lists `ticket.get` and `ticket.comment.add`; the empty
+    /// namespace lists every action.
+    pub fn actions_in_namespace(&self, namespace: &str) -> Vec<&AtlasAction> {
+        let mut actions: Vec<&AtlasAction> = self
+            .atlases
+            .values()
+            .flat_map(|atlas| atlas.actions_in_namespace(namespace))
+            .collect();
+        actions.sort_by(|a, b| a.action_id.cmp(&b.action_id));
+        actions
+    }
+
+    /// Content policies of all loaded atlases, sorted by policy ID
+    ///
+    /// These are not evaluated here: they are handed to wrappers (e.g. at
+    /// bootstrap), which enforce them on input and output text.
+    pub fn content_policies(&self) -> Vec<ContentPolicy> {
+        let mut policies: Vec<ContentPolicy> = self
+            .atlases
+            .values()
+            .flat_map(|atlas| atlas.content_policies().unwrap_or_default())
+            .collect();
+        policies.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+        policies
+    }
+
+    /// Create a new session
+    ///
+    /// Returns the session ID and any triggered session start checkpoints.
+    pub fn create_session(&mut self, agent_id: &str, goal: &str) -> Result<String> {
+        self.create_session_with_variables(agent_id, goal, SessionVariables::new())
+    }
+
+    /// Bootstrap an agent: create its session and resolve its intent
+    ///
+    /// The response carries the session's genesis hash and chain head, the
+    /// standard governance rules, the loaded atlases' policies, the initial
+    /// resolution's context (inlined) plus every `always` context block, and
+    /// the resolution's actions and constraints.
+    /// See [`super::bootstrap`] for the wire schema.
+    pub fn bootstrap(&mut self, request: &BootstrapRequest) -> Result<BootstrapResponse> {
+        check_protocol_version(&request.protocol_version)?;
+
+        let session_id = self.create_session(&request.agent_id, &request.intent)?;
+        let mut carp_request = CARPRequest::new(session_id.clone(), request.agent_id.clone(), request.intent.clone());
+        if !request.context_hints.is_empty() {
+            carp_request.context_hints = Some(request.context_hints.clone());
+        }
+        if !request.capabilities.is_empty() {
+            carp_request.metadata = Some(serde_json::json!({ "agent_capabilities": request.capabilities }));
+        }
+        let mut resolution = self.resolve(&carp_request)?;
+        self.inline_context(&mut resolution)?;
+
+        self.flush_traces()?;
+        let trace = self.get_trace(&session_id)?;
+        let (Some(genesis), Some(head)) = (trace.first(), trace.last()) else {
+            return Err(CRAError::InvalidBootstrap {
+                reason: format!("session '{}' has no TRACE events", session_id),
+            });
+        };
+
+        let mut policies: Vec<PolicySummary> = self
+            .atlases
+            .values()
+            .flat_map(|atlas| &atlas.policies)
+            .filter(|policy| policy.policy_type != PolicyType::Content)
+            .map(PolicySummary::from)
+            .collect();
+        policies.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+
+        let mut response = BootstrapResponse::new(session_id.clone(), genesis.event_hash.clone())
+            .with_rules(GovernanceRule::standard())
+            .with_content_policies(self.content_policies());
+        response.current_hash = head.event_hash.clone();
+        response.sequence = head.sequence;
+        response.resolution_id = Some(resolution.trace_id.clone());
+        response.policies = policies;
+        response.contexts = resolution.context_blocks.iter().map(BootstrapContext::from).collect();
+        let mut mandatory: Vec<_> = self
+            .atlases
+            .values()
+            .flat_map(|atlas| &atlas.context_blocks)
+            .filter(|block| block.inject_mode == InjectMode::Always)
+            .filter(|block| !response.contexts.iter().any(|c| c.context_id == block.context_id))
+            .map(|block| BootstrapContext::new(block.context_id.clone(), block.content.clone(), block.priority))
+            .collect();
+        response.contexts.append(&mut mandatory);
+        response.contexts.sort_by_key(|c| std::cmp::Reverse(c.priority));
+        response.allowed_actions = resolution.allowed_actions.iter().map(|a| a.action_id.clone()).collect();
+        response.constraints = resolution.constraints.iter().map(SessionConstraint::from).collect();
+        Ok(response)
+    }
+
+    /// Create a new session with variables for `${name}` action templates
+    ///
+    /// Variables fill in action parameter defaults and executors when the
+    /// session resolves (see [`super::template`]).
+    pub fn create_session_with_variables(
+        &mut self,
+        agent_id: &str,
+        goal: &str,
+        variables: SessionVariables,
+    ) -> Result<String> {
+        self.open_session(agent_id, goal, variables, None, None, TraceVerbosity::default())
+    }
+
+    /// Create a new session that records TRACE at the given verbosity
+    ///
+    /// A level other than `standard` is noted in `session.started`. See
+    /// [`TraceVerbosity`] for what each level keeps.
+    pub fn create_session_with_verbosity(
+        &mut self,
+        agent_id: &str,
+        goal: &str,
+        verbosity: TraceVerbosity,
+    ) -> Result<String> {
+        self.open_session(agent_id, goal, SessionVariables::new(), None, None, verbosity)
+    }
+
+    /// Create a session for an authenticated caller
+    ///
+    /// The session runs as the principal's `agent_id` and records the
+    /// principal, which `session.started` and the decision requests of
+    /// `external` policies carry. Child sessions inherit it.
+    pub fn create_session_for_principal(
+        &mut self,
+        principal: &Principal,
+        goal: &str,
+        variables: SessionVariables,
+    ) -> Result<String> {
+        self.open_session(&principal.agent_id, goal, variables, None, Some(principal.clone()), TraceVerbosity::default())
+    }
+
+    /// Create a child session for a sub-agent of an active session
+    ///
+    /// The child gets the parent's agent ID and variables. Of the atlas
+    /// capabilities, it may use only those in `capability_subset` that the
+    /// parent may use now; the rest are locked. Capabilities the parent
+    /// loses later are denied to the child too, and ending the parent ends
+    /// its children. The link is recorded as `parent_session_id` in the
+    /// child's `session.started` and as `session.child_created` in the
+    /// parent's trace. The child records TRACE at the parent's verbosity.
+    pub fn create_child_session(
+        &mut self,
+        parent_session_id: &str,
+        goal: &str,
+        capability_subset: &[String],
+    ) -> Result<String> {
+        let parent = self.sessions.get(parent_session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: parent_session_id.to_string(),
+        })?;
+        if !parent.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: parent_session_id.to_string(),
+            });
+        }
+        let (agent_id, variables, principal, verbosity) = (
+            parent.agent_id.clone(),
+            parent.variables.clone(),
+            parent.principal.clone(),
+            parent.trace_verbosity,
+        );
+
+        let mut capability_ids: Vec<String> = self
+            .atlases
+            .values()
+            .flat_map(|a| a.capabilities.iter().map(|c| c.capability_id.clone()))
+            .collect();
+        capability_ids.sort();
+        capability_ids.dedup();
+        if let Some(unknown) = capability_subset.iter().find(|id| !capability_ids.contains(id)) {
+            return Err(CRAError::CapabilityNotFound {
+                capability_id: unknown.clone(),
+            });
+        }
+
+        let lineage = self.lineage(parent_session_id);
+        let delegated: Vec<String> = capability_ids
+            .iter()
+            .filter(|id| capability_subset.contains(id))
+            .filter(|id| {
+                lineage
+                    .iter()
+                    .all(|s| self.capability_states.get(s).is_none_or(|state| state.is_usable(id)))
+            })
+            .cloned()
+            .collect();
+
+        let child_id = self.open_session(&agent_id, goal, variables, Some(parent_session_id), principal, verbosity)?;
+        let source = CapabilityChangeSource::Delegation {
+            parent_session_id: parent_session_id.to_string(),
+        };
+        for capability_id in &capability_ids {
+            let to = if delegated.contains(capability_id) {
+                CapabilityStatus::Granted
+            } else {
+                CapabilityStatus::Locked
+            };
+            self.transition_capability(&child_id, capability_id, to, source.clone())?;
+        }
+
+        self.trace_collector.record(
+            parent_session_id,
+            EventType::SessionChildCreated,
+            serde_json::json!({
+                "child_session_id": child_id,
+                "goal": goal,
+                "capabilities": delegated,
+            }),
+        )?;
+
+        self.persist_session(&child_id)?;
+        self.persist_session(parent_session_id)?;
+        Ok(child_id)
+    }
+
+    /// Sessions created by [`Resolver::create_child_session`] for a parent, oldest first
+    pub fn child_sessions(&self, parent_session_id: &str) -> Vec<&Session> {
+        self.list_sessions()
+            .into_iter()
+            .filter(|s| s.parent_session_id.as_deref() == Some(parent_session_id))
+            .collect()
+    }
+
+    /// A session followed by its parent, grandparent and so on
+    fn lineage(&self, session_id: &str) -> Vec<String> {
+        let mut lineage = vec![session_id.to_string()];
+        while let Some(parent) = self
+            .sessions
+            .get(lineage.last().map(String::as_str).unwrap_or_default())
+            .and_then(|s| s.parent_session_id.clone())
+        {
+            lineage.push(parent);
+        }
+        lineage
+    }
+
+    fn open_session(
+        &mut self,
+        agent_id: &str,
+        goal: &str,
+        variables: SessionVariables,
+        parent_session_id: Option<&str>,
+        principal: Option<Principal>,
+        verbosity: TraceVerbosity,
+    ) -> Result<String> {
+        if let Some(name) = variables.keys().find(|name| !template::is_valid_name(name)) {
+            return Err(CRAError::InvalidCARPRequest {
+                reason: format!("invalid session variable name '{}'", name),
+            });
+        }
+        let session_id = self.ids.next_id();
+
+        if self.sessions.contains_key(&session_id) {
+            return Err(CRAError::SessionAlreadyExists {
+                session_id: session_id.clone(),
+            });
+        }
+
+        let mut session = Session::new(session_id.clone(), agent_id.to_string(), goal.to_string());
+        session.created_at = self.clock.now();
+        session.variables = variables;
+        session.parent_session_id = parent_session_id.map(String::from);
+        session.principal = principal;
+        session.trace_verbosity = verbosity;
+
+        // Initialize checkpoint state for this session
+        self.checkpoint_states.insert(session_id.clone(), SessionCheckpointState::new());
+        self.capability_states.insert(session_id.clone(), CapabilityState::new());
+        self.guidance.insert(session_id.clone(), GuidanceManager::new());
+
+        // Emit session.started event (variable names only, values may be sensitive)
+        let mut payload = serde_json::json!({
+            "agent_id": agent_id,
+            "goal": goal,
+            "atlas_ids": self.list_atlases(),
+        });
+        if !session.variables.is_empty() {
+            payload["variables"] = serde_json::json!(session.variables.keys().collect::<Vec<_>>());
+        }
+        if let Some(parent_session_id) = parent_session_id {
+            payload["parent_session_id"] = serde_json::json!(parent_session_id);
+        }
+        if let Some(principal) = &session.principal {
+            payload["principal"] = serde_json::to_value(principal)?;
+        }
+        if !verbosity.is_standard() {
+            payload["trace_verbosity"] = serde_json::json!(verbosity);
+            self.trace_collector.set_verbosity(&session_id, verbosity);
+        }
+        self.trace_collector.record(&session_id, EventType::SessionStarted, payload)?;
+
+        // Capabilities unlocked by a checkpoint start behind their gate
+        let gated: Vec<String> = self
+            .atlases
+            .values()
+            .flat_map(|a| a.checkpoints.iter())
+            .flat_map(|c| c.unlock_capabilities.iter().cloned())
+            .collect();
+        for capability_id in gated {
+            self.transition_capability(
+                &session_id,
+                &capability_id,
+                CapabilityStatus::PendingGate,
+                CapabilityChangeSource::SessionStart,
+            )?;
+        }
+
+        // Evaluate session start checkpoints from atlases
+        let session_start_checkpoints = self.evaluate_session_start_checkpoints(&session_id)?;
+
+        if !session_start_checkpoints.is_empty() {
+            // Store pending checkpoints if any require response
+            let pending: Vec<_> = session_start_checkpoints
+                .iter()
+                .filter(|c| c.requires_response())
+                .cloned()
                 .collect();
 
             if !pending.is_empty() {
@@ -314,7 +1393,10 @@ impl Resolver {
             }
         }
 
+        session.trace_verbosity = self.trace_collector.verbosity(&session_id);
+        self.observers.session_started(&session);
         self.sessions.insert(session_id.clone(), session);
+        self.persist_session(&session_id)?;
         Ok(session_id)
     }
 
@@ -340,7 +1422,7 @@ impl Resolver {
         // Now emit events and collect checkpoints
         for (checkpoint_id, name, mode, question_count, has_guidance, triggered) in checkpoint_data {
             // Emit checkpoint.triggered event
-            self.trace_collector.emit(
+            self.trace_collector.record(
                 session_id,
                 EventType::CheckpointTriggered,
                 serde_json::json!({
@@ -353,10 +1435,7 @@ impl Resolver {
                 }),
             )?;
 
-            // Emit guidance if present
-            if let Some(guidance) = &triggered.guidance {
-                self.emit_guidance_injected(session_id, &checkpoint_id, guidance)?;
-            }
+            self.apply_checkpoint_guidance(session_id, &triggered)?;
 
             checkpoints.push(triggered);
         }
@@ -367,6 +1446,57 @@ impl Resolver {
         Ok(checkpoints)
     }
 
+    /// Set a session variable for `${name}` action templates
+    ///
+    /// Takes effect from the session's next resolution.
+    pub fn set_session_variable(&mut self, session_id: &str, name: &str, value: &str) -> Result<()> {
+        if !template::is_valid_name(name) {
+            return Err(CRAError::InvalidCARPRequest {
+                reason: format!("invalid session variable name '{}'", name),
+            });
+        }
+        let session = self.sessions.get_mut(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        session.variables.insert(name.to_string(), value.to_string());
+        self.persist_session(session_id)?;
+        Ok(())
+    }
+
+    /// Change how much detail a session's TRACE records
+    ///
+    /// The change is recorded as `trace.verbosity_changed` at every level,
+    /// with `reason` saying who asked for it, so audits can tell when detail
+    /// was reduced. Setting the current level again does nothing.
+    pub fn set_trace_verbosity(&mut self, session_id: &str, verbosity: TraceVerbosity, reason: &str) -> Result<()> {
+        let session = self.sessions.get_mut(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        let from = session.trace_verbosity;
+        if from == verbosity {
+            return Ok(());
+        }
+        session.trace_verbosity = verbosity;
+
+        change_verbosity(&mut self.trace_collector, session_id, from, verbosity, reason)?;
+        self.persist_session(session_id)
+    }
+
+    /// Variables of a session
+    pub fn session_variables(&self, session_id: &str) -> Option<&SessionVariables> {
+        self.sessions.get(session_id).map(|s| &s.variables)
+    }
+
     /// Get pending checkpoints for a session that require response
     pub fn get_pending_checkpoints(&self, session_id: &str) -> Option<&Vec<TriggeredCheckpoint>> {
         self.pending_checkpoints.get(session_id)
@@ -411,7 +1541,7 @@ impl Resolver {
         // Emit response received event
         for (question_id, answer) in &response.answers {
             let answer_hash = hash_value(&serde_json::to_value(answer).unwrap_or_default());
-            self.trace_collector.emit(
+            self.trace_collector.record(
                 session_id,
                 EventType::CheckpointResponseReceived,
                 serde_json::json!({
@@ -424,12 +1554,27 @@ impl Resolver {
             )?;
         }
 
-        // Validate the response
-        let validation = CheckpointValidator::validate(checkpoint, response);
+        // Validate the response, with custom validators charged to the latency budget
+        let validation = self
+            .answer_validator
+            .validate_response_within(checkpoint, response, self.policy_evaluator.latency_budget());
+
+        // Answers to questions that set session variables
+        let answered_variables: Vec<(String, String)> = checkpoint
+            .steward_def
+            .iter()
+            .flat_map(|def| def.questions.iter())
+            .filter_map(|q| {
+                let name = q.sets_variable.clone()?;
+                let answer = response.answers.get(&q.question_id)?;
+                Some((name, answer.to_variable_value()))
+            })
+            .collect();
+        let verbosity = checkpoint.steward_def.as_ref().and_then(|def| def.trace_verbosity);
 
         // Emit validation events
         for (question_id, result) in &validation.question_results {
-            self.trace_collector.emit(
+            self.trace_collector.record(
                 session_id,
                 EventType::CheckpointValidated,
                 serde_json::json!({
@@ -443,7 +1588,7 @@ impl Resolver {
 
         // Emit passed/failed event
         if validation.is_valid {
-            self.trace_collector.emit(
+            self.trace_collector.record(
                 session_id,
                 EventType::CheckpointPassed,
                 serde_json::json!({
@@ -455,14 +1600,20 @@ impl Resolver {
                 }),
             )?;
 
-            // Apply capability changes
-            if let Some(caps) = self.unlocked_capabilities.get_mut(session_id) {
-                for cap in &validation.unlocked_capabilities {
-                    caps.insert(cap.clone());
-                }
-                for cap in &validation.locked_capabilities {
-                    caps.remove(cap);
-                }
+            self.apply_capability_outcome(
+                session_id,
+                &response.checkpoint_id,
+                &validation.unlocked_capabilities,
+                &validation.locked_capabilities,
+            )?;
+
+            self.complete_checkpoint_guidance(session_id, &response.checkpoint_id)?;
+
+            for (name, value) in answered_variables {
+                self.set_session_variable(session_id, &name, &value)?;
+            }
+            if let Some(verbosity) = verbosity {
+                self.apply_checkpoint_verbosity(session_id, &response.checkpoint_id, verbosity)?;
             }
 
             // Remove the responded checkpoint from pending
@@ -475,7 +1626,7 @@ impl Resolver {
                 });
             }
         } else {
-            self.trace_collector.emit(
+            self.trace_collector.record(
                 session_id,
                 EventType::CheckpointFailed,
                 serde_json::json!({
@@ -486,6 +1637,7 @@ impl Resolver {
             )?;
         }
 
+        self.persist_session(session_id)?;
         Ok(validation)
     }
 
@@ -509,7 +1661,7 @@ impl Resolver {
                 );
 
                 // Emit checkpoint.triggered event
-                self.trace_collector.emit(
+                self.trace_collector.record(
                     session_id,
                     EventType::CheckpointTriggered,
                     serde_json::json!({
@@ -527,293 +1679,416 @@ impl Resolver {
             }
         }
 
+        for triggered in &checkpoints {
+            self.apply_checkpoint_guidance(session_id, triggered)?;
+        }
+
+        // Custom-triggered checkpoints get a chance to fire before every action
+        checkpoints.extend(self.evaluate_custom_checkpoints(session_id, Some(action_id))?);
+
         // Sort by priority
         checkpoints.sort_by(|a, b| b.priority.cmp(&a.priority));
 
         Ok(checkpoints)
     }
 
-    /// Check if a capability is unlocked for a session
+    /// Evaluate custom-triggered checkpoints from all loaded atlases
+    ///
+    /// Each `CheckpointTrigger::Custom` checkpoint is handed to the callback
+    /// registered for its `trigger_id`. Every evaluation is recorded to TRACE:
+    /// `checkpoint.triggered` when it fires, `checkpoint.skipped` when the
+    /// callback declines or no callback is registered. Fired checkpoints that
+    /// require a response become pending for the session.
+    pub fn evaluate_custom_checkpoints(
+        &mut self,
+        session_id: &str,
+        action_id: Option<&str>,
+    ) -> Result<Vec<TriggeredCheckpoint>> {
+        let session = self.sessions.get(session_id).ok_or_else(|| {
+            CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            }
+        })?;
+
+        let (total_actions, actions_since_checkpoint) = self
+            .checkpoint_states
+            .get(session_id)
+            .map(|s| (s.total_actions, s.action_count))
+            .unwrap_or((session.action_count, 0));
+
+        let mut unlocked_capabilities = self.get_unlocked_capabilities(session_id);
+        unlocked_capabilities.sort();
+
+        let context = CustomTriggerContext {
+            session_id: session_id.to_string(),
+            agent_id: session.agent_id.clone(),
+            goal: session.goal.clone(),
+            action_id: action_id.map(|s| s.to_string()),
+            total_actions,
+            actions_since_checkpoint,
+            unlocked_capabilities,
+        };
+
+        // Evaluate callbacks first (without mutable borrow). Once they have
+        // spent the latency budget, the remaining triggers skip uncalled.
+        let budget = LatencyBudget::start(self.policy_evaluator.latency_budget());
+        let evaluations: Vec<_> = self.atlases.values()
+            .flat_map(|atlas| atlas.get_custom_checkpoints())
+            .filter_map(|def| match &def.trigger {
+                CheckpointTrigger::Custom { trigger_id, params } => {
+                    let exhausted = budget.is_exhausted();
+                    let decision = if exhausted {
+                        None
+                    } else {
+                        self.custom_triggers.evaluate(trigger_id, &context, params)
+                    };
+                    Some((def.clone(), trigger_id.clone(), params.clone(), decision, exhausted))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut checkpoints = Vec::new();
+        for (def, trigger_id, params, decision, exhausted) in evaluations {
+            match decision {
+                Some(CustomTriggerDecision::Fire) => {
+                    self.trace_collector.record(
+                        session_id,
+                        EventType::CheckpointTriggered,
+                        serde_json::json!({
+                            "checkpoint_id": def.checkpoint_id,
+                            "checkpoint_name": def.name,
+                            "trigger_type": "custom",
+                            "trigger_id": trigger_id,
+                            "decision": CustomTriggerDecision::Fire.as_str(),
+                            "mode": format!("{:?}", def.mode).to_lowercase(),
+                            "question_count": def.questions.len(),
+                            "has_guidance": def.guidance.is_some(),
+                            "trigger_action_id": action_id,
+                        }),
+                    )?;
+
+                    let triggered = self.checkpoint_evaluator.evaluate_steward_checkpoint(
+                        &def,
+                        Some(TriggerData::Custom { trigger_id, params }),
+                    );
+
+                    self.apply_checkpoint_guidance(session_id, &triggered)?;
+
+                    if triggered.requires_response() {
+                        self.pending_checkpoints
+                            .entry(session_id.to_string())
+                            .or_default()
+                            .push(triggered.clone());
+                    }
+
+                    checkpoints.push(triggered);
+                }
+                Some(CustomTriggerDecision::Skip) | None => {
+                    self.trace_collector.record(
+                        session_id,
+                        EventType::CheckpointSkipped,
+                        serde_json::json!({
+                            "checkpoint_id": def.checkpoint_id,
+                            "trigger_type": "custom",
+                            "trigger_id": trigger_id,
+                            "decision": CustomTriggerDecision::Skip.as_str(),
+                            "reason": if exhausted {
+                                "latency_budget"
+                            } else if decision.is_some() {
+                                "callback_skipped"
+                            } else {
+                                "no_callback_registered"
+                            },
+                            "trigger_action_id": action_id,
+                        }),
+                    )?;
+                }
+            }
+        }
+
+        checkpoints.sort_by_key(|c| std::cmp::Reverse(c.priority));
+
+        self.persist_session(session_id)?;
+        Ok(checkpoints)
+    }
+
+    /// Check if a capability is unlocked (granted) for a session
     pub fn is_capability_unlocked(&self, session_id: &str, capability_id: &str) -> bool {
-        self.unlocked_capabilities
+        self.capability_states
             .get(session_id)
-            .map(|caps| caps.contains(capability_id))
-            .unwrap_or(false)
+            .and_then(|state| state.status(capability_id))
+            == Some(CapabilityStatus::Granted)
     }
 
-    /// Get all unlocked capabilities for a session
+    /// Get all unlocked (granted) capabilities for a session
     pub fn get_unlocked_capabilities(&self, session_id: &str) -> Vec<String> {
-        self.unlocked_capabilities
+        self.capability_states
             .get(session_id)
-            .map(|caps| caps.iter().cloned().collect())
+            .map(|state| state.granted())
             .unwrap_or_default()
     }
 
-    /// Helper to emit guidance injected event
-    fn emit_guidance_injected(
-        &mut self,
-        session_id: &str,
-        checkpoint_id: &str,
-        guidance: &super::checkpoint::GuidanceBlock,
-    ) -> Result<()> {
-        let guidance_hash = hash_value(&serde_json::json!({
-            "content": guidance.content,
-            "format": format!("{:?}", guidance.format),
-        }));
+    /// Get the capability state for a session
+    pub fn get_capability_state(&self, session_id: &str) -> Option<&CapabilityState> {
+        self.capability_states.get(session_id)
+    }
 
-        self.trace_collector.emit(
-            session_id,
-            EventType::CheckpointGuidanceInjected,
-            serde_json::json!({
-                "checkpoint_id": checkpoint_id,
-                "guidance_format": format!("{:?}", guidance.format).to_lowercase(),
-                "guidance_size_bytes": guidance.content.len(),
-                "guidance_hash": guidance_hash,
-            }),
-        )?;
+    /// Grant a capability to a session (admin API)
+    ///
+    /// Returns true if the capability changed state.
+    pub fn grant_capability(&mut self, session_id: &str, capability_id: &str, actor: &str) -> Result<bool> {
+        let changed = self.admin_transition(session_id, capability_id, CapabilityStatus::Granted, actor)?;
+        self.persist_session(session_id)?;
+        Ok(changed)
+    }
 
-        Ok(())
+    /// Lock a capability for a session (admin API)
+    ///
+    /// Returns true if the capability changed state.
+    pub fn lock_capability(&mut self, session_id: &str, capability_id: &str, actor: &str) -> Result<bool> {
+        let changed = self.admin_transition(session_id, capability_id, CapabilityStatus::Locked, actor)?;
+        self.persist_session(session_id)?;
+        Ok(changed)
     }
 
-    /// End a session
-    pub fn end_session(&mut self, session_id: &str) -> Result<()> {
-        let session = self.sessions.get_mut(session_id).ok_or_else(|| {
-            CRAError::SessionNotFound {
+    /// Restore a session's capability state (e.g. after rehydrating a session)
+    ///
+    /// Use [`CapabilityState::replay`] to rebuild the state from stored TRACE
+    /// events. Restoring does not emit transitions; they are already in the trace.
+    pub fn restore_capability_state(&mut self, session_id: &str, state: CapabilityState) -> Result<()> {
+        if !self.sessions.contains_key(session_id) {
+            return Err(CRAError::SessionNotFound {
                 session_id: session_id.to_string(),
-            }
-        })?;
+            });
+        }
 
-        if !session.is_active {
-            return Err(CRAError::SessionAlreadyEnded {
+        self.capability_states.insert(session_id.to_string(), state);
+        self.persist_session(session_id)?;
+        Ok(())
+    }
+
+    fn admin_transition(
+        &mut self,
+        session_id: &str,
+        capability_id: &str,
+        to: CapabilityStatus,
+        actor: &str,
+    ) -> Result<bool> {
+        if !self.sessions.contains_key(session_id) {
+            return Err(CRAError::SessionNotFound {
                 session_id: session_id.to_string(),
             });
         }
 
-        session.end();
-
-        // Emit session.ended event
-        self.trace_collector.emit(
-            session_id,
-            EventType::SessionEnded,
-            serde_json::json!({
-                "reason": "completed",
-                "duration_ms": session.duration_ms(),
-                "resolution_count": session.resolution_count,
-                "action_count": session.action_count,
-            }),
-        )?;
+        let known = self
+            .atlases
+            .values()
+            .any(|a| a.capabilities.iter().any(|c| c.capability_id == capability_id));
+        if !known {
+            return Err(CRAError::CapabilityNotFound {
+                capability_id: capability_id.to_string(),
+            });
+        }
 
-        // Clean up checkpoint state
-        self.checkpoint_states.remove(session_id);
-        self.pending_checkpoints.remove(session_id);
-        self.unlocked_capabilities.remove(session_id);
+        let source = CapabilityChangeSource::Admin { actor: actor.to_string() };
+        Ok(self.transition_capability(session_id, capability_id, to, source)?.is_some())
+    }
 
-        Ok(())
-    }
-
-    /// Get a session by ID
-    pub fn get_session(&self, session_id: &str) -> Option<&Session> {
-        self.sessions.get(session_id)
-    }
-
-    /// Resolve a CARP request
-    ///
-    /// This is the core resolution function that:
-    /// 1. Validates the request
-    /// 2. Loads relevant atlases
-    /// 3. Evaluates policies for each action
-    /// 4. Assembles the resolution with allowed/denied actions
-    /// 5. Emits TRACE events
-    pub fn resolve(&mut self, request: &CARPRequest) -> Result<CARPResolution> {
-        // Validate request
-        request.validate().map_err(|e| CRAError::InvalidCARPRequest { reason: e })?;
-
-        // Check session exists and is active
-        let session = self.sessions.get_mut(&request.session_id).ok_or_else(|| {
-            CRAError::SessionNotFound {
-                session_id: request.session_id.clone(),
-            }
-        })?;
+    /// Apply the unlock/lock outcome of a completed checkpoint
+    fn apply_capability_outcome(
+        &mut self,
+        session_id: &str,
+        checkpoint_id: &str,
+        unlock: &[String],
+        lock: &[String],
+    ) -> Result<()> {
+        let changes = unlock
+            .iter()
+            .map(|c| (c, CapabilityStatus::Granted))
+            .chain(lock.iter().map(|c| (c, CapabilityStatus::Locked)));
 
-        if !session.is_active {
-            return Err(CRAError::SessionAlreadyEnded {
-                session_id: request.session_id.clone(),
-            });
+        for (capability_id, to) in changes {
+            let source = CapabilityChangeSource::Checkpoint {
+                checkpoint_id: checkpoint_id.to_string(),
+            };
+            self.transition_capability(session_id, capability_id, to, source)?;
         }
 
-        // Generate trace ID for this resolution
-        let trace_id = Uuid::new_v4().to_string();
+        Ok(())
+    }
 
-        // Emit carp.request.received event
-        self.trace_collector.emit(
-            &request.session_id,
-            EventType::CARPRequestReceived,
-            serde_json::json!({
-                "request_id": trace_id,
-                "operation": "resolve",
-                "goal": request.goal,
-                "agent_id": request.agent_id,
-            }),
-        )?;
+    /// Transition a capability and emit capability.state_changed
+    fn transition_capability(
+        &mut self,
+        session_id: &str,
+        capability_id: &str,
+        to: CapabilityStatus,
+        source: CapabilityChangeSource,
+    ) -> Result<Option<CapabilityTransition>> {
+        let transition = self
+            .capability_states
+            .entry(session_id.to_string())
+            .or_default()
+            .transition(capability_id, to, source);
+
+        if let Some(transition) = &transition {
+            self.trace_collector.record(
+                session_id,
+                EventType::CapabilityStateChanged,
+                serde_json::to_value(transition)?,
+            )?;
+        }
 
-        // Collect all actions from loaded atlases
-        let all_actions: Vec<&AtlasAction> = self
-            .atlases
-            .values()
-            .flat_map(|a| a.actions.iter())
-            .collect();
+        Ok(transition)
+    }
 
-        let mut allowed_actions = Vec::new();
-        let mut denied_actions = Vec::new();
-        let mut constraints = Vec::new();
+    /// Get the assembled guidance for a session, in merge order
+    pub fn get_session_guidance(&self, session_id: &str) -> Option<ContextBlock> {
+        self.guidance.get(session_id).and_then(|g| g.to_context_block())
+    }
 
-        // Evaluate each action against policies
-        for action in all_actions {
-            let result = self.policy_evaluator.evaluate(&action.action_id);
+    /// Apply a triggered steward checkpoint to the session's guidance
+    ///
+    /// Non-blocking checkpoints complete as soon as they trigger, so their
+    /// capability changes apply and guidance bound to them expires before
+    /// their own guidance is injected. Blocking checkpoints complete when
+    /// they pass.
+    fn apply_checkpoint_guidance(
+        &mut self,
+        session_id: &str,
+        triggered: &TriggeredCheckpoint,
+    ) -> Result<()> {
+        let Some(def) = &triggered.steward_def else {
+            return Ok(());
+        };
 
-            // Emit policy.evaluated event
-            self.trace_collector.emit(
-                &request.session_id,
-                EventType::PolicyEvaluated,
-                serde_json::json!({
-                    "action_id": action.action_id,
-                    "result": format!("{:?}", result),
-                }),
+        if !triggered.requires_response() {
+            self.apply_capability_outcome(
+                session_id,
+                &def.checkpoint_id,
+                &def.unlock_capabilities,
+                &def.lock_capabilities,
             )?;
-
-            match result {
-                PolicyResult::Deny { policy_id, reason } => {
-                    denied_actions.push(DeniedAction::new(
-                        action.action_id.clone(),
-                        policy_id,
-                        reason,
-                    ));
-                }
-                PolicyResult::RequiresApproval { policy_id } => {
-                    denied_actions.push(DeniedAction::new(
-                        action.action_id.clone(),
-                        policy_id,
-                        "Requires human approval".to_string(),
-                    ));
-                }
-                PolicyResult::RateLimitExceeded { policy_id, retry_after } => {
-                    denied_actions.push(DeniedAction::new(
-                        action.action_id.clone(),
-                        policy_id,
-                        format!("Rate limit exceeded, retry after {} seconds", retry_after),
-                    ));
-                }
-                PolicyResult::Allow | PolicyResult::AllowWithConstraints(_) | PolicyResult::NoMatch => {
-                    allowed_actions.push(AllowedAction {
-                        action_id: action.action_id.clone(),
-                        name: action.name.clone(),
-                        description: Some(action.description.clone()),
-                        parameters_schema: action.parameters_schema.clone(),
-                        risk_tier: action.risk_tier.clone(),
-                    });
-
-                    // Add constraints if any
-                    if let PolicyResult::AllowWithConstraints(constraint_ids) = result {
-                        for constraint_id in constraint_ids {
-                            constraints.push(Constraint::new(
-                                constraint_id.clone(),
-                                crate::carp::ConstraintType::Custom,
-                                format!("Constraint from {}", constraint_id),
-                            ));
-                        }
-                    }
-                }
+            self.complete_checkpoint_guidance(session_id, &def.checkpoint_id)?;
+            if let Some(verbosity) = def.trace_verbosity {
+                self.apply_checkpoint_verbosity(session_id, &def.checkpoint_id, verbosity)?;
             }
         }
 
-        // Determine overall decision
-        let decision = if denied_actions.is_empty() && !allowed_actions.is_empty() {
-            Decision::Allow
-        } else if allowed_actions.is_empty() {
-            Decision::Deny
-        } else if !constraints.is_empty() {
-            Decision::AllowWithConstraints
-        } else {
-            Decision::Partial
-        };
+        if let Some(guidance) = &triggered.guidance {
+            let replaced = self
+                .guidance
+                .entry(session_id.to_string())
+                .or_default()
+                .inject(&def.checkpoint_id, guidance.clone());
 
-        // Update session stats
-        session.resolution_count += 1;
+            self.emit_guidance_injected(session_id, &def.checkpoint_id, guidance)?;
+            for removed in replaced {
+                self.emit_guidance_expired(session_id, &removed, GuidanceRemoval::Replaced, None)?;
+            }
+        }
 
-        // Query context registry for matching context based on goal
-        let context_hints: Vec<String> = request.context_hints.clone().unwrap_or_default();
-        let matching_contexts = self.context_registry.query(&request.goal, None);
+        Ok(())
+    }
 
-        // Convert matching context to ContextBlocks and emit TRACE events
-        let mut context_blocks: Vec<ContextBlock> = Vec::new();
-        for ctx in matching_contexts {
-            // Evaluate conditions with the matcher for fine-grained matching
-            let match_result = self.context_matcher.evaluate(
-                ctx.conditions.as_ref(),
-                &request.goal,
-                None, // TODO: Parse risk tier from request if provided
-                &context_hints,
-                ctx.priority,
-            );
+    /// Switch trace verbosity when a checkpoint that asks for it completes
+    fn apply_checkpoint_verbosity(
+        &mut self,
+        session_id: &str,
+        checkpoint_id: &str,
+        verbosity: TraceVerbosity,
+    ) -> Result<()> {
+        let reason = format!("checkpoint:{}", checkpoint_id);
+        if self.sessions.contains_key(session_id) {
+            return self.set_trace_verbosity(session_id, verbosity, &reason);
+        }
 
-            if match_result.matched {
-                let block = ctx.to_context_block();
+        // Session start checkpoints run before the session is stored;
+        // open_session copies the level over
+        let from = self.trace_collector.verbosity(session_id);
+        if from != verbosity {
+            change_verbosity(&mut self.trace_collector, session_id, from, verbosity, &reason)?;
+        }
+        Ok(())
+    }
 
-                // Emit context.injected TRACE event
-                self.trace_collector.emit(
-                    &request.session_id,
-                    EventType::ContextInjected,
-                    serde_json::json!({
-                        "context_id": block.block_id,
-                        "source_atlas": block.source_atlas,
-                        "priority": block.priority,
-                        "content_type": block.content_type,
-                        "token_estimate": ctx.token_estimate(),
-                        "match_score": match_result.score.total(),
-                    }),
-                )?;
+    /// Expire guidance bound to a completed checkpoint
+    fn complete_checkpoint_guidance(&mut self, session_id: &str, checkpoint_id: &str) -> Result<()> {
+        let expired = self
+            .guidance
+            .get_mut(session_id)
+            .map(|g| g.complete_checkpoint(checkpoint_id))
+            .unwrap_or_default();
 
-                context_blocks.push(block);
-            }
+        for removed in expired {
+            self.emit_guidance_expired(session_id, &removed, GuidanceRemoval::Expired, Some(checkpoint_id))?;
         }
 
-        // Build resolution with injected context
-        let resolution = CARPResolution::builder(request.session_id.clone())
-            .trace_id(trace_id.clone())
-            .decision(decision)
-            .allowed_actions(allowed_actions.clone())
-            .denied_actions(denied_actions.clone())
-            .constraints(constraints)
-            .context_blocks(context_blocks.clone())
-            .ttl_seconds(self.default_ttl)
-            .build();
+        Ok(())
+    }
 
-        // Emit carp.resolution.completed event
-        self.trace_collector.emit(
-            &request.session_id,
-            EventType::CARPResolutionCompleted,
+    /// Helper to emit guidance expired event
+    fn emit_guidance_expired(
+        &mut self,
+        session_id: &str,
+        removed: &ActiveGuidance,
+        reason: GuidanceRemoval,
+        expired_by: Option<&str>,
+    ) -> Result<()> {
+        let guidance_hash = hash_value(&serde_json::json!({
+            "content": removed.block.content,
+            "format": format!("{:?}", removed.block.format),
+        }));
+
+        self.trace_collector.record(
+            session_id,
+            EventType::CheckpointGuidanceExpired,
             serde_json::json!({
-                "resolution_id": trace_id,
-                "decision_type": resolution.decision.to_string(),
-                "allowed_count": allowed_actions.len(),
-                "denied_count": denied_actions.len(),
-                "context_count": context_blocks.len(),
-                "ttl_seconds": self.default_ttl,
+                "checkpoint_id": removed.checkpoint_id,
+                "reason": reason.as_str(),
+                "guidance_hash": guidance_hash,
+                "expired_by": expired_by,
             }),
         )?;
 
-        Ok(resolution)
+        Ok(())
     }
 
-    /// Execute an action within a session
-    pub fn execute(
+    /// Helper to emit guidance injected event
+    fn emit_guidance_injected(
         &mut self,
         session_id: &str,
-        resolution_id: &str,
-        action_id: &str,
-        parameters: Value,
-    ) -> Result<Value> {
-        // Check session exists and is active
+        checkpoint_id: &str,
+        guidance: &super::checkpoint::GuidanceBlock,
+    ) -> Result<()> {
+        let guidance_hash = hash_value(&serde_json::json!({
+            "content": guidance.content,
+            "format": format!("{:?}", guidance.format),
+        }));
+
+        self.trace_collector.record(
+            session_id,
+            EventType::CheckpointGuidanceInjected,
+            serde_json::json!({
+                "checkpoint_id": checkpoint_id,
+                "guidance_format": format!("{:?}", guidance.format).to_lowercase(),
+                "guidance_size_bytes": guidance.content.len(),
+                "guidance_hash": guidance_hash,
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    /// End a session
+    ///
+    /// Active child sessions end with it.
+    pub fn end_session(&mut self, session_id: &str) -> Result<()> {
+        self.end_session_with_reason(session_id, "completed")
+    }
+
+    fn end_session_with_reason(&mut self, session_id: &str, reason: &str) -> Result<()> {
         let session = self.sessions.get_mut(session_id).ok_or_else(|| {
             CRAError::SessionNotFound {
                 session_id: session_id.to_string(),
@@ -826,279 +2101,3709 @@ impl Resolver {
             });
         }
 
-        let execution_id = Uuid::new_v4().to_string();
+        session.end_at(self.clock.now());
 
-        // Emit action.requested event
-        self.trace_collector.emit(
+        // Emit session.ended event
+        self.trace_collector.record(
             session_id,
-            EventType::ActionRequested,
+            EventType::SessionEnded,
             serde_json::json!({
-                "action_id": action_id,
-                "resolution_id": resolution_id,
-                "execution_id": execution_id,
-                "parameters_hash": hash_value(&parameters),
+                "reason": reason,
+                "duration_ms": session.duration_ms(),
+                "resolution_count": session.resolution_count,
+                "action_count": session.action_count,
             }),
         )?;
+        self.observers.session_ended(session, reason);
 
-        // Re-evaluate policy for this action
-        let policy_result = self.policy_evaluator.evaluate(action_id);
+        // Clean up checkpoint state
+        self.checkpoint_states.remove(session_id);
+        self.pending_checkpoints.remove(session_id);
+        self.capability_states.remove(session_id);
+        self.guidance.remove(session_id);
+        self.resolution_pins.retain(|_, pin| pin.session_id != session_id);
+        self.approvals.remove_session(session_id);
+        self.idempotency.remove_session(session_id);
+        self.tokens.remove_session(session_id);
+
+        self.persist_session(session_id)?;
+        self.end_child_sessions(session_id)
+    }
 
-        if let PolicyResult::Deny { policy_id, reason } = policy_result {
-            // Emit action.denied event
-            self.trace_collector.emit(
-                session_id,
-                EventType::ActionDenied,
-                serde_json::json!({
-                    "action_id": action_id,
-                    "reason": reason,
-                    "policy_id": policy_id,
-                }),
-            )?;
+    /// Record that a client re-attached to an active session
+    ///
+    /// For transports where a dropped client reconnects to its session
+    /// (e.g. a resumed MCP bootstrap) instead of starting a new one.
+    /// Appends `session.resumed` with `reason`.
+    pub fn resume_session(&mut self, session_id: &str, reason: &str) -> Result<()> {
+        let session = self.active_session(session_id)?;
+        let payload = serde_json::json!({
+            "reason": reason,
+            "age_ms": (self.clock.now() - session.created_at).num_milliseconds().max(0),
+        });
+
+        self.trace_collector.record(session_id, EventType::SessionResumed, payload)?;
+        Ok(())
+    }
+
+    /// End a session its TTL has run out on
+    ///
+    /// Appends `session.expired` with the `cause` (`idle`, `max_lifetime`
+    /// or an embedder-specific one such as `orphaned`), then ends the
+    /// session with reason `expired`.
+    pub fn expire_session(&mut self, session_id: &str, cause: &str) -> Result<()> {
+        let session = self.active_session(session_id)?;
+        let payload = serde_json::json!({
+            "cause": cause,
+            "age_ms": (self.clock.now() - session.created_at).num_milliseconds().max(0),
+            "resolution_count": session.resolution_count,
+        });
+
+        self.trace_collector.record(session_id, EventType::SessionExpired, payload)?;
+        self.end_session_with_reason(session_id, "expired")
+    }
+
+    fn active_session(&self, session_id: &str) -> Result<&Session> {
+        let session = self.sessions.get(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        Ok(session)
+    }
+
+    /// End the active children of a session that has ended or moved away
+    fn end_child_sessions(&mut self, parent_session_id: &str) -> Result<()> {
+        let children: Vec<String> = self
+            .child_sessions(parent_session_id)
+            .into_iter()
+            .filter(|s| s.is_active)
+            .map(|s| s.session_id.clone())
+            .collect();
+        for child_id in children {
+            self.end_session_with_reason(&child_id, "parent_ended")?;
+        }
+        Ok(())
+    }
+
+    /// Get a session by ID
+    pub fn get_session(&self, session_id: &str) -> Option<&Session> {
+        self.sessions.get(session_id)
+    }
+
+    /// All sessions, active and ended, oldest first
+    pub fn list_sessions(&self) -> Vec<&Session> {
+        let mut sessions: Vec<&Session> = self.sessions.values().collect();
+        sessions.sort_by(|a, b| (a.created_at, &a.session_id).cmp(&(b.created_at, &b.session_id)));
+        sessions
+    }
+
+    /// Hand a session off to another resolver
+    ///
+    /// Emits `session.handoff_out`, ends the session here and returns a
+    /// snapshot signed by the event signer (if any). Sessions with checkpoints
+    /// awaiting a response can't be handed off. Active child sessions end.
+    pub fn export_session(&mut self, session_id: &str, destination: &str) -> Result<SessionSnapshot> {
+        let handoff_error = |reason: &str| CRAError::SessionHandoffError {
+            session_id: session_id.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let session = self.sessions.get(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        if self.has_pending_checkpoints(session_id) {
+            return Err(handoff_error("respond to pending checkpoints first"));
+        }
+        let (resolution_count, action_count) = (session.resolution_count, session.action_count);
+
+        self.trace_collector.flush()?;
+        self.trace_collector.record(
+            session_id,
+            EventType::SessionHandoffOut,
+            serde_json::to_value(SessionHandoffOutPayload {
+                destination: destination.to_string(),
+                resolution_count,
+                action_count,
+            })?,
+        )?;
+        self.trace_collector.flush()?;
+
+        let events = self.trace_collector.get_events(session_id)?;
+        let head = events.last().ok_or_else(|| handoff_error("session has no trace"))?;
+        let chain_head = ChainHead {
+            sequence: head.sequence,
+            event_hash: head.event_hash.clone(),
+        };
+
+        let now = self.clock.now();
+        let session = self.sessions.get_mut(session_id).ok_or_else(|| handoff_error("session disappeared"))?;
+        session.end_at(now);
+        let checkpoint_state = self.checkpoint_states.remove(session_id).unwrap_or_default();
+        let mut matched_keywords: Vec<String> = checkpoint_state.matched_keywords.into_iter().collect();
+        matched_keywords.sort();
+        let mut atlas_ids: Vec<String> = self.atlases.keys().cloned().collect();
+        atlas_ids.sort();
+
+        let mut snapshot = SessionSnapshot {
+            snapshot_version: SNAPSHOT_VERSION.to_string(),
+            session_id: session_id.to_string(),
+            agent_id: session.agent_id.clone(),
+            goal: session.goal.clone(),
+            created_at: session.created_at,
+            resolution_count,
+            action_count,
+            atlas_ids,
+            capability_state: self.capability_states.remove(session_id).unwrap_or_default(),
+            guidance: self.guidance.remove(session_id).unwrap_or_default(),
+            actions_since_checkpoint: checkpoint_state.action_count,
+            matched_keywords,
+            variables: session.variables.clone(),
+            destination: destination.to_string(),
+            exported_at: now,
+            chain_head,
+            events,
+            signature: None,
+        };
+        self.pending_checkpoints.remove(session_id);
+        self.tokens.remove_session(session_id);
+
+        if let Some(signer) = self.trace_collector.signer() {
+            snapshot.signature = Some(signer.sign_hash(&snapshot.digest()));
+        }
+        self.persist_session(session_id)?;
+        // Children can't follow their parent to another resolver
+        self.end_child_sessions(session_id)?;
+        Ok(snapshot)
+    }
+
+    /// Continue a session handed off by another resolver
+    ///
+    /// Checks that the snapshot's chain is intact and ends in
+    /// `session.handoff_out`, that its atlases are loaded here, and (with the
+    /// `signing` feature) that it is signed by a key in
+    /// [`Resolver::trace_keys`]. Unsigned snapshots are only accepted when no
+    /// trace keys are configured. The chain then continues with
+    /// `session.handoff_in`.
+    pub fn import_session(&mut self, snapshot: SessionSnapshot) -> Result<String> {
+        let session_id = snapshot.session_id.clone();
+        let handoff_error = |reason: String| CRAError::SessionHandoffError {
+            session_id: session_id.clone(),
+            reason,
+        };
+
+        if self.sessions.contains_key(&session_id) || self.trace_collector.has_session(&session_id) {
+            return Err(CRAError::SessionAlreadyExists { session_id });
+        }
+        snapshot.check_continuity().map_err(handoff_error)?;
+
+        let verification = crate::trace::ChainVerifier::verify(&snapshot.events);
+        if !verification.is_valid {
+            return Err(CRAError::TraceChainIntegrityError {
+                reason: verification.error_message.unwrap_or_default(),
+            });
+        }
+
+        let digest = snapshot.digest();
+        #[cfg(feature = "signing")]
+        {
+            let keys = self.trace_keys();
+            match &snapshot.signature {
+                Some(signature) => crate::crypto::KeyRing::from_keys(&keys)?
+                    .verify_hash(signature, &digest, snapshot.exported_at)
+                    .map_err(handoff_error)?,
+                None if !keys.is_empty() => return Err(handoff_error("snapshot is not signed".to_string())),
+                None => {}
+            }
+        }
+
+        if let Some(missing) = snapshot.atlas_ids.iter().find(|id| !self.atlases.contains_key(*id)) {
+            return Err(CRAError::AtlasNotFound {
+                atlas_id: missing.clone(),
+            });
+        }
+
+        let source_event_hash = snapshot.chain_head.event_hash.clone();
+        self.trace_collector.import_chain(&session_id, snapshot.events)?;
+        self.trace_collector.record(
+            &session_id,
+            EventType::SessionHandoffIn,
+            serde_json::to_value(SessionHandoffInPayload {
+                source_event_hash,
+                snapshot_digest: digest,
+                signed_by: snapshot.signature.map(|s| s.key_id),
+            })?,
+        )?;
+
+        let mut checkpoint_state = SessionCheckpointState::new();
+        checkpoint_state.action_count = snapshot.actions_since_checkpoint;
+        checkpoint_state.total_actions = snapshot.action_count;
+        checkpoint_state.matched_keywords = snapshot.matched_keywords.into_iter().collect();
+        self.checkpoint_states.insert(session_id.clone(), checkpoint_state);
+        self.capability_states.insert(session_id.clone(), snapshot.capability_state);
+        self.guidance.insert(session_id.clone(), snapshot.guidance);
+
+        let mut session = Session::new(session_id.clone(), snapshot.agent_id, snapshot.goal);
+        session.created_at = snapshot.created_at;
+        session.resolution_count = snapshot.resolution_count;
+        session.action_count = snapshot.action_count;
+        session.variables = snapshot.variables;
+        self.sessions.insert(session_id.clone(), session);
+
+        self.persist_session(&session_id)?;
+        Ok(session_id)
+    }
+
+    /// Serialize the resolver's full state
+    ///
+    /// Captures loaded atlases, sessions with their checkpoint and capability
+    /// state, every TRACE chain and its head, and rate limit counters, in the
+    /// versioned, digest-checked encoding described in
+    /// [`ResolverState`]. Pending trace events are flushed first.
+    pub fn snapshot(&mut self) -> Result<Vec<u8>> {
+        self.trace_collector.flush()?;
+
+        let mut atlases: Vec<AtlasManifest> = self.atlases.values().cloned().collect();
+        atlases.sort_by(|a, b| a.atlas_id.cmp(&b.atlas_id));
+
+        let mut sessions: Vec<SessionSnapshotState> =
+            self.sessions.values().map(|session| self.session_state(session)).collect();
+        sessions.sort_by(|a, b| a.session.session_id.cmp(&b.session.session_id));
+
+        let mut session_ids: Vec<String> = self.trace_collector.session_ids().into_iter().map(String::from).collect();
+        session_ids.sort();
+        let mut traces = Vec::with_capacity(session_ids.len());
+        for session_id in session_ids {
+            let events = self.trace_collector.get_events(&session_id)?;
+            let Some(head) = events.last() else { continue };
+            let chain_head = ChainHead {
+                sequence: head.sequence,
+                event_hash: head.event_hash.clone(),
+            };
+            traces.push(TraceChainSnapshot { session_id, chain_head, events });
+        }
+
+        ResolverState {
+            created_at: self.clock.now(),
+            default_ttl: self.default_ttl,
+            atlases,
+            sessions,
+            traces,
+            rate_limits: self.policy_evaluator.rate_limit_counters(),
+            retired_trace_keys: self.retired_trace_keys.clone(),
+        }
+        .encode()
+    }
+
+    /// Cut a batch of changes since a read replica's cursor
+    ///
+    /// Holds up to `max_events` new TRACE events (whole sessions' backlogs
+    /// are split across batches), the current records of sessions with new
+    /// events, and every atlas if the loaded set changed. Pending trace
+    /// events are flushed first. See [`crate::replication`].
+    #[cfg(not(feature = "minimal"))]
+    pub fn replication_batch(&mut self, since: &ReplicationCursor, max_events: usize) -> Result<ReplicationBatch> {
+        self.trace_collector.flush()?;
+
+        let mut cursor = since.clone();
+        let mut events = Vec::new();
+        let mut session_ids: Vec<String> = self.trace_collector.session_ids().into_iter().map(String::from).collect();
+        session_ids.sort();
+        for session_id in session_ids {
+            let budget = max_events.saturating_sub(events.len());
+            if budget == 0 {
+                break;
+            }
+            let held = since.traces.get(&session_id).copied().unwrap_or(0);
+            let new = self.trace_collector.get_events_page(&session_id, held, budget)?;
+            if !new.is_empty() {
+                cursor.traces.insert(session_id, held + new.len());
+                events.extend(new);
+            }
+        }
+
+        let mut sessions: Vec<Session> = cursor
+            .traces
+            .iter()
+            .filter(|(id, count)| since.traces.get(*id) != Some(count))
+            .filter_map(|(id, _)| self.sessions.get(id).cloned())
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+        let atlas_versions: BTreeMap<String, String> =
+            self.atlases.values().map(|a| (a.atlas_id.clone(), a.version.clone())).collect();
+        let atlases = (atlas_versions != since.atlas_versions).then(|| {
+            let mut atlases: Vec<AtlasManifest> = self.atlases.values().cloned().collect();
+            atlases.sort_by(|a, b| a.atlas_id.cmp(&b.atlas_id));
+            atlases
+        });
+        cursor.atlas_versions = atlas_versions;
+
+        Ok(ReplicationBatch {
+            created_at: self.clock.now(),
+            since: since.clone(),
+            cursor,
+            events,
+            sessions,
+            atlases,
+        })
+    }
+
+    /// Cursor a replica holding all of this resolver's state would have
+    #[cfg(not(feature = "minimal"))]
+    pub fn replication_cursor(&mut self) -> Result<ReplicationCursor> {
+        self.trace_collector.flush()?;
+        Ok(ReplicationCursor {
+            traces: self
+                .trace_collector
+                .session_ids()
+                .into_iter()
+                .filter_map(|id| Some((id.to_string(), self.trace_collector.event_count(id)?)))
+                .collect(),
+            atlas_versions: self.atlases.values().map(|a| (a.atlas_id.clone(), a.version.clone())).collect(),
+        })
+    }
+
+    /// Checkpoint and capability state of one session, in portable form
+    fn session_state(&self, session: &Session) -> SessionSnapshotState {
+        let id = &session.session_id;
+        let checkpoint_state = self.checkpoint_states.get(id);
+        let mut matched_keywords: Vec<String> = checkpoint_state
+            .map(|s| s.matched_keywords.iter().cloned().collect())
+            .unwrap_or_default();
+        matched_keywords.sort();
+        SessionSnapshotState {
+            session: session.clone(),
+            capability_state: self.capability_states.get(id).cloned().unwrap_or_default(),
+            guidance: self.guidance.get(id).cloned().unwrap_or_default(),
+            pending_checkpoints: self.pending_checkpoints.get(id).cloned().unwrap_or_default(),
+            actions_since_checkpoint: checkpoint_state.map(|s| s.action_count).unwrap_or(0),
+            since_checkpoint_ms: checkpoint_state
+                .map(|s| s.last_checkpoint.elapsed().as_millis() as u64)
+                .unwrap_or(0),
+            matched_keywords,
+        }
+    }
+
+    /// Install a session from its portable state
+    fn install_session_state(&mut self, entry: SessionSnapshotState) {
+        let now = crate::clock::Instant::now();
+        let session_id = entry.session.session_id.clone();
+        let mut checkpoint_state = SessionCheckpointState::new();
+        checkpoint_state.action_count = entry.actions_since_checkpoint;
+        checkpoint_state.total_actions = entry.session.action_count;
+        checkpoint_state.matched_keywords = entry.matched_keywords.into_iter().collect();
+        checkpoint_state.last_checkpoint = now
+            .checked_sub(std::time::Duration::from_millis(entry.since_checkpoint_ms))
+            .unwrap_or(now);
+
+        // Ended sessions keep only the session record, as after end_session
+        if entry.session.is_active {
+            self.checkpoint_states.insert(session_id.clone(), checkpoint_state);
+            self.capability_states.insert(session_id.clone(), entry.capability_state);
+            self.guidance.insert(session_id.clone(), entry.guidance);
+            if !entry.pending_checkpoints.is_empty() {
+                self.pending_checkpoints.insert(session_id.clone(), entry.pending_checkpoints);
+            }
+        }
+        if !entry.session.trace_verbosity.is_standard() {
+            self.trace_collector.set_verbosity(&session_id, entry.session.trace_verbosity);
+        }
+        self.sessions.insert(session_id, entry.session);
+    }
+
+    /// Write a session's state and the rate limit counters to the session store
+    fn persist_session(&self, session_id: &str) -> Result<()> {
+        let (Some(store), Some(session)) = (&self.session_store, self.sessions.get(session_id)) else {
+            return Ok(());
+        };
+        store.save_session(&self.session_state(session))?;
+        store.save_rate_limits(&self.policy_evaluator.rate_limit_counters())
+    }
+
+    /// Reload sessions from the session store after a restart
+    ///
+    /// Installs every stored session this resolver doesn't already hold and
+    /// restores the rate limit counters. When trace storage is attached,
+    /// each recovered session's TRACE chain is reloaded and verified so new
+    /// events continue it. Atlases aren't session state; load them first.
+    /// Returns the number of sessions recovered.
+    pub fn recover_sessions(&mut self) -> Result<usize> {
+        let Some(store) = self.session_store.clone() else {
+            return Ok(0);
+        };
+
+        let mut recovered = 0;
+        for session_id in store.list_sessions()? {
+            if self.sessions.contains_key(&session_id) {
+                continue;
+            }
+            let Some(state) = store.load_session(&session_id)? else { continue };
+            if !self.trace_collector.has_session(&session_id) {
+                self.trace_collector.resume_from_storage(&session_id)?;
+            }
+            self.install_session_state(state);
+            recovered += 1;
+        }
+
+        self.policy_evaluator.restore_rate_limit_counters(store.load_rate_limits()?);
+        Ok(recovered)
+    }
+
+    /// Restore state captured by [`Resolver::snapshot`]
+    ///
+    /// The resolver must be freshly configured: no atlases, sessions or
+    /// traces. Runtime configuration (clock, ID generator, event signer,
+    /// custom triggers and validators) is not part of a snapshot and should
+    /// be set up as on the original resolver. Every TRACE chain is verified
+    /// before anything is restored, and chains continue where they left off.
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<()> {
+        if !self.atlases.is_empty() || !self.sessions.is_empty() || !self.trace_collector.session_ids().is_empty() {
+            return Err(CRAError::ResolverSnapshotError {
+                reason: "restore requires a resolver without atlases, sessions or traces".to_string(),
+            });
+        }
+        let state = ResolverState::decode(bytes)?;
+        state.verify_traces()?;
+
+        for atlas in state.atlases {
+            self.load_atlas(atlas)?;
+        }
+        for trace in state.traces {
+            self.trace_collector.import_chain(&trace.session_id, trace.events)?;
+        }
+
+        for entry in state.sessions {
+            self.install_session_state(entry);
+        }
+
+        self.policy_evaluator.restore_rate_limit_counters(state.rate_limits);
+        self.retired_trace_keys = state.retired_trace_keys;
+        self.default_ttl = state.default_ttl;
+        Ok(())
+    }
+
+    /// Resolve a CARP request
+    ///
+    /// This is the core resolution function that:
+    /// 1. Validates the request
+    /// 2. Loads relevant atlases
+    /// 3. Evaluates policies for each action
+    /// 4. Assembles the resolution with allowed/denied actions
+    /// 5. Emits TRACE events
+    pub fn resolve(&mut self, request: &CARPRequest) -> Result<CARPResolution> {
+        let start = Instant::now();
+        let result = self.resolve_request(request);
+
+        match &result {
+            Ok(resolution) => {
+                let decision = resolution.decision.to_string();
+                self.metrics.increment(metrics::RESOLUTIONS_TOTAL, &[("decision", &decision)], 1);
+                self.observers.resolved(request, resolution);
+            }
+            Err(e) => self.metrics.increment(metrics::RESOLVE_ERRORS_TOTAL, &[("code", e.error_code())], 1),
+        }
+        self.metrics.observe(metrics::RESOLVE_DURATION_US, &[], start.elapsed().as_micros() as u64);
+        self.persist_session(&request.session_id)?;
+        result
+    }
+
+    /// Resolve many CARP requests in one call
+    ///
+    /// Requests are resolved in order, each as by [`Resolver::resolve`]; a
+    /// failing request is reported in its slot without affecting the rest.
+    /// Fails as a whole only for an empty batch or one larger than
+    /// [`MAX_BATCH_SIZE`](super::MAX_BATCH_SIZE).
+    pub fn resolve_batch(&mut self, requests: &[CARPRequest]) -> Result<BatchResolveResponse> {
+        batch::validate_batch(requests)?;
+        Ok(BatchResolveResponse::from_results(
+            requests.iter().map(|request| (request, self.resolve(request))),
+        ))
+    }
+
+    fn resolve_request(&mut self, request: &CARPRequest) -> Result<CARPResolution> {
+        // Validate request
+        request.validate().map_err(|e| CRAError::InvalidCARPRequest { reason: e })?;
+
+        // Child sessions are limited by their ancestors' capabilities too
+        let lineage = self.lineage(&request.session_id);
+
+        // Check session exists and is active
+        let session = self.sessions.get_mut(&request.session_id).ok_or_else(|| {
+            CRAError::SessionNotFound {
+                session_id: request.session_id.clone(),
+            }
+        })?;
+
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: request.session_id.clone(),
+            });
+        }
+        let variables = session.variables.clone();
+        let session_context = session_context(session);
+
+        // A session whose chain is broken gets no actions until released
+        if self.integrity_violations.get(&request.session_id).is_some_and(|v| v.quarantined) {
+            return Ok(CARPResolution::builder(request.session_id.clone())
+                .trace_id(self.ids.next_id())
+                .decision(Decision::Quarantined)
+                .ttl_seconds(0)
+                .timestamp(self.clock.now())
+                .build());
+        }
+
+        // Generate trace ID for this resolution
+        let trace_id = self.ids.next_id();
+
+        // Emit carp.request.received event
+        let mut received = serde_json::json!({
+            "request_id": trace_id,
+            "operation": "resolve",
+            "goal": request.goal,
+            "agent_id": request.agent_id,
+        });
+        if self.trace_collector.verbosity(&request.session_id) == TraceVerbosity::Debug {
+            received["request"] = serde_json::to_value(request)?;
+        }
+        self.trace_collector.record(&request.session_id, EventType::CARPRequestReceived, received)?;
+
+        // Collect all actions from loaded atlases
+        let all_actions: Vec<&AtlasAction> = self
+            .atlases
+            .values()
+            .flat_map(|a| a.actions.iter())
+            .collect();
+
+        let mut allowed_actions = Vec::new();
+        let mut denied_actions = Vec::new();
+        let mut constraints = Vec::new();
+
+        // Evaluate each action against policies
+        let mut budget = LatencyBudget::start(self.policy_evaluator.latency_budget());
+        for action in all_actions {
+            if let Some((capability_id, status)) =
+                lineage_gate(&self.atlases, &self.capability_states, &lineage, &action.action_id)
+            {
+                denied_actions.push(DeniedAction::new(
+                    action.action_id.clone(),
+                    format!("capability:{}", capability_id),
+                    format!("Capability '{}' is {}", capability_id, status),
+                ));
+                continue;
+            }
+
+            if self.strict_mode && granting_capabilities(&self.atlases, &action.action_id).is_empty() {
+                denied_actions.push(DeniedAction::new(
+                    action.action_id.clone(),
+                    STRICT_MODE_POLICY_ID.to_string(),
+                    STRICT_MODE_REASON.to_string(),
+                ));
+                continue;
+            }
+
+            let mut result = self.policy_evaluator.evaluate(&action.action_id);
+            if matches!(result, PolicyResult::Allow | PolicyResult::AllowWithConstraints(_) | PolicyResult::NoMatch) {
+                let input = serde_json::json!({
+                    "operation": "resolve",
+                    "action_id": action.action_id,
+                    "parameters": Value::Null,
+                    "session": session_context,
+                });
+                if let Some(denial) = external_gate(
+                    &self.external_policies,
+                    self.policy_evaluator.external_policies(&action.action_id),
+                    &mut self.trace_collector,
+                    &request.session_id,
+                    &action.action_id,
+                    input,
+                    &mut budget,
+                )? {
+                    result = denial;
+                }
+            }
+            self.metrics.increment(metrics::POLICY_DECISIONS_TOTAL, &[("result", policy_result_label(&result))], 1);
+
+            // Emit policy.evaluated event
+            self.trace_collector.record(
+                &request.session_id,
+                EventType::PolicyEvaluated,
+                serde_json::json!({
+                    "action_id": action.action_id,
+                    "result": format!("{:?}", result),
+                }),
+            )?;
+
+            match result {
+                PolicyResult::Deny { policy_id, reason } => {
+                    denied_actions.push(DeniedAction::new(
+                        action.action_id.clone(),
+                        policy_id,
+                        reason,
+                    ));
+                }
+                PolicyResult::RequiresApproval { policy_id } => {
+                    denied_actions.push(DeniedAction::new(
+                        action.action_id.clone(),
+                        policy_id,
+                        "Requires human approval".to_string(),
+                    ));
+                }
+                PolicyResult::RateLimitExceeded { policy_id, retry_after } => {
+                    denied_actions.push(DeniedAction::new(
+                        action.action_id.clone(),
+                        policy_id,
+                        format!("Rate limit exceeded, retry after {} seconds", retry_after),
+                    ));
+                }
+                PolicyResult::Allow | PolicyResult::AllowWithConstraints(_) | PolicyResult::NoMatch => {
+                    let unknown = |variable: String| CRAError::UnknownSessionVariable {
+                        variable,
+                        action_id: action.action_id.clone(),
+                    };
+                    let mut parameters_schema = action.parameters_schema.clone();
+                    template::render_schema_defaults(&mut parameters_schema, &variables).map_err(unknown)?;
+                    let executor = action
+                        .executor
+                        .as_deref()
+                        .map(|e| template::render(e, &variables))
+                        .transpose()
+                        .map_err(unknown)?;
+
+                    allowed_actions.push(AllowedAction {
+                        action_id: action.action_id.clone(),
+                        name: action.name.clone(),
+                        description: Some(action.description.clone()),
+                        parameters_schema,
+                        risk_tier: action.risk_tier.clone(),
+                        executor,
+                    });
+
+                    // Add constraints if any
+                    if let PolicyResult::AllowWithConstraints(constraint_ids) = result {
+                        for constraint_id in constraint_ids {
+                            constraints.push(Constraint::new(
+                                constraint_id.clone(),
+                                crate::carp::ConstraintType::Custom,
+                                format!("Constraint from {}", constraint_id),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Narrow to what the request asked for
+        let narrowing = (request.requested_actions.is_some() || request.max_risk_tier.is_some()).then(|| {
+            let mut narrowing = Narrowing {
+                requested_actions: request.requested_actions.clone(),
+                max_risk_tier: request.max_risk_tier,
+                excluded_actions: Vec::new(),
+            };
+            allowed_actions.retain(|action: &AllowedAction| {
+                let admitted = narrowing.admits(&action.action_id, &action.risk_tier);
+                if !admitted {
+                    narrowing.excluded_actions.push(action.action_id.clone());
+                }
+                admitted
+            });
+            narrowing
+        });
+
+        // Determine overall decision
+        let decision = if denied_actions.is_empty() && !allowed_actions.is_empty() {
+            Decision::Allow
+        } else if allowed_actions.is_empty() {
+            Decision::Deny
+        } else if !constraints.is_empty() {
+            Decision::AllowWithConstraints
+        } else {
+            Decision::Partial
+        };
+
+        // Update session stats
+        session.resolution_count += 1;
+
+        // Query context registry for matching context based on goal
+        let context_hints: Vec<String> = request.context_hints.clone().unwrap_or_default();
+        let matching_contexts = self.context_registry.query(&request.goal, None);
+
+        // Convert matching context to ContextBlocks and emit TRACE events
+        let mut context_blocks: Vec<ContextBlock> = Vec::new();
+        for ctx in matching_contexts {
+            // Evaluate conditions with the matcher for fine-grained matching
+            let match_result = self.context_matcher.evaluate(
+                ctx.conditions.as_ref(),
+                &request.goal,
+                request.risk_tier,
+                &context_hints,
+                ctx.priority,
+            );
+
+            if match_result.matched {
+                let mut block = ctx.to_context_block();
+                let content_hash = self.content_store.put(&block.content);
+
+                // Emit context.injected TRACE event
+                self.trace_collector.record(
+                    &request.session_id,
+                    EventType::ContextInjected,
+                    serde_json::json!({
+                        "context_id": block.block_id,
+                        "source_atlas": block.source_atlas,
+                        "priority": block.priority,
+                        "content_type": block.content_type,
+                        "content_hash": content_hash,
+                        "token_estimate": ctx.token_estimate(),
+                        "match_score": match_result.score.total(),
+                    }),
+                )?;
+
+                block.content_hash = Some(content_hash);
+                context_blocks.push(block);
+            }
+        }
+
+        // Include assembled checkpoint guidance
+        if let Some(mut block) = self.get_session_guidance(&request.session_id) {
+            let content_hash = self.content_store.put(&block.content);
+            self.trace_collector.record(
+                &request.session_id,
+                EventType::ContextInjected,
+                serde_json::json!({
+                    "context_id": block.block_id,
+                    "source_atlas": block.source_atlas,
+                    "priority": block.priority,
+                    "content_type": block.content_type,
+                    "content_hash": content_hash,
+                    "token_estimate": block.content.len() / 4,
+                    "source": "checkpoint_guidance",
+                }),
+            )?;
+
+            block.content_hash = Some(content_hash);
+            context_blocks.push(block);
+        }
+        if self.reference_context {
+            context_blocks.iter_mut().for_each(|b| b.content.clear());
+        }
+
+        // Pin the atlas versions this resolution was made against
+        let atlas_versions = self.atlas_versions();
+        self.resolution_pins.insert(
+            trace_id.clone(),
+            ResolutionPin {
+                session_id: request.session_id.clone(),
+                atlas_versions: atlas_versions.clone(),
+                invalidated: None,
+                narrowing: narrowing.clone(),
+                expires_at: self.clock.now() + chrono::Duration::seconds(self.default_ttl as i64),
+            },
+        );
+
+        // Build resolution with injected context
+        let mut builder = CARPResolution::builder(request.session_id.clone())
+            .trace_id(trace_id.clone())
+            .decision(decision)
+            .allowed_actions(allowed_actions.clone())
+            .denied_actions(denied_actions.clone())
+            .constraints(constraints)
+            .context_blocks(context_blocks.clone())
+            .ttl_seconds(self.default_ttl)
+            .timestamp(self.clock.now())
+            .atlas_versions(atlas_versions)
+            .degraded_evaluations(budget.degraded);
+        if let Some(narrowing) = narrowing {
+            builder = builder.narrowing(narrowing);
+        }
+        let resolution = builder.build();
+
+        // Emit carp.resolution.completed event
+        let mut completed = serde_json::json!({
+            "resolution_id": trace_id,
+            "decision_type": resolution.decision.to_string(),
+            "allowed_count": allowed_actions.len(),
+            "denied_count": denied_actions.len(),
+            "context_count": context_blocks.len(),
+            "ttl_seconds": self.default_ttl,
+        });
+        if let Some(narrowing) = &resolution.narrowing {
+            completed["narrowing"] = serde_json::to_value(narrowing)?;
+        }
+        if resolution.is_degraded() {
+            completed["degraded_evaluations"] = serde_json::to_value(&resolution.degraded_evaluations)?;
+        }
+        self.trace_collector.record(&request.session_id, EventType::CARPResolutionCompleted, completed)?;
+
+        Ok(resolution)
+    }
+
+    /// Resolve a CARP request at most once per idempotency key
+    ///
+    /// The first successful resolution for a (session, key) pair is returned
+    /// again for retries with the same key until the idempotency TTL passes;
+    /// replays are recorded as `carp.request.replayed`. Reusing a key for a
+    /// different request fails with `IdempotencyKeyConflict`. Failed calls
+    /// aren't stored, so they can be retried with the same key.
+    pub fn resolve_idempotent(&mut self, request: &CARPRequest, idempotency_key: &str) -> Result<CARPResolution> {
+        let request_hash = hash_request(&serde_json::to_value(request)?);
+        if let Some(response) = self.replay(&request.session_id, idempotency_key, "resolve", &request_hash)? {
+            return Ok(serde_json::from_value(response)?);
+        }
+
+        let resolution = self.resolve(request)?;
+        self.idempotency.store(
+            &request.session_id,
+            idempotency_key,
+            "resolve",
+            &request_hash,
+            serde_json::to_value(&resolution)?,
+            self.clock.now(),
+        );
+        Ok(resolution)
+    }
+
+    /// Execute an action at most once per idempotency key
+    ///
+    /// See [`Resolver::resolve_idempotent`]. A retried call returns the first
+    /// call's result without running the action again.
+    pub fn execute_idempotent(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: Value,
+        idempotency_key: &str,
+    ) -> Result<Value> {
+        let request_hash = hash_request(&serde_json::json!({
+            "resolution_id": resolution_id,
+            "action_id": action_id,
+            "parameters": parameters,
+        }));
+        if let Some(response) = self.replay(session_id, idempotency_key, "execute", &request_hash)? {
+            return Ok(response);
+        }
+
+        let result = self.execute(session_id, resolution_id, action_id, parameters)?;
+        self.idempotency.store(
+            session_id,
+            idempotency_key,
+            "execute",
+            &request_hash,
+            result.clone(),
+            self.clock.now(),
+        );
+        Ok(result)
+    }
+
+    /// Return the stored response for an idempotency key, recording the replay
+    fn replay(&mut self, session_id: &str, key: &str, operation: &str, request_hash: &str) -> Result<Option<Value>> {
+        if !self.sessions.contains_key(session_id) {
+            return Err(CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            });
+        }
+        let Some(record) = self.idempotency.lookup(session_id, key, operation, request_hash, self.clock.now())? else {
+            return Ok(None);
+        };
+        let response = record.response.clone();
+        let original_at = record.created_at;
+
+        self.trace_collector.record(
+            session_id,
+            EventType::CARPRequestReplayed,
+            serde_json::json!({
+                "operation": operation,
+                "idempotency_key": key,
+                "request_hash": request_hash,
+                "original_at": original_at,
+            }),
+        )?;
+        Ok(Some(response))
+    }
+
+    /// Execute an action within a session
+    pub fn execute(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: Value,
+    ) -> Result<Value> {
+        self.run_execute(session_id, resolution_id, action_id, parameters, None)
+    }
+
+    /// Mint a capability token authorizing only `actions` in a session
+    ///
+    /// `actions` are action IDs or namespace patterns. The token is valid
+    /// for `ttl`, in this session only, with
+    /// [`Resolver::execute_with_token`]. It never widens what the session
+    /// may do. The minting is recorded as `capability_token.minted`; the
+    /// token itself is not.
+    pub fn mint_capability_token(
+        &mut self,
+        session_id: &str,
+        actions: &[String],
+        ttl: std::time::Duration,
+    ) -> Result<String> {
+        let session = self.sessions.get(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        if actions.is_empty() {
+            return Err(CRAError::InvalidCARPRequest {
+                reason: "a capability token must name at least one action".to_string(),
+            });
+        }
+        let ttl = chrono::Duration::from_std(ttl).map_err(|e| CRAError::InvalidCARPRequest {
+            reason: format!("invalid capability token ttl: {}", e),
+        })?;
+
+        let issued_at = self.clock.now();
+        let token = CapabilityToken {
+            token_id: self.ids.next_id(),
+            session_id: session_id.to_string(),
+            actions: actions.to_vec(),
+            issued_at,
+            expires_at: issued_at + ttl,
+        };
+        self.trace_collector.record(
+            session_id,
+            EventType::CapabilityTokenMinted,
+            serde_json::json!({
+                "token_id": token.token_id,
+                "actions": token.actions,
+                "expires_at": token.expires_at,
+            }),
+        )?;
+        Ok(self.tokens.mint(&token))
+    }
+
+    /// Revoke a capability token before it expires
+    ///
+    /// Returns false if the token is unknown, already revoked or its
+    /// session has ended.
+    pub fn revoke_capability_token(&mut self, token_id: &str, actor: &str) -> Result<bool> {
+        let Some(session_id) = self.tokens.revoke(token_id) else {
+            return Ok(false);
+        };
+        self.trace_collector.record(
+            &session_id,
+            EventType::CapabilityTokenRevoked,
+            serde_json::json!({
+                "token_id": token_id,
+                "actor": actor,
+            }),
+        )?;
+        Ok(true)
+    }
+
+    /// Execute an action presenting a capability token
+    ///
+    /// Runs every check [`Resolver::execute`] does, and is also denied
+    /// (policy `capability_token`) unless the token was minted for this
+    /// session, covers the action, hasn't expired and hasn't been revoked.
+    pub fn execute_with_token(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: Value,
+        token: &str,
+    ) -> Result<Value> {
+        self.run_execute(session_id, resolution_id, action_id, parameters, Some(token))
+    }
+
+    fn run_execute(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: Value,
+        token: Option<&str>,
+    ) -> Result<Value> {
+        let start = Instant::now();
+        let result = self.execute_action(session_id, resolution_id, action_id, parameters, token);
+
+        let outcome = match &result {
+            Ok(_) => "executed".to_string(),
+            Err(e) => e.error_code().to_lowercase(),
+        };
+        self.metrics.increment(metrics::EXECUTIONS_TOTAL, &[("outcome", &outcome)], 1);
+        self.metrics.observe(metrics::EXECUTE_DURATION_US, &[], start.elapsed().as_micros() as u64);
+        self.observers.executed(session_id, action_id, &result);
+        self.persist_session(session_id)?;
+        result
+    }
+
+    fn execute_action(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: Value,
+        token: Option<&str>,
+    ) -> Result<Value> {
+        // Check session exists and is active
+        let session = self.sessions.get_mut(session_id).ok_or_else(|| {
+            CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            }
+        })?;
+
+        if !session.is_active {
+            return Err(CRAError::SessionAlreadyEnded {
+                session_id: session_id.to_string(),
+            });
+        }
+        let session_context = session_context(session);
+
+        if let Some(violation) = self.integrity_violations.get(session_id).filter(|v| v.quarantined) {
+            return Err(CRAError::SessionQuarantined {
+                session_id: session_id.to_string(),
+                reason: violation.error.clone(),
+            });
+        }
+
+        // Resolutions made against an unloaded or upgraded atlas must be re-resolved
+        self.check_resolution_pin(session_id, resolution_id)?;
+
+        let execution_id = self.ids.next_id();
+
+        // Emit action.requested event
+        let mut requested = serde_json::json!({
+            "action_id": action_id,
+            "resolution_id": resolution_id,
+            "execution_id": execution_id,
+            "parameters_hash": hash_value(&parameters),
+        });
+        if self.trace_collector.verbosity(session_id) == TraceVerbosity::Debug {
+            requested["parameters"] = parameters.clone();
+        }
+        self.trace_collector.record(session_id, EventType::ActionRequested, requested)?;
+
+        // Re-evaluate policy for this action
+        let mut policy_result = self.policy_evaluator.evaluate(action_id);
+        if matches!(policy_result, PolicyResult::Allow | PolicyResult::AllowWithConstraints(_) | PolicyResult::NoMatch) {
+            let input = serde_json::json!({
+                "operation": "execute",
+                "action_id": action_id,
+                "parameters": parameters,
+                "session": session_context,
+            });
+            let mut budget = LatencyBudget::start(self.policy_evaluator.latency_budget());
+            if let Some(denial) = external_gate(
+                &self.external_policies,
+                self.policy_evaluator.external_policies(action_id),
+                &mut self.trace_collector,
+                session_id,
+                action_id,
+                input,
+                &mut budget,
+            )? {
+                policy_result = denial;
+            }
+        }
+
+        if let PolicyResult::Deny { policy_id, reason } = policy_result {
+            // Emit action.denied event
+            self.trace_collector.record(
+                session_id,
+                EventType::ActionDenied,
+                serde_json::json!({
+                    "action_id": action_id,
+                    "reason": reason,
+                    "policy_id": policy_id,
+                }),
+            )?;
+
+            return Err(CRAError::ActionDenied { policy_id, reason });
+        }
+
+        // Check the capability state of the session and its ancestors
+        let lineage = self.lineage(session_id);
+        if let Some((capability_id, status)) =
+            lineage_gate(&self.atlases, &self.capability_states, &lineage, action_id)
+        {
+            let policy_id = format!("capability:{}", capability_id);
+            let reason = format!("Capability '{}' is {}", capability_id, status);
+
+            self.trace_collector.record(
+                session_id,
+                EventType::ActionDenied,
+                serde_json::json!({
+                    "action_id": action_id,
+                    "reason": reason,
+                    "policy_id": policy_id,
+                }),
+            )?;
+
+            return Err(CRAError::ActionDenied { policy_id, reason });
+        }
+
+        if self.strict_mode && granting_capabilities(&self.atlases, action_id).is_empty() {
+            self.trace_collector.record(
+                session_id,
+                EventType::ActionDenied,
+                serde_json::json!({
+                    "action_id": action_id,
+                    "reason": STRICT_MODE_REASON,
+                    "policy_id": STRICT_MODE_POLICY_ID,
+                }),
+            )?;
+
+            return Err(CRAError::ActionDenied {
+                policy_id: STRICT_MODE_POLICY_ID.to_string(),
+                reason: STRICT_MODE_REASON.to_string(),
+            });
+        }
+
+        // Find the action definition
+        let (action_name, risk_tier) = self
+            .atlases
+            .values()
+            .flat_map(|a| a.actions.iter())
+            .find(|a| a.action_id == action_id)
+            .map(|a| (a.name.clone(), a.risk_tier.clone()))
+            .ok_or_else(|| CRAError::ActionNotFound {
+                action_id: action_id.to_string(),
+            })?;
+
+        // A narrowed resolution only covers the actions the request asked for
+        let narrowing = self
+            .resolution_pins
+            .get(resolution_id)
+            .filter(|pin| pin.session_id == session_id)
+            .and_then(|pin| pin.narrowing.as_ref());
+        if narrowing.is_some_and(|n| !n.admits(action_id, &risk_tier)) {
+            let reason = format!("Action is outside the narrowing of resolution '{}'", resolution_id);
+            self.trace_collector.record(
+                session_id,
+                EventType::ActionDenied,
+                serde_json::json!({
+                    "action_id": action_id,
+                    "reason": reason,
+                    "policy_id": NARROWING_POLICY_ID,
+                }),
+            )?;
+
+            return Err(CRAError::ActionDenied {
+                policy_id: NARROWING_POLICY_ID.to_string(),
+                reason,
+            });
+        }
+
+        // A presented token limits the execution to the actions it names
+        if let Some(token) = token {
+            match self.tokens.check(token, session_id, action_id, self.clock.now()) {
+                Ok(token) => {
+                    self.trace_collector.record(
+                        session_id,
+                        EventType::CapabilityTokenUsed,
+                        serde_json::json!({
+                            "token_id": token.token_id,
+                            "action_id": action_id,
+                            "resolution_id": resolution_id,
+                        }),
+                    )?;
+                }
+                Err(reason) => {
+                    self.trace_collector.record(
+                        session_id,
+                        EventType::ActionDenied,
+                        serde_json::json!({
+                            "action_id": action_id,
+                            "reason": reason,
+                            "policy_id": CAPABILITY_TOKEN_POLICY_ID,
+                        }),
+                    )?;
+
+                    return Err(CRAError::ActionDenied {
+                        policy_id: CAPABILITY_TOKEN_POLICY_ID.to_string(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        // Actions under a requires_approval policy need a human decision first
+        let approval_id = match policy_result {
+            PolicyResult::RequiresApproval { policy_id } => {
+                Some(self.require_approval(session_id, action_id, &policy_id, hash_value(&parameters))?)
+            }
+            _ => None,
+        };
+
+        // In a real implementation, you would validate parameters against schema
+        // and execute the actual action here. For now, we just record the execution.
+
+        // Emit action.approved event
+        let mut approved = serde_json::json!({
+            "action_id": action_id,
+            "resolution_id": resolution_id,
+        });
+        if let Some(approval_id) = approval_id {
+            approved["approval_id"] = Value::String(approval_id);
+        }
+        self.trace_collector.record(session_id, EventType::ActionApproved, approved)?;
+
+        // Simulate execution
+        let start = self.clock.now();
+
+        // Placeholder result - in reality this would come from actual action execution
+        let result = serde_json::json!({
+            "status": "success",
+            "action_id": action_id,
+            "message": format!("Action {} executed successfully", action_name),
+        });
+
+        let duration_ms = (self.clock.now() - start).num_milliseconds().max(0) as u64;
+
+        // Update session stats
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.action_count += 1;
+        }
+
+        // Emit action.executed event
+        self.trace_collector.record(
+            session_id,
+            EventType::ActionExecuted,
+            serde_json::json!({
+                "action_id": action_id,
+                "execution_id": execution_id,
+                "duration_ms": duration_ms,
+                "result_hash": hash_value(&result),
+            }),
+        )?;
+
+        Ok(result)
+    }
+
+    /// Let an approved request through, or escalate a new one
+    ///
+    /// Returns the ID of the approval the execution uses.
+    fn require_approval(
+        &mut self,
+        session_id: &str,
+        action_id: &str,
+        policy_id: &str,
+        parameters_hash: String,
+    ) -> Result<String> {
+        let open = self
+            .approvals
+            .find_open(session_id, action_id, &parameters_hash)
+            .map(|record| (record.request.approval_id.clone(), record.status));
+
+        let approval_id = match open {
+            Some((approval_id, ApprovalStatus::Approved)) => approval_id,
+            Some((approval_id, _)) => {
+                if let Some(decision) = self.approvals.poll(&approval_id) {
+                    self.decide_approval(&approval_id, decision)?;
+                }
+                approval_id
+            }
+            None => {
+                let request = ApprovalRequest {
+                    approval_id: self.ids.next_id(),
+                    session_id: session_id.to_string(),
+                    action_id: action_id.to_string(),
+                    policy_id: policy_id.to_string(),
+                    reason: self
+                        .atlases
+                        .values()
+                        .flat_map(|a| a.policies.iter())
+                        .find(|p| p.policy_id == policy_id)
+                        .and_then(|p| p.reason.clone()),
+                    parameters_hash,
+                    requested_at: self.clock.now(),
+                };
+                let record = self.approvals.open(request);
+                let approval_id = record.request.approval_id.clone();
+                let payload = serde_json::json!({
+                    "approval_id": approval_id,
+                    "action_id": action_id,
+                    "policy_id": policy_id,
+                    "parameters_hash": record.request.parameters_hash,
+                    "deliveries": record.deliveries,
+                });
+                self.trace_collector.record(session_id, EventType::ApprovalRequested, payload)?;
+                approval_id
+            }
+        };
+
+        let record = self.approvals.get(&approval_id).ok_or_else(|| CRAError::ApprovalNotFound {
+            approval_id: approval_id.clone(),
+        })?;
+        match record.status {
+            ApprovalStatus::Approved => {
+                self.approvals.mark_executed(&approval_id);
+                Ok(approval_id)
+            }
+            ApprovalStatus::Rejected => {
+                let decided_by = record.decision.as_ref().map(|d| d.decided_by.as_str()).unwrap_or("unknown");
+                let reason = format!("Approval '{}' rejected by {}", approval_id, decided_by);
+                self.trace_collector.record(
+                    session_id,
+                    EventType::ActionDenied,
+                    serde_json::json!({
+                        "action_id": action_id,
+                        "reason": reason,
+                        "policy_id": policy_id,
+                        "approval_id": approval_id,
+                    }),
+                )?;
+                Err(CRAError::ActionDenied {
+                    policy_id: policy_id.to_string(),
+                    reason,
+                })
+            }
+            ApprovalStatus::Pending | ApprovalStatus::Executed => Err(CRAError::ActionRequiresApproval {
+                action_id: action_id.to_string(),
+                approval_id,
+            }),
+        }
+    }
+
+    /// Record a human decision on an approval request
+    ///
+    /// An approval lets one execution of the same action with the same
+    /// parameters through.
+    pub fn decide_approval(&mut self, approval_id: &str, decision: ApprovalDecision) -> Result<()> {
+        let record = self.approvals.decide(approval_id, decision)?;
+        let session_id = record.request.session_id.clone();
+        let Some(decision) = &record.decision else {
+            return Ok(());
+        };
+        let payload = serde_json::json!({
+            "approval_id": approval_id,
+            "action_id": record.request.action_id,
+            "approved": decision.approved,
+            "decided_by": decision.decided_by,
+            "channel": decision.channel,
+            "reason": decision.reason,
+        });
+        self.trace_collector.record(&session_id, EventType::ApprovalDecided, payload)?;
+        Ok(())
+    }
+
+    /// Ask the escalation channels for a decision on a pending request
+    pub fn poll_approval(&mut self, approval_id: &str) -> Result<ApprovalStatus> {
+        if let Some(decision) = self.approvals.poll(approval_id) {
+            self.decide_approval(approval_id, decision)?;
+        }
+        self.approvals
+            .get(approval_id)
+            .map(|record| record.status)
+            .ok_or_else(|| CRAError::ApprovalNotFound {
+                approval_id: approval_id.to_string(),
+            })
+    }
+
+    /// Get an approval request by ID
+    pub fn get_approval(&self, approval_id: &str) -> Option<&ApprovalRecord> {
+        self.approvals.get(approval_id)
+    }
+
+    /// All approval requests, oldest first
+    pub fn list_approvals(&self) -> Vec<&ApprovalRecord> {
+        self.approvals.records()
+    }
+
+    /// Approval requests in a session still waiting for a human
+    pub fn pending_approvals(&self, session_id: &str) -> Vec<&ApprovalRecord> {
+        self.approvals.pending(session_id)
+    }
+
+    /// Get the TRACE for a session
+    pub fn get_trace(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        self.trace_collector.get_events(session_id)
+    }
+
+    /// Get up to `limit` TRACE events for a session starting at index `start`
+    pub fn get_trace_page(&self, session_id: &str, start: usize, limit: usize) -> Result<Vec<TRACEEvent>> {
+        self.trace_collector.get_events_page(session_id, start, limit)
+    }
+
+    /// Search TRACE events across sessions
+    ///
+    /// Searches the trace storage backend when one is attached (see
+    /// [`Resolver::with_trace_storage`]), otherwise the events in memory.
+    pub fn search_traces(&self, query: &EventQuery) -> Result<EventPage> {
+        match self.trace_collector.storage() {
+            Some(storage) => storage.search_events(query),
+            None => self.trace_collector.search(query),
+        }
+    }
+
+    /// Get every TRACE event stamped with a correlation ID, across sessions
+    ///
+    /// Events are ordered by timestamp. See [`Resolver::set_correlation_id`].
+    pub fn get_events_by_correlation(&self, correlation_id: &str) -> Result<Vec<TRACEEvent>> {
+        let mut query = EventQuery::new()
+            .correlation(correlation_id)
+            .limit(MAX_SEARCH_LIMIT);
+        let mut events = Vec::new();
+        loop {
+            let page = self.search_traces(&query)?;
+            events.extend(page.events);
+            match page.next_offset {
+                Some(offset) => query.offset = offset,
+                None => return Ok(events),
+            }
+        }
+    }
+
+    /// Stamp a correlation ID into every TRACE event emitted from now on
+    ///
+    /// Typically set from the `X-CRA-Correlation-Id` header
+    /// ([`CORRELATION_HEADER`](crate::trace::CORRELATION_HEADER)) for the
+    /// duration of one request; pass `None` to stop stamping.
+    pub fn set_correlation_id(&mut self, correlation_id: Option<&str>) {
+        self.trace_collector
+            .set_correlation_id(correlation_id.map(String::from));
+    }
+
+    /// Run `f` with a correlation ID stamped into its TRACE events
+    ///
+    /// The previous correlation ID is restored afterwards.
+    pub fn with_correlation_id<T>(&mut self, correlation_id: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = self.trace_collector.correlation_id().map(String::from);
+        self.trace_collector
+            .set_correlation_id(Some(correlation_id.to_string()));
+        let result = f(self);
+        self.trace_collector.set_correlation_id(previous);
+        result
+    }
+
+    /// Record a fault injected by a chaos test in the session's TRACE
+    ///
+    /// For injection points outside the collector (see [`crate::chaos`]).
+    /// Appends `chaos.fault_injected` when `error` is an injected fault and
+    /// the session exists; other errors are ignored.
+    #[cfg(feature = "chaos")]
+    pub fn record_injected_fault(&mut self, session_id: &str, error: &CRAError) -> Result<()> {
+        let Some(payload) = crate::trace::injected_fault_payload(error) else { return Ok(()) };
+        if self.sessions.contains_key(session_id) {
+            self.trace_collector
+                .emit(session_id, EventType::ChaosFaultInjected, payload)?;
+        }
+        Ok(())
+    }
+
+    /// Export a session's TRACE as a downloadable JSONL artifact
+    ///
+    /// The events are followed by a trailer with the event count and final
+    /// chain hash. In deferred mode, call `flush_traces()` first.
+    pub fn export_trace(&self, session_id: &str) -> Result<TraceExport> {
+        self.export_trace_with_context(session_id, false)
+    }
+
+    /// Export a session's TRACE, optionally inlining its context
+    ///
+    /// Events reference injected context by `content_hash`. With
+    /// `inline_context`, the export also carries the content of each block
+    /// still in the content store, as records before the trailer.
+    pub fn export_trace_with_context(&self, session_id: &str, inline_context: bool) -> Result<TraceExport> {
+        let events = self.trace_collector.get_events(session_id)?;
+        let trace_id = self.trace_collector.trace_id(session_id).unwrap_or_default();
+        if !inline_context {
+            return TraceExport::new(session_id, trace_id, &events);
+        }
+
+        let mut seen = HashSet::new();
+        let contents: Vec<ContentRecord> = events
+            .iter()
+            .filter(|e| e.event_type == EventType::ContextInjected)
+            .filter_map(|e| e.payload.get("content_hash").and_then(Value::as_str))
+            .filter(|hash| seen.insert(*hash))
+            .filter_map(|hash| self.content_store.get(hash))
+            .map(ContentRecord::new)
+            .collect();
+        TraceExport::with_contents(session_id, trace_id, &events, &contents)
+    }
+
+    /// Render a session's activity as a DOT or Mermaid graph
+    ///
+    /// See [`crate::trace::visualize`]. In deferred mode, call
+    /// `flush_traces()` first.
+    pub fn visualize_trace(&self, session_id: &str, format: GraphFormat) -> Result<String> {
+        let events = self.trace_collector.get_events(session_id)?;
+        Ok(crate::trace::visualize(session_id, &events, format))
+    }
+
+    /// Verify the hash chain integrity for a session
+    ///
+    /// With the `signing` feature, event signatures are also checked when any
+    /// trace keys are known (see [`Resolver::trace_keys`]).
+    pub fn verify_chain(&self, session_id: &str) -> Result<crate::trace::ChainVerification> {
+        let verification = self.check_chain(session_id)?;
+        #[cfg(not(feature = "minimal"))]
+        if let (false, Some(notifier)) = (verification.is_valid, &self.notifier) {
+            notifier.notify(
+                NotificationClass::ChainInvalid,
+                session_id,
+                serde_json::to_value(&verification).unwrap_or_default(),
+            )?;
+        }
+        Ok(verification)
+    }
+
+    /// Reconstruct a session's state as of one of its TRACE events
+    ///
+    /// Replays the chain up to and including event `sequence`. See
+    /// [`SessionStateSnapshot`] for what is reconstructed.
+    pub fn state_at(&self, session_id: &str, sequence: u64) -> Result<SessionStateSnapshot> {
+        if !self.trace_collector.has_session(session_id) {
+            return Err(CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            });
+        }
+        let events = self.trace_collector.get_events(session_id)?;
+        SessionStateSnapshot::replay(session_id, &events, sequence)
+    }
+
+    /// Summarize a session for audit
+    ///
+    /// Covers duration, action outcomes by action and policy, injected
+    /// context, checkpoint outcomes, chain verification and any usage
+    /// reported in event payloads. Render with serde or
+    /// [`SessionReport::to_markdown`].
+    pub fn session_report(&self, session_id: &str) -> Result<SessionReport> {
+        let session = self.sessions.get(session_id).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })?;
+        let events = self.trace_collector.get_events(session_id)?;
+        let chain = self.check_chain(session_id)?;
+        Ok(SessionReport::build(
+            session,
+            &events,
+            chain,
+            self.is_quarantined(session_id),
+            self.clock.now(),
+        ))
+    }
+
+    fn check_chain(&self, session_id: &str) -> Result<crate::trace::ChainVerification> {
+        #[cfg(feature = "signing")]
+        {
+            let keys = self.trace_keys();
+            if !keys.is_empty() {
+                let ring = crate::crypto::KeyRing::from_keys(&keys)?;
+                return Ok(ring.verify_chain(&self.trace_collector.get_events(session_id)?));
+            }
+        }
+        self.trace_collector.verify_chain(session_id)
+    }
+
+    /// Public keys trusted to sign this resolver's TRACE events
+    ///
+    /// The event signer's own key, keys it replaced, then the `trace_keys`
+    /// of each loaded atlas's steward.
+    pub fn trace_keys(&self) -> Vec<TraceKey> {
+        let mut keys: Vec<TraceKey> = self.trace_collector.signer().map(|s| s.public_key()).into_iter().collect();
+        keys.extend(self.retired_trace_keys.iter().rev().cloned());
+        let mut atlases: Vec<&AtlasManifest> = self.atlases.values().collect();
+        atlases.sort_by(|a, b| a.atlas_id.cmp(&b.atlas_id));
+        keys.extend(
+            atlases
+                .into_iter()
+                .filter_map(|atlas| atlas.steward.as_ref())
+                .flat_map(|steward| steward.trace_keys.iter().cloned()),
+        );
+        keys
+    }
+
+    /// Report which policies and capabilities cover each loaded action
+    ///
+    /// Lists actions no policy applies to, actions no capability grants, and
+    /// the actions that are allowed only by default (none in strict mode).
+    pub fn policy_coverage(&self) -> PolicyCoverage {
+        let mut actions: Vec<ActionCoverage> = self
+            .atlases
+            .values()
+            .flat_map(|atlas| atlas.actions.iter().map(move |action| (atlas, action)))
+            .map(|(atlas, action)| {
+                let mut capabilities: Vec<String> = granting_capabilities(&self.atlases, &action.action_id)
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                capabilities.sort();
+                ActionCoverage {
+                    action_id: action.action_id.clone(),
+                    atlas_id: atlas.atlas_id.clone(),
+                    policies: self
+                        .policy_evaluator
+                        .matching_policies(&action.action_id)
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                    capabilities,
+                }
+            })
+            .collect();
+        actions.sort_by(|a, b| a.action_id.cmp(&b.action_id).then_with(|| a.atlas_id.cmp(&b.atlas_id)));
+
+        let uncovered_actions: Vec<String> = actions
+            .iter()
+            .filter(|a| a.policies.is_empty())
+            .map(|a| a.action_id.clone())
+            .collect();
+        let ungranted_actions = actions
+            .iter()
+            .filter(|a| a.capabilities.is_empty())
+            .map(|a| a.action_id.clone())
+            .collect();
+        let default_allowed_actions = if self.strict_mode {
+            Vec::new()
+        } else {
+            uncovered_actions.clone()
+        };
+
+        PolicyCoverage {
+            strict_mode: self.strict_mode,
+            actions,
+            uncovered_actions,
+            ungranted_actions,
+            default_allowed_actions,
+        }
+    }
+
+    /// Get the trace collector (for advanced operations)
+    pub fn trace_collector(&self) -> &TraceCollector {
+        &self.trace_collector
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Label for a policy result in `cra_policy_decisions_total`
+fn policy_result_label(result: &PolicyResult) -> &'static str {
+    match result {
+        PolicyResult::Allow => "allow",
+        PolicyResult::AllowWithConstraints(_) => "allow_with_constraints",
+        PolicyResult::Deny { .. } => "deny",
+        PolicyResult::RequiresApproval { .. } => "requires_approval",
+        PolicyResult::RateLimitExceeded { .. } => "rate_limited",
+        PolicyResult::NoMatch => "no_match",
+    }
+}
+
+/// Reason given for actions denied by strict mode
+const STRICT_MODE_REASON: &str = "Not granted by any capability (strict mode)";
+
+/// IDs of the capabilities that include an action
+fn granting_capabilities<'a>(atlases: &'a HashMap<String, AtlasManifest>, action_id: &str) -> Vec<&'a str> {
+    atlases
+        .values()
+        .flat_map(|a| a.capabilities.iter())
+        .filter(|c| c.includes(action_id))
+        .map(|c| c.capability_id.as_str())
+        .collect()
+}
+
+/// Find the capability gating an action in a session
+///
+/// An action is gated when it belongs to at least one capability and none of
+/// its capabilities are usable. Returns the first gating capability.
+fn gated_capability(
+    atlases: &HashMap<String, AtlasManifest>,
+    state: Option<&CapabilityState>,
+    action_id: &str,
+) -> Option<(String, CapabilityStatus)> {
+    let state = state?;
+    let mut gate = None;
+
+    for capability in atlases
+        .values()
+        .flat_map(|a| a.capabilities.iter())
+        .filter(|c| c.includes(action_id))
+    {
+        match state.status(&capability.capability_id) {
+            Some(status) if !status.is_usable() => {
+                gate.get_or_insert((capability.capability_id.clone(), status));
+            }
+            _ => return None,
+        }
+    }
+
+    gate
+}
+
+/// First capability gate on an action in a session or any of its ancestors
+fn lineage_gate(
+    atlases: &HashMap<String, AtlasManifest>,
+    states: &HashMap<String, CapabilityState>,
+    lineage: &[String],
+    action_id: &str,
+) -> Option<(String, CapabilityStatus)> {
+    lineage
+        .iter()
+        .find_map(|session_id| gated_capability(atlases, states.get(session_id), action_id))
+}
+
+/// Session fields sent to external policy engines
+fn session_context(session: &Session) -> Value {
+    serde_json::json!({
+        "session_id": session.session_id,
+        "agent_id": session.agent_id,
+        "goal": session.goal,
+        "parent_session_id": session.parent_session_id,
+        "principal": session.principal,
+    })
+}
+
+/// Ask every external policy on an action, recording each decision
+///
+/// Returns the first denial, or `None` if all of them allow the action.
+fn external_gate(
+    external: &ExternalPolicies,
+    policies: Vec<&AtlasPolicy>,
+    trace_collector: &mut TraceCollector,
+    session_id: &str,
+    action_id: &str,
+    mut input: Value,
+    budget: &mut LatencyBudget,
+) -> Result<Option<PolicyResult>> {
+    for policy in policies {
+        input["policy_id"] = Value::String(policy.policy_id.clone());
+        let decision = external.decide(policy, action_id, &input, budget.remaining());
+
+        if let Some(timeout) = &decision.timeout {
+            let fallback = if decision.allowed { "allow" } else { "deny" };
+            trace_collector.record(
+                session_id,
+                EventType::PolicyEvaluationTimeout,
+                serde_json::json!({
+                    "policy_id": policy.policy_id,
+                    "action_id": action_id,
+                    "cause": timeout.cause,
+                    "timeout_ms": timeout.timeout_ms,
+                    "elapsed_ms": decision.latency_ms,
+                    "fallback": fallback,
+                }),
+            )?;
+            budget.degraded.push(DegradedEvaluation {
+                action_id: action_id.to_string(),
+                policy_id: policy.policy_id.clone(),
+                cause: timeout.cause.to_string(),
+                fallback: fallback.to_string(),
+            });
+        }
+
+        let mut payload = serde_json::json!({
+            "policy_id": policy.policy_id,
+            "action_id": action_id,
+            "outcome": decision.outcome,
+            "allowed": decision.allowed,
+            "latency_ms": decision.latency_ms,
+            "cached": decision.cached,
+        });
+        if let Some(error) = &decision.error {
+            payload["error"] = Value::String(error.clone());
+        }
+        trace_collector.record(session_id, EventType::PolicyExternalDecision, payload)?;
+
+        if !decision.allowed {
+            return Ok(Some(PolicyResult::Deny {
+                policy_id: policy.policy_id.clone(),
+                reason: decision.reason,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Time callbacks may still take in one resolution, execution or checkpoint pass
+///
+/// External policies, custom checkpoint triggers and custom answer
+/// validators share the same budget per call. Only external policies can be
+/// cut short; triggers and validators are charged for the time they took, and
+/// once the budget is spent the remaining ones are not called.
+struct LatencyBudget {
+    /// When evaluation started, and the budget
+    limit: Option<(Instant, std::time::Duration)>,
+    /// Decisions that fell back after a timeout
+    degraded: Vec<DegradedEvaluation>,
+}
+
+impl LatencyBudget {
+    fn start(budget: Option<std::time::Duration>) -> Self {
+        Self {
+            limit: budget.map(|budget| (Instant::now(), budget)),
+            degraded: Vec::new(),
+        }
+    }
+
+    /// What is left of the budget, if there is one
+    fn remaining(&self) -> Option<std::time::Duration> {
+        self.limit.map(|(start, budget)| budget.saturating_sub(start.elapsed()))
+    }
+
+    /// Whether there is a budget and it has been spent
+    fn is_exhausted(&self) -> bool {
+        self.remaining().is_some_and(|remaining| remaining.is_zero())
+    }
+}
+
+/// Record a verbosity change, then apply it
+///
+/// The change is emitted at every level, before the new level takes effect.
+fn change_verbosity(
+    trace_collector: &mut TraceCollector,
+    session_id: &str,
+    from: TraceVerbosity,
+    to: TraceVerbosity,
+    reason: &str,
+) -> Result<()> {
+    trace_collector.emit(
+        session_id,
+        EventType::TraceVerbosityChanged,
+        serde_json::json!({
+            "from": from,
+            "to": to,
+            "reason": reason,
+        }),
+    )?;
+    trace_collector.set_verbosity(session_id, to);
+    Ok(())
+}
+
+/// Hash a JSON value for audit purposes
+fn hash_value(value: &Value) -> String {
+    use sha2::{Digest, Sha256};
+
+    let canonical = serde_json::to_string(value).unwrap_or_default();
+    let hash = Sha256::digest(canonical.as_bytes());
+    hex::encode(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn create_test_atlas() -> AtlasManifest {
+        serde_json::from_value(json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.resolver",
+            "version": "1.0.0",
+            "name": "Test Resolver Atlas",
+            "description": "Atlas for testing the resolver",
+            "domains": ["test"],
+            "capabilities": [],
+            "policies": [
+                {
+                    "policy_id": "deny-delete",
+                    "type": "deny",
+                    "actions": ["*.delete"],
+                    "reason": "Deletion not allowed"
+                }
+            ],
+            "actions": [
+                {
+                    "action_id": "test.get",
+                    "name": "Get Test",
+                    "description": "Get a test resource",
+                    "parameters_schema": { "type": "object" },
+                    "risk_tier": "low"
+                },
+                {
+                    "action_id": "test.create",
+                    "name": "Create Test",
+                    "description": "Create a test resource",
+                    "parameters_schema": { "type": "object" },
+                    "risk_tier": "medium"
+                },
+                {
+                    "action_id": "test.delete",
+                    "name": "Delete Test",
+                    "description": "Delete a test resource",
+                    "parameters_schema": { "type": "object" },
+                    "risk_tier": "high"
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_load_atlas() {
+        let mut resolver = Resolver::new();
+        let atlas = create_test_atlas();
+
+        let result = resolver.load_atlas(atlas);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "com.test.resolver");
+        assert!(resolver.get_atlas("com.test.resolver").is_some());
+    }
+
+    #[test]
+    fn test_create_session() {
+        let mut resolver = Resolver::new();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let session = resolver.get_session(&session_id).unwrap();
+        assert_eq!(session.agent_id, "test-agent");
+        assert_eq!(session.goal, "Test goal");
+        assert!(session.is_active);
+    }
+
+    #[test]
+    fn test_resolve_request() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let request = CARPRequest::new(
+            session_id.clone(),
+            "test-agent".to_string(),
+            "I want to test things".to_string(),
+        );
+
+        let resolution = resolver.resolve(&request).unwrap();
+
+        // test.get and test.create should be allowed
+        // test.delete should be denied
+        assert!(resolution.is_action_allowed("test.get"));
+        assert!(resolution.is_action_allowed("test.create"));
+        assert!(!resolution.is_action_allowed("test.delete"));
+
+        // Check denied reason
+        let denial = resolution.denied_actions.iter()
+            .find(|d| d.action_id == "test.delete");
+        assert!(denial.is_some());
+    }
+
+    #[test]
+    fn test_execute_action() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        // Execute allowed action
+        let result = resolver.execute(
+            &session_id,
+            "resolution-1",
+            "test.get",
+            json!({}),
+        );
+        assert!(result.is_ok());
+
+        // Execute denied action should fail
+        let result = resolver.execute(
+            &session_id,
+            "resolution-1",
+            "test.delete",
+            json!({}),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_idempotent_resolve_and_execute() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        let first = resolver.resolve_idempotent(&request, "resolve-1").unwrap();
+        let replayed = resolver.resolve_idempotent(&request, "resolve-1").unwrap();
+        assert_eq!(replayed.trace_id, first.trace_id);
+
+        let result = resolver.execute_idempotent(&session_id, &first.trace_id, "test.get", json!({"id": 1}), "exec-1").unwrap();
+        let retried = resolver.execute_idempotent(&session_id, &first.trace_id, "test.get", json!({"id": 1}), "exec-1").unwrap();
+        assert_eq!(retried, result);
+        assert_eq!(resolver.get_session(&session_id).unwrap().action_count, 1);
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let replays: Vec<_> = trace.iter().filter(|e| e.event_type == EventType::CARPRequestReplayed).collect();
+        assert_eq!(replays.len(), 2);
+        assert_eq!(replays[1].payload["operation"], "execute");
+        assert_eq!(replays[1].payload["idempotency_key"], "exec-1");
+
+        let err = resolver
+            .execute_idempotent(&session_id, &first.trace_id, "test.get", json!({"id": 2}), "exec-1")
+            .unwrap_err();
+        assert_eq!(err.error_code(), "IDEMPOTENCY_KEY_CONFLICT");
+    }
+
+    #[test]
+    fn test_recover_sessions_from_store() {
+        use crate::storage::{InMemorySessionStore, InMemoryStorage};
+
+        let traces: Arc<dyn StorageBackend> = Arc::new(InMemoryStorage::new());
+        let store: Arc<dyn SessionStore> = Arc::new(InMemorySessionStore::new());
+        let resolver = |store: &Arc<dyn SessionStore>| {
+            let mut resolver = Resolver::new().with_trace_storage(traces.clone()).with_session_store(store.clone());
+            resolver.load_atlas(create_test_atlas()).unwrap();
+            resolver
+        };
+
+        let mut first = resolver(&store);
+        let live = first.create_session("test-agent", "Test goal").unwrap();
+        let ended = first.create_session("test-agent", "Other goal").unwrap();
+        let request = CARPRequest::new(live.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = first.resolve(&request).unwrap();
+        first.execute(&live, &resolution.trace_id, "test.get", json!({})).unwrap();
+        first.set_session_variable(&live, "ticket", "T-1").unwrap();
+        first.end_session(&ended).unwrap();
+        drop(first);
+
+        // A restarted resolver gets both sessions and their chains back
+        let mut second = resolver(&store);
+        assert_eq!(second.recover_sessions().unwrap(), 2);
+        assert_eq!(second.recover_sessions().unwrap(), 0);
+        let session = second.get_session(&live).unwrap();
+        assert_eq!((session.resolution_count, session.action_count), (1, 1));
+        assert_eq!(session.variables["ticket"], "T-1");
+        assert!(!second.get_session(&ended).unwrap().is_active);
+
+        let resolution = second.resolve(&request).unwrap();
+        second.execute(&live, &resolution.trace_id, "test.get", json!({})).unwrap();
+        assert!(second.verify_chain(&live).unwrap().is_valid);
+        assert_eq!(traces.get_events(&live).unwrap().len(), second.get_trace(&live).unwrap().len());
+        assert_eq!(store.load_session(&live).unwrap().unwrap().session.action_count, 2);
+    }
+
+    #[test]
+    fn test_capability_token() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let other = resolver.create_session("test-agent", "Other goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        let ttl = std::time::Duration::from_secs(60);
+
+        let token = resolver.mint_capability_token(&session_id, &["test.get".to_string()], ttl).unwrap();
+        let denied = |r: Result<Value>| matches!(r, Err(CRAError::ActionDenied { ref policy_id, .. }) if policy_id == CAPABILITY_TOKEN_POLICY_ID);
+        resolver.execute_with_token(&session_id, &resolution.trace_id, "test.get", json!({}), &token).unwrap();
+        assert!(denied(resolver.execute_with_token(&session_id, &resolution.trace_id, "test.create", json!({}), &token)));
+        assert!(denied(resolver.execute_with_token(&other, &resolution.trace_id, "test.get", json!({}), &token)));
+
+        // A token never widens what policy allows
+        let wide = resolver.mint_capability_token(&session_id, &["test.*".to_string()], ttl).unwrap();
+        let err = resolver.execute_with_token(&session_id, &resolution.trace_id, "test.delete", json!({}), &wide).unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { ref policy_id, .. } if policy_id != CAPABILITY_TOKEN_POLICY_ID));
+
+        let minted = resolver
+            .get_trace(&session_id)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.event_type == EventType::CapabilityTokenMinted)
+            .unwrap();
+        let token_id = minted.payload["token_id"].as_str().unwrap().to_string();
+        assert!(resolver.revoke_capability_token(&token_id, "ops").unwrap());
+        assert!(!resolver.revoke_capability_token(&token_id, "ops").unwrap());
+        assert!(denied(resolver.execute_with_token(&session_id, &resolution.trace_id, "test.get", json!({}), &token)));
+
+        let types: Vec<EventType> = resolver.get_trace(&session_id).unwrap().iter().map(|e| e.event_type).collect();
+        assert!(types.contains(&EventType::CapabilityTokenUsed));
+        assert!(types.contains(&EventType::CapabilityTokenRevoked));
+        assert!(resolver.mint_capability_token(&session_id, &[], ttl).is_err());
+    }
+
+    #[test]
+    fn test_external_policy() {
+        use crate::atlas::{AtlasPolicy, PolicyType};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let atlas = |fail_open: bool| {
+            let mut atlas = create_test_atlas();
+            atlas.policies.push(AtlasPolicy {
+                policy_id: "opa".to_string(),
+                policy_type: PolicyType::External,
+                actions: vec!["test.*".to_string()],
+                reason: None,
+                parameters: Some(json!({ "url": "https://opa.test/v1/data/cra/allow", "fail_open": fail_open })),
+            });
+            atlas
+        };
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client: PolicyDecisionFn = Arc::new(move |url: &str, body: &Value, _| {
+            assert_eq!(url, "https://opa.test/v1/data/cra/allow");
+            counter.fetch_add(1, Ordering::SeqCst);
+            let over = body["input"]["parameters"]["amount"].as_u64().unwrap_or(0) > 100;
+            Ok(json!({ "result": { "allow": !over, "reason": "Amount over limit" } }))
+        });
+
+        let mut resolver = Resolver::new().with_external_policy_client(client);
+        resolver.load_atlas(atlas(false)).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        // The deny policy wins before the engine is asked about test.delete
+        let resolution = resolver.resolve(&request).unwrap();
+        assert_eq!(resolution.allowed_actions.len(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        resolver.resolve(&request).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        resolver.execute(&session_id, &resolution.trace_id, "test.create", json!({ "amount": 5 })).unwrap();
+        let err = resolver
+            .execute(&session_id, &resolution.trace_id, "test.create", json!({ "amount": 500 }))
+            .unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { ref policy_id, ref reason } if policy_id == "opa" && reason == "Amount over limit"));
+
+        let decisions: Vec<Value> = resolver
+            .get_trace(&session_id)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == EventType::PolicyExternalDecision)
+            .map(|e| e.payload)
+            .collect();
+        assert_eq!(decisions.len(), 6);
+        assert!(decisions[2]["cached"].as_bool().unwrap());
+        assert_eq!(decisions[5]["outcome"], "deny");
+
+        // Without a client the engine counts as down
+        for (fail_open, allowed) in [(false, 0), (true, 2)] {
+            let mut resolver = Resolver::new();
+            resolver.load_atlas(atlas(fail_open)).unwrap();
+            let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+            let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+            assert_eq!(resolver.resolve(&request).unwrap().allowed_actions.len(), allowed);
+            let event = resolver
+                .get_trace(&session_id)
+                .unwrap()
+                .into_iter()
+                .find(|e| e.event_type == EventType::PolicyExternalDecision)
+                .unwrap();
+            assert_eq!(event.payload["outcome"], "error");
+        }
+
+        let mut broken = atlas(false);
+        broken.policies.last_mut().unwrap().parameters = Some(json!({ "url": "opa.test" }));
+        assert!(Resolver::new().load_atlas(broken).is_err());
+    }
+
+    #[test]
+    fn test_external_policy_latency_budget() {
+        use crate::atlas::{AtlasPolicy, PolicyType};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let atlas = |timeout_ms: u64, fail_open: bool| {
+            let mut atlas = create_test_atlas();
+            atlas.policies.push(AtlasPolicy {
+                policy_id: "slow-opa".to_string(),
+                policy_type: PolicyType::External,
+                actions: vec!["test.*".to_string()],
+                reason: None,
+                parameters: Some(json!({ "url": "https://opa.test/allow", "timeout_ms": timeout_ms, "fail_open": fail_open })),
+            });
+            atlas
+        };
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let slow: PolicyDecisionFn = Arc::new(move |_: &str, _: &Value, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(40));
+            Ok(json!({ "result": true }))
+        });
+        let timeouts = |resolver: &Resolver, session_id: &str| -> Vec<Value> {
+            resolver
+                .get_trace(session_id)
+                .unwrap()
+                .into_iter()
+                .filter(|e| e.event_type == EventType::PolicyEvaluationTimeout)
+                .map(|e| e.payload)
+                .collect()
+        };
+
+        // Each call overruns the policy's own timeout and fails closed
+        let mut resolver = Resolver::new().with_external_policy_client(slow.clone());
+        resolver.load_atlas(atlas(10, false)).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert!(resolution.allowed_actions.is_empty());
+        assert_eq!(resolution.degraded_evaluations.len(), 2);
+        assert!(resolution
+            .degraded_evaluations
+            .iter()
+            .all(|d| d.policy_id == "slow-opa" && d.cause == "policy_timeout" && d.fallback == "deny"));
+        let events = timeouts(&resolver, &session_id);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["timeout_ms"], 10);
+        assert!(events[0]["elapsed_ms"].as_u64().unwrap() >= 10);
+
+        // The budget runs out during the first call; the second engine call
+        // is skipped and both fail open
+        calls.store(0, Ordering::SeqCst);
+        let mut resolver = Resolver::new()
+            .with_external_policy_client(slow)
+            .with_policy_latency_budget(Duration::from_millis(15));
+        resolver.load_atlas(atlas(1000, true)).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert_eq!(resolution.allowed_actions.len(), 2);
+        assert!(resolution.is_degraded());
+        assert!(resolution.degraded_evaluations.iter().all(|d| d.cause == "latency_budget" && d.fallback == "allow"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let events = timeouts(&resolver, &session_id);
+        assert_eq!(events[1]["timeout_ms"], 0);
+
+        let completed = resolver
+            .get_trace(&session_id)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.event_type == EventType::CARPResolutionCompleted)
+            .unwrap();
+        assert_eq!(completed.payload["degraded_evaluations"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_session_for_principal() {
+        use crate::identity::ApiKeyProvider;
+
+        let principal = Principal::new("api_key", "ci-runner")
+            .with_agent_id("deploy-agent")
+            .with_tenant("acme")
+            .with_roles(vec!["deployer".to_string()]);
+        let resolver = Resolver::new();
+        assert!(matches!(resolver.authenticate("k-1"), Err(CRAError::AuthenticationFailed { .. })));
+
+        let mut resolver = Resolver::new().with_identity_provider(ApiKeyProvider::new().with_key("k-1", principal.clone()));
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let err = resolver.authenticate("k-2").unwrap_err();
+        assert_eq!(err.http_status_code(), 401);
+
+        let authenticated = resolver.authenticate("k-1").unwrap();
+        assert_eq!(authenticated, principal);
+        let session_id = resolver
+            .create_session_for_principal(&authenticated, "Deploy", SessionVariables::new())
+            .unwrap();
+        let session = resolver.get_session(&session_id).unwrap();
+        assert_eq!(session.agent_id, "deploy-agent");
+
+        let started = &resolver.get_trace(&session_id).unwrap()[0];
+        assert_eq!(started.payload["principal"]["tenant"], "acme");
+        assert_eq!(started.payload["principal"]["roles"], json!(["deployer"]));
+
+        let child = resolver.create_child_session(&session_id, "Sub-task", &[]).unwrap();
+        assert_eq!(resolver.get_session(&child).unwrap().principal.as_ref(), Some(&principal));
+    }
+
+    #[test]
+    fn test_trace_verbosity() {
+        use crate::carp::{CheckpointTrigger, StewardCheckpointDef};
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver
+            .create_session_with_verbosity("test-agent", "Test goal", TraceVerbosity::Debug)
+            .unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        resolver.resolve(&request).unwrap();
+
+        resolver.set_trace_verbosity(&session_id, TraceVerbosity::Minimal, "operator").unwrap();
+        resolver.set_trace_verbosity(&session_id, TraceVerbosity::Minimal, "operator").unwrap();
+        let before = resolver.get_trace(&session_id).unwrap().len();
+        resolver.resolve(&request).unwrap();
+        resolver.end_session(&session_id).unwrap();
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        assert_eq!(trace[0].payload["trace_verbosity"], "debug");
+        let received = trace.iter().find(|e| e.event_type == EventType::CARPRequestReceived).unwrap();
+        assert_eq!(received.payload["request"]["goal"], "Test goal");
+        let changes: Vec<_> = trace.iter().filter(|e| e.event_type == EventType::TraceVerbosityChanged).collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].payload, json!({"from": "debug", "to": "minimal", "reason": "operator"}));
+        // At minimal the second resolution records only its decision
+        assert_eq!(trace.len(), before + 2);
+        let decision = &trace[before];
+        assert_eq!(decision.event_type, EventType::CARPResolutionCompleted);
+        assert_eq!(decision.payload["decision_type"], "partial");
+        assert_eq!(trace.last().unwrap().event_type, EventType::SessionEnded);
+        assert!(resolver.verify_chain(&session_id).unwrap().is_valid);
+
+        // A checkpoint can lower the level from the start
+        let mut atlas = create_test_atlas();
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("quiet", "Quiet", CheckpointTrigger::SessionStart)
+                .with_trace_verbosity(TraceVerbosity::Minimal),
+        );
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        assert_eq!(resolver.get_session(&session_id).unwrap().trace_verbosity, TraceVerbosity::Minimal);
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let change = trace.iter().find(|e| e.event_type == EventType::TraceVerbosityChanged).unwrap();
+        assert_eq!(change.payload["reason"], "checkpoint:quiet");
+    }
+
+    #[test]
+    fn test_resolve_batch() {
+        use crate::carp::{BatchItem, MAX_BATCH_SIZE};
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let first = resolver.create_session("test-agent", "First").unwrap();
+        let second = resolver.create_session("test-agent", "Second").unwrap();
+        let request = |session_id: &str| CARPRequest::new(session_id.to_string(), "test-agent".to_string(), "Test goal".to_string());
+
+        let response = resolver
+            .resolve_batch(&[request(&first), request("missing"), request(&second)])
+            .unwrap();
+        assert_eq!((response.succeeded, response.failed), (2, 1));
+        assert_eq!(response.results.iter().map(BatchItem::index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(matches!(&response.results[2], BatchItem::Ok { resolution, .. } if resolution.session_id == second));
+        match &response.results[1] {
+            BatchItem::Error { session_id, error, http_status, .. } => {
+                assert_eq!(session_id, "missing");
+                assert_eq!(error.code, "SESSION_NOT_FOUND");
+                assert_eq!(*http_status, 404);
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert_eq!(serde_json::to_value(&response).unwrap()["results"][1]["status"], "error");
+
+        assert!(resolver.resolve_batch(&[]).is_err());
+        let oversized = vec![request(&first); MAX_BATCH_SIZE + 1];
+        assert!(matches!(resolver.resolve_batch(&oversized), Err(CRAError::InvalidCARPRequest { .. })));
+    }
+
+    #[test]
+    fn test_events_by_correlation() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let first = resolver.create_session("test-agent", "First").unwrap();
+        let second = resolver.create_session("test-agent", "Second").unwrap();
+
+        let request = CARPRequest::new(first.clone(), "test-agent".to_string(), "First".to_string());
+        let resolved = resolver
+            .with_correlation_id("req-42", |r| r.resolve(&request))
+            .unwrap();
+        resolver.set_correlation_id(Some("req-42"));
+        resolver.end_session(&second).unwrap();
+        resolver.set_correlation_id(None);
+        resolver.end_session(&first).unwrap();
+
+        let events = resolver.get_events_by_correlation("req-42").unwrap();
+        assert!(events.iter().any(|e| e.session_id == first && e.event_type == EventType::CARPResolutionCompleted));
+        assert!(events.iter().any(|e| e.session_id == second && e.event_type == EventType::SessionEnded));
+        assert!(!events.iter().any(|e| e.session_id == first && e.event_type == EventType::SessionEnded));
+        assert!(events.iter().all(|e| e.payload["correlation_id"] == "req-42"));
+        assert!(events.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert_eq!(resolved.session_id, first);
+        assert!(resolver.get_events_by_correlation("other").unwrap().is_empty());
+        assert!(resolver.verify_chain(&first).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_child_session() {
+        use crate::atlas::AtlasCapability;
+
+        let mut atlas = create_test_atlas();
+        for (id, action) in [("read", "test.get"), ("write", "test.create")] {
+            atlas.capabilities.push(AtlasCapability {
+                capability_id: id.to_string(),
+                name: id.to_string(),
+                description: String::new(),
+                actions: vec![action.to_string()],
+            });
+        }
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        let parent = resolver.create_session("test-agent", "Parent goal").unwrap();
+        resolver.lock_capability(&parent, "write", "ops").unwrap();
+
+        // Asking for more than the parent has gets the intersection
+        let subset = vec!["read".to_string(), "write".to_string()];
+        let child = resolver.create_child_session(&parent, "Child goal", &subset).unwrap();
+        assert_eq!(resolver.get_session(&child).unwrap().parent_session_id.as_deref(), Some(parent.as_str()));
+        assert_eq!(resolver.child_sessions(&parent).len(), 1);
+
+        let request = CARPRequest::new(child.clone(), "test-agent".to_string(), "Child goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert!(resolution.is_action_allowed("test.get"));
+        assert!(!resolution.is_action_allowed("test.create"));
+
+        // Granting the child a capability can't exceed what the parent may use now
+        resolver.grant_capability(&child, "write", "ops").unwrap();
+        assert!(!resolver.resolve(&request).unwrap().is_action_allowed("test.create"));
+        resolver.lock_capability(&parent, "read", "ops").unwrap();
+        let err = resolver.execute(&child, &resolution.trace_id, "test.get", json!({})).unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { .. }));
+
+        let err = resolver.create_child_session(&parent, "goal", &["nope".to_string()]).unwrap_err();
+        assert!(matches!(err, CRAError::CapabilityNotFound { .. }));
+
+        // The link is in both chains, and ending the parent ends the child
+        resolver.end_session(&parent).unwrap();
+        assert!(!resolver.get_session(&child).unwrap().is_active);
+        let parent_trace = resolver.get_trace(&parent).unwrap();
+        let created = parent_trace.iter().find(|e| e.event_type == EventType::SessionChildCreated).unwrap();
+        assert_eq!(created.payload["child_session_id"], json!(child));
+        assert_eq!(created.payload["capabilities"], json!(["read"]));
+        let child_trace = resolver.get_trace(&child).unwrap();
+        assert_eq!(child_trace[0].payload["parent_session_id"], json!(parent));
+        assert_eq!(child_trace.last().unwrap().payload["reason"], "parent_ended");
+        assert!(matches!(
+            resolver.resolve(&request),
+            Err(CRAError::SessionAlreadyEnded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resume_and_expire_session() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        resolver.resume_session(&session_id, "reconnected").unwrap();
+        resolver.expire_session(&session_id, "idle").unwrap();
+        assert!(!resolver.get_session(&session_id).unwrap().is_active);
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let types: Vec<_> = trace.iter().map(|e| e.event_type).collect();
+        assert_eq!(
+            types,
+            [EventType::SessionStarted, EventType::SessionResumed, EventType::SessionExpired, EventType::SessionEnded]
+        );
+        assert_eq!(trace[1].payload["reason"], "reconnected");
+        assert_eq!(trace[2].payload["cause"], "idle");
+        assert_eq!(trace[3].payload["reason"], "expired");
+
+        // Neither applies to a session that has ended
+        assert!(matches!(
+            resolver.resume_session(&session_id, "reconnected"),
+            Err(CRAError::SessionAlreadyEnded { .. })
+        ));
+        assert!(matches!(
+            resolver.expire_session("nope", "idle"),
+            Err(CRAError::SessionNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_narrowing() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let request = CARPRequest::builder(session_id.clone(), "test-agent".to_string(), "Test goal".to_string())
+            .requested_actions(vec!["test.*".to_string()])
+            .max_risk_tier(crate::carp::RiskTier::Low)
+            .build();
+        let resolution = resolver.resolve(&request).unwrap();
+        assert!(resolution.is_action_allowed("test.get"));
+        assert!(!resolution.is_action_allowed("test.create"));
+        let narrowing = resolution.narrowing.as_ref().unwrap();
+        assert_eq!(narrowing.excluded_actions, vec!["test.create"]);
+
+        // Narrowed-out actions can't be executed against the resolution
+        let err = resolver
+            .execute(&session_id, &resolution.trace_id, "test.create", json!({}))
+            .unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { ref policy_id, .. } if policy_id == NARROWING_POLICY_ID));
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+
+        let completed = resolver
+            .get_trace(&session_id)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.event_type == EventType::CARPResolutionCompleted)
+            .unwrap();
+        assert_eq!(completed.payload["narrowing"]["excluded_actions"], json!(["test.create"]));
+        let state = resolver.state_at(&session_id, completed.sequence).unwrap();
+        assert_eq!(state.latest_resolution.unwrap().allowed_actions, vec!["test.get"]);
+
+        // Without narrowing fields the resolution is unchanged
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert!(resolution.narrowing.is_none());
+        assert!(resolution.is_action_allowed("test.create"));
+    }
+
+    #[test]
+    fn test_state_at_sequence() {
+        use crate::atlas::AtlasCapability;
+
+        let mut atlas = create_test_atlas();
+        atlas.capabilities.push(AtlasCapability {
+            capability_id: "write".to_string(),
+            name: "Write".to_string(),
+            description: String::new(),
+            actions: vec!["test.create".to_string()],
+        });
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+        let before_lock = resolver.get_trace(&session_id).unwrap().last().unwrap().sequence;
+        resolver.lock_capability(&session_id, "write", "ops").unwrap();
+        resolver.end_session(&session_id).unwrap();
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let completed = trace.iter().find(|e| e.event_type == EventType::CARPResolutionCompleted).unwrap();
+        let state = resolver.state_at(&session_id, completed.sequence).unwrap();
+        let latest = state.latest_resolution.as_ref().unwrap();
+        assert!(latest.completed);
+        assert_eq!(latest.resolution_id, resolution.trace_id);
+        assert_eq!(latest.allowed_actions.len(), resolution.allowed_actions.len());
+        assert!(latest.denied_actions.contains_key("test.delete"));
+        assert_eq!((state.resolution_count, state.action_count), (1, 0));
+        assert!(state.chain.is_valid && state.is_active);
+
+        let state = resolver.state_at(&session_id, before_lock).unwrap();
+        assert_eq!(state.action_count, 1);
+        assert!(state.capabilities.is_empty());
+
+        let last = trace.last().unwrap().sequence;
+        let state = resolver.state_at(&session_id, last).unwrap();
+        assert_eq!(state.capabilities["write"], crate::carp::CapabilityStatus::Locked);
+        assert!(!state.is_active);
+        assert_eq!(state.chain.event_count, trace.len());
+
+        assert_eq!(resolver.state_at(&session_id, last + 1).unwrap_err().error_code(), "INVALID_QUERY");
+        assert_eq!(resolver.state_at("missing", 0).unwrap_err().error_code(), "SESSION_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_session_report() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        let resolution = resolver.resolve(&request).unwrap();
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({"id": 2})).unwrap();
+        assert!(resolver.execute(&session_id, &resolution.trace_id, "test.delete", json!({})).is_err());
+        resolver.end_session(&session_id).unwrap();
+
+        let report = resolver.session_report(&session_id).unwrap();
+        assert_eq!(report.resolutions, 1);
+        assert_eq!((report.actions.attempted, report.actions.allowed, report.actions.denied), (3, 2, 1));
+        assert_eq!(report.actions.by_action["test.get"].allowed, 2);
+        assert_eq!(report.actions.denied_by_policy["deny-delete"], 1);
+        assert!(report.chain.is_valid);
+        assert!(!report.is_active && report.ended_at.is_some());
+        assert_eq!(report.event_count, resolver.get_trace(&session_id).unwrap().len());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("3 attempted, 2 allowed, 1 denied"));
+        assert!(markdown.contains("| deny-delete | 1 |"));
+        assert_eq!(resolver.session_report("missing").unwrap_err().error_code(), "SESSION_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_resolution_pinning() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        let resolution = resolver.resolve(&request).unwrap();
+        assert_eq!(resolution.atlas_versions.get("com.test.resolver").map(String::as_str), Some("1.0.0"));
+        assert_eq!(resolver.is_resolution_valid(&resolution.trace_id), Some(true));
+        assert_eq!(resolver.is_resolution_valid("resolution-unknown"), None);
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+
+        // Upgrading the atlas invalidates resolutions made against the old version
+        resolver.unload_atlas("com.test.resolver").unwrap();
+        let mut upgraded = create_test_atlas();
+        upgraded.version = "2.0.0".to_string();
+        resolver.load_atlas(upgraded).unwrap();
+
+        assert_eq!(resolver.is_resolution_valid(&resolution.trace_id), Some(false));
+        let err = resolver
+            .execute(&session_id, &resolution.trace_id, "test.get", json!({}))
+            .unwrap_err();
+        assert!(matches!(err, CRAError::ResolutionInvalidated { ref reason, .. } if reason.contains("unloaded")));
+        assert!(err.is_recoverable());
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let invalidated: Vec<_> = trace
+            .iter()
+            .filter(|e| e.event_type == EventType::ResolutionInvalidated)
+            .collect();
+        assert_eq!(invalidated.len(), 1);
+        assert_eq!(invalidated[0].payload["resolution_id"], resolution.trace_id);
+        assert_eq!(invalidated[0].payload["atlas_versions"]["com.test.resolver"], "1.0.0");
+        assert!(!trace.iter().any(|e| e.event_type == EventType::ActionRequested
+            && e.payload["resolution_id"] == resolution.trace_id
+            && e.sequence > invalidated[0].sequence));
+
+        // A fresh resolution pins the new version
+        let resolution = resolver.resolve(&request).unwrap();
+        assert_eq!(resolution.atlas_versions["com.test.resolver"], "2.0.0");
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+
+        resolver.end_session(&session_id).unwrap();
+        assert_eq!(resolver.is_resolution_valid(&resolution.trace_id), None);
+    }
+
+    #[test]
+    fn test_approval_escalation() {
+        use crate::carp::{HttpPostFn, SlackChannel, WebhookChannel};
+        use crate::atlas::AtlasPolicy;
+        use std::sync::Mutex;
+
+        /// Channel that hands out a decision queued by the test
+        struct Queue(Arc<Mutex<Option<ApprovalDecision>>>);
+
+        impl EscalationChannel for Queue {
+            fn name(&self) -> &str {
+                "queue"
+            }
+
+            fn send(&self, _request: &ApprovalRequest) -> std::result::Result<(), String> {
+                Ok(())
+            }
+
+            fn receive(&self, _approval_id: &str) -> Option<ApprovalDecision> {
+                self.0.lock().unwrap().take()
+            }
+        }
+
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let log = posted.clone();
+        let post: HttpPostFn = Arc::new(move |url: &str, _: &Value| {
+            log.lock().unwrap().push(url.to_string());
+            Ok(())
+        });
+        let slack_down: HttpPostFn = Arc::new(|_: &str, _: &Value| Err("503".to_string()));
+        let queued = Arc::new(Mutex::new(None));
+
+        let mut atlas = create_test_atlas();
+        atlas.policies.push(AtlasPolicy {
+            reason: Some("Creates need a reviewer".to_string()),
+            ..AtlasPolicy::requires_approval("review-create".to_string(), vec!["test.create".to_string()])
+        });
+        let mut resolver = Resolver::new()
+            .with_escalation_channel(WebhookChannel::new("https://hooks.example.com/approve", post))
+            .with_escalation_channel(SlackChannel::new("https://hooks.slack.com/x", slack_down))
+            .with_escalation_channel(Queue(queued.clone()));
+        resolver.load_atlas(atlas).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        // First attempt escalates
+        let err = resolver.execute(&session_id, "r1", "test.create", json!({ "n": 1 })).unwrap_err();
+        let CRAError::ActionRequiresApproval { approval_id, .. } = err else { panic!("{:?}", err) };
+        assert_eq!(posted.lock().unwrap().len(), 1);
+        let record = resolver.get_approval(&approval_id).unwrap();
+        assert_eq!(record.request.reason.as_deref(), Some("Creates need a reviewer"));
+        assert!(!record.deliveries[1].delivered);
+        assert_eq!(resolver.pending_approvals(&session_id).len(), 1);
+
+        // Retrying while pending does not escalate again
+        assert!(resolver.execute(&session_id, "r1", "test.create", json!({ "n": 1 })).is_err());
+        assert_eq!(posted.lock().unwrap().len(), 1);
+
+        // A pushed approval lets one execution through
+        resolver
+            .decide_approval(&approval_id, ApprovalDecision::approve("dana").with_channel("webhook"))
+            .unwrap();
+        resolver.execute(&session_id, "r1", "test.create", json!({ "n": 1 })).unwrap();
+        assert_eq!(resolver.get_approval(&approval_id).unwrap().status, ApprovalStatus::Executed);
+        assert!(matches!(
+            resolver.execute(&session_id, "r1", "test.create", json!({ "n": 1 })),
+            Err(CRAError::ActionRequiresApproval { .. })
+        ));
+
+        // A rejection pulled from a channel denies the action
+        let err = resolver.execute(&session_id, "r1", "test.create", json!({ "n": 2 })).unwrap_err();
+        let CRAError::ActionRequiresApproval { approval_id: second, .. } = err else { panic!("{:?}", err) };
+        *queued.lock().unwrap() = Some(ApprovalDecision::reject("sam").with_reason("Not today"));
+        let err = resolver.execute(&session_id, "r1", "test.create", json!({ "n": 2 })).unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { ref reason, .. } if reason.contains("sam")));
+        assert_eq!(resolver.poll_approval(&second).unwrap(), ApprovalStatus::Rejected);
+
+        // Actions without an approval policy are unaffected
+        resolver.execute(&session_id, "r1", "test.get", json!({})).unwrap();
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let requested: Vec<_> = trace.iter().filter(|e| e.event_type == EventType::ApprovalRequested).collect();
+        assert_eq!(requested.len(), 3);
+        assert_eq!(requested[0].payload["deliveries"][0]["channel"], "webhook");
+        assert_eq!(requested[0].payload["deliveries"][1]["error"], "503");
+
+        let decided: Vec<_> = trace.iter().filter(|e| e.event_type == EventType::ApprovalDecided).collect();
+        assert_eq!(decided.len(), 2);
+        assert_eq!(decided[0].payload["decided_by"], "dana");
+        assert_eq!(decided[0].payload["approved"], true);
+        assert_eq!(decided[1].payload["decided_by"], "sam");
+        assert_eq!(decided[1].payload["channel"], "queue");
+        assert_eq!(decided[1].payload["reason"], "Not today");
+
+        assert!(trace.iter().any(|e| e.event_type == EventType::ActionApproved
+            && e.payload["approval_id"] == approval_id.as_str()));
+        assert!(resolver.verify_chain(&session_id).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_metrics_snapshot() {
+        use crate::metrics::{self, NoopMetrics};
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        resolver.resolve(&request).unwrap();
+        resolver.resolve(&request).unwrap();
+        let unknown = CARPRequest::new("missing".to_string(), "test-agent".to_string(), "Test goal".to_string());
+        assert!(resolver.resolve(&unknown).is_err());
+        resolver.execute(&session_id, "r1", "test.get", json!({})).unwrap();
+        assert!(resolver.execute(&session_id, "r1", "test.delete", json!({})).is_err());
+
+        let snapshot = resolver.metrics_snapshot();
+        assert_eq!(snapshot.counter(metrics::RESOLUTIONS_TOTAL, &[("decision", "partial")]), 2);
+        assert_eq!(snapshot.counter(metrics::RESOLVE_ERRORS_TOTAL, &[("code", "SESSION_NOT_FOUND")]), 1);
+        assert_eq!(snapshot.histogram(metrics::RESOLVE_DURATION_US, &[]).unwrap().count, 3);
+        assert_eq!(snapshot.counter(metrics::POLICY_DECISIONS_TOTAL, &[("result", "deny")]), 2);
+        assert_eq!(snapshot.counter(metrics::POLICY_DECISIONS_TOTAL, &[("result", "no_match")]), 4);
+        assert_eq!(snapshot.counter(metrics::EXECUTIONS_TOTAL, &[("outcome", "executed")]), 1);
+        assert_eq!(snapshot.counter(metrics::EXECUTIONS_TOTAL, &[("outcome", "action_denied")]), 1);
+        assert_eq!(snapshot.histogram(metrics::EXECUTE_DURATION_US, &[]).unwrap().count, 2);
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        assert_eq!(snapshot.counter_total(metrics::TRACE_EVENTS_TOTAL), trace.len() as u64);
+        assert_eq!(
+            snapshot.counter(metrics::TRACE_EVENTS_TOTAL, &[("event_type", "action.executed")]),
+            1
+        );
+        assert!(snapshot.to_prometheus().contains("cra_executions_total{outcome=\"executed\"} 1"));
+
+        // Deferred tracing keeps reporting to the same sink
+        let mut resolver = Resolver::new().with_deferred_tracing(DeferredConfig::default());
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        resolver.flush_traces().unwrap();
+        assert_eq!(
+            resolver.metrics_snapshot().counter(metrics::TRACE_EVENTS_TOTAL, &[("event_type", "session.started")]),
+            1
+        );
+        resolver.end_session(&session_id).unwrap();
+
+        // Custom sinks replace the built-in aggregator
+        let mut resolver = Resolver::new().with_metrics_sink(NoopMetrics);
+        resolver.create_session("test-agent", "Test goal").unwrap();
+        assert_eq!(resolver.metrics_snapshot(), MetricsSnapshot::default());
+    }
+
+    #[test]
+    fn test_trace_chain() {
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(create_test_atlas()).unwrap();
+
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let request = CARPRequest::new(
+            session_id.clone(),
+            "test-agent".to_string(),
+            "Test goal".to_string(),
+        );
+        resolver.resolve(&request).unwrap();
+
+        // Verify chain
+        let verification = resolver.verify_chain(&session_id).unwrap();
+        assert!(verification.is_valid);
+    }
+
+    #[test]
+    fn test_end_session() {
+        let mut resolver = Resolver::new();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        assert!(resolver.get_session(&session_id).unwrap().is_active);
+
+        resolver.end_session(&session_id).unwrap();
+
+        assert!(!resolver.get_session(&session_id).unwrap().is_active);
+
+        // Trying to end again should fail
+        let result = resolver.end_session(&session_id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_custom_trigger_checkpoints() {
+        use crate::carp::{CheckpointQuestion, CheckpointTrigger, StewardCheckpointDef};
+
+        let mut atlas = create_test_atlas();
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new(
+                "high-volume",
+                "High Volume Review",
+                CheckpointTrigger::Custom {
+                    trigger_id: "volume".to_string(),
+                    params: [("limit".to_string(), json!(1))].into_iter().collect(),
+                },
+            )
+            .blocking()
+            .with_question(CheckpointQuestion::acknowledgment("ack", "Slow down")),
+        );
+        atlas.checkpoints.push(StewardCheckpointDef::new(
+            "unregistered",
+            "Unregistered",
+            CheckpointTrigger::Custom {
+                trigger_id: "nobody-home".to_string(),
+                params: HashMap::new(),
+            },
+        ));
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        resolver.register_custom_trigger("volume", |ctx, params| {
+            let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(0);
+            if ctx.action_id.as_deref() == Some("test.create") && ctx.total_actions >= limit {
+                CustomTriggerDecision::Fire
+            } else {
+                CustomTriggerDecision::Skip
+            }
+        });
+
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let fired = resolver.evaluate_action_checkpoints(&session_id, "test.get").unwrap();
+        assert!(fired.is_empty());
+
+        resolver.checkpoint_states.get_mut(&session_id).unwrap().record_action();
+        let fired = resolver.evaluate_action_checkpoints(&session_id, "test.create").unwrap();
+        assert_eq!(fired.len(), 1);
+        assert!(matches!(
+            fired[0].trigger_data,
+            Some(TriggerData::Custom { ref trigger_id, .. }) if trigger_id == "volume"
+        ));
+        assert!(resolver.has_pending_checkpoints(&session_id));
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let triggered: Vec<_> = trace.iter()
+            .filter(|e| e.event_type == EventType::CheckpointTriggered)
+            .collect();
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].payload["trigger_id"], "volume");
+
+        let skipped: Vec<_> = trace.iter()
+            .filter(|e| e.event_type == EventType::CheckpointSkipped)
+            .collect();
+        // volume skipped once, unregistered skipped twice
+        assert_eq!(skipped.len(), 3);
+        assert!(skipped.iter().any(|e| e.payload["reason"] == "no_callback_registered"));
+        assert!(resolver.verify_chain(&session_id).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_respond_with_custom_answer_validator() {
+        use crate::carp::{AnswerValidation, CheckpointTrigger, StewardCheckpointDef};
+
+        let mut atlas = create_test_atlas();
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("onboarding", "Onboarding", CheckpointTrigger::SessionStart)
+                .blocking()
+                .with_question(CheckpointQuestion::text("employee-id", "Employee ID?").with_validation(
+                    AnswerValidation {
+                        pattern: None,
+                        min_length: None,
+                        max_length: None,
+                        must_contain: vec![],
+                        must_not_contain: vec![],
+                        custom_validator: Some("employee-directory".to_string()),
+                    },
+                )),
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        resolver.register_answer_validator("employee-directory", |_, answer| match answer {
+            AnswerValue::Text(id) if id == "E42" => Ok(()),
+            _ => Err("Unknown employee".to_string()),
+        });
+
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let respond = |id: &str| CheckpointResponse {
+            checkpoint_id: "onboarding".to_string(),
+            answers: [("employee-id".to_string(), AnswerValue::Text(id.to_string()))]
+                .into_iter()
+                .collect(),
+            guidance_acknowledged: false,
+            responded_at: Utc::now().to_rfc3339(),
+            session_id: session_id.clone(),
+        };
+
+        let validation = resolver.respond_to_checkpoint(&session_id, &respond("E1")).unwrap();
+        assert!(!validation.is_valid);
+        assert!(resolver.has_pending_checkpoints(&session_id));
+
+        let validation = resolver.respond_to_checkpoint(&session_id, &respond("E42")).unwrap();
+        assert!(validation.is_valid);
+        assert!(!resolver.has_pending_checkpoints(&session_id));
+    }
+
+    #[test]
+    fn test_latency_budget_covers_validators_and_triggers() {
+        use crate::carp::{AnswerValidation, CheckpointTrigger, StewardCheckpointDef};
+        use std::time::Duration;
+
+        let validated_by = |name: &str| AnswerValidation {
+            pattern: None,
+            min_length: None,
+            max_length: None,
+            must_contain: vec![],
+            must_not_contain: vec![],
+            custom_validator: Some(name.to_string()),
+        };
+        let mut atlas = create_test_atlas();
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("intake", "Intake", CheckpointTrigger::SessionStart)
+                .blocking()
+                .with_question(CheckpointQuestion::text("ticket", "Ticket?").with_validation(validated_by("slow")))
+                .with_question(CheckpointQuestion::text("owner", "Owner?").with_validation(validated_by("fast"))),
+        );
+        for checkpoint_id in ["first", "second"] {
+            atlas.checkpoints.push(StewardCheckpointDef::new(
+                checkpoint_id,
+                checkpoint_id,
+                CheckpointTrigger::Custom {
+                    trigger_id: "slow".to_string(),
+                    params: HashMap::new(),
+                },
+            ));
+        }
+
+        let mut resolver = Resolver::new().with_policy_latency_budget(Duration::from_millis(10));
+        resolver.load_atlas(atlas).unwrap();
+        resolver.register_answer_validator("slow", |_, _| {
+            std::thread::sleep(Duration::from_millis(30));
+            Ok(())
+        });
+        resolver.register_answer_validator("fast", |_, _| Ok(()));
+        resolver.register_custom_trigger("slow", |_, _| {
+            std::thread::sleep(Duration::from_millis(30));
+            CustomTriggerDecision::Fire
+        });
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        // The slow validator spends the budget, so the fast one is never asked
+        let response = CheckpointResponse {
+            checkpoint_id: "intake".to_string(),
+            answers: [
+                ("ticket".to_string(), AnswerValue::Text("T-1".to_string())),
+                ("owner".to_string(), AnswerValue::Text("ops".to_string())),
+            ]
+            .into_iter()
+            .collect(),
+            guidance_acknowledged: false,
+            responded_at: Utc::now().to_rfc3339(),
+            session_id: session_id.clone(),
+        };
+        let validation = resolver.respond_to_checkpoint(&session_id, &response).unwrap();
+        assert!(!validation.is_valid);
+        assert!(validation.question_results["ticket"].is_valid);
+        let owner = &validation.question_results["owner"];
+        assert!(owner.error_message.as_deref().unwrap().contains("latency budget exhausted"));
+
+        // Likewise the first slow trigger leaves nothing for the second
+        let fired = resolver.evaluate_custom_checkpoints(&session_id, None).unwrap();
+        assert_eq!(fired.len(), 1);
+        let skipped: Vec<Value> = resolver
+            .get_trace(&session_id)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == EventType::CheckpointSkipped)
+            .map(|e| e.payload)
+            .collect();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0]["reason"], "latency_budget");
+    }
+
+    #[test]
+    fn test_guidance_injection_and_expiry() {
+        use crate::carp::{CheckpointTrigger, GuidanceBlock, StewardCheckpointDef, GUIDANCE_BLOCK_ID};
+
+        let mut atlas = create_test_atlas();
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("welcome", "Welcome", CheckpointTrigger::SessionStart)
+                .with_guidance(GuidanceBlock::text("Read-only until reviewed").expires_after("review")),
+        );
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("rules", "Rules", CheckpointTrigger::SessionStart)
+                .with_guidance(GuidanceBlock::text("Never share secrets").with_priority(50)),
+        );
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("review", "Review", CheckpointTrigger::SessionStart)
+                .blocking()
+                .with_question(CheckpointQuestion::acknowledgment("ack", "I will follow the rules")),
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        let guidance = resolution.context_blocks.iter().find(|b| b.block_id == GUIDANCE_BLOCK_ID).unwrap();
+        assert_eq!(guidance.content, "Never share secrets\n\nRead-only until reviewed");
+
+        let response = CheckpointResponse {
+            checkpoint_id: "review".to_string(),
+            answers: [("ack".to_string(), AnswerValue::Acknowledged)].into_iter().collect(),
+            guidance_acknowledged: true,
+            responded_at: Utc::now().to_rfc3339(),
+            session_id: session_id.clone(),
+        };
+        assert!(resolver.respond_to_checkpoint(&session_id, &response).unwrap().is_valid);
+
+        let resolution = resolver.resolve(&request).unwrap();
+        let guidance = resolution.context_blocks.iter().find(|b| b.block_id == GUIDANCE_BLOCK_ID).unwrap();
+        assert_eq!(guidance.content, "Never share secrets");
+
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let expired: Vec<_> = trace.iter()
+            .filter(|e| e.event_type == EventType::CheckpointGuidanceExpired)
+            .collect();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].payload["checkpoint_id"], "welcome");
+        assert_eq!(expired[0].payload["expired_by"], "review");
+        assert_eq!(
+            trace.iter().filter(|e| e.event_type == EventType::CheckpointGuidanceInjected).count(),
+            2
+        );
+        assert!(resolver.verify_chain(&session_id).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_capability_state_transitions() {
+        use crate::atlas::AtlasCapability;
+        use crate::carp::{CapabilityState, CapabilityStatus, CheckpointTrigger, StewardCheckpointDef};
+
+        let mut atlas = create_test_atlas();
+        atlas.capabilities.push(AtlasCapability {
+            capability_id: "write".to_string(),
+            name: "Write".to_string(),
+            description: String::new(),
+            actions: vec!["test.create".to_string()],
+        });
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("write-gate", "Write Gate", CheckpointTrigger::SessionStart)
+                .blocking()
+                .with_question(CheckpointQuestion::acknowledgment("ack", "I understand"))
+                .unlock_capabilities(vec!["write".to_string()]),
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        assert_eq!(
+            resolver.get_capability_state(&session_id).unwrap().status("write"),
+            Some(CapabilityStatus::PendingGate)
+        );
 
-            return Err(CRAError::ActionDenied { policy_id, reason });
-        }
+        // Gated capability is denied in resolution and execution
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert!(resolution.denied_actions.iter().any(|a| a.action_id == "test.create"));
+        assert!(resolution.allowed_actions.iter().any(|a| a.action_id == "test.get"));
+        assert!(resolver.execute(&session_id, &resolution.trace_id, "test.create", json!({})).is_err());
+
+        // Passing the checkpoint grants it
+        let response = CheckpointResponse {
+            checkpoint_id: "write-gate".to_string(),
+            answers: [("ack".to_string(), AnswerValue::Acknowledged)].into_iter().collect(),
+            guidance_acknowledged: true,
+            responded_at: Utc::now().to_rfc3339(),
+            session_id: session_id.clone(),
+        };
+        assert!(resolver.respond_to_checkpoint(&session_id, &response).unwrap().is_valid);
+        assert!(resolver.is_capability_unlocked(&session_id, "write"));
+        assert!(resolver.execute(&session_id, &resolution.trace_id, "test.create", json!({})).is_ok());
 
-        // Find the action definition
-        let action = self
-            .atlases
-            .values()
-            .flat_map(|a| a.actions.iter())
-            .find(|a| a.action_id == action_id)
-            .ok_or_else(|| CRAError::ActionNotFound {
-                action_id: action_id.to_string(),
-            })?;
+        // Admin APIs
+        assert!(resolver.lock_capability(&session_id, "write", "ops").unwrap());
+        assert!(!resolver.lock_capability(&session_id, "write", "ops").unwrap());
+        assert!(resolver.lock_capability(&session_id, "missing", "ops").is_err());
+        assert!(resolver.execute(&session_id, &resolution.trace_id, "test.create", json!({})).is_err());
 
-        // In a real implementation, you would validate parameters against schema
-        // and execute the actual action here. For now, we just record the execution.
+        // Every transition is traced and the state can be rebuilt from the trace
+        let trace = resolver.get_trace(&session_id).unwrap();
+        let changes: Vec<_> = trace.iter()
+            .filter(|e| e.event_type == EventType::CapabilityStateChanged)
+            .collect();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[1].payload["checkpoint_id"], "write-gate");
+        assert_eq!(changes[2].payload["actor"], "ops");
+
+        let replayed = CapabilityState::replay(&trace);
+        assert_eq!(&replayed, resolver.get_capability_state(&session_id).unwrap());
+
+        let other = resolver.create_session("test-agent", "Test goal").unwrap();
+        resolver.restore_capability_state(&other, replayed).unwrap();
+        assert_eq!(
+            resolver.get_capability_state(&other).unwrap().status("write"),
+            Some(CapabilityStatus::Locked)
+        );
+    }
 
-        // Emit action.approved event
-        self.trace_collector.emit(
-            session_id,
-            EventType::ActionApproved,
-            serde_json::json!({
-                "action_id": action_id,
-                "resolution_id": resolution_id,
-            }),
-        )?;
+    #[test]
+    fn test_deterministic_mode() {
+        use crate::clock::FixedClock;
+        use crate::id::SequentialIdGen;
+
+        let run = || {
+            let mut resolver = Resolver::new()
+                .with_clock(FixedClock::from_millis(1_700_000_000_000))
+                .with_id_generator(SequentialIdGen::new());
+            resolver.load_atlas(create_test_atlas()).unwrap();
+            let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+            let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+            let resolution = resolver.resolve(&request).unwrap();
+            resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+            let trace = resolver.trace_collector().export_jsonl(&session_id).unwrap();
+            (session_id, resolution, trace)
+        };
 
-        // Simulate execution
-        let start = std::time::Instant::now();
+        let (first_session, first_resolution, first_trace) = run();
+        let (second_session, second_resolution, second_trace) = run();
 
-        // Placeholder result - in reality this would come from actual action execution
-        let result = serde_json::json!({
-            "status": "success",
-            "action_id": action_id,
-            "message": format!("Action {} executed successfully", action.name),
-        });
+        assert_eq!(first_session, "00000000-0000-0000-0000-000000000001");
+        assert_eq!(first_session, second_session);
+        assert_eq!(first_resolution.trace_id, second_resolution.trace_id);
+        assert_eq!(first_resolution.timestamp.timestamp_millis(), 1_700_000_000_000);
+        assert_eq!(first_trace, second_trace);
+    }
 
-        let duration_ms = start.elapsed().as_millis() as u64;
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_event_signing() {
+        use crate::atlas::StewardConfig;
+        use crate::crypto::Ed25519Signer;
 
-        // Update session stats
-        session.action_count += 1;
+        let mut resolver = Resolver::new().with_event_signer(Ed25519Signer::from_bytes("runtime", &[1; 32]));
+        resolver.load_atlas(create_test_atlas()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        resolver.end_session(&session_id).unwrap();
 
-        // Emit action.executed event
-        self.trace_collector.emit(
-            session_id,
-            EventType::ActionExecuted,
-            serde_json::json!({
-                "action_id": action_id,
-                "execution_id": execution_id,
-                "duration_ms": duration_ms,
-                "result_hash": hash_value(&result),
-            }),
-        )?;
+        let verification = resolver.verify_chain(&session_id).unwrap();
+        assert!(verification.is_valid);
+        assert_eq!(verification.signatures_valid, Some(true));
+        assert!(resolver.get_trace(&session_id).unwrap().iter().all(|e| e.signature.is_some()));
+
+        // Keys published by atlas stewards are trusted; others are not
+        let steward_key = Ed25519Signer::from_bytes("steward", &[2; 32]).public_key();
+        let mut atlas = create_test_atlas();
+        atlas.steward = Some(StewardConfig::new("steward").with_trace_key(steward_key));
+        let verifier = Resolver::new();
+        let mut with_atlas = Resolver::new();
+        with_atlas.load_atlas(atlas).unwrap();
+        assert!(verifier.trace_keys().is_empty());
+        assert_eq!(with_atlas.trace_keys().len(), 1);
+
+        let keys = crate::crypto::KeyRing::from_keys(&with_atlas.trace_keys()).unwrap();
+        let verification = keys.verify_chain(&resolver.get_trace(&session_id).unwrap());
+        assert!(verification.is_valid);
+        assert_eq!(verification.signatures_valid, Some(false));
+    }
 
-        Ok(result)
+    #[test]
+    fn test_session_handoff() {
+        let mut edge = Resolver::new();
+        edge.load_atlas(create_test_atlas()).unwrap();
+        let session_id = edge.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        edge.resolve(&request).unwrap();
+
+        let snapshot = edge.export_session(&session_id, "central").unwrap();
+        assert_eq!(snapshot.resolution_count, 1);
+        assert!(!edge.get_session(&session_id).unwrap().is_active);
+        assert!(matches!(edge.resolve(&request), Err(CRAError::SessionAlreadyEnded { .. })));
+
+        let mut central = Resolver::new();
+        assert!(matches!(
+            central.import_session(snapshot.clone()),
+            Err(CRAError::AtlasNotFound { .. })
+        ));
+        central.load_atlas(create_test_atlas()).unwrap();
+        central.import_session(snapshot.clone()).unwrap();
+        assert!(matches!(
+            central.import_session(snapshot.clone()),
+            Err(CRAError::SessionAlreadyExists { .. })
+        ));
+
+        let resolution = central.resolve(&request).unwrap();
+        central.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+        central.end_session(&session_id).unwrap();
+
+        let trace = central.get_trace(&session_id).unwrap();
+        let out = snapshot.events.len() - 1;
+        assert_eq!(trace[out].event_type, EventType::SessionHandoffOut);
+        assert_eq!(trace[out + 1].event_type, EventType::SessionHandoffIn);
+        assert_eq!(trace[out + 1].previous_event_hash, snapshot.chain_head.event_hash);
+        assert_eq!(trace[out + 1].trace_id, trace[0].trace_id);
+        assert!(central.verify_chain(&session_id).unwrap().is_valid);
+        assert_eq!(central.get_session(&session_id).unwrap().resolution_count, 2);
     }
 
-    /// Get the TRACE for a session
-    pub fn get_trace(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
-        self.trace_collector.get_events(session_id)
+    #[test]
+    fn test_session_handoff_rejects_broken_chain() {
+        let mut edge = Resolver::new();
+        edge.load_atlas(create_test_atlas()).unwrap();
+        let session_id = edge.create_session("test-agent", "Test goal").unwrap();
+        let mut snapshot = edge.export_session(&session_id, "central").unwrap();
+
+        let mut central = Resolver::new();
+        central.load_atlas(create_test_atlas()).unwrap();
+
+        let mut truncated = snapshot.clone();
+        truncated.events.pop();
+        assert!(matches!(
+            central.import_session(truncated),
+            Err(CRAError::SessionHandoffError { .. })
+        ));
+
+        snapshot.events[0].payload = json!({"agent_id": "someone-else"});
+        assert!(matches!(
+            central.import_session(snapshot),
+            Err(CRAError::TraceChainIntegrityError { .. })
+        ));
     }
 
-    /// Verify the hash chain integrity for a session
-    pub fn verify_chain(&self, session_id: &str) -> Result<crate::trace::ChainVerification> {
-        self.trace_collector.verify_chain(session_id)
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_signed_session_handoff() {
+        use crate::crypto::Ed25519Signer;
+
+        let edge_key = Ed25519Signer::from_bytes("edge", &[1; 32]);
+        let mut edge_atlas = create_test_atlas();
+        edge_atlas.steward = Some(crate::atlas::StewardConfig::new("steward").with_trace_key(edge_key.public_key()));
+
+        let mut edge = Resolver::new().with_event_signer(edge_key);
+        edge.load_atlas(create_test_atlas()).unwrap();
+        let session_id = edge.create_session("test-agent", "Test goal").unwrap();
+        let snapshot = edge.export_session(&session_id, "central").unwrap();
+        assert_eq!(snapshot.signature.as_ref().unwrap().key_id, "edge");
+
+        // The central resolver trusts the edge key via the atlas steward
+        let mut central = Resolver::new().with_event_signer(Ed25519Signer::from_bytes("central", &[2; 32]));
+        central.load_atlas(edge_atlas).unwrap();
+
+        let mut forged = snapshot.clone();
+        forged.action_count = 99;
+        assert!(matches!(
+            central.import_session(forged),
+            Err(CRAError::SessionHandoffError { .. })
+        ));
+
+        let mut unsigned = snapshot.clone();
+        unsigned.signature = None;
+        assert!(central.import_session(unsigned).is_err());
+
+        central.import_session(snapshot).unwrap();
+        central.end_session(&session_id).unwrap();
+        let verification = central.verify_chain(&session_id).unwrap();
+        assert!(verification.is_valid);
+        assert_eq!(verification.signatures_valid, Some(true));
     }
 
-    /// Get the trace collector (for advanced operations)
-    pub fn trace_collector(&self) -> &TraceCollector {
-        &self.trace_collector
+    fn atlas_with_capability() -> AtlasManifest {
+        let mut atlas = create_test_atlas();
+        atlas.capabilities.push(crate::atlas::AtlasCapability::new(
+            "read".to_string(),
+            "Read".to_string(),
+            vec!["test.get".to_string(), "test.delete".to_string()],
+        ));
+        atlas
     }
-}
 
-impl Default for Resolver {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_strict_mode() {
+        let mut resolver = Resolver::new().with_strict_mode(true);
+        resolver.load_atlas(atlas_with_capability()).unwrap();
+        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+
+        let resolution = resolver.resolve(&request).unwrap();
+        let allowed: Vec<&str> = resolution.allowed_actions.iter().map(|a| a.action_id.as_str()).collect();
+        assert_eq!(allowed, vec!["test.get"]);
+        let create = resolution.denied_actions.iter().find(|d| d.action_id == "test.create").unwrap();
+        assert_eq!(create.policy_id, STRICT_MODE_POLICY_ID);
+        // Explicit deny policies still apply to granted actions
+        let delete = resolution.denied_actions.iter().find(|d| d.action_id == "test.delete").unwrap();
+        assert_eq!(delete.policy_id, "deny-delete");
+
+        let err = resolver.execute(&session_id, &resolution.trace_id, "test.create", json!({})).unwrap_err();
+        assert!(matches!(err, CRAError::ActionDenied { ref policy_id, .. } if policy_id == STRICT_MODE_POLICY_ID));
+        resolver.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+
+        // Without strict mode, ungranted actions fall through to allow
+        let mut permissive = Resolver::new();
+        permissive.load_atlas(atlas_with_capability()).unwrap();
+        let session_id = permissive.create_session("test-agent", "Test goal").unwrap();
+        let request = CARPRequest::new(session_id, "test-agent".to_string(), "Test goal".to_string());
+        let resolution = permissive.resolve(&request).unwrap();
+        assert!(resolution.allowed_actions.iter().any(|a| a.action_id == "test.create"));
     }
-}
 
-/// Hash a JSON value for audit purposes
-fn hash_value(value: &Value) -> String {
-    use sha2::{Digest, Sha256};
+    #[test]
+    fn test_action_namespaces() {
+        let mut atlas = create_test_atlas();
+        atlas.namespaces.push(crate::atlas::AtlasNamespace::new("test.admin".to_string()).with_risk_tier(crate::atlas::RiskTier::Critical));
+        atlas.namespaces.push(crate::atlas::AtlasNamespace::new("test".to_string()).with_risk_tier(crate::atlas::RiskTier::Medium));
+        for action_id in ["test.admin.purge", "test.admin.users.list", "test.list"] {
+            atlas.actions.push(serde_json::from_value(json!({
+                "action_id": action_id,
+                "name": action_id,
+                "description": "",
+                "parameters_schema": { "type": "object" }
+            })).unwrap());
+        }
+        atlas.capabilities.push(crate::atlas::AtlasCapability::new(
+            "admin".to_string(),
+            "Admin".to_string(),
+            vec!["test.admin.**".to_string()],
+        ));
+        atlas.capabilities.push(crate::atlas::AtlasCapability::new(
+            "basic".to_string(),
+            "Basic".to_string(),
+            vec!["test.*".to_string()],
+        ));
+        assert!(atlas.validate().is_ok());
+
+        let mut resolver = Resolver::new().with_strict_mode(true);
+        resolver.load_atlas(atlas).unwrap();
 
-    let canonical = serde_json::to_string(value).unwrap_or_default();
-    let hash = Sha256::digest(canonical.as_bytes());
-    hex::encode(hash)
-}
+        let admin: Vec<&str> = resolver.actions_in_namespace("test.admin").iter().map(|a| a.action_id.as_str()).collect();
+        assert_eq!(admin, vec!["test.admin.purge", "test.admin.users.list"]);
+        assert_eq!(resolver.actions_in_namespace("test").len(), 6);
+        assert!(resolver.actions_in_namespace("tes").is_empty());
+
+        // Closest namespace default wins; declared tiers are kept
+        let tier = |id: &str| resolver.get_atlas("com.test.resolver").unwrap().get_action(id).unwrap().risk_tier.clone();
+        assert_eq!(tier("test.admin.users.list"), "critical");
+        assert_eq!(tier("test.list"), "medium");
+        assert_eq!(tier("test.get"), "low");
+
+        // `test.*` grants one level, `test.admin.**` any depth below admin
+        let coverage = resolver.policy_coverage();
+        let granted = |id: &str| coverage.actions.iter().find(|a| a.action_id == id).unwrap().capabilities.clone();
+        assert_eq!(granted("test.list"), vec!["basic"]);
+        assert_eq!(granted("test.admin.users.list"), vec!["admin"]);
+        assert!(coverage.ungranted_actions.is_empty());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
+    #[test]
+    fn test_content_policies() {
+        let mut atlas = create_test_atlas();
+        atlas.policies.push(serde_json::from_value(json!({
+            "policy_id": "no-pii",
+            "type": "content",
+            "parameters": {
+                "stages": ["output"],
+                "action": "redact",
+                "detectors": [{ "type": "regex", "name": "email", "patterns": ["\\S+@\\S+"] }]
+            }
+        })).unwrap());
 
-    fn create_test_atlas() -> AtlasManifest {
-        serde_json::from_value(json!({
-            "atlas_version": "1.0",
-            "atlas_id": "com.test.resolver",
-            "version": "1.0.0",
-            "name": "Test Resolver Atlas",
-            "description": "Atlas for testing the resolver",
-            "domains": ["test"],
-            "capabilities": [],
-            "policies": [
-                {
-                    "policy_id": "deny-delete",
-                    "type": "deny",
-                    "actions": ["*.delete"],
-                    "reason": "Deletion not allowed"
-                }
-            ],
-            "actions": [
-                {
-                    "action_id": "test.get",
-                    "name": "Get Test",
-                    "description": "Get a test resource",
-                    "parameters_schema": { "type": "object" },
-                    "risk_tier": "low"
-                },
-                {
-                    "action_id": "test.create",
-                    "name": "Create Test",
-                    "description": "Create a test resource",
-                    "parameters_schema": { "type": "object" },
-                    "risk_tier": "medium"
-                },
-                {
-                    "action_id": "test.delete",
-                    "name": "Delete Test",
-                    "description": "Delete a test resource",
-                    "parameters_schema": { "type": "object" },
-                    "risk_tier": "high"
-                }
-            ]
-        }))
-        .unwrap()
+        let mut resolver = Resolver::new();
+        resolver.load_atlas(atlas.clone()).unwrap();
+        let policies = resolver.content_policies();
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].policy_id, "no-pii");
+
+        // Content policies never match actions
+        let coverage = resolver.policy_coverage();
+        assert!(coverage.actions.iter().all(|a| !a.policies.contains(&"no-pii".to_string())));
+
+        atlas.atlas_id = "com.test.broken".to_string();
+        atlas.policies.last_mut().unwrap().parameters = Some(json!({ "action": "redact", "detectors": [] }));
+        let err = resolver.load_atlas(atlas).unwrap_err();
+        assert!(matches!(err, CRAError::InvalidAtlasManifest { .. }));
     }
 
     #[test]
-    fn test_load_atlas() {
+    fn test_session_variables() {
+        use crate::carp::StewardCheckpointDef;
+
+        let mut atlas = create_test_atlas();
+        atlas.actions[0].parameters_schema = json!({
+            "type": "object",
+            "properties": { "tenant": { "type": "string", "default": "${tenant_id}" } }
+        });
+        atlas.actions[0].executor = Some("webhook:https://${env}.example.com/get".to_string());
+        atlas.checkpoints.push(
+            StewardCheckpointDef::new("setup", "Setup", CheckpointTrigger::SessionStart)
+                .blocking()
+                .with_question(CheckpointQuestion::text("env", "Which environment?").sets_variable("env")),
+        );
+
         let mut resolver = Resolver::new();
-        let atlas = create_test_atlas();
+        resolver.load_atlas(atlas).unwrap();
+        let variables: SessionVariables = [("tenant_id".to_string(), "acme".to_string())].into_iter().collect();
+        let session_id = resolver.create_session_with_variables("test-agent", "Test goal", variables).unwrap();
+
+        // `env` is not set until the checkpoint is answered
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let err = resolver.resolve(&request).unwrap_err();
+        assert!(matches!(err, CRAError::UnknownSessionVariable { ref variable, ref action_id }
+            if variable == "env" && action_id == "test.get"));
+
+        let response = CheckpointResponse {
+            checkpoint_id: "setup".to_string(),
+            answers: [("env".to_string(), AnswerValue::Text("staging".to_string()))].into_iter().collect(),
+            guidance_acknowledged: false,
+            responded_at: Utc::now().to_rfc3339(),
+            session_id: session_id.clone(),
+        };
+        assert!(resolver.respond_to_checkpoint(&session_id, &response).unwrap().is_valid);
+        assert_eq!(resolver.session_variables(&session_id).unwrap()["env"], "staging");
 
-        let result = resolver.load_atlas(atlas);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "com.test.resolver");
-        assert!(resolver.get_atlas("com.test.resolver").is_some());
+        let resolution = resolver.resolve(&request).unwrap();
+        let get = resolution.allowed_actions.iter().find(|a| a.action_id == "test.get").unwrap();
+        assert_eq!(get.parameters_schema["properties"]["tenant"]["default"], "acme");
+        assert_eq!(get.executor.as_deref(), Some("webhook:https://staging.example.com/get"));
+
+        resolver.set_session_variable(&session_id, "tenant_id", "globex").unwrap();
+        let resolution = resolver.resolve(&request).unwrap();
+        let get = resolution.allowed_actions.iter().find(|a| a.action_id == "test.get").unwrap();
+        assert_eq!(get.parameters_schema["properties"]["tenant"]["default"], "globex");
+
+        assert!(resolver.set_session_variable(&session_id, "bad name", "x").is_err());
+
+        // Only variable names reach the trace
+        let started = &resolver.get_trace(&session_id).unwrap()[0];
+        assert_eq!(started.payload["variables"], json!(["tenant_id"]));
     }
 
     #[test]
-    fn test_create_session() {
+    fn test_policy_coverage() {
         let mut resolver = Resolver::new();
-        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        resolver.load_atlas(atlas_with_capability()).unwrap();
+
+        let coverage = resolver.policy_coverage();
+        assert!(!coverage.strict_mode);
+        assert_eq!(coverage.actions.len(), 3);
+        let delete = coverage.actions.iter().find(|a| a.action_id == "test.delete").unwrap();
+        assert_eq!(delete.policies, vec!["deny-delete"]);
+        assert_eq!(delete.capabilities, vec!["read"]);
+        assert_eq!(coverage.uncovered_actions, vec!["test.create", "test.get"]);
+        assert_eq!(coverage.ungranted_actions, vec!["test.create"]);
+        assert_eq!(coverage.default_allowed_actions, vec!["test.create", "test.get"]);
+        assert!(!coverage.is_least_privilege());
+
+        let mut strict = Resolver::new().with_strict_mode(true);
+        strict.load_atlas(atlas_with_capability()).unwrap();
+        let coverage = strict.policy_coverage();
+        assert_eq!(coverage.uncovered_actions, vec!["test.create", "test.get"]);
+        assert!(coverage.is_least_privilege());
+    }
 
-        let session = resolver.get_session(&session_id).unwrap();
-        assert_eq!(session.agent_id, "test-agent");
-        assert_eq!(session.goal, "Test goal");
-        assert!(session.is_active);
+    #[test]
+    fn test_snapshot_restore() {
+        let mut atlas = create_test_atlas();
+        atlas.policies.push(crate::atlas::AtlasPolicy::rate_limit("limit-get".to_string(), vec!["test.get".to_string()], 2, 60));
+
+        let mut original = Resolver::new().with_default_ttl(120);
+        original.load_atlas(atlas).unwrap();
+        let active = original.create_session("test-agent", "Keep going").unwrap();
+        let ended = original.create_session("test-agent", "Done").unwrap();
+        original.end_session(&ended).unwrap();
+        let request = CARPRequest::new(active.clone(), "test-agent".to_string(), "Keep going".to_string());
+        let resolution = original.resolve(&request).unwrap();
+        original.execute(&active, &resolution.trace_id, "test.get", json!({})).unwrap();
+
+        let bytes = original.snapshot().unwrap();
+        assert!(bytes.starts_with(crate::carp::RESOLVER_SNAPSHOT_MAGIC));
+
+        let mut restored = Resolver::new();
+        restored.restore(&bytes).unwrap();
+        assert_eq!(restored.default_ttl, 120);
+        assert!(restored.get_atlas("com.test.resolver").is_some());
+        assert!(!restored.get_session(&ended).unwrap().is_active);
+        assert_eq!(
+            restored.policy_evaluator.get_rate_limit_count("limit-get", "test.get"),
+            original.policy_evaluator.get_rate_limit_count("limit-get", "test.get"),
+        );
+
+        // Chains continue where they left off
+        let before = original.get_trace(&active).unwrap();
+        restored.resolve(&request).unwrap();
+        let trace = restored.get_trace(&active).unwrap();
+        assert_eq!(trace[before.len()].previous_event_hash, before.last().unwrap().event_hash);
+        assert_eq!(trace[before.len()].trace_id, before[0].trace_id);
+        assert!(restored.verify_chain(&active).unwrap().is_valid);
+        assert_eq!(restored.get_session(&active).unwrap().resolution_count, 2);
+
+        assert!(matches!(
+            restored.restore(&bytes),
+            Err(CRAError::ResolverSnapshotError { .. })
+        ));
     }
 
     #[test]
-    fn test_resolve_request() {
-        let mut resolver = Resolver::new();
-        resolver.load_atlas(create_test_atlas()).unwrap();
+    fn test_restore_rejects_tampered_snapshot() {
+        let mut original = Resolver::new();
+        original.load_atlas(create_test_atlas()).unwrap();
+        original.create_session("test-agent", "Test goal").unwrap();
+
+        let mut state = ResolverState::decode(&original.snapshot().unwrap()).unwrap();
+        state.traces[0].events[0].payload = json!({"agent_id": "someone-else"});
+        // Re-encoding gives a valid digest, so the chain check must catch it
+        let bytes = state.encode().unwrap();
+        assert!(matches!(
+            Resolver::new().restore(&bytes),
+            Err(CRAError::TraceChainIntegrityError { .. })
+        ));
+
+        let mut bytes = original.snapshot().unwrap();
+        let last = bytes.len() - 2;
+        bytes[last] ^= 0x01;
+        assert!(matches!(
+            Resolver::new().restore(&bytes),
+            Err(CRAError::ResolverSnapshotError { .. })
+        ));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_rotate_event_signer() {
+        use crate::crypto::Ed25519Signer;
+        use crate::trace::ADMIN_AUDIT_SESSION;
 
+        let mut resolver = Resolver::new().with_event_signer(Ed25519Signer::from_bytes("k1", &[1; 32]));
+        resolver.load_atlas(create_test_atlas()).unwrap();
         let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
 
-        let request = CARPRequest::new(
-            session_id.clone(),
-            "test-agent".to_string(),
-            "I want to test things".to_string(),
-        );
+        let payload = resolver
+            .rotate_event_signer(Ed25519Signer::from_bytes("k2", &[2; 32]), chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(payload.previous_key_id.as_deref(), Some("k1"));
+        resolver.end_session(&session_id).unwrap();
 
-        let resolution = resolver.resolve(&request).unwrap();
+        let key_ids: Vec<String> = resolver.trace_keys().into_iter().map(|k| k.key_id).collect();
+        assert_eq!(key_ids, vec!["k2", "k1"]);
 
-        // test.get and test.create should be allowed
-        // test.delete should be denied
-        assert!(resolution.is_action_allowed("test.get"));
-        assert!(resolution.is_action_allowed("test.create"));
-        assert!(!resolution.is_action_allowed("test.delete"));
+        let verification = resolver.verify_chain(&session_id).unwrap();
+        assert_eq!(verification.signatures_valid, Some(true));
 
-        // Check denied reason
-        let denial = resolution.denied_actions.iter()
-            .find(|d| d.action_id == "test.delete");
-        assert!(denial.is_some());
+        let audit = resolver.get_trace(ADMIN_AUDIT_SESSION).unwrap();
+        assert_eq!(audit[0].event_type, EventType::KeyRotated);
+        assert_eq!(resolver.verify_chain(ADMIN_AUDIT_SESSION).unwrap().signatures_valid, Some(true));
+    }
+
+    /// Append a copy of the session's last event with an edited payload
+    fn tamper_trace(resolver: &mut Resolver, session_id: &str) {
+        let mut forged = resolver.get_trace(session_id).unwrap().pop().unwrap();
+        forged.payload = json!({"forged": true});
+        forged.sequence += 1;
+        let line = serde_json::to_string(&forged).unwrap();
+        resolver.trace_collector.import_jsonl(session_id, &line).unwrap();
     }
 
     #[test]
-    fn test_execute_action() {
+    fn test_sweep_quarantines_broken_chain() {
+        use crate::trace::ADMIN_AUDIT_SESSION;
+
         let mut resolver = Resolver::new();
         resolver.load_atlas(create_test_atlas()).unwrap();
+        let broken = resolver.create_session("test-agent", "Test goal").unwrap();
+        let intact = resolver.create_session("test-agent", "Test goal").unwrap();
+        tamper_trace(&mut resolver, &broken);
 
-        let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        assert_eq!(resolver.sweep_chains(10).unwrap(), vec![broken.clone()]);
+        assert!(resolver.is_quarantined(&broken));
+        assert!(!resolver.is_quarantined(&intact));
+        // Known violations aren't reported again
+        assert!(resolver.sweep_chains(10).unwrap().is_empty());
 
-        // Execute allowed action
-        let result = resolver.execute(
-            &session_id,
-            "resolution-1",
-            "test.get",
-            json!({}),
+        let request = CARPRequest::new(broken.clone(), "test-agent".to_string(), "Test goal".to_string());
+        let resolution = resolver.resolve(&request).unwrap();
+        assert_eq!(resolution.decision, Decision::Quarantined);
+        assert!(resolution.allowed_actions.is_empty());
+        let err = resolver.execute(&broken, "resolution-1", "test.get", json!({})).unwrap_err();
+        assert_eq!(err.error_code(), "SESSION_QUARANTINED");
+
+        let audit = resolver.get_trace(ADMIN_AUDIT_SESSION).unwrap();
+        assert_eq!(audit[0].event_type, EventType::IntegrityViolation);
+        assert_eq!(audit[0].payload["session_id"], broken.as_str());
+        assert_eq!(audit[0].payload["detected_by"], "sweep");
+        assert_eq!(
+            resolver.metrics_snapshot().counter(metrics::INTEGRITY_VIOLATIONS_TOTAL, &[("detected_by", "sweep")]),
+            1
         );
-        assert!(result.is_ok());
 
-        // Execute denied action should fail
-        let result = resolver.execute(
-            &session_id,
-            "resolution-1",
-            "test.delete",
-            json!({}),
-        );
-        assert!(result.is_err());
+        assert!(resolver.release_quarantine(&broken).is_some());
+        let request = CARPRequest::new(broken.clone(), "test-agent".to_string(), "Test goal".to_string());
+        assert_eq!(resolver.resolve(&request).unwrap().decision, Decision::Partial);
     }
 
     #[test]
-    fn test_trace_chain() {
-        let mut resolver = Resolver::new();
-        resolver.load_atlas(create_test_atlas()).unwrap();
-
+    fn test_verify_on_flush() {
+        let mut resolver = Resolver::new()
+            .with_deferred_tracing(DeferredConfig::default())
+            .with_verify_on_flush();
         let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
+        resolver.flush_traces().unwrap();
+        assert!(!resolver.is_quarantined(&session_id));
 
-        let request = CARPRequest::new(
-            session_id.clone(),
-            "test-agent".to_string(),
-            "Test goal".to_string(),
-        );
+        tamper_trace(&mut resolver, &session_id);
+        let request = CARPRequest::new(session_id.clone(), "test-agent".to_string(), "Test goal".to_string());
         resolver.resolve(&request).unwrap();
+        resolver.flush_traces().unwrap();
 
-        // Verify chain
-        let verification = resolver.verify_chain(&session_id).unwrap();
-        assert!(verification.is_valid);
+        assert!(resolver.is_quarantined(&session_id));
+        assert_eq!(resolver.integrity_violations()[0].detected_by, "flush");
     }
 
     #[test]
-    fn test_end_session() {
-        let mut resolver = Resolver::new();
+    #[cfg(not(feature = "minimal"))]
+    fn test_webhook_notifications() {
+        use crate::notify::{SignedPostFn, WebhookConfig, WebhookSubscription};
+        use std::sync::Mutex;
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let post: SignedPostFn = {
+            let sent = sent.clone();
+            Arc::new(move |_: &str, headers: &[(String, String)], _: &str| {
+                let class = headers.iter().find(|(name, _)| name == "X-CRA-Event").unwrap().1.clone();
+                sent.lock().unwrap().push(class);
+                Ok(())
+            })
+        };
+        let notifier = Arc::new(WebhookNotifier::with_config(post, WebhookConfig::default()));
+        notifier.subscribe(WebhookSubscription::new(
+            "ops",
+            "https://ops.example/hook",
+            "secret",
+            vec![NotificationClass::PolicyDenied, NotificationClass::SessionEnded],
+        ));
+
+        // The callback survives switching to deferred tracing
+        let mut resolver = Resolver::new()
+            .with_webhook_notifier(notifier.clone())
+            .with_deferred_tracing(DeferredConfig::default());
+        resolver.load_atlas(create_test_atlas()).unwrap();
         let session_id = resolver.create_session("test-agent", "Test goal").unwrap();
-
-        assert!(resolver.get_session(&session_id).unwrap().is_active);
-
+        assert!(resolver.execute(&session_id, "resolution-1", "test.delete", json!({})).is_err());
         resolver.end_session(&session_id).unwrap();
+        assert!(sent.lock().unwrap().is_empty());
 
-        assert!(!resolver.get_session(&session_id).unwrap().is_active);
-
-        // Trying to end again should fail
-        let result = resolver.end_session(&session_id);
-        assert!(result.is_err());
+        resolver.flush_traces().unwrap();
+        assert!(notifier.flush(std::time::Duration::from_secs(5)));
+        assert_eq!(*sent.lock().unwrap(), vec!["policy.denied", "session.ended"]);
     }
 
     #[test]
@@ -1188,4 +5893,114 @@ mod tests {
             .collect();
         assert!(!context_events.is_empty(), "Should have context.injected trace events");
     }
+
+    #[test]
+    fn test_bootstrap() {
+        use crate::atlas::AtlasContextBlock;
+        use crate::carp::{BootstrapRequest, BOOTSTRAP_PROTOCOL_VERSION};
+
+        let mut atlas = create_test_atlas();
+        atlas.context_blocks = vec![AtlasContextBlock {
+            context_id: "ground-rules".to_string(),
+            name: "Ground Rules".to_string(),
+            priority: 10,
+            content: "Never touch production".to_string(),
+            content_type: "text/markdown".to_string(),
+            inject_mode: InjectMode::Always,
+            also_inject: vec![],
+            inject_when: vec![],
+            keywords: vec![],
+            risk_tiers: vec![],
+        }];
+        let mut resolver = Resolver::new().with_context_references(true);
+        resolver.load_atlas(atlas).unwrap();
+
+        let request = BootstrapRequest::new("agent-1", "Manage test resources")
+            .with_capabilities(vec!["shell".to_string()]);
+        let response = resolver.bootstrap(&request).unwrap();
+
+        let trace = resolver.get_trace(&response.session_id).unwrap();
+        assert_eq!(response.protocol_version, BOOTSTRAP_PROTOCOL_VERSION);
+        assert_eq!(response.genesis_hash, trace[0].event_hash);
+        assert_eq!(response.current_hash, trace.last().unwrap().event_hash);
+        assert_eq!(response.policies[0].policy_id, "deny-delete");
+        assert!(response.allowed_actions.contains(&"test.get".to_string()));
+
+        // Context arrives inlined even when the resolver references it
+        let context = &response.contexts[0];
+        assert_eq!(context.content, "Never touch production");
+        assert!(context.is_intact());
+
+        let resolution_id = response.resolution_id.clone().unwrap();
+        resolver.execute(&response.session_id, &resolution_id, "test.get", json!({})).unwrap();
+
+        let mut unsupported = request.clone();
+        unsupported.protocol_version = "2.0".to_string();
+        assert!(matches!(
+            resolver.bootstrap(&unsupported),
+            Err(CRAError::InvalidBootstrap { .. })
+        ));
+    }
+
+    #[test]
+    fn test_context_by_reference() {
+        use crate::atlas::{AtlasContextBlock, InjectMode};
+        use crate::context::ContentStore;
+
+        let mut atlas = create_test_atlas();
+        atlas.context_blocks = vec![AtlasContextBlock {
+            context_id: "style-guide".to_string(),
+            name: "Style Guide".to_string(),
+            priority: 10,
+            content: "Prefer small, reviewable changes".to_string(),
+            content_type: "text/markdown".to_string(),
+            inject_mode: InjectMode::OnMatch,
+            also_inject: vec![],
+            inject_when: vec![],
+            keywords: vec!["code".to_string()],
+            risk_tiers: vec![],
+        }];
+
+        let store = Arc::new(ContentStore::new());
+        let mut resolver = Resolver::new()
+            .with_content_store(store.clone())
+            .with_context_references(true);
+        resolver.load_atlas(atlas).unwrap();
+
+        let mut hashes = Vec::new();
+        for agent in ["agent-1", "agent-2"] {
+            let session_id = resolver.create_session(agent, "Write code").unwrap();
+            let request = CARPRequest::new(session_id, agent.to_string(), "Write code".to_string());
+            let mut resolution = resolver.resolve(&request).unwrap();
+
+            let block = resolution.context_blocks.iter().find(|b| b.block_id == "style-guide").unwrap();
+            assert!(block.is_reference());
+            hashes.push(block.content_hash.clone().unwrap());
+
+            resolver.inline_context(&mut resolution).unwrap();
+            let block = resolution.context_blocks.iter().find(|b| b.block_id == "style-guide").unwrap();
+            assert_eq!(block.content, "Prefer small, reviewable changes");
+        }
+
+        // Both sessions share one stored copy
+        assert_eq!(hashes[0], hashes[1]);
+        assert_eq!(store.stats().blocks, 1);
+        assert_eq!(store.stats().references, 2);
+
+        let session_id = resolver.list_sessions()[0].session_id.clone();
+        let referenced = resolver.export_trace(&session_id).unwrap();
+        let inlined = resolver.export_trace_with_context(&session_id, true).unwrap();
+        let body = String::from_utf8(inlined.body).unwrap();
+        assert!(body.contains("Prefer small, reviewable changes"));
+        assert!(!String::from_utf8(referenced.body).unwrap().contains("Prefer small"));
+
+        let verification = cra_trace_verify::verify_jsonl(&body, crate::trace::GENESIS_HASH);
+        assert!(verification.is_valid);
+        assert_eq!(verification.content_count, 1);
+
+        assert!(matches!(
+            resolver.context_content("sha256:missing"),
+            Err(CRAError::ContentNotFound { .. })
+        ));
+    }
 }
@@ -0,0 +1,193 @@
+//! Resolver Snapshots
+//!
+//! Serializes a resolver's full state so a process can restart (or move to
+//! another host) without losing sessions: loaded atlases, session and
+//! checkpoint state, every TRACE chain with its head, and rate limit
+//! counters.
+//!
+//! ## Encoding
+//!
+//! ```text
+//! ┌──────────┬─────────┬──────────────────┬──────────────────────┐
+//! │ "CRASNAP"│ version │ SHA-256 of body  │ body (JSON)          │
+//! │ 7 bytes  │ 1 byte  │ 32 bytes         │ ResolverState        │
+//! └──────────┴─────────┴──────────────────┴──────────────────────┘
+//! ```
+//!
+//! The digest catches truncation and corruption; it is not a signature.
+//! Runtime configuration (clock, ID generator, event signer, custom
+//! triggers and validators) is code, not state, and is not included:
+//! configure the restoring resolver the same way as the original.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::atlas::AtlasManifest;
+use crate::error::{CRAError, Result};
+use crate::trace::{TraceKey, TRACEEvent};
+
+use super::policy::RateLimitCounter;
+use super::resolver::Session;
+use super::{CapabilityState, ChainHead, GuidanceManager, TriggeredCheckpoint};
+
+/// Magic bytes at the start of every resolver snapshot
+pub const RESOLVER_SNAPSHOT_MAGIC: &[u8; 7] = b"CRASNAP";
+
+/// Resolver snapshot format version
+pub const RESOLVER_SNAPSHOT_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = RESOLVER_SNAPSHOT_MAGIC.len() + 1 + 32;
+
+/// Checkpoint and capability state of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshotState {
+    /// The session itself
+    pub session: Session,
+    /// Capability gates and grants
+    pub capability_state: CapabilityState,
+    /// Active checkpoint guidance
+    pub guidance: GuidanceManager,
+    /// Checkpoints awaiting a response
+    pub pending_checkpoints: Vec<TriggeredCheckpoint>,
+    /// Actions since the last checkpoint (for count-interval triggers)
+    pub actions_since_checkpoint: u64,
+    /// Milliseconds since the last checkpoint (for time-interval triggers)
+    pub since_checkpoint_ms: u64,
+    /// Keywords that already fired keyword checkpoints
+    pub matched_keywords: Vec<String>,
+}
+
+/// One TRACE chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceChainSnapshot {
+    /// Session the chain belongs to
+    pub session_id: String,
+    /// Last event of the chain
+    pub chain_head: ChainHead,
+    /// The chain, genesis first
+    pub events: Vec<TRACEEvent>,
+}
+
+/// Everything [`Resolver::snapshot`](super::Resolver::snapshot) captures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolverState {
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Default TTL for resolutions in seconds
+    pub default_ttl: u64,
+    /// Loaded atlases, by ID
+    pub atlases: Vec<AtlasManifest>,
+    /// Sessions, active and ended, by ID
+    pub sessions: Vec<SessionSnapshotState>,
+    /// TRACE chains, including sessions without resolver state (e.g. admin audit)
+    pub traces: Vec<TraceChainSnapshot>,
+    /// Rate limit counters
+    pub rate_limits: Vec<RateLimitCounter>,
+    /// Signing keys retired by key rotation
+    pub retired_trace_keys: Vec<TraceKey>,
+}
+
+impl ResolverState {
+    /// Encode the state as snapshot bytes
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let body = serde_json::to_vec(self)?;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.extend_from_slice(RESOLVER_SNAPSHOT_MAGIC);
+        bytes.push(RESOLVER_SNAPSHOT_VERSION);
+        bytes.extend_from_slice(&Sha256::digest(&body));
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Decode snapshot bytes, checking the format version and digest
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let snapshot_error = |reason: String| CRAError::ResolverSnapshotError { reason };
+
+        if bytes.len() < HEADER_LEN || !bytes.starts_with(RESOLVER_SNAPSHOT_MAGIC) {
+            return Err(snapshot_error("not a resolver snapshot".to_string()));
+        }
+        let version = bytes[RESOLVER_SNAPSHOT_MAGIC.len()];
+        if version != RESOLVER_SNAPSHOT_VERSION {
+            return Err(snapshot_error(format!("unsupported snapshot version {}", version)));
+        }
+        let (digest, body) = bytes[RESOLVER_SNAPSHOT_MAGIC.len() + 1..].split_at(32);
+        if Sha256::digest(body).as_slice() != digest {
+            return Err(snapshot_error("digest mismatch, snapshot is corrupt".to_string()));
+        }
+        serde_json::from_slice(body).map_err(|e| snapshot_error(format!("invalid body: {}", e)))
+    }
+
+    /// Check that every trace chain is intact and matches its head
+    pub fn verify_traces(&self) -> Result<()> {
+        for trace in &self.traces {
+            let last = trace.events.last().ok_or_else(|| CRAError::ResolverSnapshotError {
+                reason: format!("trace for '{}' has no events", trace.session_id),
+            })?;
+            if let Some(event) = trace.events.iter().find(|e| e.session_id != trace.session_id) {
+                return Err(CRAError::ResolverSnapshotError {
+                    reason: format!("event {} belongs to session '{}'", event.event_id, event.session_id),
+                });
+            }
+            if last.sequence != trace.chain_head.sequence || last.event_hash != trace.chain_head.event_hash {
+                return Err(CRAError::ResolverSnapshotError {
+                    reason: format!("chain head of '{}' does not match its last event", trace.session_id),
+                });
+            }
+            let verification = crate::trace::ChainVerifier::verify(&trace.events);
+            if !verification.is_valid {
+                return Err(CRAError::TraceChainIntegrityError {
+                    reason: verification.error_message.unwrap_or_default(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_state() -> ResolverState {
+        ResolverState {
+            created_at: DateTime::UNIX_EPOCH,
+            default_ttl: 300,
+            atlases: Vec::new(),
+            sessions: Vec::new(),
+            traces: Vec::new(),
+            rate_limits: Vec::new(),
+            retired_trace_keys: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let bytes = empty_state().encode().unwrap();
+        assert!(bytes.starts_with(RESOLVER_SNAPSHOT_MAGIC));
+        assert_eq!(bytes[7], RESOLVER_SNAPSHOT_VERSION);
+
+        let state = ResolverState::decode(&bytes).unwrap();
+        assert_eq!(state.default_ttl, 300);
+    }
+
+    #[test]
+    fn test_decode_rejects_corruption() {
+        let mut bytes = empty_state().encode().unwrap();
+        let last = bytes.len() - 2;
+        bytes[last] ^= 0x01;
+        let err = ResolverState::decode(&bytes).unwrap_err();
+        assert!(err.to_string().contains("digest mismatch"));
+
+        assert!(ResolverState::decode(b"CRASNAP").is_err());
+        assert!(ResolverState::decode(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_version() {
+        let mut bytes = empty_state().encode().unwrap();
+        bytes[7] = RESOLVER_SNAPSHOT_VERSION + 1;
+        let err = ResolverState::decode(&bytes).unwrap_err();
+        assert!(err.to_string().contains("unsupported snapshot version"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Session Variable Templating
+//!
+//! Atlases reference session variables as `${name}` in action parameter
+//! defaults and executors, e.g. `"default": "${tenant_id}"` or
+//! `"executor": "webhook:https://${env}.example.com/hooks/tickets"`. The
+//! resolver substitutes them when it builds a resolution, so agents see the
+//! concrete values.
+//!
+//! `$$` is a literal `$`. A `${` without a closing `}` is left as is.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Session variables, by name
+pub type SessionVariables = BTreeMap<String, String>;
+
+/// Whether `name` can be used as a session variable name
+///
+/// Names are non-empty and made of ASCII letters, digits, `_`, `-` and `.`.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Substitute `${name}` references in `template`
+///
+/// Returns the name of the first unknown variable as the error.
+pub fn render(template: &str, variables: &SessionVariables) -> Result<String, String> {
+    if !template.contains('$') {
+        return Ok(template.to_string());
+    }
+
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(after_dollar) = after.strip_prefix('$') {
+            output.push('$');
+            rest = after_dollar;
+        } else if let Some((name, tail)) = after.strip_prefix('{').and_then(|r| r.split_once('}')) {
+            let value = variables.get(name.trim()).ok_or_else(|| name.trim().to_string())?;
+            output.push_str(value);
+            rest = tail;
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Substitute variables in every `default` of a JSON Schema, in place
+pub fn render_schema_defaults(schema: &mut Value, variables: &SessionVariables) -> Result<(), String> {
+    match schema {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if key == "default" {
+                    render_value(value, variables)?;
+                } else {
+                    render_schema_defaults(value, variables)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                render_schema_defaults(item, variables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn render_value(value: &mut Value, variables: &SessionVariables) -> Result<(), String> {
+    match value {
+        Value::String(s) => *s = render(s, variables)?,
+        Value::Array(items) => {
+            for item in items {
+                render_value(item, variables)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                render_value(item, variables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> SessionVariables {
+        [("tenant_id", "acme"), ("env", "staging")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(render("https://${env}.example.com/${tenant_id}", &vars()).unwrap(), "https://staging.example.com/acme");
+        assert_eq!(render("${ tenant_id }", &vars()).unwrap(), "acme");
+        assert_eq!(render("costs $$5, not ${", &vars()).unwrap(), "costs $5, not ${");
+        assert_eq!(render("$env", &vars()).unwrap(), "$env");
+        assert_eq!(render("${region}", &vars()).unwrap_err(), "region");
+
+        assert!(is_valid_name("tenant_id"));
+        assert!(is_valid_name("deploy.env"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("a}b"));
+    }
+
+    #[test]
+    fn test_render_schema_defaults() {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "tenant": { "type": "string", "default": "${tenant_id}" },
+                "tags": { "type": "array", "default": ["${env}", "fixed"] },
+                "filter": {
+                    "type": "object",
+                    "properties": { "env": { "type": "string", "default": "${env}" } }
+                },
+                "pattern": { "type": "string", "pattern": "${not_a_default}" }
+            }
+        });
+        render_schema_defaults(&mut schema, &vars()).unwrap();
+
+        assert_eq!(schema["properties"]["tenant"]["default"], "acme");
+        assert_eq!(schema["properties"]["tags"]["default"], json!(["staging", "fixed"]));
+        assert_eq!(schema["properties"]["filter"]["properties"]["env"]["default"], "staging");
+        assert_eq!(schema["properties"]["pattern"]["pattern"], "${not_a_default}");
+
+        let mut schema = json!({ "properties": { "x": { "default": "${missing}" } } });
+        assert_eq!(render_schema_defaults(&mut schema, &vars()).unwrap_err(), "missing");
+    }
+}
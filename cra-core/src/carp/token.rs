@@ -0,0 +1,186 @@
+//! Capability attenuation tokens
+//!
+//! A [`CapabilityToken`] narrows what an execution may do to a named set of
+//! actions (IDs or namespace patterns) in one session, for a short time. The
+//! resolver mints it as `{claims}.{mac}`: the claims as hex-encoded JSON and
+//! an HMAC-SHA256 over them under a secret only the resolver holds. A token
+//! never grants anything by itself; executions presenting one still pass
+//! every policy and capability check, and are denied when the action is
+//! outside the token.
+//!
+//! Tokens are only honored by the resolver that minted them, until they
+//! expire, are revoked or their session ends.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::atlas::namespace::pattern_matches;
+
+/// Policy ID reported for executions refused because of their token
+pub const CAPABILITY_TOKEN_POLICY_ID: &str = "capability_token";
+
+/// Claims carried by a capability token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityToken {
+    /// Unique token ID, used to revoke it
+    pub token_id: String,
+    /// The only session the token is valid in
+    pub session_id: String,
+    /// Action IDs or patterns the token authorizes
+    pub actions: Vec<String>,
+    /// When the token was minted
+    pub issued_at: DateTime<Utc>,
+    /// When the token stops being accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+impl CapabilityToken {
+    /// Whether the token covers an action
+    pub fn covers(&self, action_id: &str) -> bool {
+        self.actions.iter().any(|p| pattern_matches(p, action_id))
+    }
+}
+
+/// Mints and checks capability tokens for a resolver
+pub(crate) struct TokenIssuer {
+    secret: Vec<u8>,
+    /// Tokens minted and not yet dropped, by token ID
+    issued: HashMap<String, IssuedToken>,
+}
+
+#[derive(Debug, Clone)]
+struct IssuedToken {
+    session_id: String,
+    revoked: bool,
+}
+
+impl std::fmt::Debug for TokenIssuer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenIssuer")
+            .field("issued", &self.issued.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TokenIssuer {
+    fn default() -> Self {
+        let mut secret = uuid::Uuid::new_v4().as_bytes().to_vec();
+        secret.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+        Self {
+            secret,
+            issued: HashMap::new(),
+        }
+    }
+}
+
+impl TokenIssuer {
+    /// Record a token and return its signed encoding
+    pub fn mint(&mut self, token: &CapabilityToken) -> String {
+        self.issued.insert(
+            token.token_id.clone(),
+            IssuedToken {
+                session_id: token.session_id.clone(),
+                revoked: false,
+            },
+        );
+        let claims = hex::encode(serde_json::to_vec(token).unwrap_or_default());
+        let mac = hex::encode(self.mac(&claims).finalize().into_bytes());
+        format!("{}.{}", claims, mac)
+    }
+
+    /// Check a presented token for a session and action
+    ///
+    /// Returns the claims, or why the token doesn't authorize the action.
+    pub fn check(
+        &self,
+        encoded: &str,
+        session_id: &str,
+        action_id: &str,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<CapabilityToken, String> {
+        let token = self.decode(encoded).ok_or("Capability token is malformed or not signed by this resolver")?;
+        let issued = self
+            .issued
+            .get(&token.token_id)
+            .ok_or_else(|| format!("Capability token '{}' is not active", token.token_id))?;
+        if issued.revoked {
+            return Err(format!("Capability token '{}' was revoked", token.token_id));
+        }
+        if token.session_id != session_id || issued.session_id != session_id {
+            return Err(format!("Capability token '{}' belongs to another session", token.token_id));
+        }
+        if now > token.expires_at {
+            return Err(format!("Capability token '{}' expired at {}", token.token_id, token.expires_at.to_rfc3339()));
+        }
+        if !token.covers(action_id) {
+            return Err(format!("Capability token '{}' does not cover '{}'", token.token_id, action_id));
+        }
+        Ok(token)
+    }
+
+    /// Mark a token revoked, returning its session if it was active
+    pub fn revoke(&mut self, token_id: &str) -> Option<String> {
+        let issued = self.issued.get_mut(token_id).filter(|t| !t.revoked)?;
+        issued.revoked = true;
+        Some(issued.session_id.clone())
+    }
+
+    /// Forget the tokens of an ended session
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.issued.retain(|_, t| t.session_id != session_id);
+    }
+
+    fn decode(&self, encoded: &str) -> Option<CapabilityToken> {
+        let (claims, mac) = encoded.split_once('.')?;
+        self.mac(claims).verify_slice(&hex::decode(mac).ok()?).ok()?;
+        serde_json::from_slice(&hex::decode(claims).ok()?).ok()
+    }
+
+    fn mac(&self, claims: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(claims.as_bytes());
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_at: DateTime<Utc>) -> CapabilityToken {
+        CapabilityToken {
+            token_id: "tok-1".to_string(),
+            session_id: "s1".to_string(),
+            actions: vec!["ticket.*".to_string()],
+            issued_at: DateTime::UNIX_EPOCH,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_tampered_and_foreign_tokens_rejected() {
+        let now = DateTime::UNIX_EPOCH;
+        let mut issuer = TokenIssuer::default();
+        let encoded = issuer.mint(&token(now + chrono::Duration::seconds(60)));
+        assert!(issuer.check(&encoded, "s1", "ticket.get", now).is_ok());
+        assert!(issuer.check(&encoded, "s1", "user.delete", now).is_err());
+        assert!(issuer.check(&encoded, "s2", "ticket.get", now).is_err());
+
+        // Widening the claims breaks the MAC
+        let mut widened = token(now + chrono::Duration::seconds(60));
+        widened.actions = vec!["**".to_string()];
+        let forged = format!(
+            "{}.{}",
+            hex::encode(serde_json::to_vec(&widened).unwrap()),
+            encoded.split_once('.').unwrap().1
+        );
+        assert!(issuer.check(&forged, "s1", "user.delete", now).is_err());
+
+        // Another resolver's secret doesn't verify
+        assert!(TokenIssuer::default().check(&encoded, "s1", "ticket.get", now).is_err());
+    }
+}
@@ -0,0 +1,470 @@
+//! Fault injection for chaos testing
+//!
+//! CRA is meant to fail closed: a lost storage write, a stalled resolver or a
+//! timer that never fires must surface as an error, not as silently missing
+//! TRACE. This module lets tests provoke those faults on demand. Requires the
+//! `chaos` feature; nothing here is compiled into production builds.
+//!
+//! A [`ChaosInjector`] holds the fault plan and is shared (`Arc`) between the
+//! components it drives, so faults can be armed and cleared while they run:
+//!
+//! | Point | Hooked by | Failure | Latency |
+//! |-------|-----------|---------|---------|
+//! | [`FaultPoint::StorageWrite`] | [`ChaosStorage`], `AsyncRuntime` storage | Write returns an error | Write is delayed |
+//! | [`FaultPoint::TraceBuffer`] | `TraceCollector::with_chaos` (deferred mode) | Buffer reports overflow | - |
+//! | [`FaultPoint::Timer`] | [`ChaosTimerBackend`] | Timer is never scheduled | Timer fires late |
+//! | [`FaultPoint::Resolve`] | `AsyncRuntime::with_chaos` | Resolution returns an error | Resolution is delayed |
+//!
+//! Injected failures are [`CRAError::InjectedFault`] errors, and when they
+//! hit a session the collector appends a `chaos.fault_injected` event to its
+//! TRACE, so assertions can tell real errors from injected ones:
+//!
+//! ```rust,ignore
+//! use cra_core::chaos::{ChaosInjector, ChaosStorage, FaultPoint};
+//!
+//! let chaos = Arc::new(ChaosInjector::new());
+//! let storage = Arc::new(ChaosStorage::new(InMemoryStorage::new(), chaos.clone()));
+//! let mut resolver = Resolver::new().with_storage(storage);
+//!
+//! chaos.fail_next(FaultPoint::StorageWrite, 1);
+//! let err = resolver.create_session("agent", "goal").unwrap_err();
+//! assert_eq!(err.error_code(), "INJECTED_FAULT");
+//! assert_eq!(chaos.injected_failures(FaultPoint::StorageWrite), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CRAError, Result};
+use crate::storage::{EventPage, EventQuery, StorageBackend};
+use crate::timing::{TimerBackend, TimerEvent};
+use crate::trace::TRACEEvent;
+
+/// Where a fault can be injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultPoint {
+    /// Writing an event to storage
+    StorageWrite,
+    /// Pushing an event onto the deferred trace buffer
+    TraceBuffer,
+    /// Scheduling a timer
+    Timer,
+    /// Resolving a CARP request in the async runtime
+    Resolve,
+}
+
+impl FaultPoint {
+    /// Snake-case name of the point
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultPoint::StorageWrite => "storage_write",
+            FaultPoint::TraceBuffer => "trace_buffer",
+            FaultPoint::Timer => "timer",
+            FaultPoint::Resolve => "resolve",
+        }
+    }
+}
+
+impl fmt::Display for FaultPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What an injected fault did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// The operation failed
+    Failure,
+    /// The operation was delayed
+    Latency,
+}
+
+/// A fault that was injected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InjectedFault {
+    /// Where it was injected
+    pub point: FaultPoint,
+    /// What it did
+    pub kind: FaultKind,
+    /// Session the operation was for, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Delay added, in milliseconds (latency faults)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+}
+
+/// Faults to apply to one operation
+#[derive(Debug, Default)]
+pub struct Injection {
+    /// Extra delay before the operation
+    pub delay: Duration,
+    /// Error the operation must fail with, if any
+    pub failure: Option<CRAError>,
+}
+
+/// Fault plan for one point
+#[derive(Debug, Clone, Default)]
+struct FaultRule {
+    /// Failures left to inject; `None` fails until cleared
+    failures: Option<usize>,
+    failing: bool,
+    latency: Duration,
+    reason: String,
+}
+
+#[derive(Debug, Default)]
+struct ChaosState {
+    rules: HashMap<FaultPoint, FaultRule>,
+    injected: Vec<InjectedFault>,
+}
+
+/// Runtime-configurable fault plan shared by the chaos hooks
+#[derive(Debug, Default)]
+pub struct ChaosInjector {
+    state: Mutex<ChaosState>,
+}
+
+impl ChaosInjector {
+    /// Create an injector with no faults armed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the next `times` operations at `point`
+    pub fn fail_next(&self, point: FaultPoint, times: usize) {
+        let mut state = self.state();
+        let rule = state.rules.entry(point).or_default();
+        rule.failing = true;
+        rule.failures = Some(times);
+        rule.reason = format!("next {} operation(s) fail", times);
+    }
+
+    /// Fail every operation at `point` until cleared
+    pub fn fail_always(&self, point: FaultPoint) {
+        let mut state = self.state();
+        let rule = state.rules.entry(point).or_default();
+        rule.failing = true;
+        rule.failures = None;
+        rule.reason = "failing until cleared".to_string();
+    }
+
+    /// Delay every operation at `point` by `latency` until cleared
+    pub fn add_latency(&self, point: FaultPoint, latency: Duration) {
+        self.state().rules.entry(point).or_default().latency = latency;
+    }
+
+    /// Disarm all faults at `point`
+    pub fn clear(&self, point: FaultPoint) {
+        self.state().rules.remove(&point);
+    }
+
+    /// Disarm every fault, keeping the log of injected ones
+    pub fn clear_all(&self) {
+        self.state().rules.clear();
+    }
+
+    /// Whether any fault is armed at `point`
+    pub fn is_armed(&self, point: FaultPoint) -> bool {
+        self.state().rules.contains_key(&point)
+    }
+
+    /// Every fault injected so far, oldest first
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        self.state().injected.clone()
+    }
+
+    /// Number of failures injected at `point`
+    pub fn injected_failures(&self, point: FaultPoint) -> usize {
+        self.state()
+            .injected
+            .iter()
+            .filter(|f| f.point == point && f.kind == FaultKind::Failure)
+            .count()
+    }
+
+    /// Work out the faults for one operation at `point`, logging them
+    ///
+    /// Hooks that can't simply sleep (timers, async tasks) apply the delay
+    /// themselves; others use [`ChaosInjector::check`].
+    pub fn inject(&self, point: FaultPoint, session_id: Option<&str>) -> Injection {
+        let mut state = self.state();
+        let Some(rule) = state.rules.get_mut(&point) else {
+            return Injection::default();
+        };
+
+        let delay = rule.latency;
+        let mut failure = None;
+        if rule.failing {
+            match rule.failures.as_mut() {
+                Some(0) => {}
+                Some(left) => {
+                    *left -= 1;
+                    failure = Some(rule.reason.clone());
+                }
+                None => failure = Some(rule.reason.clone()),
+            }
+        }
+
+        let mut log = |kind, delay_ms| {
+            state.injected.push(InjectedFault {
+                point,
+                kind,
+                session_id: session_id.map(String::from),
+                delay_ms,
+            })
+        };
+        if !delay.is_zero() {
+            log(FaultKind::Latency, Some(delay.as_millis() as u64));
+        }
+        if failure.is_some() {
+            log(FaultKind::Failure, None);
+        }
+
+        Injection {
+            delay,
+            failure: failure.map(|reason| CRAError::InjectedFault {
+                point: point.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    /// Apply the faults for one operation at `point`
+    ///
+    /// Sleeps for any injected latency, then returns the injected failure.
+    pub fn check(&self, point: FaultPoint, session_id: Option<&str>) -> Result<()> {
+        let injection = self.inject(point, session_id);
+        if !injection.delay.is_zero() {
+            std::thread::sleep(injection.delay);
+        }
+        match injection.failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ChaosState> {
+        // A test panicking mid-injection must not disarm the others
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Storage backend that injects faults into another backend's writes
+///
+/// Reads pass through untouched, so a test can inspect what made it to
+/// storage before the fault.
+pub struct ChaosStorage<B: StorageBackend> {
+    inner: B,
+    chaos: Arc<ChaosInjector>,
+}
+
+impl<B: StorageBackend> ChaosStorage<B> {
+    /// Wrap `inner`, injecting the faults armed on `chaos`
+    pub fn new(inner: B, chaos: Arc<ChaosInjector>) -> Self {
+        Self { inner, chaos }
+    }
+
+    /// The wrapped backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B: StorageBackend> StorageBackend for ChaosStorage<B> {
+    fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+        self.chaos.check(FaultPoint::StorageWrite, Some(&event.session_id))?;
+        self.inner.store_event(event)
+    }
+
+    fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        self.inner.get_events(session_id)
+    }
+
+    fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+        self.inner.get_events_by_type(session_id, event_type)
+    }
+
+    fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+        self.inner.get_last_events(session_id, n)
+    }
+
+    fn get_event_count(&self, session_id: &str) -> Result<usize> {
+        self.inner.get_event_count(session_id)
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.inner.delete_session(session_id)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        self.inner.list_sessions()
+    }
+
+    fn search_events(&self, query: &EventQuery) -> Result<EventPage> {
+        self.inner.search_events(query)
+    }
+
+    fn health_check(&self) -> Result<()> {
+        self.inner.health_check()
+    }
+
+    fn name(&self) -> &'static str {
+        "chaos"
+    }
+}
+
+/// Timer backend that injects misfires into another backend
+///
+/// An injected failure drops the timer: scheduling reports success but it
+/// never fires. Injected latency makes it fire late.
+pub struct ChaosTimerBackend<B: TimerBackend> {
+    inner: B,
+    chaos: Arc<ChaosInjector>,
+}
+
+impl<B: TimerBackend> ChaosTimerBackend<B> {
+    /// Wrap `inner`, injecting the faults armed on `chaos`
+    pub fn new(inner: B, chaos: Arc<ChaosInjector>) -> Self {
+        Self { inner, chaos }
+    }
+
+    /// The wrapped backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B: TimerBackend> TimerBackend for ChaosTimerBackend<B> {
+    fn schedule_once(&self, id: &str, delay: Duration, event: TimerEvent) -> Result<()> {
+        let injection = self.chaos.inject(FaultPoint::Timer, None);
+        if injection.failure.is_some() {
+            return Ok(());
+        }
+        self.inner.schedule_once(id, delay + injection.delay, event)
+    }
+
+    fn schedule_repeating(&self, id: &str, interval: Duration, event: TimerEvent) -> Result<()> {
+        let injection = self.chaos.inject(FaultPoint::Timer, None);
+        if injection.failure.is_some() {
+            return Ok(());
+        }
+        self.inner.schedule_repeating(id, interval + injection.delay, event)
+    }
+
+    fn cancel(&self, id: &str) -> Result<bool> {
+        self.inner.cancel(id)
+    }
+
+    fn exists(&self, id: &str) -> bool {
+        self.inner.exists(id)
+    }
+
+    fn time_remaining(&self, id: &str) -> Option<Duration> {
+        self.inner.time_remaining(id)
+    }
+
+    fn name(&self) -> &'static str {
+        "chaos"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemoryStorage;
+    use crate::trace::{DeferredConfig, EventType, TraceCollector};
+    use serde_json::json;
+
+    #[test]
+    fn test_fail_next_and_clear() {
+        let chaos = ChaosInjector::new();
+        chaos.fail_next(FaultPoint::StorageWrite, 2);
+        assert!(chaos.check(FaultPoint::StorageWrite, Some("s1")).is_err());
+        assert!(chaos.check(FaultPoint::StorageWrite, None).is_err());
+        assert!(chaos.check(FaultPoint::StorageWrite, None).is_ok());
+        assert!(chaos.check(FaultPoint::Resolve, None).is_ok());
+
+        chaos.fail_always(FaultPoint::Resolve);
+        chaos.add_latency(FaultPoint::Resolve, Duration::from_millis(1));
+        let err = chaos.check(FaultPoint::Resolve, None).unwrap_err();
+        assert_eq!(err.error_code(), "INJECTED_FAULT");
+        chaos.clear(FaultPoint::Resolve);
+        assert!(chaos.check(FaultPoint::Resolve, None).is_ok());
+
+        assert_eq!(chaos.injected_failures(FaultPoint::StorageWrite), 2);
+        let injected = chaos.injected();
+        assert_eq!(injected[0].session_id.as_deref(), Some("s1"));
+        assert_eq!(injected[2].kind, FaultKind::Latency);
+    }
+
+    #[test]
+    fn test_storage_fault_marked_in_trace() {
+        let chaos = Arc::new(ChaosInjector::new());
+        let storage = Arc::new(ChaosStorage::new(InMemoryStorage::new(), chaos.clone()));
+        let mut collector = TraceCollector::new().with_storage(storage.clone());
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+
+        chaos.fail_next(FaultPoint::StorageWrite, 1);
+        let err = collector.emit("s1", EventType::ActionRequested, json!({})).unwrap_err();
+        assert!(matches!(err, CRAError::InjectedFault { .. }));
+
+        // The failed write is retried by the next emit, marker included
+        collector.emit("s1", EventType::ActionExecuted, json!({})).unwrap();
+        let stored = storage.get_events("s1").unwrap();
+        let types: Vec<_> = stored.iter().map(|e| e.event_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                EventType::SessionStarted,
+                EventType::ActionRequested,
+                EventType::ChaosFaultInjected,
+                EventType::ActionExecuted,
+            ]
+        );
+        assert_eq!(stored[2].payload["point"], "storage_write");
+        assert!(collector.verify_chain("s1").unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_buffer_overflow_injected() {
+        let chaos = Arc::new(ChaosInjector::new());
+        let mut collector =
+            TraceCollector::with_deferred(DeferredConfig::default()).with_chaos(chaos.clone());
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+
+        chaos.fail_next(FaultPoint::TraceBuffer, 1);
+        assert!(collector.emit("s1", EventType::ActionRequested, json!({})).is_err());
+        collector.flush().unwrap();
+
+        let types: Vec<_> = collector.get_events("s1").unwrap().iter().map(|e| e.event_type).collect();
+        assert_eq!(types, vec![EventType::SessionStarted, EventType::ChaosFaultInjected]);
+        assert!(collector.verify_chain("s1").unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_timer_misfire() {
+        use crate::timing::MockTimerBackend;
+
+        let chaos = Arc::new(ChaosInjector::new());
+        let timers = ChaosTimerBackend::new(MockTimerBackend::new(), chaos.clone());
+        chaos.fail_next(FaultPoint::Timer, 1);
+        timers
+            .schedule_once("t1", Duration::from_secs(1), TimerEvent::TraceBatchFlush)
+            .unwrap();
+        assert!(!timers.exists("t1"));
+
+        chaos.add_latency(FaultPoint::Timer, Duration::from_secs(5));
+        timers
+            .schedule_once("t2", Duration::from_secs(1), TimerEvent::TraceBatchFlush)
+            .unwrap();
+        assert_eq!(timers.inner().get_timer("t2").unwrap().duration, Duration::from_secs(6));
+    }
+}
@@ -0,0 +1,202 @@
+//! Clock Abstraction
+//!
+//! All wall-clock and monotonic time used by the core goes through this
+//! module, so constrained runtimes (edge workers, embedded, bare
+//! `wasm32-unknown-unknown`) can supply their own time source.
+//!
+//! - [`now`] returns the current wall-clock time from the installed [`Clock`]
+//! - [`Instant`] is `std::time::Instant` normally, and a clock-backed
+//!   equivalent with the `minimal` feature
+//!
+//! Without the `minimal` feature, [`SystemClock`] is used unless another
+//! clock is installed with [`set_clock`]. With `minimal`, there is no system
+//! clock: embedders should install one at startup, otherwise every timestamp
+//! is the Unix epoch.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use cra_core::clock::{Clock, FixedClock};
+//!
+//! let clock = Arc::new(FixedClock::from_millis(1_700_000_000_000));
+//! assert_eq!(clock.now().timestamp_millis(), 1_700_000_000_000);
+//!
+//! // Install globally with cra_core::clock::set_clock(clock);
+//! ```
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Source of wall-clock time
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Current wall-clock time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock that reads the process-wide clock (see [`set_clock`])
+///
+/// This is the default for resolvers and trace collectors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalClock;
+
+impl Clock for GlobalClock {
+    fn now(&self) -> DateTime<Utc> {
+        now()
+    }
+}
+
+/// Clock backed by the operating system
+#[cfg(not(feature = "minimal"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(not(feature = "minimal"))]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Manually controlled clock (tests, replay, hosts with their own time source)
+#[derive(Debug)]
+pub struct FixedClock {
+    now: RwLock<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    /// Create a clock fixed at the given time
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: RwLock::new(now) }
+    }
+
+    /// Create a clock fixed at the given Unix time in milliseconds
+    pub fn from_millis(millis: i64) -> Self {
+        Self::new(DateTime::from_timestamp_millis(millis).unwrap_or(DateTime::UNIX_EPOCH))
+    }
+
+    /// Set the current time
+    pub fn set(&self, now: DateTime<Utc>) {
+        if let Ok(mut current) = self.now.write() {
+            *current = now;
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut current) = self.now.write() {
+            *current += chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::zero());
+        }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.read().map(|n| *n).unwrap_or(DateTime::UNIX_EPOCH)
+    }
+}
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Install the process-wide clock
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    if let Ok(mut current) = CLOCK.write() {
+        *current = Some(clock);
+    }
+}
+
+/// Remove the installed clock, reverting to the default
+pub fn reset_clock() {
+    if let Ok(mut current) = CLOCK.write() {
+        *current = None;
+    }
+}
+
+/// Current wall-clock time from the installed clock
+pub fn now() -> DateTime<Utc> {
+    if let Some(clock) = CLOCK.read().ok().and_then(|c| c.clone()) {
+        return clock.now();
+    }
+
+    #[cfg(not(feature = "minimal"))]
+    {
+        Utc::now()
+    }
+
+    #[cfg(feature = "minimal")]
+    {
+        DateTime::UNIX_EPOCH
+    }
+}
+
+#[cfg(not(feature = "minimal"))]
+pub use std::time::Instant;
+
+/// Monotonic-style instant derived from the installed [`Clock`]
+///
+/// Mirrors the subset of `std::time::Instant` used by the core. Durations
+/// saturate to zero if the clock moves backwards.
+#[cfg(feature = "minimal")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(DateTime<Utc>);
+
+#[cfg(feature = "minimal")]
+impl Instant {
+    /// Current instant
+    pub fn now() -> Self {
+        Self(now())
+    }
+
+    /// Time elapsed since this instant
+    pub fn elapsed(&self) -> Duration {
+        Self::now().saturating_duration_since(*self)
+    }
+
+    /// Time between an earlier instant and this one
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Time between an earlier instant and this one, or zero
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        (self.0 - earlier.0).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// The instant `duration` before this one, if representable
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        let duration = chrono::Duration::from_std(duration).ok()?;
+        self.0.checked_sub_signed(duration).map(Instant)
+    }
+}
+
+#[cfg(feature = "minimal")]
+impl std::ops::Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        chrono::Duration::from_std(rhs)
+            .ok()
+            .and_then(|d| self.0.checked_add_signed(d))
+            .map(Instant)
+            .unwrap_or(Instant(DateTime::<Utc>::MAX_UTC))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::from_millis(1_000);
+        assert_eq!(clock.now().timestamp_millis(), 1_000);
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(clock.now().timestamp_millis(), 1_500);
+
+        clock.set(DateTime::UNIX_EPOCH);
+        assert_eq!(clock.now(), DateTime::UNIX_EPOCH);
+    }
+}
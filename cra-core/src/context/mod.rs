@@ -25,9 +25,11 @@
 
 mod registry;
 mod matcher;
+mod store;
 
 pub use registry::{ContextRegistry, LoadedContext, ContextSource};
 pub use matcher::{ContextMatcher, MatchResult, MatchScore, ConditionBuilder};
+pub use store::{content_address, ContentStore, ContentStoreStats};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,125 @@
+//! Content-addressed storage for context blocks
+//!
+//! Large context documents tend to be injected into every resolution of
+//! every session. The [`ContentStore`] keeps each distinct content once,
+//! keyed by its content address (`sha256:<hex>`, see
+//! [`content_address`]); resolutions and `context.injected` events carry
+//! the address, and the bytes are looked up or inlined when needed.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+pub use cra_trace_verify::content_address;
+
+/// Sizes and savings of a [`ContentStore`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentStoreStats {
+    /// Distinct contents held
+    pub blocks: usize,
+    /// Bytes held
+    pub bytes: usize,
+    /// Times content was stored, including repeats
+    pub references: u64,
+    /// Bytes not stored again because the content was already present
+    pub bytes_deduplicated: u64,
+}
+
+#[derive(Debug, Default)]
+struct StoreInner {
+    contents: HashMap<String, String>,
+    stats: ContentStoreStats,
+}
+
+/// Context content keyed by content address
+///
+/// Thread-safe, so one store can be shared by several resolvers with
+/// `Resolver::with_content_store`.
+#[derive(Debug, Default)]
+pub struct ContentStore {
+    inner: RwLock<StoreInner>,
+}
+
+impl ContentStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store content, returning its address
+    ///
+    /// Content already present is only counted, not stored again.
+    pub fn put(&self, content: &str) -> String {
+        let hash = content_address(content);
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.stats.references += 1;
+        if inner.contents.contains_key(&hash) {
+            inner.stats.bytes_deduplicated += content.len() as u64;
+        } else {
+            inner.stats.blocks += 1;
+            inner.stats.bytes += content.len();
+            inner.contents.insert(hash.clone(), content.to_string());
+        }
+        hash
+    }
+
+    /// Content stored under an address
+    pub fn get(&self, content_hash: &str) -> Option<String> {
+        self.read().contents.get(content_hash).cloned()
+    }
+
+    /// Whether content is stored under an address
+    pub fn contains(&self, content_hash: &str) -> bool {
+        self.read().contents.contains_key(content_hash)
+    }
+
+    /// Addresses of all stored content
+    pub fn hashes(&self) -> Vec<String> {
+        self.read().contents.keys().cloned().collect()
+    }
+
+    /// Drop content, returning the bytes freed
+    pub fn remove(&self, content_hash: &str) -> Option<usize> {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        let content = inner.contents.remove(content_hash)?;
+        inner.stats.blocks -= 1;
+        inner.stats.bytes -= content.len();
+        Some(content.len())
+    }
+
+    /// Current sizes and savings
+    pub fn stats(&self) -> ContentStoreStats {
+        self.read().stats
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, StoreInner> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_stored_once() {
+        let store = ContentStore::new();
+        let first = store.put("Use the staging cluster");
+        let second = store.put("Use the staging cluster");
+        let other = store.put("Never force-push");
+
+        assert_eq!(first, second);
+        assert!(first.starts_with("sha256:"));
+        assert_eq!(store.get(&first).as_deref(), Some("Use the staging cluster"));
+
+        let stats = store.stats();
+        assert_eq!(stats.blocks, 2);
+        assert_eq!(stats.references, 3);
+        assert_eq!(stats.bytes_deduplicated, 23);
+
+        assert_eq!(store.remove(&other), Some(16));
+        assert!(!store.contains(&other));
+        assert_eq!(store.stats().bytes, 23);
+    }
+}
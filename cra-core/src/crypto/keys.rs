@@ -0,0 +1,377 @@
+//! Key Management
+//!
+//! Signing keys are rotated rather than replaced. After a rotation the
+//! previous key stays trusted for an overlap window, so events it signed
+//! before the switch keep verifying and runtimes that haven't picked up the
+//! new key yet aren't rejected. The window is recorded as the old key's
+//! `expires_at`; [`KeyRing`] rejects events it signed after that.
+//!
+//! Rotations are recorded as `key.rotated` events in the
+//! [`ADMIN_AUDIT_SESSION`](crate::trace::ADMIN_AUDIT_SESSION) trace, signed by the outgoing key.
+//!
+//! Verifiers load trusted keys from a [`TrustStore`]: a JSON file, an
+//! environment variable, or a remote endpoint fetched by the embedder.
+
+#[cfg(not(feature = "minimal"))]
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::clock::{Clock, GlobalClock};
+use crate::error::{CRAError, Result};
+use crate::trace::{KeyRotatedPayload, TraceCollector, TraceKey, TraceSigner};
+
+use super::{Ed25519Signer, KeyRing};
+
+/// Default time a rotated-out key stays trusted
+pub const DEFAULT_OVERLAP_HOURS: i64 = 24;
+
+/// Derive a key ID from a public key
+///
+/// `<prefix>-<first 16 hex chars of SHA-256(public key)>`, so the ID in a
+/// signature identifies the key even without a registry.
+pub fn derive_key_id(prefix: &str, public_key: &[u8]) -> String {
+    let digest = hex::encode(Sha256::digest(public_key));
+    format!("{}-{}", prefix, &digest[..16])
+}
+
+/// Generate a new key with an ID derived from its public key
+pub fn generate_key(prefix: &str) -> Ed25519Signer {
+    let key = Ed25519Signer::generate("");
+    let public_key = hex::decode(key.public_key().public_key).unwrap_or_default();
+    Ed25519Signer::from_bytes(derive_key_id(prefix, &public_key), &key.secret_bytes())
+}
+
+/// Active signing key plus the keys it replaced
+#[derive(Debug)]
+pub struct KeyManager {
+    /// Key signing new events
+    active: Arc<Ed25519Signer>,
+    /// Rotated-out keys, oldest first, each with `expires_at` set
+    previous: Vec<TraceKey>,
+    /// How long a rotated-out key stays trusted
+    overlap: Duration,
+    /// Clock for rotation times
+    clock: Arc<dyn Clock>,
+}
+
+impl KeyManager {
+    /// Manage an existing key
+    pub fn new(active: Ed25519Signer) -> Self {
+        Self {
+            active: Arc::new(active),
+            previous: Vec::new(),
+            overlap: Duration::hours(DEFAULT_OVERLAP_HOURS),
+            clock: Arc::new(GlobalClock),
+        }
+    }
+
+    /// Set how long a rotated-out key stays trusted
+    pub fn with_overlap(mut self, overlap: Duration) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Use a specific clock for rotation times
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The key signing new events
+    pub fn active(&self) -> Arc<Ed25519Signer> {
+        self.active.clone()
+    }
+
+    /// Replace the active key
+    ///
+    /// The old key stays trusted until now + overlap. Use
+    /// [`KeyManager::rotate_collector`] to also switch a collector's signer.
+    pub fn rotate(&mut self, next: Ed25519Signer) -> KeyRotatedPayload {
+        let expires_at = self.clock.now() + self.overlap;
+        let next_key = next.public_key();
+
+        let mut retired = self.active.public_key();
+        retired.expires_at = Some(expires_at);
+        self.previous.push(retired);
+        let previous_key_id = self.active.key_id().to_string();
+        self.active = Arc::new(next);
+
+        KeyRotatedPayload {
+            key_id: next_key.key_id,
+            algorithm: next_key.algorithm,
+            public_key: next_key.public_key,
+            previous_key_id: Some(previous_key_id),
+            previous_expires_at: Some(expires_at),
+        }
+    }
+
+    /// Replace the active key and switch `collector` to it
+    ///
+    /// The rotation is recorded in the collector's admin audit trace.
+    pub fn rotate_collector(
+        &mut self,
+        next: Ed25519Signer,
+        collector: &mut TraceCollector,
+    ) -> Result<KeyRotatedPayload> {
+        let payload = self.rotate(next);
+        collector.rotate_signer(self.active.clone(), payload.previous_expires_at)
+    }
+
+    /// Every key whose signatures should still verify, active key first
+    pub fn trusted_keys(&self) -> Vec<TraceKey> {
+        std::iter::once(self.active.public_key())
+            .chain(self.previous.iter().rev().cloned())
+            .collect()
+    }
+
+    /// Key ring of [`KeyManager::trusted_keys`]
+    pub fn key_ring(&self) -> Result<KeyRing> {
+        KeyRing::from_keys(&self.trusted_keys())
+    }
+
+    /// Forget rotated-out keys that expired before `before`
+    ///
+    /// Events those keys signed will no longer verify. Returns how many keys
+    /// were removed.
+    pub fn prune(&mut self, before: DateTime<Utc>) -> usize {
+        let count = self.previous.len();
+        self.previous.retain(|key| key.is_valid_at(before));
+        count - self.previous.len()
+    }
+}
+
+/// Source of keys a verifier trusts
+pub trait TrustStore: Send + Sync + std::fmt::Debug {
+    /// Load the trusted keys
+    fn load(&self) -> Result<Vec<TraceKey>>;
+
+    /// Load the trusted keys into a key ring
+    fn key_ring(&self) -> Result<KeyRing> {
+        KeyRing::from_keys(&self.load()?)
+    }
+}
+
+/// Trust document: a list of keys, or an object with a `keys` list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TrustDocument {
+    Keys(Vec<TraceKey>),
+    Wrapped { keys: Vec<TraceKey> },
+}
+
+/// Parse a trust document (see [`TrustStore`] implementations)
+pub fn parse_trust_document(text: &str) -> Result<Vec<TraceKey>> {
+    Ok(match serde_json::from_str(text)? {
+        TrustDocument::Keys(keys) | TrustDocument::Wrapped { keys } => keys,
+    })
+}
+
+/// Trusted keys from a JSON file
+#[cfg(not(feature = "minimal"))]
+#[derive(Debug, Clone)]
+pub struct FileTrustStore {
+    path: PathBuf,
+}
+
+#[cfg(not(feature = "minimal"))]
+impl FileTrustStore {
+    /// Read keys from `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(not(feature = "minimal"))]
+impl TrustStore for FileTrustStore {
+    fn load(&self) -> Result<Vec<TraceKey>> {
+        let text = std::fs::read_to_string(&self.path).map_err(|e| CRAError::IoError {
+            message: format!("{}: {}", self.path.display(), e),
+        })?;
+        parse_trust_document(&text)
+    }
+}
+
+/// Trusted keys from an environment variable holding a trust document
+#[derive(Debug, Clone)]
+pub struct EnvTrustStore {
+    var: String,
+}
+
+impl EnvTrustStore {
+    /// Read keys from the variable `var` (e.g. `CRA_TRACE_KEYS`)
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl TrustStore for EnvTrustStore {
+    fn load(&self) -> Result<Vec<TraceKey>> {
+        let text = std::env::var(&self.var).map_err(|e| CRAError::IoError {
+            message: format!("{}: {}", self.var, e),
+        })?;
+        parse_trust_document(&text)
+    }
+}
+
+/// Fetches a URL and returns the response body
+pub type FetchFn = dyn Fn(&str) -> std::result::Result<String, String> + Send + Sync;
+
+/// Trusted keys from a remote endpoint (e.g. a server's key set)
+///
+/// The core has no HTTP client, so the embedder supplies the transport.
+#[derive(Clone)]
+pub struct RemoteTrustStore {
+    url: String,
+    fetch: Arc<FetchFn>,
+}
+
+impl RemoteTrustStore {
+    /// Fetch keys from `url` with `fetch`
+    pub fn new<F>(url: impl Into<String>, fetch: F) -> Self
+    where
+        F: Fn(&str) -> std::result::Result<String, String> + Send + Sync + 'static,
+    {
+        Self {
+            url: url.into(),
+            fetch: Arc::new(fetch),
+        }
+    }
+}
+
+impl std::fmt::Debug for RemoteTrustStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteTrustStore")
+            .field("url", &self.url)
+            .field("fetch", &"<callback>")
+            .finish()
+    }
+}
+
+impl TrustStore for RemoteTrustStore {
+    fn load(&self) -> Result<Vec<TraceKey>> {
+        let text = (self.fetch)(&self.url).map_err(|e| CRAError::IoError {
+            message: format!("{}: {}", self.url, e),
+        })?;
+        parse_trust_document(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::trace::{EventType, ADMIN_AUDIT_SESSION};
+    use serde_json::json;
+
+    const EPOCH_MS: i64 = 1_700_000_000_000;
+
+    fn manager() -> KeyManager {
+        KeyManager::new(Ed25519Signer::from_bytes("k1", &[1; 32]))
+            .with_overlap(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock::from_millis(EPOCH_MS)))
+    }
+
+    #[test]
+    fn test_derived_key_id() {
+        let key = generate_key("runtime");
+        let public_key = hex::decode(key.public_key().public_key).unwrap();
+
+        assert_eq!(key.key_id(), derive_key_id("runtime", &public_key));
+        assert_eq!(key.key_id().len(), "runtime-".len() + 16);
+        assert_ne!(generate_key("runtime").key_id(), key.key_id());
+    }
+
+    #[test]
+    fn test_rotation_overlap_window() {
+        let mut keys = manager();
+        let payload = keys.rotate(Ed25519Signer::from_bytes("k2", &[2; 32]));
+
+        assert_eq!(payload.previous_key_id.as_deref(), Some("k1"));
+        assert_eq!(keys.active().key_id(), "k2");
+
+        let trusted = keys.trusted_keys();
+        assert_eq!(trusted.len(), 2);
+        let expires = trusted[1].expires_at.unwrap();
+        assert_eq!(expires.timestamp_millis(), EPOCH_MS + 3_600_000);
+
+        assert_eq!(keys.prune(expires), 0);
+        assert_eq!(keys.prune(expires + Duration::seconds(1)), 1);
+        assert_eq!(keys.trusted_keys().len(), 1);
+    }
+
+    #[test]
+    fn test_expired_key_rejects_later_events() {
+        let mut keys = manager();
+        let old = keys.active();
+        keys.rotate(Ed25519Signer::from_bytes("k2", &[2; 32]));
+        let ring = keys.key_ring().unwrap();
+
+        let clock = Arc::new(FixedClock::from_millis(EPOCH_MS));
+        let mut collector = TraceCollector::new().with_clock(clock.clone()).with_signer(old);
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+        assert!(ring.verify_chain(&collector.get_events("s1").unwrap()).signatures_valid.unwrap());
+
+        clock.advance(std::time::Duration::from_secs(2 * 3600));
+        collector.emit("s1", EventType::SessionEnded, json!({})).unwrap();
+        let verification = ring.verify_chain(&collector.get_events("s1").unwrap());
+        assert_eq!(verification.signatures_valid, Some(false));
+    }
+
+    #[test]
+    fn test_rotation_recorded_to_admin_audit() {
+        let mut keys = manager();
+        let mut collector = TraceCollector::new()
+            .with_clock(Arc::new(FixedClock::from_millis(EPOCH_MS)))
+            .with_signer(keys.active());
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+
+        keys.rotate_collector(Ed25519Signer::from_bytes("k2", &[2; 32]), &mut collector)
+            .unwrap();
+        collector.emit("s1", EventType::SessionEnded, json!({})).unwrap();
+
+        let audit = collector.get_events(ADMIN_AUDIT_SESSION).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].event_type, EventType::KeyRotated);
+        assert_eq!(audit[0].payload["key_id"], "k2");
+        assert_eq!(audit[0].signature.as_ref().unwrap().key_id, "k1");
+
+        let events = collector.get_events("s1").unwrap();
+        assert_eq!(events[1].signature.as_ref().unwrap().key_id, "k2");
+        assert_eq!(
+            keys.key_ring().unwrap().verify_chain(&events).signatures_valid,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_trust_stores() {
+        let key = Ed25519Signer::from_bytes("k1", &[1; 32]).public_key();
+        let document = serde_json::to_string(&json!({"keys": [key]})).unwrap();
+
+        let remote = RemoteTrustStore::new("https://cra.example/keys", {
+            let document = document.clone();
+            move |_| Ok(document.clone())
+        });
+        assert_eq!(remote.load().unwrap(), vec![key.clone()]);
+
+        std::env::set_var("CRA_TEST_TRACE_KEYS", &document);
+        assert_eq!(EnvTrustStore::new("CRA_TEST_TRACE_KEYS").load().unwrap().len(), 1);
+        assert!(EnvTrustStore::new("CRA_TEST_TRACE_KEYS_UNSET").load().is_err());
+    }
+
+    #[cfg(not(feature = "minimal"))]
+    #[test]
+    fn test_file_trust_store() {
+        let key = Ed25519Signer::from_bytes("k1", &[1; 32]).public_key();
+        let path = std::env::temp_dir().join(format!("cra-trust-{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&vec![&key]).unwrap()).unwrap();
+
+        let ring = FileTrustStore::new(&path).key_ring();
+        std::fs::remove_file(&path).unwrap();
+        assert!(ring.unwrap().contains("k1"));
+    }
+}
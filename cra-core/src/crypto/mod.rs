@@ -0,0 +1,357 @@
+//! Ed25519 Runtime Attestation
+//!
+//! The TRACE hash chain proves that events weren't reordered or altered, but
+//! anyone can build a valid chain. Signing event hashes with a runtime key
+//! proves which runtime emitted them.
+//!
+//! - [`Ed25519Signer`] implements [`TraceSigner`] for a
+//!   [`TraceCollector`](crate::trace::TraceCollector)
+//! - [`KeyRing`] holds the public keys a verifier trusts, typically from
+//!   atlas stewards' `trace_keys`
+//! - [`verify_signatures`] checks the signatures on a chain
+//! - [`keys`] handles key generation, rotation and trust stores
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use cra_core::crypto::{Ed25519Signer, KeyRing};
+//! use cra_core::trace::{EventType, TraceCollector, TraceSigner};
+//! use serde_json::json;
+//!
+//! let signer = Arc::new(Ed25519Signer::generate("runtime-1"));
+//! let mut collector = TraceCollector::new().with_signer(signer.clone());
+//! collector.emit("session-1", EventType::SessionStarted, json!({})).unwrap();
+//!
+//! let mut keys = KeyRing::new();
+//! keys.add(&signer.public_key()).unwrap();
+//!
+//! let events = collector.get_events("session-1").unwrap();
+//! let verification = keys.verify_chain(&events);
+//! assert!(verification.is_valid);
+//! assert_eq!(verification.signatures_valid, Some(true));
+//! ```
+
+pub mod keys;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::{OsRng, RngCore};
+
+use crate::error::{CRAError, Result};
+use crate::trace::{
+    ChainVerification, ChainVerifier, EventSignature, SigningMode, TRACEEvent, TraceKey,
+    TraceSigner, ED25519,
+};
+
+/// Signs TRACE event hashes with an Ed25519 key
+pub struct Ed25519Signer {
+    key_id: String,
+    key: SigningKey,
+    mode: SigningMode,
+}
+
+impl Ed25519Signer {
+    /// Generate a new random key
+    pub fn generate(key_id: impl Into<String>) -> Self {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        Self::from_bytes(key_id, &secret)
+    }
+
+    /// Load a key from its 32-byte secret
+    pub fn from_bytes(key_id: impl Into<String>, secret: &[u8; 32]) -> Self {
+        Self {
+            key_id: key_id.into(),
+            key: SigningKey::from_bytes(secret),
+            mode: SigningMode::EveryEvent,
+        }
+    }
+
+    /// Set which events are signed
+    pub fn with_mode(mut self, mode: SigningMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The 32-byte secret, for persisting the key
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.key.to_bytes()
+    }
+}
+
+impl std::fmt::Debug for Ed25519Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ed25519Signer")
+            .field("key_id", &self.key_id)
+            .field("public_key", &hex::encode(self.key.verifying_key().as_bytes()))
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+impl TraceSigner for Ed25519Signer {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn public_key(&self) -> TraceKey {
+        TraceKey {
+            key_id: self.key_id.clone(),
+            algorithm: ED25519.to_string(),
+            public_key: hex::encode(self.key.verifying_key().as_bytes()),
+            expires_at: None,
+        }
+    }
+
+    fn sign_hash(&self, event_hash: &str) -> EventSignature {
+        // Event hashes are hex; sign the raw digest when possible so other
+        // implementations don't need to agree on hex case
+        let message = hex::decode(event_hash).unwrap_or_else(|_| event_hash.as_bytes().to_vec());
+        EventSignature {
+            key_id: self.key_id.clone(),
+            algorithm: ED25519.to_string(),
+            signature: hex::encode(self.key.sign(&message).to_bytes()),
+        }
+    }
+
+    fn mode(&self) -> SigningMode {
+        self.mode
+    }
+}
+
+/// Outcome of checking the signatures on a chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureCheck {
+    /// Every signature verified and at least one event is signed
+    pub valid: bool,
+    /// Number of signed events
+    pub signed_count: usize,
+    /// Index of the last event with a valid signature
+    pub attested_through: Option<usize>,
+    /// Index and reason of the first bad signature
+    pub error: Option<(usize, String)>,
+}
+
+/// Public keys trusted to sign TRACE events
+#[derive(Debug, Clone, Default)]
+pub struct KeyRing {
+    keys: HashMap<String, (VerifyingKey, Option<DateTime<Utc>>)>,
+}
+
+impl KeyRing {
+    /// Create an empty key ring
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a key ring from a list of keys
+    pub fn from_keys<'a>(keys: impl IntoIterator<Item = &'a TraceKey>) -> Result<Self> {
+        let mut ring = Self::new();
+        for key in keys {
+            ring.add(key)?;
+        }
+        Ok(ring)
+    }
+
+    /// Trust a key, replacing any key with the same ID
+    pub fn add(&mut self, key: &TraceKey) -> Result<()> {
+        let invalid = |reason: String| CRAError::InvalidTraceKey {
+            key_id: key.key_id.clone(),
+            reason,
+        };
+
+        if key.algorithm != ED25519 {
+            return Err(invalid(format!("unsupported algorithm '{}'", key.algorithm)));
+        }
+        let bytes: [u8; 32] = hex::decode(&key.public_key)
+            .map_err(|e| invalid(e.to_string()))?
+            .try_into()
+            .map_err(|_| invalid("public key must be 32 bytes".to_string()))?;
+        let verifying = VerifyingKey::from_bytes(&bytes).map_err(|e| invalid(e.to_string()))?;
+
+        self.keys.insert(key.key_id.clone(), (verifying, key.expires_at));
+        Ok(())
+    }
+
+    /// Check whether a key ID is trusted
+    pub fn contains(&self, key_id: &str) -> bool {
+        self.keys.contains_key(key_id)
+    }
+
+    /// Number of trusted keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if no keys are trusted
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify one event's signature
+    pub fn verify_event(&self, event: &TRACEEvent) -> std::result::Result<(), String> {
+        let signature = event.signature.as_ref().ok_or("event is not signed")?;
+        self.verify_hash(signature, &event.event_hash, event.timestamp)
+    }
+
+    /// Verify a signature over a hex-encoded hash made at time `at`
+    pub fn verify_hash(
+        &self,
+        signature: &EventSignature,
+        hash: &str,
+        at: DateTime<Utc>,
+    ) -> std::result::Result<(), String> {
+        if signature.algorithm != ED25519 {
+            return Err(format!("unsupported algorithm '{}'", signature.algorithm));
+        }
+        let (key, expires_at) = self
+            .keys
+            .get(&signature.key_id)
+            .ok_or_else(|| format!("untrusted key '{}'", signature.key_id))?;
+        if expires_at.is_some_and(|expires| at > expires) {
+            return Err(format!("key '{}' had expired at {}", signature.key_id, at));
+        }
+        let bytes: [u8; 64] = hex::decode(&signature.signature)
+            .map_err(|e| e.to_string())?
+            .try_into()
+            .map_err(|_| "signature must be 64 bytes".to_string())?;
+        let message = hex::decode(hash).map_err(|e| e.to_string())?;
+
+        key.verify(&message, &Signature::from_bytes(&bytes))
+            .map_err(|_| format!("signature by '{}' does not match hash", signature.key_id))
+    }
+
+    /// Verify the hash chain and the signatures on it
+    pub fn verify_chain(&self, events: &[TRACEEvent]) -> ChainVerification {
+        let check = verify_signatures(events, self);
+        ChainVerifier::verify(events).with_signatures(check.valid, check.attested_through)
+    }
+}
+
+/// Check the signatures on a chain against trusted keys
+///
+/// Unsigned events are allowed (batch signing leaves gaps); a signature that
+/// fails or comes from an untrusted key is not. A chain with no signatures at
+/// all is not valid.
+pub fn verify_signatures(events: &[TRACEEvent], keys: &KeyRing) -> SignatureCheck {
+    let mut check = SignatureCheck {
+        valid: false,
+        signed_count: 0,
+        attested_through: None,
+        error: None,
+    };
+
+    for (i, event) in events.iter().enumerate() {
+        if event.signature.is_none() {
+            continue;
+        }
+        check.signed_count += 1;
+        if let Err(reason) = keys.verify_event(event) {
+            check.error = Some((i, reason));
+            return check;
+        }
+        check.attested_through = Some(i);
+    }
+
+    check.valid = check.signed_count > 0;
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::trace::{EventType, TraceCollector};
+    use serde_json::json;
+
+    fn signed_session(signer: Arc<Ed25519Signer>, events: usize) -> Vec<TRACEEvent> {
+        let mut collector = TraceCollector::new().with_signer(signer);
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+        for i in 1..events {
+            collector.emit("s1", EventType::ActionExecuted, json!({"n": i})).unwrap();
+        }
+        collector.get_events("s1").unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = Arc::new(Ed25519Signer::from_bytes("rt-1", &[7; 32]));
+        let events = signed_session(signer.clone(), 3);
+        let keys = KeyRing::from_keys([&signer.public_key()]).unwrap();
+
+        let verification = keys.verify_chain(&events);
+        assert!(verification.is_valid);
+        assert_eq!(verification.signatures_valid, Some(true));
+        assert_eq!(verification.attested_through, Some(2));
+    }
+
+    #[test]
+    fn test_untrusted_key() {
+        let signer = Arc::new(Ed25519Signer::from_bytes("rt-1", &[7; 32]));
+        let other = Ed25519Signer::from_bytes("rt-1", &[8; 32]);
+        let events = signed_session(signer, 2);
+        let keys = KeyRing::from_keys([&other.public_key()]).unwrap();
+
+        let check = verify_signatures(&events, &keys);
+        assert!(!check.valid);
+        assert_eq!(check.error.map(|(i, _)| i), Some(0));
+        assert!(verify_signatures(&events, &KeyRing::new()).error.is_some());
+    }
+
+    #[test]
+    fn test_forged_chain_fails_signature_check() {
+        let signer = Arc::new(Ed25519Signer::from_bytes("rt-1", &[7; 32]));
+        let mut events = signed_session(signer.clone(), 2);
+        let keys = KeyRing::from_keys([&signer.public_key()]).unwrap();
+
+        // Rewrite the payload and recompute the hash: the chain is valid
+        // again, but the signature no longer matches
+        events[1].payload = json!({"n": 99});
+        events[1].event_hash = events[1].compute_hash();
+
+        let verification = keys.verify_chain(&events);
+        assert!(verification.is_valid);
+        assert_eq!(verification.signatures_valid, Some(false));
+    }
+
+    #[test]
+    fn test_batch_mode_attests_through_last_signature() {
+        let signer = Arc::new(
+            Ed25519Signer::from_bytes("rt-1", &[7; 32]).with_mode(SigningMode::Batch { interval: 2 }),
+        );
+        let events = signed_session(signer.clone(), 4);
+        let keys = KeyRing::from_keys([&signer.public_key()]).unwrap();
+
+        let check = verify_signatures(&events, &keys);
+        assert!(check.valid);
+        assert_eq!(check.signed_count, 2);
+        assert_eq!(check.attested_through, Some(3));
+    }
+
+    #[test]
+    fn test_unsigned_chain() {
+        let mut collector = TraceCollector::new();
+        collector.emit("s1", EventType::SessionStarted, json!({})).unwrap();
+        let events = collector.get_events("s1").unwrap();
+
+        let check = verify_signatures(&events, &KeyRing::new());
+        assert!(!check.valid);
+        assert_eq!(check.signed_count, 0);
+        assert!(check.error.is_none());
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let key = TraceKey {
+            key_id: "k".to_string(),
+            algorithm: ED25519.to_string(),
+            public_key: "abcd".to_string(),
+            expires_at: None,
+        };
+        assert!(matches!(KeyRing::new().add(&key), Err(CRAError::InvalidTraceKey { .. })));
+    }
+}
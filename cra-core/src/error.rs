@@ -120,6 +120,30 @@ pub enum CRAError {
     #[error("Session already ended: '{session_id}'. Create a new session to continue.")]
     SessionAlreadyEnded { session_id: String },
 
+    /// Session was quarantined after its TRACE chain failed verification
+    #[error("Session quarantined: '{session_id}': {reason}")]
+    SessionQuarantined { session_id: String, reason: String },
+
+    /// Session snapshot could not be exported or imported
+    #[error("Session handoff failed for '{session_id}': {reason}")]
+    SessionHandoffError { session_id: String, reason: String },
+
+    /// Bootstrap request or acknowledgment was rejected
+    #[error("Bootstrap rejected: {reason}")]
+    InvalidBootstrap { reason: String },
+
+    /// Resolver snapshot could not be taken or restored
+    #[error("Resolver snapshot failed: {reason}")]
+    ResolverSnapshotError { reason: String },
+
+    /// Replication batch could not be applied to a read replica
+    #[error("Replication failed: {reason}")]
+    ReplicationError { reason: String },
+
+    /// Read replica hasn't synced with its primary within its staleness bound
+    #[error("Read replica is stale: last synced {staleness_ms}ms ago (limit {max_staleness_ms}ms). Retry or read from the primary.")]
+    ReplicaStale { staleness_ms: u64, max_staleness_ms: u64 },
+
     // ═══════════════════════════════════════════════════════════════════════
     // CARP errors (context and action resolution)
     // ═══════════════════════════════════════════════════════════════════════
@@ -128,21 +152,53 @@ pub enum CRAError {
     #[error("Invalid CARP request: {reason}")]
     InvalidCARPRequest { reason: String },
 
+    /// HTTP request body doesn't match its endpoint's JSON Schema
+    #[error("Invalid request body for {endpoint}: {}", FieldError::join(.errors))]
+    InvalidRequestBody { endpoint: String, errors: Vec<FieldError> },
+
+    /// Idempotency key was already used for a different request
+    #[error("Idempotency key '{key}' conflicts: {reason}")]
+    IdempotencyKeyConflict { key: String, reason: String },
+
     /// Resolution has expired and can no longer be used
     #[error("Resolution expired: TTL exceeded. Request a new resolution.")]
     ResolutionExpired,
 
+    /// An atlas the resolution was made against has been unloaded or replaced
+    #[error("Resolution '{resolution_id}' is no longer valid: {reason}. Request a new resolution.")]
+    ResolutionInvalidated { resolution_id: String, reason: String },
+
     /// Action ID doesn't exist in any loaded atlas
     #[error("Action not found: '{action_id}'. Verify the action exists in a loaded atlas.")]
     ActionNotFound { action_id: String },
 
+    /// Capability ID doesn't exist in any loaded atlas
+    #[error("Capability not found: '{capability_id}'. Verify the capability exists in a loaded atlas.")]
+    CapabilityNotFound { capability_id: String },
+
+    /// Credential presented by a caller was rejected by every identity provider
+    #[error("Authentication failed: {reason}")]
+    AuthenticationFailed { reason: String },
+
     /// Action is explicitly denied by a policy
     #[error("Action denied by policy '{policy_id}': {reason}")]
     ActionDenied { policy_id: String, reason: String },
 
     /// Action requires human approval before execution
-    #[error("Action '{action_id}' requires approval. Submit for review before executing.")]
-    ActionRequiresApproval { action_id: String },
+    #[error("Action '{action_id}' requires approval (request '{approval_id}'). Retry once a human has approved it.")]
+    ActionRequiresApproval { action_id: String, approval_id: String },
+
+    /// Approval request ID doesn't exist
+    #[error("Approval request not found: '{approval_id}'")]
+    ApprovalNotFound { approval_id: String },
+
+    /// Content-addressed context isn't in the content store
+    #[error("Context content not found: '{content_hash}'")]
+    ContentNotFound { content_hash: String },
+
+    /// Approval request has already been decided
+    #[error("Approval request '{approval_id}' is already {status}")]
+    ApprovalAlreadyDecided { approval_id: String, status: String },
 
     /// Rate limit for this action has been exceeded
     #[error("Rate limit exceeded for action '{action_id}'. Wait before retrying.")]
@@ -160,6 +216,10 @@ pub enum CRAError {
     #[error("Invalid trace event: {reason}")]
     InvalidTraceEvent { reason: String },
 
+    /// Trace signing key is malformed or uses an unsupported algorithm
+    #[error("Invalid trace key '{key_id}': {reason}")]
+    InvalidTraceKey { key_id: String, reason: String },
+
     /// Replay of trace events failed
     #[error("Replay failed: {reason}")]
     ReplayError { reason: String },
@@ -188,6 +248,10 @@ pub enum CRAError {
     #[error("Invalid parameters for action '{action_id}': {reason}")]
     InvalidParameters { action_id: String, reason: String },
 
+    /// Action template references a variable the session doesn't define
+    #[error("Unknown session variable '{variable}' referenced by action '{action_id}'. Set it at create_session() or with set_session_variable().")]
+    UnknownSessionVariable { variable: String, action_id: String },
+
     // ═══════════════════════════════════════════════════════════════════════
     // Execution errors
     // ═══════════════════════════════════════════════════════════════════════
@@ -208,10 +272,27 @@ pub enum CRAError {
     #[error("Storage backend lock poisoned. This is a bug; please report it.")]
     StorageLocked,
 
+    /// Stored data could not be encrypted or decrypted (missing key or
+    /// tampered ciphertext)
+    #[error("Storage encryption error: {reason}")]
+    StorageEncryptionError { reason: String },
+
+    /// Event search query is malformed (bad JSONPath or time range)
+    #[error("Invalid query: {reason}")]
+    InvalidQuery { reason: String },
+
+    /// Storage configuration is invalid or names a backend this build lacks
+    #[error("Invalid storage configuration: {reason}")]
+    StorageConfigError { reason: String },
+
     /// I/O operation failed
     #[error("IO error: {message}")]
     IoError { message: String },
 
+    /// Fault deliberately injected by a chaos test (`chaos` feature)
+    #[error("Injected fault at {point}: {reason}")]
+    InjectedFault { point: String, reason: String },
+
     /// Internal error that shouldn't happen
     #[error("Internal error: {reason}. This is a bug; please report it.")]
     InternalError { reason: String },
@@ -233,9 +314,12 @@ impl CRAError {
         matches!(
             self,
             CRAError::ResolutionExpired
+                | CRAError::ResolutionInvalidated { .. }
                 | CRAError::RateLimitExceeded { .. }
                 | CRAError::ActionRequiresApproval { .. }
                 | CRAError::StorageLocked
+                | CRAError::ReplicaStale { .. }
+                | CRAError::InjectedFault { .. }
         )
     }
 
@@ -269,25 +353,39 @@ impl CRAError {
             // Not found
             CRAError::AtlasNotFound { .. }
             | CRAError::SessionNotFound { .. }
-            | CRAError::ActionNotFound { .. } => ErrorCategory::NotFound,
+            | CRAError::ActionNotFound { .. }
+            | CRAError::CapabilityNotFound { .. }
+            | CRAError::ApprovalNotFound { .. }
+            | CRAError::ContentNotFound { .. } => ErrorCategory::NotFound,
 
             // Validation
             CRAError::InvalidAtlasManifest { .. }
             | CRAError::AtlasVersionMismatch { .. }
+            | CRAError::InvalidBootstrap { .. }
             | CRAError::InvalidCARPRequest { .. }
+            | CRAError::InvalidRequestBody { .. }
             | CRAError::InvalidTraceEvent { .. }
+            | CRAError::InvalidTraceKey { .. }
             | CRAError::InvalidPolicy { .. }
             | CRAError::SchemaValidationError { .. }
-            | CRAError::InvalidParameters { .. } => ErrorCategory::Validation,
+            | CRAError::InvalidParameters { .. }
+            | CRAError::UnknownSessionVariable { .. }
+            | CRAError::InvalidQuery { .. }
+            | CRAError::StorageConfigError { .. } => ErrorCategory::Validation,
 
             // Authorization
             CRAError::ActionDenied { .. }
-            | CRAError::ActionRequiresApproval { .. } => ErrorCategory::Authorization,
+            | CRAError::ActionRequiresApproval { .. }
+            | CRAError::SessionQuarantined { .. }
+            | CRAError::AuthenticationFailed { .. } => ErrorCategory::Authorization,
 
             // Conflict
             CRAError::AtlasAlreadyLoaded { .. }
             | CRAError::SessionAlreadyExists { .. }
-            | CRAError::SessionAlreadyEnded { .. } => ErrorCategory::Conflict,
+            | CRAError::SessionAlreadyEnded { .. }
+            | CRAError::ResolutionInvalidated { .. }
+            | CRAError::ApprovalAlreadyDecided { .. }
+            | CRAError::IdempotencyKeyConflict { .. } => ErrorCategory::Conflict,
 
             // Rate limit
             CRAError::RateLimitExceeded { .. }
@@ -296,18 +394,24 @@ impl CRAError {
 
             // Integrity
             CRAError::TraceChainIntegrityError { .. }
-            | CRAError::ReplayError { .. } => ErrorCategory::Integrity,
+            | CRAError::ReplayError { .. }
+            | CRAError::SessionHandoffError { .. }
+            | CRAError::ResolverSnapshotError { .. }
+            | CRAError::ReplicationError { .. }
+            | CRAError::StorageEncryptionError { .. } => ErrorCategory::Integrity,
 
             // Internal
             CRAError::StorageLocked
             | CRAError::InternalError { .. }
+            | CRAError::InjectedFault { .. }
             | CRAError::PolicyEvaluationError { .. } => ErrorCategory::Internal,
 
-            // External (I/O, JSON, file loading)
+            // External (I/O, JSON, file loading, replication)
             CRAError::AtlasLoadError { .. }
             | CRAError::ExecutionError { .. }
             | CRAError::JsonError(_)
-            | CRAError::IoError { .. } => ErrorCategory::External,
+            | CRAError::IoError { .. }
+            | CRAError::ReplicaStale { .. } => ErrorCategory::External,
         }
     }
 
@@ -329,24 +433,44 @@ impl CRAError {
             CRAError::SessionAlreadyExists { .. } => "SESSION_ALREADY_EXISTS",
             CRAError::SessionExpired { .. } => "SESSION_EXPIRED",
             CRAError::SessionAlreadyEnded { .. } => "SESSION_ALREADY_ENDED",
+            CRAError::SessionQuarantined { .. } => "SESSION_QUARANTINED",
+            CRAError::IdempotencyKeyConflict { .. } => "IDEMPOTENCY_KEY_CONFLICT",
+            CRAError::SessionHandoffError { .. } => "SESSION_HANDOFF_ERROR",
+            CRAError::InvalidBootstrap { .. } => "INVALID_BOOTSTRAP",
+            CRAError::ResolverSnapshotError { .. } => "RESOLVER_SNAPSHOT_ERROR",
+            CRAError::ReplicationError { .. } => "REPLICATION_ERROR",
+            CRAError::ReplicaStale { .. } => "REPLICA_STALE",
             CRAError::InvalidCARPRequest { .. } => "INVALID_CARP_REQUEST",
+            CRAError::InvalidRequestBody { .. } => "INVALID_REQUEST_BODY",
             CRAError::ResolutionExpired => "RESOLUTION_EXPIRED",
+            CRAError::ResolutionInvalidated { .. } => "RESOLUTION_INVALIDATED",
             CRAError::ActionNotFound { .. } => "ACTION_NOT_FOUND",
+            CRAError::CapabilityNotFound { .. } => "CAPABILITY_NOT_FOUND",
+            CRAError::AuthenticationFailed { .. } => "AUTHENTICATION_FAILED",
             CRAError::ActionDenied { .. } => "ACTION_DENIED",
             CRAError::ActionRequiresApproval { .. } => "ACTION_REQUIRES_APPROVAL",
+            CRAError::ApprovalNotFound { .. } => "APPROVAL_NOT_FOUND",
+            CRAError::ContentNotFound { .. } => "CONTENT_NOT_FOUND",
+            CRAError::ApprovalAlreadyDecided { .. } => "APPROVAL_ALREADY_DECIDED",
             CRAError::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
             CRAError::TraceChainIntegrityError { .. } => "TRACE_CHAIN_INTEGRITY_ERROR",
             CRAError::InvalidTraceEvent { .. } => "INVALID_TRACE_EVENT",
+            CRAError::InvalidTraceKey { .. } => "INVALID_TRACE_KEY",
             CRAError::ReplayError { .. } => "REPLAY_ERROR",
             CRAError::InvalidPolicy { .. } => "INVALID_POLICY",
             CRAError::PolicyEvaluationError { .. } => "POLICY_EVALUATION_ERROR",
             CRAError::SchemaValidationError { .. } => "SCHEMA_VALIDATION_ERROR",
             CRAError::InvalidParameters { .. } => "INVALID_PARAMETERS",
+            CRAError::UnknownSessionVariable { .. } => "UNKNOWN_SESSION_VARIABLE",
             CRAError::ExecutionError { .. } => "EXECUTION_ERROR",
             CRAError::JsonError(_) => "JSON_ERROR",
             CRAError::StorageLocked => "STORAGE_LOCKED",
+            CRAError::StorageEncryptionError { .. } => "STORAGE_ENCRYPTION_ERROR",
+            CRAError::StorageConfigError { .. } => "STORAGE_CONFIG_ERROR",
+            CRAError::InvalidQuery { .. } => "INVALID_QUERY",
             CRAError::IoError { .. } => "IO_ERROR",
             CRAError::InternalError { .. } => "INTERNAL_ERROR",
+            CRAError::InjectedFault { .. } => "INJECTED_FAULT",
         }
     }
 
@@ -359,32 +483,50 @@ impl CRAError {
             // 400 Bad Request - Client sent invalid data
             CRAError::InvalidAtlasManifest { .. }
             | CRAError::AtlasVersionMismatch { .. }
+            | CRAError::InvalidBootstrap { .. }
             | CRAError::InvalidCARPRequest { .. }
+            | CRAError::InvalidRequestBody { .. }
             | CRAError::InvalidTraceEvent { .. }
+            | CRAError::InvalidTraceKey { .. }
             | CRAError::InvalidPolicy { .. }
             | CRAError::SchemaValidationError { .. }
-            | CRAError::InvalidParameters { .. } => 400,
+            | CRAError::InvalidParameters { .. }
+            | CRAError::UnknownSessionVariable { .. }
+            | CRAError::InvalidQuery { .. }
+            | CRAError::StorageConfigError { .. } => 400,
+
+            // 401 Unauthorized - Caller could not be authenticated
+            CRAError::AuthenticationFailed { .. } => 401,
 
             // 403 Forbidden - Action not allowed
-            CRAError::ActionDenied { .. } => 403,
+            CRAError::ActionDenied { .. } | CRAError::SessionQuarantined { .. } => 403,
 
             // 404 Not Found - Resource doesn't exist
             CRAError::AtlasNotFound { .. }
             | CRAError::SessionNotFound { .. }
-            | CRAError::ActionNotFound { .. } => 404,
+            | CRAError::ActionNotFound { .. }
+            | CRAError::CapabilityNotFound { .. }
+            | CRAError::ApprovalNotFound { .. }
+            | CRAError::ContentNotFound { .. } => 404,
 
             // 409 Conflict - Resource state conflict
             CRAError::AtlasAlreadyLoaded { .. }
             | CRAError::SessionAlreadyExists { .. }
-            | CRAError::SessionAlreadyEnded { .. } => 409,
+            | CRAError::SessionAlreadyEnded { .. }
+            | CRAError::ApprovalAlreadyDecided { .. }
+            | CRAError::IdempotencyKeyConflict { .. } => 409,
 
             // 410 Gone - Resource no longer available
             CRAError::SessionExpired { .. }
-            | CRAError::ResolutionExpired => 410,
+            | CRAError::ResolutionExpired
+            | CRAError::ResolutionInvalidated { .. } => 410,
 
             // 422 Unprocessable Entity - Semantic error
             CRAError::TraceChainIntegrityError { .. }
             | CRAError::ReplayError { .. }
+            | CRAError::SessionHandoffError { .. }
+            | CRAError::ResolverSnapshotError { .. }
+            | CRAError::ReplicationError { .. }
             | CRAError::PolicyEvaluationError { .. } => 422,
 
             // 423 Locked - Resource temporarily unavailable
@@ -395,6 +537,7 @@ impl CRAError {
 
             // 500 Internal Server Error - Our fault
             CRAError::StorageLocked
+            | CRAError::StorageEncryptionError { .. }
             | CRAError::InternalError { .. } => 500,
 
             // 502 Bad Gateway - External dependency failed
@@ -402,6 +545,10 @@ impl CRAError {
             | CRAError::ExecutionError { .. }
             | CRAError::JsonError(_)
             | CRAError::IoError { .. } => 502,
+
+            // 503 Service Unavailable - Replica behind its primary, or a
+            // fault injected by a chaos test
+            CRAError::ReplicaStale { .. } | CRAError::InjectedFault { .. } => 503,
         }
     }
 
@@ -418,13 +565,21 @@ impl CRAError {
     ///   }
     /// }
     /// ```
+    ///
+    /// `INVALID_REQUEST_BODY` errors also list each invalid field in
+    /// `error.fields`.
     pub fn to_error_response(&self) -> ErrorResponse {
+        let fields = match self {
+            CRAError::InvalidRequestBody { errors, .. } => errors.clone(),
+            _ => vec![],
+        };
         ErrorResponse {
             error: ErrorDetail {
                 code: self.error_code().to_string(),
                 message: self.to_string(),
                 category: self.category(),
                 recoverable: self.is_recoverable(),
+                fields,
             },
         }
     }
@@ -448,6 +603,37 @@ pub struct ErrorDetail {
     pub category: ErrorCategory,
     /// Whether retry might succeed
     pub recoverable: bool,
+    /// Invalid fields of a rejected request body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+/// One invalid field of a request body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Path to the field, e.g. `goal` or `parameters.items[0]`; empty for
+    /// the body itself
+    pub field: String,
+    /// What's wrong with it
+    pub message: String,
+}
+
+impl FieldError {
+    /// Create a field error
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    fn join(errors: &[FieldError]) -> String {
+        errors
+            .iter()
+            .map(|e| if e.field.is_empty() { e.message.clone() } else { format!("{}: {}", e.field, e.message) })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
 }
 
 #[cfg(test)]
@@ -516,6 +702,13 @@ mod tests {
             .http_status_code(),
             429
         );
+        assert_eq!(
+            CRAError::AuthenticationFailed {
+                reason: "token has expired".to_string()
+            }
+            .http_status_code(),
+            401
+        );
         assert_eq!(CRAError::StorageLocked.http_status_code(), 500);
     }
 
@@ -28,6 +28,8 @@ use std::cell::RefCell;
 
 use crate::atlas::AtlasManifest;
 use crate::carp::{CARPRequest, Resolver};
+use crate::clock::FixedClock;
+use crate::id::SequentialIdGen;
 
 // Thread-local storage for error messages
 thread_local! {
@@ -116,6 +118,22 @@ pub extern "C" fn cra_resolver_new() -> *mut CRAResolver {
     }))
 }
 
+/// Create a deterministic CRA resolver.
+///
+/// Timestamps are fixed at `epoch_ms` (Unix milliseconds) and IDs are
+/// sequential from `seed`, so identical calls produce identical traces.
+/// Used by the golden trace conformance suite.
+/// The resolver must be freed with `cra_resolver_free`.
+#[no_mangle]
+pub extern "C" fn cra_resolver_new_deterministic(epoch_ms: i64, seed: u64) -> *mut CRAResolver {
+    clear_error();
+    Box::into_raw(Box::new(CRAResolver {
+        inner: Resolver::new()
+            .with_clock(FixedClock::from_millis(epoch_ms))
+            .with_id_generator(SequentialIdGen::with_seed(seed)),
+    }))
+}
+
 /// Free a resolver.
 #[no_mangle]
 pub extern "C" fn cra_resolver_free(resolver: *mut CRAResolver) {
@@ -527,6 +545,99 @@ pub extern "C" fn cra_resolver_verify_chain(
     }
 }
 
+/// Serialize the resolver's full state (see `Resolver::snapshot`).
+///
+/// Returns the snapshot bytes on success, null on error, and writes their
+/// length to `out_len`. The returned buffer must be freed with
+/// `cra_free_bytes`.
+///
+/// # Safety
+///
+/// `resolver` must come from `cra_resolver_new` and `out_len` must point to
+/// writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn cra_resolver_snapshot(resolver: *mut CRAResolver, out_len: *mut usize) -> *mut u8 {
+    clear_error();
+
+    let resolver = unsafe {
+        match resolver.as_mut() {
+            Some(r) => r,
+            None => {
+                set_error("Null resolver pointer".to_string());
+                return ptr::null_mut();
+            }
+        }
+    };
+    if out_len.is_null() {
+        set_error("Null length pointer".to_string());
+        return ptr::null_mut();
+    }
+
+    match resolver.inner.snapshot() {
+        Ok(bytes) => {
+            let bytes = bytes.into_boxed_slice();
+            unsafe {
+                *out_len = bytes.len();
+            }
+            Box::into_raw(bytes) as *mut u8
+        }
+        Err(e) => {
+            set_error(format!("Failed to snapshot resolver: {}", e));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Restore state from `cra_resolver_snapshot` into a fresh resolver.
+///
+/// Returns 0 on success, -1 on error.
+///
+/// # Safety
+///
+/// `resolver` must come from `cra_resolver_new` and `data` must point to
+/// `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cra_resolver_restore(resolver: *mut CRAResolver, data: *const u8, len: usize) -> i32 {
+    clear_error();
+
+    let resolver = unsafe {
+        match resolver.as_mut() {
+            Some(r) => r,
+            None => {
+                set_error("Null resolver pointer".to_string());
+                return -1;
+            }
+        }
+    };
+    if data.is_null() {
+        set_error("Null snapshot pointer".to_string());
+        return -1;
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    match resolver.inner.restore(bytes) {
+        Ok(()) => 0,
+        Err(e) => {
+            set_error(format!("Failed to restore resolver: {}", e));
+            -1
+        }
+    }
+}
+
+/// Free a buffer returned by `cra_resolver_snapshot`.
+///
+/// # Safety
+///
+/// `data` and `len` must be exactly as returned by `cra_resolver_snapshot`.
+#[no_mangle]
+pub unsafe extern "C" fn cra_free_bytes(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+        }
+    }
+}
+
 // ============================================================================
 // Version Info
 // ============================================================================
@@ -589,6 +700,33 @@ mod tests {
         cra_resolver_free(resolver);
     }
 
+    #[test]
+    fn test_snapshot_restore() {
+        let resolver = cra_resolver_new();
+        let agent_id = CString::new("test-agent").unwrap();
+        let goal = CString::new("test goal").unwrap();
+        let session_id = cra_resolver_create_session(resolver, agent_id.as_ptr(), goal.as_ptr());
+
+        let mut len = 0;
+        let bytes = unsafe { cra_resolver_snapshot(resolver, &mut len) };
+        assert!(!bytes.is_null());
+        assert!(len > 0);
+
+        let restored = cra_resolver_new();
+        assert_eq!(unsafe { cra_resolver_restore(restored, bytes, len) }, 0);
+        let trace = cra_resolver_get_trace(restored, session_id);
+        assert!(!trace.is_null());
+
+        // Restoring twice fails: the resolver is no longer empty
+        assert_eq!(unsafe { cra_resolver_restore(restored, bytes, len) }, -1);
+
+        cra_free_string(trace);
+        unsafe { cra_free_bytes(bytes, len) };
+        cra_free_string(session_id);
+        cra_resolver_free(restored);
+        cra_resolver_free(resolver);
+    }
+
     #[test]
     fn test_error_handling() {
         // Try to create session with null resolver
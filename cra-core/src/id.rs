@@ -0,0 +1,77 @@
+//! ID Generation
+//!
+//! Session IDs, trace IDs, event IDs and span IDs come from an [`IdGen`].
+//! The default is random UUIDv4; [`SequentialIdGen`] yields reproducible
+//! IDs for tests, replay and golden traces.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use uuid::Uuid;
+
+/// Source of unique identifiers
+pub trait IdGen: Send + Sync + std::fmt::Debug {
+    /// Generate the next identifier
+    fn next_id(&self) -> String;
+}
+
+/// Random UUIDv4 identifiers (default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidGen;
+
+impl IdGen for UuidGen {
+    fn next_id(&self) -> String {
+        Uuid::new_v4().to_string()
+    }
+}
+
+/// Deterministic UUID-formatted identifiers from a counter
+///
+/// IDs are `{seed}` in the high 64 bits and the counter in the low 64 bits,
+/// so two generators with the same seed produce the same sequence.
+#[derive(Debug, Default)]
+pub struct SequentialIdGen {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl SequentialIdGen {
+    /// Create a generator starting at 1 with seed 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a generator with a seed (distinguishes parallel generators)
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+}
+
+impl IdGen for SequentialIdGen {
+    fn next_id(&self) -> String {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        Uuid::from_u64_pair(self.seed, n).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_ids() {
+        let ids = SequentialIdGen::new();
+        assert_eq!(ids.next_id(), "00000000-0000-0000-0000-000000000001");
+        assert_eq!(ids.next_id(), "00000000-0000-0000-0000-000000000002");
+
+        let seeded = SequentialIdGen::with_seed(7);
+        assert_eq!(seeded.next_id(), "00000000-0000-0007-0000-000000000001");
+    }
+
+    #[test]
+    fn test_uuid_ids_are_unique() {
+        assert_ne!(UuidGen.next_id(), UuidGen.next_id());
+    }
+}
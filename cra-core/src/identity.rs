@@ -0,0 +1,631 @@
+//! Caller identity
+//!
+//! An [`IdentityProvider`] turns a credential presented to a server (an API
+//! key, or a JWT bearer token) into a validated [`Principal`]. Sessions
+//! created for a principal record it, its `agent_id` becomes the session's
+//! agent, and it is sent to `external` policy engines with every decision
+//! request, so policies can use roles and tenant.
+//!
+//! Two providers are built in:
+//!
+//! - [`ApiKeyProvider`] maps static API keys to principals
+//! - [`OidcProvider`] validates JWTs issued by an OIDC issuer against its
+//!   JWKS, enforcing issuer, audience, expiry and not-before, and maps
+//!   claims to the principal
+//!
+//! Supported JWT algorithms are HS256, EdDSA (with the `signing` feature)
+//! and RS256 (with the `oidc` feature). As elsewhere, the core has no HTTP
+//! client: the JWKS document is fetched through an embedder-provided
+//! [`JwksFetchFn`] and cached.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::clock::Instant;
+use crate::error::{CRAError, Result};
+
+/// Default time a fetched JWKS is reused
+pub const DEFAULT_JWKS_TTL: Duration = Duration::from_secs(3600);
+
+/// Default minimum time between JWKS fetches triggered by unknown key IDs
+pub const DEFAULT_JWKS_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+/// Default clock skew tolerated on `exp` and `nbf`
+pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+/// Callback that GETs a JSON document (the issuer's JWKS)
+///
+/// Returns `Err(message)` when the fetch fails.
+pub type JwksFetchFn = Arc<dyn Fn(&str) -> std::result::Result<Value, String> + Send + Sync>;
+
+/// A validated caller
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    /// Provider that validated the credential
+    pub provider: String,
+    /// Stable subject identifier (JWT `sub`, or the API key's name)
+    pub subject: String,
+    /// Agent ID sessions are created under
+    pub agent_id: String,
+    /// Token issuer, for JWTs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Tenant the caller belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Roles granted to the caller
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// When the credential stops being valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Principal {
+    /// Create a principal whose agent ID is its subject
+    pub fn new(provider: impl Into<String>, subject: impl Into<String>) -> Self {
+        let subject = subject.into();
+        Self {
+            provider: provider.into(),
+            agent_id: subject.clone(),
+            subject,
+            issuer: None,
+            tenant: None,
+            roles: Vec::new(),
+            expires_at: None,
+        }
+    }
+
+    /// Set the agent ID
+    pub fn with_agent_id(mut self, agent_id: impl Into<String>) -> Self {
+        self.agent_id = agent_id.into();
+        self
+    }
+
+    /// Set the tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Set the roles
+    pub fn with_roles(mut self, roles: Vec<String>) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    /// Whether the principal has a role
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// Validates credentials presented by callers
+pub trait IdentityProvider: Send + Sync + std::fmt::Debug {
+    /// Validate a credential, as of `now`
+    ///
+    /// `credential` is the bare API key or token, without any `Bearer `
+    /// prefix. Fails with [`CRAError::AuthenticationFailed`].
+    fn authenticate(&self, credential: &str, now: DateTime<Utc>) -> Result<Principal>;
+
+    /// Get provider name for logging
+    fn name(&self) -> &'static str;
+}
+
+fn rejected(reason: impl Into<String>) -> CRAError {
+    CRAError::AuthenticationFailed { reason: reason.into() }
+}
+
+/// Identity provider for static API keys
+///
+/// Only SHA-256 hashes of the keys are kept.
+#[derive(Debug, Default)]
+pub struct ApiKeyProvider {
+    keys: HashMap<String, Principal>,
+}
+
+impl ApiKeyProvider {
+    /// Create a provider with no keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `key` as `principal`
+    pub fn with_key(mut self, key: &str, principal: Principal) -> Self {
+        self.keys.insert(hex::encode(Sha256::digest(key.as_bytes())), principal);
+        self
+    }
+}
+
+impl IdentityProvider for ApiKeyProvider {
+    fn authenticate(&self, credential: &str, _now: DateTime<Utc>) -> Result<Principal> {
+        self.keys
+            .get(&hex::encode(Sha256::digest(credential.as_bytes())))
+            .cloned()
+            .ok_or_else(|| rejected("unknown API key"))
+    }
+
+    fn name(&self) -> &'static str {
+        "api_key"
+    }
+}
+
+/// Where principal fields come from in JWT claims
+///
+/// Claim names may be dotted paths into nested claims, e.g.
+/// `realm_access.roles`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimMapping {
+    /// Claim holding the agent ID (default `sub`)
+    pub agent_id: String,
+    /// Claim holding the tenant, if any
+    pub tenant: Option<String>,
+    /// Claim holding the roles, as an array or a space-separated string (default `roles`)
+    pub roles: String,
+}
+
+impl Default for ClaimMapping {
+    fn default() -> Self {
+        Self {
+            agent_id: "sub".to_string(),
+            tenant: None,
+            roles: "roles".to_string(),
+        }
+    }
+}
+
+/// Configuration of an [`OidcProvider`]
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Expected `iss` claim
+    pub issuer: String,
+    /// Audience that must appear in the `aud` claim
+    pub audience: String,
+    /// JWKS location (default `{issuer}/.well-known/jwks.json`)
+    pub jwks_uri: String,
+    /// How claims map to the principal
+    pub claims: ClaimMapping,
+    /// Clock skew tolerated on `exp` and `nbf`
+    pub leeway: Duration,
+    /// How long a fetched JWKS is reused
+    pub jwks_ttl: Duration,
+    /// Minimum time between JWKS fetches, so tokens with unknown key IDs
+    /// can't make every request hit the issuer
+    pub jwks_min_refresh: Duration,
+}
+
+impl OidcConfig {
+    /// Configuration for an issuer and audience
+    pub fn new(issuer: impl Into<String>, audience: impl Into<String>) -> Self {
+        let issuer = issuer.into();
+        Self {
+            jwks_uri: format!("{}/.well-known/jwks.json", issuer.trim_end_matches('/')),
+            issuer,
+            audience: audience.into(),
+            claims: ClaimMapping::default(),
+            leeway: DEFAULT_LEEWAY,
+            jwks_ttl: DEFAULT_JWKS_TTL,
+            jwks_min_refresh: DEFAULT_JWKS_MIN_REFRESH,
+        }
+    }
+
+    /// Set the JWKS location
+    pub fn with_jwks_uri(mut self, uri: impl Into<String>) -> Self {
+        self.jwks_uri = uri.into();
+        self
+    }
+
+    /// Set the claim mapping
+    pub fn with_claim_mapping(mut self, claims: ClaimMapping) -> Self {
+        self.claims = claims;
+        self
+    }
+
+    /// Set the tolerated clock skew
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Set how long a fetched JWKS is reused
+    pub fn with_jwks_ttl(mut self, ttl: Duration) -> Self {
+        self.jwks_ttl = ttl;
+        self
+    }
+
+    /// Set the minimum time between JWKS fetches
+    pub fn with_jwks_min_refresh(mut self, interval: Duration) -> Self {
+        self.jwks_min_refresh = interval;
+        self
+    }
+}
+
+/// Identity provider for JWTs issued by an OIDC issuer
+pub struct OidcProvider {
+    config: OidcConfig,
+    fetch: JwksFetchFn,
+    jwks: RwLock<JwksCache>,
+}
+
+/// Keys from the last usable JWKS, and when the issuer was last asked
+#[derive(Debug, Clone, Default)]
+struct JwksCache {
+    keys: Vec<Value>,
+    /// When `keys` were fetched
+    fetched_at: Option<Instant>,
+    /// When the last fetch was attempted, whatever its outcome
+    attempted_at: Option<Instant>,
+}
+
+impl std::fmt::Debug for OidcProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OidcProvider")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OidcProvider {
+    /// Create a provider fetching the JWKS through `fetch`
+    pub fn new(config: OidcConfig, fetch: JwksFetchFn) -> Self {
+        Self {
+            config,
+            fetch,
+            jwks: RwLock::new(JwksCache::default()),
+        }
+    }
+
+    /// Find the key for a token, refetching the JWKS once if it's stale or lacks the key
+    ///
+    /// Fetches are at most one per `jwks_min_refresh`. A failed fetch, or a
+    /// document without usable keys, leaves the cached keys in place.
+    fn key_for(&self, header: &Value) -> Result<Value> {
+        let no_match = || rejected(format!("no JWKS key matches kid {}", header["kid"]));
+        let cached = self.jwks.read().map_err(|_| CRAError::StorageLocked)?.clone();
+        let fresh = cached.fetched_at.is_some_and(|at| at.elapsed() < self.config.jwks_ttl);
+        if fresh {
+            if let Some(key) = select_key(&cached.keys, header) {
+                return Ok(key);
+            }
+        }
+
+        // Checked under the write lock so concurrent misses fetch only once
+        let mut cache = self.jwks.write().map_err(|_| CRAError::StorageLocked)?;
+        if cache.attempted_at.is_some_and(|at| at.elapsed() < self.config.jwks_min_refresh) {
+            return select_key(&cache.keys, header).ok_or_else(no_match);
+        }
+        cache.attempted_at = Some(Instant::now());
+        let document = (self.fetch)(&self.config.jwks_uri)
+            .map_err(|e| rejected(format!("fetching JWKS from {} failed: {}", self.config.jwks_uri, e)))?;
+        let keys: Vec<Value> = document["keys"]
+            .as_array()
+            .map(|keys| keys.iter().filter(|key| key["kty"].is_string()).cloned().collect())
+            .unwrap_or_default();
+        if !keys.is_empty() {
+            cache.keys = keys;
+            cache.fetched_at = Some(Instant::now());
+        }
+        select_key(&cache.keys, header).ok_or_else(no_match)
+    }
+}
+
+impl IdentityProvider for OidcProvider {
+    fn authenticate(&self, credential: &str, now: DateTime<Utc>) -> Result<Principal> {
+        let mut parts = credential.split('.');
+        let (Some(header_b64), Some(claims_b64), Some(signature_b64), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(rejected("token is not a JWT"));
+        };
+        let header: Value = decode_json(header_b64)?;
+        let claims: Value = decode_json(claims_b64)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| rejected("token signature is not base64url"))?;
+
+        let alg = header["alg"].as_str().unwrap_or_default();
+        let key = self.key_for(&header)?;
+        let signed = &credential[..header_b64.len() + 1 + claims_b64.len()];
+        verify_signature(alg, &key, signed.as_bytes(), &signature)?;
+
+        let config = &self.config;
+        if claims["iss"].as_str() != Some(config.issuer.as_str()) {
+            return Err(rejected(format!("issuer {} is not {}", claims["iss"], config.issuer)));
+        }
+        let audience_ok = match &claims["aud"] {
+            Value::String(aud) => *aud == config.audience,
+            Value::Array(auds) => auds.iter().any(|a| a.as_str() == Some(config.audience.as_str())),
+            _ => false,
+        };
+        if !audience_ok {
+            return Err(rejected(format!("audience {} does not include {}", claims["aud"], config.audience)));
+        }
+        let leeway = i64::try_from(config.leeway.as_secs()).unwrap_or(i64::MAX);
+        let exp = claims["exp"].as_i64().ok_or_else(|| rejected("token has no exp claim"))?;
+        if now.timestamp() > exp.saturating_add(leeway) {
+            return Err(rejected("token has expired"));
+        }
+        if claims["nbf"].as_i64().is_some_and(|nbf| now.timestamp().saturating_add(leeway) < nbf) {
+            return Err(rejected("token is not valid yet"));
+        }
+
+        let subject = claims["sub"].as_str().ok_or_else(|| rejected("token has no sub claim"))?;
+        let agent_id = claim(&claims, &config.claims.agent_id)
+            .and_then(Value::as_str)
+            .ok_or_else(|| rejected(format!("token has no {} claim", config.claims.agent_id)))?;
+        let roles = match claim(&claims, &config.claims.roles) {
+            Some(Value::Array(roles)) => roles.iter().filter_map(Value::as_str).map(String::from).collect(),
+            Some(Value::String(roles)) => roles.split_whitespace().map(String::from).collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Principal {
+            provider: self.name().to_string(),
+            subject: subject.to_string(),
+            agent_id: agent_id.to_string(),
+            issuer: Some(config.issuer.clone()),
+            tenant: config
+                .claims
+                .tenant
+                .as_deref()
+                .and_then(|path| claim(&claims, path))
+                .and_then(Value::as_str)
+                .map(String::from),
+            roles,
+            expires_at: DateTime::from_timestamp(exp, 0),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "oidc"
+    }
+}
+
+fn decode_json(part: &str) -> Result<Value> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| rejected("token segment is not base64url"))?;
+    serde_json::from_slice(&bytes).map_err(|_| rejected("token segment is not JSON"))
+}
+
+/// Look up a dotted claim path
+fn claim<'a>(claims: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(claims, |value, segment| value.get(segment))
+}
+
+/// Key matching the token's `kid`, or the only key if the token has none
+fn select_key(keys: &[Value], header: &Value) -> Option<Value> {
+    match header["kid"].as_str() {
+        Some(kid) => keys.iter().find(|k| k["kid"].as_str() == Some(kid)).cloned(),
+        None if keys.len() == 1 => keys.first().cloned(),
+        None => None,
+    }
+}
+
+fn key_bytes(key: &Value, field: &str) -> Result<Vec<u8>> {
+    key[field]
+        .as_str()
+        .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+        .ok_or_else(|| rejected(format!("JWKS key has no valid '{}'", field)))
+}
+
+/// Check a JWT signature with a JWK
+fn verify_signature(alg: &str, key: &Value, signed: &[u8], signature: &[u8]) -> Result<()> {
+    if key["alg"].as_str().is_some_and(|key_alg| key_alg != alg) {
+        return Err(rejected(format!("token alg {} does not match key alg {}", alg, key["alg"])));
+    }
+    let invalid = || rejected("token signature is invalid");
+    match (alg, key["kty"].as_str()) {
+        ("HS256", Some("oct")) => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(&key_bytes(key, "k")?).map_err(|_| invalid())?;
+            mac.update(signed);
+            mac.verify_slice(signature).map_err(|_| invalid())
+        }
+        #[cfg(feature = "signing")]
+        ("EdDSA", Some("OKP")) if key["crv"] == "Ed25519" => {
+            use ed25519_dalek::{Signature, VerifyingKey};
+
+            let x: [u8; 32] = key_bytes(key, "x")?.try_into().map_err(|_| invalid())?;
+            let verifying_key = VerifyingKey::from_bytes(&x).map_err(|_| invalid())?;
+            let signature = Signature::from_slice(signature).map_err(|_| invalid())?;
+            verifying_key.verify_strict(signed, &signature).map_err(|_| invalid())
+        }
+        #[cfg(feature = "oidc")]
+        ("RS256", Some("RSA")) => {
+            use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+
+            let n = BigUint::from_bytes_be(&key_bytes(key, "n")?);
+            let e = BigUint::from_bytes_be(&key_bytes(key, "e")?);
+            let public_key = RsaPublicKey::new(n, e).map_err(|_| invalid())?;
+            public_key
+                .verify(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(signed), signature)
+                .map_err(|_| invalid())
+        }
+        (alg, kty) => Err(rejected(format!(
+            "unsupported token algorithm {} for key type {}",
+            alg,
+            kty.unwrap_or("(none)")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"shared-secret-for-tests";
+
+    fn hs256(claims: Value) -> String {
+        hs256_with_kid("k1", claims)
+    }
+
+    fn hs256_with_kid(kid: &str, claims: Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "HS256", "kid": kid}).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(format!("{}.{}", header, claims).as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{}.{}.{}", header, claims, signature)
+    }
+
+    fn provider(fetches: Arc<std::sync::atomic::AtomicUsize>) -> OidcProvider {
+        let config = OidcConfig::new("https://sso.example.com", "cra").with_claim_mapping(ClaimMapping {
+            agent_id: "agent".to_string(),
+            tenant: Some("org.id".to_string()),
+            roles: "scope".to_string(),
+        });
+        let fetch: JwksFetchFn = Arc::new(move |uri: &str| {
+            assert_eq!(uri, "https://sso.example.com/.well-known/jwks.json");
+            fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(json!({"keys": [{"kty": "oct", "kid": "k1", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode(SECRET)}]}))
+        });
+        OidcProvider::new(config, fetch)
+    }
+
+    #[test]
+    fn test_oidc_token_validation() {
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let provider = provider(fetches.clone());
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let claims = json!({
+            "iss": "https://sso.example.com",
+            "aud": ["cra", "other"],
+            "sub": "svc-42",
+            "agent": "billing-agent",
+            "org": {"id": "acme"},
+            "scope": "reader operator",
+            "exp": 1_700_000_600,
+        });
+
+        let principal = provider.authenticate(&hs256(claims.clone()), now).unwrap();
+        assert_eq!(principal.agent_id, "billing-agent");
+        assert_eq!(principal.subject, "svc-42");
+        assert_eq!(principal.tenant.as_deref(), Some("acme"));
+        assert!(principal.has_role("operator"));
+
+        // The JWKS is cached between tokens
+        provider.authenticate(&hs256(claims.clone()), now).unwrap();
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let later = DateTime::from_timestamp(1_700_001_000, 0).unwrap();
+        assert!(provider.authenticate(&hs256(claims.clone()), later).is_err());
+        let mut wrong_audience = claims.clone();
+        wrong_audience["aud"] = json!("someone-else");
+        assert!(provider.authenticate(&hs256(wrong_audience), now).is_err());
+
+        // Changing the claims breaks the signature
+        let token = hs256(claims.clone());
+        let mut forged = claims;
+        forged["agent"] = json!("admin-agent");
+        let forged_claims = URL_SAFE_NO_PAD.encode(forged.to_string());
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged_token = format!("{}.{}.{}", parts[0], forged_claims, parts[2]);
+        assert!(matches!(
+            provider.authenticate(&forged_token, now),
+            Err(CRAError::AuthenticationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_oidc_exp_does_not_overflow() {
+        let provider = provider(Arc::new(std::sync::atomic::AtomicUsize::new(0)));
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let claims = json!({
+            "iss": "https://sso.example.com",
+            "aud": "cra",
+            "sub": "svc-42",
+            "agent": "billing-agent",
+            "exp": i64::MAX,
+            "nbf": i64::MAX,
+        });
+        assert!(matches!(
+            provider.authenticate(&hs256(claims), now),
+            Err(CRAError::AuthenticationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_oidc_jwks_refetch_is_rate_limited() {
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = fetches.clone();
+        let fetch: JwksFetchFn = Arc::new(move |_: &str| {
+            // Only the first fetch returns keys; later ones fail or come back empty
+            match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 => Ok(json!({"keys": [{"kty": "oct", "kid": "k1", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode(SECRET)}]})),
+                1 => Ok(json!({"keys": []})),
+                _ => Err("issuer unavailable".to_string()),
+            }
+        });
+        let config = OidcConfig::new("https://sso.example.com", "cra").with_jwks_min_refresh(Duration::from_secs(3600));
+        let provider = OidcProvider::new(config, fetch);
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let claims = json!({"iss": "https://sso.example.com", "aud": "cra", "sub": "svc-42", "exp": 1_700_000_600});
+
+        provider.authenticate(&hs256(claims.clone()), now).unwrap();
+        for _ in 0..5 {
+            assert!(provider.authenticate(&hs256_with_kid("unknown", claims.clone()), now).is_err());
+        }
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Neither an empty nor a failed fetch drops the cached keys
+        let provider = OidcProvider {
+            config: provider.config.clone().with_jwks_min_refresh(Duration::ZERO),
+            ..provider
+        };
+        assert!(provider.authenticate(&hs256_with_kid("unknown", claims.clone()), now).is_err());
+        assert!(provider.authenticate(&hs256_with_kid("unknown", claims.clone()), now).is_err());
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 3);
+        provider.authenticate(&hs256(claims), now).unwrap();
+    }
+
+    #[cfg(all(feature = "oidc", feature = "signing"))]
+    #[test]
+    fn test_rs256_token() {
+        use rsa::traits::PublicKeyParts;
+        use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+
+        let private_key = RsaPrivateKey::new(&mut rand_core::OsRng, 1024).unwrap();
+        let jwk = json!({
+            "kty": "RSA",
+            "kid": "rsa-1",
+            "n": URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be()),
+            "e": URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be()),
+        });
+        let fetch: JwksFetchFn = Arc::new(move |_: &str| Ok(json!({ "keys": [jwk.clone()] })));
+        let provider = OidcProvider::new(OidcConfig::new("https://sso.example.com", "cra"), fetch);
+
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "RS256", "kid": "rsa-1"}).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(
+            json!({"iss": "https://sso.example.com", "aud": "cra", "sub": "agent-7", "exp": 1_700_000_600}).to_string(),
+        );
+        let signed = format!("{}.{}", header, claims);
+        let signature = private_key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(signed.as_bytes()))
+            .unwrap();
+        let token = format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature));
+
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(provider.authenticate(&token, now).unwrap().agent_id, "agent-7");
+
+        // A token claiming HS256 can't be verified with the RSA key
+        let downgraded = format!("{}.{}.{}", URL_SAFE_NO_PAD.encode(json!({"alg": "HS256", "kid": "rsa-1"}).to_string()), claims, "AAAA");
+        assert!(provider.authenticate(&downgraded, now).is_err());
+    }
+
+    #[test]
+    fn test_api_key_provider() {
+        let provider = ApiKeyProvider::new().with_key("k-123", Principal::new("api_key", "ci").with_roles(vec!["deployer".to_string()]));
+        assert!(provider.authenticate("k-123", Utc::now()).unwrap().has_role("deployer"));
+        assert!(provider.authenticate("k-124", Utc::now()).is_err());
+    }
+}
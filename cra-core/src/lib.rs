@@ -67,6 +67,19 @@ pub mod error;
 pub mod storage;
 pub mod timing;
 pub mod cache;
+pub mod clock;
+pub mod id;
+pub mod identity;
+pub mod listing;
+pub mod metrics;
+pub mod prelude;
+pub mod request_schema;
+#[cfg(feature = "signing")]
+pub mod crypto;
+#[cfg(not(feature = "minimal"))]
+pub mod notify;
+#[cfg(not(feature = "minimal"))]
+pub mod replication;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
@@ -74,39 +87,72 @@ pub mod ffi;
 #[cfg(feature = "async-runtime")]
 pub mod runtime;
 
+#[cfg(feature = "chaos")]
+pub mod chaos;
+
 // Re-export main types
 pub use carp::{
     CARPRequest, CARPResolution, Decision, AllowedAction, DeniedAction,
-    Constraint, Resolver, RiskTier, ContextBlock,
+    Constraint, Resolver, ResolverObserver, RiskTier, ContextBlock, SessionReport, SessionStateSnapshot, CapabilityToken,
     // Checkpoint system
     CheckpointType, CheckpointMode, CheckpointEvaluator, StewardCheckpointDef,
     CheckpointTrigger, CheckpointQuestion, GuidanceBlock, CheckpointValidator,
+    // Human approvals
+    ApprovalManager, ApprovalDecision, ApprovalStatus, EscalationChannel, WebhookChannel, SlackChannel,
+    // Garbage collection
+    GcPolicy, GcStats,
+    // Bootstrap
+    BootstrapRequest, BootstrapResponse, BOOTSTRAP_PROTOCOL_VERSION,
 };
 pub use context::{
     ContextRegistry, LoadedContext, ContextSource, ContextMatcher,
 };
 pub use trace::{
     TRACEEvent, EventType, TraceCollector, ChainVerification, ReplayResult,
-    RawEvent, TraceRingBuffer, BufferStats, TraceProcessor, ProcessorConfig, ProcessorHandle,
-    DeferredConfig, AsyncTraceQueue, AsyncQueueConfig, QueueStats,
+    RawEvent, TraceRingBuffer, BufferStats, DeferredConfig, TraceExport, TraceVerbosity, GraphFormat,
+    CORRELATION_HEADER,
+};
+#[cfg(not(feature = "minimal"))]
+pub use trace::{
+    TraceProcessor, ProcessorConfig, ProcessorHandle,
+    AsyncTraceQueue, AsyncQueueConfig, QueueStats,
 };
 pub use atlas::{
     AtlasManifest, AtlasAction, AtlasPolicy, AtlasCapability, PolicyType,
-    AtlasLoader,
+    AtlasLoader, ContentPolicy,
     // Steward config
     StewardConfig, AccessConfig, AccessType, DeliveryConfig, DeliveryMode,
     NotificationConfig, NotificationTrigger, MarketplaceConfig,
 };
-pub use error::{CRAError, Result, ErrorCategory, ErrorResponse, ErrorDetail};
-pub use storage::{StorageBackend, InMemoryStorage, FileStorage, NullStorage};
+pub use error::{CRAError, Result, ErrorCategory, ErrorResponse, ErrorDetail, FieldError};
+pub use storage::{StorageBackend, InMemoryStorage, NullStorage, SessionStore, InMemorySessionStore};
+#[cfg(not(feature = "minimal"))]
+pub use storage::{FileStorage, FileStorageConfig, SegmentLogStorage, SegmentLogConfig, FsyncPolicy, TieredStorage, TieredConfig, StorageConfig, StorageFactory};
+#[cfg(feature = "object-storage")]
+pub use storage::{ObjectStorage, ObjectStore, ArchiveConfig};
+#[cfg(feature = "encryption")]
+pub use storage::{EncryptedStorage, KeyProvider, StaticKeyProvider};
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosInjector, ChaosStorage, FaultPoint};
 pub use timing::{
     TimerEvent, TimerCallback, TimerBackend,
     HeartbeatConfig, SessionTTLConfig,
     SlidingWindowRateLimiter, RateLimitResult,
     TraceBatcher, HeartbeatMetrics,
     TimerManager, TimerHandler, NullTimerHandler,
-    MockTimerBackend, StdTimerBackend,
+    MockTimerBackend,
 };
+#[cfg(not(feature = "minimal"))]
+pub use timing::StdTimerBackend;
+#[cfg(not(feature = "minimal"))]
+pub use notify::{WebhookNotifier, WebhookSubscription, WebhookConfig, NotificationClass};
+#[cfg(not(feature = "minimal"))]
+pub use replication::{ReadReplica, ReplicationBatch, ReplicationCursor, ReplicaRead, Staleness};
+pub use clock::{Clock, FixedClock};
+pub use id::{IdGen, SequentialIdGen};
+pub use identity::{ApiKeyProvider, IdentityProvider, OidcConfig, OidcProvider, Principal};
+pub use listing::{ListParams, ListPage};
+pub use metrics::{MetricsSink, InMemoryMetrics, MetricsSnapshot};
 pub use cache::{
     CRACache, ContextCache, PolicyCache, CachedContext, CachedPolicy,
     ContextCacheConfig, PolicyCacheConfig, CacheCombinedStats,
@@ -0,0 +1,339 @@
+//! Pagination, sorting and projection for list endpoints
+//!
+//! Every list endpoint (sessions, atlases, approvals, traces) takes the
+//! same [`ListParams`] and answers with the same [`ListPage`] envelope, so
+//! servers parse the query string once and hand any serializable items to
+//! [`ListParams::paginate`].
+//!
+//! - `page` / `per_page`: 1-based page number and page size
+//! - `cursor`: the previous page's `next_cursor`; overrides `page`
+//! - `sort`: comma-separated fields, `-` prefix for descending
+//! - `fields`: comma-separated fields to keep in each item
+//!
+//! Fields may name nested values with dots (`request.action_id`).
+//!
+//! ```rust
+//! use cra_core::listing::ListParams;
+//! use serde_json::json;
+//!
+//! let items = vec![json!({"id": "a", "n": 2}), json!({"id": "b", "n": 1})];
+//! let params = ListParams::new().sort("-n").fields("id").per_page(1);
+//! let page = params.paginate(items).unwrap();
+//! assert_eq!(page.items, vec![json!({"id": "a"})]);
+//! assert_eq!(page.total, 2);
+//! assert_eq!(page.next_cursor.as_deref(), Some("1"));
+//! ```
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{CRAError, Result};
+
+/// Page size when a request doesn't set one
+pub const DEFAULT_PER_PAGE: usize = 50;
+
+/// Largest page size a request may ask for
+pub const MAX_PER_PAGE: usize = 500;
+
+/// Query parameters shared by list endpoints
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListParams {
+    /// 1-based page number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    /// Items per page (default [`DEFAULT_PER_PAGE`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<usize>,
+    /// Sort fields, e.g. `-created_at,session_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    /// Fields to return, e.g. `session_id,agent_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<String>,
+    /// `next_cursor` from the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// One page of a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListPage {
+    /// Items on this page, projected to the requested fields
+    pub items: Vec<Value>,
+    /// Items across all pages
+    pub total: usize,
+    /// Cursor for the next page, if there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// One field of a `sort` parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    /// Dotted field path
+    pub field: String,
+    /// Largest first
+    pub descending: bool,
+}
+
+impl ListParams {
+    /// Create parameters for the first page with default size
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the page number
+    pub fn page(mut self, page: usize) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// Set the page size
+    pub fn per_page(mut self, per_page: usize) -> Self {
+        self.per_page = Some(per_page);
+        self
+    }
+
+    /// Set the sort fields
+    pub fn sort(mut self, sort: impl Into<String>) -> Self {
+        self.sort = Some(sort.into());
+        self
+    }
+
+    /// Set the fields to return
+    pub fn fields(mut self, fields: impl Into<String>) -> Self {
+        self.fields = Some(fields.into());
+        self
+    }
+
+    /// Continue from a previous page's `next_cursor`
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Page size, defaulted
+    pub fn page_size(&self) -> usize {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE)
+    }
+
+    /// Index of the first item on the requested page
+    pub fn offset(&self) -> Result<usize> {
+        if let Some(cursor) = &self.cursor {
+            return cursor.parse().map_err(|_| invalid(format!("malformed cursor '{}'", cursor)));
+        }
+        match self.page {
+            Some(0) => Err(invalid("page starts at 1".to_string())),
+            page => Ok((page.unwrap_or(1) - 1).saturating_mul(self.page_size())),
+        }
+    }
+
+    /// Parsed `sort` parameter
+    pub fn sort_keys(&self) -> Result<Vec<SortKey>> {
+        split_list(self.sort.as_deref())
+            .map(|field| {
+                let (field, descending) = match field.strip_prefix('-') {
+                    Some(field) => (field, true),
+                    None => (field.strip_prefix('+').unwrap_or(field), false),
+                };
+                check_field(field, "sort")?;
+                Ok(SortKey {
+                    field: field.to_string(),
+                    descending,
+                })
+            })
+            .collect()
+    }
+
+    /// Parsed `fields` parameter (empty means every field)
+    pub fn field_list(&self) -> Result<Vec<&str>> {
+        split_list(self.fields.as_deref())
+            .map(|field| check_field(field, "fields").map(|_| field))
+            .collect()
+    }
+
+    /// Check the parameters without listing anything
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_PER_PAGE).contains(&self.page_size()) {
+            return Err(invalid(format!("per_page must be between 1 and {}", MAX_PER_PAGE)));
+        }
+        self.offset()?;
+        self.sort_keys()?;
+        self.field_list()?;
+        Ok(())
+    }
+
+    /// Sort, page and project `items`
+    ///
+    /// Items are serialized to JSON first. Items that compare equal keep
+    /// their input order, so pass them in a stable order. Missing and null
+    /// sort values sort last.
+    pub fn paginate<T: Serialize>(&self, items: impl IntoIterator<Item = T>) -> Result<ListPage> {
+        self.validate()?;
+        let mut items = items
+            .into_iter()
+            .map(|item| serde_json::to_value(item))
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+
+        let sort_keys = self.sort_keys()?;
+        if !sort_keys.is_empty() {
+            items.sort_by(|a, b| {
+                sort_keys
+                    .iter()
+                    .map(|key| compare(lookup(a, &key.field), lookup(b, &key.field), key.descending))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+        }
+
+        let total = items.len();
+        let offset = self.offset()?;
+        let end = offset.saturating_add(self.page_size()).min(total);
+        let fields = self.field_list()?;
+        let items = items
+            .into_iter()
+            .skip(offset)
+            .take(end.saturating_sub(offset))
+            .map(|item| project(item, &fields))
+            .collect();
+
+        Ok(ListPage {
+            items,
+            total,
+            next_cursor: (end < total).then(|| end.to_string()),
+        })
+    }
+}
+
+fn invalid(reason: String) -> CRAError {
+    CRAError::InvalidQuery { reason }
+}
+
+fn split_list(list: Option<&str>) -> impl Iterator<Item = &str> {
+    list.unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn check_field(field: &str, param: &str) -> Result<()> {
+    if field.split('.').any(str::is_empty) {
+        return Err(invalid(format!("malformed field '{}' in {}", field, param)));
+    }
+    Ok(())
+}
+
+/// Value at a dotted path
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, name| value.get(name))
+}
+
+/// Order two sort values; missing and null sort last in either direction
+fn compare(a: Option<&Value>, b: Option<&Value>, descending: bool) -> Ordering {
+    let (x, y) = match (a.filter(|v| !v.is_null()), b.filter(|v| !v.is_null())) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(x), Some(y)) => (x, y),
+    };
+    let ordering = match (x, y) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()).unwrap_or(Ordering::Equal),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (x, y) => x.to_string().cmp(&y.to_string()),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+/// Keep only `fields` of an object item
+fn project(item: Value, fields: &[&str]) -> Value {
+    if fields.is_empty() || !item.is_object() {
+        return item;
+    }
+    let mut projected = Value::Object(Map::new());
+    for field in fields {
+        let Some(value) = lookup(&item, field) else { continue };
+        let mut target = &mut projected;
+        let mut names = field.split('.').peekable();
+        while let Some(name) = names.next() {
+            let Value::Object(map) = target else { break };
+            if names.peek().is_none() {
+                map.insert(name.to_string(), value.clone());
+                break;
+            }
+            target = map.entry(name.to_string()).or_insert_with(|| Value::Object(Map::new()));
+        }
+    }
+    projected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sessions() -> Vec<Value> {
+        vec![
+            json!({"session_id": "s1", "agent": {"id": "b"}, "count": 3}),
+            json!({"session_id": "s2", "agent": {"id": "a"}, "count": 10}),
+            json!({"session_id": "s3", "agent": {"id": "a"}}),
+            json!({"session_id": "s4", "agent": {"id": "c"}, "count": 1}),
+        ]
+    }
+
+    #[test]
+    fn test_sort_page_and_project() {
+        let params = ListParams::new().sort("agent.id,-count").fields("session_id,agent.id").per_page(3);
+        let page = params.paginate(sessions()).unwrap();
+        assert_eq!(
+            page.items,
+            vec![
+                json!({"session_id": "s2", "agent": {"id": "a"}}),
+                json!({"session_id": "s3", "agent": {"id": "a"}}),
+                json!({"session_id": "s1", "agent": {"id": "b"}}),
+            ]
+        );
+        assert_eq!((page.total, page.next_cursor.as_deref()), (4, Some("3")));
+
+        let next = params.clone().cursor("3").paginate(sessions()).unwrap();
+        assert_eq!(next.items, vec![json!({"session_id": "s4", "agent": {"id": "c"}})]);
+        assert_eq!(next.next_cursor, None);
+        assert_eq!(params.page(2).paginate(sessions()).unwrap(), next);
+
+        // Missing values sort last in both directions
+        let ids = |sort: &str| -> Vec<Value> {
+            ListParams::new()
+                .sort(sort)
+                .paginate(sessions())
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|s| s["session_id"].clone())
+                .collect()
+        };
+        assert_eq!(ids("count"), vec!["s4", "s1", "s2", "s3"]);
+        assert_eq!(ids("-count"), vec!["s2", "s1", "s4", "s3"]);
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        for params in [
+            ListParams::new().per_page(0),
+            ListParams::new().per_page(MAX_PER_PAGE + 1),
+            ListParams::new().page(0),
+            ListParams::new().cursor("abc"),
+            ListParams::new().sort("agent..id"),
+            ListParams::new().fields("agent."),
+        ] {
+            let err = params.paginate(sessions()).unwrap_err();
+            assert_eq!(err.error_code(), "INVALID_QUERY", "{:?}", params);
+        }
+
+        let params: ListParams = serde_json::from_value(json!({"page": 2, "per_page": 10, "sort": "-count"})).unwrap();
+        assert_eq!(params.offset().unwrap(), 10);
+    }
+}
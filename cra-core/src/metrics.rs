@@ -0,0 +1,447 @@
+//! Metrics
+//!
+//! The resolver and trace collector report what they do to a [`MetricsSink`]:
+//! counters for decisions and outcomes, and histograms for latencies. The
+//! default sink, [`InMemoryMetrics`], aggregates in process and is read with
+//! `Resolver::metrics_snapshot()`; [`MetricsSnapshot::to_prometheus`] renders
+//! it in the Prometheus text format for servers to expose.
+//!
+//! Histograms use log-linear buckets in the style of HDR histograms: each
+//! power of two is split into [`SUB_BUCKETS`] equal buckets, so recorded
+//! values keep about 12.5% relative precision whatever their magnitude.
+//!
+//! ## Metrics
+//!
+//! | Name | Type | Labels |
+//! |------|------|--------|
+//! | `cra_resolutions_total` | counter | `decision` |
+//! | `cra_resolve_errors_total` | counter | `code` |
+//! | `cra_resolve_duration_us` | histogram | |
+//! | `cra_policy_decisions_total` | counter | `result` |
+//! | `cra_executions_total` | counter | `outcome` |
+//! | `cra_execute_duration_us` | histogram | |
+//! | `cra_trace_events_total` | counter | `event_type` |
+//! | `cra_trace_emit_duration_us` | histogram | |
+//! | `cra_integrity_violations_total` | counter | `detected_by` |
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// Resolutions completed, by decision
+pub const RESOLUTIONS_TOTAL: &str = "cra_resolutions_total";
+/// Resolutions that failed, by error code
+pub const RESOLVE_ERRORS_TOTAL: &str = "cra_resolve_errors_total";
+/// Time spent resolving, in microseconds
+pub const RESOLVE_DURATION_US: &str = "cra_resolve_duration_us";
+/// Per-action policy results during resolution
+pub const POLICY_DECISIONS_TOTAL: &str = "cra_policy_decisions_total";
+/// Execute calls, by outcome (`executed` or a lowercase error code)
+pub const EXECUTIONS_TOTAL: &str = "cra_executions_total";
+/// Time spent in execute, in microseconds
+pub const EXECUTE_DURATION_US: &str = "cra_execute_duration_us";
+/// TRACE events emitted, by event type
+pub const TRACE_EVENTS_TOTAL: &str = "cra_trace_events_total";
+/// Time spent emitting a TRACE event, in microseconds
+pub const TRACE_EMIT_DURATION_US: &str = "cra_trace_emit_duration_us";
+/// TRACE chains found broken, by what found them (`flush` or `sweep`)
+pub const INTEGRITY_VIOLATIONS_TOTAL: &str = "cra_integrity_violations_total";
+
+/// Receives counter increments and histogram observations
+pub trait MetricsSink: Send + Sync + std::fmt::Debug {
+    /// Add `by` to a counter
+    fn increment(&self, name: &str, labels: &[(&str, &str)], by: u64);
+
+    /// Record a value in a histogram
+    fn observe(&self, name: &str, labels: &[(&str, &str)], value: u64);
+
+    /// Current aggregated values
+    ///
+    /// Sinks that forward metrics elsewhere keep the default, which is empty.
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::default()
+    }
+}
+
+/// Sink that discards everything
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {
+    fn increment(&self, _name: &str, _labels: &[(&str, &str)], _by: u64) {}
+
+    fn observe(&self, _name: &str, _labels: &[(&str, &str)], _value: u64) {}
+}
+
+/// Sub-buckets per power of two, as a power of two
+const PRECISION_BITS: u32 = 3;
+
+/// Buckets each power of two is split into
+pub const SUB_BUCKETS: u64 = 1 << PRECISION_BITS;
+
+/// Histogram with log-linear buckets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    counts: BTreeMap<u32, u64>,
+    count: u64,
+    sum: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Histogram {
+    /// Create an empty histogram
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a value
+    pub fn record(&mut self, value: u64) {
+        *self.counts.entry(bucket_index(value)).or_insert(0) += 1;
+        self.min = if self.count == 0 { value } else { self.min.min(value) };
+        self.max = self.max.max(value);
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+    }
+
+    /// Number of recorded values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sum of recorded values
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Smallest recorded value (0 when empty)
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    /// Largest recorded value (0 when empty)
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Value at a quantile between 0 and 1
+    ///
+    /// Returns the upper bound of the bucket holding the quantile, capped at
+    /// the largest recorded value.
+    pub fn value_at_quantile(&self, quantile: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (&index, &count) in &self.counts {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper(index).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// Non-empty buckets as (inclusive upper bound, cumulative count)
+    pub fn buckets(&self) -> Vec<(u64, u64)> {
+        let mut cumulative = 0;
+        self.counts
+            .iter()
+            .map(|(&index, &count)| {
+                cumulative += count;
+                (bucket_upper(index), cumulative)
+            })
+            .collect()
+    }
+}
+
+fn bucket_index(value: u64) -> u32 {
+    if value < SUB_BUCKETS {
+        return value as u32;
+    }
+    let exponent = 63 - value.leading_zeros();
+    let shift = exponent - PRECISION_BITS;
+    let sub = (value >> shift) - SUB_BUCKETS;
+    ((exponent - PRECISION_BITS + 1) << PRECISION_BITS) + sub as u32
+}
+
+fn bucket_upper(index: u32) -> u64 {
+    if u64::from(index) < SUB_BUCKETS {
+        return u64::from(index);
+    }
+    let group = index >> PRECISION_BITS;
+    let sub = u64::from(index) & (SUB_BUCKETS - 1);
+    let shift = group - 1;
+    let lower = (SUB_BUCKETS + sub) << shift;
+    lower + ((1u64 << shift) - 1)
+}
+
+type MetricKey = (String, BTreeMap<String, String>);
+
+fn key(name: &str, labels: &[(&str, &str)]) -> MetricKey {
+    (
+        name.to_string(),
+        labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    )
+}
+
+/// Sink that aggregates counters and histograms in memory
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    counters: Mutex<BTreeMap<MetricKey, u64>>,
+    histograms: Mutex<BTreeMap<MetricKey, Histogram>>,
+}
+
+impl InMemoryMetrics {
+    /// Create an empty aggregator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop all aggregated values
+    pub fn reset(&self) {
+        if let Ok(mut counters) = self.counters.lock() {
+            counters.clear();
+        }
+        if let Ok(mut histograms) = self.histograms.lock() {
+            histograms.clear();
+        }
+    }
+}
+
+impl MetricsSink for InMemoryMetrics {
+    fn increment(&self, name: &str, labels: &[(&str, &str)], by: u64) {
+        if let Ok(mut counters) = self.counters.lock() {
+            *counters.entry(key(name, labels)).or_insert(0) += by;
+        }
+    }
+
+    fn observe(&self, name: &str, labels: &[(&str, &str)], value: u64) {
+        if let Ok(mut histograms) = self.histograms.lock() {
+            histograms.entry(key(name, labels)).or_default().record(value);
+        }
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        let counters = self
+            .counters
+            .lock()
+            .map(|counters| {
+                counters
+                    .iter()
+                    .map(|((name, labels), &value)| CounterSample {
+                        name: name.clone(),
+                        labels: labels.clone(),
+                        value,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let histograms = self
+            .histograms
+            .lock()
+            .map(|histograms| {
+                histograms
+                    .iter()
+                    .map(|((name, labels), histogram)| HistogramSample::new(name, labels, histogram))
+                    .collect()
+            })
+            .unwrap_or_default();
+        MetricsSnapshot { counters, histograms }
+    }
+}
+
+/// A counter's value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CounterSample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: u64,
+}
+
+/// A histogram's summary and buckets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistogramSample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub count: u64,
+    pub sum: u64,
+    pub min: u64,
+    pub max: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    /// Non-empty buckets as (inclusive upper bound, cumulative count)
+    pub buckets: Vec<(u64, u64)>,
+}
+
+impl HistogramSample {
+    fn new(name: &str, labels: &BTreeMap<String, String>, histogram: &Histogram) -> Self {
+        Self {
+            name: name.to_string(),
+            labels: labels.clone(),
+            count: histogram.count(),
+            sum: histogram.sum(),
+            min: histogram.min(),
+            max: histogram.max(),
+            p50: histogram.value_at_quantile(0.5),
+            p90: histogram.value_at_quantile(0.9),
+            p99: histogram.value_at_quantile(0.99),
+            buckets: histogram.buckets(),
+        }
+    }
+}
+
+/// Point-in-time copy of aggregated metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Counters, sorted by name and labels
+    pub counters: Vec<CounterSample>,
+    /// Histograms, sorted by name and labels
+    pub histograms: Vec<HistogramSample>,
+}
+
+impl MetricsSnapshot {
+    /// Value of the counter with exactly these labels (0 if never incremented)
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        let (_, labels) = key(name, labels);
+        self.counters
+            .iter()
+            .find(|c| c.name == name && c.labels == labels)
+            .map(|c| c.value)
+            .unwrap_or(0)
+    }
+
+    /// Sum of a counter across all label values
+    pub fn counter_total(&self, name: &str) -> u64 {
+        self.counters.iter().filter(|c| c.name == name).map(|c| c.value).sum()
+    }
+
+    /// The histogram with exactly these labels
+    pub fn histogram(&self, name: &str, labels: &[(&str, &str)]) -> Option<&HistogramSample> {
+        let (_, labels) = key(name, labels);
+        self.histograms.iter().find(|h| h.name == name && h.labels == labels)
+    }
+
+    /// Render in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut last_name = "";
+        for counter in &self.counters {
+            if counter.name != last_name {
+                out.push_str(&format!("# TYPE {} counter\n", counter.name));
+                last_name = &counter.name;
+            }
+            out.push_str(&format!("{}{} {}\n", counter.name, prometheus_labels(&counter.labels, None), counter.value));
+        }
+        for histogram in &self.histograms {
+            if histogram.name != last_name {
+                out.push_str(&format!("# TYPE {} histogram\n", histogram.name));
+                last_name = &histogram.name;
+            }
+            for (upper, cumulative) in &histogram.buckets {
+                out.push_str(&format!(
+                    "{}_bucket{} {}\n",
+                    histogram.name,
+                    prometheus_labels(&histogram.labels, Some(&upper.to_string())),
+                    cumulative
+                ));
+            }
+            out.push_str(&format!(
+                "{}_bucket{} {}\n",
+                histogram.name,
+                prometheus_labels(&histogram.labels, Some("+Inf")),
+                histogram.count
+            ));
+            let labels = prometheus_labels(&histogram.labels, None);
+            out.push_str(&format!("{}_sum{} {}\n", histogram.name, labels, histogram.sum));
+            out.push_str(&format!("{}_count{} {}\n", histogram.name, labels, histogram.count));
+        }
+        out
+    }
+}
+
+fn prometheus_labels(labels: &BTreeMap<String, String>, le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bounds() {
+        for value in [0, 1, 7, 8, 9, 15, 16, 17, 100, 1_000, 123_456, u64::MAX / 3] {
+            let upper = bucket_upper(bucket_index(value));
+            assert!(upper >= value, "{} > {}", value, upper);
+            // Relative error stays within one sub-bucket
+            assert!(upper - value <= value / SUB_BUCKETS, "{} -> {}", value, upper);
+        }
+        assert!(bucket_index(1_000) < bucket_index(1_200));
+    }
+
+    #[test]
+    fn test_histogram_quantiles() {
+        let mut histogram = Histogram::new();
+        for value in 1..=1000 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.sum(), 500_500);
+        assert_eq!(histogram.min(), 1);
+        assert_eq!(histogram.max(), 1000);
+
+        let p50 = histogram.value_at_quantile(0.5);
+        assert!((500..=560).contains(&p50), "{}", p50);
+        let p99 = histogram.value_at_quantile(0.99);
+        assert!((990..=1000).contains(&p99), "{}", p99);
+        assert_eq!(histogram.value_at_quantile(1.0), 1000);
+        assert_eq!(Histogram::new().value_at_quantile(0.5), 0);
+
+        let buckets = histogram.buckets();
+        assert_eq!(buckets.last().unwrap().1, 1000);
+        assert!(buckets.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1));
+    }
+
+    #[test]
+    fn test_in_memory_snapshot_and_prometheus() {
+        let metrics = InMemoryMetrics::new();
+        metrics.increment(RESOLUTIONS_TOTAL, &[("decision", "allow")], 1);
+        metrics.increment(RESOLUTIONS_TOTAL, &[("decision", "allow")], 2);
+        metrics.increment(RESOLUTIONS_TOTAL, &[("decision", "deny")], 1);
+        metrics.observe(RESOLVE_DURATION_US, &[], 5);
+        metrics.observe(RESOLVE_DURATION_US, &[], 40);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.counter(RESOLUTIONS_TOTAL, &[("decision", "allow")]), 3);
+        assert_eq!(snapshot.counter(RESOLUTIONS_TOTAL, &[("decision", "partial")]), 0);
+        assert_eq!(snapshot.counter_total(RESOLUTIONS_TOTAL), 4);
+        let histogram = snapshot.histogram(RESOLVE_DURATION_US, &[]).unwrap();
+        assert_eq!((histogram.count, histogram.sum, histogram.max), (2, 45, 40));
+
+        let text = snapshot.to_prometheus();
+        assert!(text.contains("# TYPE cra_resolutions_total counter\n"));
+        assert!(text.contains("cra_resolutions_total{decision=\"allow\"} 3\n"));
+        assert!(text.contains("# TYPE cra_resolve_duration_us histogram\n"));
+        assert!(text.contains("cra_resolve_duration_us_bucket{le=\"5\"} 1\n"));
+        assert!(text.contains("cra_resolve_duration_us_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("cra_resolve_duration_us_count 2\n"));
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+        assert_eq!(NoopMetrics.snapshot(), MetricsSnapshot::default());
+    }
+}
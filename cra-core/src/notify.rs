@@ -0,0 +1,678 @@
+//! Webhook notifications for governance events
+//!
+//! A [`WebhookNotifier`] pushes selected governance events to subscribed
+//! URLs instead of making operators poll traces:
+//!
+//! - `policy.denied` - an action was denied by policy
+//! - `chain.invalid` - a session's hash chain failed verification
+//! - `approval.pending` - an action is waiting for a human
+//! - `session.ended` - a session ended
+//!
+//! Each delivery is a JSON envelope signed with the subscription's secret
+//! (HMAC-SHA256 over `"{timestamp}.{body}"`, see [`sign_payload`]) and is
+//! retried with exponential backoff on a background thread. The outcome of
+//! every delivery is kept in [`WebhookNotifier::deliveries`].
+//!
+//! As with approval channels, the core has no HTTP client: requests are
+//! handed to an embedder-provided [`SignedPostFn`].
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::error::{CRAError, Result};
+use crate::id::{IdGen, UuidGen};
+use crate::trace::{EventType, TRACEEvent};
+
+/// Header carrying the notification class
+pub const EVENT_HEADER: &str = "X-CRA-Event";
+
+/// Header carrying the delivery ID (stable across retries)
+pub const DELIVERY_HEADER: &str = "X-CRA-Delivery";
+
+/// Header carrying the Unix timestamp included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-CRA-Timestamp";
+
+/// Header carrying `sha256=<hex HMAC>`
+pub const SIGNATURE_HEADER: &str = "X-CRA-Signature";
+
+/// Callback that POSTs a JSON body with headers to a URL
+///
+/// Returns `Err(message)` when delivery fails (including non-2xx responses).
+pub type SignedPostFn =
+    Arc<dyn Fn(&str, &[(String, String)], &str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// Governance events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NotificationClass {
+    /// An action was denied by policy
+    #[serde(rename = "policy.denied")]
+    PolicyDenied,
+    /// A session's hash chain failed verification
+    #[serde(rename = "chain.invalid")]
+    ChainInvalid,
+    /// An action is waiting for human approval
+    #[serde(rename = "approval.pending")]
+    ApprovalPending,
+    /// A session ended
+    #[serde(rename = "session.ended")]
+    SessionEnded,
+}
+
+impl NotificationClass {
+    /// Get the class name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationClass::PolicyDenied => "policy.denied",
+            NotificationClass::ChainInvalid => "chain.invalid",
+            NotificationClass::ApprovalPending => "approval.pending",
+            NotificationClass::SessionEnded => "session.ended",
+        }
+    }
+
+    /// The class a TRACE event notifies as, if any
+    ///
+    /// Chain failures aren't TRACE events; report them with
+    /// [`WebhookNotifier::notify`].
+    pub fn from_event(event: &TRACEEvent) -> Option<Self> {
+        match event.event_type {
+            EventType::ActionDenied => Some(NotificationClass::PolicyDenied),
+            EventType::ApprovalRequested => Some(NotificationClass::ApprovalPending),
+            EventType::SessionEnded => Some(NotificationClass::SessionEnded),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A URL subscribed to some notification classes
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    /// Subscription ID, recorded with each delivery
+    pub id: String,
+    /// URL deliveries are posted to
+    pub url: String,
+    /// Classes delivered to this URL
+    pub classes: Vec<NotificationClass>,
+    /// Shared secret deliveries are signed with
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+}
+
+impl std::fmt::Debug for WebhookSubscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSubscription")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("classes", &self.classes)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl WebhookSubscription {
+    /// Subscribe `url` to `classes`, signing with `secret`
+    pub fn new(
+        id: impl Into<String>,
+        url: impl Into<String>,
+        secret: impl Into<String>,
+        classes: Vec<NotificationClass>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            url: url.into(),
+            classes,
+            secret: secret.into(),
+        }
+    }
+
+    /// Whether this subscription receives `class`
+    pub fn wants(&self, class: NotificationClass) -> bool {
+        self.classes.contains(&class)
+    }
+}
+
+/// Retry and history settings for [`WebhookNotifier`]
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Retries per delivery after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub retry_backoff: Duration,
+    /// Finished deliveries kept for [`WebhookNotifier::deliveries`]
+    pub history: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            retry_backoff: Duration::from_millis(500),
+            history: 1000,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Set the number of retries
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the initial retry backoff
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Set how many finished deliveries are kept
+    pub fn history(mut self, deliveries: usize) -> Self {
+        self.history = deliveries;
+        self
+    }
+}
+
+/// Where a delivery stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    /// Queued or being retried
+    Pending,
+    /// Accepted by the endpoint
+    Delivered,
+    /// Abandoned after exhausting retries
+    Failed,
+}
+
+/// A notification sent (or being sent) to one subscription
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// Delivery ID, sent in [`DELIVERY_HEADER`]
+    pub delivery_id: String,
+    /// Subscription it was sent to
+    pub subscription_id: String,
+    /// Notification class
+    pub class: NotificationClass,
+    /// Session the notification is about
+    pub session_id: String,
+    /// Current state
+    pub state: DeliveryState,
+    /// Attempts made so far
+    pub attempts: u32,
+    /// Error from the last failed attempt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When the notification was raised
+    pub created_at: DateTime<Utc>,
+}
+
+/// Delivery counters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebhookStats {
+    /// Deliveries accepted by their endpoint
+    pub delivered: u64,
+    /// Deliveries abandoned after exhausting retries
+    pub failed: u64,
+    /// Retry attempts made
+    pub retries: u64,
+    /// Deliveries waiting to be sent
+    pub pending: usize,
+}
+
+/// Compute the signature header value for a delivery
+///
+/// `sha256=` followed by the hex HMAC-SHA256 of `"{timestamp}.{body}"`.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = hmac_for(secret);
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check a received signature header in constant time
+pub fn verify_signature(secret: &str, timestamp: i64, body: &str, signature: &str) -> bool {
+    let Some(hex_mac) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_mac) else {
+        return false;
+    };
+    let mut mac = hmac_for(secret);
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn hmac_for(secret: &str) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+#[derive(Debug)]
+struct Job {
+    delivery_id: String,
+    url: String,
+    secret: String,
+    class: NotificationClass,
+    body: String,
+}
+
+#[derive(Debug, Default)]
+struct Deliveries {
+    queue: VecDeque<Job>,
+    records: HashMap<String, WebhookDelivery>,
+    /// Finished delivery IDs, oldest first, for trimming history
+    finished: VecDeque<String>,
+    stats: WebhookStats,
+    shutdown: bool,
+}
+
+type Shared = Arc<(Mutex<Deliveries>, Condvar)>;
+
+/// Delivers governance notifications to subscribed webhooks
+///
+/// Not available with the `minimal` feature.
+pub struct WebhookNotifier {
+    subscriptions: Mutex<Vec<WebhookSubscription>>,
+    config: WebhookConfig,
+    ids: Arc<dyn IdGen>,
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for WebhookNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookNotifier")
+            .field("subscriptions", &self.subscriptions())
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl WebhookNotifier {
+    /// Create a notifier with default configuration
+    pub fn new(post: SignedPostFn) -> Self {
+        Self::with_config(post, WebhookConfig::default())
+    }
+
+    /// Create a notifier and start its delivery thread
+    pub fn with_config(post: SignedPostFn, config: WebhookConfig) -> Self {
+        let shared: Shared = Arc::new((Mutex::new(Deliveries::default()), Condvar::new()));
+        let worker = {
+            let shared = shared.clone();
+            let config = config.clone();
+            thread::spawn(move || deliver_loop(shared, post, config))
+        };
+
+        Self {
+            subscriptions: Mutex::new(Vec::new()),
+            config,
+            ids: Arc::new(UuidGen),
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Use a specific generator for delivery IDs
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGen>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Add a subscription, replacing any with the same ID
+    pub fn subscribe(&self, subscription: WebhookSubscription) {
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            subscriptions.retain(|s| s.id != subscription.id);
+            subscriptions.push(subscription);
+        }
+    }
+
+    /// Remove a subscription; returns false if there was none
+    pub fn unsubscribe(&self, subscription_id: &str) -> bool {
+        self.subscriptions
+            .lock()
+            .map(|mut subscriptions| {
+                let before = subscriptions.len();
+                subscriptions.retain(|s| s.id != subscription_id);
+                subscriptions.len() != before
+            })
+            .unwrap_or(false)
+    }
+
+    /// Current subscriptions
+    pub fn subscriptions(&self) -> Vec<WebhookSubscription> {
+        self.subscriptions.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Notify subscribers of a TRACE event, if it is a governance event
+    pub fn observe(&self, event: &TRACEEvent) -> Result<Vec<String>> {
+        match NotificationClass::from_event(event) {
+            Some(class) => self.notify(
+                class,
+                &event.session_id,
+                json!({
+                    "event_id": event.event_id,
+                    "event_type": event.event_type.as_str(),
+                    "event_hash": event.event_hash,
+                    "payload": event.payload,
+                }),
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Queue a notification to every subscription that wants `class`
+    ///
+    /// Returns the IDs of the queued deliveries.
+    pub fn notify(&self, class: NotificationClass, session_id: &str, data: Value) -> Result<Vec<String>> {
+        let subscriptions: Vec<WebhookSubscription> = self
+            .subscriptions()
+            .into_iter()
+            .filter(|s| s.wants(class))
+            .collect();
+        if subscriptions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let created_at = crate::clock::now();
+        let mut state = self.lock()?;
+        let mut queued = Vec::new();
+        for subscription in subscriptions {
+            let delivery_id = self.ids.next_id();
+            let body = json!({
+                "delivery_id": delivery_id,
+                "type": class.as_str(),
+                "session_id": session_id,
+                "occurred_at": created_at,
+                "data": data,
+            });
+            state.records.insert(
+                delivery_id.clone(),
+                WebhookDelivery {
+                    delivery_id: delivery_id.clone(),
+                    subscription_id: subscription.id.clone(),
+                    class,
+                    session_id: session_id.to_string(),
+                    state: DeliveryState::Pending,
+                    attempts: 0,
+                    last_error: None,
+                    created_at,
+                },
+            );
+            state.queue.push_back(Job {
+                delivery_id: delivery_id.clone(),
+                url: subscription.url,
+                secret: subscription.secret,
+                class,
+                body: body.to_string(),
+            });
+            queued.push(delivery_id);
+        }
+        self.shared.1.notify_all();
+        Ok(queued)
+    }
+
+    /// Status of one delivery, while it is pending or in the history
+    pub fn delivery(&self, delivery_id: &str) -> Option<WebhookDelivery> {
+        self.lock().ok()?.records.get(delivery_id).cloned()
+    }
+
+    /// Pending deliveries and recent finished ones, oldest first
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        let mut deliveries: Vec<_> = self
+            .lock()
+            .map(|state| state.records.values().cloned().collect())
+            .unwrap_or_default();
+        deliveries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.delivery_id.cmp(&b.delivery_id)));
+        deliveries
+    }
+
+    /// Drop successful deliveries from the history, returning them
+    ///
+    /// Failed deliveries are kept for inspection. With `dry_run`, returns
+    /// what would be dropped without dropping it.
+    pub fn purge_delivered(&self, dry_run: bool) -> Vec<WebhookDelivery> {
+        let Ok(mut state) = self.lock() else {
+            return Vec::new();
+        };
+        let delivered: Vec<String> = state
+            .records
+            .values()
+            .filter(|d| d.state == DeliveryState::Delivered)
+            .map(|d| d.delivery_id.clone())
+            .collect();
+        if dry_run {
+            return delivered.iter().filter_map(|id| state.records.get(id).cloned()).collect();
+        }
+        state.finished.retain(|id| !delivered.contains(id));
+        delivered.iter().filter_map(|id| state.records.remove(id)).collect()
+    }
+
+    /// Get delivery counters
+    pub fn stats(&self) -> WebhookStats {
+        self.lock()
+            .map(|state| {
+                let mut stats = state.stats.clone();
+                stats.pending = state
+                    .records
+                    .values()
+                    .filter(|d| d.state == DeliveryState::Pending)
+                    .count();
+                stats
+            })
+            .unwrap_or_default()
+    }
+
+    /// Wait until no delivery is pending
+    ///
+    /// Returns false if the timeout elapsed first.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (lock, condvar) = &*self.shared;
+        let Ok(state) = lock.lock() else {
+            return false;
+        };
+        condvar
+            .wait_timeout_while(state, timeout, |state| {
+                state.records.values().any(|d| d.state == DeliveryState::Pending)
+            })
+            .map(|(_, result)| !result.timed_out())
+            .unwrap_or(false)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Deliveries>> {
+        self.shared.0.lock().map_err(|_| CRAError::StorageLocked)
+    }
+}
+
+impl Drop for WebhookNotifier {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.0.lock() {
+            state.shutdown = true;
+        }
+        self.shared.1.notify_all();
+        // Send whatever is still queued before returning
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn deliver_loop(shared: Shared, post: SignedPostFn, config: WebhookConfig) {
+    let (lock, condvar) = &*shared;
+    loop {
+        let job = {
+            let Ok(mut state) = lock.lock() else {
+                return;
+            };
+            while state.queue.is_empty() && !state.shutdown {
+                state = match condvar.wait(state) {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+            }
+            match state.queue.pop_front() {
+                Some(job) => job,
+                None => return,
+            }
+        };
+
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            let timestamp = crate::clock::now().timestamp();
+            let headers = vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                (EVENT_HEADER.to_string(), job.class.as_str().to_string()),
+                (DELIVERY_HEADER.to_string(), job.delivery_id.clone()),
+                (TIMESTAMP_HEADER.to_string(), timestamp.to_string()),
+                (SIGNATURE_HEADER.to_string(), sign_payload(&job.secret, timestamp, &job.body)),
+            ];
+            let result = post(&job.url, &headers, &job.body);
+            if result.is_ok() || attempts > config.max_retries {
+                break result;
+            }
+            if let Ok(mut state) = lock.lock() {
+                if let Some(record) = state.records.get_mut(&job.delivery_id) {
+                    record.attempts = attempts;
+                    record.last_error = result.err();
+                }
+            }
+            thread::sleep(config.retry_backoff.saturating_mul(2u32.saturating_pow(attempts - 1)));
+        };
+
+        let Ok(mut state) = lock.lock() else {
+            return;
+        };
+        state.stats.retries += u64::from(attempts - 1);
+        if let Some(record) = state.records.get_mut(&job.delivery_id) {
+            record.attempts = attempts;
+            match result {
+                Ok(()) => record.state = DeliveryState::Delivered,
+                Err(e) => {
+                    eprintln!("Error delivering webhook {} to {}: {}", job.delivery_id, job.url, e);
+                    record.state = DeliveryState::Failed;
+                    record.last_error = Some(e);
+                }
+            }
+            match record.state {
+                DeliveryState::Delivered => state.stats.delivered += 1,
+                _ => state.stats.failed += 1,
+            }
+        }
+        state.finished.push_back(job.delivery_id);
+        while state.finished.len() > config.history {
+            if let Some(old) = state.finished.pop_front() {
+                state.records.remove(&old);
+            }
+        }
+        condvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type Sent = Arc<Mutex<Vec<(String, Vec<(String, String)>, String)>>>;
+
+    /// Records posts, failing the first `failures`
+    fn recorder(failures: usize) -> (SignedPostFn, Sent) {
+        let sent: Sent = Arc::default();
+        let remaining = Arc::new(AtomicUsize::new(failures));
+        let post: SignedPostFn = {
+            let sent = sent.clone();
+            Arc::new(move |url: &str, headers: &[(String, String)], body: &str| {
+                if remaining.load(Ordering::SeqCst) > 0 {
+                    remaining.fetch_sub(1, Ordering::SeqCst);
+                    return Err("503 Service Unavailable".to_string());
+                }
+                sent.lock().unwrap().push((url.to_string(), headers.to_vec(), body.to_string()));
+                Ok(())
+            })
+        };
+        (post, sent)
+    }
+
+    fn config() -> WebhookConfig {
+        WebhookConfig::default().max_retries(2).retry_backoff(Duration::from_millis(1))
+    }
+
+    fn header<'a>(headers: &'a [(String, String)], name: &str) -> &'a str {
+        headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str()).unwrap()
+    }
+
+    #[test]
+    fn test_signed_delivery_to_matching_subscriptions() {
+        let (post, sent) = recorder(0);
+        let notifier = WebhookNotifier::with_config(post, config());
+        notifier.subscribe(WebhookSubscription::new(
+            "ops",
+            "https://ops.example/hook",
+            "s3cret",
+            vec![NotificationClass::PolicyDenied],
+        ));
+        notifier.subscribe(WebhookSubscription::new(
+            "audit",
+            "https://audit.example/hook",
+            "other",
+            vec![NotificationClass::SessionEnded],
+        ));
+
+        let denied = TRACEEvent::new("s1".to_string(), "t1".to_string(), EventType::ActionDenied, json!({"action_id": "ticket.delete"}));
+        let ids = notifier.observe(&denied).unwrap();
+        assert_eq!(ids.len(), 1);
+        let started = TRACEEvent::new("s1".to_string(), "t1".to_string(), EventType::SessionStarted, json!({}));
+        assert!(notifier.observe(&started).unwrap().is_empty());
+        assert!(notifier.flush(Duration::from_secs(5)));
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let (url, headers, body) = &sent[0];
+        assert_eq!(url, "https://ops.example/hook");
+        assert_eq!(header(headers, EVENT_HEADER), "policy.denied");
+        let timestamp: i64 = header(headers, TIMESTAMP_HEADER).parse().unwrap();
+        assert!(verify_signature("s3cret", timestamp, body, header(headers, SIGNATURE_HEADER)));
+        assert!(!verify_signature("wrong", timestamp, body, header(headers, SIGNATURE_HEADER)));
+
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["type"], "policy.denied");
+        assert_eq!(body["data"]["payload"]["action_id"], "ticket.delete");
+        assert_eq!(notifier.delivery(&ids[0]).unwrap().state, DeliveryState::Delivered);
+    }
+
+    #[test]
+    fn test_retries_then_tracks_failure() {
+        let (post, sent) = recorder(1);
+        let notifier = WebhookNotifier::with_config(post, config());
+        notifier.subscribe(WebhookSubscription::new("ops", "https://ops.example/hook", "k", vec![NotificationClass::ChainInvalid]));
+
+        let id = notifier.notify(NotificationClass::ChainInvalid, "s1", json!({})).unwrap().remove(0);
+        assert!(notifier.flush(Duration::from_secs(5)));
+        let delivery = notifier.delivery(&id).unwrap();
+        assert_eq!((delivery.state, delivery.attempts), (DeliveryState::Delivered, 2));
+        assert_eq!(sent.lock().unwrap().len(), 1);
+
+        let (post, _) = recorder(usize::MAX);
+        let notifier = WebhookNotifier::with_config(post, config());
+        notifier.subscribe(WebhookSubscription::new("ops", "https://ops.example/hook", "k", vec![NotificationClass::ChainInvalid]));
+        let id = notifier.notify(NotificationClass::ChainInvalid, "s1", json!({})).unwrap().remove(0);
+        assert!(notifier.flush(Duration::from_secs(5)));
+
+        let delivery = notifier.delivery(&id).unwrap();
+        assert_eq!((delivery.state, delivery.attempts), (DeliveryState::Failed, 3));
+        assert_eq!(delivery.last_error.as_deref(), Some("503 Service Unavailable"));
+        assert_eq!(notifier.stats(), WebhookStats { delivered: 0, failed: 1, retries: 2, pending: 0 });
+    }
+}
@@ -0,0 +1,47 @@
+//! Common imports for embedders
+//!
+//! ```rust
+//! use cra_core::prelude::*;
+//!
+//! let atlas = AtlasManifest::builder("com.example.ops".to_string(), "Ops".to_string()).build();
+//! let mut resolver = Resolver::new();
+//! resolver.load_atlas(atlas).unwrap();
+//!
+//! let session_id = resolver.create_session("agent", "Restart the web tier").unwrap();
+//! let request = CARPRequest::new(session_id.clone(), "agent".to_string(), "restart".to_string());
+//! let resolution: CARPResolution = resolver.resolve(&request).unwrap();
+//! assert!(resolution.allowed_actions.is_empty());
+//!
+//! let verification: ChainVerification = resolver.verify_chain(&session_id).unwrap();
+//! assert!(verification.is_valid);
+//! ```
+//!
+//! Types behind a feature are only here when it's enabled.
+
+pub use crate::atlas::{AtlasLoader, AtlasManifest, AtlasManifestBuilder};
+pub use crate::carp::{
+    AllowedAction, CARPRequest, CARPResolution, Decision, DeniedAction, Resolver, ResolverObserver,
+    RiskTier,
+};
+pub use crate::clock::{Clock, FixedClock};
+pub use crate::error::{CRAError, ErrorCategory, Result};
+pub use crate::storage::{InMemoryStorage, NullStorage, StorageBackend};
+pub use crate::timing::{
+    HeartbeatConfig, MockTimerBackend, SessionTTLConfig, TimerBackend, TimerEvent, TimerManager,
+};
+pub use crate::trace::{ChainVerification, EventType, TRACEEvent, TraceCollector};
+
+#[cfg(not(feature = "minimal"))]
+pub use crate::storage::{FileStorage, SegmentLogStorage, StorageFactory, TieredStorage};
+#[cfg(not(feature = "minimal"))]
+pub use crate::timing::StdTimerBackend;
+#[cfg(feature = "object-storage")]
+pub use crate::storage::ObjectStorage;
+#[cfg(feature = "encryption")]
+pub use crate::storage::EncryptedStorage;
+#[cfg(feature = "redb-store")]
+pub use crate::storage::RedbSessionStore;
+#[cfg(feature = "async-runtime")]
+pub use crate::runtime::{AsyncRuntime, AsyncStorageBackend, RuntimeConfig};
+#[cfg(feature = "signing")]
+pub use crate::trace::{SigningMode, TraceSigner};
@@ -0,0 +1,420 @@
+//! Read replicas
+//!
+//! A primary [`Resolver`](crate::Resolver) ships its state to read replicas
+//! so trace reads and chain verification don't load the primary:
+//!
+//! 1. The replica bootstraps from a primary snapshot
+//!    ([`Resolver::snapshot`](crate::Resolver::snapshot)) with
+//!    [`ReadReplica::from_snapshot`].
+//! 2. The primary cuts [`ReplicationBatch`]es of new TRACE events, changed
+//!    sessions and atlas changes since the replica's [`ReplicationCursor`]
+//!    ([`Resolver::replication_batch`](crate::Resolver::replication_batch)),
+//!    and ships them over any transport.
+//! 3. The replica applies batches in order with [`ReadReplica::apply`],
+//!    checking that every chain extends what it already holds.
+//!
+//! Replicas serve traces, chain verification, sessions and atlases. Every
+//! read reports how far behind the primary the replica is ([`Staleness`]);
+//! reads fail with `ReplicaStale` once that exceeds the replica's bound, so
+//! callers can fall back to the primary.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atlas::AtlasManifest;
+use crate::carp::{ResolverState, Session};
+use crate::clock::{Clock, GlobalClock};
+use crate::error::{CRAError, Result};
+use crate::trace::{ChainVerification, ChainVerifier, TRACEEvent, GENESIS_HASH};
+
+/// Default staleness bound for replica reads
+pub const DEFAULT_MAX_STALENESS: Duration = Duration::from_secs(30);
+
+/// Ships a batch to a replica (e.g. an HTTP POST); returns an error message on failure
+pub type ShipBatchFn = Arc<dyn Fn(&ReplicationBatch) -> std::result::Result<(), String> + Send + Sync>;
+
+/// How much of the primary's state a replica holds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationCursor {
+    /// Events held per session
+    pub traces: BTreeMap<String, usize>,
+    /// Version of each atlas held
+    pub atlas_versions: BTreeMap<String, String>,
+}
+
+/// State the primary changed since a cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationBatch {
+    /// Primary time the batch was cut at
+    pub created_at: DateTime<Utc>,
+    /// Cursor the batch applies on top of
+    pub since: ReplicationCursor,
+    /// Cursor after applying the batch
+    pub cursor: ReplicationCursor,
+    /// New events, in sequence order per session
+    pub events: Vec<TRACEEvent>,
+    /// Current records of sessions with new events
+    pub sessions: Vec<Session>,
+    /// Every loaded atlas, if the set changed since `since`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlases: Option<Vec<AtlasManifest>>,
+}
+
+impl ReplicationBatch {
+    /// Whether the batch carries no changes (a heartbeat)
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.sessions.is_empty() && self.atlases.is_none()
+    }
+
+    /// Whether the primary had more changes than fit in the batch
+    pub fn has_more(&self, primary: &ReplicationCursor) -> bool {
+        self.cursor.traces != primary.traces
+    }
+}
+
+/// How far a replica is behind its primary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Staleness {
+    /// Primary time of the last applied batch or snapshot
+    pub synced_at: Option<DateTime<Utc>>,
+    /// Time since `synced_at`
+    pub staleness_ms: u64,
+    /// Bound beyond which reads fail
+    pub max_staleness_ms: u64,
+}
+
+/// A replica read, with the replica's staleness when it was served
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaRead<T> {
+    pub data: T,
+    pub staleness: Staleness,
+}
+
+/// Read-only copy of a primary resolver's traces, sessions and atlases
+pub struct ReadReplica {
+    traces: BTreeMap<String, Vec<TRACEEvent>>,
+    sessions: BTreeMap<String, Session>,
+    atlases: BTreeMap<String, AtlasManifest>,
+    cursor: ReplicationCursor,
+    synced_at: Option<DateTime<Utc>>,
+    max_staleness: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl std::fmt::Debug for ReadReplica {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadReplica")
+            .field("sessions", &self.sessions.len())
+            .field("traces", &self.traces.len())
+            .field("atlases", &self.atlases.len())
+            .field("synced_at", &self.synced_at)
+            .field("max_staleness", &self.max_staleness)
+            .finish()
+    }
+}
+
+impl Default for ReadReplica {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadReplica {
+    /// Create an empty replica; it serves reads once it applies a batch
+    pub fn new() -> Self {
+        Self {
+            traces: BTreeMap::new(),
+            sessions: BTreeMap::new(),
+            atlases: BTreeMap::new(),
+            cursor: ReplicationCursor::default(),
+            synced_at: None,
+            max_staleness: DEFAULT_MAX_STALENESS,
+            clock: Arc::new(GlobalClock),
+        }
+    }
+
+    /// Bootstrap from a primary snapshot
+    ///
+    /// Every chain in the snapshot is verified first.
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self> {
+        let state = ResolverState::decode(bytes)?;
+        state.verify_traces()?;
+
+        let mut replica = Self::new();
+        for atlas in state.atlases {
+            replica.cursor.atlas_versions.insert(atlas.atlas_id.clone(), atlas.version.clone());
+            replica.atlases.insert(atlas.atlas_id.clone(), atlas);
+        }
+        for trace in state.traces {
+            replica.cursor.traces.insert(trace.session_id.clone(), trace.events.len());
+            replica.traces.insert(trace.session_id, trace.events);
+        }
+        for entry in state.sessions {
+            replica.sessions.insert(entry.session.session_id.clone(), entry.session);
+        }
+        replica.synced_at = Some(state.created_at);
+        Ok(replica)
+    }
+
+    /// Set the staleness bound for reads
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+
+    /// Use a custom clock to measure staleness
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Position to request the next batch from
+    pub fn cursor(&self) -> &ReplicationCursor {
+        &self.cursor
+    }
+
+    /// Apply a batch from the primary; returns the number of new events
+    ///
+    /// The batch must start at this replica's cursor, and each session's
+    /// new events must extend the chain the replica holds. A rejected batch
+    /// leaves the replica unchanged.
+    pub fn apply(&mut self, batch: ReplicationBatch) -> Result<usize> {
+        if batch.since != self.cursor {
+            return Err(CRAError::ReplicationError {
+                reason: "batch does not start at the replica's cursor".to_string(),
+            });
+        }
+
+        let mut extensions: BTreeMap<&str, Vec<&TRACEEvent>> = BTreeMap::new();
+        for event in &batch.events {
+            extensions.entry(event.session_id.as_str()).or_default().push(event);
+        }
+        for (session_id, events) in &extensions {
+            let held = self.traces.get(*session_id).map(Vec::as_slice).unwrap_or_default();
+            check_extension(session_id, held, events)?;
+            if batch.cursor.traces.get(*session_id) != Some(&(held.len() + events.len())) {
+                return Err(CRAError::ReplicationError {
+                    reason: format!("cursor for '{}' does not match the batch's events", session_id),
+                });
+            }
+        }
+
+        let count = batch.events.len();
+        for event in batch.events {
+            self.traces.entry(event.session_id.clone()).or_default().push(event);
+        }
+        for session in batch.sessions {
+            self.sessions.insert(session.session_id.clone(), session);
+        }
+        if let Some(atlases) = batch.atlases {
+            self.atlases = atlases.into_iter().map(|a| (a.atlas_id.clone(), a)).collect();
+        }
+        self.cursor = batch.cursor;
+        self.synced_at = Some(batch.created_at);
+        Ok(count)
+    }
+
+    /// How far behind the primary this replica is
+    pub fn staleness(&self) -> Staleness {
+        let staleness_ms = match self.synced_at {
+            Some(synced_at) => (self.clock.now() - synced_at).num_milliseconds().max(0) as u64,
+            None => u64::MAX,
+        };
+        Staleness {
+            synced_at: self.synced_at,
+            staleness_ms,
+            max_staleness_ms: self.max_staleness.as_millis() as u64,
+        }
+    }
+
+    /// All TRACE events for a session
+    pub fn get_trace(&self, session_id: &str) -> Result<ReplicaRead<Vec<TRACEEvent>>> {
+        self.read(|replica| Ok(replica.events(session_id)?.to_vec()))
+    }
+
+    /// Verify a session's hash chain
+    pub fn verify_chain(&self, session_id: &str) -> Result<ReplicaRead<ChainVerification>> {
+        self.read(|replica| Ok(ChainVerifier::verify(replica.events(session_id)?)))
+    }
+
+    /// A session record
+    pub fn get_session(&self, session_id: &str) -> Result<ReplicaRead<Session>> {
+        self.read(|replica| {
+            replica.sessions.get(session_id).cloned().ok_or_else(|| CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            })
+        })
+    }
+
+    /// All sessions, active and ended, oldest first
+    pub fn list_sessions(&self) -> Result<ReplicaRead<Vec<Session>>> {
+        self.read(|replica| {
+            let mut sessions: Vec<Session> = replica.sessions.values().cloned().collect();
+            sessions.sort_by(|a, b| (a.created_at, &a.session_id).cmp(&(b.created_at, &b.session_id)));
+            Ok(sessions)
+        })
+    }
+
+    /// All loaded atlases, by ID
+    pub fn list_atlases(&self) -> Result<ReplicaRead<Vec<AtlasManifest>>> {
+        self.read(|replica| Ok(replica.atlases.values().cloned().collect()))
+    }
+
+    fn events(&self, session_id: &str) -> Result<&[TRACEEvent]> {
+        self.traces.get(session_id).map(Vec::as_slice).ok_or_else(|| CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        })
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<ReplicaRead<T>> {
+        let staleness = self.staleness();
+        if staleness.staleness_ms > staleness.max_staleness_ms {
+            return Err(CRAError::ReplicaStale {
+                staleness_ms: staleness.staleness_ms,
+                max_staleness_ms: staleness.max_staleness_ms,
+            });
+        }
+        Ok(ReplicaRead { data: f(self)?, staleness })
+    }
+}
+
+/// Check that `events` continue the chain `held`
+fn check_extension(session_id: &str, held: &[TRACEEvent], events: &[&TRACEEvent]) -> Result<()> {
+    let mut previous = held.last();
+    for event in events {
+        let (expected_hash, expected_sequence) = match previous {
+            Some(p) => (p.event_hash.as_str(), p.sequence + 1),
+            None => (GENESIS_HASH, 0),
+        };
+        if event.previous_event_hash != expected_hash || event.sequence != expected_sequence || !event.verify_hash() {
+            return Err(CRAError::ReplicationError {
+                reason: format!(
+                    "event {} does not extend the chain of '{}' at sequence {}",
+                    event.event_id, session_id, expected_sequence
+                ),
+            });
+        }
+        previous = Some(*event);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::{CARPRequest, Resolver};
+    use serde_json::json;
+
+    fn atlas(version: &str) -> AtlasManifest {
+        serde_json::from_value(json!({
+            "atlas_version": "1.0",
+            "atlas_id": "com.test.replica",
+            "version": version,
+            "name": "Replica Atlas",
+            "description": "Atlas for replication tests",
+            "domains": ["test"],
+            "capabilities": [],
+            "policies": [],
+            "actions": [{
+                "action_id": "test.get",
+                "name": "Get",
+                "description": "Get a resource",
+                "parameters_schema": {"type": "object"},
+                "risk_tier": "low"
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn activity(primary: &mut Resolver) -> String {
+        let session_id = primary.create_session("agent", "goal").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "agent".to_string(), "goal".to_string());
+        let resolution = primary.resolve(&request).unwrap();
+        primary.execute(&session_id, &resolution.trace_id, "test.get", json!({})).unwrap();
+        session_id
+    }
+
+    #[test]
+    fn test_snapshot_then_batches() {
+        let mut primary = Resolver::new();
+        primary.load_atlas(atlas("1.0.0")).unwrap();
+        let first = activity(&mut primary);
+
+        let mut replica = ReadReplica::from_snapshot(&primary.snapshot().unwrap()).unwrap();
+        assert_eq!(replica.cursor(), &primary.replication_cursor().unwrap());
+
+        // New sessions, more events and an atlas upgrade, shipped in small batches
+        let second = activity(&mut primary);
+        primary.end_session(&first).unwrap();
+        primary.unload_atlas("com.test.replica").unwrap();
+        primary.load_atlas(atlas("2.0.0")).unwrap();
+
+        let target = primary.replication_cursor().unwrap();
+        let mut batches = 0;
+        loop {
+            let batch = primary.replication_batch(replica.cursor(), 3).unwrap();
+            assert!(batch.events.len() <= 3);
+            let more = batch.has_more(&target);
+            replica.apply(batch).unwrap();
+            batches += 1;
+            if !more {
+                break;
+            }
+        }
+        assert!(batches > 1);
+        assert_eq!(replica.cursor(), &target);
+        assert!(primary.replication_batch(replica.cursor(), 3).unwrap().is_empty());
+
+        for session_id in [&first, &second] {
+            let trace = replica.get_trace(session_id).unwrap();
+            let hashes = |events: &[TRACEEvent]| events.iter().map(|e| e.event_hash.clone()).collect::<Vec<_>>();
+            assert_eq!(hashes(&trace.data), hashes(&primary.get_trace(session_id).unwrap()));
+            assert!(replica.verify_chain(session_id).unwrap().data.is_valid);
+        }
+        assert!(!replica.get_session(&first).unwrap().data.is_active);
+        assert_eq!(replica.list_sessions().unwrap().data.len(), 2);
+        assert_eq!(replica.list_atlases().unwrap().data[0].version, "2.0.0");
+    }
+
+    #[test]
+    fn test_rejects_gaps_and_bounds_staleness() {
+        let mut primary = Resolver::new();
+        primary.load_atlas(atlas("1.0.0")).unwrap();
+        activity(&mut primary);
+
+        let mut replica = ReadReplica::new();
+        let err = replica.list_sessions().unwrap_err();
+        assert_eq!(err.error_code(), "REPLICA_STALE");
+
+        // A batch cut for a different cursor doesn't apply
+        let ahead = primary.replication_cursor().unwrap();
+        let err = replica.apply(primary.replication_batch(&ahead, 100).unwrap()).unwrap_err();
+        assert_eq!(err.error_code(), "REPLICATION_ERROR");
+
+        // Neither does a batch whose events don't extend the held chain
+        let mut batch = primary.replication_batch(replica.cursor(), 100).unwrap();
+        batch.events.remove(1);
+        assert_eq!(replica.apply(batch).unwrap_err().error_code(), "REPLICATION_ERROR");
+        assert!(replica.cursor().traces.is_empty());
+
+        let batch = primary.replication_batch(replica.cursor(), 100).unwrap();
+        let clock = Arc::new(FixedClock::new(batch.created_at));
+        let mut replica = replica.with_clock(clock.clone()).with_max_staleness(Duration::from_secs(5));
+        replica.apply(batch).unwrap();
+
+        clock.advance(Duration::from_secs(2));
+        let read = replica.list_sessions().unwrap();
+        assert_eq!((read.staleness.staleness_ms, read.staleness.max_staleness_ms), (2000, 5000));
+
+        clock.advance(Duration::from_secs(4));
+        let err = replica.list_sessions().unwrap_err();
+        assert!(matches!(err, CRAError::ReplicaStale { staleness_ms: 6000, .. }));
+        assert_eq!(err.http_status_code(), 503);
+        assert!(err.is_recoverable());
+    }
+}
@@ -0,0 +1,260 @@
+//! JSON Schemas for HTTP request bodies
+//!
+//! Servers check the bodies of `POST /v1/sessions`, `/v1/resolve` and
+//! `/v1/execute` with [`validate_request`] before deserializing them, so a
+//! malformed body answers 400 `INVALID_REQUEST_BODY` with every invalid
+//! field listed in `error.fields`, rather than whatever the JSON extractor
+//! makes of it. The schemas are published as
+//! `specs/schemas/<name>.schema.json` and referenced from
+//! `specs/openapi.yaml`; `tests/request_schemas.rs` keeps those copies
+//! identical to [`RequestEndpoint::schema`].
+//!
+//! ```rust
+//! use cra_core::request_schema::{validate_request, RequestEndpoint};
+//! use cra_core::CRAError;
+//! use serde_json::json;
+//!
+//! let body = json!({"session_id": "s-1", "goal": 42});
+//! match validate_request(RequestEndpoint::Resolve, &body) {
+//!     Err(CRAError::InvalidRequestBody { errors, .. }) => {
+//!         let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+//!         assert_eq!(fields, ["agent_id", "goal"]);
+//!     }
+//!     other => panic!("expected INVALID_REQUEST_BODY, got {:?}", other),
+//! }
+//! ```
+
+use std::sync::OnceLock;
+
+use jsonschema::paths::{JSONPointer, PathChunk};
+use jsonschema::{error::ValidationErrorKind, JSONSchema, ValidationError};
+use serde_json::{json, Value};
+
+use crate::error::{CRAError, FieldError, Result};
+
+/// Base of the schemas' `$id`s
+pub const SCHEMA_BASE_URL: &str = "https://cra.dev/schemas/";
+
+/// An endpoint whose request body has a schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestEndpoint {
+    /// `POST /v1/sessions`
+    CreateSession,
+    /// `POST /v1/resolve`
+    Resolve,
+    /// `POST /v1/execute`
+    Execute,
+}
+
+impl RequestEndpoint {
+    /// Every endpoint with a request schema
+    pub const ALL: [RequestEndpoint; 3] = [Self::CreateSession, Self::Resolve, Self::Execute];
+
+    /// Request path
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::CreateSession => "/v1/sessions",
+            Self::Resolve => "/v1/resolve",
+            Self::Execute => "/v1/execute",
+        }
+    }
+
+    /// Schema name; the published file is `<name>.schema.json`
+    pub fn schema_name(&self) -> &'static str {
+        match self {
+            Self::CreateSession => "create-session-request",
+            Self::Resolve => "resolve-request",
+            Self::Execute => "execute-request",
+        }
+    }
+
+    /// The request body schema
+    pub fn schema(&self) -> &'static Value {
+        static SCHEMAS: OnceLock<[Value; 3]> = OnceLock::new();
+        let schemas = SCHEMAS.get_or_init(|| RequestEndpoint::ALL.map(|endpoint| endpoint.build_schema()));
+        &schemas[*self as usize]
+    }
+
+    fn compiled(&self) -> &'static JSONSchema {
+        static COMPILED: OnceLock<[JSONSchema; 3]> = OnceLock::new();
+        let compiled = COMPILED.get_or_init(|| {
+            RequestEndpoint::ALL.map(|endpoint| {
+                JSONSchema::compile(endpoint.schema()).expect("request schemas are valid")
+            })
+        });
+        &compiled[*self as usize]
+    }
+
+    fn build_schema(&self) -> Value {
+        let (title, required, properties) = match self {
+            Self::CreateSession => (
+                "Create Session Request",
+                json!(["goal"]),
+                json!({
+                    "agent_id": {
+                        "type": "string",
+                        "minLength": 1,
+                        "maxLength": 255,
+                        "description": "Agent opening the session; taken from the bearer token when one is sent"
+                    },
+                    "goal": {
+                        "type": "string",
+                        "minLength": 1,
+                        "description": "What the agent is trying to do"
+                    },
+                    "trace_verbosity": {
+                        "type": "string",
+                        "enum": ["minimal", "standard", "debug"],
+                        "description": "TRACE verbosity for the session (default standard)"
+                    }
+                }),
+            ),
+            Self::Resolve => (
+                "Resolve Request",
+                json!(["session_id", "agent_id", "goal"]),
+                json!({
+                    "session_id": {"type": "string", "minLength": 1},
+                    "agent_id": {"type": "string", "minLength": 1, "maxLength": 255},
+                    "goal": {"type": "string", "minLength": 1}
+                }),
+            ),
+            Self::Execute => (
+                "Execute Request",
+                json!(["session_id", "resolution_id", "action_id"]),
+                json!({
+                    "session_id": {"type": "string", "minLength": 1},
+                    "resolution_id": {"type": "string", "minLength": 1},
+                    "action_id": {"type": "string", "minLength": 1},
+                    "parameters": {
+                        "type": "object",
+                        "description": "Checked against the action's parameters_schema at execution (default {})"
+                    }
+                }),
+            ),
+        };
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("{}{}.schema.json", SCHEMA_BASE_URL, self.schema_name()),
+            "title": title,
+            "description": format!("Body of POST {}", self.path()),
+            "type": "object",
+            "required": required,
+            "properties": properties,
+            "additionalProperties": false
+        })
+    }
+}
+
+/// Check a request body against its endpoint's schema
+///
+/// Fails with `INVALID_REQUEST_BODY` listing every invalid field, in
+/// field order.
+pub fn validate_request(endpoint: RequestEndpoint, body: &Value) -> Result<()> {
+    let mut errors: Vec<FieldError> = match endpoint.compiled().validate(body) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors.flat_map(|e| field_errors(&e)).collect(),
+    };
+    errors.sort_by(|a, b| a.field.cmp(&b.field));
+
+    Err(CRAError::InvalidRequestBody {
+        endpoint: endpoint.path().to_string(),
+        errors,
+    })
+}
+
+/// Field errors for one schema violation, naming the missing or unexpected
+/// property itself rather than the object holding it
+fn field_errors(error: &ValidationError) -> Vec<FieldError> {
+    let parent = field_path(&error.instance_path);
+    let child = |name: &str| {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", parent, name)
+        }
+    };
+
+    match &error.kind {
+        ValidationErrorKind::Required { property } => {
+            let name = property.as_str().unwrap_or_default();
+            vec![FieldError::new(child(name), "is required")]
+        }
+        ValidationErrorKind::AdditionalProperties { unexpected } => unexpected
+            .iter()
+            .map(|name| FieldError::new(child(name), "is not a recognized field"))
+            .collect(),
+        ValidationErrorKind::Type { .. } if parent.is_empty() => {
+            vec![FieldError::new("", "request body must be a JSON object")]
+        }
+        _ => vec![FieldError::new(parent, error.to_string())],
+    }
+}
+
+/// `parameters.items[0]` for the pointer `/parameters/items/0`
+fn field_path(path: &JSONPointer) -> String {
+    let mut field = String::new();
+    for chunk in path.iter() {
+        match chunk {
+            PathChunk::Property(name) => {
+                if !field.is_empty() {
+                    field.push('.');
+                }
+                field.push_str(name);
+            }
+            PathChunk::Index(index) => field.push_str(&format!("[{}]", index)),
+            PathChunk::Keyword(keyword) => field.push_str(keyword),
+        }
+    }
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_errors_of(endpoint: RequestEndpoint, body: Value) -> Vec<FieldError> {
+        match validate_request(endpoint, &body) {
+            Err(CRAError::InvalidRequestBody { errors, .. }) => errors,
+            other => panic!("expected INVALID_REQUEST_BODY, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_bodies() {
+        let bodies = [
+            (RequestEndpoint::CreateSession, json!({"agent_id": "agent-1", "goal": "Help"})),
+            (RequestEndpoint::CreateSession, json!({"goal": "Help", "trace_verbosity": "debug"})),
+            (RequestEndpoint::Resolve, json!({"session_id": "s", "agent_id": "a", "goal": "Help"})),
+            (RequestEndpoint::Execute, json!({"session_id": "s", "resolution_id": "r", "action_id": "file.read"})),
+        ];
+        for (endpoint, body) in bodies {
+            validate_request(endpoint, &body).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_every_invalid_field_is_listed() {
+        let errors = field_errors_of(
+            RequestEndpoint::Execute,
+            json!({"session_id": "", "action_id": 7, "parameters": [], "paramters": {}}),
+        );
+
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["action_id", "parameters", "paramters", "resolution_id", "session_id"]);
+        assert_eq!(errors[2].message, "is not a recognized field");
+        assert_eq!(errors[3].message, "is required");
+        assert!(errors[1].message.contains("object"), "{}", errors[1].message);
+    }
+
+    #[test]
+    fn test_error_response_lists_fields() {
+        let err = validate_request(RequestEndpoint::CreateSession, &json!("hello")).unwrap_err();
+        assert_eq!(err.http_status_code(), 400);
+
+        let response = err.to_error_response();
+        assert_eq!(response.error.code, "INVALID_REQUEST_BODY");
+        assert_eq!(response.error.fields, vec![FieldError::new("", "request body must be a JSON object")]);
+        assert!(response.error.message.contains("/v1/sessions"));
+    }
+}
@@ -66,8 +66,13 @@ use std::time::Duration;
 
 use tokio::sync::mpsc;
 
+#[cfg(feature = "chaos")]
+use crate::chaos::{ChaosInjector, FaultPoint};
 use crate::error::Result;
+#[cfg(not(feature = "minimal"))]
+use crate::replication::{ReplicationCursor, ShipBatchFn};
 use crate::trace::{TraceRingBuffer, BufferStats};
+use crate::carp::{validate_batch, BatchResolveResponse};
 use crate::{AtlasManifest, CARPRequest, CARPResolution, Resolver, TRACEEvent};
 
 /// Configuration for the async runtime
@@ -199,6 +204,9 @@ pub struct AsyncRuntime {
     trace_buffer: Arc<TraceRingBuffer>,
     /// Shutdown signal for background tasks
     shutdown_tx: Option<mpsc::Sender<()>>,
+    /// Fault plan for chaos tests
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<ChaosInjector>>,
 }
 
 impl AsyncRuntime {
@@ -212,6 +220,8 @@ impl AsyncRuntime {
             subscribers: Vec::new(),
             trace_buffer: Arc::new(TraceRingBuffer::new(buffer_capacity)),
             shutdown_tx: None,
+            #[cfg(feature = "chaos")]
+            chaos: None,
         })
     }
 
@@ -221,6 +231,17 @@ impl AsyncRuntime {
         self
     }
 
+    /// Inject faults from a chaos fault plan
+    ///
+    /// Faults armed at [`FaultPoint::Resolve`] delay or fail resolutions and
+    /// those at [`FaultPoint::StorageWrite`] delay or fail async storage
+    /// writes. Injected failures are recorded in the session's TRACE.
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
     /// Add an event subscriber for streaming
     pub fn with_subscriber(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
         self.subscribers.push(subscriber);
@@ -264,6 +285,77 @@ impl AsyncRuntime {
         TraceProcessorHandle { handle, shutdown_tx: tx }
     }
 
+    /// Start background TRACE chain verification
+    ///
+    /// Every `interval`, verifies up to `sessions_per_sweep` chains with
+    /// [`Resolver::sweep_chains`], quarantining sessions whose chain is broken.
+    pub fn start_chain_monitor(&self, interval: Duration, sessions_per_sweep: usize) -> TraceProcessorHandle {
+        let (tx, mut rx) = mpsc::channel::<()>(1);
+        let resolver = self.resolver.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                tokio::select! {
+                    _ = rx.recv() => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = resolver.write().sweep_chains(sessions_per_sweep) {
+                            eprintln!("Error verifying TRACE chains: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+
+        TraceProcessorHandle { handle, shutdown_tx: tx }
+    }
+
+    /// Start shipping changes to a read replica
+    ///
+    /// Every `interval`, cuts a batch of up to `max_events` changes since
+    /// the replica's cursor with [`Resolver::replication_batch`] and hands
+    /// it to `ship`. The cursor advances only when `ship` succeeds, so a
+    /// failed batch is cut again on the next tick. Empty batches are shipped
+    /// too, as heartbeats that keep the replica's staleness low.
+    #[cfg(not(feature = "minimal"))]
+    pub fn start_replication(
+        &self,
+        interval: Duration,
+        max_events: usize,
+        start: ReplicationCursor,
+        ship: ShipBatchFn,
+    ) -> TraceProcessorHandle {
+        let (tx, mut rx) = mpsc::channel::<()>(1);
+        let resolver = self.resolver.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            let mut cursor = start;
+
+            loop {
+                tokio::select! {
+                    _ = rx.recv() => break,
+                    _ = interval.tick() => {
+                        let batch = match resolver.write().replication_batch(&cursor, max_events) {
+                            Ok(batch) => batch,
+                            Err(e) => {
+                                eprintln!("Error cutting replication batch: {}", e);
+                                continue;
+                            }
+                        };
+                        match ship(&batch) {
+                            Ok(()) => cursor = batch.cursor,
+                            Err(e) => eprintln!("Error shipping replication batch: {}", e),
+                        }
+                    }
+                }
+            }
+        });
+
+        TraceProcessorHandle { handle, shutdown_tx: tx }
+    }
+
     /// Process a batch of events from the buffer
     async fn process_buffer_batch(
         buffer: &TraceRingBuffer,
@@ -317,7 +409,7 @@ impl AsyncRuntime {
         if let Some(ref storage) = self.storage {
             let events = self.resolver.read().get_trace(&session_id)?;
             for event in events {
-                storage.store_event(&event).await?;
+                self.store_event(storage.as_ref(), &event).await?;
                 self.notify_subscribers(&event).await?;
             }
         }
@@ -332,10 +424,15 @@ impl AsyncRuntime {
         let resolver = self.resolver.clone();
         let session_id = request.session_id.clone();
         let request_clone = request.clone();
+        #[cfg(feature = "chaos")]
+        let chaos = self.chaos.clone();
 
         // Run CPU-bound resolution on blocking thread pool
         let resolution = tokio::task::spawn_blocking(move || {
-            resolver.write().resolve(&request_clone)
+            let mut resolver = resolver.write();
+            #[cfg(feature = "chaos")]
+            inject_resolve_fault(chaos.as_deref(), &mut resolver, &request_clone.session_id)?;
+            resolver.resolve(&request_clone)
         })
         .await
         .map_err(|e| crate::CRAError::InternalError {
@@ -346,7 +443,7 @@ impl AsyncRuntime {
         if let Some(ref storage) = self.storage {
             let events = self.resolver.read().get_trace(&session_id)?;
             for event in events {
-                storage.store_event(&event).await?;
+                self.store_event(storage.as_ref(), &event).await?;
                 self.notify_subscribers(&event).await?;
             }
         }
@@ -354,6 +451,61 @@ impl AsyncRuntime {
         Ok(resolution)
     }
 
+    /// Resolve many requests asynchronously
+    ///
+    /// The batch is split into up to `resolver_pool_size` chunks, each
+    /// resolved on the blocking pool under a single resolver lock, so other
+    /// callers can interleave between chunks. Results come back in request
+    /// order with per-request errors (see [`Resolver::resolve_batch`]).
+    pub async fn resolve_batch(&self, requests: Vec<CARPRequest>) -> Result<BatchResolveResponse> {
+        validate_batch(&requests)?;
+        let chunk_size = requests.len().div_ceil(self.config.resolver_pool_size.max(1));
+
+        let tasks: Vec<_> = requests
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let resolver = self.resolver.clone();
+                let chunk = chunk.to_vec();
+                #[cfg(feature = "chaos")]
+                let chaos = self.chaos.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut resolver = resolver.write();
+                    chunk
+                        .iter()
+                        .map(|request| {
+                            #[cfg(feature = "chaos")]
+                            inject_resolve_fault(chaos.as_deref(), &mut resolver, &request.session_id)?;
+                            resolver.resolve(request)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(requests.len());
+        for task in tasks {
+            results.extend(task.await.map_err(|e| crate::CRAError::InternalError {
+                reason: format!("Task join error: {}", e),
+            })?);
+        }
+
+        // Store trace events asynchronously, once per session
+        if let Some(ref storage) = self.storage {
+            let mut session_ids: Vec<&str> = requests.iter().map(|r| r.session_id.as_str()).collect();
+            session_ids.sort_unstable();
+            session_ids.dedup();
+            for session_id in session_ids {
+                let Ok(events) = self.resolver.read().get_trace(session_id) else { continue };
+                for event in events {
+                    self.store_event(storage.as_ref(), &event).await?;
+                    self.notify_subscribers(&event).await?;
+                }
+            }
+        }
+
+        Ok(BatchResolveResponse::from_results(requests.iter().zip(results)))
+    }
+
     /// End a session asynchronously
     pub async fn end_session(&self, session_id: &str) -> Result<()> {
         self.resolver.write().end_session(session_id)?;
@@ -371,6 +523,22 @@ impl AsyncRuntime {
         &self.resolver
     }
 
+    /// Write an event to async storage, applying any injected fault
+    async fn store_event(&self, storage: &dyn AsyncStorageBackend, event: &TRACEEvent) -> Result<()> {
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            let injection = chaos.inject(FaultPoint::StorageWrite, Some(&event.session_id));
+            if !injection.delay.is_zero() {
+                tokio::time::sleep(injection.delay).await;
+            }
+            if let Some(e) = injection.failure {
+                self.resolver.write().record_injected_fault(&event.session_id, &e)?;
+                return Err(e);
+            }
+        }
+        storage.store_event(event).await
+    }
+
     /// Notify all subscribers of an event
     async fn notify_subscribers(&self, event: &TRACEEvent) -> Result<()> {
         for subscriber in &self.subscribers {
@@ -389,10 +557,26 @@ impl Clone for AsyncRuntime {
             subscribers: self.subscribers.clone(),
             trace_buffer: self.trace_buffer.clone(),
             shutdown_tx: self.shutdown_tx.clone(),
+            #[cfg(feature = "chaos")]
+            chaos: self.chaos.clone(),
         }
     }
 }
 
+/// Apply any fault armed at [`FaultPoint::Resolve`] before a resolution
+///
+/// Runs under the resolver lock, so injected latency stalls other callers
+/// the way a slow resolution would.
+#[cfg(feature = "chaos")]
+fn inject_resolve_fault(chaos: Option<&ChaosInjector>, resolver: &mut Resolver, session_id: &str) -> Result<()> {
+    let Some(chaos) = chaos else { return Ok(()) };
+    if let Err(e) = chaos.check(FaultPoint::Resolve, Some(session_id)) {
+        resolver.record_injected_fault(session_id, &e)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
 /// Swarm coordinator for multi-agent scenarios
 ///
 /// Provides higher-level primitives for agent swarms:
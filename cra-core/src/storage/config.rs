@@ -0,0 +1,278 @@
+//! Storage selection from configuration
+//!
+//! [`StorageConfig`] describes a backend as data (`type` plus
+//! backend-specific options), so binaries can pick persistence from a
+//! config file instead of at compile time. [`StorageFactory`] turns it into
+//! a backend.
+//!
+//! ```rust
+//! use cra_core::storage::{StorageConfig, StorageFactory};
+//!
+//! let config = StorageConfig::from_json(r#"{"type": "memory"}"#).unwrap();
+//! let storage = StorageFactory::new().create(&config).unwrap();
+//! assert_eq!(storage.name(), "in-memory");
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    FileStorage, FileStorageConfig, FsyncPolicy, InMemoryStorage, NullStorage, SegmentLogConfig,
+    SegmentLogStorage, StorageBackend, TieredConfig, TieredStorage,
+};
+use crate::error::{CRAError, Result};
+use crate::trace::EventType;
+
+/// Storage backend configuration
+///
+/// Unset options keep the backend's defaults; the file backend syncs every
+/// event unless group commit is configured. For `fsync_interval_ms`, `0`
+/// syncs on every write.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum StorageConfig {
+    /// In-memory storage; events are lost on restart
+    #[default]
+    Memory,
+    /// Discard all events
+    Null,
+    /// One JSONL file per session ([`FileStorage`])
+    File {
+        directory: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fsync_interval_ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_buffered_events: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sync_on_event_types: Option<Vec<EventType>>,
+    },
+    /// Segmented append-only log ([`SegmentLogStorage`])
+    Segment {
+        directory: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_segment_bytes: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fsync_interval_ms: Option<u64>,
+    },
+    /// A hot tier replicated to a durable tier ([`TieredStorage`])
+    Tiered {
+        hot: Box<StorageConfig>,
+        durable: Box<StorageConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_retries: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_backoff_ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_pending: Option<usize>,
+    },
+    /// SQLite database; needs a backend registered with
+    /// [`StorageFactory::with_backend`]
+    Sqlite { path: PathBuf },
+    /// PostgreSQL database; needs a backend registered with
+    /// [`StorageFactory::with_backend`]
+    Postgres { url: String },
+}
+
+impl StorageConfig {
+    /// Parse a configuration from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| CRAError::StorageConfigError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Read a configuration from a JSON file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| CRAError::IoError {
+            message: format!("{}: {}", path.display(), e),
+        })?;
+        Self::from_json(&text)
+    }
+
+    /// The backend type name, as written in the `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StorageConfig::Memory => "memory",
+            StorageConfig::Null => "null",
+            StorageConfig::File { .. } => "file",
+            StorageConfig::Segment { .. } => "segment",
+            StorageConfig::Tiered { .. } => "tiered",
+            StorageConfig::Sqlite { .. } => "sqlite",
+            StorageConfig::Postgres { .. } => "postgres",
+        }
+    }
+}
+
+/// Constructor for a backend type that isn't built in
+pub type BackendConstructor = Box<dyn Fn(&StorageConfig) -> Result<Arc<dyn StorageBackend>> + Send + Sync>;
+
+/// Builds storage backends from [`StorageConfig`]
+///
+/// Memory, null, file, segment and tiered backends are built in. Other
+/// types (SQLite, PostgreSQL) are served by constructors registered with
+/// [`StorageFactory::with_backend`], which may also override a built-in type.
+#[derive(Default)]
+pub struct StorageFactory {
+    constructors: HashMap<String, BackendConstructor>,
+}
+
+impl std::fmt::Debug for StorageFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut kinds: Vec<_> = self.constructors.keys().collect();
+        kinds.sort();
+        f.debug_struct("StorageFactory").field("constructors", &kinds).finish()
+    }
+}
+
+impl StorageFactory {
+    /// Create a factory with only the built-in backends
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build configurations of type `kind` with `constructor`
+    pub fn with_backend<F>(mut self, kind: impl Into<String>, constructor: F) -> Self
+    where
+        F: Fn(&StorageConfig) -> Result<Arc<dyn StorageBackend>> + Send + Sync + 'static,
+    {
+        self.constructors.insert(kind.into(), Box::new(constructor));
+        self
+    }
+
+    /// Build the backend a configuration describes
+    pub fn create(&self, config: &StorageConfig) -> Result<Arc<dyn StorageBackend>> {
+        if let Some(constructor) = self.constructors.get(config.kind()) {
+            return constructor(config);
+        }
+
+        let storage: Arc<dyn StorageBackend> = match config {
+            StorageConfig::Memory => Arc::new(InMemoryStorage::new()),
+            StorageConfig::Null => Arc::new(NullStorage::new()),
+            StorageConfig::File {
+                directory,
+                fsync_interval_ms,
+                max_buffered_events,
+                sync_on_event_types,
+            } => {
+                let mut file_config = FileStorageConfig::default();
+                if let Some(ms) = fsync_interval_ms {
+                    file_config = file_config.fsync_interval(Some(Duration::from_millis(*ms)));
+                }
+                if let Some(events) = max_buffered_events {
+                    file_config = file_config.max_buffered_events(*events);
+                }
+                if let Some(event_types) = sync_on_event_types {
+                    file_config = file_config.sync_on_event_types(event_types.clone());
+                }
+                Arc::new(FileStorage::with_config(directory, file_config)?)
+            }
+            StorageConfig::Segment {
+                directory,
+                max_segment_bytes,
+                fsync_interval_ms,
+            } => {
+                let mut segment_config = SegmentLogConfig::default();
+                if let Some(bytes) = max_segment_bytes {
+                    segment_config = segment_config.max_segment_bytes(*bytes);
+                }
+                match fsync_interval_ms {
+                    Some(0) => segment_config = segment_config.fsync(FsyncPolicy::Always),
+                    Some(ms) => {
+                        segment_config = segment_config.fsync(FsyncPolicy::Interval(Duration::from_millis(*ms)))
+                    }
+                    None => {}
+                }
+                Arc::new(SegmentLogStorage::open(directory, segment_config)?)
+            }
+            StorageConfig::Tiered {
+                hot,
+                durable,
+                max_retries,
+                retry_backoff_ms,
+                max_pending,
+            } => {
+                let mut tiered_config = TieredConfig::default();
+                if let Some(retries) = max_retries {
+                    tiered_config = tiered_config.max_retries(*retries);
+                }
+                if let Some(ms) = retry_backoff_ms {
+                    tiered_config = tiered_config.retry_backoff(Duration::from_millis(*ms));
+                }
+                if let Some(pending) = max_pending {
+                    tiered_config = tiered_config.max_pending(*pending);
+                }
+                Arc::new(TieredStorage::with_config(
+                    self.create(hot)?,
+                    self.create(durable)?,
+                    tiered_config,
+                ))
+            }
+            StorageConfig::Sqlite { .. } | StorageConfig::Postgres { .. } => {
+                return Err(CRAError::StorageConfigError {
+                    reason: format!("no '{}' storage backend is registered in this build", config.kind()),
+                });
+            }
+        };
+        Ok(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::TRACEEvent;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_and_create() {
+        let temp_dir = std::env::temp_dir().join("cra-test-storage-config");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let config = StorageConfig::from_json(
+            &json!({
+                "type": "tiered",
+                "hot": {"type": "memory"},
+                "durable": {
+                    "type": "file",
+                    "directory": temp_dir,
+                    "fsync_interval_ms": 0,
+                    "sync_on_event_types": ["action.denied", "session.ended"]
+                },
+                "max_retries": 2
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(config.kind(), "tiered");
+        assert_eq!(StorageConfig::from_json(&serde_json::to_string(&config).unwrap()).unwrap(), config);
+
+        let storage = StorageFactory::new().create(&config).unwrap();
+        assert_eq!(storage.name(), "tiered");
+        assert!(temp_dir.is_dir());
+        let event = TRACEEvent::new("s1".to_string(), "t1".to_string(), EventType::SessionStarted, json!({}));
+        storage.store_event(&event).unwrap();
+        assert_eq!(storage.get_event_count("s1").unwrap(), 1);
+
+        drop(storage);
+        assert_eq!(StorageFactory::new().create(&StorageConfig::default()).unwrap().name(), "in-memory");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_unregistered_backends() {
+        let err = StorageConfig::from_json(r#"{"type": "redis"}"#).unwrap_err();
+        assert_eq!(err.error_code(), "STORAGE_CONFIG_ERROR");
+        assert!(StorageConfig::from_json(r#"{"type": "file", "directory": "x", "fsync": "always"}"#).is_err());
+
+        let config = StorageConfig::from_json(r#"{"type": "sqlite", "path": "traces.db"}"#).unwrap();
+        let err = StorageFactory::new().create(&config).map(|_| ()).unwrap_err();
+        assert_eq!(err.error_code(), "STORAGE_CONFIG_ERROR");
+
+        let factory = StorageFactory::new().with_backend("sqlite", |_| Ok(Arc::new(InMemoryStorage::new())));
+        assert_eq!(factory.create(&config).unwrap().name(), "in-memory");
+    }
+}
@@ -0,0 +1,388 @@
+//! Encryption at rest for stored events
+//!
+//! [`EncryptedStorage`] wraps another backend and replaces each event
+//! payload with an AES-256-GCM envelope before it is stored:
+//!
+//! ```json
+//! {"encrypted": {"alg": "aes-256-gcm", "key_id": "k1",
+//!                "nonce": "...", "ciphertext": "..."}}
+//! ```
+//!
+//! Everything else (IDs, sequence, timestamp, event type and both hashes)
+//! stays in the clear, and the event ID, session ID, sequence and event hash
+//! are bound to the ciphertext as associated data. Reads decrypt payloads
+//! back, so hashes recompute exactly. Without the key,
+//! [`EncryptedStorage::verify_sealed_chain`] can still check hash linkage
+//! and sequencing.
+//!
+//! Keys come from a [`KeyProvider`]; new writes use its current key and
+//! older keys stay available for reads, which allows rotation. Requires the
+//! `encryption` feature.
+
+use std::collections::HashMap;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::StorageBackend;
+use crate::error::{CRAError, Result};
+use crate::trace::{ChainErrorType, ChainVerification, TRACEEvent, GENESIS_HASH};
+
+/// Envelope algorithm identifier
+pub const ENCRYPTION_ALGORITHM: &str = "aes-256-gcm";
+
+/// Supplies AES-256 keys by ID
+pub trait KeyProvider: Send + Sync {
+    /// ID of the key used to encrypt new events
+    fn current_key_id(&self) -> String;
+
+    /// Look up a key by ID
+    fn key(&self, key_id: &str) -> Option<[u8; 32]>;
+}
+
+/// Key provider holding keys in memory
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: HashMap<String, [u8; 32]>,
+}
+
+impl std::fmt::Debug for StaticKeyProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        let mut key_ids: Vec<&String> = self.keys.keys().collect();
+        key_ids.sort();
+        f.debug_struct("StaticKeyProvider")
+            .field("current", &self.current)
+            .field("key_ids", &key_ids)
+            .finish()
+    }
+}
+
+impl StaticKeyProvider {
+    /// Create a provider with a single current key
+    pub fn new(key_id: impl Into<String>, key: [u8; 32]) -> Self {
+        let key_id = key_id.into();
+        let mut keys = HashMap::new();
+        keys.insert(key_id.clone(), key);
+        Self { current: key_id, keys }
+    }
+
+    /// Add a key that can still decrypt existing events
+    pub fn with_key(mut self, key_id: impl Into<String>, key: [u8; 32]) -> Self {
+        self.keys.insert(key_id.into(), key);
+        self
+    }
+
+    /// Rotate to a new current key, keeping old keys for reads
+    pub fn rotate(mut self, key_id: impl Into<String>, key: [u8; 32]) -> Self {
+        let key_id = key_id.into();
+        self.keys.insert(key_id.clone(), key);
+        self.current = key_id;
+        self
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> String {
+        self.current.clone()
+    }
+
+    fn key(&self, key_id: &str) -> Option<[u8; 32]> {
+        self.keys.get(key_id).copied()
+    }
+}
+
+/// Encrypted payload of a stored event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedPayload {
+    /// Always [`ENCRYPTION_ALGORITHM`]
+    pub alg: String,
+
+    /// ID of the key that encrypted the payload
+    pub key_id: String,
+
+    /// 96-bit nonce (hex)
+    pub nonce: String,
+
+    /// Ciphertext with GCM tag (hex)
+    pub ciphertext: String,
+}
+
+impl EncryptedPayload {
+    /// Read the envelope from an event, if its payload is encrypted
+    pub fn from_event(event: &TRACEEvent) -> Option<Self> {
+        serde_json::from_value(event.payload.get("encrypted")?.clone()).ok()
+    }
+}
+
+/// Metadata bound to the ciphertext so it cannot be moved between events
+fn associated_data(event: &TRACEEvent) -> Vec<u8> {
+    format!(
+        "{}\n{}\n{}\n{}",
+        event.event_id, event.session_id, event.sequence, event.event_hash
+    )
+    .into_bytes()
+}
+
+fn encryption_error(reason: impl Into<String>) -> CRAError {
+    CRAError::StorageEncryptionError {
+        reason: reason.into(),
+    }
+}
+
+/// Storage decorator that encrypts event payloads at rest
+#[derive(Debug)]
+pub struct EncryptedStorage<B: StorageBackend, K: KeyProvider = StaticKeyProvider> {
+    inner: B,
+    keys: K,
+}
+
+impl<B: StorageBackend, K: KeyProvider> EncryptedStorage<B, K> {
+    /// Wrap a backend, encrypting with keys from the provider
+    pub fn new(inner: B, keys: K) -> Self {
+        Self { inner, keys }
+    }
+
+    /// Get the wrapped backend (which only sees encrypted payloads)
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Get the key provider
+    pub fn keys(&self) -> &K {
+        &self.keys
+    }
+
+    /// Encrypt an event's payload
+    pub fn encrypt(&self, event: &TRACEEvent) -> Result<TRACEEvent> {
+        let key_id = self.keys.current_key_id();
+        let cipher = self.cipher(&key_id)?;
+        let plaintext = serde_json::to_vec(&event.payload)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &associated_data(event),
+                },
+            )
+            .map_err(|_| encryption_error("encryption failed"))?;
+
+        let envelope = EncryptedPayload {
+            alg: ENCRYPTION_ALGORITHM.to_string(),
+            key_id,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        let mut encrypted = event.clone();
+        encrypted.payload = json!({ "encrypted": envelope });
+        Ok(encrypted)
+    }
+
+    /// Decrypt an event's payload; events stored in the clear pass through
+    pub fn decrypt(&self, event: TRACEEvent) -> Result<TRACEEvent> {
+        let Some(envelope) = EncryptedPayload::from_event(&event) else {
+            return Ok(event);
+        };
+        if envelope.alg != ENCRYPTION_ALGORITHM {
+            return Err(encryption_error(format!("unsupported algorithm '{}'", envelope.alg)));
+        }
+
+        let cipher = self.cipher(&envelope.key_id)?;
+        let nonce = hex::decode(&envelope.nonce)
+            .ok()
+            .filter(|n| n.len() == 12)
+            .ok_or_else(|| encryption_error("malformed nonce"))?;
+        let ciphertext = hex::decode(&envelope.ciphertext)
+            .map_err(|_| encryption_error("malformed ciphertext"))?;
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &associated_data(&event),
+                },
+            )
+            .map_err(|_| {
+                encryption_error(format!(
+                    "event {} failed authentication (wrong key or tampered data)",
+                    event.event_id
+                ))
+            })?;
+
+        let mut decrypted = event;
+        decrypted.payload = serde_json::from_slice::<Value>(&plaintext)?;
+        Ok(decrypted)
+    }
+
+    /// Check hash linkage and sequencing of a session without any key
+    ///
+    /// Event hashes cover the plaintext payload, so they are only recomputed
+    /// for events stored in the clear; encrypted events are checked for
+    /// linkage, while their payloads are protected by the GCM tag.
+    pub fn verify_sealed_chain(&self, session_id: &str) -> Result<ChainVerification> {
+        let events = self.inner.get_events(session_id)?;
+        let mut last_hash = GENESIS_HASH.to_string();
+        for (i, event) in events.iter().enumerate() {
+            if event.previous_event_hash != last_hash {
+                let error_type = if i == 0 {
+                    ChainErrorType::InvalidGenesis
+                } else {
+                    ChainErrorType::ChainBroken
+                };
+                return Ok(ChainVerification::invalid(
+                    events.len(),
+                    i,
+                    error_type,
+                    format!(
+                        "Event {} previous_event_hash {} doesn't match {}",
+                        i, event.previous_event_hash, last_hash
+                    ),
+                ));
+            }
+            if event.sequence != i as u64 {
+                return Ok(ChainVerification::invalid(
+                    events.len(),
+                    i,
+                    ChainErrorType::SequenceGap,
+                    format!("Event {} has sequence {}", i, event.sequence),
+                ));
+            }
+            if EncryptedPayload::from_event(event).is_none() && !event.verify_hash() {
+                return Ok(ChainVerification::invalid(
+                    events.len(),
+                    i,
+                    ChainErrorType::HashMismatch,
+                    format!("Event {} hash mismatch", i),
+                ));
+            }
+            last_hash = event.event_hash.clone();
+        }
+        Ok(ChainVerification::valid(events.len(), last_hash))
+    }
+
+    fn cipher(&self, key_id: &str) -> Result<Aes256Gcm> {
+        let key = self
+            .keys
+            .key(key_id)
+            .ok_or_else(|| encryption_error(format!("unknown key '{}'", key_id)))?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    fn decrypt_all(&self, events: Vec<TRACEEvent>) -> Result<Vec<TRACEEvent>> {
+        events.into_iter().map(|e| self.decrypt(e)).collect()
+    }
+}
+
+impl<B: StorageBackend, K: KeyProvider> StorageBackend for EncryptedStorage<B, K> {
+    fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+        self.inner.store_event(&self.encrypt(event)?)
+    }
+
+    fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        self.decrypt_all(self.inner.get_events(session_id)?)
+    }
+
+    fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+        self.decrypt_all(self.inner.get_events_by_type(session_id, event_type)?)
+    }
+
+    fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+        self.decrypt_all(self.inner.get_last_events(session_id, n)?)
+    }
+
+    fn get_event_count(&self, session_id: &str) -> Result<usize> {
+        self.inner.get_event_count(session_id)
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.inner.delete_session(session_id)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        self.inner.list_sessions()
+    }
+
+    fn health_check(&self) -> Result<()> {
+        self.cipher(&self.keys.current_key_id())?;
+        self.inner.health_check()
+    }
+
+    fn name(&self) -> &'static str {
+        "encrypted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemoryStorage;
+    use crate::trace::{ChainVerifier, EventType, TraceCollector};
+
+    fn store_session(storage: &impl StorageBackend) -> Vec<TRACEEvent> {
+        let mut collector = TraceCollector::new();
+        collector
+            .emit("s1", EventType::SessionStarted, json!({"agent_id": "agent-1"}))
+            .unwrap();
+        collector
+            .emit("s1", EventType::ActionExecuted, json!({"secret": "hunter2"}))
+            .unwrap();
+        let events = collector.get_events("s1").unwrap();
+        for event in &events {
+            storage.store_event(event).unwrap();
+        }
+        events
+    }
+
+    #[test]
+    fn test_encrypted_roundtrip_keeps_chain_valid() {
+        let storage = EncryptedStorage::new(InMemoryStorage::new(), StaticKeyProvider::new("k1", [7; 32]));
+        let events = store_session(&storage);
+
+        let raw = storage.inner().get_events("s1").unwrap();
+        assert!(!serde_json::to_string(&raw).unwrap().contains("hunter2"));
+        assert_eq!(raw[1].event_hash, events[1].event_hash);
+        assert!(storage.verify_sealed_chain("s1").unwrap().is_valid);
+
+        let decrypted = storage.get_events("s1").unwrap();
+        assert_eq!(decrypted[1].payload, events[1].payload);
+        assert!(ChainVerifier::verify(&decrypted).is_valid);
+        assert_eq!(storage.get_events_by_type("s1", "action.executed").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_encrypted_key_rotation_and_wrong_key() {
+        let storage = EncryptedStorage::new(InMemoryStorage::new(), StaticKeyProvider::new("k1", [1; 32]));
+        store_session(&storage);
+        let raw = storage.inner().get_events("s1").unwrap();
+
+        let rotated = EncryptedStorage::new(
+            InMemoryStorage::new(),
+            StaticKeyProvider::new("k1", [1; 32]).rotate("k2", [2; 32]),
+        );
+        for event in &raw {
+            rotated.inner().store_event(event).unwrap();
+        }
+        assert_eq!(rotated.get_events("s1").unwrap().len(), 2);
+
+        let wrong = EncryptedStorage::new(InMemoryStorage::new(), StaticKeyProvider::new("k1", [9; 32]));
+        wrong.inner().store_event(&raw[0]).unwrap();
+        let err = wrong.get_events("s1").unwrap_err();
+        assert_eq!(err.error_code(), "STORAGE_ENCRYPTION_ERROR");
+    }
+
+    #[test]
+    fn test_encrypted_detects_moved_ciphertext() {
+        let storage = EncryptedStorage::new(InMemoryStorage::new(), StaticKeyProvider::new("k1", [3; 32]));
+        store_session(&storage);
+        let mut raw = storage.inner().get_events("s1").unwrap();
+        let swapped = raw[0].payload.clone();
+        raw[1].payload = swapped;
+
+        assert!(storage.decrypt(raw[1].clone()).is_err());
+    }
+}
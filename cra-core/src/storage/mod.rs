@@ -2,7 +2,15 @@
 //!
 //! This module provides traits and implementations for persisting CRA data.
 //! The default is in-memory storage, but users can implement custom backends
-//! for SQLite, PostgreSQL, files, etc.
+//! for SQLite, PostgreSQL, files, etc. For high write rates on local disk,
+//! use [`SegmentLogStorage`], a segmented append-only log; [`TieredStorage`]
+//! pairs a fast hot tier with a durable one. With the
+//! `object-storage` feature, `ObjectStorage` archives closed sessions to
+//! S3-compatible object storage. With the `encryption` feature,
+//! `EncryptedStorage` wraps any backend to encrypt payloads at rest.
+//! [`StorageConfig`] and [`StorageFactory`] select a backend from
+//! configuration. [`EventQuery`] searches events across sessions.
+//! [`SessionStore`]s persist live session state for crash recovery.
 //!
 //! # Example
 //!
@@ -22,6 +30,40 @@ use std::sync::RwLock;
 use crate::error::{CRAError, Result};
 use crate::trace::TRACEEvent;
 
+mod query;
+mod session_store;
+#[cfg(not(feature = "minimal"))]
+mod config;
+#[cfg(not(feature = "minimal"))]
+mod segment;
+#[cfg(not(feature = "minimal"))]
+mod tiered;
+#[cfg(feature = "object-storage")]
+mod object;
+#[cfg(feature = "encryption")]
+mod encrypted;
+#[cfg(feature = "redb-store")]
+mod redb_store;
+
+pub use query::{EventPage, EventQuery, PayloadPredicate, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+pub use session_store::{InMemorySessionStore, SessionStore};
+#[cfg(feature = "redb-store")]
+pub use redb_store::RedbSessionStore;
+#[cfg(not(feature = "minimal"))]
+pub use config::{BackendConstructor, StorageConfig, StorageFactory};
+#[cfg(not(feature = "minimal"))]
+pub use segment::{CompactorHandle, FsyncPolicy, SegmentLogConfig, SegmentLogStats, SegmentLogStorage};
+#[cfg(not(feature = "minimal"))]
+pub use tiered::{TieredConfig, TieredStats, TieredStorage};
+#[cfg(feature = "encryption")]
+pub use encrypted::{EncryptedPayload, EncryptedStorage, KeyProvider, StaticKeyProvider, ENCRYPTION_ALGORITHM};
+#[cfg(feature = "object-storage")]
+pub use object::{
+    ArchiveConfig, ArchivedSession, DirectoryObjectStore, InMemoryObjectStore, ObjectMetadata,
+    ObjectStorage, ObjectStore, META_AGENT_ID, META_DATE, META_EVENT_COUNT, META_FINAL_HASH,
+    META_SESSION_ID,
+};
+
 /// Storage backend trait for persisting traces
 ///
 /// Implement this trait to add custom persistence backends.
@@ -45,6 +87,26 @@ pub trait StorageBackend: Send + Sync {
     /// Delete all events for a session
     fn delete_session(&self, session_id: &str) -> Result<()>;
 
+    /// Get the IDs of all sessions with stored events
+    fn list_sessions(&self) -> Result<Vec<String>>;
+
+    /// Find events matching a query across sessions
+    ///
+    /// The default implementation reads every candidate session; backends
+    /// with an index over event type, time or payload should override it.
+    fn search_events(&self, query: &EventQuery) -> Result<EventPage> {
+        query.validate()?;
+        let sessions = match &query.session_id {
+            Some(session_id) => vec![session_id.clone()],
+            None => self.list_sessions()?,
+        };
+        let mut events = Vec::new();
+        for session_id in sessions {
+            events.extend(self.get_events(&session_id)?.into_iter().filter(|e| query.matches(e)));
+        }
+        Ok(query.paginate(events))
+    }
+
     /// Check if backend is healthy
     fn health_check(&self) -> Result<()>;
 
@@ -140,6 +202,11 @@ impl StorageBackend for InMemoryStorage {
         Ok(())
     }
 
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let events = self.events.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(events.keys().cloned().collect())
+    }
+
     fn health_check(&self) -> Result<()> {
         // In-memory is always healthy if we can acquire the lock
         let _events = self.events.read().map_err(|_| CRAError::StorageLocked)?;
@@ -151,55 +218,293 @@ impl StorageBackend for InMemoryStorage {
     }
 }
 
+/// Durability settings for [`FileStorage`]
+///
+/// The default writes and fsyncs every event before `store_event` returns.
+/// Group commit trades that for throughput and must be asked for explicitly
+/// with a larger `max_buffered_events` or `fsync_interval`.
+#[cfg(not(feature = "minimal"))]
+#[derive(Debug, Clone)]
+pub struct FileStorageConfig {
+    /// Fsync pending writes once this much time has passed since the last
+    /// sync; `None` syncs only on `flush`, critical events and drop. There is
+    /// no timer: the interval is checked when the next event is written
+    pub fsync_interval: Option<std::time::Duration>,
+    /// Events buffered in process before they are written to the OS
+    /// (1 writes every event through immediately)
+    pub max_buffered_events: usize,
+    /// Event types that force an immediate flush and fsync of all pending
+    /// writes, e.g. policy denials that must survive a crash
+    pub sync_on_event_types: Vec<crate::trace::EventType>,
+}
+
+#[cfg(not(feature = "minimal"))]
+impl Default for FileStorageConfig {
+    fn default() -> Self {
+        Self {
+            fsync_interval: Some(std::time::Duration::ZERO),
+            max_buffered_events: 1,
+            sync_on_event_types: vec![crate::trace::EventType::ActionDenied],
+        }
+    }
+}
+
+#[cfg(not(feature = "minimal"))]
+impl FileStorageConfig {
+    /// Set the group-commit fsync interval
+    pub fn fsync_interval(mut self, interval: Option<std::time::Duration>) -> Self {
+        self.fsync_interval = interval;
+        self
+    }
+
+    /// Set how many events are buffered before writing to the OS
+    pub fn max_buffered_events(mut self, events: usize) -> Self {
+        self.max_buffered_events = events.max(1);
+        self
+    }
+
+    /// Set the event types that force an immediate fsync
+    pub fn sync_on_event_types(mut self, event_types: Vec<crate::trace::EventType>) -> Self {
+        self.sync_on_event_types = event_types;
+        self
+    }
+}
+
+/// Open append handles and group-commit bookkeeping
+#[cfg(not(feature = "minimal"))]
+#[derive(Debug)]
+struct FileWriters {
+    writers: HashMap<String, std::io::BufWriter<std::fs::File>>,
+    /// Events written since the last flush to the OS
+    buffered: usize,
+    /// Sessions with data not yet fsynced
+    unsynced: std::collections::HashSet<String>,
+    last_sync: std::time::Instant,
+}
+
 /// File-based storage backend (JSONL files)
 ///
 /// Stores events as newline-delimited JSON files, one per session.
-/// Suitable for development and small-scale deployments.
+/// Suitable for development and small-scale deployments. Not available
+/// with the `minimal` feature.
+///
+/// By default every event is written and fsynced before `store_event`
+/// returns. With group commit configured, appends go through buffered
+/// per-session handles: written to the OS every `max_buffered_events` events
+/// and fsynced by the first write after `fsync_interval` has elapsed, or
+/// immediately for `sync_on_event_types`. Nothing syncs in the background,
+/// so events still pending after the last write reach disk only on `flush`
+/// or drop. On open, a trailing line left incomplete by a crash is
+/// truncated away.
+#[cfg(not(feature = "minimal"))]
 #[derive(Debug)]
 pub struct FileStorage {
     directory: std::path::PathBuf,
+    config: FileStorageConfig,
+    writers: std::sync::Mutex<FileWriters>,
 }
 
+/// Most session files kept open at once
+#[cfg(not(feature = "minimal"))]
+const MAX_OPEN_FILES: usize = 256;
+
+#[cfg(not(feature = "minimal"))]
 impl FileStorage {
     /// Create a new file storage in the given directory
     pub fn new<P: Into<std::path::PathBuf>>(directory: P) -> Result<Self> {
+        Self::with_config(directory, FileStorageConfig::default())
+    }
+
+    /// Create a file storage with explicit durability settings
+    pub fn with_config<P: Into<std::path::PathBuf>>(directory: P, config: FileStorageConfig) -> Result<Self> {
         let dir = directory.into();
         std::fs::create_dir_all(&dir).map_err(|e| CRAError::IoError {
             message: format!("Failed to create storage directory: {}", e),
         })?;
-        Ok(Self { directory: dir })
+        let storage = Self {
+            directory: dir,
+            config,
+            writers: std::sync::Mutex::new(FileWriters {
+                writers: HashMap::new(),
+                buffered: 0,
+                unsynced: std::collections::HashSet::new(),
+                last_sync: std::time::Instant::now(),
+            }),
+        };
+        storage.recover()?;
+        Ok(storage)
+    }
+
+    /// Get the durability settings
+    pub fn config(&self) -> &FileStorageConfig {
+        &self.config
+    }
+
+    /// Truncate incomplete trailing lines left by a crash
+    ///
+    /// Runs automatically on open. Returns the number of files repaired.
+    pub fn recover(&self) -> Result<usize> {
+        let entries = std::fs::read_dir(&self.directory).map_err(|e| CRAError::IoError {
+            message: format!("Failed to list storage directory: {}", e),
+        })?;
+        let mut repaired = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl")
+                && truncate_partial_line(&path).map_err(|e| CRAError::IoError {
+                    message: format!("Failed to recover {}: {}", path.display(), e),
+                })?
+            {
+                repaired += 1;
+            }
+        }
+        Ok(repaired)
+    }
+
+    /// Write all buffered events to disk and fsync them
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.lock()?;
+        Self::commit(&mut state, true)
     }
 
     fn session_file(&self, session_id: &str) -> std::path::PathBuf {
         self.directory.join(format!("{}.jsonl", session_id))
     }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, FileWriters>> {
+        self.writers.lock().map_err(|_| CRAError::StorageLocked)
+    }
+
+    /// Flush every open writer, and fsync the ones with unsynced data
+    fn commit(state: &mut FileWriters, fsync: bool) -> Result<()> {
+        use std::io::Write;
+
+        for writer in state.writers.values_mut() {
+            writer.flush().map_err(|e| CRAError::IoError {
+                message: format!("Failed to write: {}", e),
+            })?;
+        }
+        state.buffered = 0;
+
+        if fsync {
+            for session_id in state.unsynced.drain() {
+                if let Some(writer) = state.writers.get(&session_id) {
+                    writer.get_ref().sync_data().map_err(|e| CRAError::IoError {
+                        message: format!("Failed to sync: {}", e),
+                    })?;
+                }
+            }
+            state.last_sync = std::time::Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Make a session's buffered events visible to readers
+    fn flush_session(&self, session_id: &str) -> Result<()> {
+        use std::io::Write;
+
+        let mut state = self.lock()?;
+        if let Some(writer) = state.writers.get_mut(session_id) {
+            writer.flush().map_err(|e| CRAError::IoError {
+                message: format!("Failed to write: {}", e),
+            })?;
+        }
+        Ok(())
+    }
 }
 
+/// Drop bytes after the last newline; returns true if the file changed
+#[cfg(not(feature = "minimal"))]
+fn truncate_partial_line(path: &std::path::Path) -> std::io::Result<bool> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let mut end = len;
+    let mut chunk = [0u8; 4096];
+    while end > 0 {
+        let start = end.saturating_sub(chunk.len() as u64);
+        let buf = &mut chunk[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(buf)?;
+        if let Some(pos) = buf.iter().rposition(|b| *b == b'\n') {
+            end = start + pos as u64 + 1;
+            break;
+        }
+        end = start;
+    }
+
+    if end == len {
+        return Ok(false);
+    }
+    file.set_len(end)?;
+    file.sync_data()?;
+    Ok(true)
+}
+
+#[cfg(not(feature = "minimal"))]
+impl Drop for FileStorage {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.writers.lock() {
+            let _ = Self::commit(&mut state, true);
+        }
+    }
+}
+
+#[cfg(not(feature = "minimal"))]
 impl StorageBackend for FileStorage {
     fn store_event(&self, event: &TRACEEvent) -> Result<()> {
         use std::io::Write;
 
-        let path = self.session_file(&event.session_id);
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .map_err(|e| CRAError::IoError {
-                message: format!("Failed to open file: {}", e),
-            })?;
-
         let line = serde_json::to_string(event)?;
+        let mut state = self.lock()?;
+
+        if !state.writers.contains_key(&event.session_id) {
+            if state.writers.len() >= MAX_OPEN_FILES {
+                Self::commit(&mut state, true)?;
+                state.writers.clear();
+            }
+            let path = self.session_file(&event.session_id);
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| CRAError::IoError {
+                    message: format!("Failed to open file: {}", e),
+                })?;
+            state
+                .writers
+                .insert(event.session_id.clone(), std::io::BufWriter::new(file));
+        }
 
-        writeln!(file, "{}", line).map_err(|e| CRAError::IoError {
+        let writer = state
+            .writers
+            .get_mut(&event.session_id)
+            .expect("writer opened above");
+        writeln!(writer, "{}", line).map_err(|e| CRAError::IoError {
             message: format!("Failed to write: {}", e),
         })?;
-
-        Ok(())
+        state.buffered += 1;
+        state.unsynced.insert(event.session_id.clone());
+
+        let critical = self.config.sync_on_event_types.contains(&event.event_type);
+        let sync_due = self
+            .config
+            .fsync_interval
+            .is_some_and(|interval| state.last_sync.elapsed() >= interval);
+        if critical || sync_due {
+            Self::commit(&mut state, true)
+        } else if state.buffered >= self.config.max_buffered_events {
+            Self::commit(&mut state, false)
+        } else {
+            Ok(())
+        }
     }
 
     fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
         use std::io::BufRead;
 
+        self.flush_session(session_id)?;
         let path = self.session_file(session_id);
         if !path.exists() {
             return Ok(Vec::new());
@@ -243,6 +548,11 @@ impl StorageBackend for FileStorage {
     }
 
     fn delete_session(&self, session_id: &str) -> Result<()> {
+        {
+            let mut state = self.lock()?;
+            state.writers.remove(session_id);
+            state.unsynced.remove(session_id);
+        }
         let path = self.session_file(session_id);
         if path.exists() {
             std::fs::remove_file(&path).map_err(|e| CRAError::IoError {
@@ -252,6 +562,23 @@ impl StorageBackend for FileStorage {
         Ok(())
     }
 
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let entries = std::fs::read_dir(&self.directory).map_err(|e| CRAError::IoError {
+            message: format!("Failed to list storage directory: {}", e),
+        })?;
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    path.file_stem().and_then(|s| s.to_str()).map(String::from)
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
     fn health_check(&self) -> Result<()> {
         if self.directory.exists() && self.directory.is_dir() {
             Ok(())
@@ -304,6 +631,10 @@ impl StorageBackend for NullStorage {
         Ok(())
     }
 
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     fn health_check(&self) -> Result<()> {
         Ok(())
     }
@@ -348,6 +679,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "minimal"))]
     fn test_file_storage() {
         let temp_dir = std::env::temp_dir().join("cra-test-storage");
         let storage = FileStorage::new(&temp_dir).unwrap();
@@ -364,6 +696,76 @@ mod tests {
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
+    #[test]
+    #[cfg(not(feature = "minimal"))]
+    fn test_file_storage_group_commit() {
+        let temp_dir = std::env::temp_dir().join("cra-test-storage-group-commit");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let config = FileStorageConfig::default()
+            .fsync_interval(None)
+            .max_buffered_events(100);
+        let storage = FileStorage::with_config(&temp_dir, config).unwrap();
+        let path = temp_dir.join("s1.jsonl");
+
+        storage.store_event(&create_test_event("s1", 0)).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        // Reads see buffered events
+        assert_eq!(storage.get_event_count("s1").unwrap(), 1);
+
+        // Critical events force everything pending to disk
+        storage.store_event(&create_test_event("s1", 1)).unwrap();
+        let mut denied = create_test_event("s1", 2);
+        denied.event_type = EventType::ActionDenied;
+        storage.store_event(&denied).unwrap();
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(on_disk.lines().count(), 3);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    #[cfg(not(feature = "minimal"))]
+    fn test_file_storage_default_syncs_every_event() {
+        let temp_dir = std::env::temp_dir().join("cra-test-storage-default-sync");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let storage = FileStorage::new(&temp_dir).unwrap();
+        storage.store_event(&create_test_event("s1", 0)).unwrap();
+
+        // Skip Drop, as a crash would: the event must already be on disk
+        std::mem::forget(storage);
+        let on_disk = std::fs::read_to_string(temp_dir.join("s1.jsonl")).unwrap();
+        assert_eq!(on_disk.lines().count(), 1);
+
+        let reopened = FileStorage::new(&temp_dir).unwrap();
+        assert_eq!(reopened.get_events("s1").unwrap().len(), 1);
+
+        drop(reopened);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    #[cfg(not(feature = "minimal"))]
+    fn test_file_storage_recovers_partial_line() {
+        let temp_dir = std::env::temp_dir().join("cra-test-storage-recovery");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        {
+            let storage = FileStorage::new(&temp_dir).unwrap();
+            storage.store_event(&create_test_event("s1", 0)).unwrap();
+        }
+
+        let path = temp_dir.join("s1.jsonl");
+        let mut content = std::fs::read(&path).unwrap();
+        content.extend_from_slice(b"{\"trace_version\":\"1.0\",\"event_");
+        std::fs::write(&path, content).unwrap();
+
+        let storage = FileStorage::new(&temp_dir).unwrap();
+        assert_eq!(storage.get_events("s1").unwrap().len(), 1);
+        assert_eq!(storage.recover().unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
     #[test]
     fn test_null_storage() {
         let storage = NullStorage::new();
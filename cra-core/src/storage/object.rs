@@ -0,0 +1,723 @@
+//! Object storage archive backend
+//!
+//! Archives closed sessions to S3-compatible object storage as gzipped JSONL
+//! objects, keeping only active sessions in a hot backend. Archived sessions
+//! are fetched back lazily when read, so verification and replay work the
+//! same whether a session is hot or archived.
+//!
+//! The object store itself is pluggable through [`ObjectStore`], whose
+//! methods mirror the S3 PutObject/GetObject/HeadObject/DeleteObject/
+//! ListObjectsV2 calls; an adapter over any S3-compatible client (AWS, MinIO,
+//! R2, GCS interop) is a thin wrapper. [`InMemoryObjectStore`] and
+//! [`DirectoryObjectStore`] are provided for tests and local development.
+//!
+//! Requires the `object-storage` feature.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use super::StorageBackend;
+use crate::error::{CRAError, Result};
+use crate::trace::{ChainVerification, ChainVerifier, EventType, TRACEEvent};
+
+/// User metadata attached to an object (S3 `x-amz-meta-*` headers)
+pub type ObjectMetadata = BTreeMap<String, String>;
+
+/// Metadata key for the archived session ID
+pub const META_SESSION_ID: &str = "cra-session-id";
+/// Metadata key for the agent that ran the session
+pub const META_AGENT_ID: &str = "cra-agent-id";
+/// Metadata key for the session date (YYYY-MM-DD, from the first event)
+pub const META_DATE: &str = "cra-date";
+/// Metadata key for the hash of the last event in the chain
+pub const META_FINAL_HASH: &str = "cra-final-hash";
+/// Metadata key for the number of archived events
+pub const META_EVENT_COUNT: &str = "cra-event-count";
+
+/// Minimal S3-compatible object store interface
+pub trait ObjectStore: Send + Sync {
+    /// Store an object, replacing any existing one (PutObject)
+    fn put_object(&self, key: &str, body: &[u8], metadata: &ObjectMetadata) -> Result<()>;
+
+    /// Fetch an object body (GetObject)
+    fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Fetch object metadata without the body (HeadObject)
+    fn head_object(&self, key: &str) -> Result<Option<ObjectMetadata>>;
+
+    /// Delete an object; deleting a missing key is not an error (DeleteObject)
+    fn delete_object(&self, key: &str) -> Result<()>;
+
+    /// List keys starting with a prefix (ListObjectsV2)
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Check if the store is reachable
+    fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Get store name (for logging/debugging)
+    fn name(&self) -> &'static str;
+}
+
+/// In-memory object store
+#[derive(Debug, Default)]
+pub struct InMemoryObjectStore {
+    objects: RwLock<BTreeMap<String, (Vec<u8>, ObjectMetadata)>>,
+}
+
+impl InMemoryObjectStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ObjectStore for InMemoryObjectStore {
+    fn put_object(&self, key: &str, body: &[u8], metadata: &ObjectMetadata) -> Result<()> {
+        let mut objects = self.objects.write().map_err(|_| CRAError::StorageLocked)?;
+        objects.insert(key.to_string(), (body.to_vec(), metadata.clone()));
+        Ok(())
+    }
+
+    fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let objects = self.objects.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(objects.get(key).map(|(body, _)| body.clone()))
+    }
+
+    fn head_object(&self, key: &str) -> Result<Option<ObjectMetadata>> {
+        let objects = self.objects.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(objects.get(key).map(|(_, metadata)| metadata.clone()))
+    }
+
+    fn delete_object(&self, key: &str) -> Result<()> {
+        let mut objects = self.objects.write().map_err(|_| CRAError::StorageLocked)?;
+        objects.remove(key);
+        Ok(())
+    }
+
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let objects = self.objects.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(objects
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn name(&self) -> &'static str {
+        "in-memory"
+    }
+}
+
+/// Object store backed by a local directory
+///
+/// Keys map to relative paths; metadata is kept in a `<key>.meta.json`
+/// sidecar file.
+#[derive(Debug)]
+pub struct DirectoryObjectStore {
+    directory: PathBuf,
+}
+
+impl DirectoryObjectStore {
+    /// Create a store rooted at the given directory
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).map_err(|e| CRAError::IoError {
+            message: format!("Failed to create object directory: {}", e),
+        })?;
+        Ok(Self { directory })
+    }
+
+    fn object_path(&self, key: &str) -> PathBuf {
+        self.directory.join(key)
+    }
+
+    fn metadata_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.meta.json", key))
+    }
+
+    fn read_optional(path: &PathBuf) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(CRAError::IoError {
+                message: format!("Failed to read object: {}", e),
+            }),
+        }
+    }
+}
+
+impl ObjectStore for DirectoryObjectStore {
+    fn put_object(&self, key: &str, body: &[u8], metadata: &ObjectMetadata) -> Result<()> {
+        let path = self.object_path(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| CRAError::IoError {
+                message: format!("Failed to create object directory: {}", e),
+            })?;
+        }
+        std::fs::write(self.metadata_path(key), serde_json::to_vec(metadata)?)
+            .and_then(|_| std::fs::write(&path, body))
+            .map_err(|e| CRAError::IoError {
+                message: format!("Failed to write object: {}", e),
+            })
+    }
+
+    fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Self::read_optional(&self.object_path(key))
+    }
+
+    fn head_object(&self, key: &str) -> Result<Option<ObjectMetadata>> {
+        if !self.object_path(key).exists() {
+            return Ok(None);
+        }
+        match Self::read_optional(&self.metadata_path(key))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(Some(ObjectMetadata::new())),
+        }
+    }
+
+    fn delete_object(&self, key: &str) -> Result<()> {
+        for path in [self.object_path(key), self.metadata_path(key)] {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(CRAError::IoError {
+                        message: format!("Failed to delete object: {}", e),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut pending = vec![self.directory.clone()];
+        while let Some(dir) = pending.pop() {
+            let entries = std::fs::read_dir(&dir).map_err(|e| CRAError::IoError {
+                message: format!("Failed to list objects: {}", e),
+            })?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&self.directory) else {
+                    continue;
+                };
+                let key = relative.to_string_lossy().replace('\\', "/");
+                if !key.ends_with(".meta.json") && key.starts_with(prefix) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn health_check(&self) -> Result<()> {
+        if self.directory.is_dir() {
+            Ok(())
+        } else {
+            Err(CRAError::IoError {
+                message: "Object directory does not exist".to_string(),
+            })
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "directory"
+    }
+}
+
+/// Summary of an archived session, read from its object metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedSession {
+    /// Session ID
+    pub session_id: String,
+    /// Object key
+    pub key: String,
+    /// Agent that ran the session, if recorded in session.started
+    pub agent_id: Option<String>,
+    /// Date of the first event (YYYY-MM-DD)
+    pub date: String,
+    /// Hash of the last event in the chain
+    pub final_hash: String,
+    /// Number of archived events
+    pub event_count: usize,
+}
+
+impl ArchivedSession {
+    fn to_metadata(&self) -> ObjectMetadata {
+        let mut metadata = ObjectMetadata::new();
+        metadata.insert(META_SESSION_ID.to_string(), self.session_id.clone());
+        if let Some(agent_id) = &self.agent_id {
+            metadata.insert(META_AGENT_ID.to_string(), agent_id.clone());
+        }
+        metadata.insert(META_DATE.to_string(), self.date.clone());
+        metadata.insert(META_FINAL_HASH.to_string(), self.final_hash.clone());
+        metadata.insert(META_EVENT_COUNT.to_string(), self.event_count.to_string());
+        metadata
+    }
+
+    fn from_metadata(key: &str, metadata: &ObjectMetadata) -> Self {
+        let get = |name: &str| metadata.get(name).cloned().unwrap_or_default();
+        Self {
+            session_id: get(META_SESSION_ID),
+            key: key.to_string(),
+            agent_id: metadata.get(META_AGENT_ID).cloned(),
+            date: get(META_DATE),
+            final_hash: get(META_FINAL_HASH),
+            event_count: get(META_EVENT_COUNT).parse().unwrap_or(0),
+        }
+    }
+}
+
+/// Configuration for archiving to object storage
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    /// Key prefix for archived sessions (e.g. `traces/`)
+    pub prefix: String,
+    /// Archive a session as soon as its session.ended event is stored
+    pub archive_on_session_end: bool,
+    /// Sessions idle for longer than this are archived by `apply_lifecycle`
+    pub hot_retention: Option<chrono::Duration>,
+    /// Number of restored sessions kept in memory
+    pub cache_sessions: usize,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            prefix: "traces/".to_string(),
+            archive_on_session_end: true,
+            hot_retention: None,
+            cache_sessions: 16,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    /// Set the key prefix
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set whether sessions are archived when they end
+    pub fn archive_on_session_end(mut self, enabled: bool) -> Self {
+        self.archive_on_session_end = enabled;
+        self
+    }
+
+    /// Set how long idle sessions stay in the hot backend
+    pub fn hot_retention(mut self, retention: chrono::Duration) -> Self {
+        self.hot_retention = Some(retention);
+        self
+    }
+
+    /// Set the number of restored sessions cached in memory
+    pub fn cache_sessions(mut self, count: usize) -> Self {
+        self.cache_sessions = count;
+        self
+    }
+}
+
+/// Storage backend that tiers closed sessions from a hot backend into
+/// object storage
+///
+/// Events are written to the hot backend. Sessions move to the object store
+/// when they end (if `archive_on_session_end` is set), when
+/// [`apply_lifecycle`](Self::apply_lifecycle) finds them idle past the hot
+/// retention, or when [`archive_session`](Self::archive_session) is called.
+/// Reads fall back to the archive when the hot backend has no events.
+pub struct ObjectStorage {
+    hot: Arc<dyn StorageBackend>,
+    store: Arc<dyn ObjectStore>,
+    config: ArchiveConfig,
+    /// Last event time of sessions written through this backend
+    activity: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Recently restored archives, oldest first
+    cache: Mutex<VecDeque<(String, Arc<Vec<TRACEEvent>>)>>,
+}
+
+impl std::fmt::Debug for ObjectStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectStorage")
+            .field("hot", &self.hot.name())
+            .field("store", &self.store.name())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl ObjectStorage {
+    /// Create an archive backend with default configuration
+    pub fn new(hot: Arc<dyn StorageBackend>, store: Arc<dyn ObjectStore>) -> Self {
+        Self::with_config(hot, store, ArchiveConfig::default())
+    }
+
+    /// Create an archive backend with the given configuration
+    pub fn with_config(
+        hot: Arc<dyn StorageBackend>,
+        store: Arc<dyn ObjectStore>,
+        config: ArchiveConfig,
+    ) -> Self {
+        Self {
+            hot,
+            store,
+            config,
+            activity: RwLock::new(HashMap::new()),
+            cache: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ArchiveConfig {
+        &self.config
+    }
+
+    /// Object key for a session
+    pub fn object_key(&self, session_id: &str) -> String {
+        format!("{}{}.jsonl.gz", self.config.prefix, session_id)
+    }
+
+    /// Move a session from the hot backend into the object store
+    pub fn archive_session(&self, session_id: &str) -> Result<ArchivedSession> {
+        let events = self.hot.get_events(session_id)?;
+        let (first, last) = match (events.first(), events.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return Err(CRAError::SessionNotFound {
+                    session_id: session_id.to_string(),
+                })
+            }
+        };
+
+        let archived = ArchivedSession {
+            session_id: session_id.to_string(),
+            key: self.object_key(session_id),
+            agent_id: events
+                .iter()
+                .find(|e| e.event_type == EventType::SessionStarted)
+                .and_then(|e| e.payload.get("agent_id"))
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            date: first.timestamp.format("%Y-%m-%d").to_string(),
+            final_hash: last.event_hash.clone(),
+            event_count: events.len(),
+        };
+
+        let body = encode_events(&events)?;
+        self.store
+            .put_object(&archived.key, &body, &archived.to_metadata())?;
+        self.hot.delete_session(session_id)?;
+        if let Ok(mut activity) = self.activity.write() {
+            activity.remove(session_id);
+        }
+        Ok(archived)
+    }
+
+    /// Archive every session idle for longer than the hot retention
+    ///
+    /// Only sessions written through this backend are tracked. Returns the
+    /// archived session IDs; does nothing without a `hot_retention`.
+    pub fn apply_lifecycle(&self) -> Result<Vec<String>> {
+        let Some(retention) = self.config.hot_retention else {
+            return Ok(Vec::new());
+        };
+        let cutoff = crate::clock::now() - retention;
+        let idle: Vec<String> = {
+            let activity = self.activity.read().map_err(|_| CRAError::StorageLocked)?;
+            activity
+                .iter()
+                .filter(|(_, last)| **last < cutoff)
+                .map(|(session_id, _)| session_id.clone())
+                .collect()
+        };
+
+        for session_id in &idle {
+            self.archive_session(session_id)?;
+        }
+        Ok(idle)
+    }
+
+    /// Get the summary of an archived session
+    pub fn archived_session(&self, session_id: &str) -> Result<Option<ArchivedSession>> {
+        let key = self.object_key(session_id);
+        Ok(self
+            .store
+            .head_object(&key)?
+            .map(|metadata| ArchivedSession::from_metadata(&key, &metadata)))
+    }
+
+    /// List the IDs of all archived sessions
+    pub fn archived_session_ids(&self) -> Result<Vec<String>> {
+        Ok(self
+            .store
+            .list_objects(&self.config.prefix)?
+            .into_iter()
+            .filter_map(|key| {
+                key.strip_prefix(&self.config.prefix)
+                    .and_then(|rest| rest.strip_suffix(".jsonl.gz"))
+                    .map(String::from)
+            })
+            .collect())
+    }
+
+    /// Fetch an archived session's events, using the restore cache
+    pub fn restore_session(&self, session_id: &str) -> Result<Option<Arc<Vec<TRACEEvent>>>> {
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((_, events)) = cache.iter().find(|(id, _)| id == session_id) {
+                return Ok(Some(events.clone()));
+            }
+        }
+
+        let Some(body) = self.store.get_object(&self.object_key(session_id))? else {
+            return Ok(None);
+        };
+        let events = Arc::new(decode_events(&body)?);
+
+        if self.config.cache_sessions > 0 {
+            if let Ok(mut cache) = self.cache.lock() {
+                if cache.len() >= self.config.cache_sessions {
+                    cache.pop_front();
+                }
+                cache.push_back((session_id.to_string(), events.clone()));
+            }
+        }
+        Ok(Some(events))
+    }
+
+    /// Verify an archived session's hash chain and its final hash tag
+    pub fn verify_archived(&self, session_id: &str) -> Result<ChainVerification> {
+        let not_found = || CRAError::SessionNotFound {
+            session_id: session_id.to_string(),
+        };
+        let summary = self.archived_session(session_id)?.ok_or_else(not_found)?;
+        let events = self.restore_session(session_id)?.ok_or_else(not_found)?;
+
+        let mut verification = ChainVerifier::verify(&events);
+        let final_hash = events.last().map(|e| e.event_hash.as_str()).unwrap_or_default();
+        if verification.is_valid && final_hash != summary.final_hash {
+            verification.is_valid = false;
+            verification.error_message = Some(format!(
+                "Archived chain ends at {} but object metadata records {}",
+                final_hash, summary.final_hash
+            ));
+        }
+        Ok(verification)
+    }
+
+    fn forget(&self, session_id: &str) {
+        if let Ok(mut activity) = self.activity.write() {
+            activity.remove(session_id);
+        }
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.retain(|(id, _)| id != session_id);
+        }
+    }
+}
+
+impl StorageBackend for ObjectStorage {
+    fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+        self.hot.store_event(event)?;
+        if let Ok(mut activity) = self.activity.write() {
+            activity.insert(event.session_id.clone(), event.timestamp);
+        }
+        if self.config.archive_on_session_end && event.event_type == EventType::SessionEnded {
+            self.archive_session(&event.session_id)?;
+        }
+        Ok(())
+    }
+
+    fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        let events = self.hot.get_events(session_id)?;
+        if !events.is_empty() {
+            return Ok(events);
+        }
+        Ok(self
+            .restore_session(session_id)?
+            .map(|events| events.as_ref().clone())
+            .unwrap_or_default())
+    }
+
+    fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+        Ok(self
+            .get_events(session_id)?
+            .into_iter()
+            .filter(|e| e.event_type.to_string() == event_type)
+            .collect())
+    }
+
+    fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+        let events = self.get_events(session_id)?;
+        Ok(events.into_iter().rev().take(n).rev().collect())
+    }
+
+    fn get_event_count(&self, session_id: &str) -> Result<usize> {
+        let count = self.hot.get_event_count(session_id)?;
+        if count > 0 {
+            return Ok(count);
+        }
+        Ok(self
+            .archived_session(session_id)?
+            .map(|archived| archived.event_count)
+            .unwrap_or(0))
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.hot.delete_session(session_id)?;
+        self.store.delete_object(&self.object_key(session_id))?;
+        self.forget(session_id);
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let mut sessions = self.hot.list_sessions()?;
+        sessions.extend(self.archived_session_ids()?);
+        sessions.sort();
+        sessions.dedup();
+        Ok(sessions)
+    }
+
+    fn health_check(&self) -> Result<()> {
+        self.hot.health_check()?;
+        self.store.health_check()
+    }
+
+    fn name(&self) -> &'static str {
+        "object"
+    }
+}
+
+fn encode_events(events: &[TRACEEvent]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for event in events {
+        serde_json::to_writer(&mut encoder, event)?;
+        encoder.write_all(b"\n").map_err(|e| CRAError::IoError {
+            message: format!("Failed to compress archive: {}", e),
+        })?;
+    }
+    encoder.finish().map_err(|e| CRAError::IoError {
+        message: format!("Failed to compress archive: {}", e),
+    })
+}
+
+fn decode_events(body: &[u8]) -> Result<Vec<TRACEEvent>> {
+    let mut jsonl = String::new();
+    GzDecoder::new(body)
+        .read_to_string(&mut jsonl)
+        .map_err(|e| CRAError::IoError {
+            message: format!("Failed to decompress archive: {}", e),
+        })?;
+    jsonl
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Into::into))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemoryStorage;
+    use crate::TraceCollector;
+    use serde_json::json;
+
+    fn session_events(session_id: &str) -> Vec<TRACEEvent> {
+        let mut collector = TraceCollector::new();
+        collector
+            .emit(session_id, EventType::SessionStarted, json!({"agent_id": "agent-7"}))
+            .unwrap();
+        collector
+            .emit(session_id, EventType::ActionExecuted, json!({"action_id": "a"}))
+            .unwrap();
+        collector
+            .emit(session_id, EventType::SessionEnded, json!({"reason": "done"}))
+            .unwrap();
+        collector.get_events(session_id).unwrap()
+    }
+
+    #[test]
+    fn test_archive_on_session_end_and_lazy_restore() {
+        let hot = Arc::new(InMemoryStorage::new());
+        let store = Arc::new(InMemoryObjectStore::new());
+        let storage = ObjectStorage::new(hot.clone(), store.clone());
+
+        let events = session_events("s1");
+        for event in &events {
+            storage.store_event(event).unwrap();
+        }
+
+        assert_eq!(hot.get_event_count("s1").unwrap(), 0);
+        let archived = storage.archived_session("s1").unwrap().unwrap();
+        assert_eq!(archived.agent_id.as_deref(), Some("agent-7"));
+        assert_eq!(archived.event_count, 3);
+        assert_eq!(archived.final_hash, events[2].event_hash);
+        assert_eq!(storage.archived_session_ids().unwrap(), vec!["s1".to_string()]);
+
+        let hashes = |events: &[TRACEEvent]| events.iter().map(|e| e.event_hash.clone()).collect::<Vec<_>>();
+        assert_eq!(hashes(&storage.get_events("s1").unwrap()), hashes(&events));
+        assert_eq!(storage.get_event_count("s1").unwrap(), 3);
+        assert!(storage.verify_archived("s1").unwrap().is_valid);
+
+        storage.delete_session("s1").unwrap();
+        assert!(store.list_objects("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_verify_detects_final_hash_mismatch() {
+        let store = Arc::new(InMemoryObjectStore::new());
+        let storage = ObjectStorage::with_config(
+            Arc::new(InMemoryStorage::new()),
+            store.clone(),
+            ArchiveConfig::default().archive_on_session_end(false),
+        );
+        for event in session_events("s1") {
+            storage.store_event(&event).unwrap();
+        }
+        let key = storage.object_key("s1");
+        assert!(store.head_object(&key).unwrap().is_none());
+
+        storage.archive_session("s1").unwrap();
+        let body = store.get_object(&key).unwrap().unwrap();
+        let mut metadata = store.head_object(&key).unwrap().unwrap();
+        metadata.insert(META_FINAL_HASH.to_string(), "0".repeat(64));
+        store.put_object(&key, &body, &metadata).unwrap();
+
+        assert!(!storage.verify_archived("s1").unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_lifecycle_tiering_with_directory_store() {
+        let dir = std::env::temp_dir().join(format!("cra-object-store-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = ObjectStorage::with_config(
+            Arc::new(InMemoryStorage::new()),
+            Arc::new(DirectoryObjectStore::new(&dir).unwrap()),
+            ArchiveConfig::default()
+                .archive_on_session_end(false)
+                .hot_retention(chrono::Duration::zero()),
+        );
+        for event in session_events("s1") {
+            storage.store_event(&event).unwrap();
+        }
+
+        assert_eq!(storage.apply_lifecycle().unwrap(), vec!["s1".to_string()]);
+        assert!(dir.join("traces/s1.jsonl.gz").exists());
+        assert_eq!(storage.get_events("s1").unwrap().len(), 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
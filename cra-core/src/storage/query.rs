@@ -0,0 +1,395 @@
+//! Event search across sessions
+//!
+//! An [`EventQuery`] filters stored events by session, event type, time
+//! range, full text, and JSONPath predicates on the payload, and returns
+//! one [`EventPage`] at a time. [`StorageBackend::search_events`](super::StorageBackend::search_events)
+//! scans sessions by default; backends with an index override it.
+//!
+//! Supported JSONPath: `$`, `.name`, `['name']`, `[n]`, `.*` and `[*]`.
+//!
+//! ```rust
+//! use cra_core::storage::{EventQuery, PayloadPredicate};
+//! use serde_json::json;
+//!
+//! let query = EventQuery::new()
+//!     .event_type("action.executed")
+//!     .payload(PayloadPredicate::equals("$.action_id", json!("ticket.delete")))
+//!     .limit(50);
+//! assert!(query.validate().is_ok());
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{CRAError, Result};
+use crate::trace::TRACEEvent;
+
+/// Page size when a query doesn't set one
+pub const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Largest page a query may request
+pub const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// A condition on the values a JSONPath selects from an event payload
+///
+/// The predicate holds if any selected value satisfies it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PayloadPredicate {
+    /// The path selects at least one value
+    Exists { path: String },
+    /// A selected value equals `value`
+    Equals { path: String, value: Value },
+    /// A selected string contains `text`, ignoring case
+    Contains { path: String, text: String },
+}
+
+impl PayloadPredicate {
+    /// The path selects at least one value
+    pub fn exists(path: impl Into<String>) -> Self {
+        PayloadPredicate::Exists { path: path.into() }
+    }
+
+    /// A selected value equals `value`
+    pub fn equals(path: impl Into<String>, value: Value) -> Self {
+        PayloadPredicate::Equals { path: path.into(), value }
+    }
+
+    /// A selected string contains `text`, ignoring case
+    pub fn contains(path: impl Into<String>, text: impl Into<String>) -> Self {
+        PayloadPredicate::Contains { path: path.into(), text: text.into() }
+    }
+
+    /// The JSONPath this predicate applies to
+    pub fn path(&self) -> &str {
+        match self {
+            PayloadPredicate::Exists { path }
+            | PayloadPredicate::Equals { path, .. }
+            | PayloadPredicate::Contains { path, .. } => path,
+        }
+    }
+
+    fn matches(&self, payload: &Value) -> bool {
+        let Ok(segments) = parse_path(self.path()) else {
+            return false;
+        };
+        let selected = select(payload, &segments);
+        match self {
+            PayloadPredicate::Exists { .. } => !selected.is_empty(),
+            PayloadPredicate::Equals { value, .. } => selected.contains(&value),
+            PayloadPredicate::Contains { text, .. } => {
+                let text = text.to_lowercase();
+                selected
+                    .iter()
+                    .any(|v| v.as_str().is_some_and(|s| s.to_lowercase().contains(&text)))
+            }
+        }
+    }
+}
+
+/// Filters and paging for [`StorageBackend::search_events`](super::StorageBackend::search_events)
+///
+/// All set filters must match. Results are ordered by timestamp, then
+/// session ID and sequence, so `offset` pages are stable.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventQuery {
+    /// Only this session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Only these event types (any of them)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<String>,
+    /// Only events at or after this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    /// Only events before this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+    /// Only events whose payload contains this text, ignoring case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Payload conditions (all of them)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub payload: Vec<PayloadPredicate>,
+    /// Matches to skip
+    pub offset: usize,
+    /// Page size (default [`DEFAULT_SEARCH_LIMIT`], at most [`MAX_SEARCH_LIMIT`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    /// Create a query matching every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to one session
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Also match this event type
+    pub fn event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_types.push(event_type.into());
+        self
+    }
+
+    /// Restrict to events at or after `since`
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Restrict to events before `until`
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Restrict to payloads containing `text`
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Add a payload condition
+    pub fn payload(mut self, predicate: PayloadPredicate) -> Self {
+        self.payload.push(predicate);
+        self
+    }
+
+    /// Restrict to events stamped with a correlation ID
+    pub fn correlation(self, correlation_id: impl Into<String>) -> Self {
+        self.payload(PayloadPredicate::equals(
+            "$.correlation_id",
+            Value::String(correlation_id.into()),
+        ))
+    }
+
+    /// Skip the first `offset` matches
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the page size
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The effective page size
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT)
+    }
+
+    /// Check the JSONPath expressions and time range
+    pub fn validate(&self) -> Result<()> {
+        for predicate in &self.payload {
+            parse_path(predicate.path()).map_err(|reason| CRAError::InvalidQuery {
+                reason: format!("{}: {}", predicate.path(), reason),
+            })?;
+        }
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err(CRAError::InvalidQuery {
+                    reason: "since is after until".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an event passes every filter (paging aside)
+    pub fn matches(&self, event: &TRACEEvent) -> bool {
+        if self.session_id.as_ref().is_some_and(|id| *id != event.session_id) {
+            return false;
+        }
+        if !self.event_types.is_empty() && !self.event_types.iter().any(|t| t == event.event_type.as_str()) {
+            return false;
+        }
+        if self.since.is_some_and(|since| event.timestamp < since)
+            || self.until.is_some_and(|until| event.timestamp >= until)
+        {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            if !event.payload.to_string().to_lowercase().contains(&text.to_lowercase()) {
+                return false;
+            }
+        }
+        self.payload.iter().all(|p| p.matches(&event.payload))
+    }
+
+    /// Order matching events and cut out the requested page
+    pub fn paginate(&self, mut events: Vec<TRACEEvent>) -> EventPage {
+        events.retain(|e| self.matches(e));
+        events.sort_by(|a, b| {
+            (a.timestamp, &a.session_id, a.sequence).cmp(&(b.timestamp, &b.session_id, b.sequence))
+        });
+
+        let total = events.len();
+        let end = self.offset.saturating_add(self.page_size()).min(total);
+        let events: Vec<_> = events.drain(self.offset.min(end)..end).collect();
+        EventPage {
+            events,
+            total,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPage {
+    /// Matching events on this page
+    pub events: Vec<TRACEEvent>,
+    /// Matching events across all pages
+    pub total: usize,
+    /// `offset` of the next page, if there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+fn parse_path(path: &str) -> std::result::Result<Vec<PathSegment>, String> {
+    let rest = path.strip_prefix('$').ok_or("path must start with '$'")?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                let name: String = chars[start..end].iter().collect();
+                segments.push(match name.as_str() {
+                    "" => return Err(format!("empty name at position {}", start + 1)),
+                    "*" => PathSegment::Wildcard,
+                    _ => PathSegment::Key(name),
+                });
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|p| i + p)
+                    .ok_or("unclosed '['")?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                segments.push(if let Some(name) = quoted {
+                    PathSegment::Key(name.to_string())
+                } else if inner == "*" {
+                    PathSegment::Wildcard
+                } else {
+                    PathSegment::Index(inner.parse().map_err(|_| format!("invalid index '{}'", inner))?)
+                });
+                i = close + 1;
+            }
+            c => return Err(format!("unexpected '{}' at position {}", c, i + 1)),
+        }
+    }
+    Ok(segments)
+}
+
+fn select<'a>(root: &'a Value, segments: &[PathSegment]) -> Vec<&'a Value> {
+    let mut current = vec![root];
+    for segment in segments {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (segment, value) {
+                    (PathSegment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (PathSegment::Index(index), Value::Array(items)) => items.get(*index).into_iter().collect(),
+                    (PathSegment::Wildcard, Value::Object(map)) => map.values().collect(),
+                    (PathSegment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{InMemoryStorage, StorageBackend};
+    use crate::trace::EventType;
+    use serde_json::json;
+
+    fn event(session_id: &str, sequence: u64, event_type: EventType, payload: Value) -> TRACEEvent {
+        let mut event = TRACEEvent::new(session_id.to_string(), "trace".to_string(), event_type, payload);
+        event.sequence = sequence;
+        event.timestamp = DateTime::from_timestamp(1_700_000_000 + sequence as i64, 0).unwrap();
+        event
+    }
+
+    #[test]
+    fn test_parse_and_select_paths() {
+        let payload = json!({"action": {"id": "ticket.delete"}, "tags": ["a", "b"], "odd key": 1});
+        let values = |path: &str| select(&payload, &parse_path(path).unwrap()).into_iter().cloned().collect::<Vec<_>>();
+
+        assert_eq!(values("$.action.id"), vec![json!("ticket.delete")]);
+        assert_eq!(values("$['odd key']"), vec![json!(1)]);
+        assert_eq!(values("$.tags[1]"), vec![json!("b")]);
+        assert_eq!(values("$.tags[*]"), vec![json!("a"), json!("b")]);
+        assert!(values("$.missing.id").is_empty());
+        assert!(parse_path("action.id").is_err());
+        assert!(parse_path("$.tags[x]").is_err());
+        assert!(EventQuery::new().payload(PayloadPredicate::exists("$.")).validate().is_err());
+    }
+
+    #[test]
+    fn test_search_across_sessions() {
+        let storage = InMemoryStorage::new();
+        for (session, offset) in [("s1", 0), ("s2", 10)] {
+            storage.store_event(&event(session, offset, EventType::SessionStarted, json!({"goal": "Triage"}))).unwrap();
+            storage
+                .store_event(&event(session, offset + 1, EventType::ActionExecuted, json!({"action_id": "ticket.delete"})))
+                .unwrap();
+            storage
+                .store_event(&event(session, offset + 2, EventType::ActionExecuted, json!({"action_id": "ticket.read"})))
+                .unwrap();
+        }
+
+        let query = EventQuery::new()
+            .event_type("action.executed")
+            .payload(PayloadPredicate::equals("$.action_id", json!("ticket.delete")));
+        let page = storage.search_events(&query).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.events[0].session_id, "s1");
+        assert_eq!(page.events[1].session_id, "s2");
+
+        let since = DateTime::from_timestamp(1_700_000_005, 0).unwrap();
+        let page = storage.search_events(&query.clone().since(since)).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.events[0].session_id, "s2");
+
+        let page = storage.search_events(&EventQuery::new().text("triage").limit(1)).unwrap();
+        assert_eq!((page.total, page.events.len(), page.next_offset), (2, 1, Some(1)));
+        let page = storage.search_events(&EventQuery::new().text("triage").limit(1).offset(1)).unwrap();
+        assert_eq!((page.events[0].session_id.as_str(), page.next_offset), ("s2", None));
+
+        let invalid = EventQuery::new().payload(PayloadPredicate::exists("action_id"));
+        assert_eq!(storage.search_events(&invalid).unwrap_err().error_code(), "INVALID_QUERY");
+    }
+}
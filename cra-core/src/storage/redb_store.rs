@@ -0,0 +1,153 @@
+//! Session store on an embedded redb database
+//!
+//! [`RedbSessionStore`] keeps each session's state as JSON in one table and
+//! the rate limit counters in another, in a single database file. Every
+//! write is its own durable transaction, so state saved before a crash is
+//! there on the next start. Requires the `redb-store` feature.
+
+use std::path::Path;
+
+use redb::{Database, ReadableTable, TableDefinition, TableError};
+
+use super::SessionStore;
+use crate::carp::{RateLimitCounter, SessionSnapshotState};
+use crate::error::{CRAError, Result};
+
+const SESSIONS: TableDefinition<&str, &[u8]> = TableDefinition::new("sessions");
+const RATE_LIMITS: TableDefinition<&str, &[u8]> = TableDefinition::new("rate_limits");
+const RATE_LIMITS_KEY: &str = "counters";
+
+/// Session store backed by a redb database file
+pub struct RedbSessionStore {
+    db: Database,
+}
+
+impl std::fmt::Debug for RedbSessionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbSessionStore").finish_non_exhaustive()
+    }
+}
+
+impl RedbSessionStore {
+    /// Open the database at `path`, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = Database::create(path.as_ref()).map_err(io_error)?;
+        Ok(Self { db })
+    }
+
+    fn put(&self, table: TableDefinition<&str, &[u8]>, key: &str, value: &[u8]) -> Result<()> {
+        let txn = self.db.begin_write().map_err(io_error)?;
+        {
+            let mut table = txn.open_table(table).map_err(io_error)?;
+            table.insert(key, value).map_err(io_error)?;
+        }
+        txn.commit().map_err(io_error)
+    }
+
+    fn get(&self, table: TableDefinition<&str, &[u8]>, key: &str) -> Result<Option<Vec<u8>>> {
+        let txn = self.db.begin_read().map_err(io_error)?;
+        let table = match txn.open_table(table) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(io_error(e)),
+        };
+        let value = table.get(key).map_err(io_error)?;
+        Ok(value.map(|v| v.value().to_vec()))
+    }
+}
+
+impl SessionStore for RedbSessionStore {
+    fn save_session(&self, state: &SessionSnapshotState) -> Result<()> {
+        self.put(SESSIONS, &state.session.session_id, &serde_json::to_vec(state)?)
+    }
+
+    fn load_session(&self, session_id: &str) -> Result<Option<SessionSnapshotState>> {
+        match self.get(SESSIONS, session_id)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        let txn = self.db.begin_write().map_err(io_error)?;
+        {
+            let mut table = txn.open_table(SESSIONS).map_err(io_error)?;
+            table.remove(session_id).map_err(io_error)?;
+        }
+        txn.commit().map_err(io_error)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let txn = self.db.begin_read().map_err(io_error)?;
+        let table = match txn.open_table(SESSIONS) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(e)),
+        };
+        let mut ids = Vec::new();
+        for entry in table.iter().map_err(io_error)? {
+            let (key, _) = entry.map_err(io_error)?;
+            ids.push(key.value().to_string());
+        }
+        Ok(ids)
+    }
+
+    fn save_rate_limits(&self, counters: &[RateLimitCounter]) -> Result<()> {
+        self.put(RATE_LIMITS, RATE_LIMITS_KEY, &serde_json::to_vec(counters)?)
+    }
+
+    fn load_rate_limits(&self) -> Result<Vec<RateLimitCounter>> {
+        match self.get(RATE_LIMITS, RATE_LIMITS_KEY)? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "redb"
+    }
+}
+
+fn io_error(e: impl std::fmt::Display) -> CRAError {
+    CRAError::IoError {
+        message: format!("redb session store: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carp::Session;
+
+    #[test]
+    fn test_state_survives_reopen() {
+        let path = std::env::temp_dir().join("cra-test-redb-session-store.redb");
+        let _ = std::fs::remove_file(&path);
+
+        let state = SessionSnapshotState {
+            session: Session::new("s1".to_string(), "agent".to_string(), "goal".to_string()),
+            capability_state: Default::default(),
+            guidance: Default::default(),
+            pending_checkpoints: Vec::new(),
+            actions_since_checkpoint: 2,
+            since_checkpoint_ms: 0,
+            matched_keywords: vec!["deploy".to_string()],
+        };
+        {
+            let store = RedbSessionStore::open(&path).unwrap();
+            assert!(store.load_session("s1").unwrap().is_none());
+            assert!(store.list_sessions().unwrap().is_empty());
+            store.save_session(&state).unwrap();
+            store.save_rate_limits(&[]).unwrap();
+        }
+
+        let store = RedbSessionStore::open(&path).unwrap();
+        assert_eq!(store.list_sessions().unwrap(), vec!["s1"]);
+        let loaded = store.load_session("s1").unwrap().unwrap();
+        assert_eq!(loaded.actions_since_checkpoint, 2);
+        assert_eq!(loaded.matched_keywords, vec!["deploy"]);
+        store.delete_session("s1").unwrap();
+        assert!(store.list_sessions().unwrap().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,947 @@
+//! Segmented append-only trace log
+//!
+//! High-throughput local persistence for TRACE events. Events are appended
+//! to a buffered active segment file and indexed in memory by session and
+//! sequence, so reads seek straight to the records they need instead of
+//! re-parsing whole files. Once the active segment reaches its size limit it
+//! is sealed and a new one is started.
+//!
+//! Deleting a session appends a tombstone; the space is reclaimed by
+//! compaction, which rewrites sealed segments whose live fraction has dropped
+//! below a threshold. Compaction can run on a background thread via
+//! [`SegmentLogStorage::start_compactor`].
+//!
+//! # On-disk format
+//!
+//! Segments are named `segment-<id>.log` and hold one record per line:
+//!
+//! ```text
+//! E\t{"trace_version":"1.0",...}     an event
+//! D\t"session-id"                    a session tombstone
+//! ```
+//!
+//! On open, segments are replayed in id order to rebuild the index. A torn
+//! record at the end of the newest segment (from a crash mid-write) is
+//! truncated away; corruption anywhere else is reported as an error.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::StorageBackend;
+use crate::error::{CRAError, Result};
+use crate::trace::{EventType, TRACEEvent};
+
+const EVENT_TAG: &[u8] = b"E\t";
+const TOMBSTONE_TAG: &[u8] = b"D\t";
+const SEGMENT_PREFIX: &str = "segment-";
+const SEGMENT_EXTENSION: &str = ".log";
+const COMPACT_EXTENSION: &str = ".log.compact";
+
+/// How often the compactor thread checks whether it is due or shut down
+const COMPACTOR_TICK: Duration = Duration::from_millis(50);
+
+/// When appended records are made durable with fsync
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsyncPolicy {
+    /// Fsync after every append (slowest, no loss on power failure)
+    Always,
+    /// Fsync at most once per interval; a crash can lose the last interval
+    Interval(Duration),
+    /// Only fsync when a segment is sealed or the log is closed
+    Never,
+}
+
+/// Configuration for the segment log
+#[derive(Debug, Clone)]
+pub struct SegmentLogConfig {
+    /// Seal the active segment once it reaches this many bytes
+    pub max_segment_bytes: u64,
+    /// When appended records are fsynced
+    pub fsync: FsyncPolicy,
+    /// In-process write buffer size
+    pub write_buffer_bytes: usize,
+    /// Compact sealed segments whose live fraction falls below this
+    pub compaction_threshold: f64,
+}
+
+impl Default for SegmentLogConfig {
+    fn default() -> Self {
+        Self {
+            max_segment_bytes: 64 * 1024 * 1024,
+            fsync: FsyncPolicy::Interval(Duration::from_millis(100)),
+            write_buffer_bytes: 256 * 1024,
+            compaction_threshold: 0.5,
+        }
+    }
+}
+
+impl SegmentLogConfig {
+    /// Set the segment size limit
+    pub fn max_segment_bytes(mut self, bytes: u64) -> Self {
+        self.max_segment_bytes = bytes;
+        self
+    }
+
+    /// Set the fsync policy
+    pub fn fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Set the write buffer size
+    pub fn write_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.write_buffer_bytes = bytes;
+        self
+    }
+
+    /// Set the live fraction below which sealed segments are compacted
+    pub fn compaction_threshold(mut self, threshold: f64) -> Self {
+        self.compaction_threshold = threshold;
+        self
+    }
+}
+
+/// Point-in-time statistics for a segment log
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentLogStats {
+    /// Number of segment files, including the active one
+    pub segments: usize,
+    /// Bytes on disk across all segments
+    pub total_bytes: u64,
+    /// Bytes holding events that are still readable
+    pub live_bytes: u64,
+    /// Sessions with at least one event
+    pub sessions: usize,
+    /// Readable events across all sessions
+    pub events: usize,
+}
+
+/// Location of one event record
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    segment: u64,
+    /// Offset of the event JSON (after the record tag)
+    offset: u64,
+    /// Length of the event JSON
+    len: u32,
+    sequence: u64,
+    event_type: String,
+}
+
+impl IndexEntry {
+    /// Bytes the whole record occupies, including tag and newline
+    fn record_len(&self) -> u64 {
+        self.len as u64 + EVENT_TAG.len() as u64 + 1
+    }
+}
+
+#[derive(Debug)]
+struct SegmentInfo {
+    size: u64,
+    live_bytes: u64,
+    /// Sessions that have (possibly deleted) events in this segment; used
+    /// to decide which tombstones compaction must keep
+    sessions: HashSet<String>,
+}
+
+impl SegmentInfo {
+    fn empty() -> Self {
+        Self {
+            size: 0,
+            live_bytes: 0,
+            sessions: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ActiveSegment {
+    id: u64,
+    writer: BufWriter<File>,
+    /// Appended data not yet fsynced
+    dirty: bool,
+    last_sync: Instant,
+}
+
+#[derive(Debug)]
+struct LogState {
+    index: HashMap<String, Vec<IndexEntry>>,
+    segments: BTreeMap<u64, SegmentInfo>,
+    active: ActiveSegment,
+    readers: HashMap<u64, File>,
+}
+
+/// Fields needed to index an event during replay
+#[derive(Deserialize)]
+struct EventKey {
+    session_id: String,
+    sequence: u64,
+    event_type: EventType,
+}
+
+enum Record {
+    Event(EventKey),
+    Tombstone(String),
+}
+
+fn parse_record(line: &[u8]) -> Option<Record> {
+    if let Some(json) = line.strip_prefix(EVENT_TAG) {
+        serde_json::from_slice(json).ok().map(Record::Event)
+    } else if let Some(json) = line.strip_prefix(TOMBSTONE_TAG) {
+        serde_json::from_slice(json).ok().map(Record::Tombstone)
+    } else {
+        None
+    }
+}
+
+fn io_error(context: &str, e: std::io::Error) -> CRAError {
+    CRAError::IoError {
+        message: format!("{}: {}", context, e),
+    }
+}
+
+/// Remove a session from the index, releasing its live bytes
+fn drop_session(
+    index: &mut HashMap<String, Vec<IndexEntry>>,
+    segments: &mut BTreeMap<u64, SegmentInfo>,
+    session_id: &str,
+) {
+    for entry in index.remove(session_id).unwrap_or_default() {
+        if let Some(info) = segments.get_mut(&entry.segment) {
+            info.live_bytes = info.live_bytes.saturating_sub(entry.record_len());
+        }
+    }
+}
+
+/// Segment-based append-only log storage backend
+///
+/// Writes go through a buffered writer on the active segment and are made
+/// durable according to the configured [`FsyncPolicy`]. Reads use the
+/// in-memory index, so counts are free and `get_last_events` or
+/// `get_events_by_type` only touch the records they return. Not available
+/// with the `minimal` feature.
+#[derive(Debug)]
+pub struct SegmentLogStorage {
+    directory: PathBuf,
+    config: SegmentLogConfig,
+    state: Mutex<LogState>,
+}
+
+impl SegmentLogStorage {
+    /// Open (or create) a segment log in the given directory with defaults
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Result<Self> {
+        Self::open(directory, SegmentLogConfig::default())
+    }
+
+    /// Open (or create) a segment log, replaying existing segments
+    pub fn open<P: Into<PathBuf>>(directory: P, config: SegmentLogConfig) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)
+            .map_err(|e| io_error("Failed to create storage directory", e))?;
+
+        let mut ids = Vec::new();
+        let entries = std::fs::read_dir(&directory)
+            .map_err(|e| io_error("Failed to list storage directory", e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| io_error("Failed to list storage directory", e))?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(COMPACT_EXTENSION) {
+                // Left behind by a compaction that did not finish
+                let _ = std::fs::remove_file(entry.path());
+            } else if let Some(id) = name
+                .strip_prefix(SEGMENT_PREFIX)
+                .and_then(|rest| rest.strip_suffix(SEGMENT_EXTENSION))
+                .and_then(|id| id.parse::<u64>().ok())
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+
+        let mut index = HashMap::new();
+        let mut segments = BTreeMap::new();
+        for (position, id) in ids.iter().enumerate() {
+            let is_last = position + 1 == ids.len();
+            replay_segment(&directory, *id, is_last, &mut index, &mut segments)?;
+        }
+
+        let active_id = match ids.last() {
+            Some(&id) if segments[&id].size < config.max_segment_bytes => id,
+            Some(&id) => id + 1,
+            None => 0,
+        };
+        segments.entry(active_id).or_insert_with(SegmentInfo::empty);
+        let active = open_active(&directory, active_id, &config)?;
+
+        Ok(Self {
+            directory,
+            config,
+            state: Mutex::new(LogState {
+                index,
+                segments,
+                active,
+                readers: HashMap::new(),
+            }),
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &SegmentLogConfig {
+        &self.config
+    }
+
+    /// Flush buffered records and fsync the active segment
+    pub fn sync(&self) -> Result<()> {
+        let mut state = self.lock()?;
+        sync_active(&mut state.active)
+    }
+
+    /// Get the event with the given sequence number in a session
+    pub fn get_event(&self, session_id: &str, sequence: u64) -> Result<Option<TRACEEvent>> {
+        let mut state = self.lock()?;
+        let LogState { index, active, readers, .. } = &mut *state;
+        let Some(entries) = index.get(session_id) else {
+            return Ok(None);
+        };
+        match entries.binary_search_by_key(&sequence, |e| e.sequence) {
+            Ok(position) => self
+                .read_entry(readers, active, &entries[position])
+                .map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Get all events in a session with a sequence number of at least `from`
+    pub fn get_events_since(&self, session_id: &str, from: u64) -> Result<Vec<TRACEEvent>> {
+        let mut state = self.lock()?;
+        let LogState { index, active, readers, .. } = &mut *state;
+        let Some(entries) = index.get(session_id) else {
+            return Ok(Vec::new());
+        };
+        let start = entries.partition_point(|e| e.sequence < from);
+        entries[start..]
+            .iter()
+            .map(|entry| self.read_entry(readers, active, entry))
+            .collect()
+    }
+
+    /// Get segment statistics
+    pub fn stats(&self) -> Result<SegmentLogStats> {
+        let state = self.lock()?;
+        Ok(SegmentLogStats {
+            segments: state.segments.len(),
+            total_bytes: state.segments.values().map(|s| s.size).sum(),
+            live_bytes: state.segments.values().map(|s| s.live_bytes).sum(),
+            sessions: state.index.len(),
+            events: state.index.values().map(|v| v.len()).sum(),
+        })
+    }
+
+    /// Rewrite sealed segments whose live fraction is below the threshold
+    ///
+    /// Returns the number of segments compacted. Segments with nothing left
+    /// to keep are deleted. Holds the log lock while it runs.
+    pub fn compact(&self) -> Result<usize> {
+        let mut state = self.lock()?;
+        let active_id = state.active.id;
+        let candidates: Vec<u64> = state
+            .segments
+            .iter()
+            .filter(|(id, info)| {
+                **id != active_id
+                    && (info.size == 0
+                        || (info.live_bytes as f64) < info.size as f64 * self.config.compaction_threshold)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in &candidates {
+            self.compact_segment(&mut state, *id)?;
+        }
+        Ok(candidates.len())
+    }
+
+    /// Run compaction (and interval fsyncs) on a background thread
+    ///
+    /// The thread holds only a weak reference and exits once the storage is
+    /// dropped or the returned handle is shut down.
+    pub fn start_compactor(self: &Arc<Self>, interval: Duration) -> CompactorHandle {
+        let storage = Arc::downgrade(self);
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let flag = shutdown.clone();
+
+        let handle = thread::spawn(move || {
+            let mut last_run = Instant::now();
+            while !flag.load(Ordering::Relaxed) {
+                thread::sleep(COMPACTOR_TICK.min(interval));
+                let Some(storage) = storage.upgrade() else {
+                    break;
+                };
+                if let Err(e) = storage.sync_if_due() {
+                    eprintln!("Error syncing segment log: {:?}", e);
+                }
+                if last_run.elapsed() >= interval {
+                    last_run = Instant::now();
+                    if let Err(e) = storage.compact() {
+                        eprintln!("Error compacting segment log: {:?}", e);
+                    }
+                }
+            }
+        });
+
+        CompactorHandle {
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, LogState>> {
+        self.state.lock().map_err(|_| CRAError::StorageLocked)
+    }
+
+    fn segment_path(&self, id: u64) -> PathBuf {
+        segment_path(&self.directory, id)
+    }
+
+    fn read_entry(
+        &self,
+        readers: &mut HashMap<u64, File>,
+        active: &mut ActiveSegment,
+        entry: &IndexEntry,
+    ) -> Result<TRACEEvent> {
+        if entry.segment == active.id {
+            active
+                .writer
+                .flush()
+                .map_err(|e| io_error("Failed to flush segment", e))?;
+        }
+
+        let file = match readers.entry(entry.segment) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => e.insert(
+                File::open(self.segment_path(entry.segment))
+                    .map_err(|e| io_error("Failed to open segment", e))?,
+            ),
+        };
+        let mut buf = vec![0; entry.len as usize];
+        file.seek(SeekFrom::Start(entry.offset))
+            .and_then(|_| file.read_exact(&mut buf))
+            .map_err(|e| io_error("Failed to read segment", e))?;
+        Ok(serde_json::from_slice(&buf)?)
+    }
+
+    fn read_entries(
+        &self,
+        state: &mut LogState,
+        session_id: &str,
+        filter: impl Fn(&IndexEntry) -> bool,
+        last: Option<usize>,
+    ) -> Result<Vec<TRACEEvent>> {
+        let LogState { index, active, readers, .. } = state;
+        let Some(entries) = index.get(session_id) else {
+            return Ok(Vec::new());
+        };
+        let start = last.map_or(0, |n| entries.len().saturating_sub(n));
+        entries[start..]
+            .iter()
+            .filter(|entry| filter(entry))
+            .map(|entry| self.read_entry(readers, active, entry))
+            .collect()
+    }
+
+    /// Append a record to the active segment, returning its starting offset
+    fn append(&self, state: &mut LogState, tag: &[u8], json: &[u8]) -> Result<u64> {
+        let offset = state.segments[&state.active.id].size;
+        let writer = &mut state.active.writer;
+        writer
+            .write_all(tag)
+            .and_then(|_| writer.write_all(json))
+            .and_then(|_| writer.write_all(b"\n"))
+            .map_err(|e| io_error("Failed to write segment", e))?;
+        state.active.dirty = true;
+        if let Some(info) = state.segments.get_mut(&state.active.id) {
+            info.size += (tag.len() + json.len() + 1) as u64;
+        }
+        Ok(offset)
+    }
+
+    /// Apply the fsync policy and seal the active segment if it is full
+    fn after_append(&self, state: &mut LogState) -> Result<()> {
+        match self.config.fsync {
+            FsyncPolicy::Always => sync_active(&mut state.active)?,
+            FsyncPolicy::Interval(interval) if state.active.last_sync.elapsed() >= interval => {
+                sync_active(&mut state.active)?
+            }
+            _ => {}
+        }
+
+        if state.segments[&state.active.id].size >= self.config.max_segment_bytes {
+            sync_active(&mut state.active)?;
+            let next = state.segments.keys().next_back().map_or(0, |id| id + 1);
+            state.segments.insert(next, SegmentInfo::empty());
+            state.active = open_active(&self.directory, next, &self.config)?;
+        }
+        Ok(())
+    }
+
+    fn sync_if_due(&self) -> Result<()> {
+        let FsyncPolicy::Interval(interval) = self.config.fsync else {
+            return Ok(());
+        };
+        let mut state = self.lock()?;
+        if state.active.dirty && state.active.last_sync.elapsed() >= interval {
+            sync_active(&mut state.active)?;
+        }
+        Ok(())
+    }
+
+    fn compact_segment(&self, state: &mut LogState, id: u64) -> Result<()> {
+        let LogState { index, segments, readers, .. } = state;
+        readers.remove(&id);
+
+        // Live event records in this segment, keyed by JSON offset
+        let mut live: HashMap<u64, (String, usize)> = HashMap::new();
+        for (session_id, entries) in index.iter() {
+            for (position, entry) in entries.iter().enumerate() {
+                if entry.segment == id {
+                    live.insert(entry.offset, (session_id.clone(), position));
+                }
+            }
+        }
+
+        let path = self.segment_path(id);
+        let temp_path = self
+            .directory
+            .join(format!("{}{:010}{}", SEGMENT_PREFIX, id, COMPACT_EXTENSION));
+        let source = File::open(&path).map_err(|e| io_error("Failed to open segment", e))?;
+        let mut reader = BufReader::new(source);
+        let temp = File::create(&temp_path).map_err(|e| io_error("Failed to create segment", e))?;
+        let mut writer = BufWriter::new(temp);
+
+        let mut compacted = SegmentInfo::empty();
+        let mut moved = Vec::new();
+        let mut offset = 0u64;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| io_error("Failed to read segment", e))?;
+            if read == 0 {
+                break;
+            }
+
+            let keep = if line.starts_with(EVENT_TAG) {
+                match live.remove(&(offset + EVENT_TAG.len() as u64)) {
+                    Some((session_id, position)) => {
+                        moved.push((session_id.clone(), position, compacted.size + EVENT_TAG.len() as u64));
+                        compacted.live_bytes += read as u64;
+                        compacted.sessions.insert(session_id);
+                        true
+                    }
+                    None => false,
+                }
+            } else {
+                // A tombstone is still needed while an older segment may
+                // hold events of the deleted session
+                match parse_record(line.strip_suffix(b"\n").unwrap_or(&line)) {
+                    Some(Record::Tombstone(session_id)) => segments
+                        .range(..id)
+                        .any(|(_, info)| info.sessions.contains(&session_id)),
+                    _ => false,
+                }
+            };
+
+            if keep {
+                writer
+                    .write_all(&line)
+                    .map_err(|e| io_error("Failed to write segment", e))?;
+                compacted.size += read as u64;
+            }
+            offset += read as u64;
+        }
+
+        let temp = writer
+            .into_inner()
+            .map_err(|e| io_error("Failed to write segment", e.into_error()))?;
+        temp.sync_data()
+            .map_err(|e| io_error("Failed to sync segment", e))?;
+        drop(temp);
+
+        if compacted.size == 0 {
+            std::fs::remove_file(&temp_path).map_err(|e| io_error("Failed to remove segment", e))?;
+            std::fs::remove_file(&path).map_err(|e| io_error("Failed to remove segment", e))?;
+            segments.remove(&id);
+        } else {
+            std::fs::rename(&temp_path, &path).map_err(|e| io_error("Failed to replace segment", e))?;
+            segments.insert(id, compacted);
+        }
+        if let Ok(dir) = File::open(&self.directory) {
+            let _ = dir.sync_all();
+        }
+
+        for (session_id, position, new_offset) in moved {
+            if let Some(entry) = index.get_mut(&session_id).and_then(|e| e.get_mut(position)) {
+                entry.offset = new_offset;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SegmentLogStorage {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            let _ = sync_active(&mut state.active);
+        }
+    }
+}
+
+fn segment_path(directory: &std::path::Path, id: u64) -> PathBuf {
+    directory.join(format!("{}{:010}{}", SEGMENT_PREFIX, id, SEGMENT_EXTENSION))
+}
+
+fn open_active(directory: &std::path::Path, id: u64, config: &SegmentLogConfig) -> Result<ActiveSegment> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(directory, id))
+        .map_err(|e| io_error("Failed to open segment", e))?;
+    Ok(ActiveSegment {
+        id,
+        writer: BufWriter::with_capacity(config.write_buffer_bytes, file),
+        dirty: false,
+        last_sync: Instant::now(),
+    })
+}
+
+fn sync_active(active: &mut ActiveSegment) -> Result<()> {
+    active
+        .writer
+        .flush()
+        .map_err(|e| io_error("Failed to flush segment", e))?;
+    if active.dirty {
+        active
+            .writer
+            .get_ref()
+            .sync_data()
+            .map_err(|e| io_error("Failed to sync segment", e))?;
+        active.dirty = false;
+    }
+    active.last_sync = Instant::now();
+    Ok(())
+}
+
+/// Rebuild index entries from one segment file
+fn replay_segment(
+    directory: &std::path::Path,
+    id: u64,
+    is_last: bool,
+    index: &mut HashMap<String, Vec<IndexEntry>>,
+    segments: &mut BTreeMap<u64, SegmentInfo>,
+) -> Result<()> {
+    let path = segment_path(directory, id);
+    let file = File::open(&path).map_err(|e| io_error("Failed to open segment", e))?;
+    let mut reader = BufReader::new(file);
+    segments.insert(id, SegmentInfo::empty());
+
+    let mut offset = 0u64;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| io_error("Failed to read segment", e))?;
+        if read == 0 {
+            break;
+        }
+
+        let record = line.strip_suffix(b"\n").and_then(parse_record);
+        match record {
+            Some(Record::Event(key)) => {
+                let entry = IndexEntry {
+                    segment: id,
+                    offset: offset + EVENT_TAG.len() as u64,
+                    len: (read - EVENT_TAG.len() - 1) as u32,
+                    sequence: key.sequence,
+                    event_type: key.event_type.to_string(),
+                };
+                let info = segments.get_mut(&id).expect("segment inserted above");
+                info.live_bytes += read as u64;
+                info.sessions.insert(key.session_id.clone());
+                index.entry(key.session_id).or_default().push(entry);
+            }
+            Some(Record::Tombstone(session_id)) => {
+                drop_session(index, segments, &session_id);
+            }
+            None if is_last => {
+                // Torn write from a crash: drop the partial tail
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .map_err(|e| io_error("Failed to open segment", e))?;
+                file.set_len(offset)
+                    .and_then(|_| file.sync_data())
+                    .map_err(|e| io_error("Failed to truncate segment", e))?;
+                break;
+            }
+            None => {
+                return Err(CRAError::IoError {
+                    message: format!(
+                        "Corrupt record in segment {} at offset {}",
+                        path.display(),
+                        offset
+                    ),
+                });
+            }
+        }
+        offset += read as u64;
+    }
+
+    if let Some(info) = segments.get_mut(&id) {
+        info.size = offset;
+    }
+    Ok(())
+}
+
+impl StorageBackend for SegmentLogStorage {
+    fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+        let json = serde_json::to_vec(event)?;
+        let mut state = self.lock()?;
+        let state = &mut *state;
+
+        let offset = self.append(state, EVENT_TAG, &json)?;
+        let entry = IndexEntry {
+            segment: state.active.id,
+            offset: offset + EVENT_TAG.len() as u64,
+            len: json.len() as u32,
+            sequence: event.sequence,
+            event_type: event.event_type.to_string(),
+        };
+        if let Some(info) = state.segments.get_mut(&state.active.id) {
+            info.live_bytes += entry.record_len();
+            info.sessions.insert(event.session_id.clone());
+        }
+        state
+            .index
+            .entry(event.session_id.clone())
+            .or_default()
+            .push(entry);
+
+        self.after_append(state)
+    }
+
+    fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        let mut state = self.lock()?;
+        self.read_entries(&mut state, session_id, |_| true, None)
+    }
+
+    fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+        let mut state = self.lock()?;
+        self.read_entries(&mut state, session_id, |e| e.event_type == event_type, None)
+    }
+
+    fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+        let mut state = self.lock()?;
+        self.read_entries(&mut state, session_id, |_| true, Some(n))
+    }
+
+    fn get_event_count(&self, session_id: &str) -> Result<usize> {
+        let state = self.lock()?;
+        Ok(state.index.get(session_id).map(|v| v.len()).unwrap_or(0))
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        let mut state = self.lock()?;
+        let state = &mut *state;
+        if !state.index.contains_key(session_id) {
+            return Ok(());
+        }
+
+        let json = serde_json::to_vec(session_id)?;
+        self.append(state, TOMBSTONE_TAG, &json)?;
+        drop_session(&mut state.index, &mut state.segments, session_id);
+        self.after_append(state)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let state = self.lock()?;
+        Ok(state.index.keys().cloned().collect())
+    }
+
+    fn health_check(&self) -> Result<()> {
+        let _state = self.lock()?;
+        if self.directory.is_dir() {
+            Ok(())
+        } else {
+            Err(CRAError::IoError {
+                message: "Storage directory does not exist".to_string(),
+            })
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "segment-log"
+    }
+}
+
+/// Handle to the background compactor thread
+pub struct CompactorHandle {
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CompactorHandle {
+    /// Signal the compactor to stop
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop the compactor and wait for it to exit
+    pub fn join(mut self) -> thread::Result<()> {
+        self.shutdown();
+        if let Some(handle) = self.handle.take() {
+            handle.join()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for CompactorHandle {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cra-segment-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn event(session_id: &str, seq: u64, event_type: EventType) -> TRACEEvent {
+        TRACEEvent::new(
+            session_id.to_string(),
+            "trace-1".to_string(),
+            event_type,
+            json!({"seq": seq}),
+        )
+        .chain(seq, "0".repeat(64))
+    }
+
+    #[test]
+    fn test_segment_log_roundtrip_and_reopen() {
+        let dir = temp_dir("roundtrip");
+        {
+            let storage = SegmentLogStorage::new(&dir).unwrap();
+            for seq in 0..10 {
+                let event_type = if seq % 2 == 0 { EventType::ActionRequested } else { EventType::ActionExecuted };
+                storage.store_event(&event("s1", seq, event_type)).unwrap();
+            }
+            storage.store_event(&event("s2", 0, EventType::SessionStarted)).unwrap();
+
+            assert_eq!(storage.get_event_count("s1").unwrap(), 10);
+            assert_eq!(storage.get_events("s1").unwrap().len(), 10);
+            assert_eq!(storage.get_events_by_type("s1", "action.executed").unwrap().len(), 5);
+            let last = storage.get_last_events("s1", 3).unwrap();
+            assert_eq!(last.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![7, 8, 9]);
+            assert_eq!(storage.get_event("s1", 4).unwrap().unwrap().sequence, 4);
+            assert_eq!(storage.get_events_since("s1", 8).unwrap().len(), 2);
+        }
+
+        let storage = SegmentLogStorage::new(&dir).unwrap();
+        assert_eq!(storage.get_event_count("s1").unwrap(), 10);
+        assert_eq!(storage.get_events("s2").unwrap()[0].event_type, EventType::SessionStarted);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_segment_log_truncates_torn_tail() {
+        let dir = temp_dir("torn");
+        {
+            let storage = SegmentLogStorage::open(&dir, SegmentLogConfig::default().fsync(FsyncPolicy::Always)).unwrap();
+            storage.store_event(&event("s1", 0, EventType::SessionStarted)).unwrap();
+            storage.store_event(&event("s1", 1, EventType::SessionEnded)).unwrap();
+        }
+
+        let path = segment_path(&dir, 0);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"E\t{\"trace_version\":\"1.0\",\"sess").unwrap();
+        drop(file);
+
+        let storage = SegmentLogStorage::new(&dir).unwrap();
+        assert_eq!(storage.get_event_count("s1").unwrap(), 2);
+        storage.store_event(&event("s1", 2, EventType::SessionStarted)).unwrap();
+        drop(storage);
+
+        let storage = SegmentLogStorage::new(&dir).unwrap();
+        assert_eq!(storage.get_events("s1").unwrap().len(), 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_segment_log_compaction_keeps_deletes() {
+        let dir = temp_dir("compact");
+        let config = SegmentLogConfig::default().max_segment_bytes(2048);
+        {
+            let storage = SegmentLogStorage::open(&dir, config.clone()).unwrap();
+            for seq in 0..20 {
+                storage.store_event(&event("doomed", seq, EventType::ActionRequested)).unwrap();
+                storage.store_event(&event("kept", seq, EventType::ActionRequested)).unwrap();
+            }
+            storage.delete_session("doomed").unwrap();
+            let before = storage.stats().unwrap();
+            assert!(before.segments > 2);
+
+            assert!(storage.compact().unwrap() > 0);
+            let after = storage.stats().unwrap();
+            assert!(after.total_bytes < before.total_bytes);
+            assert_eq!(after.events, 20);
+
+            let kept = storage.get_events("kept").unwrap();
+            assert_eq!(kept.iter().map(|e| e.sequence).collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+        }
+
+        let storage = SegmentLogStorage::open(&dir, config).unwrap();
+        assert_eq!(storage.get_event_count("doomed").unwrap(), 0);
+        assert_eq!(storage.get_event_count("kept").unwrap(), 20);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_segment_log_background_compactor() {
+        let dir = temp_dir("compactor");
+        let storage = Arc::new(
+            SegmentLogStorage::open(&dir, SegmentLogConfig::default().max_segment_bytes(1024)).unwrap(),
+        );
+        for seq in 0..10 {
+            storage.store_event(&event("s1", seq, EventType::ActionRequested)).unwrap();
+        }
+        storage.delete_session("s1").unwrap();
+        let before = storage.stats().unwrap().segments;
+
+        let handle = storage.start_compactor(Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(200));
+        handle.join().unwrap();
+
+        assert!(storage.stats().unwrap().segments < before);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,98 @@
+//! Session state persistence
+//!
+//! A [`SessionStore`] keeps everything the resolver holds per session (the
+//! session record, capability state, guidance, pending checkpoints and
+//! checkpoint counters, as one [`SessionSnapshotState`]) plus the rate
+//! limit counters, so live sessions survive a restart. The resolver writes
+//! through to the store after every call that changes a session and reads
+//! it back with [`Resolver::recover_sessions`](crate::Resolver::recover_sessions).
+//!
+//! [`InMemorySessionStore`] is the default; with the `redb-store` feature,
+//! `RedbSessionStore` persists to an embedded database file.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::carp::{RateLimitCounter, SessionSnapshotState};
+use crate::error::{CRAError, Result};
+
+/// Persistence for session-scoped resolver state
+pub trait SessionStore: Send + Sync + std::fmt::Debug {
+    /// Save a session's state, replacing any earlier state
+    fn save_session(&self, state: &SessionSnapshotState) -> Result<()>;
+
+    /// Load a session's state
+    fn load_session(&self, session_id: &str) -> Result<Option<SessionSnapshotState>>;
+
+    /// Delete a session's state
+    fn delete_session(&self, session_id: &str) -> Result<()>;
+
+    /// Get the IDs of all stored sessions
+    fn list_sessions(&self) -> Result<Vec<String>>;
+
+    /// Save the rate limit counters, replacing earlier ones
+    fn save_rate_limits(&self, counters: &[RateLimitCounter]) -> Result<()>;
+
+    /// Load the rate limit counters
+    fn load_rate_limits(&self) -> Result<Vec<RateLimitCounter>>;
+
+    /// Get store name for logging
+    fn name(&self) -> &'static str;
+}
+
+/// Session store that keeps state in memory
+///
+/// State is lost with the process; useful for tests and as a default.
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    sessions: RwLock<HashMap<String, SessionSnapshotState>>,
+    rate_limits: RwLock<Vec<RateLimitCounter>>,
+}
+
+impl InMemorySessionStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn save_session(&self, state: &SessionSnapshotState) -> Result<()> {
+        let mut sessions = self.sessions.write().map_err(|_| CRAError::StorageLocked)?;
+        sessions.insert(state.session.session_id.clone(), state.clone());
+        Ok(())
+    }
+
+    fn load_session(&self, session_id: &str) -> Result<Option<SessionSnapshotState>> {
+        let sessions = self.sessions.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(sessions.get(session_id).cloned())
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        let mut sessions = self.sessions.write().map_err(|_| CRAError::StorageLocked)?;
+        sessions.remove(session_id);
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let sessions = self.sessions.read().map_err(|_| CRAError::StorageLocked)?;
+        let mut ids: Vec<String> = sessions.keys().cloned().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn save_rate_limits(&self, counters: &[RateLimitCounter]) -> Result<()> {
+        let mut rate_limits = self.rate_limits.write().map_err(|_| CRAError::StorageLocked)?;
+        *rate_limits = counters.to_vec();
+        Ok(())
+    }
+
+    fn load_rate_limits(&self) -> Result<Vec<RateLimitCounter>> {
+        let rate_limits = self.rate_limits.read().map_err(|_| CRAError::StorageLocked)?;
+        Ok(rate_limits.clone())
+    }
+
+    fn name(&self) -> &'static str {
+        "in-memory"
+    }
+}
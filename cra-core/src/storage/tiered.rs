@@ -0,0 +1,536 @@
+//! Tiered storage
+//!
+//! Writes land synchronously in a fast hot tier and are replicated to a
+//! durable tier by a background thread, with retries. Reads are served from
+//! the hot tier and fall through to the durable tier on a miss, refilling
+//! the hot tier. Replication failures and count mismatches between tiers are
+//! reported by `health_check`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::StorageBackend;
+use crate::error::{CRAError, Result};
+use crate::trace::TRACEEvent;
+
+/// Configuration for tiered storage
+#[derive(Debug, Clone)]
+pub struct TieredConfig {
+    /// Retries per replication task after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub retry_backoff: Duration,
+    /// Backlog above which `health_check` reports replication lag
+    pub max_pending: usize,
+    /// Refill the hot tier with events read from the durable tier
+    pub read_through_fill: bool,
+}
+
+impl Default for TieredConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            retry_backoff: Duration::from_millis(50),
+            max_pending: 10_000,
+            read_through_fill: true,
+        }
+    }
+}
+
+impl TieredConfig {
+    /// Set the number of retries
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the initial retry backoff
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Set the replication backlog limit reported by `health_check`
+    pub fn max_pending(mut self, pending: usize) -> Self {
+        self.max_pending = pending;
+        self
+    }
+
+    /// Set whether durable reads refill the hot tier
+    pub fn read_through_fill(mut self, enabled: bool) -> Self {
+        self.read_through_fill = enabled;
+        self
+    }
+}
+
+/// Replication counters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TieredStats {
+    /// Tasks applied to the durable tier
+    pub replicated: u64,
+    /// Retry attempts made
+    pub retries: u64,
+    /// Tasks dropped after exhausting retries
+    pub failed: u64,
+    /// Tasks waiting to be replicated
+    pub pending: usize,
+    /// Reads served from the durable tier after a hot miss
+    pub read_through: u64,
+}
+
+#[derive(Debug)]
+enum Task {
+    Store(Box<TRACEEvent>),
+    Delete(String),
+}
+
+impl Task {
+    fn session_id(&self) -> &str {
+        match self {
+            Task::Store(event) => &event.session_id,
+            Task::Delete(session_id) => session_id,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Replication {
+    queue: VecDeque<Task>,
+    /// Queued tasks per session
+    pending: HashMap<String, usize>,
+    /// Sessions written through this tier, for consistency checks
+    sessions: HashSet<String>,
+    /// Sessions with a replication task that was dropped
+    failed_sessions: HashSet<String>,
+    stats: TieredStats,
+    shutdown: bool,
+}
+
+type Shared = Arc<(Mutex<Replication>, Condvar)>;
+
+/// Storage backend over a hot tier and a durable tier
+///
+/// Not available with the `minimal` feature.
+pub struct TieredStorage {
+    hot: Arc<dyn StorageBackend>,
+    durable: Arc<dyn StorageBackend>,
+    config: TieredConfig,
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for TieredStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TieredStorage")
+            .field("hot", &self.hot.name())
+            .field("durable", &self.durable.name())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TieredStorage {
+    /// Create tiered storage with default configuration
+    pub fn new(hot: Arc<dyn StorageBackend>, durable: Arc<dyn StorageBackend>) -> Self {
+        Self::with_config(hot, durable, TieredConfig::default())
+    }
+
+    /// Create tiered storage and start its replication thread
+    pub fn with_config(
+        hot: Arc<dyn StorageBackend>,
+        durable: Arc<dyn StorageBackend>,
+        config: TieredConfig,
+    ) -> Self {
+        let shared: Shared = Arc::new((Mutex::new(Replication::default()), Condvar::new()));
+        let worker = {
+            let shared = shared.clone();
+            let durable = durable.clone();
+            let config = config.clone();
+            thread::spawn(move || replicate_loop(shared, durable, config))
+        };
+
+        Self {
+            hot,
+            durable,
+            config,
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &TieredConfig {
+        &self.config
+    }
+
+    /// Get replication counters
+    pub fn stats(&self) -> TieredStats {
+        self.lock()
+            .map(|state| {
+                let mut stats = state.stats.clone();
+                stats.pending = state.queue.len();
+                stats
+            })
+            .unwrap_or_default()
+    }
+
+    /// Wait until the replication queue is empty
+    ///
+    /// Returns false if the timeout elapsed first.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (lock, condvar) = &*self.shared;
+        let Ok(state) = lock.lock() else {
+            return false;
+        };
+        condvar
+            .wait_timeout_while(state, timeout, |state| !state.pending.is_empty())
+            .map(|(_, result)| !result.timed_out())
+            .unwrap_or(false)
+    }
+
+    /// Compare hot and durable event counts for fully replicated sessions
+    ///
+    /// Returns a description of each inconsistency found, including sessions
+    /// whose replication was abandoned after exhausting retries.
+    pub fn check_consistency(&self) -> Result<Vec<String>> {
+        let (sessions, failed) = {
+            let state = self.lock()?;
+            let settled: Vec<String> = state
+                .sessions
+                .iter()
+                .filter(|id| !state.pending.contains_key(*id))
+                .cloned()
+                .collect();
+            (settled, state.failed_sessions.clone())
+        };
+
+        let mut problems: Vec<String> = failed
+            .iter()
+            .map(|id| format!("session {}: replication to durable tier failed", id))
+            .collect();
+        for session_id in sessions.iter().filter(|id| !failed.contains(*id)) {
+            let hot = self.hot.get_event_count(session_id)?;
+            let durable = self.durable.get_event_count(session_id)?;
+            if hot > durable {
+                problems.push(format!(
+                    "session {}: {} events in hot tier but {} in durable tier",
+                    session_id, hot, durable
+                ));
+            }
+        }
+        problems.sort();
+        Ok(problems)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Replication>> {
+        self.shared.0.lock().map_err(|_| CRAError::StorageLocked)
+    }
+
+    fn enqueue(&self, task: Task) -> Result<()> {
+        let mut state = self.lock()?;
+        let session_id = task.session_id().to_string();
+        *state.pending.entry(session_id.clone()).or_default() += 1;
+        state.sessions.insert(session_id);
+        state.queue.push_back(task);
+        self.shared.1.notify_all();
+        Ok(())
+    }
+
+    /// Read from the durable tier after a hot miss
+    fn read_through(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        let events = self.durable.get_events(session_id)?;
+        if events.is_empty() {
+            return Ok(events);
+        }
+        if let Ok(mut state) = self.lock() {
+            state.stats.read_through += 1;
+        }
+        if self.config.read_through_fill {
+            for event in &events {
+                self.hot.store_event(event)?;
+            }
+        }
+        Ok(events)
+    }
+}
+
+impl Drop for TieredStorage {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.0.lock() {
+            state.shutdown = true;
+        }
+        self.shared.1.notify_all();
+        // Drain whatever is still queued before returning
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn replicate_loop(shared: Shared, durable: Arc<dyn StorageBackend>, config: TieredConfig) {
+    let (lock, condvar) = &*shared;
+    loop {
+        let task = {
+            let Ok(mut state) = lock.lock() else {
+                return;
+            };
+            while state.queue.is_empty() && !state.shutdown {
+                state = match condvar.wait(state) {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+            }
+            match state.queue.pop_front() {
+                Some(task) => task,
+                None => return,
+            }
+        };
+
+        let mut retries = 0;
+        let result = loop {
+            let result = match &task {
+                Task::Store(event) => durable.store_event(event),
+                Task::Delete(session_id) => durable.delete_session(session_id),
+            };
+            if result.is_ok() || retries >= config.max_retries {
+                break result;
+            }
+            thread::sleep(config.retry_backoff.saturating_mul(2u32.saturating_pow(retries)));
+            retries += 1;
+        };
+
+        let Ok(mut state) = lock.lock() else {
+            return;
+        };
+        let session_id = task.session_id();
+        state.stats.retries += retries as u64;
+        match result {
+            Ok(()) => state.stats.replicated += 1,
+            Err(e) => {
+                eprintln!("Error replicating trace event for session {}: {:?}", session_id, e);
+                state.stats.failed += 1;
+                state.failed_sessions.insert(session_id.to_string());
+            }
+        }
+        if let Some(count) = state.pending.get_mut(session_id) {
+            *count -= 1;
+            if *count == 0 {
+                state.pending.remove(session_id);
+            }
+        }
+        condvar.notify_all();
+    }
+}
+
+impl StorageBackend for TieredStorage {
+    fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+        self.hot.store_event(event)?;
+        self.enqueue(Task::Store(Box::new(event.clone())))
+    }
+
+    fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+        let events = self.hot.get_events(session_id)?;
+        if !events.is_empty() {
+            return Ok(events);
+        }
+        self.read_through(session_id)
+    }
+
+    fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+        if self.hot.get_event_count(session_id)? > 0 {
+            return self.hot.get_events_by_type(session_id, event_type);
+        }
+        Ok(self
+            .read_through(session_id)?
+            .into_iter()
+            .filter(|e| e.event_type.to_string() == event_type)
+            .collect())
+    }
+
+    fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+        if self.hot.get_event_count(session_id)? > 0 {
+            return self.hot.get_last_events(session_id, n);
+        }
+        let events = self.read_through(session_id)?;
+        Ok(events.into_iter().rev().take(n).rev().collect())
+    }
+
+    fn get_event_count(&self, session_id: &str) -> Result<usize> {
+        let count = self.hot.get_event_count(session_id)?;
+        if count > 0 {
+            return Ok(count);
+        }
+        self.durable.get_event_count(session_id)
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.hot.delete_session(session_id)?;
+        self.enqueue(Task::Delete(session_id.to_string()))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let mut sessions = self.hot.list_sessions()?;
+        sessions.extend(self.durable.list_sessions()?);
+        sessions.sort();
+        sessions.dedup();
+        Ok(sessions)
+    }
+
+    fn health_check(&self) -> Result<()> {
+        self.hot.health_check()?;
+        self.durable.health_check()?;
+
+        let pending = self.stats().pending;
+        if pending > self.config.max_pending {
+            return Err(CRAError::IoError {
+                message: format!(
+                    "Tiered storage replication lagging: {} events pending (limit {})",
+                    pending, self.config.max_pending
+                ),
+            });
+        }
+
+        let problems = self.check_consistency()?;
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(CRAError::IoError {
+                message: format!("Tiered storage inconsistent: {}", problems.join("; ")),
+            })
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "tiered"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemoryStorage;
+    use crate::trace::EventType;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn event(session_id: &str, seq: u64) -> TRACEEvent {
+        TRACEEvent::new(
+            session_id.to_string(),
+            "trace-1".to_string(),
+            EventType::ActionExecuted,
+            json!({"seq": seq}),
+        )
+        .chain(seq, "0".repeat(64))
+    }
+
+    /// Fails the first `failures` writes, then delegates
+    struct FlakyStorage {
+        inner: InMemoryStorage,
+        failures: AtomicUsize,
+    }
+
+    impl StorageBackend for FlakyStorage {
+        fn store_event(&self, event: &TRACEEvent) -> Result<()> {
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(CRAError::IoError {
+                    message: "unavailable".to_string(),
+                });
+            }
+            self.inner.store_event(event)
+        }
+
+        fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
+            self.inner.get_events(session_id)
+        }
+
+        fn get_events_by_type(&self, session_id: &str, event_type: &str) -> Result<Vec<TRACEEvent>> {
+            self.inner.get_events_by_type(session_id, event_type)
+        }
+
+        fn get_last_events(&self, session_id: &str, n: usize) -> Result<Vec<TRACEEvent>> {
+            self.inner.get_last_events(session_id, n)
+        }
+
+        fn get_event_count(&self, session_id: &str) -> Result<usize> {
+            self.inner.get_event_count(session_id)
+        }
+
+        fn delete_session(&self, session_id: &str) -> Result<()> {
+            self.inner.delete_session(session_id)
+        }
+
+        fn list_sessions(&self) -> Result<Vec<String>> {
+            self.inner.list_sessions()
+        }
+
+        fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+    }
+
+    fn flaky(failures: usize) -> Arc<FlakyStorage> {
+        Arc::new(FlakyStorage {
+            inner: InMemoryStorage::new(),
+            failures: AtomicUsize::new(failures),
+        })
+    }
+
+    #[test]
+    fn test_tiered_replicates_with_retry() {
+        let hot = Arc::new(InMemoryStorage::new());
+        let durable = flaky(2);
+        let config = TieredConfig::default().retry_backoff(Duration::from_millis(1));
+        let storage = TieredStorage::with_config(hot.clone(), durable.clone(), config);
+
+        for seq in 0..5 {
+            storage.store_event(&event("s1", seq)).unwrap();
+        }
+        assert!(storage.flush(Duration::from_secs(5)));
+
+        assert_eq!(durable.get_event_count("s1").unwrap(), 5);
+        let stats = storage.stats();
+        assert_eq!(stats.replicated, 5);
+        assert_eq!(stats.retries, 2);
+        assert!(storage.health_check().is_ok());
+    }
+
+    #[test]
+    fn test_tiered_read_through_fills_hot_tier() {
+        let hot = Arc::new(InMemoryStorage::new());
+        let durable = Arc::new(InMemoryStorage::new());
+        for seq in 0..3 {
+            durable.store_event(&event("s1", seq)).unwrap();
+        }
+        let storage = TieredStorage::new(hot.clone(), durable);
+
+        assert_eq!(storage.get_last_events("s1", 2).unwrap().len(), 2);
+        assert_eq!(hot.get_event_count("s1").unwrap(), 3);
+        assert_eq!(storage.stats().read_through, 1);
+        assert!(storage.get_events("missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tiered_health_check_reports_failed_replication() {
+        let durable = flaky(usize::MAX);
+        let config = TieredConfig::default()
+            .max_retries(1)
+            .retry_backoff(Duration::from_millis(1));
+        let storage = TieredStorage::with_config(Arc::new(InMemoryStorage::new()), durable, config);
+
+        storage.store_event(&event("s1", 0)).unwrap();
+        assert!(storage.flush(Duration::from_secs(5)));
+
+        assert_eq!(storage.stats().failed, 1);
+        let problems = storage.check_consistency().unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(storage.health_check().is_err());
+    }
+}
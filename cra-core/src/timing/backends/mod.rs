@@ -3,7 +3,8 @@
 //! Provides different TimerBackend implementations for various runtime environments:
 //!
 //! - `MockTimerBackend`: For testing, records scheduled timers
-//! - `StdTimerBackend`: Uses std::thread for simple sync cases
+//! - `StdTimerBackend`: Uses std::thread for simple sync cases (not
+//!   available with the `minimal` feature)
 //! - `MinootsTimerBackend`: Integrates with minoots Horology Kernel (optional)
 //!
 //! ## Choosing a Backend
@@ -12,11 +13,15 @@
 //! - **Simple sync apps**: Use `StdTimerBackend`
 //! - **Async apps with tokio**: Use async-runtime feature
 //! - **Integration with minoots**: Use `MinootsTimerBackend`
+//! - **Browsers and edge workers**: Use `WasmTimerBackend` from `cra-wasm`
+//!   (JS timers), with the `minimal` feature
 
 mod mock;
+#[cfg(not(feature = "minimal"))]
 mod std_backend;
 
 pub use mock::MockTimerBackend;
+#[cfg(not(feature = "minimal"))]
 pub use std_backend::StdTimerBackend;
 
 #[cfg(feature = "minoots")]
@@ -5,8 +5,9 @@
 
 use std::collections::HashMap;
 use std::sync::RwLock;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use crate::clock::Instant;
 use crate::error::Result;
 
 use super::{HeartbeatConfig, SessionTTLConfig, TimerBackend, TimerEvent};
@@ -27,6 +28,12 @@ pub trait TimerHandler: Send + Sync {
 
     /// Called when a rate limit window resets
     fn on_rate_limit_reset(&self, policy_id: &str, action_id: &str) -> Result<()>;
+
+    /// Called when a garbage collection run is due (see
+    /// `Resolver::collect_garbage`)
+    fn on_garbage_collect(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Session tracking state
@@ -76,6 +83,9 @@ pub struct TimerManager<B: TimerBackend> {
     /// Trace flush interval
     trace_flush_interval: Duration,
 
+    /// Garbage collection interval, if GC runs are scheduled
+    gc_interval: Option<Duration>,
+
     /// Tracked sessions
     sessions: RwLock<HashMap<String, SessionState>>,
 
@@ -91,6 +101,7 @@ impl<B: TimerBackend> TimerManager<B> {
             heartbeat_config: HeartbeatConfig::default(),
             session_ttl_config: SessionTTLConfig::default(),
             trace_flush_interval: Duration::from_secs(5),
+            gc_interval: None,
             sessions: RwLock::new(HashMap::new()),
             heartbeat_running: RwLock::new(false),
         }
@@ -114,6 +125,12 @@ impl<B: TimerBackend> TimerManager<B> {
         self
     }
 
+    /// Schedule garbage collection runs at this interval
+    pub fn with_gc_interval(mut self, interval: Duration) -> Self {
+        self.gc_interval = Some(interval);
+        self
+    }
+
     /// Start the timer manager (schedules repeating timers)
     pub fn start(&self) -> Result<()> {
         // Start heartbeat timer
@@ -132,6 +149,10 @@ impl<B: TimerBackend> TimerManager<B> {
             TimerEvent::TraceBatchFlush,
         )?;
 
+        if let Some(interval) = self.gc_interval {
+            self.backend.schedule_repeating("cra:gc", interval, TimerEvent::GarbageCollect)?;
+        }
+
         *self.heartbeat_running.write().unwrap() = true;
 
         Ok(())
@@ -141,6 +162,7 @@ impl<B: TimerBackend> TimerManager<B> {
     pub fn stop(&self) -> Result<()> {
         self.backend.cancel("cra:heartbeat")?;
         self.backend.cancel("cra:trace-flush")?;
+        self.backend.cancel("cra:gc")?;
 
         // Cancel all session timers
         let sessions = self.sessions.read().unwrap();
@@ -299,6 +321,20 @@ mod tests {
         assert_eq!(manager.backend_name(), "mock");
     }
 
+    #[test]
+    fn test_gc_timer() {
+        let manager = TimerManager::new(MockTimerBackend::new()).with_gc_interval(Duration::from_secs(600));
+
+        manager.start().unwrap();
+        let timer = manager.backend().get_timer("cra:gc").unwrap();
+        assert!(timer.repeating);
+        assert_eq!(timer.duration, Duration::from_secs(600));
+        assert!(matches!(timer.event, TimerEvent::GarbageCollect));
+
+        manager.stop().unwrap();
+        assert!(manager.backend().get_timer("cra:gc").unwrap().cancelled);
+    }
+
     #[test]
     fn test_session_tracking() {
         let backend = MockTimerBackend::new();
@@ -62,15 +62,18 @@
 pub mod backends;
 pub mod manager;
 
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use crate::clock::Instant;
 use crate::error::Result;
 use crate::trace::TRACEEvent;
 
 // Re-export backends
-pub use backends::{MockTimerBackend, StdTimerBackend};
+pub use backends::MockTimerBackend;
+#[cfg(not(feature = "minimal"))]
+pub use backends::StdTimerBackend;
 
 // Re-export manager
 pub use manager::{TimerManager, TimerHandler, NullTimerHandler};
@@ -90,6 +93,8 @@ pub enum TimerEvent {
     RateLimitReset { policy_id: String, action_id: String },
     /// Trace batch flush time
     TraceBatchFlush,
+    /// Garbage collection run due
+    GarbageCollect,
     /// Custom timer
     Custom { name: String, data: serde_json::Value },
 }
@@ -250,27 +255,20 @@ impl SlidingWindowRateLimiter {
     pub fn check_and_record(&self, policy_id: &str, action_id: &str) -> RateLimitResult {
         let key = (policy_id.to_string(), action_id.to_string());
         let now = Instant::now();
-        let window_start = now - self.window;
+        let window_start = now.checked_sub(self.window);
 
         let mut requests = self.requests.write().unwrap();
         let timestamps = requests.entry(key).or_default();
 
         // Remove expired timestamps
-        timestamps.retain(|&t| t > window_start);
+        timestamps.retain(|&t| window_start.is_none_or(|start| t > start));
 
         let current_count = timestamps.len() as u64;
 
         if current_count >= self.max_requests {
             // Calculate when the oldest request will expire
             let oldest = timestamps.first().copied();
-            let reset_after = oldest.map(|t| {
-                let expires_at = t + self.window;
-                if expires_at > now {
-                    expires_at - now
-                } else {
-                    Duration::ZERO
-                }
-            });
+            let reset_after = oldest.map(|t| (t + self.window).saturating_duration_since(now));
 
             RateLimitResult::Exceeded {
                 current: current_count,
@@ -289,13 +287,16 @@ impl SlidingWindowRateLimiter {
     /// Get current count without recording
     pub fn current_count(&self, policy_id: &str, action_id: &str) -> u64 {
         let key = (policy_id.to_string(), action_id.to_string());
-        let now = Instant::now();
-        let window_start = now - self.window;
+        let window_start = Instant::now().checked_sub(self.window);
 
         let requests = self.requests.read().unwrap();
         requests
             .get(&key)
-            .map(|ts| ts.iter().filter(|&&t| t > window_start).count() as u64)
+            .map(|ts| {
+                ts.iter()
+                    .filter(|&&t| window_start.is_none_or(|start| t > start))
+                    .count() as u64
+            })
             .unwrap_or(0)
     }
 
@@ -27,6 +27,18 @@ pub struct ChainVerification {
 
     /// Hash of the last valid event
     pub last_valid_hash: Option<String>,
+
+    /// Whether event signatures verified against trusted keys
+    /// (`None` if signatures were not checked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures_valid: Option<bool>,
+
+    /// Index of the last event covered by a valid signature
+    ///
+    /// A signature over an event hash attests every event before it, so with
+    /// batch signing this is the last signed event rather than the last event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attested_through: Option<usize>,
 }
 
 impl ChainVerification {
@@ -39,6 +51,8 @@ impl ChainVerification {
             error_type: None,
             error_message: None,
             last_valid_hash: Some(last_hash),
+            signatures_valid: None,
+            attested_through: None,
         }
     }
 
@@ -56,6 +70,8 @@ impl ChainVerification {
             error_type: Some(error_type),
             error_message: Some(message),
             last_valid_hash: None,
+            signatures_valid: None,
+            attested_through: None,
         }
     }
 
@@ -68,8 +84,17 @@ impl ChainVerification {
             error_type: None,
             error_message: None,
             last_valid_hash: Some(GENESIS_HASH.to_string()),
+            signatures_valid: None,
+            attested_through: None,
         }
     }
+
+    /// Record the outcome of a signature check
+    pub fn with_signatures(mut self, valid: bool, attested_through: Option<usize>) -> Self {
+        self.signatures_valid = Some(valid);
+        self.attested_through = attested_through;
+        self
+    }
 }
 
 /// Types of chain errors
@@ -14,17 +14,24 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use serde_json::Value;
-use uuid::Uuid;
-
+#[cfg(feature = "chaos")]
+use crate::chaos::{ChaosInjector, FaultPoint};
+use crate::clock::{Clock, GlobalClock, Instant};
 use crate::error::{CRAError, Result};
+use crate::id::{IdGen, UuidGen};
+use crate::metrics::{MetricsSink, TRACE_EMIT_DURATION_US, TRACE_EVENTS_TOTAL};
+use crate::storage::{EventPage, EventQuery, StorageBackend};
 
 use super::{
     buffer::TraceRingBuffer,
     chain::{ChainVerification, ChainVerifier},
-    event::{EventType, TRACEEvent},
+    event::{EventType, KeyRotatedPayload, TRACEEvent},
     raw::RawEvent,
-    GENESIS_HASH,
+    signature::TraceSigner,
+    verbosity::TraceVerbosity,
+    ADMIN_AUDIT_SESSION, GENESIS_HASH,
 };
 
 /// Session trace state
@@ -38,6 +45,10 @@ struct SessionTrace {
     sequence: u64,
     /// Hash of the last event
     last_hash: String,
+    /// Number of leading events written to the storage backend
+    persisted: usize,
+    /// Detail recorded by `record()`
+    verbosity: TraceVerbosity,
 }
 
 impl SessionTrace {
@@ -47,11 +58,14 @@ impl SessionTrace {
             events: Vec::new(),
             sequence: 0,
             last_hash: GENESIS_HASH.to_string(),
+            persisted: 0,
+            verbosity: TraceVerbosity::default(),
         }
     }
 
-    fn append(&mut self, mut event: TRACEEvent) -> &TRACEEvent {
+    fn append(&mut self, mut event: TRACEEvent, signer: Option<&dyn TraceSigner>) -> &TRACEEvent {
         event = event.chain(self.sequence, self.last_hash.clone());
+        sign_event(&mut event, signer);
         self.last_hash = event.event_hash.clone();
         self.sequence += 1;
         self.events.push(event);
@@ -94,6 +108,15 @@ impl DeferredConfig {
     }
 }
 
+/// Header carrying a correlation ID between CRA components
+///
+/// Servers, proxies and wrappers forward it unchanged and stamp its value
+/// into the events they emit, so one request can be followed across traces.
+pub const CORRELATION_HEADER: &str = "X-CRA-Correlation-Id";
+
+/// Callback run for each chained event
+pub type EmitCallback = Arc<dyn Fn(&TRACEEvent) + Send + Sync>;
+
 /// TRACE Event Collector
 ///
 /// Collects, stores, and provides access to trace events with hash chain integrity.
@@ -110,14 +133,35 @@ pub struct TraceCollector {
     sessions: HashMap<String, SessionTrace>,
 
     /// Optional callback for event emission (for streaming/export)
-    #[allow(dead_code)]
-    on_emit: Option<Box<dyn Fn(&TRACEEvent) + Send + Sync>>,
+    on_emit: Option<EmitCallback>,
 
     /// Ring buffer for deferred mode
     buffer: Option<Arc<TraceRingBuffer>>,
 
     /// Whether deferred mode is enabled
     deferred: bool,
+
+    /// Clock for event timestamps
+    clock: Arc<dyn Clock>,
+
+    /// Generator for trace, event, and span IDs
+    ids: Arc<dyn IdGen>,
+
+    /// Optional signer attesting events as they are chained
+    signer: Option<Arc<dyn TraceSigner>>,
+
+    /// Optional sink for emitted-event counts and emit latency
+    metrics: Option<Arc<dyn MetricsSink>>,
+
+    /// Optional backend that chained events are written to
+    storage: Option<Arc<dyn StorageBackend>>,
+
+    /// Correlation ID stamped into event payloads
+    correlation_id: Option<String>,
+
+    /// Fault plan for chaos tests
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<ChaosInjector>>,
 }
 
 impl std::fmt::Debug for TraceCollector {
@@ -127,6 +171,12 @@ impl std::fmt::Debug for TraceCollector {
             .field("on_emit", &self.on_emit.as_ref().map(|_| "<callback>"))
             .field("deferred", &self.deferred)
             .field("pending", &self.pending_count())
+            .field("clock", &self.clock)
+            .field("ids", &self.ids)
+            .field("signer", &self.signer)
+            .field("metrics", &self.metrics)
+            .field("storage", &self.storage.as_ref().map(|s| s.name()))
+            .field("correlation_id", &self.correlation_id)
             .finish()
     }
 }
@@ -139,6 +189,14 @@ impl TraceCollector {
             on_emit: None,
             buffer: None,
             deferred: false,
+            clock: Arc::new(GlobalClock),
+            ids: Arc::new(UuidGen),
+            signer: None,
+            metrics: None,
+            storage: None,
+            correlation_id: None,
+            #[cfg(feature = "chaos")]
+            chaos: None,
         }
     }
 
@@ -156,18 +214,132 @@ impl TraceCollector {
             on_emit: None,
             buffer: Some(Arc::new(TraceRingBuffer::new(config.buffer_capacity))),
             deferred: true,
+            clock: Arc::new(GlobalClock),
+            ids: Arc::new(UuidGen),
+            signer: None,
+            metrics: None,
+            storage: None,
+            correlation_id: None,
+            #[cfg(feature = "chaos")]
+            chaos: None,
         }
     }
 
     /// Create a collector with an event callback
+    ///
+    /// In deferred mode the callback runs when events are chained by `flush()`.
     pub fn with_callback<F>(mut self, callback: F) -> Self
     where
         F: Fn(&TRACEEvent) + Send + Sync + 'static,
     {
-        self.on_emit = Some(Box::new(callback));
+        self.on_emit = Some(Arc::new(callback));
+        self
+    }
+
+    /// The event callback, if any
+    pub fn callback(&self) -> Option<&EmitCallback> {
+        self.on_emit.as_ref()
+    }
+
+    /// Use a specific clock for event timestamps
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Use a specific generator for trace, event, and span IDs
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGen>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Sign events with a runtime key
+    ///
+    /// Events are signed as they are chained (on `flush()` in deferred mode),
+    /// following the signer's [`SigningMode`](super::SigningMode).
+    pub fn with_signer(mut self, signer: Arc<dyn TraceSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Report emitted events and emit latency to a metrics sink
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Write chained events to a storage backend
+    ///
+    /// Each event is stored once its hash is final: on emit in immediate
+    /// mode, on `flush()` in deferred mode. If the backend fails, the event
+    /// stays in memory, the error is returned, and the write is retried with
+    /// the session's next emit or flush.
+    pub fn with_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Inject trace buffer overflows from a chaos fault plan
+    ///
+    /// Arm [`FaultPoint::TraceBuffer`] to make deferred-mode emits fail as
+    /// if the buffer were full. A `chaos.fault_injected` event is queued in
+    /// place of the rejected one.
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = Some(chaos);
         self
     }
 
+    /// The storage backend attached to this collector, if any
+    pub fn storage(&self) -> Option<&Arc<dyn StorageBackend>> {
+        self.storage.as_ref()
+    }
+
+    /// Stamp a correlation ID into the payload of every event emitted from now on
+    ///
+    /// The ID is added as `correlation_id` to object payloads that don't
+    /// already carry one, so it is covered by the event hash. Pass `None`
+    /// to stop stamping.
+    pub fn set_correlation_id(&mut self, correlation_id: Option<String>) {
+        self.correlation_id = correlation_id;
+    }
+
+    /// The correlation ID currently stamped into events, if any
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.correlation_id.as_deref()
+    }
+
+    /// The signer attached to this collector, if any
+    pub fn signer(&self) -> Option<&Arc<dyn TraceSigner>> {
+        self.signer.as_ref()
+    }
+
+    /// Switch to a new signing key, recording the rotation
+    ///
+    /// Emits `key.rotated` to the [`ADMIN_AUDIT_SESSION`] trace before the
+    /// switch, so the event announcing the new key is signed by the old one.
+    /// `previous_expires_at` is the end of the old key's overlap window.
+    pub fn rotate_signer(
+        &mut self,
+        signer: Arc<dyn TraceSigner>,
+        previous_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<KeyRotatedPayload> {
+        let key = signer.public_key();
+        let payload = KeyRotatedPayload {
+            key_id: key.key_id,
+            algorithm: key.algorithm,
+            public_key: key.public_key,
+            previous_key_id: self.signer.as_ref().map(|s| s.key_id().to_string()),
+            previous_expires_at: self.signer.as_ref().and(previous_expires_at),
+        };
+
+        // In deferred mode, pending events must be signed by the old key
+        self.emit(ADMIN_AUDIT_SESSION, EventType::KeyRotated, serde_json::to_value(&payload)?)?;
+        self.flush()?;
+        self.signer = Some(signer);
+        Ok(payload)
+    }
+
     /// Check if deferred mode is enabled
     pub fn is_deferred(&self) -> bool {
         self.deferred
@@ -203,8 +375,17 @@ impl TraceCollector {
         let _ = buffer.drain_all();
 
         // Recompute hashes for all sessions with "deferred" placeholder hashes
-        for session in self.sessions.values_mut() {
-            recompute_session_hashes(session);
+        for (session_id, session) in self.sessions.iter_mut() {
+            let first_deferred = session.events.iter().position(|e| e.event_hash == "deferred");
+            recompute_session_hashes(session, self.signer.as_deref());
+            if let Err(e) = persist_session(self.storage.as_deref(), session) {
+                let (clock, ids) = (self.clock.as_ref(), self.ids.as_ref());
+                mark_injected_fault(session, session_id, &e, clock, ids, self.signer.as_deref());
+                return Err(e);
+            }
+            if let (Some(callback), Some(first)) = (&self.on_emit, first_deferred) {
+                session.events[first..].iter().for_each(|e| callback(e));
+            }
         }
 
         Ok(())
@@ -222,30 +403,51 @@ impl TraceCollector {
         event_type: EventType,
         payload: Value,
     ) -> Result<&TRACEEvent> {
+        let start = Instant::now();
+        let payload = correlate(payload, self.correlation_id.as_deref());
+
         // Deferred mode: push to buffer
         if self.deferred {
-            return self.emit_deferred(session_id, event_type, payload);
+            #[cfg(feature = "chaos")]
+            if let Some(chaos) = &self.chaos {
+                if let Err(e) = chaos.check(FaultPoint::TraceBuffer, Some(session_id)) {
+                    if let Some(marker) = injected_fault_payload(&e) {
+                        self.emit_deferred(session_id, EventType::ChaosFaultInjected, marker, start)?;
+                    }
+                    return Err(e);
+                }
+            }
+            return self.emit_deferred(session_id, event_type, payload, start);
         }
 
         // Immediate mode: compute hash inline
-        let trace_id = Uuid::new_v4().to_string();
+        let ids = &self.ids;
         let session = self
             .sessions
             .entry(session_id.to_string())
-            .or_insert_with(|| SessionTrace::new(trace_id));
+            .or_insert_with(|| SessionTrace::new(ids.next_id()));
 
-        let event = TRACEEvent::new(
+        let event = TRACEEvent::new_with(
             session_id.to_string(),
             session.trace_id.clone(),
             event_type,
             payload,
+            self.clock.as_ref(),
+            self.ids.as_ref(),
         );
 
-        let appended = session.append(event);
+        session.append(event, self.signer.as_deref());
+        if let Err(e) = persist_session(self.storage.as_deref(), session) {
+            let (clock, ids) = (self.clock.as_ref(), self.ids.as_ref());
+            mark_injected_fault(session, session_id, &e, clock, ids, self.signer.as_deref());
+            return Err(e);
+        }
+        let appended = session.events.last().unwrap();
 
         if let Some(ref callback) = self.on_emit {
             callback(appended);
         }
+        record_emit(self.metrics.as_deref(), event_type, start);
 
         Ok(appended)
     }
@@ -260,6 +462,7 @@ impl TraceCollector {
         session_id: &str,
         event_type: EventType,
         payload: Value,
+        start: Instant,
     ) -> Result<&TRACEEvent> {
         let buffer = self.buffer.as_ref()
             .ok_or_else(|| CRAError::InternalError {
@@ -267,19 +470,21 @@ impl TraceCollector {
             })?;
 
         // Ensure session exists with a trace_id
-        let trace_id = Uuid::new_v4().to_string();
+        let ids = &self.ids;
         let session = self
             .sessions
             .entry(session_id.to_string())
-            .or_insert_with(|| SessionTrace::new(trace_id));
+            .or_insert_with(|| SessionTrace::new(ids.next_id()));
         let trace_id = session.trace_id.clone();
 
         // Create the event immediately (with placeholder hash)
-        let mut event = TRACEEvent::new(
+        let mut event = TRACEEvent::new_with(
             session_id.to_string(),
             trace_id.clone(),
             event_type.clone(),
             payload.clone(),
+            self.clock.as_ref(),
+            self.ids.as_ref(),
         );
 
         // Set sequence and previous hash (for chain ordering)
@@ -307,6 +512,7 @@ impl TraceCollector {
                 reason: "Trace buffer full - call flush() or increase capacity".to_string(),
             });
         }
+        record_emit(self.metrics.as_deref(), event_type, start);
 
         Ok(session.events.last().unwrap())
     }
@@ -319,29 +525,73 @@ impl TraceCollector {
         event_type: EventType,
         payload: Value,
     ) -> Result<&TRACEEvent> {
-        let trace_id = Uuid::new_v4().to_string();
+        let start = Instant::now();
+        let payload = correlate(payload, self.correlation_id.as_deref());
+        let ids = &self.ids;
         let session = self
             .sessions
             .entry(session_id.to_string())
-            .or_insert_with(|| SessionTrace::new(trace_id));
+            .or_insert_with(|| SessionTrace::new(ids.next_id()));
 
-        let event = TRACEEvent::new(
+        let event = TRACEEvent::new_with(
             session_id.to_string(),
             session.trace_id.clone(),
             event_type,
             payload,
+            self.clock.as_ref(),
+            self.ids.as_ref(),
         )
         .with_parent_span(parent_span_id.to_string());
 
-        let appended = session.append(event);
+        session.append(event, self.signer.as_deref());
+        if let Err(e) = persist_session(self.storage.as_deref(), session) {
+            let (clock, ids) = (self.clock.as_ref(), self.ids.as_ref());
+            mark_injected_fault(session, session_id, &e, clock, ids, self.signer.as_deref());
+            return Err(e);
+        }
+        let appended = session.events.last().unwrap();
 
         if let Some(ref callback) = self.on_emit {
             callback(appended);
         }
+        record_emit(self.metrics.as_deref(), event_type, start);
 
         Ok(appended)
     }
 
+    /// Emit an event if the session's verbosity includes it
+    ///
+    /// Fields the verbosity leaves out are dropped from the payload first.
+    /// Returns whether the event was emitted. Use `emit()` for events that
+    /// must be recorded at every level.
+    pub fn record(&mut self, session_id: &str, event_type: EventType, payload: Value) -> Result<bool> {
+        let verbosity = self.verbosity(session_id);
+        if !verbosity.includes(event_type) {
+            return Ok(false);
+        }
+        self.emit(session_id, event_type, verbosity.filter_payload(payload))?;
+        Ok(true)
+    }
+
+    /// Set the detail `record()` keeps for a session
+    ///
+    /// Doesn't emit anything; callers record the change themselves.
+    pub fn set_verbosity(&mut self, session_id: &str, verbosity: TraceVerbosity) {
+        let ids = &self.ids;
+        self.sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionTrace::new(ids.next_id()))
+            .verbosity = verbosity;
+    }
+
+    /// The verbosity of a session (`standard` for unknown sessions)
+    pub fn verbosity(&self, session_id: &str) -> TraceVerbosity {
+        self.sessions
+            .get(session_id)
+            .map(|s| s.verbosity)
+            .unwrap_or_default()
+    }
+
     /// Get all events for a session
     pub fn get_events(&self, session_id: &str) -> Result<Vec<TRACEEvent>> {
         self.sessions
@@ -352,6 +602,20 @@ impl TraceCollector {
             })
     }
 
+    /// Get up to `limit` events for a session starting at index `start`
+    ///
+    /// Lets callers page through long traces without copying them whole.
+    /// Returns an empty page once `start` is past the last event.
+    pub fn get_events_page(&self, session_id: &str, start: usize, limit: usize) -> Result<Vec<TRACEEvent>> {
+        let session = self.sessions.get(session_id).ok_or_else(|| {
+            CRAError::SessionNotFound {
+                session_id: session_id.to_string(),
+            }
+        })?;
+
+        Ok(session.events.iter().skip(start).take(limit).cloned().collect())
+    }
+
     /// Get event count for a session
     pub fn event_count(&self, session_id: &str) -> Option<usize> {
         self.sessions.get(session_id).map(|s| s.events.len())
@@ -383,6 +647,18 @@ impl TraceCollector {
             .collect())
     }
 
+    /// Find events matching a query across the sessions held in memory
+    pub fn search(&self, query: &EventQuery) -> Result<EventPage> {
+        query.validate()?;
+        let events = self
+            .sessions
+            .iter()
+            .filter(|(id, _)| query.session_id.as_ref().is_none_or(|s| s == *id))
+            .flat_map(|(_, session)| session.events.iter().filter(|e| query.matches(e)).cloned())
+            .collect();
+        Ok(query.paginate(events))
+    }
+
     /// Verify the hash chain integrity for a session
     pub fn verify_chain(&self, session_id: &str) -> Result<ChainVerification> {
         let events = self.get_events(session_id)?;
@@ -401,11 +677,11 @@ impl TraceCollector {
 
     /// Import events from JSONL
     pub fn import_jsonl(&mut self, session_id: &str, jsonl: &str) -> Result<usize> {
-        let trace_id = Uuid::new_v4().to_string();
+        let ids = &self.ids;
         let session = self
             .sessions
             .entry(session_id.to_string())
-            .or_insert_with(|| SessionTrace::new(trace_id));
+            .or_insert_with(|| SessionTrace::new(ids.next_id()));
 
         let mut count = 0;
         for line in jsonl.lines() {
@@ -426,15 +702,77 @@ impl TraceCollector {
             session.sequence = last.sequence + 1;
             session.last_hash = last.event_hash.clone();
         }
+        persist_session(self.storage.as_deref(), session)?;
 
         Ok(count)
     }
 
+    /// Reload a session's chain from the storage backend after a restart
+    ///
+    /// The chain is verified, and new events continue it. Returns false if
+    /// there is no storage or it holds no events for the session.
+    pub fn resume_from_storage(&mut self, session_id: &str) -> Result<bool> {
+        if self.sessions.contains_key(session_id) {
+            return Err(CRAError::SessionAlreadyExists {
+                session_id: session_id.to_string(),
+            });
+        }
+        let Some(storage) = &self.storage else { return Ok(false) };
+        let events = storage.get_events(session_id)?;
+        let Some(last) = events.last() else { return Ok(false) };
+
+        let verification = ChainVerifier::verify(&events);
+        if !verification.is_valid {
+            return Err(CRAError::TraceChainIntegrityError {
+                reason: verification.error_message.unwrap_or_default(),
+            });
+        }
+        let mut session = SessionTrace::new(last.trace_id.clone());
+        session.sequence = last.sequence + 1;
+        session.last_hash = last.event_hash.clone();
+        session.persisted = events.len();
+        session.events = events;
+        self.sessions.insert(session_id.to_string(), session);
+        Ok(true)
+    }
+
+    /// Continue a chain exported from another collector
+    ///
+    /// The session keeps the chain's trace ID, and new events link to the
+    /// last imported event. Verify the chain before importing it.
+    pub fn import_chain(&mut self, session_id: &str, events: Vec<TRACEEvent>) -> Result<()> {
+        if self.sessions.contains_key(session_id) {
+            return Err(CRAError::SessionAlreadyExists {
+                session_id: session_id.to_string(),
+            });
+        }
+        let last = events.last().ok_or_else(|| CRAError::InvalidTraceEvent {
+            reason: "cannot import an empty chain".to_string(),
+        })?;
+
+        let mut session = SessionTrace::new(last.trace_id.clone());
+        session.sequence = last.sequence + 1;
+        session.last_hash = last.event_hash.clone();
+        session.events = events;
+        persist_session(self.storage.as_deref(), &mut session)?;
+        self.sessions.insert(session_id.to_string(), session);
+        Ok(())
+    }
+
     /// Clear all events for a session
     pub fn clear_session(&mut self, session_id: &str) {
         self.sessions.remove(session_id);
     }
 
+    /// Sessions with events waiting to be chained by `flush()`
+    pub fn unchained_sessions(&self) -> Vec<&str> {
+        self.sessions
+            .iter()
+            .filter(|(_, s)| s.events.iter().any(|e| e.event_hash == "deferred"))
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
     /// Get all session IDs
     pub fn session_ids(&self) -> Vec<&str> {
         self.sessions.keys().map(|s| s.as_str()).collect()
@@ -457,8 +795,16 @@ impl Default for TraceCollector {
     }
 }
 
+/// Count an emitted event and its emit latency (standalone to avoid borrow issues)
+fn record_emit(metrics: Option<&dyn MetricsSink>, event_type: EventType, start: Instant) {
+    if let Some(metrics) = metrics {
+        metrics.increment(TRACE_EVENTS_TOTAL, &[("event_type", event_type.as_str())], 1);
+        metrics.observe(TRACE_EMIT_DURATION_US, &[], start.elapsed().as_micros() as u64);
+    }
+}
+
 /// Recompute hashes for a session's events (standalone to avoid borrow issues)
-fn recompute_session_hashes(session: &mut SessionTrace) {
+fn recompute_session_hashes(session: &mut SessionTrace, signer: Option<&dyn TraceSigner>) {
     let mut last_hash = GENESIS_HASH.to_string();
 
     for (i, event) in session.events.iter_mut().enumerate() {
@@ -469,6 +815,7 @@ fn recompute_session_hashes(session: &mut SessionTrace) {
 
             // Use the event's own compute_hash method to ensure consistency
             event.event_hash = event.compute_hash();
+            sign_event(event, signer);
         }
 
         last_hash = event.event_hash.clone();
@@ -478,6 +825,79 @@ fn recompute_session_hashes(session: &mut SessionTrace) {
     session.last_hash = last_hash;
 }
 
+/// Add `correlation_id` to an object payload that doesn't have one
+fn correlate(mut payload: Value, correlation_id: Option<&str>) -> Value {
+    if let (Some(id), Some(object)) = (correlation_id, payload.as_object_mut()) {
+        object
+            .entry("correlation_id")
+            .or_insert_with(|| Value::String(id.to_string()));
+    }
+    payload
+}
+
+/// Store events the backend hasn't seen yet, stopping at the first
+/// unflushed deferred event (standalone to avoid borrow issues)
+fn persist_session(storage: Option<&dyn StorageBackend>, session: &mut SessionTrace) -> Result<()> {
+    if let Some(storage) = storage {
+        while let Some(event) = session.events.get(session.persisted) {
+            if event.event_hash == "deferred" {
+                break;
+            }
+            storage.store_event(event)?;
+            session.persisted += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Record an injected fault hit while persisting a session
+///
+/// Appends a `chaos.fault_injected` event when `error` was injected by a
+/// chaos test, so the TRACE shows which failures were deliberate. The
+/// marker is chained but not stored; the next successful write stores it
+/// after the event whose write failed.
+fn mark_injected_fault(
+    session: &mut SessionTrace,
+    session_id: &str,
+    error: &CRAError,
+    clock: &dyn Clock,
+    ids: &dyn IdGen,
+    signer: Option<&dyn TraceSigner>,
+) {
+    if let Some(payload) = injected_fault_payload(error) {
+        let event = TRACEEvent::new_with(
+            session_id.to_string(),
+            session.trace_id.clone(),
+            EventType::ChaosFaultInjected,
+            payload,
+            clock,
+            ids,
+        );
+        session.append(event, signer);
+    }
+}
+
+/// Payload of the `chaos.fault_injected` event for an injected fault
+pub(crate) fn injected_fault_payload(error: &CRAError) -> Option<Value> {
+    match error {
+        CRAError::InjectedFault { point, reason } => Some(serde_json::json!({
+            "point": point,
+            "reason": reason,
+            "injected": true,
+        })),
+        _ => None,
+    }
+}
+
+/// Attach a signature if the signer's mode covers this event
+fn sign_event(event: &mut TRACEEvent, signer: Option<&dyn TraceSigner>) {
+    if let Some(signer) = signer {
+        if signer.mode().should_sign(event) {
+            event.signature = Some(signer.sign_hash(&event.event_hash));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -565,6 +985,65 @@ mod tests {
         assert_eq!(action_events.len(), 2);
     }
 
+    #[test]
+    fn test_get_events_page() {
+        let mut collector = TraceCollector::new();
+        for i in 0..5 {
+            collector
+                .emit("session-1", EventType::ContextInjected, json!({"index": i}))
+                .unwrap();
+        }
+
+        let first = collector.get_events_page("session-1", 0, 2).unwrap();
+        assert_eq!(first.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![0, 1]);
+
+        let last = collector.get_events_page("session-1", 4, 2).unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].sequence, 4);
+
+        assert!(collector.get_events_page("session-1", 5, 2).unwrap().is_empty());
+        assert!(collector.get_events_page("missing", 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_correlation_id_stamped() {
+        let mut collector = TraceCollector::new();
+        collector.set_correlation_id(Some("req-1".to_string()));
+        collector
+            .emit("session-1", EventType::ContextInjected, json!({"index": 0}))
+            .unwrap();
+        collector
+            .emit("session-1", EventType::ContextInjected, json!({"correlation_id": "own"}))
+            .unwrap();
+        collector.set_correlation_id(None);
+        collector
+            .emit("session-1", EventType::ContextInjected, json!({"index": 2}))
+            .unwrap();
+
+        let events = collector.get_events("session-1").unwrap();
+        assert_eq!(events[0].payload["correlation_id"], "req-1");
+        assert_eq!(events[1].payload["correlation_id"], "own");
+        assert!(events[2].payload.get("correlation_id").is_none());
+        assert!(collector.verify_chain("session-1").unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_record_follows_verbosity() {
+        let mut collector = TraceCollector::new();
+        collector.set_verbosity("session-1", TraceVerbosity::Minimal);
+        assert!(collector
+            .record("session-1", EventType::SessionStarted, json!({"agent_id": "a", "goal": "g"}))
+            .unwrap());
+        assert!(!collector
+            .record("session-1", EventType::PolicyEvaluated, json!({"action_id": "x"}))
+            .unwrap());
+
+        let events = collector.get_events("session-1").unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, json!({"agent_id": "a"}));
+        assert_eq!(collector.verbosity("other"), TraceVerbosity::Standard);
+    }
+
     #[test]
     fn test_export_import_jsonl() {
         let mut collector = TraceCollector::new();
@@ -860,4 +1339,36 @@ mod tests {
         assert_eq!(context_events.len(), 1);
         assert_eq!(context_events[0].payload["context_id"], "ctx-123");
     }
+
+    #[test]
+    fn test_storage_receives_chained_events() {
+        use crate::storage::InMemoryStorage;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let mut collector = TraceCollector::new().with_storage(storage.clone());
+        collector
+            .emit("session-1", EventType::SessionStarted, json!({"agent_id": "agent-1", "goal": "test"}))
+            .unwrap();
+        collector
+            .emit_with_parent("session-1", "span-1", EventType::ActionRequested, json!({"action_id": "a"}))
+            .unwrap();
+
+        let hashes = |events: Vec<TRACEEvent>| events.into_iter().map(|e| e.event_hash).collect::<Vec<_>>();
+        assert_eq!(
+            hashes(storage.get_events("session-1").unwrap()),
+            hashes(collector.get_events("session-1").unwrap())
+        );
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let mut collector = TraceCollector::with_deferred(DeferredConfig::default()).with_storage(storage.clone());
+        collector
+            .emit("session-1", EventType::SessionStarted, json!({"agent_id": "agent-1", "goal": "test"}))
+            .unwrap();
+        assert_eq!(storage.get_event_count("session-1").unwrap(), 0);
+
+        collector.flush().unwrap();
+        let stored = storage.get_events("session-1").unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_ne!(stored[0].event_hash, "deferred");
+    }
 }
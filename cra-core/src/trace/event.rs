@@ -1,11 +1,14 @@
 //! TRACE Event types
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
+use cra_trace_verify::HashInput;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sha2::{Digest, Sha256};
-use uuid::Uuid;
 
+use crate::clock::{Clock, GlobalClock};
+use crate::id::{IdGen, UuidGen};
+
+use super::signature::EventSignature;
 use super::VERSION;
 
 /// A single TRACE event in the audit log
@@ -47,6 +50,10 @@ pub struct TRACEEvent {
 
     /// SHA-256 hash of the preceding event
     pub previous_event_hash: String,
+
+    /// Runtime signature over `event_hash` (not itself hashed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<EventSignature>,
 }
 
 impl TRACEEvent {
@@ -56,20 +63,33 @@ impl TRACEEvent {
         trace_id: String,
         event_type: EventType,
         payload: Value,
+    ) -> Self {
+        Self::new_with(session_id, trace_id, event_type, payload, &GlobalClock, &UuidGen)
+    }
+
+    /// Create a new TRACE event with an explicit clock and ID generator
+    pub fn new_with(
+        session_id: String,
+        trace_id: String,
+        event_type: EventType,
+        payload: Value,
+        clock: &dyn Clock,
+        ids: &dyn IdGen,
     ) -> Self {
         Self {
             trace_version: VERSION.to_string(),
-            event_id: Uuid::new_v4().to_string(),
+            event_id: ids.next_id(),
             trace_id,
-            span_id: Uuid::new_v4().to_string(),
+            span_id: ids.next_id(),
             parent_span_id: None,
             session_id,
             sequence: 0, // Will be set by collector
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             event_type,
             payload,
             event_hash: String::new(),   // Will be computed by collector
             previous_event_hash: String::new(), // Will be set by collector
+            signature: None,
         }
     }
 
@@ -107,22 +127,24 @@ impl TRACEEvent {
     /// trace_version || event_id || trace_id || span_id || parent_span_id ||
     /// session_id || sequence || timestamp || event_type || canonical_json(payload) ||
     /// previous_event_hash
+    ///
+    /// The timestamp is hashed exactly as it is serialized (RFC 3339, UTC with
+    /// `Z`), and the payload in RFC 8785 canonical form (see [`super::canonical_json`]).
     pub fn compute_hash(&self) -> String {
-        let mut hasher = Sha256::new();
-
-        hasher.update(self.trace_version.as_bytes());
-        hasher.update(self.event_id.as_bytes());
-        hasher.update(self.trace_id.as_bytes());
-        hasher.update(self.span_id.as_bytes());
-        hasher.update(self.parent_span_id.as_deref().unwrap_or("").as_bytes());
-        hasher.update(self.session_id.as_bytes());
-        hasher.update(self.sequence.to_string().as_bytes());
-        hasher.update(self.timestamp.to_rfc3339().as_bytes());
-        hasher.update(self.event_type.as_str().as_bytes());
-        hasher.update(canonical_json(&self.payload).as_bytes());
-        hasher.update(self.previous_event_hash.as_bytes());
-
-        hex::encode(hasher.finalize())
+        HashInput {
+            trace_version: &self.trace_version,
+            event_id: &self.event_id,
+            trace_id: &self.trace_id,
+            span_id: &self.span_id,
+            parent_span_id: self.parent_span_id.as_deref(),
+            session_id: &self.session_id,
+            sequence: self.sequence,
+            timestamp: &hash_timestamp(&self.timestamp),
+            event_type: self.event_type.as_str(),
+            payload: &self.payload,
+            previous_event_hash: &self.previous_event_hash,
+        }
+        .compute()
     }
 
     /// Verify this event's hash
@@ -131,24 +153,9 @@ impl TRACEEvent {
     }
 }
 
-/// Canonical JSON serialization (sorted keys)
-fn canonical_json(value: &Value) -> String {
-    match value {
-        Value::Object(map) => {
-            let mut pairs: Vec<_> = map.iter().collect();
-            pairs.sort_by_key(|(k, _)| *k);
-            let contents: Vec<String> = pairs
-                .iter()
-                .map(|(k, v)| format!("\"{}\":{}", k, canonical_json(v)))
-                .collect();
-            format!("{{{}}}", contents.join(","))
-        }
-        Value::Array(arr) => {
-            let contents: Vec<String> = arr.iter().map(canonical_json).collect();
-            format!("[{}]", contents.join(","))
-        }
-        _ => serde_json::to_string(value).unwrap_or_default(),
-    }
+/// Timestamp form used in the hash: the same string the event serializes to
+fn hash_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
 }
 
 /// Event types defined in TRACE/1.0
@@ -160,6 +167,16 @@ pub enum EventType {
     SessionStarted,
     #[serde(rename = "session.ended")]
     SessionEnded,
+    #[serde(rename = "session.handoff_out")]
+    SessionHandoffOut,
+    #[serde(rename = "session.handoff_in")]
+    SessionHandoffIn,
+    #[serde(rename = "session.child_created")]
+    SessionChildCreated,
+    #[serde(rename = "session.resumed")]
+    SessionResumed,
+    #[serde(rename = "session.expired")]
+    SessionExpired,
 
     // CARP events
     #[serde(rename = "carp.request.received")]
@@ -168,6 +185,10 @@ pub enum EventType {
     CARPResolutionCompleted,
     #[serde(rename = "carp.resolution.cached")]
     CARPResolutionCached,
+    #[serde(rename = "carp.request.replayed")]
+    CARPRequestReplayed,
+    #[serde(rename = "resolution.invalidated")]
+    ResolutionInvalidated,
 
     // Action events
     #[serde(rename = "action.requested")]
@@ -181,11 +202,21 @@ pub enum EventType {
     #[serde(rename = "action.failed")]
     ActionFailed,
 
+    // Approval events
+    #[serde(rename = "approval.requested")]
+    ApprovalRequested,
+    #[serde(rename = "approval.decided")]
+    ApprovalDecided,
+
     // Policy events
     #[serde(rename = "policy.evaluated")]
     PolicyEvaluated,
     #[serde(rename = "policy.violated")]
     PolicyViolated,
+    #[serde(rename = "policy.external_decision")]
+    PolicyExternalDecision,
+    #[serde(rename = "policy.evaluation_timeout")]
+    PolicyEvaluationTimeout,
 
     // Context events
     #[serde(rename = "context.injected")]
@@ -212,6 +243,34 @@ pub enum EventType {
     CheckpointSkipped,
     #[serde(rename = "checkpoint.guidance_injected")]
     CheckpointGuidanceInjected,
+    #[serde(rename = "checkpoint.guidance_expired")]
+    CheckpointGuidanceExpired,
+
+    // Capability events
+    #[serde(rename = "capability.state_changed")]
+    CapabilityStateChanged,
+    #[serde(rename = "capability_token.minted")]
+    CapabilityTokenMinted,
+    #[serde(rename = "capability_token.used")]
+    CapabilityTokenUsed,
+    #[serde(rename = "capability_token.revoked")]
+    CapabilityTokenRevoked,
+
+    // Key management events
+    #[serde(rename = "key.rotated")]
+    KeyRotated,
+
+    // Integrity events
+    #[serde(rename = "trace.integrity_violation")]
+    IntegrityViolation,
+    #[serde(rename = "trace.verbosity_changed")]
+    TraceVerbosityChanged,
+    #[serde(rename = "gc.completed")]
+    GcCompleted,
+
+    // Fault injection (chaos feature)
+    #[serde(rename = "chaos.fault_injected")]
+    ChaosFaultInjected,
 
     // Error events
     #[serde(rename = "error.occurred")]
@@ -224,16 +283,27 @@ impl EventType {
         match self {
             EventType::SessionStarted => "session.started",
             EventType::SessionEnded => "session.ended",
+            EventType::SessionHandoffOut => "session.handoff_out",
+            EventType::SessionHandoffIn => "session.handoff_in",
+            EventType::SessionChildCreated => "session.child_created",
+            EventType::SessionResumed => "session.resumed",
+            EventType::SessionExpired => "session.expired",
             EventType::CARPRequestReceived => "carp.request.received",
             EventType::CARPResolutionCompleted => "carp.resolution.completed",
             EventType::CARPResolutionCached => "carp.resolution.cached",
+            EventType::CARPRequestReplayed => "carp.request.replayed",
+            EventType::ResolutionInvalidated => "resolution.invalidated",
             EventType::ActionRequested => "action.requested",
             EventType::ActionApproved => "action.approved",
             EventType::ActionDenied => "action.denied",
             EventType::ActionExecuted => "action.executed",
             EventType::ActionFailed => "action.failed",
+            EventType::ApprovalRequested => "approval.requested",
+            EventType::ApprovalDecided => "approval.decided",
             EventType::PolicyEvaluated => "policy.evaluated",
             EventType::PolicyViolated => "policy.violated",
+            EventType::PolicyExternalDecision => "policy.external_decision",
+            EventType::PolicyEvaluationTimeout => "policy.evaluation_timeout",
             EventType::ContextInjected => "context.injected",
             EventType::ContextRedacted => "context.redacted",
             EventType::ContextStale => "context.stale",
@@ -245,13 +315,32 @@ impl EventType {
             EventType::CheckpointFailed => "checkpoint.failed",
             EventType::CheckpointSkipped => "checkpoint.skipped",
             EventType::CheckpointGuidanceInjected => "checkpoint.guidance_injected",
+            EventType::CheckpointGuidanceExpired => "checkpoint.guidance_expired",
+            EventType::CapabilityStateChanged => "capability.state_changed",
+            EventType::CapabilityTokenMinted => "capability_token.minted",
+            EventType::CapabilityTokenUsed => "capability_token.used",
+            EventType::CapabilityTokenRevoked => "capability_token.revoked",
+            EventType::KeyRotated => "key.rotated",
+            EventType::IntegrityViolation => "trace.integrity_violation",
+            EventType::TraceVerbosityChanged => "trace.verbosity_changed",
+            EventType::GcCompleted => "gc.completed",
+            EventType::ChaosFaultInjected => "chaos.fault_injected",
             EventType::ErrorOccurred => "error.occurred",
         }
     }
 
     /// Check if this is a session event
     pub fn is_session_event(&self) -> bool {
-        matches!(self, EventType::SessionStarted | EventType::SessionEnded)
+        matches!(
+            self,
+            EventType::SessionStarted
+                | EventType::SessionEnded
+                | EventType::SessionHandoffOut
+                | EventType::SessionHandoffIn
+                | EventType::SessionChildCreated
+                | EventType::SessionResumed
+                | EventType::SessionExpired
+        )
     }
 
     /// Check if this is a CARP event
@@ -261,6 +350,8 @@ impl EventType {
             EventType::CARPRequestReceived
                 | EventType::CARPResolutionCompleted
                 | EventType::CARPResolutionCached
+                | EventType::CARPRequestReplayed
+                | EventType::ResolutionInvalidated
         )
     }
 
@@ -288,6 +379,7 @@ impl EventType {
                 | EventType::CheckpointFailed
                 | EventType::CheckpointSkipped
                 | EventType::CheckpointGuidanceInjected
+                | EventType::CheckpointGuidanceExpired
         )
     }
 }
@@ -305,16 +397,27 @@ impl std::str::FromStr for EventType {
         match s {
             "session.started" => Ok(EventType::SessionStarted),
             "session.ended" => Ok(EventType::SessionEnded),
+            "session.handoff_out" => Ok(EventType::SessionHandoffOut),
+            "session.handoff_in" => Ok(EventType::SessionHandoffIn),
+            "session.child_created" => Ok(EventType::SessionChildCreated),
+            "session.resumed" => Ok(EventType::SessionResumed),
+            "session.expired" => Ok(EventType::SessionExpired),
             "carp.request.received" => Ok(EventType::CARPRequestReceived),
             "carp.resolution.completed" => Ok(EventType::CARPResolutionCompleted),
             "carp.resolution.cached" => Ok(EventType::CARPResolutionCached),
+            "carp.request.replayed" => Ok(EventType::CARPRequestReplayed),
+            "resolution.invalidated" => Ok(EventType::ResolutionInvalidated),
             "action.requested" => Ok(EventType::ActionRequested),
             "action.approved" => Ok(EventType::ActionApproved),
             "action.denied" => Ok(EventType::ActionDenied),
             "action.executed" => Ok(EventType::ActionExecuted),
             "action.failed" => Ok(EventType::ActionFailed),
+            "approval.requested" => Ok(EventType::ApprovalRequested),
+            "approval.decided" => Ok(EventType::ApprovalDecided),
             "policy.evaluated" => Ok(EventType::PolicyEvaluated),
             "policy.violated" => Ok(EventType::PolicyViolated),
+            "policy.external_decision" => Ok(EventType::PolicyExternalDecision),
+            "policy.evaluation_timeout" => Ok(EventType::PolicyEvaluationTimeout),
             "context.injected" => Ok(EventType::ContextInjected),
             "context.redacted" => Ok(EventType::ContextRedacted),
             "context.stale" => Ok(EventType::ContextStale),
@@ -326,6 +429,16 @@ impl std::str::FromStr for EventType {
             "checkpoint.failed" => Ok(EventType::CheckpointFailed),
             "checkpoint.skipped" => Ok(EventType::CheckpointSkipped),
             "checkpoint.guidance_injected" => Ok(EventType::CheckpointGuidanceInjected),
+            "checkpoint.guidance_expired" => Ok(EventType::CheckpointGuidanceExpired),
+            "capability.state_changed" => Ok(EventType::CapabilityStateChanged),
+            "capability_token.minted" => Ok(EventType::CapabilityTokenMinted),
+            "capability_token.used" => Ok(EventType::CapabilityTokenUsed),
+            "capability_token.revoked" => Ok(EventType::CapabilityTokenRevoked),
+            "key.rotated" => Ok(EventType::KeyRotated),
+            "trace.integrity_violation" => Ok(EventType::IntegrityViolation),
+            "trace.verbosity_changed" => Ok(EventType::TraceVerbosityChanged),
+            "gc.completed" => Ok(EventType::GcCompleted),
+            "chaos.fault_injected" => Ok(EventType::ChaosFaultInjected),
             "error.occurred" => Ok(EventType::ErrorOccurred),
             _ => Err(format!("Unknown event type: {}", s)),
         }
@@ -354,6 +467,7 @@ pub enum EventPayload {
     CheckpointFailed(CheckpointFailedPayload),
     CheckpointSkipped(CheckpointSkippedPayload),
     CheckpointGuidanceInjected(CheckpointGuidanceInjectedPayload),
+    CheckpointGuidanceExpired(CheckpointGuidanceExpiredPayload),
     Generic(Value),
 }
 
@@ -377,6 +491,27 @@ pub struct SessionEndedPayload {
     pub error: Option<String>,
 }
 
+/// Payload for session.handoff_out events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHandoffOutPayload {
+    /// Resolver the session is moving to
+    pub destination: String,
+    pub resolution_count: u64,
+    pub action_count: u64,
+}
+
+/// Payload for session.handoff_in events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHandoffInPayload {
+    /// Hash of the source's session.handoff_out event
+    pub source_event_hash: String,
+    /// Digest of the imported snapshot
+    pub snapshot_digest: String,
+    /// Key that signed the snapshot, if it was signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_by: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CARPRequestPayload {
     pub request_id: String,
@@ -583,6 +718,55 @@ pub struct CheckpointGuidanceInjectedPayload {
     pub injected_context_ids: Option<Vec<String>>,
 }
 
+/// Payload for checkpoint.guidance_expired event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointGuidanceExpiredPayload {
+    /// Checkpoint that originally injected the guidance
+    pub checkpoint_id: String,
+    /// Why the guidance was removed ("expired" or "replaced")
+    pub reason: String,
+    /// Hash of the guidance content
+    pub guidance_hash: String,
+    /// Checkpoint whose completion expired the guidance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expired_by: Option<String>,
+}
+
+/// Payload for key.rotated events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotatedPayload {
+    /// Key that signs events from now on
+    pub key_id: String,
+    /// Signature algorithm of the new key
+    pub algorithm: String,
+    /// New public key, hex-encoded
+    pub public_key: String,
+    /// Key that was replaced (None for the first key)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_key_id: Option<String>,
+    /// End of the previous key's overlap window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Payload for trace.integrity_violation events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityViolationPayload {
+    /// Session whose chain failed verification
+    pub session_id: String,
+    /// What found the break ("flush", "sweep" or "verify")
+    pub detected_by: String,
+    /// Index of the first invalid event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_invalid_index: Option<usize>,
+    /// Verification error message
+    pub error: String,
+    /// Whether the session was quarantined
+    pub quarantined: bool,
+    /// When the break was detected
+    pub detected_at: DateTime<Utc>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -655,13 +839,22 @@ mod tests {
     #[test]
     fn test_canonical_json() {
         let value = json!({"b": 2, "a": 1, "c": {"y": 2, "x": 1}});
-        let canonical = canonical_json(&value);
+        let canonical = crate::trace::canonical_json(&value);
 
         // Keys should be sorted
         assert!(canonical.starts_with("{\"a\":1"));
         assert!(canonical.contains("\"c\":{\"x\":1,\"y\":2}"));
     }
 
+    #[test]
+    fn test_hash_timestamp_matches_serialized_form() {
+        for nanos in [0, 120_000_000, 123_456_000, 123_456_789] {
+            let timestamp = DateTime::from_timestamp(1_700_000_000, nanos).unwrap();
+            let serialized = serde_json::to_value(timestamp).unwrap();
+            assert_eq!(serialized, hash_timestamp(&timestamp));
+        }
+    }
+
     #[test]
     fn test_event_type_parsing() {
         assert_eq!(
@@ -786,6 +979,10 @@ mod tests {
             "checkpoint.guidance_injected".parse::<EventType>().unwrap(),
             EventType::CheckpointGuidanceInjected
         );
+        assert_eq!(
+            "checkpoint.guidance_expired".parse::<EventType>().unwrap(),
+            EventType::CheckpointGuidanceExpired
+        );
     }
 
     #[test]
@@ -798,6 +995,16 @@ mod tests {
         assert_eq!(EventType::CheckpointFailed.as_str(), "checkpoint.failed");
         assert_eq!(EventType::CheckpointSkipped.as_str(), "checkpoint.skipped");
         assert_eq!(EventType::CheckpointGuidanceInjected.as_str(), "checkpoint.guidance_injected");
+        assert_eq!(EventType::CheckpointGuidanceExpired.as_str(), "checkpoint.guidance_expired");
+    }
+
+    #[test]
+    fn test_capability_event_type() {
+        assert_eq!(EventType::CapabilityStateChanged.as_str(), "capability.state_changed");
+        assert_eq!(
+            "capability.state_changed".parse::<EventType>().unwrap(),
+            EventType::CapabilityStateChanged
+        );
     }
 
     #[test]
@@ -807,11 +1014,13 @@ mod tests {
         assert!(EventType::CheckpointPassed.is_checkpoint_event());
         assert!(EventType::CheckpointFailed.is_checkpoint_event());
         assert!(EventType::CheckpointGuidanceInjected.is_checkpoint_event());
+        assert!(EventType::CheckpointGuidanceExpired.is_checkpoint_event());
 
         // Non-checkpoint events should return false
         assert!(!EventType::SessionStarted.is_checkpoint_event());
         assert!(!EventType::ActionExecuted.is_checkpoint_event());
         assert!(!EventType::PolicyEvaluated.is_checkpoint_event());
+        assert!(!EventType::CapabilityStateChanged.is_checkpoint_event());
     }
 
     #[test]
@@ -0,0 +1,159 @@
+//! Downloadable session trace exports
+//!
+//! An export is the session's events as JSONL followed by an
+//! [`ExportTrailer`] line with the event count and final chain hash, so the
+//! file can be checked for completeness with `cra_trace_verify::verify_jsonl`.
+//! Exports that inline context add a [`ContentRecord`] per distinct block
+//! between the events and the trailer.
+//! With the `gzip` feature, exports can be gzip-compressed.
+
+use crate::error::{CRAError, Result};
+
+use super::{event::TRACEEvent, ContentRecord, ExportTrailer, GENESIS_HASH};
+
+/// Media type of an uncompressed export
+pub const EXPORT_CONTENT_TYPE: &str = "application/vnd.cra.trace+jsonl";
+
+/// Media type of a gzip-compressed export
+pub const EXPORT_GZIP_CONTENT_TYPE: &str = "application/gzip";
+
+/// A session trace ready to download
+#[derive(Debug, Clone)]
+pub struct TraceExport {
+    /// The exported session
+    pub session_id: String,
+    /// JSONL body, gzip-compressed if `compressed`
+    pub body: Vec<u8>,
+    /// Whether `body` is gzip-compressed
+    pub compressed: bool,
+    /// The trailer closing the body
+    pub trailer: ExportTrailer,
+}
+
+impl TraceExport {
+    /// Serialize a session's events and append the trailer
+    ///
+    /// Fails if any event still has a deferred placeholder hash.
+    pub fn new(session_id: &str, trace_id: &str, events: &[TRACEEvent]) -> Result<Self> {
+        Self::with_contents(session_id, trace_id, events, &[])
+    }
+
+    /// Serialize a session's events and inlined context, then the trailer
+    pub fn with_contents(
+        session_id: &str,
+        trace_id: &str,
+        events: &[TRACEEvent],
+        contents: &[ContentRecord],
+    ) -> Result<Self> {
+        let mut body = Vec::new();
+        for event in events {
+            if event.event_hash == "deferred" {
+                return Err(CRAError::InvalidTraceEvent {
+                    reason: "trace has unflushed deferred events; flush before exporting".to_string(),
+                });
+            }
+            serde_json::to_writer(&mut body, event)?;
+            body.push(b'\n');
+        }
+        for record in contents {
+            serde_json::to_writer(&mut body, record)?;
+            body.push(b'\n');
+        }
+
+        let final_hash = events.last().map_or(GENESIS_HASH, |e| e.event_hash.as_str());
+        let trailer = ExportTrailer::new(session_id, trace_id, events.len(), final_hash);
+        serde_json::to_writer(&mut body, &trailer)?;
+        body.push(b'\n');
+
+        Ok(Self {
+            session_id: session_id.to_string(),
+            body,
+            compressed: false,
+            trailer,
+        })
+    }
+
+    /// Gzip-compress the body
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self) -> Result<Self> {
+        use std::io::Write;
+
+        if self.compressed {
+            return Ok(self);
+        }
+        let io_error = |e: std::io::Error| CRAError::IoError { message: e.to_string() };
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&self.body).map_err(io_error)?;
+        self.body = encoder.finish().map_err(io_error)?;
+        self.compressed = true;
+        Ok(self)
+    }
+
+    /// Value for the `Content-Type` header
+    pub fn content_type(&self) -> &'static str {
+        if self.compressed {
+            EXPORT_GZIP_CONTENT_TYPE
+        } else {
+            EXPORT_CONTENT_TYPE
+        }
+    }
+
+    /// Download file name, e.g. `<session_id>.trace.jsonl.gz`
+    ///
+    /// Characters other than ASCII letters, digits, `.`, `-` and `_` in the
+    /// session ID are replaced with `_`.
+    pub fn filename(&self) -> String {
+        let stem: String = self
+            .session_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect();
+        let extension = if self.compressed { "trace.jsonl.gz" } else { "trace.jsonl" };
+        format!("{}.{}", stem, extension)
+    }
+
+    /// Value for the `Content-Disposition` header
+    pub fn content_disposition(&self) -> String {
+        format!("attachment; filename=\"{}\"", self.filename())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{EventType, TraceCollector};
+    use serde_json::json;
+
+    #[test]
+    fn test_export_ends_with_verifiable_trailer() {
+        let mut collector = TraceCollector::new();
+        collector.emit("session/1", EventType::SessionStarted, json!({"agent_id": "a", "goal": "g"})).unwrap();
+        collector.emit("session/1", EventType::SessionEnded, json!({"reason": "done"})).unwrap();
+        let events = collector.get_events("session/1").unwrap();
+
+        let export = TraceExport::new("session/1", collector.trace_id("session/1").unwrap(), &events).unwrap();
+        assert_eq!(export.trailer.event_count, 2);
+        assert_eq!(export.trailer.final_hash, events[1].event_hash);
+        assert_eq!(export.content_disposition(), "attachment; filename=\"session_1.trace.jsonl\"");
+
+        let verification = cra_trace_verify::verify_jsonl(std::str::from_utf8(&export.body).unwrap(), GENESIS_HASH);
+        assert!(verification.is_valid);
+        assert_eq!(verification.trailer, Some(export.trailer));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_export() {
+        use std::io::Read;
+
+        let export = TraceExport::new("s1", "t1", &[]).unwrap();
+        let plain = export.body.clone();
+        let export = export.gzip().unwrap();
+        assert_eq!(export.content_type(), EXPORT_GZIP_CONTENT_TYPE);
+        assert_eq!(export.filename(), "s1.trace.jsonl.gz");
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(export.body.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, plain);
+    }
+}
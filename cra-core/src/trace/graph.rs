@@ -0,0 +1,547 @@
+//! Session activity graphs
+//!
+//! [`visualize`] turns a session's TRACE into a DOT or Mermaid graph for
+//! dashboards and incident reviews:
+//!
+//! - resolutions are nodes, linked in the order they happened
+//! - actions are edges from the resolution they ran under to their outcome,
+//!   labelled with the decision and the denying policy
+//! - checkpoints are gates (diamonds) on the timeline
+//! - a broken hash chain adds a highlighted node where verification failed
+//!
+//! ```rust,ignore
+//! let mermaid = resolver.visualize_trace(&session_id, GraphFormat::Mermaid)?;
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::chain::{ChainVerification, ChainVerifier};
+use super::event::{EventType, TRACEEvent};
+
+/// Graph output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    #[default]
+    Mermaid,
+}
+
+impl GraphFormat {
+    /// Lowercase name of the format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Mermaid => "mermaid",
+        }
+    }
+
+    /// Media type of a rendered graph
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "text/vnd.graphviz",
+            GraphFormat::Mermaid => "text/vnd.mermaid",
+        }
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            other => Err(format!("unknown graph format '{}'", other)),
+        }
+    }
+}
+
+/// What a node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// Session start or end
+    Session,
+    /// A CARP resolution
+    Resolution,
+    /// A checkpoint gate
+    Checkpoint,
+    /// Where an action ended up
+    Outcome,
+    /// Where hash chain verification failed
+    ChainBreak,
+}
+
+/// How a node is highlighted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    /// Completed normally
+    #[default]
+    Ok,
+    /// Waiting (approval, checkpoint response)
+    Pending,
+    /// Denied by policy
+    Denied,
+    /// Failed
+    Failed,
+    /// The hash chain is broken here
+    Broken,
+}
+
+/// A node of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Identifier, unique within the graph
+    pub id: String,
+    /// What the node stands for
+    pub kind: NodeKind,
+    /// Display label; lines are separated by `\n`
+    pub label: String,
+    /// Highlighting
+    pub status: NodeStatus,
+}
+
+/// An edge of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    /// Source node ID
+    pub from: String,
+    /// Target node ID
+    pub to: String,
+    /// Display label, for action edges
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Whether this edge is an action (as opposed to the session timeline)
+    pub action: bool,
+}
+
+/// Graph of one session's activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceGraph {
+    /// The session graphed
+    pub session_id: String,
+    /// Nodes, in the order they appeared in the trace
+    pub nodes: Vec<GraphNode>,
+    /// Edges, in the order they appeared in the trace
+    pub edges: Vec<GraphEdge>,
+}
+
+/// An action seen in the trace, waiting for its outcome
+struct OpenAction {
+    from: String,
+    outcome: String,
+}
+
+impl TraceGraph {
+    /// Build the graph of a session's events
+    pub fn from_events(session_id: &str, events: &[TRACEEvent]) -> Self {
+        let mut builder = Builder::new(session_id);
+        let verification = ChainVerifier::verify(events);
+        for (index, event) in events.iter().enumerate() {
+            if verification.first_invalid_index == Some(index) {
+                builder.chain_break(index, &verification);
+            }
+            builder.event(event);
+        }
+        builder.graph
+    }
+
+    /// Render in `format`
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Render as Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(&self.session_id));
+        out.push_str("    rankdir=TB;\n    node [fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Session => "oval",
+                NodeKind::Resolution => "box",
+                NodeKind::Checkpoint => "diamond",
+                NodeKind::Outcome => "circle",
+                NodeKind::ChainBreak => "octagon",
+            };
+            let style = match node.status {
+                NodeStatus::Ok => "",
+                NodeStatus::Pending => ", style=dashed",
+                NodeStatus::Denied => ", style=filled, fillcolor=\"#fde2e1\", color=\"#c62828\"",
+                NodeStatus::Failed => ", style=filled, fillcolor=\"#fff3cd\", color=\"#b26a00\"",
+                NodeStatus::Broken => ", style=\"filled,bold\", fillcolor=\"#ff5252\", color=\"#b71c1c\", penwidth=3",
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label=\"{}\", shape={}{}];",
+                node.id,
+                dot_escape(&node.label),
+                shape,
+                style
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if let Some(label) = &edge.label {
+                attrs.push(format!("label=\"{}\"", dot_escape(label)));
+            }
+            if edge.action {
+                attrs.push("fontsize=10".to_string());
+            } else {
+                attrs.push("style=bold".to_string());
+            }
+            let _ = writeln!(out, "    {} -> {} [{}];", edge.from, edge.to, attrs.join(", "));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for node in &self.nodes {
+            let label = mermaid_escape(&node.label);
+            let shape = match node.kind {
+                NodeKind::Session => format!("([\"{}\"])", label),
+                NodeKind::Resolution => format!("[\"{}\"]", label),
+                NodeKind::Checkpoint => format!("{{\"{}\"}}", label),
+                NodeKind::Outcome => format!("((\"{}\"))", label),
+                NodeKind::ChainBreak => format!("{{{{\"{}\"}}}}", label),
+            };
+            let _ = writeln!(out, "    {}{}", node.id, shape);
+        }
+        for edge in &self.edges {
+            let arrow = if edge.action { "-.->" } else { "-->" };
+            match &edge.label {
+                Some(label) => {
+                    let _ = writeln!(out, "    {} {}|\"{}\"| {}", edge.from, arrow, mermaid_escape(label), edge.to);
+                }
+                None => {
+                    let _ = writeln!(out, "    {} {} {}", edge.from, arrow, edge.to);
+                }
+            }
+        }
+
+        let classes = [
+            (NodeStatus::Pending, "pending", "stroke-dasharray:4 3"),
+            (NodeStatus::Denied, "denied", "fill:#fde2e1,stroke:#c62828"),
+            (NodeStatus::Failed, "failed", "fill:#fff3cd,stroke:#b26a00"),
+            (NodeStatus::Broken, "broken", "fill:#ff5252,stroke:#b71c1c,stroke-width:3px,color:#fff"),
+        ];
+        for (status, class, style) in classes {
+            let ids: Vec<&str> = self
+                .nodes
+                .iter()
+                .filter(|n| n.status == status)
+                .map(|n| n.id.as_str())
+                .collect();
+            if !ids.is_empty() {
+                let _ = writeln!(out, "    classDef {} {}", class, style);
+                let _ = writeln!(out, "    class {} {}", ids.join(","), class);
+            }
+        }
+        out
+    }
+}
+
+/// Render a session's events as a graph
+pub fn visualize(session_id: &str, events: &[TRACEEvent], format: GraphFormat) -> String {
+    TraceGraph::from_events(session_id, events).render(format)
+}
+
+/// Walks the events, adding nodes and edges
+struct Builder {
+    graph: TraceGraph,
+    /// Last node on the session timeline
+    last: Option<String>,
+    /// Node of each resolution, by resolution ID
+    resolutions: HashMap<String, String>,
+    /// Node of each checkpoint, by checkpoint ID
+    checkpoints: HashMap<String, usize>,
+    /// Actions without a final outcome, by action ID
+    open_actions: HashMap<String, OpenAction>,
+    resolution_count: usize,
+}
+
+impl Builder {
+    fn new(session_id: &str) -> Self {
+        Self {
+            graph: TraceGraph {
+                session_id: session_id.to_string(),
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+            last: None,
+            resolutions: HashMap::new(),
+            checkpoints: HashMap::new(),
+            open_actions: HashMap::new(),
+            resolution_count: 0,
+        }
+    }
+
+    fn event(&mut self, event: &TRACEEvent) {
+        let payload = &event.payload;
+        match event.event_type {
+            EventType::SessionStarted => {
+                let label = match str_field(payload, "agent_id") {
+                    Some(agent) => format!("Session started\n{}", agent),
+                    None => "Session started".to_string(),
+                };
+                self.timeline(NodeKind::Session, label, NodeStatus::Ok);
+            }
+            EventType::SessionEnded => {
+                self.timeline(NodeKind::Session, "Session ended".to_string(), NodeStatus::Ok);
+            }
+            EventType::CARPResolutionCompleted => {
+                self.resolution_count += 1;
+                let decision = str_field(payload, "decision_type").unwrap_or("unknown");
+                let label = format!(
+                    "Resolution {}\n{}: {} allowed, {} denied",
+                    self.resolution_count,
+                    decision,
+                    payload["allowed_count"].as_u64().unwrap_or(0),
+                    payload["denied_count"].as_u64().unwrap_or(0),
+                );
+                let status = if decision == "deny" { NodeStatus::Denied } else { NodeStatus::Ok };
+                let id = self.timeline(NodeKind::Resolution, label, status);
+                if let Some(resolution_id) = str_field(payload, "resolution_id") {
+                    self.resolutions.insert(resolution_id.to_string(), id);
+                }
+            }
+            EventType::CheckpointTriggered => {
+                let checkpoint_id = str_field(payload, "checkpoint_id").unwrap_or("checkpoint");
+                let label = format!("{}\npending", checkpoint_id);
+                self.timeline(NodeKind::Checkpoint, label, NodeStatus::Pending);
+                self.checkpoints
+                    .insert(checkpoint_id.to_string(), self.graph.nodes.len() - 1);
+            }
+            EventType::CheckpointPassed => self.checkpoint_outcome(payload, "passed", NodeStatus::Ok),
+            EventType::CheckpointFailed => self.checkpoint_outcome(payload, "failed", NodeStatus::Failed),
+            EventType::CheckpointSkipped => self.checkpoint_outcome(payload, "skipped", NodeStatus::Ok),
+            EventType::ActionRequested => {
+                let Some(action_id) = str_field(payload, "action_id") else { return };
+                let from = str_field(payload, "resolution_id")
+                    .and_then(|r| self.resolutions.get(r).cloned())
+                    .or_else(|| self.last.clone());
+                let Some(from) = from else { return };
+                let outcome = self.node(NodeKind::Outcome, "requested".to_string(), NodeStatus::Pending);
+                self.edge(&from, &outcome, Some(format!("{}: requested", action_id)), true);
+                self.open_actions
+                    .insert(action_id.to_string(), OpenAction { from, outcome });
+            }
+            EventType::ApprovalRequested => {
+                self.action_outcome(payload, "awaiting approval", "awaiting approval", NodeStatus::Pending, false);
+            }
+            EventType::ActionApproved => {
+                self.action_outcome(payload, "approved", "approved", NodeStatus::Pending, false);
+            }
+            EventType::ActionDenied => {
+                let decision = match str_field(payload, "policy_id") {
+                    Some(policy) => format!("denied by {}", policy),
+                    None => "denied".to_string(),
+                };
+                self.action_outcome(payload, &decision, "denied", NodeStatus::Denied, true);
+            }
+            EventType::ActionExecuted => {
+                self.action_outcome(payload, "executed", "executed", NodeStatus::Ok, true);
+            }
+            EventType::ActionFailed => {
+                self.action_outcome(payload, "failed", "failed", NodeStatus::Failed, true);
+            }
+            _ => {}
+        }
+    }
+
+    /// Add a highlighted node where chain verification failed
+    fn chain_break(&mut self, index: usize, verification: &ChainVerification) {
+        let kind = verification
+            .error_type
+            .as_ref()
+            .map_or("chain break".to_string(), |t| t.to_string());
+        let label = format!("Chain break at event {}\n{}", index, kind);
+        self.timeline(NodeKind::ChainBreak, label, NodeStatus::Broken);
+    }
+
+    /// Record the latest outcome of an action
+    ///
+    /// `decision` labels the action edge and `outcome` the outcome node.
+    /// `last` closes the action, so a later request starts a new edge.
+    fn action_outcome(&mut self, payload: &Value, decision: &str, outcome: &str, status: NodeStatus, last: bool) {
+        let Some(action_id) = str_field(payload, "action_id") else { return };
+        let open = match self.open_actions.get(action_id) {
+            Some(open) => OpenAction { from: open.from.clone(), outcome: open.outcome.clone() },
+            None => {
+                // Denied before it was requested (e.g. an invalidated resolution)
+                let Some(from) = self.last.clone() else { return };
+                let node = self.node(NodeKind::Outcome, outcome.to_string(), status);
+                self.edge(&from, &node, None, true);
+                OpenAction { from, outcome: node }
+            }
+        };
+
+        if let Some(node) = self.graph.nodes.iter_mut().find(|n| n.id == open.outcome) {
+            node.label = outcome.to_string();
+            node.status = status;
+        }
+        if let Some(edge) = self
+            .graph
+            .edges
+            .iter_mut()
+            .find(|e| e.from == open.from && e.to == open.outcome)
+        {
+            edge.label = Some(format!("{}: {}", action_id, decision));
+        }
+
+        if last {
+            self.open_actions.remove(action_id);
+        } else {
+            self.open_actions.insert(action_id.to_string(), open);
+        }
+    }
+
+    fn checkpoint_outcome(&mut self, payload: &Value, outcome: &str, status: NodeStatus) {
+        let Some(checkpoint_id) = str_field(payload, "checkpoint_id") else { return };
+        if let Some(&index) = self.checkpoints.get(checkpoint_id) {
+            let node = &mut self.graph.nodes[index];
+            node.label = format!("{}\n{}", checkpoint_id, outcome);
+            node.status = status;
+        }
+    }
+
+    /// Add a node to the session timeline, linked from the previous one
+    fn timeline(&mut self, kind: NodeKind, label: String, status: NodeStatus) -> String {
+        let id = self.node(kind, label, status);
+        if let Some(previous) = self.last.replace(id.clone()) {
+            self.edge(&previous, &id, None, false);
+        }
+        id
+    }
+
+    fn node(&mut self, kind: NodeKind, label: String, status: NodeStatus) -> String {
+        let id = format!("n{}", self.graph.nodes.len());
+        self.graph.nodes.push(GraphNode { id: id.clone(), kind, label, status });
+        id
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: Option<String>, action: bool) {
+        self.graph.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label,
+            action,
+        });
+    }
+}
+
+fn str_field<'a>(payload: &'a Value, field: &str) -> Option<&'a str> {
+    payload.get(field).and_then(Value::as_str)
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::TraceCollector;
+    use serde_json::json;
+
+    fn session_events() -> Vec<TRACEEvent> {
+        let mut collector = TraceCollector::new();
+        let mut emit = |event_type, payload| {
+            collector.emit("s1", event_type, payload).unwrap();
+        };
+        emit(EventType::SessionStarted, json!({"agent_id": "agent-1"}));
+        emit(
+            EventType::CARPResolutionCompleted,
+            json!({"resolution_id": "r1", "decision_type": "allow", "allowed_count": 2, "denied_count": 1}),
+        );
+        emit(EventType::ActionRequested, json!({"action_id": "read", "resolution_id": "r1"}));
+        emit(EventType::ActionApproved, json!({"action_id": "read", "resolution_id": "r1"}));
+        emit(EventType::ActionExecuted, json!({"action_id": "read"}));
+        emit(EventType::ActionRequested, json!({"action_id": "delete", "resolution_id": "r1"}));
+        emit(EventType::ActionDenied, json!({"action_id": "delete", "policy_id": "no-deletes"}));
+        emit(EventType::CheckpointTriggered, json!({"checkpoint_id": "review"}));
+        emit(EventType::CheckpointPassed, json!({"checkpoint_id": "review"}));
+        emit(EventType::SessionEnded, json!({}));
+        collector.get_events("s1").unwrap()
+    }
+
+    #[test]
+    fn test_graph_structure() {
+        let graph = TraceGraph::from_events("s1", &session_events());
+        let kinds: Vec<NodeKind> = graph.nodes.iter().map(|n| n.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                NodeKind::Session,
+                NodeKind::Resolution,
+                NodeKind::Outcome,
+                NodeKind::Outcome,
+                NodeKind::Checkpoint,
+                NodeKind::Session,
+            ]
+        );
+        let labels: Vec<_> = graph.edges.iter().filter_map(|e| e.label.as_deref()).collect();
+        assert_eq!(labels, vec!["read: executed", "delete: denied by no-deletes"]);
+        assert_eq!(graph.nodes[3].status, NodeStatus::Denied);
+        assert_eq!(graph.nodes[4].label, "review\npassed");
+        // Timeline: start -> resolution -> checkpoint -> end
+        assert_eq!(graph.edges.iter().filter(|e| !e.action).count(), 3);
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let events = session_events();
+        let mermaid = visualize("s1", &events, GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("n1 -.->|\"read: executed\"| n2"));
+        assert!(mermaid.contains("n4{\"review<br/>passed\"}"));
+        assert!(mermaid.contains("class n3 denied"));
+
+        let dot = visualize("s1", &events, GraphFormat::Dot);
+        assert!(dot.starts_with("digraph \"s1\" {"));
+        assert!(dot.contains("n4 [label=\"review\\npassed\", shape=diamond];"));
+        assert!(dot.contains("n0 -> n1 [style=bold];"));
+        assert_eq!("dot".parse::<GraphFormat>(), Ok(GraphFormat::Dot));
+    }
+
+    #[test]
+    fn test_chain_break_highlighted() {
+        let mut events = session_events();
+        events[4].payload = json!({"action_id": "read", "tampered": true});
+
+        let graph = TraceGraph::from_events("s1", &events);
+        let broken: Vec<_> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.status == NodeStatus::Broken)
+            .collect();
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0].label.starts_with("Chain break at event 4"));
+        assert!(graph.render(GraphFormat::Mermaid).contains("class n3 broken"));
+    }
+}
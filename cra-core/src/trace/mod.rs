@@ -32,15 +32,22 @@ mod event;
 mod collector;
 mod chain;
 mod replay;
+mod export;
 mod raw;
 mod buffer;
+mod signature;
+mod verbosity;
+mod graph;
+#[cfg(not(feature = "minimal"))]
 mod processor;
+#[cfg(not(feature = "minimal"))]
 mod queue;
 
 pub use event::{
     TRACEEvent, EventType, EventPayload,
     // Session payloads
     SessionStartedPayload, SessionEndedPayload,
+    SessionHandoffOutPayload, SessionHandoffInPayload,
     // CARP payloads
     CARPRequestPayload, CARPResolutionPayload,
     // Action payloads
@@ -53,21 +60,39 @@ pub use event::{
     CheckpointTriggeredPayload, CheckpointQuestionPresentedPayload,
     CheckpointResponseReceivedPayload, CheckpointValidatedPayload,
     CheckpointPassedPayload, CheckpointFailedPayload,
-    CheckpointSkippedPayload, CheckpointGuidanceInjectedPayload,
+    CheckpointSkippedPayload, CheckpointGuidanceInjectedPayload, CheckpointGuidanceExpiredPayload,
+    // Key management payloads
+    KeyRotatedPayload,
+    // Integrity payloads
+    IntegrityViolationPayload,
 };
-pub use collector::{TraceCollector, DeferredConfig};
-pub use chain::{ChainVerification, ChainVerifier};
+pub use cra_trace_verify::{
+    canonical_json, ContentRecord, ExportTrailer, CONTENT_RECORD_TYPE, TRAILER_RECORD_TYPE,
+};
+pub use collector::{TraceCollector, DeferredConfig, EmitCallback, CORRELATION_HEADER};
+#[cfg(feature = "chaos")]
+pub(crate) use collector::injected_fault_payload;
+pub use chain::{ChainErrorType, ChainVerification, ChainVerifier};
 pub use replay::{ReplayEngine, ReplayResult, ReplayDiff};
+pub use export::{TraceExport, EXPORT_CONTENT_TYPE, EXPORT_GZIP_CONTENT_TYPE};
 pub use raw::RawEvent;
 pub use buffer::{TraceRingBuffer, BufferStats};
+pub use signature::{EventSignature, SigningMode, TraceKey, TraceSigner, ED25519};
+pub use verbosity::{TraceVerbosity, MINIMAL_OMITTED_FIELDS};
+pub use graph::{visualize, GraphEdge, GraphFormat, GraphNode, NodeKind, NodeStatus, TraceGraph};
+#[cfg(not(feature = "minimal"))]
 pub use processor::{TraceProcessor, ProcessorConfig, ProcessorHandle};
+#[cfg(not(feature = "minimal"))]
 pub use queue::{AsyncTraceQueue, AsyncQueueConfig, QueueStats};
 
 /// TRACE protocol version
 pub const VERSION: &str = "1.0";
 
+/// Session ID of the administrative audit trace (key rotations)
+pub const ADMIN_AUDIT_SESSION: &str = "cra.admin";
+
 /// Genesis hash - used as previous_event_hash for first event
-pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+pub const GENESIS_HASH: &str = cra_trace_verify::GENESIS_HASH;
 
 #[cfg(test)]
 mod tests {
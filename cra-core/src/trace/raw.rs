@@ -58,7 +58,7 @@ impl RawEvent {
             parent_span_id: None,
             event_type,
             payload,
-            timestamp: Utc::now(),
+            timestamp: crate::clock::now(),
         }
     }
 
@@ -0,0 +1,123 @@
+//! TRACE Event Signatures
+//!
+//! The hash chain proves ordering and integrity; signatures prove origin. A
+//! runtime holding a signing key attaches an [`EventSignature`] over an
+//! event's hash. Because each hash covers the previous one, a valid signature
+//! on event `n` attests the whole chain up to `n`, so signing every event is
+//! optional: [`SigningMode::Batch`] signs every Nth event and the end of the
+//! session.
+//!
+//! Signatures are not part of the event hash. Public keys are distributed as
+//! [`TraceKey`]s, e.g. in an atlas steward's `trace_keys`. Ed25519 signing and
+//! verification live in [`crate::crypto`] (feature `signing`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::event::{EventType, TRACEEvent};
+
+/// Signature algorithm identifier for Ed25519
+pub const ED25519: &str = "ed25519";
+
+/// A signature over an event hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSignature {
+    /// Key that produced the signature
+    pub key_id: String,
+    /// Signature algorithm (currently always "ed25519")
+    pub algorithm: String,
+    /// Signature over the 32 raw bytes of `event_hash`, hex-encoded
+    pub signature: String,
+}
+
+/// A public key trusted to sign TRACE events
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceKey {
+    /// Key identifier, matched against `EventSignature::key_id`
+    pub key_id: String,
+    /// Signature algorithm
+    #[serde(default = "default_algorithm")]
+    pub algorithm: String,
+    /// Public key, hex-encoded
+    pub public_key: String,
+    /// End of the key's overlap window after rotation; events timestamped
+    /// later are not accepted under this key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl TraceKey {
+    /// Check whether the key may sign an event with this timestamp
+    pub fn is_valid_at(&self, timestamp: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires| timestamp <= expires)
+    }
+}
+
+fn default_algorithm() -> String {
+    ED25519.to_string()
+}
+
+/// Which events a signer signs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SigningMode {
+    /// Sign every event
+    #[default]
+    EveryEvent,
+    /// Sign every `interval`th event, checkpoint events, session ends and
+    /// handoffs
+    Batch { interval: u64 },
+}
+
+impl SigningMode {
+    /// Check whether an event should carry a signature
+    pub fn should_sign(&self, event: &TRACEEvent) -> bool {
+        match self {
+            SigningMode::EveryEvent => true,
+            SigningMode::Batch { interval } => {
+                matches!(event.event_type, EventType::SessionEnded | EventType::SessionHandoffOut)
+                    || event.event_type.is_checkpoint_event()
+                    || (*interval > 0 && (event.sequence + 1).is_multiple_of(*interval))
+            }
+        }
+    }
+}
+
+/// Source of event signatures for a [`TraceCollector`](super::TraceCollector)
+pub trait TraceSigner: Send + Sync + std::fmt::Debug {
+    /// Identifier of the signing key
+    fn key_id(&self) -> &str;
+
+    /// Public half of the signing key
+    fn public_key(&self) -> TraceKey;
+
+    /// Sign an event hash (64 hex characters)
+    fn sign_hash(&self, event_hash: &str) -> EventSignature;
+
+    /// Which events to sign
+    fn mode(&self) -> SigningMode {
+        SigningMode::EveryEvent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_mode() {
+        let mode = SigningMode::Batch { interval: 3 };
+        let event = |sequence, event_type| {
+            let mut e = TRACEEvent::new("s".to_string(), "t".to_string(), event_type, json!({}));
+            e.sequence = sequence;
+            e
+        };
+
+        assert!(!mode.should_sign(&event(0, EventType::SessionStarted)));
+        assert!(mode.should_sign(&event(2, EventType::ActionExecuted)));
+        assert!(mode.should_sign(&event(3, EventType::CheckpointPassed)));
+        assert!(mode.should_sign(&event(4, EventType::SessionEnded)));
+        assert!(SigningMode::EveryEvent.should_sign(&event(0, EventType::SessionStarted)));
+    }
+}
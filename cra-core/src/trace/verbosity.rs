@@ -0,0 +1,147 @@
+//! Trace verbosity levels
+//!
+//! A session's verbosity decides which events and payload fields reach its
+//! TRACE. Filtering happens before an event is chained, so a reduced trace
+//! still verifies; what was left out is simply never emitted. Changes of
+//! level are always recorded as `trace.verbosity_changed`.
+//!
+//! | Level | Events | Payloads |
+//! |-------|--------|----------|
+//! | `minimal` | Session lifecycle, decisions and outcomes | Bulky and free-text fields dropped |
+//! | `standard` | All | As emitted |
+//! | `debug` | All | Plus raw action parameters and the full CARP request |
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::event::EventType;
+
+/// Payload fields dropped at [`TraceVerbosity::Minimal`]
+pub const MINIMAL_OMITTED_FIELDS: &[&str] =
+    &["goal", "content", "parameters", "variables", "atlas_ids", "request"];
+
+/// How much detail a session's TRACE records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceVerbosity {
+    /// Lifecycle, decisions and outcomes only
+    Minimal,
+    /// Every event with its usual payload
+    #[default]
+    Standard,
+    /// Every event, with extra payload detail for troubleshooting
+    Debug,
+}
+
+impl TraceVerbosity {
+    /// Whether events of this type are emitted at this level
+    pub fn includes(&self, event_type: EventType) -> bool {
+        match self {
+            TraceVerbosity::Standard | TraceVerbosity::Debug => true,
+            TraceVerbosity::Minimal => {
+                event_type.is_session_event()
+                    || matches!(
+                        event_type,
+                        EventType::CARPResolutionCompleted
+                            | EventType::CARPRequestReplayed
+                            | EventType::ResolutionInvalidated
+                            | EventType::ActionApproved
+                            | EventType::ActionDenied
+                            | EventType::ActionExecuted
+                            | EventType::ActionFailed
+                            | EventType::ApprovalRequested
+                            | EventType::ApprovalDecided
+                            | EventType::PolicyViolated
+                            | EventType::PolicyExternalDecision
+                            | EventType::PolicyEvaluationTimeout
+                            | EventType::CheckpointPassed
+                            | EventType::CheckpointFailed
+                            | EventType::CapabilityStateChanged
+                            | EventType::CapabilityTokenMinted
+                            | EventType::CapabilityTokenUsed
+                            | EventType::CapabilityTokenRevoked
+                            | EventType::KeyRotated
+                            | EventType::GcCompleted
+                            | EventType::IntegrityViolation
+                            | EventType::ErrorOccurred
+                            | EventType::TraceVerbosityChanged
+                            | EventType::ChaosFaultInjected
+                    )
+            }
+        }
+    }
+
+    /// Drop the payload fields this level leaves out
+    pub fn filter_payload(&self, mut payload: Value) -> Value {
+        if let (TraceVerbosity::Minimal, Some(object)) = (self, payload.as_object_mut()) {
+            for field in MINIMAL_OMITTED_FIELDS {
+                object.remove(*field);
+            }
+        }
+        payload
+    }
+
+    /// Whether this is the default level
+    pub fn is_standard(&self) -> bool {
+        *self == TraceVerbosity::Standard
+    }
+
+    /// Lowercase name of the level
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceVerbosity::Minimal => "minimal",
+            TraceVerbosity::Standard => "standard",
+            TraceVerbosity::Debug => "debug",
+        }
+    }
+}
+
+impl fmt::Display for TraceVerbosity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TraceVerbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(TraceVerbosity::Minimal),
+            "standard" => Ok(TraceVerbosity::Standard),
+            "debug" => Ok(TraceVerbosity::Debug),
+            other => Err(format!("unknown trace verbosity '{}'", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_minimal_filters_events_and_fields() {
+        let minimal = TraceVerbosity::Minimal;
+        assert!(minimal.includes(EventType::SessionStarted));
+        assert!(minimal.includes(EventType::ActionDenied));
+        assert!(minimal.includes(EventType::TraceVerbosityChanged));
+        assert!(minimal.includes(EventType::CARPResolutionCompleted));
+        assert!(minimal.includes(EventType::CARPRequestReplayed));
+        assert!(minimal.includes(EventType::ResolutionInvalidated));
+        assert!(minimal.includes(EventType::PolicyExternalDecision));
+        assert!(!minimal.includes(EventType::CARPRequestReceived));
+        assert!(!minimal.includes(EventType::PolicyEvaluated));
+        assert!(!minimal.includes(EventType::ContextInjected));
+        assert!(TraceVerbosity::Standard.includes(EventType::PolicyEvaluated));
+
+        let payload = json!({"agent_id": "a", "goal": "secret plan", "atlas_ids": []});
+        assert_eq!(minimal.filter_payload(payload.clone()), json!({"agent_id": "a"}));
+        assert_eq!(TraceVerbosity::Debug.filter_payload(payload.clone()), payload);
+        assert_eq!("debug".parse::<TraceVerbosity>(), Ok(TraceVerbosity::Debug));
+        assert!("verbose".parse::<TraceVerbosity>().is_err());
+    }
+}
@@ -5,7 +5,9 @@
 
 use cra_core::atlas::{AtlasManifest, AtlasContextBlock, InjectMode};
 use cra_core::carp::{CARPRequest, Decision, Resolver};
-use cra_core::trace::EventType;
+use cra_core::clock::FixedClock;
+use cra_core::id::SequentialIdGen;
+use cra_core::trace::{EventType, ReplayEngine, TRACEEvent};
 use serde_json::{json, Value};
 
 /// Load the simple-resolve test atlas
@@ -348,6 +350,46 @@ fn conformance_policy_deny_takes_precedence() {
     assert!(allowed_ids.contains(&"data.get"), "data.get should be allowed");
 }
 
+/// Run the simple-resolve scenario with a fixed clock and sequential IDs
+fn deterministic_simple_resolve_trace() -> Vec<TRACEEvent> {
+    let mut resolver = Resolver::new()
+        .with_clock(FixedClock::from_millis(1_700_000_000_000))
+        .with_id_generator(SequentialIdGen::new());
+    resolver.load_atlas(load_simple_atlas()).expect("Failed to load atlas");
+
+    let session_id = resolver
+        .create_session("test-agent", "I need to read and manage data")
+        .expect("Failed to create session");
+    let request = CARPRequest::new(
+        session_id.clone(),
+        "test-agent".to_string(),
+        "I need to read and manage data".to_string(),
+    );
+    resolver.resolve(&request).expect("Failed to resolve");
+
+    resolver.get_trace(&session_id).expect("Failed to get trace")
+}
+
+#[test]
+fn conformance_deterministic_trace_is_reproducible() {
+    let first = deterministic_simple_resolve_trace();
+    let second = deterministic_simple_resolve_trace();
+
+    // Same clock and IDs yield the same chain, hashes included
+    let first_hashes: Vec<&str> = first.iter().map(|e| e.event_hash.as_str()).collect();
+    let second_hashes: Vec<&str> = second.iter().map(|e| e.event_hash.as_str()).collect();
+    assert_eq!(first_hashes, second_hashes);
+
+    let engine = ReplayEngine::new().with_atlas(load_simple_atlas());
+    assert!(engine.replay(&first).expect("Replay failed").success);
+    assert!(engine.diff(&first, &second).identical);
+
+    // Event shape still matches the golden trace
+    let expected = load_expected_trace();
+    assert_eq!(first[0].event_type.to_string(), expected[0]["event_type"]);
+    assert_eq!(first[0].previous_event_hash, expected[0]["previous_event_hash"]);
+}
+
 #[test]
 fn conformance_genesis_event_hash() {
     use cra_core::trace::GENESIS_HASH;
@@ -0,0 +1,350 @@
+//! Golden trace conformance suite
+//!
+//! Each directory under `specs/conformance/golden/` with a `script.json`
+//! is a scenario: a list of resolver calls run in deterministic mode (fixed
+//! clock, sequential IDs). The resulting TRACE JSONL must match the checked-in
+//! `golden-trace.jsonl` exactly, hashes included.
+//!
+//! The same scripts are run through the Python, Node and WASM bindings by the
+//! runners in `specs/conformance/runners/`. Here they run through the core
+//! resolver and the C FFI.
+//!
+//! To regenerate the goldens after an intentional trace change:
+//!
+//! ```text
+//! CRA_BLESS=1 cargo test -p cra-core --test golden_traces
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use cra_core::atlas::AtlasManifest;
+use cra_core::carp::{CARPRequest, Resolver};
+use cra_core::clock::FixedClock;
+use cra_core::id::SequentialIdGen;
+use cra_core::trace::{ChainVerifier, TRACEEvent};
+use serde::Deserialize;
+use serde_json::Value;
+
+const GOLDEN_FILE: &str = "golden-trace.jsonl";
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    epoch_ms: i64,
+    id_seed: u64,
+    atlases: Vec<String>,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Step {
+    CreateSession {
+        agent_id: String,
+        goal: String,
+    },
+    Resolve {
+        goal: String,
+    },
+    Execute {
+        action_id: String,
+        #[serde(default)]
+        parameters: Value,
+        #[serde(default)]
+        expect_error: bool,
+    },
+    EndSession,
+}
+
+/// The resolver surface a binding exposes to the suite
+trait Binding {
+    fn load_atlas_json(&mut self, json: &str) -> Result<String, String>;
+    fn create_session(&mut self, agent_id: &str, goal: &str) -> Result<String, String>;
+    fn resolve(&mut self, session_id: &str, agent_id: &str, goal: &str) -> Result<Value, String>;
+    fn execute(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: &Value,
+    ) -> Result<Value, String>;
+    fn end_session(&mut self, session_id: &str) -> Result<(), String>;
+    fn get_trace(&self, session_id: &str) -> Result<String, String>;
+}
+
+struct CoreBinding(Resolver);
+
+impl CoreBinding {
+    fn deterministic(epoch_ms: i64, seed: u64) -> Self {
+        Self(
+            Resolver::new()
+                .with_clock(FixedClock::from_millis(epoch_ms))
+                .with_id_generator(SequentialIdGen::with_seed(seed)),
+        )
+    }
+}
+
+impl Binding for CoreBinding {
+    fn load_atlas_json(&mut self, json: &str) -> Result<String, String> {
+        let manifest: AtlasManifest = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        self.0.load_atlas(manifest).map_err(|e| e.to_string())
+    }
+
+    fn create_session(&mut self, agent_id: &str, goal: &str) -> Result<String, String> {
+        self.0.create_session(agent_id, goal).map_err(|e| e.to_string())
+    }
+
+    fn resolve(&mut self, session_id: &str, agent_id: &str, goal: &str) -> Result<Value, String> {
+        let request = CARPRequest::new(session_id.to_string(), agent_id.to_string(), goal.to_string());
+        let resolution = self.0.resolve(&request).map_err(|e| e.to_string())?;
+        serde_json::to_value(resolution).map_err(|e| e.to_string())
+    }
+
+    fn execute(
+        &mut self,
+        session_id: &str,
+        resolution_id: &str,
+        action_id: &str,
+        parameters: &Value,
+    ) -> Result<Value, String> {
+        self.0
+            .execute(session_id, resolution_id, action_id, parameters.clone())
+            .map_err(|e| e.to_string())
+    }
+
+    fn end_session(&mut self, session_id: &str) -> Result<(), String> {
+        self.0.end_session(session_id).map_err(|e| e.to_string())
+    }
+
+    fn get_trace(&self, session_id: &str) -> Result<String, String> {
+        let events = self.0.get_trace(session_id).map_err(|e| e.to_string())?;
+        let lines: Result<Vec<String>, _> = events.iter().map(serde_json::to_string).collect();
+        lines.map(|l| l.join("\n")).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "ffi")]
+mod ffi_binding {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_char;
+
+    use cra_core::ffi::*;
+    use serde_json::Value;
+
+    use super::Binding;
+
+    pub struct FfiBinding(*mut CRAResolver);
+
+    impl FfiBinding {
+        pub fn deterministic(epoch_ms: i64, seed: u64) -> Self {
+            Self(cra_resolver_new_deterministic(epoch_ms, seed))
+        }
+    }
+
+    impl Drop for FfiBinding {
+        fn drop(&mut self) {
+            cra_resolver_free(self.0);
+        }
+    }
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    /// Take ownership of a returned string, or the last error if null
+    fn take(ptr: *mut c_char) -> Result<String, String> {
+        if ptr.is_null() {
+            let err = cra_get_last_error();
+            if err.is_null() {
+                return Err("unknown error".to_string());
+            }
+            let msg = unsafe { CStr::from_ptr(err) }.to_string_lossy().into_owned();
+            cra_free_string(err);
+            return Err(msg);
+        }
+        let s = unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+        cra_free_string(ptr);
+        Ok(s)
+    }
+
+    fn take_json(ptr: *mut c_char) -> Result<Value, String> {
+        take(ptr).and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    }
+
+    impl Binding for FfiBinding {
+        fn load_atlas_json(&mut self, json: &str) -> Result<String, String> {
+            take(cra_resolver_load_atlas_json(self.0, c(json).as_ptr()))
+        }
+
+        fn create_session(&mut self, agent_id: &str, goal: &str) -> Result<String, String> {
+            take(cra_resolver_create_session(self.0, c(agent_id).as_ptr(), c(goal).as_ptr()))
+        }
+
+        fn resolve(&mut self, session_id: &str, agent_id: &str, goal: &str) -> Result<Value, String> {
+            take_json(cra_resolver_resolve(
+                self.0,
+                c(session_id).as_ptr(),
+                c(agent_id).as_ptr(),
+                c(goal).as_ptr(),
+            ))
+        }
+
+        fn execute(
+            &mut self,
+            session_id: &str,
+            resolution_id: &str,
+            action_id: &str,
+            parameters: &Value,
+        ) -> Result<Value, String> {
+            take_json(cra_resolver_execute(
+                self.0,
+                c(session_id).as_ptr(),
+                c(resolution_id).as_ptr(),
+                c(action_id).as_ptr(),
+                c(&parameters.to_string()).as_ptr(),
+            ))
+        }
+
+        fn end_session(&mut self, session_id: &str) -> Result<(), String> {
+            match cra_resolver_end_session(self.0, c(session_id).as_ptr()) {
+                0 => Ok(()),
+                _ => take(std::ptr::null_mut()).map(|_| ()),
+            }
+        }
+
+        fn get_trace(&self, session_id: &str) -> Result<String, String> {
+            take(cra_resolver_get_trace(self.0, c(session_id).as_ptr()))
+        }
+    }
+}
+
+fn golden_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../specs/conformance/golden")
+}
+
+/// Scenario directories that have a script
+fn scenarios() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(golden_root())
+        .expect("Failed to read golden directory")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join("script.json").is_file())
+        .collect();
+    dirs.sort();
+    assert!(!dirs.is_empty(), "No golden scenarios found");
+    dirs
+}
+
+fn load_script(dir: &Path) -> Script {
+    let text = std::fs::read_to_string(dir.join("script.json")).expect("Failed to read script.json");
+    serde_json::from_str(&text).expect("Failed to parse script.json")
+}
+
+/// Run a scenario script and return the session trace as JSONL
+fn run_script(dir: &Path, binding: &mut dyn Binding) -> Result<String, String> {
+    let script = load_script(dir);
+
+    for atlas in &script.atlases {
+        let json = std::fs::read_to_string(dir.join(atlas)).map_err(|e| format!("{}: {}", atlas, e))?;
+        binding.load_atlas_json(&json)?;
+    }
+
+    let mut session: Option<(String, String)> = None;
+    let mut resolution_id: Option<String> = None;
+
+    for (i, step) in script.steps.iter().enumerate() {
+        let current = || session.clone().ok_or(format!("step {}: no session", i));
+        match step {
+            Step::CreateSession { agent_id, goal } => {
+                let session_id = binding.create_session(agent_id, goal)?;
+                session = Some((session_id, agent_id.clone()));
+            }
+            Step::Resolve { goal } => {
+                let (session_id, agent_id) = current()?;
+                let resolution = binding.resolve(&session_id, &agent_id, goal)?;
+                resolution_id = resolution["trace_id"].as_str().map(str::to_string);
+            }
+            Step::Execute { action_id, parameters, expect_error } => {
+                let (session_id, _) = current()?;
+                let resolution = resolution_id.clone().ok_or(format!("step {}: no resolution", i))?;
+                let result = binding.execute(&session_id, &resolution, action_id, parameters);
+                if result.is_err() != *expect_error {
+                    return Err(format!("step {}: execute {} returned {:?}", i, action_id, result));
+                }
+            }
+            Step::EndSession => {
+                let (session_id, _) = current()?;
+                binding.end_session(&session_id)?;
+            }
+        }
+    }
+
+    let (session_id, _) = session.ok_or("script never created a session")?;
+    binding.get_trace(&session_id)
+}
+
+/// Compare a trace with the scenario's golden file (or rewrite it under CRA_BLESS)
+fn check_golden(dir: &Path, actual: &str) {
+    let path = dir.join(GOLDEN_FILE);
+    if std::env::var_os("CRA_BLESS").is_some() {
+        std::fs::write(&path, format!("{}\n", actual)).expect("Failed to write golden trace");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {} (run with CRA_BLESS=1 to create)", path.display(), e));
+    let parse = |text: &str| -> Vec<Value> {
+        text.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).expect("Invalid JSONL line"))
+            .collect()
+    };
+    let (expected, actual) = (parse(&expected), parse(actual));
+
+    for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+        assert_eq!(a, e, "{}: event {} differs from golden", dir.display(), i);
+    }
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{}: event count differs from golden",
+        dir.display()
+    );
+}
+
+fn run_all(make: impl Fn(&Script) -> Box<dyn Binding>) {
+    for dir in scenarios() {
+        let mut binding = make(&load_script(&dir));
+        let trace = run_script(&dir, binding.as_mut())
+            .unwrap_or_else(|e| panic!("{}: {}", dir.display(), e));
+        check_golden(&dir, &trace);
+    }
+}
+
+#[test]
+fn golden_traces_core() {
+    run_all(|script| Box::new(CoreBinding::deterministic(script.epoch_ms, script.id_seed)));
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn golden_traces_ffi() {
+    run_all(|script| Box::new(ffi_binding::FfiBinding::deterministic(script.epoch_ms, script.id_seed)));
+}
+
+#[test]
+fn golden_traces_are_valid_chains() {
+    if std::env::var_os("CRA_BLESS").is_some() {
+        return;
+    }
+    for dir in scenarios() {
+        let Ok(text) = std::fs::read_to_string(dir.join(GOLDEN_FILE)) else {
+            continue;
+        };
+        let events: Vec<TRACEEvent> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).expect("Invalid golden event"))
+            .collect();
+        let verification = ChainVerifier::verify(&events);
+        assert!(verification.is_valid, "{}: {:?}", dir.display(), verification.error_message);
+    }
+}
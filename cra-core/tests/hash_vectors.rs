@@ -0,0 +1,45 @@
+//! Canonical JSON and event hash vectors from specs/conformance/vectors/
+//!
+//! The same vectors are checked by the Python and JavaScript reference
+//! verifiers in specs/conformance/runners/, so all three agree on TRACE hashes.
+
+use cra_core::trace::{canonical_json, ChainVerifier, TRACEEvent};
+use serde_json::Value;
+
+fn load_vectors(json: &str) -> Vec<Value> {
+    let file: Value = serde_json::from_str(json).expect("Failed to parse vectors");
+    file["vectors"].as_array().expect("Missing vectors").clone()
+}
+
+#[test]
+fn canonical_json_vectors() {
+    let vectors = load_vectors(include_str!("../../specs/conformance/vectors/canonical-json.json"));
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let input: Value = serde_json::from_str(vector["input"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            canonical_json(&input),
+            vector["canonical"].as_str().unwrap(),
+            "vector {}",
+            vector["name"]
+        );
+    }
+}
+
+#[test]
+fn event_hash_vectors() {
+    let vectors = load_vectors(include_str!("../../specs/conformance/vectors/trace-hash.json"));
+    let mut events = Vec::new();
+
+    for vector in vectors {
+        let event: TRACEEvent = serde_json::from_value(vector["event"].clone()).unwrap();
+        assert_eq!(canonical_json(&event.payload), vector["canonical_payload"].as_str().unwrap());
+        assert_eq!(event.compute_hash(), vector["event_hash"].as_str().unwrap(), "vector {}", vector["name"]);
+        assert_eq!(event.event_hash, vector["event_hash"].as_str().unwrap());
+        events.push(event);
+    }
+
+    // The vectors form a chain
+    assert!(ChainVerifier::verify(&events).is_valid);
+}
@@ -0,0 +1,153 @@
+//! Property-based tests for TRACE chain integrity and atlas parsing.
+//!
+//! These complement the cargo-fuzz targets in `cra-core/fuzz/`: the same
+//! invariants, but run on every `cargo test`.
+
+use cra_core::atlas::{AtlasManifest, AtlasValidator};
+use cra_core::carp::PolicyEvaluator;
+use cra_core::trace::{ChainVerifier, EventType, TRACEEvent, TraceCollector};
+use proptest::prelude::*;
+use serde_json::json;
+
+/// Largest integer with an exact IEEE 754 double representation
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+fn event_type() -> impl Strategy<Value = EventType> {
+    prop::sample::select(vec![
+        EventType::CARPRequestReceived,
+        EventType::CARPResolutionCompleted,
+        EventType::ActionRequested,
+        EventType::ActionExecuted,
+        EventType::ActionDenied,
+        EventType::PolicyEvaluated,
+        EventType::ContextInjected,
+        EventType::CheckpointTriggered,
+        EventType::CapabilityStateChanged,
+    ])
+}
+
+/// Emit a session.started event followed by the given operations
+fn build_chain(ops: &[(EventType, String, i64)]) -> Vec<TRACEEvent> {
+    let mut collector = TraceCollector::new();
+    collector
+        .emit("session", EventType::SessionStarted, json!({"agent_id": "prop"}))
+        .unwrap();
+    for (event_type, text, number) in ops {
+        collector
+            .emit("session", *event_type, json!({"text": text, "number": number}))
+            .unwrap();
+    }
+    collector.get_events("session").unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn any_emit_sequence_yields_verifiable_chain(
+        ops in prop::collection::vec((event_type(), ".{0,24}", any::<i64>()), 0..24)
+    ) {
+        let events = build_chain(&ops);
+        let verification = ChainVerifier::verify(&events);
+        prop_assert!(verification.is_valid, "{:?}", verification.error_message);
+        prop_assert_eq!(verification.event_count, ops.len() + 1);
+    }
+
+    #[test]
+    fn single_byte_mutation_breaks_verification(
+        // Canonical JSON hashes numbers as IEEE doubles, so only integers
+        // a double represents exactly are guaranteed to hash distinctly
+        ops in prop::collection::vec((event_type(), "[a-z ]{0,16}", -MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER), 1..8),
+        position in any::<prop::sample::Index>(),
+        flip in 1u8..=255,
+    ) {
+        let events = build_chain(&ops);
+        let original: Vec<String> = events.iter().map(|e| serde_json::to_string(e).unwrap()).collect();
+        let mut bytes = original.join("\n").into_bytes();
+
+        let i = position.index(bytes.len());
+        bytes[i] ^= flip;
+
+        // Invalid UTF-8 or JSON is detected before verification
+        let Ok(text) = String::from_utf8(bytes) else { return Ok(()) };
+        let parsed: Result<Vec<TRACEEvent>, _> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect();
+        let Ok(parsed) = parsed else { return Ok(()) };
+
+        // Mutations that decode to the same events (e.g. an equivalent
+        // timestamp spelling) don't tamper with anything
+        let reserialized: Vec<String> = parsed.iter().map(|e| serde_json::to_string(e).unwrap()).collect();
+        prop_assume!(reserialized != original);
+
+        prop_assert!(!ChainVerifier::verify(&parsed).is_valid);
+    }
+
+    #[test]
+    fn atlas_parsing_never_panics(input in ".{0,256}") {
+        if let Ok(manifest) = serde_json::from_str::<AtlasManifest>(&input) {
+            let _ = AtlasValidator::new().validate(&manifest);
+        }
+    }
+
+    #[test]
+    fn atlas_parsing_of_json_values_never_panics(
+        atlas_id in ".{0,16}",
+        actions in prop::collection::vec(".{0,16}", 0..4),
+        patterns in prop::collection::vec(".{0,16}", 0..4),
+    ) {
+        let value = json!({
+            "atlas_version": "1.0",
+            "atlas_id": atlas_id,
+            "version": "1.0.0",
+            "name": "Prop",
+            "description": "Generated",
+            "capabilities": [{"capability_id": "cap", "name": "Cap", "actions": actions.clone()}],
+            "policies": [{"policy_id": "p", "type": "deny", "actions": patterns}],
+            "actions": actions.iter().map(|a| json!({
+                "action_id": a,
+                "name": a,
+                "description": "",
+                "parameters_schema": {"type": "object"},
+                "risk_tier": "low"
+            })).collect::<Vec<_>>(),
+        });
+
+        if let Ok(manifest) = serde_json::from_value::<AtlasManifest>(value) {
+            let _ = AtlasValidator::new().validate(&manifest);
+            let _ = manifest.validate();
+        }
+    }
+
+    #[test]
+    fn policy_pattern_matching(pattern in ".{0,16}", action_id in ".{0,16}") {
+        let evaluator = PolicyEvaluator::new();
+
+        // Never panics, even on multi-byte input
+        let _ = evaluator.pattern_matches(&pattern, &action_id);
+
+        prop_assert!(evaluator.pattern_matches("*", &action_id));
+        prop_assert!(evaluator.pattern_matches(&action_id, &action_id));
+    }
+
+    #[test]
+    fn policy_wildcards_respect_segments(
+        head in "[a-z]{1,8}",
+        tail in "[a-z]{1,8}",
+        other in "[a-z]{1,8}",
+    ) {
+        let evaluator = PolicyEvaluator::new();
+        let action_id = format!("{}.{}", head, tail);
+
+        let prefix_pattern = format!("{}.*", head);
+        let suffix_pattern = format!("*.{}", tail);
+        prop_assert!(evaluator.pattern_matches(&prefix_pattern, &action_id));
+        prop_assert!(evaluator.pattern_matches(&suffix_pattern, &action_id));
+
+        // A prefix that only matches part of a segment must not match
+        let glued = format!("{}{}.{}", head, other, tail);
+        prop_assert!(!evaluator.pattern_matches(&prefix_pattern, &glued));
+    }
+}
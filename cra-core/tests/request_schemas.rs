@@ -0,0 +1,35 @@
+//! The request schemas published in specs/schemas must match the ones
+//! servers validate with
+
+use cra_core::request_schema::RequestEndpoint;
+use serde_json::Value;
+
+fn published(endpoint: RequestEndpoint) -> Value {
+    let json = match endpoint {
+        RequestEndpoint::CreateSession => include_str!("../../specs/schemas/create-session-request.schema.json"),
+        RequestEndpoint::Resolve => include_str!("../../specs/schemas/resolve-request.schema.json"),
+        RequestEndpoint::Execute => include_str!("../../specs/schemas/execute-request.schema.json"),
+    };
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_published_request_schemas_match() {
+    for endpoint in RequestEndpoint::ALL {
+        assert_eq!(
+            &published(endpoint),
+            endpoint.schema(),
+            "specs/schemas/{}.schema.json is out of date",
+            endpoint.schema_name()
+        );
+    }
+}
+
+#[test]
+fn test_openapi_references_request_schemas() {
+    let openapi = include_str!("../../specs/openapi.yaml");
+    for endpoint in RequestEndpoint::ALL {
+        let reference = format!("./schemas/{}.schema.json", endpoint.schema_name());
+        assert!(openapi.contains(&reference), "openapi.yaml doesn't reference {}", reference);
+    }
+}
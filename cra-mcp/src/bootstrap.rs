@@ -7,11 +7,20 @@
 //! 4. CRA streams context (CONTEXT)
 //! 5. Agent signals ready (READY)
 //! 6. CRA confirms session (SESSION)
+//!
+//! The `cra_bootstrap` tool runs the whole handshake in one call with
+//! [`cra_core::Resolver::bootstrap`]; its result ([`BootstrapResult`]) is
+//! the shared [`BootstrapResponse`] wire schema. Rules, policies and
+//! context blocks in the streamed messages use the same types.
 
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+pub use cra_core::carp::{
+    BootstrapContext, BootstrapRequest, BootstrapResponse, GovernanceRule, PolicySummary,
+    BOOTSTRAP_PROTOCOL_VERSION,
+};
+
 use crate::error::{McpError, McpResult};
 use crate::session::Session;
 
@@ -93,36 +102,6 @@ pub struct GovernanceMessage {
 
 fn default_true() -> bool { true }
 
-/// A governance rule
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GovernanceRule {
-    /// Rule identifier
-    pub rule_id: String,
-
-    /// Human-readable description
-    pub description: String,
-
-    /// Enforcement level: "hard" (enforced), "soft" (encouraged)
-    #[serde(default = "default_soft")]
-    pub enforcement: String,
-}
-
-fn default_soft() -> String { "soft".to_string() }
-
-/// Summary of a policy
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PolicySummary {
-    /// Policy identifier
-    pub policy_id: String,
-
-    /// Description of what the policy does
-    pub description: String,
-
-    /// Actions affected by this policy
-    #[serde(default)]
-    pub actions_affected: Vec<String>,
-}
-
 /// ACK message - agent acknowledges governance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AckMessage {
@@ -166,25 +145,6 @@ pub struct ContextMessage {
     pub more_available: bool,
 }
 
-/// A context block sent during bootstrap
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BootstrapContext {
-    /// Context identifier
-    pub context_id: String,
-
-    /// Priority (higher = more important)
-    pub priority: i32,
-
-    /// Injection mode for this context
-    pub inject_mode: String,
-
-    /// The actual content
-    pub content: String,
-
-    /// Hash of the content
-    pub digest: String,
-}
-
 /// READY message - agent signals wrapper complete
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadyMessage {
@@ -294,23 +254,7 @@ impl BootstrapProtocol {
         let governance = GovernanceMessage {
             session_id: session.session_id.clone(),
             genesis_hash: session.genesis_hash.clone(),
-            rules: vec![
-                GovernanceRule {
-                    rule_id: "trace.required".to_string(),
-                    description: "All actions must be reported through cra_report_action".to_string(),
-                    enforcement: "hard".to_string(),
-                },
-                GovernanceRule {
-                    rule_id: "context.must_request".to_string(),
-                    description: "Request context before making domain-specific decisions".to_string(),
-                    enforcement: "soft".to_string(),
-                },
-                GovernanceRule {
-                    rule_id: "feedback.expected".to_string(),
-                    description: "Provide feedback on context usefulness".to_string(),
-                    enforcement: "soft".to_string(),
-                },
-            ],
+            rules: GovernanceRule::standard(),
             policies: Vec::new(), // Would be populated from loaded atlases
             acknowledgment_required: true,
         };
@@ -349,7 +293,7 @@ impl BootstrapProtocol {
                 .map(|a| a.understood)
                 .unwrap_or(false);
 
-            if !acked && rule.enforcement == "hard" {
+            if !acked && rule.is_hard() {
                 return Err(McpError::Validation(format!(
                     "Rule {} not acknowledged but has hard enforcement",
                     rule.rule_id
@@ -439,53 +383,58 @@ impl Default for BootstrapProtocol {
     }
 }
 
-/// Simplified bootstrap result for MCP tool response
+/// Result of the `cra_bootstrap` tool
+///
+/// The shared bootstrap response, plus what the agent must do in terms of
+/// this server's tools.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootstrapResult {
-    /// Session ID
-    pub session_id: String,
-
-    /// Genesis hash
-    pub genesis_hash: String,
-
-    /// Governance rules
-    pub governance: GovernanceSection,
-
-    /// Initial context blocks
-    pub context: Vec<BootstrapContext>,
+    /// Session, governance, context and chain state
+    #[serde(flatten)]
+    pub bootstrap: BootstrapResponse,
 
-    /// Chain state
-    pub chain_state: ChainState,
+    /// Things the agent MUST do
+    pub you_must: Vec<String>,
 
     /// Whether bootstrap is complete
     pub ready: bool,
 
     /// Message to the agent
     pub message: String,
-}
-
-/// Governance section of bootstrap result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GovernanceSection {
-    /// Rules to follow
-    pub rules: Vec<GovernanceRule>,
 
-    /// Policies in effect
-    pub policies: Vec<PolicySummary>,
+    /// Token to present to `cra_bootstrap` after reconnecting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
 
-    /// Things the agent MUST do
-    pub you_must: Vec<String>,
+    /// Whether this re-attached to an existing session
+    #[serde(default)]
+    pub resumed: bool,
 }
 
-/// Chain state in bootstrap result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ChainState {
-    /// Current hash
-    pub current_hash: String,
-
-    /// Sequence number
-    pub sequence: u64,
+impl BootstrapResult {
+    /// Wrap a bootstrap response for the tool result
+    pub fn new(bootstrap: BootstrapResponse) -> Self {
+        Self {
+            bootstrap,
+            you_must: vec![
+                "Report all significant actions via cra_report_action".to_string(),
+                "Request context when unsure about domain specifics".to_string(),
+                "Provide feedback on context usefulness".to_string(),
+            ],
+            ready: true,
+            message: "Governance established. Context internalized. You may begin.".to_string(),
+            resume_token: None,
+            resumed: false,
+        }
+    }
 
-    /// Whether chain is verified
-    pub verified: bool,
+    /// Attach the resume token; `resumed` marks a re-attached session
+    pub fn with_resume_token(mut self, token: impl Into<String>, resumed: bool) -> Self {
+        self.resume_token = Some(token.into());
+        self.resumed = resumed;
+        if resumed {
+            self.message = "Session resumed. Governance and context are unchanged. Continue where you left off.".to_string();
+        }
+        self
+    }
 }
@@ -24,6 +24,14 @@ pub enum McpError {
     #[error("Invalid session ID: {0}")]
     InvalidSession(String),
 
+    /// Resume token's session has ended or expired
+    #[error("Resume token '{0}' no longer refers to an active session. Call cra_bootstrap without resume_token.")]
+    ResumeExpired(String),
+
+    /// Resume token was issued to a different agent
+    #[error("Resume token was not issued to agent '{0}'. Call cra_bootstrap without resume_token.")]
+    ResumeForbidden(String),
+
     /// Atlas-related errors
     #[error("Atlas error: {0}")]
     Atlas(String),
@@ -80,6 +88,8 @@ impl McpError {
             McpError::InvalidSession(_) => -32003,
             McpError::AtlasNotFound(_) => -32004,
             McpError::ActionDenied(_) => -32005,
+            McpError::ResumeExpired(_) => -32006,
+            McpError::ResumeForbidden(_) => -32007,
             McpError::Validation(_) => -32600,
             McpError::Core(_) => -32603,
             McpError::Io(_) => -32603,
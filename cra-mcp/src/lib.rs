@@ -62,7 +62,7 @@ pub mod bootstrap;
 
 pub use server::McpServer;
 pub use error::{McpError, McpResult};
-pub use session::SessionManager;
+pub use session::{ResumableBootstrap, SessionManager};
 pub use bootstrap::BootstrapProtocol;
 
 /// Server metadata for MCP protocol
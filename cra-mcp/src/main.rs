@@ -11,8 +11,18 @@
 //!
 //! # Run without atlases (agents can load them later)
 //! cra-mcp-server
+//!
+//! # Persist traces using a storage configuration file
+//! cra-mcp-server --storage-config storage.json
+//!
+//! # Expire sessions after 10 minutes without a tool call
+//! cra-mcp-server --session-idle-timeout 600
 //! ```
 //!
+//! A storage configuration selects the trace backend, e.g.
+//! `{"type": "file", "directory": "/var/lib/cra/traces"}`. Without one,
+//! traces are kept in memory.
+//!
 //! ## Configuration
 //!
 //! For Claude Code, add to `~/.claude/claude_code_config.json`:
@@ -28,9 +38,13 @@
 //! }
 //! ```
 
+use std::time::Duration;
+
 use clap::Parser;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use cra_core::storage::StorageConfig;
+use cra_core::SessionTTLConfig;
 use cra_mcp::McpServer;
 
 /// CRA MCP Server - Governance layer for AI agents
@@ -42,6 +56,14 @@ struct Args {
     #[arg(short, long)]
     atlases: Option<String>,
 
+    /// JSON file selecting the trace storage backend
+    #[arg(long)]
+    storage_config: Option<String>,
+
+    /// Seconds without a tool call before a session expires (default 3600)
+    #[arg(long)]
+    session_idle_timeout: Option<u64>,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
@@ -69,6 +91,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         builder = builder.with_atlases_dir(atlases_dir);
     }
 
+    if let Some(path) = &args.storage_config {
+        let config = StorageConfig::from_file(path)?;
+        tracing::info!("Using {} trace storage from: {}", config.kind(), path);
+        builder = builder.with_storage_config(config);
+    }
+
+    if let Some(secs) = args.session_idle_timeout {
+        builder = builder.with_session_ttl(SessionTTLConfig::new().idle_timeout(Duration::from_secs(secs)));
+    }
+
     let server = builder.build().await?;
 
     // Run on stdio
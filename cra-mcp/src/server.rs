@@ -2,11 +2,13 @@
 
 use std::sync::Arc;
 
+use cra_core::storage::{StorageConfig, StorageFactory};
+use cra_core::SessionTTLConfig;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
-use crate::bootstrap::{BootstrapProtocol, BootstrapResult, BootstrapContext, GovernanceSection, ChainState, GovernanceRule, PolicySummary};
+use crate::bootstrap::{BootstrapRequest, BootstrapResult};
 use crate::error::{McpError, McpResult};
 use crate::session::SessionManager;
 use crate::tools::{self, ToolDefinition};
@@ -150,6 +152,12 @@ impl McpServer {
             .cloned()
             .unwrap_or(json!({}));
 
+        // Orphaned sessions are reaped lazily, whenever a tool is called
+        let expired = self.session_manager.expire_idle_sessions()?;
+        if !expired.is_empty() {
+            tracing::info!("Expired {} idle session(s): {}", expired.len(), expired.join(", "));
+        }
+
         let result = match name {
             "cra_start_session" => self.call_start_session(arguments).await?,
             "cra_end_session" => self.call_end_session(arguments).await?,
@@ -352,59 +360,18 @@ impl McpServer {
     async fn call_bootstrap(&self, args: Value) -> McpResult<Value> {
         let input: tools::session::BootstrapInput = serde_json::from_value(args)?;
 
-        // Start session
-        let session = self.session_manager.start_session(
-            "mcp-agent".to_string(),
-            input.intent.clone(),
-            None,
-        )?;
-
-        // Create bootstrap result with full governance info
-        let result = BootstrapResult {
-            session_id: session.session_id.clone(),
-            genesis_hash: session.genesis_hash.clone(),
-            governance: GovernanceSection {
-                rules: vec![
-                    GovernanceRule {
-                        rule_id: "trace.required".to_string(),
-                        description: "All actions must be reported through cra_report_action".to_string(),
-                        enforcement: "hard".to_string(),
-                    },
-                    GovernanceRule {
-                        rule_id: "context.must_request".to_string(),
-                        description: "Request context before making domain-specific decisions".to_string(),
-                        enforcement: "soft".to_string(),
-                    },
-                    GovernanceRule {
-                        rule_id: "feedback.expected".to_string(),
-                        description: "Provide feedback on context usefulness".to_string(),
-                        enforcement: "soft".to_string(),
-                    },
-                ],
-                policies: Vec::new(),
-                you_must: vec![
-                    "Report all significant actions via cra_report_action".to_string(),
-                    "Request context when unsure about domain specifics".to_string(),
-                    "Provide feedback on context usefulness".to_string(),
-                ],
-            },
-            context: Vec::new(), // Would be populated from atlases
-            chain_state: ChainState {
-                current_hash: session.current_hash.clone(),
-                sequence: session.event_count,
-                verified: true,
-            },
-            ready: true,
-            message: "Governance established. Context internalized. You may begin.".to_string(),
-        };
+        let request = BootstrapRequest::new("mcp-agent", input.intent).with_capabilities(input.capabilities);
+        let bootstrap = self.session_manager.bootstrap_resumable(&request, input.resume_token.as_deref())?;
 
-        Ok(json!(result))
+        Ok(json!(BootstrapResult::new(bootstrap.response).with_resume_token(bootstrap.resume_token, bootstrap.resumed)))
     }
 }
 
 /// Builder for McpServer
 pub struct McpServerBuilder {
     atlases_dir: Option<String>,
+    storage: StorageConfig,
+    session_ttl: SessionTTLConfig,
     name: String,
     version: String,
 }
@@ -413,6 +380,8 @@ impl McpServerBuilder {
     pub fn new() -> Self {
         Self {
             atlases_dir: None,
+            storage: StorageConfig::default(),
+            session_ttl: SessionTTLConfig::default(),
             name: crate::SERVER_NAME.to_string(),
             version: crate::SERVER_VERSION.to_string(),
         }
@@ -423,18 +392,32 @@ impl McpServerBuilder {
         self
     }
 
+    /// Persist TRACE events to the backend `config` describes (default: memory)
+    pub fn with_storage_config(mut self, config: StorageConfig) -> Self {
+        self.storage = config;
+        self
+    }
+
+    /// Expire sessions left idle or open past `config`'s limits
+    pub fn with_session_ttl(mut self, config: SessionTTLConfig) -> Self {
+        self.session_ttl = config;
+        self
+    }
+
     pub fn with_name(mut self, name: &str) -> Self {
         self.name = name.to_string();
         self
     }
 
     pub async fn build(self) -> McpResult<McpServer> {
+        let storage = StorageFactory::new().create(&self.storage)?;
+        let manager = SessionManager::new().with_storage(storage).with_session_ttl(self.session_ttl);
         let session_manager = if let Some(dir) = &self.atlases_dir {
-            let manager = SessionManager::new().with_atlases_dir(dir);
+            let manager = manager.with_atlases_dir(dir);
             manager.load_atlases()?;
             manager
         } else {
-            SessionManager::new()
+            manager
         };
 
         Ok(McpServer {
@@ -1,4 +1,12 @@
 //! Session management for MCP server
+//!
+//! Bootstraps through [`SessionManager::bootstrap_resumable`] hand out a
+//! resume token. An agent whose connection drops calls `cra_bootstrap`
+//! again with it and re-attaches to its session (recorded as
+//! `session.resumed`) instead of opening a duplicate. Sessions nobody
+//! comes back to are ended by [`SessionManager::expire_idle_sessions`]
+//! under the manager's [`SessionTTLConfig`], each with a `session.expired`
+//! TRACE event.
 
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -7,7 +15,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use cra_core::{Resolver, AtlasManifest, ContextBlock};
+use cra_core::{
+    AtlasManifest, BootstrapRequest, BootstrapResponse, CRAError, ContextBlock, Resolver, SessionTTLConfig,
+    StorageBackend,
+};
 
 use crate::error::{McpError, McpResult};
 
@@ -44,6 +55,10 @@ pub struct Session {
     /// Session metadata
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
+
+    /// Last time the agent used the session
+    #[serde(default = "Utc::now")]
+    pub last_activity: DateTime<Utc>,
 }
 
 impl Session {
@@ -54,20 +69,27 @@ impl Session {
 
     /// Create a new session with a specific session_id
     pub fn with_id(session_id: String, agent_id: String, goal: String, active_atlases: Vec<String>, genesis_hash: String) -> Self {
+        let now = Utc::now();
         Self {
             session_id,
             agent_id,
             goal,
-            started_at: Utc::now(),
+            started_at: now,
             active_atlases,
             genesis_hash: genesis_hash.clone(),
             current_hash: genesis_hash,
             event_count: 1, // Genesis event
             injected_contexts: Vec::new(),
             metadata: HashMap::new(),
+            last_activity: now,
         }
     }
 
+    /// Record activity, postponing idle expiry
+    pub fn touch(&mut self) {
+        self.last_activity = Utc::now();
+    }
+
     /// Update the chain hash
     pub fn update_hash(&mut self, hash: String) {
         self.current_hash = hash;
@@ -98,6 +120,37 @@ pub struct SessionManager {
 
     /// Loaded atlases directory (if any)
     atlases_dir: Option<String>,
+
+    /// When idle and long-lived sessions expire
+    session_ttl: SessionTTLConfig,
+
+    /// Resumable bootstraps by resume token
+    resume_tokens: RwLock<HashMap<String, ResumeEntry>>,
+}
+
+/// A bootstrap a reconnecting agent can re-attach to
+#[derive(Debug, Clone)]
+struct ResumeEntry {
+    session_id: String,
+    /// Only this agent may resume with the token
+    agent_id: String,
+    response: BootstrapResponse,
+}
+
+/// Outcome of [`SessionManager::bootstrap_resumable`]
+#[derive(Debug, Clone)]
+pub struct ResumableBootstrap {
+    /// The session, new or re-attached
+    pub session: Session,
+
+    /// Bootstrap response; on resume, with the chain's current head
+    pub response: BootstrapResponse,
+
+    /// Token to present when reconnecting
+    pub resume_token: String,
+
+    /// Whether this re-attached to an existing session
+    pub resumed: bool,
 }
 
 impl SessionManager {
@@ -107,15 +160,31 @@ impl SessionManager {
             resolver: RwLock::new(Resolver::new()),
             sessions: RwLock::new(HashMap::new()),
             atlases_dir: None,
+            session_ttl: SessionTTLConfig::default(),
+            resume_tokens: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Expire sessions according to `config`: idle for longer than
+    /// `idle_timeout` plus `grace_period`, or older than `max_lifetime`
+    pub fn with_session_ttl(mut self, config: SessionTTLConfig) -> Self {
+        self.session_ttl = config;
+        self
+    }
+
     /// Create with an atlases directory
     pub fn with_atlases_dir(mut self, dir: &str) -> Self {
         self.atlases_dir = Some(dir.to_string());
         self
     }
 
+    /// Persist TRACE events to a storage backend
+    pub fn with_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        let resolver = std::mem::take(self.resolver.get_mut().unwrap_or_else(|e| e.into_inner()));
+        self.resolver = RwLock::new(resolver.with_trace_storage(storage));
+        self
+    }
+
     /// Load atlases from directory
     pub fn load_atlases(&self) -> McpResult<Vec<String>> {
         let Some(dir) = &self.atlases_dir else {
@@ -186,6 +255,171 @@ impl SessionManager {
         Ok(session_clone)
     }
 
+    /// Bootstrap an agent through the resolver, tracking its session
+    pub fn bootstrap(&self, request: &BootstrapRequest) -> McpResult<(Session, BootstrapResponse)> {
+        let mut resolver = self.resolver.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+
+        let response = resolver.bootstrap(request)?;
+        let active_atlases = resolver.list_atlases().iter().map(|s| s.to_string()).collect();
+
+        let mut session = Session::with_id(
+            response.session_id.clone(),
+            request.agent_id.clone(),
+            request.intent.clone(),
+            active_atlases,
+            response.genesis_hash.clone(),
+        );
+        session.current_hash = response.current_hash.clone();
+        session.event_count = response.sequence + 1;
+        for context_id in response.context_ids() {
+            session.record_context_injection(context_id);
+        }
+
+        let mut sessions = self.sessions.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+        sessions.insert(session.session_id.clone(), session.clone());
+
+        Ok((session, response))
+    }
+
+    /// Bootstrap, or re-attach to the session a resume token belongs to
+    ///
+    /// A token from an earlier bootstrap re-attaches to its session if it
+    /// is still active, and fails with [`McpError::ResumeExpired`] if not.
+    /// It fails with [`McpError::ResumeForbidden`] when presented by an agent
+    /// other than the one it was issued to. An unknown token is taken as
+    /// the client's own: the new session is registered under it, so
+    /// retrying a bootstrap whose response was lost can't create a second
+    /// session. Tokens must be UUIDs, so a client can't pick one another
+    /// agent could guess. Without a token one is generated.
+    pub fn bootstrap_resumable(&self, request: &BootstrapRequest, resume_token: Option<&str>) -> McpResult<ResumableBootstrap> {
+        // Held throughout so concurrent retries with one token can't both bootstrap
+        let mut tokens = self.resume_tokens.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+
+        if let Some(token) = resume_token {
+            if Uuid::parse_str(token).is_err() {
+                return Err(McpError::Validation("resume_token must be a UUID".to_string()));
+            }
+            if let Some(entry) = tokens.get(token).cloned() {
+                if entry.agent_id != request.agent_id {
+                    return Err(McpError::ResumeForbidden(request.agent_id.clone()));
+                }
+                return self.resume(&mut tokens, token, entry);
+            }
+        }
+
+        let resume_token = resume_token
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let (session, response) = self.bootstrap(request)?;
+        tokens.insert(resume_token.clone(), ResumeEntry {
+            session_id: session.session_id.clone(),
+            agent_id: request.agent_id.clone(),
+            response: response.clone(),
+        });
+
+        Ok(ResumableBootstrap { session, response, resume_token, resumed: false })
+    }
+
+    fn resume(&self, tokens: &mut HashMap<String, ResumeEntry>, token: &str, entry: ResumeEntry) -> McpResult<ResumableBootstrap> {
+        let mut response = entry.response;
+        {
+            let mut resolver = self.resolver.write()
+                .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+
+            match resolver.resume_session(&entry.session_id, "bootstrap_resumed") {
+                Ok(()) => {}
+                Err(CRAError::SessionNotFound { .. } | CRAError::SessionAlreadyEnded { .. }) => {
+                    tokens.remove(token);
+                    return Err(McpError::ResumeExpired(token.to_string()));
+                }
+                Err(e) => return Err(e.into()),
+            }
+            if let Some(head) = resolver.get_trace(&entry.session_id)?.last() {
+                response.current_hash = head.event_hash.clone();
+                response.sequence = head.sequence;
+            }
+        }
+
+        let mut sessions = self.sessions.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+        let session = sessions.get_mut(&entry.session_id)
+            .ok_or_else(|| McpError::ResumeExpired(token.to_string()))?;
+        session.current_hash = response.current_hash.clone();
+        session.event_count = response.sequence + 1;
+        session.touch();
+
+        Ok(ResumableBootstrap {
+            session: session.clone(),
+            response,
+            resume_token: token.to_string(),
+            resumed: true,
+        })
+    }
+
+    /// End every session past its TTL, recording `session.expired`
+    ///
+    /// A session expires when it has been idle for longer than the
+    /// configured `idle_timeout` plus `grace_period` (cause `idle`), or is
+    /// older than `max_lifetime` (cause `max_lifetime`). Its resume tokens
+    /// are kept so a returning agent learns the session expired: the next
+    /// bootstrap with one fails with [`McpError::ResumeExpired`] and
+    /// forgets it. Returns the expired session IDs.
+    pub fn expire_idle_sessions(&self) -> McpResult<Vec<String>> {
+        let now = Utc::now();
+        let to_chrono = |d| chrono::Duration::from_std(d).unwrap_or(chrono::Duration::MAX);
+        let idle_limit = to_chrono(self.session_ttl.idle_timeout + self.session_ttl.grace_period);
+        let max_lifetime = self.session_ttl.max_lifetime.map(to_chrono);
+
+        let due: Vec<(String, &str)> = {
+            let sessions = self.sessions.read()
+                .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+            sessions.values()
+                .filter_map(|s| {
+                    if max_lifetime.is_some_and(|max| now - s.started_at >= max) {
+                        Some((s.session_id.clone(), "max_lifetime"))
+                    } else if now - s.last_activity >= idle_limit {
+                        Some((s.session_id.clone(), "idle"))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+        if due.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        {
+            let mut resolver = self.resolver.write()
+                .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+            for (session_id, cause) in &due {
+                match resolver.expire_session(session_id, cause) {
+                    Ok(()) | Err(CRAError::SessionNotFound { .. } | CRAError::SessionAlreadyEnded { .. }) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        let expired: Vec<String> = due.into_iter().map(|(session_id, _)| session_id).collect();
+        self.sessions.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?
+            .retain(|id, _| !expired.contains(id));
+        Ok(expired)
+    }
+
+    /// Record activity on a session
+    fn touch(&self, session_id: &str) -> McpResult<()> {
+        let mut sessions = self.sessions.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
+        if let Some(session) = sessions.get_mut(session_id) {
+            session.touch();
+        }
+        Ok(())
+    }
+
     /// Get a session
     pub fn get_session(&self, session_id: &str) -> McpResult<Session> {
         let sessions = self.sessions.read()
@@ -223,22 +457,29 @@ impl SessionManager {
             .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
 
         resolver.end_session(session_id)?;
+        drop(resolver);
 
+        // The agent ended it, so a retried token should start afresh
+        self.resume_tokens.write()
+            .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?
+            .retain(|_, entry| entry.session_id != session_id);
         Ok(session)
     }
 
     /// Request context for a need
-    pub fn request_context(&self, session_id: &str, need: &str, _hints: Option<Vec<String>>) -> McpResult<Vec<MatchedContext>> {
+    pub fn request_context(&self, session_id: &str, need: &str, hints: Option<Vec<String>>) -> McpResult<Vec<MatchedContext>> {
+        self.touch(session_id)?;
         let mut resolver = self.resolver.write()
             .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
 
         // Use CARP resolve to get context blocks
         // The goal field is used for context matching
-        let request = cra_core::CARPRequest::new(
+        let mut request = cra_core::CARPRequest::new(
             session_id.to_string(),
             "context-request".to_string(),
             need.to_string(),
         );
+        request.context_hints = hints;
 
         let resolution = resolver.resolve(&request)?;
 
@@ -258,6 +499,7 @@ impl SessionManager {
 
     /// Report an action for audit trail
     pub fn report_action(&self, session_id: &str, action: &str, params: serde_json::Value) -> McpResult<ActionReport> {
+        self.touch(session_id)?;
         let mut resolver = self.resolver.write()
             .map_err(|_| McpError::Internal("Lock poisoned".to_string()))?;
 
@@ -304,6 +546,7 @@ impl SessionManager {
         // Record feedback in trace
         // For now, just validate session exists
         let _session = self.get_session(session_id)?;
+        self.touch(session_id)?;
 
         // TODO: Emit feedback event to trace
 
@@ -62,6 +62,11 @@ pub fn bootstrap_tool() -> ToolDefinition {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "What tools/abilities you have"
+                },
+                "resume_token": {
+                    "type": "string",
+                    "format": "uuid",
+                    "description": "resume_token from an earlier cra_bootstrap; after a dropped connection, pass it to re-attach to that session instead of starting a new one"
                 }
             }
         }),
@@ -116,4 +121,6 @@ pub struct BootstrapInput {
     pub intent: String,
     #[serde(default)]
     pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub resume_token: Option<String>,
 }
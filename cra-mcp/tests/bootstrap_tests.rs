@@ -2,6 +2,7 @@
 
 use cra_mcp::bootstrap::*;
 use cra_mcp::session::Session;
+use cra_mcp::McpError;
 
 #[test]
 fn test_bootstrap_state_machine() {
@@ -192,13 +193,7 @@ fn test_generate_context_message() {
 
     // Generate context message
     let contexts = vec![
-        BootstrapContext {
-            context_id: "ctx-1".to_string(),
-            priority: 100,
-            inject_mode: "system".to_string(),
-            content: "You are a helpful assistant.".to_string(),
-            digest: "abc123".to_string(),
-        },
+        BootstrapContext::new("ctx-1", "You are a helpful assistant.", 100),
     ];
 
     let context_msg = protocol.generate_context(contexts, false).unwrap();
@@ -348,3 +343,151 @@ fn test_bootstrap_message_serialization() {
         _ => panic!("Wrong message type"),
     }
 }
+
+#[test]
+fn test_session_manager_bootstrap() {
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new();
+    let request = BootstrapRequest::new("mcp-agent", "Help with code").with_capabilities(vec!["read".to_string()]);
+    let (session, response) = manager.bootstrap(&request).unwrap();
+
+    assert_eq!(session.session_id, response.session_id);
+    assert_eq!(session.genesis_hash, response.genesis_hash);
+    assert_eq!(session.current_hash, response.current_hash);
+    assert_eq!(manager.get_current_session().unwrap().session_id, response.session_id);
+
+    // The tool result is the shared wire schema plus tool guidance
+    let json = serde_json::to_value(BootstrapResult::new(response)).unwrap();
+    assert_eq!(json["protocol_version"], BOOTSTRAP_PROTOCOL_VERSION);
+    assert_eq!(json["genesis_hash"], session.genesis_hash);
+    assert!(json["rules"].as_array().unwrap().iter().any(|r| r["rule_id"] == "trace.required"));
+    assert!(json["you_must"].is_array());
+}
+
+#[test]
+fn test_resume_token_reattaches_to_session() {
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new();
+    let request = BootstrapRequest::new("mcp-agent", "Help with code");
+    let first = manager.bootstrap_resumable(&request, None).unwrap();
+    assert!(!first.resumed);
+
+    let again = manager.bootstrap_resumable(&request, Some(&first.resume_token)).unwrap();
+    assert!(again.resumed);
+    assert_eq!(again.session.session_id, first.session.session_id);
+    assert_eq!(again.response.genesis_hash, first.response.genesis_hash);
+
+    // The resumed response points at the chain head, which now records the resume
+    let trace = manager.get_trace(&first.session.session_id).unwrap();
+    let head = trace.last().unwrap();
+    assert_eq!(head.event_type.as_str(), "session.resumed");
+    assert_eq!(again.response.current_hash, head.event_hash);
+    assert_eq!(again.response.sequence, head.sequence);
+    assert!(manager.verify_chain(&first.session.session_id).unwrap().is_valid);
+
+    let json = serde_json::to_value(
+        BootstrapResult::new(again.response).with_resume_token(again.resume_token, again.resumed),
+    ).unwrap();
+    assert_eq!(json["resume_token"], first.resume_token.as_str());
+    assert_eq!(json["resumed"], true);
+}
+
+#[test]
+fn test_client_chosen_resume_token_prevents_duplicates() {
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new();
+    let request = BootstrapRequest::new("mcp-agent", "Help with code");
+
+    // A retry after a lost response carries the same token
+    let token = "6f1d3c2a-9b4e-4f7a-8c1d-2e5b7a9c0d13";
+    let first = manager.bootstrap_resumable(&request, Some(token)).unwrap();
+    let retry = manager.bootstrap_resumable(&request, Some(token)).unwrap();
+    assert_eq!(first.resume_token, token);
+    assert!(!first.resumed);
+    assert!(retry.resumed);
+    assert_eq!(retry.session.session_id, first.session.session_id);
+
+    // Guessable tokens are refused
+    assert!(matches!(
+        manager.bootstrap_resumable(&request, Some("client-token-1")),
+        Err(McpError::Validation(_))
+    ));
+}
+
+#[test]
+fn test_resume_token_is_bound_to_its_agent() {
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new();
+    let owner = manager
+        .bootstrap_resumable(&BootstrapRequest::new("agent-a", "Help with code"), None)
+        .unwrap();
+
+    let intruder = BootstrapRequest::new("agent-b", "Help with code");
+    let err = manager.bootstrap_resumable(&intruder, Some(&owner.resume_token)).unwrap_err();
+    assert!(matches!(err, McpError::ResumeForbidden(ref agent) if agent == "agent-b"));
+    assert_eq!(err.error_code(), -32007);
+
+    // The owner's session and chain are untouched, and the owner can still resume
+    let trace = manager.get_trace(&owner.session.session_id).unwrap();
+    assert!(trace.iter().all(|e| e.event_type.as_str() != "session.resumed"));
+    let resumed = manager
+        .bootstrap_resumable(&BootstrapRequest::new("agent-a", "Help with code"), Some(&owner.resume_token))
+        .unwrap();
+    assert!(resumed.resumed);
+    assert_eq!(resumed.session.agent_id, "agent-a");
+}
+
+#[test]
+fn test_idle_sessions_expire_with_trace_events() {
+    use std::time::Duration;
+    use cra_core::SessionTTLConfig;
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new().with_session_ttl(
+        SessionTTLConfig::new()
+            .idle_timeout(Duration::ZERO)
+            .grace_period(Duration::ZERO),
+    );
+    let request = BootstrapRequest::new("mcp-agent", "Help with code");
+    let bootstrap = manager.bootstrap_resumable(&request, None).unwrap();
+    let session_id = bootstrap.session.session_id.clone();
+
+    assert_eq!(manager.expire_idle_sessions().unwrap(), vec![session_id.clone()]);
+    assert!(manager.get_session(&session_id).is_err());
+    assert!(manager.expire_idle_sessions().unwrap().is_empty());
+
+    let trace = manager.get_trace(&session_id).unwrap();
+    let tail: Vec<_> = trace.iter().rev().take(2).rev().collect();
+    assert_eq!(tail[0].event_type.as_str(), "session.expired");
+    assert_eq!(tail[0].payload["cause"], "idle");
+    assert_eq!(tail[1].event_type.as_str(), "session.ended");
+    assert_eq!(tail[1].payload["reason"], "expired");
+
+    let err = manager.bootstrap_resumable(&request, Some(&bootstrap.resume_token)).unwrap_err();
+    assert!(matches!(err, McpError::ResumeExpired(_)));
+    assert_eq!(err.error_code(), -32006);
+
+    // Reported once; after that the token is free again
+    let fresh = manager.bootstrap_resumable(&request, Some(&bootstrap.resume_token)).unwrap();
+    assert!(!fresh.resumed);
+    assert_ne!(fresh.session.session_id, session_id);
+}
+
+#[test]
+fn test_ended_session_cannot_be_resumed() {
+    use cra_mcp::session::SessionManager;
+
+    let manager = SessionManager::new();
+    let request = BootstrapRequest::new("mcp-agent", "Help with code");
+    let bootstrap = manager.bootstrap_resumable(&request, None).unwrap();
+    manager.end_session(&bootstrap.session.session_id, None).unwrap();
+
+    // The token now names nothing, so it starts a fresh session under that token
+    let fresh = manager.bootstrap_resumable(&request, Some(&bootstrap.resume_token)).unwrap();
+    assert!(!fresh.resumed);
+    assert_ne!(fresh.session.session_id, bootstrap.session.session_id);
+}
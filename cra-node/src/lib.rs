@@ -47,13 +47,20 @@
 //! // End the session
 //! resolver.endSession(sessionId);
 //! ```
+//!
+//! The agent-side `Wrapper` is exposed as well; see the `wrapper` module.
 
 #[macro_use]
 extern crate napi_derive;
 
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::Buffer;
 use napi::{Error, Result, Status};
 
-use cra_core::{AtlasManifest, CARPRequest, Resolver as CoreResolver};
+use cra_core::{AtlasManifest, CARPRequest, FixedClock, Resolver as CoreResolver, SequentialIdGen};
+
+mod wrapper;
 
 /// CRA Resolver for Node.js
 #[napi]
@@ -64,10 +71,25 @@ pub struct Resolver {
 #[napi]
 impl Resolver {
     /// Create a new resolver
+    ///
+    /// With `strict`, actions no atlas capability grants are denied
     #[napi(constructor)]
-    pub fn new() -> Self {
+    pub fn new(strict: Option<bool>) -> Self {
         Resolver {
-            inner: CoreResolver::new(),
+            inner: CoreResolver::new().with_strict_mode(strict.unwrap_or(false)),
+        }
+    }
+
+    /// Create a deterministic resolver
+    ///
+    /// Timestamps are fixed at `epochMs` and IDs are sequential from `seed`,
+    /// so the same calls always produce the same trace (golden trace tests)
+    #[napi(factory)]
+    pub fn deterministic(epoch_ms: i64, seed: Option<u32>) -> Self {
+        Resolver {
+            inner: CoreResolver::new()
+                .with_clock(FixedClock::from_millis(epoch_ms))
+                .with_id_generator(SequentialIdGen::with_seed(seed.unwrap_or(0) as u64)),
         }
     }
 
@@ -94,14 +116,37 @@ impl Resolver {
 
     /// Create a new session
     ///
-    /// Returns the session ID
+    /// `variables` fill in `${name}` templates in action parameter defaults
+    /// and executors. Returns the session ID
     #[napi]
-    pub fn create_session(&mut self, agent_id: String, goal: String) -> Result<String> {
+    pub fn create_session(
+        &mut self,
+        agent_id: String,
+        goal: String,
+        variables: Option<HashMap<String, String>>,
+    ) -> Result<String> {
         self.inner
-            .create_session(&agent_id, &goal)
+            .create_session_with_variables(&agent_id, &goal, variables.unwrap_or_default().into_iter().collect())
             .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create session: {}", e)))
     }
 
+    /// Set a session variable for `${name}` action templates
+    #[napi]
+    pub fn set_session_variable(&mut self, session_id: String, name: String, value: String) -> Result<()> {
+        self.inner
+            .set_session_variable(&session_id, &name, &value)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set session variable: {}", e)))
+    }
+
+    /// Get a session's variables
+    #[napi]
+    pub fn session_variables(&self, session_id: String) -> Result<HashMap<String, String>> {
+        self.inner
+            .session_variables(&session_id)
+            .map(|vars| vars.clone().into_iter().collect())
+            .ok_or_else(|| Error::new(Status::GenericFailure, format!("Session not found: {}", session_id)))
+    }
+
     /// End a session
     #[napi]
     pub fn end_session(&mut self, session_id: String) -> Result<()> {
@@ -110,12 +155,88 @@ impl Resolver {
             .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to end session: {}", e)))
     }
 
+    /// Hand a session off to another resolver
+    ///
+    /// Ends the session here and returns the snapshot as a JSON string
+    #[napi]
+    pub fn export_session(&mut self, session_id: String, destination: String) -> Result<String> {
+        let snapshot = self
+            .inner
+            .export_session(&session_id, &destination)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to export session: {}", e)))?;
+
+        serde_json::to_string(&snapshot)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to serialize: {}", e)))
+    }
+
+    /// Continue a session from another resolver's snapshot JSON
+    ///
+    /// Returns the session ID
+    #[napi]
+    pub fn import_session(&mut self, snapshot_json: String) -> Result<String> {
+        let snapshot = serde_json::from_str(&snapshot_json)
+            .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid snapshot: {}", e)))?;
+
+        self.inner
+            .import_session(snapshot)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to import session: {}", e)))
+    }
+
+    /// Report which policies and capabilities cover each loaded action
+    ///
+    /// Returns a JSON string with `actions`, `uncovered_actions`,
+    /// `ungranted_actions` and `default_allowed_actions`
+    #[napi]
+    pub fn policy_coverage(&self) -> Result<String> {
+        serde_json::to_string(&self.inner.policy_coverage())
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to serialize: {}", e)))
+    }
+
+    /// Serialize the resolver's full state to a Buffer
+    ///
+    /// Covers atlases, sessions, TRACE chains and rate limit counters
+    #[napi]
+    pub fn snapshot(&mut self) -> Result<Buffer> {
+        self.inner
+            .snapshot()
+            .map(Buffer::from)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to snapshot resolver: {}", e)))
+    }
+
+    /// Restore state from `snapshot()` into this (fresh) resolver
+    #[napi]
+    pub fn restore(&mut self, data: Buffer) -> Result<()> {
+        self.inner
+            .restore(&data)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to restore resolver: {}", e)))
+    }
+
     /// Resolve a CARP request
     ///
-    /// Returns a JSON string containing the resolution
+    /// Returns a JSON string containing the resolution. Pass
+    /// `requestedActions` (IDs or patterns) and/or `maxRiskTier` to narrow
+    /// the allowed actions to what the agent needs.
     #[napi]
-    pub fn resolve(&mut self, session_id: String, agent_id: String, goal: String) -> Result<String> {
-        let request = CARPRequest::new(session_id, agent_id, goal);
+    pub fn resolve(
+        &mut self,
+        session_id: String,
+        agent_id: String,
+        goal: String,
+        requested_actions: Option<Vec<String>>,
+        max_risk_tier: Option<String>,
+        context_hints: Option<Vec<String>>,
+    ) -> Result<String> {
+        let mut builder = CARPRequest::builder(session_id, agent_id, goal);
+        if let Some(actions) = requested_actions {
+            builder = builder.requested_actions(actions);
+        }
+        if let Some(tier) = max_risk_tier {
+            builder = builder.max_risk_tier(tier.parse().map_err(|e: String| Error::new(Status::InvalidArg, e))?);
+        }
+        if let Some(hints) = context_hints {
+            builder = builder.context_hints(hints);
+        }
+        let request = builder.build();
 
         let resolution = self
             .inner
@@ -212,3 +333,18 @@ pub fn trace_version() -> &'static str {
 pub fn atlas_version() -> &'static str {
     cra_core::ATLAS_VERSION
 }
+
+/// Verify an exported TRACE JSONL string without a resolver
+///
+/// Returns a JSON string with `is_valid`, `first_invalid_index`, `error_type`
+/// and a per-event `timeline`. Pass the hash preceding a chain segment as
+/// `genesisHash` to verify a segment.
+#[napi]
+pub fn verify_trace(jsonl: String, genesis_hash: Option<String>) -> Result<String> {
+    let verification = cra_trace_verify::verify_jsonl(
+        &jsonl,
+        genesis_hash.as_deref().unwrap_or(cra_trace_verify::GENESIS_HASH),
+    );
+    serde_json::to_string(&verification)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to serialize: {}", e)))
+}
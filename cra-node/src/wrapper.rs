@@ -0,0 +1,288 @@
+//! Node.js bindings for the agent-side Wrapper
+//!
+//! Session methods return Promises. Hooks are JavaScript functions called
+//! through a ThreadsafeFunction with the hook context object; they return
+//! `undefined`/`true` to continue, `false` or a reason string to block, or
+//! a context object (e.g. `{ ...ctx, text }`) to replace the context.
+//! Hooks must be synchronous.
+//!
+//! Streamed model output goes through an `OutputStream`, which holds back
+//! a partial token at the end of each chunk so secrets split across chunks
+//! are still redacted:
+//!
+//! ```javascript
+//! const { Wrapper } = require('@cra/core');
+//!
+//! const wrapper = new Wrapper(JSON.stringify({ hooks: { redact_secrets: true } }));
+//! wrapper.addHook('no-deletes', (ctx) => {
+//!   if (ctx.action?.startsWith('delete_')) return 'deletes are not allowed';
+//! }, ['action']);
+//!
+//! await wrapper.startSession('Help the user');
+//! const stream = await wrapper.outputStream();
+//! for await (const chunk of llm.stream(prompt)) {
+//!   process.stdout.write(await stream.push(chunk));
+//! }
+//! const { tail } = await stream.finish();
+//! process.stdout.write(tail);
+//! await wrapper.endSession();
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction};
+use napi::{Env, Error, JsFunction, Result, Status};
+use tokio::sync::Mutex;
+
+use cra_wrapper::hooks::{HookContext, HookFlow, HookMiddleware, HookStage, ALL_STAGES};
+use cra_wrapper::{
+    OutputStream as CoreOutputStream, TokenUsage, Wrapper as CoreWrapper, WrapperConfig,
+    WrapperError, WrapperResult,
+};
+
+/// Map a wrapper error to a JS error
+fn to_js_err(e: WrapperError) -> Error {
+    let status = match e {
+        WrapperError::ActionDenied(_) | WrapperError::HookBlocked { .. } => Status::Cancelled,
+        _ => Status::GenericFailure,
+    };
+    Error::new(status, e.to_string())
+}
+
+/// Serialize a wrapper result into a JS value
+fn to_js<T: serde::Serialize>(value: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(value)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to serialize: {}", e)))
+}
+
+fn parse_stage(name: &str) -> Result<HookStage> {
+    match name {
+        "input" => Ok(HookStage::Input),
+        "output" => Ok(HookStage::Output),
+        "action" => Ok(HookStage::Action),
+        other => Err(Error::new(
+            Status::InvalidArg,
+            format!("Unknown hook stage '{}' (expected input, output or action)", other),
+        )),
+    }
+}
+
+/// Hook middleware backed by a JavaScript function
+struct JsHook {
+    name: String,
+    priority: i32,
+    stages: Vec<HookStage>,
+    callback: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal>,
+}
+
+#[async_trait]
+impl HookMiddleware for JsHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn stages(&self) -> &[HookStage] {
+        &self.stages
+    }
+
+    async fn handle(&self, ctx: &mut HookContext) -> WrapperResult<HookFlow> {
+        let result: Option<serde_json::Value> = self.callback
+            .call_async(serde_json::to_value(&*ctx)?)
+            .await
+            .map_err(|e| WrapperError::Internal(format!("JS hook '{}' failed: {}", self.name, e)))?;
+
+        match result {
+            None | Some(serde_json::Value::Bool(true)) => Ok(HookFlow::Continue),
+            Some(serde_json::Value::Bool(false)) => Ok(HookFlow::Block(format!("blocked by {}", self.name))),
+            Some(serde_json::Value::String(reason)) => Ok(HookFlow::Block(reason)),
+            Some(value @ serde_json::Value::Object(_)) => {
+                *ctx = serde_json::from_value(value)?;
+                Ok(HookFlow::Continue)
+            }
+            Some(_) => Err(WrapperError::Internal(format!(
+                "JS hook '{}' must return undefined, a boolean, a string or a context object",
+                self.name
+            ))),
+        }
+    }
+}
+
+/// Agent-side CRA wrapper for Node.js
+#[napi]
+pub struct Wrapper {
+    inner: Arc<CoreWrapper>,
+}
+
+#[napi]
+impl Wrapper {
+    /// Create a wrapper from a JSON configuration (defaults if omitted)
+    #[napi(constructor)]
+    pub fn new(config_json: Option<String>) -> Result<Self> {
+        let config: WrapperConfig = match config_json {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid wrapper config: {}", e)))?,
+            None => WrapperConfig::default(),
+        };
+        Ok(Wrapper {
+            inner: Arc::new(CoreWrapper::new(config)),
+        })
+    }
+
+    /// Start a governed session, resolving to the session ID
+    #[napi]
+    pub async fn start_session(&self, goal: String) -> Result<String> {
+        self.inner.start_session(&goal).await.map_err(to_js_err)
+    }
+
+    /// End the current session, resolving to the summary
+    #[napi]
+    pub async fn end_session(&self, summary: Option<String>) -> Result<serde_json::Value> {
+        let result = self.inner.end_session(summary.as_deref()).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+
+    /// Run input through the hooks and checkpoint triggers
+    ///
+    /// Resolves to `{ original, processed, injected_context }`
+    #[napi]
+    pub async fn on_input(&self, text: String) -> Result<serde_json::Value> {
+        let result = self.inner.on_input(&text).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+
+    /// Run a complete output through the hooks
+    ///
+    /// Resolves to `{ original, processed }`
+    #[napi]
+    pub async fn on_output(&self, text: String) -> Result<serde_json::Value> {
+        let result = self.inner.on_output(&text).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+
+    /// Start processing a streamed output
+    #[napi]
+    pub async fn output_stream(&self) -> Result<OutputStream> {
+        let stream = self.inner.output_stream().await.map_err(to_js_err)?;
+        Ok(OutputStream {
+            wrapper: self.inner.clone(),
+            state: Arc::new(Mutex::new(Some(stream))),
+        })
+    }
+
+    /// Report an action before executing it
+    ///
+    /// Resolves to `{ allowed, reason }`
+    #[napi]
+    pub async fn report_action(&self, action: String, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let params = params.unwrap_or_else(|| serde_json::json!({}));
+        let result = self.inner.report_action(&action, params).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+
+    /// Report token usage for one model turn, resolving to session totals
+    #[napi]
+    pub async fn report_usage(
+        &self,
+        model: String,
+        input_tokens: u32,
+        output_tokens: u32,
+        cost_usd: Option<f64>,
+    ) -> Result<serde_json::Value> {
+        let mut usage = TokenUsage::new(&model, input_tokens as u64, output_tokens as u64);
+        usage.cost_usd = cost_usd;
+        let result = self.inner.report_usage(usage).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+
+    /// TRACE queue statistics
+    #[napi]
+    pub async fn queue_stats(&self) -> Result<serde_json::Value> {
+        to_js(&self.inner.queue_stats().await)
+    }
+
+    /// Context cache statistics
+    #[napi]
+    pub async fn cache_stats(&self) -> Result<serde_json::Value> {
+        to_js(&self.inner.cache_stats().await)
+    }
+
+    /// Register a JS function as a hook
+    ///
+    /// `stages` lists "input", "output" and "action" (all if omitted).
+    /// Higher priorities run first; a hook with the same name is replaced.
+    #[napi]
+    pub fn add_hook(
+        &self,
+        env: Env,
+        name: String,
+        callback: JsFunction,
+        stages: Option<Vec<String>>,
+        priority: Option<i32>,
+    ) -> Result<()> {
+        let stages = match stages {
+            Some(names) => names.iter().map(|s| parse_stage(s)).collect::<Result<Vec<_>>>()?,
+            None => ALL_STAGES.to_vec(),
+        };
+        let mut callback: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<serde_json::Value>| Ok(vec![ctx.value]))?;
+        // Registered hooks must not keep the process alive
+        callback.unref(&env)?;
+
+        self.inner.hooks().register(Arc::new(JsHook {
+            name,
+            priority: priority.unwrap_or(0),
+            stages,
+            callback,
+        }));
+        Ok(())
+    }
+
+    /// Remove a hook by name, returning whether it existed
+    #[napi]
+    pub fn remove_hook(&self, name: String) -> bool {
+        self.inner.hooks().unregister(&name)
+    }
+
+    /// Names of registered hooks, in run order
+    #[napi]
+    pub fn hook_names(&self) -> Vec<String> {
+        self.inner.hooks().names()
+    }
+}
+
+/// Streamed output being processed chunk by chunk
+#[napi]
+pub struct OutputStream {
+    wrapper: Arc<CoreWrapper>,
+    state: Arc<Mutex<Option<CoreOutputStream>>>,
+}
+
+#[napi]
+impl OutputStream {
+    /// Process a chunk, resolving to the text that is safe to emit now
+    #[napi]
+    pub async fn push(&self, chunk: String) -> Result<String> {
+        let mut state = self.state.lock().await;
+        let stream = state
+            .as_mut()
+            .ok_or_else(|| Error::new(Status::GenericFailure, "Output stream already finished"))?;
+        self.wrapper.push_output_chunk(stream, &chunk).await.map_err(to_js_err)
+    }
+
+    /// Finish the stream, resolving to `{ tail, original, processed, chunks, redactions }`
+    ///
+    /// `tail` is the processed held-back text to emit after the last chunk.
+    #[napi]
+    pub async fn finish(&self) -> Result<serde_json::Value> {
+        let stream = self.state.lock().await
+            .take()
+            .ok_or_else(|| Error::new(Status::GenericFailure, "Output stream already finished"))?;
+        let result = self.wrapper.finish_output_stream(stream).await.map_err(to_js_err)?;
+        to_js(&result)
+    }
+}
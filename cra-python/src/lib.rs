@@ -29,6 +29,13 @@
 //! for event in resolver.get_trace_events(session_id):
 //!     print(f"{event.event_type}: {event.payload}")
 //!
+//! # Or page through it lazily (follow=True tails a live session)
+//! for event in resolver.iter_trace(session_id):
+//!     print(event.sequence, event.event_type)
+//!
+//! # Analyze it as a dataframe (requires pyarrow and pandas)
+//! df = resolver.trace_to_pandas(session_id)
+//!
 //! # Verify chain integrity
 //! verification = resolver.verify_chain(session_id)
 //! assert verification.is_valid
@@ -36,11 +43,15 @@
 //! # End the session
 //! resolver.end_session(session_id)
 //! ```
+//!
+//! The agent-side `Wrapper` is exposed as well; see the `wrapper` module.
 
 use pyo3::prelude::*;
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
+use cra_core::carp::{CheckpointResponse, TriggeredCheckpoint};
 use cra_core::{
     self,
     AtlasManifest,
@@ -51,8 +62,12 @@ use cra_core::{
     Resolver as CoreResolver,
     TRACEEvent as CoreTRACEEvent,
     ChainVerification as CoreChainVerification,
+    FixedClock,
+    SequentialIdGen,
 };
 
+mod wrapper;
+
 // =============================================================================
 // Python Types - Proper Python objects, not just JSON strings
 // =============================================================================
@@ -71,6 +86,8 @@ pub struct AllowedAction {
     pub risk_tier: String,
     #[pyo3(get)]
     pub parameters_schema: Option<String>,
+    #[pyo3(get)]
+    pub executor: Option<String>,
 }
 
 #[pymethods]
@@ -91,6 +108,7 @@ impl AllowedAction {
             map.insert("name".to_string(), self.name.clone().into_py(py));
             map.insert("description".to_string(), self.description.clone().into_py(py));
             map.insert("risk_tier".to_string(), self.risk_tier.clone().into_py(py));
+            map.insert("executor".to_string(), self.executor.clone().into_py(py));
             map
         })
     }
@@ -104,6 +122,7 @@ impl From<&CoreAllowedAction> for AllowedAction {
             description: action.description.clone(),
             risk_tier: action.risk_tier.clone(),
             parameters_schema: Some(serde_json::to_string(&action.parameters_schema).unwrap_or_default()),
+            executor: action.executor.clone(),
         }
     }
 }
@@ -155,6 +174,9 @@ pub struct CARPResolution {
     pub denied_actions: Vec<DeniedAction>,
     #[pyo3(get)]
     pub ttl_seconds: u64,
+    /// Actions policy allowed but the request's narrowing left out
+    #[pyo3(get)]
+    pub excluded_actions: Vec<String>,
 }
 
 #[pymethods]
@@ -213,6 +235,7 @@ impl From<CoreCARPResolution> for CARPResolution {
             allowed_actions: res.allowed_actions.iter().map(AllowedAction::from).collect(),
             denied_actions: res.denied_actions.iter().map(DeniedAction::from).collect(),
             ttl_seconds: res.ttl_seconds,
+            excluded_actions: res.narrowing.map(|n| n.excluded_actions).unwrap_or_default(),
         }
     }
 }
@@ -295,6 +318,8 @@ pub struct ChainVerification {
     pub error_type: Option<String>,
     #[pyo3(get)]
     pub error_message: Option<String>,
+    #[pyo3(get)]
+    pub signatures_valid: Option<bool>,
 }
 
 #[pymethods]
@@ -320,10 +345,73 @@ impl From<CoreChainVerification> for ChainVerification {
             first_invalid_index: v.first_invalid_index,
             error_type: v.error_type.map(|e| format!("{:?}", e)),
             error_message: v.error_message,
+            signatures_valid: v.signatures_valid,
         }
     }
 }
 
+/// Lazy iterator over a session's TRACE events
+///
+/// Fetches events from the resolver a page at a time. With `follow`, it
+/// waits for new events (releasing the GIL) until `session.ended` is seen
+/// or `timeout` seconds pass without a new event.
+#[pyclass]
+pub struct TraceIterator {
+    resolver: Py<Resolver>,
+    session_id: String,
+    next_index: usize,
+    page_size: usize,
+    buffer: VecDeque<TRACEEvent>,
+    follow: bool,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    ended: bool,
+}
+
+#[pymethods]
+impl TraceIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<TRACEEvent>> {
+        let mut waiting_since = Instant::now();
+        loop {
+            if let Some(event) = self.buffer.pop_front() {
+                self.ended |= event.event_type == "session.ended";
+                return Ok(Some(event));
+            }
+            if self.ended {
+                return Ok(None);
+            }
+
+            let page = self
+                .resolver
+                .borrow(py)
+                .inner
+                .get_trace_page(&self.session_id, self.next_index, self.page_size)
+                .map_err(|e| PyRuntimeError::new_err(format!("Failed to get trace: {}", e)))?;
+            if !page.is_empty() {
+                self.next_index += page.len();
+                self.buffer.extend(page.iter().map(TRACEEvent::from));
+                waiting_since = Instant::now();
+                continue;
+            }
+
+            if !self.follow || self.timeout.is_some_and(|t| waiting_since.elapsed() >= t) {
+                return Ok(None);
+            }
+            py.check_signals()?;
+            let interval = self.poll_interval;
+            py.allow_threads(|| std::thread::sleep(interval));
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TraceIterator(session_id='{}', position={})", self.session_id, self.next_index)
+    }
+}
+
 // =============================================================================
 // Resolver - The main Python interface
 // =============================================================================
@@ -337,10 +425,27 @@ pub struct Resolver {
 #[pymethods]
 impl Resolver {
     /// Create a new resolver
+    ///
+    /// With `strict=True`, actions no atlas capability grants are denied
     #[new]
-    fn new() -> Self {
+    #[pyo3(signature = (strict=false))]
+    fn new(strict: bool) -> Self {
+        Resolver {
+            inner: CoreResolver::new().with_strict_mode(strict),
+        }
+    }
+
+    /// Create a deterministic resolver
+    ///
+    /// Timestamps are fixed at `epoch_ms` and IDs are sequential from `seed`,
+    /// so the same calls always produce the same trace (golden trace tests)
+    #[staticmethod]
+    #[pyo3(signature = (epoch_ms, seed=0))]
+    fn deterministic(epoch_ms: i64, seed: u64) -> Self {
         Resolver {
-            inner: CoreResolver::new(),
+            inner: CoreResolver::new()
+                .with_clock(FixedClock::from_millis(epoch_ms))
+                .with_id_generator(SequentialIdGen::with_seed(seed)),
         }
     }
 
@@ -377,13 +482,35 @@ impl Resolver {
 
     /// Create a new session
     ///
-    /// Returns the session ID
-    fn create_session(&mut self, agent_id: &str, goal: &str) -> PyResult<String> {
+    /// `variables` fill in `${name}` templates in action parameter defaults
+    /// and executors. Returns the session ID
+    #[pyo3(signature = (agent_id, goal, variables=None))]
+    fn create_session(
+        &mut self,
+        agent_id: &str,
+        goal: &str,
+        variables: Option<std::collections::BTreeMap<String, String>>,
+    ) -> PyResult<String> {
         self.inner
-            .create_session(agent_id, goal)
+            .create_session_with_variables(agent_id, goal, variables.unwrap_or_default())
             .map_err(|e| PyRuntimeError::new_err(format!("Failed to create session: {}", e)))
     }
 
+    /// Set a session variable for `${name}` action templates
+    fn set_session_variable(&mut self, session_id: &str, name: &str, value: &str) -> PyResult<()> {
+        self.inner
+            .set_session_variable(session_id, name, value)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to set session variable: {}", e)))
+    }
+
+    /// Get a session's variables as a dict
+    fn session_variables(&self, session_id: &str) -> PyResult<std::collections::BTreeMap<String, String>> {
+        self.inner
+            .session_variables(session_id)
+            .cloned()
+            .ok_or_else(|| PyRuntimeError::new_err(format!("Session not found: {}", session_id)))
+    }
+
     /// End a session
     fn end_session(&mut self, session_id: &str) -> PyResult<()> {
         self.inner
@@ -391,15 +518,75 @@ impl Resolver {
             .map_err(|e| PyRuntimeError::new_err(format!("Failed to end session: {}", e)))
     }
 
+    /// Hand a session off to another resolver
+    ///
+    /// Ends the session here and returns the snapshot as a JSON string
+    fn export_session(&mut self, session_id: &str, destination: &str) -> PyResult<String> {
+        let snapshot = self
+            .inner
+            .export_session(session_id, destination)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to export session: {}", e)))?;
+
+        serde_json::to_string(&snapshot).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Continue a session from another resolver's snapshot JSON
+    ///
+    /// Returns the session ID
+    fn import_session(&mut self, snapshot_json: &str) -> PyResult<String> {
+        let snapshot = serde_json::from_str(snapshot_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid snapshot: {}", e)))?;
+
+        self.inner
+            .import_session(snapshot)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to import session: {}", e)))
+    }
+
+    /// Report which policies and capabilities cover each loaded action
+    ///
+    /// Returns a dict with `actions`, `uncovered_actions`, `ungranted_actions`
+    /// and `default_allowed_actions`
+    fn policy_coverage(&self, py: Python) -> PyResult<PyObject> {
+        let coverage = serde_json::to_value(self.inner.policy_coverage())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        json_to_py(py, &coverage)
+    }
+
+    /// Serialize the resolver's full state to bytes
+    ///
+    /// Covers atlases, sessions, TRACE chains and rate limit counters
+    fn snapshot<'py>(&mut self, py: Python<'py>) -> PyResult<&'py pyo3::types::PyBytes> {
+        let bytes = self
+            .inner
+            .snapshot()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to snapshot resolver: {}", e)))?;
+
+        Ok(pyo3::types::PyBytes::new(py, &bytes))
+    }
+
+    /// Restore state from `snapshot()` into this (fresh) resolver
+    fn restore(&mut self, data: &[u8]) -> PyResult<()> {
+        self.inner
+            .restore(data)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to restore resolver: {}", e)))
+    }
+
     /// Resolve a CARP request
     ///
-    /// Returns a CARPResolution object with allowed/denied actions
-    fn resolve(&mut self, session_id: &str, agent_id: &str, goal: &str) -> PyResult<CARPResolution> {
-        let request = CoreCARPRequest::new(
-            session_id.to_string(),
-            agent_id.to_string(),
-            goal.to_string(),
-        );
+    /// Returns a CARPResolution object with allowed/denied actions. Pass
+    /// `requested_actions` (IDs or patterns) and/or `max_risk_tier` to
+    /// narrow the allowed actions to what the agent needs.
+    #[pyo3(signature = (session_id, agent_id, goal, requested_actions=None, max_risk_tier=None, context_hints=None))]
+    fn resolve(
+        &mut self,
+        session_id: &str,
+        agent_id: &str,
+        goal: &str,
+        requested_actions: Option<Vec<String>>,
+        max_risk_tier: Option<&str>,
+        context_hints: Option<Vec<String>>,
+    ) -> PyResult<CARPResolution> {
+        let request = build_request(session_id, agent_id, goal, requested_actions, max_risk_tier, context_hints)?;
 
         let resolution = self
             .inner
@@ -410,12 +597,17 @@ impl Resolver {
     }
 
     /// Resolve and return JSON string (for compatibility)
-    fn resolve_json(&mut self, session_id: &str, agent_id: &str, goal: &str) -> PyResult<String> {
-        let request = CoreCARPRequest::new(
-            session_id.to_string(),
-            agent_id.to_string(),
-            goal.to_string(),
-        );
+    #[pyo3(signature = (session_id, agent_id, goal, requested_actions=None, max_risk_tier=None, context_hints=None))]
+    fn resolve_json(
+        &mut self,
+        session_id: &str,
+        agent_id: &str,
+        goal: &str,
+        requested_actions: Option<Vec<String>>,
+        max_risk_tier: Option<&str>,
+        context_hints: Option<Vec<String>>,
+    ) -> PyResult<String> {
+        let request = build_request(session_id, agent_id, goal, requested_actions, max_risk_tier, context_hints)?;
 
         let resolution = self
             .inner
@@ -476,6 +668,73 @@ impl Resolver {
         Ok(events.iter().map(TRACEEvent::from).collect())
     }
 
+    /// Iterate over a session's TRACE events lazily
+    ///
+    /// Events are fetched `page_size` at a time. With `follow=True` the
+    /// iterator keeps waiting for new events, polling every `poll_interval`
+    /// seconds, until the session ends or `timeout` seconds pass idle.
+    #[pyo3(signature = (session_id, follow=false, page_size=256, poll_interval=0.5, timeout=None))]
+    fn iter_trace(
+        slf: PyRef<'_, Self>,
+        session_id: &str,
+        follow: bool,
+        page_size: usize,
+        poll_interval: f64,
+        timeout: Option<f64>,
+    ) -> PyResult<TraceIterator> {
+        // Fail fast on unknown sessions
+        slf.inner
+            .get_trace_page(session_id, 0, 0)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to get trace: {}", e)))?;
+        if page_size == 0 {
+            return Err(PyValueError::new_err("page_size must be positive"));
+        }
+        let seconds = |s: f64| {
+            Duration::try_from_secs_f64(s)
+                .map_err(|_| PyValueError::new_err(format!("Invalid duration: {}", s)))
+        };
+
+        Ok(TraceIterator {
+            resolver: slf.into(),
+            session_id: session_id.to_string(),
+            next_index: 0,
+            page_size,
+            buffer: VecDeque::new(),
+            follow,
+            poll_interval: seconds(poll_interval)?,
+            timeout: timeout.map(seconds).transpose()?,
+            ended: false,
+        })
+    }
+
+    /// Export a session's trace as a pyarrow RecordBatch
+    ///
+    /// Columns: sequence, timestamp (UTC, microseconds), event_type,
+    /// event_id, trace_id, span_id, parent_span_id, event_hash,
+    /// previous_event_hash, the common payload fields in
+    /// `TRACE_PAYLOAD_COLUMNS` (null where absent) and the full payload as
+    /// JSON. Requires pyarrow.
+    ///
+    /// This is a copy, not a zero-copy Arrow C data interface export: each
+    /// column is built as a Python list and converted by `pyarrow.array`.
+    /// While it runs, the trace is held three times, as Rust events, as
+    /// Python objects and as Arrow buffers; the Python lists are freed once
+    /// the batch is built. Peak memory is several times the trace's size,
+    /// so export very long traces in slices of `iter_trace`.
+    fn trace_to_arrow(&self, py: Python, session_id: &str) -> PyResult<PyObject> {
+        let events = self
+            .inner
+            .get_trace(session_id)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to get trace: {}", e)))?;
+        trace_record_batch(py, &events)
+    }
+
+    /// Export a session's trace as a pandas DataFrame (via `trace_to_arrow`)
+    fn trace_to_pandas(&self, py: Python, session_id: &str) -> PyResult<PyObject> {
+        let batch = self.trace_to_arrow(py, session_id)?;
+        batch.call_method0(py, "to_pandas")
+    }
+
     /// Verify the hash chain for a session
     fn verify_chain(&self, session_id: &str) -> PyResult<ChainVerification> {
         let verification = self
@@ -486,6 +745,57 @@ impl Resolver {
         Ok(ChainVerification::from(verification))
     }
 
+    /// Get checkpoints awaiting a response for a session
+    ///
+    /// Returns a list of dicts with checkpoint_id, name, requires_response,
+    /// questions and guidance
+    fn get_pending_checkpoints(&self, py: Python, session_id: &str) -> PyResult<Vec<PyObject>> {
+        self.inner
+            .get_pending_checkpoints(session_id)
+            .map(|pending| pending.iter().map(|c| checkpoint_to_py(py, c)).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    /// Answer a pending checkpoint
+    ///
+    /// `answers` maps question IDs to answers. Returns a dict with is_valid,
+    /// per-question errors and the capabilities unlocked or locked.
+    #[pyo3(signature = (session_id, checkpoint_id, answers, guidance_acknowledged=false))]
+    fn respond_to_checkpoint(
+        &mut self,
+        py: Python,
+        session_id: &str,
+        checkpoint_id: &str,
+        answers: &PyAny,
+        guidance_acknowledged: bool,
+    ) -> PyResult<PyObject> {
+        let answers: String = py.import("json")?.call_method1("dumps", (answers,))?.extract()?;
+        let response = CheckpointResponse {
+            checkpoint_id: checkpoint_id.to_string(),
+            answers: serde_json::from_str(&answers)
+                .map_err(|e| PyValueError::new_err(format!("Invalid answers: {}", e)))?,
+            guidance_acknowledged,
+            responded_at: chrono::Utc::now().to_rfc3339(),
+            session_id: session_id.to_string(),
+        };
+
+        let validation = self
+            .inner
+            .respond_to_checkpoint(session_id, &response)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to respond to checkpoint: {}", e)))?;
+
+        let errors: HashMap<String, String> = validation.question_results.iter()
+            .filter_map(|(id, r)| r.error_message.clone().map(|m| (id.clone(), m)))
+            .collect();
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("is_valid", validation.is_valid)?;
+        dict.set_item("errors", errors)?;
+        dict.set_item("unlocked_capabilities", validation.unlocked_capabilities)?;
+        dict.set_item("locked_capabilities", validation.locked_capabilities)?;
+        dict.set_item("inject_contexts", validation.inject_contexts)?;
+        Ok(dict.into())
+    }
+
     /// Get event count for a session
     fn get_event_count(&self, session_id: &str) -> PyResult<usize> {
         let events = self
@@ -531,6 +841,126 @@ fn json_to_py(py: Python, value: &serde_json::Value) -> PyResult<PyObject> {
     }
 }
 
+/// Payload fields flattened into their own trace export columns
+const TRACE_PAYLOAD_COLUMNS: &[&str] = &[
+    "agent_id",
+    "goal",
+    "action_id",
+    "execution_id",
+    "resolution_id",
+    "decision",
+    "context_id",
+    "duration_ms",
+];
+
+/// One typed column of a trace export
+#[derive(Debug, Clone, PartialEq)]
+enum TraceColumn {
+    /// `uint64`; `None` is null
+    UInt64(Vec<Option<u64>>),
+    /// `timestamp[us, tz=UTC]`
+    TimestampMicros(Vec<i64>),
+    /// `string`; `None` is null
+    Utf8(Vec<Option<String>>),
+}
+
+/// Trace export columns, in order, with their values
+fn trace_columns(events: &[CoreTRACEEvent]) -> Vec<(&'static str, TraceColumn)> {
+    let strings = |f: &dyn Fn(&CoreTRACEEvent) -> Option<String>| TraceColumn::Utf8(events.iter().map(f).collect());
+
+    let mut columns = vec![
+        ("sequence", TraceColumn::UInt64(events.iter().map(|e| Some(e.sequence)).collect())),
+        ("timestamp", TraceColumn::TimestampMicros(events.iter().map(|e| e.timestamp.timestamp_micros()).collect())),
+        ("event_type", strings(&|e| Some(e.event_type.to_string()))),
+        ("event_id", strings(&|e| Some(e.event_id.clone()))),
+        ("trace_id", strings(&|e| Some(e.trace_id.clone()))),
+        ("span_id", strings(&|e| Some(e.span_id.clone()))),
+        ("parent_span_id", strings(&|e| e.parent_span_id.clone())),
+        ("event_hash", strings(&|e| Some(e.event_hash.clone()))),
+        ("previous_event_hash", strings(&|e| Some(e.previous_event_hash.clone()))),
+    ];
+    for field in TRACE_PAYLOAD_COLUMNS {
+        let column = if *field == "duration_ms" {
+            TraceColumn::UInt64(events.iter().map(|e| e.payload.get(field).and_then(|v| v.as_u64())).collect())
+        } else {
+            strings(&|e| match e.payload.get(field) {
+                Some(serde_json::Value::String(s)) => Some(s.clone()),
+                Some(serde_json::Value::Null) | None => None,
+                Some(other) => Some(other.to_string()),
+            })
+        };
+        columns.push((field, column));
+    }
+    columns.push(("payload", strings(&|e| serde_json::to_string(&e.payload).ok())));
+    columns
+}
+
+/// Build a pyarrow RecordBatch from the trace export columns
+///
+/// Each column is handed to `pyarrow.array` as a Python list, so values are
+/// copied rather than shared over the Arrow C data interface.
+fn trace_record_batch(py: Python, events: &[CoreTRACEEvent]) -> PyResult<PyObject> {
+    let pa = py.import("pyarrow").map_err(|_| {
+        pyo3::exceptions::PyImportError::new_err("trace_to_arrow requires pyarrow")
+    })?;
+
+    let names = pyo3::types::PyList::empty(py);
+    let arrays = pyo3::types::PyList::empty(py);
+    for (name, column) in trace_columns(events) {
+        let (values, arrow_type) = match column {
+            TraceColumn::UInt64(values) => (values.into_py(py), pa.call_method0("uint64")?),
+            TraceColumn::TimestampMicros(values) => (values.into_py(py), pa.call_method1("timestamp", ("us", "UTC"))?),
+            TraceColumn::Utf8(values) => (values.into_py(py), pa.call_method0("string")?),
+        };
+        let kwargs = pyo3::types::PyDict::new(py);
+        kwargs.set_item("type", arrow_type)?;
+        names.append(name)?;
+        arrays.append(pa.call_method("array", (values,), Some(kwargs))?)?;
+    }
+
+    let kwargs = pyo3::types::PyDict::new(py);
+    kwargs.set_item("names", names)?;
+    Ok(pa
+        .getattr("RecordBatch")?
+        .call_method("from_arrays", (arrays,), Some(kwargs))?
+        .into())
+}
+
+/// Dict view of a pending checkpoint
+fn checkpoint_to_py(py: Python, checkpoint: &TriggeredCheckpoint) -> PyResult<PyObject> {
+    let definition = checkpoint.steward_def.as_ref();
+    let value = serde_json::json!({
+        "checkpoint_id": definition.map(|d| d.checkpoint_id.clone()),
+        "name": definition.map(|d| d.name.clone()),
+        "requires_response": checkpoint.requires_response(),
+        "questions": checkpoint.questions,
+        "guidance": checkpoint.guidance,
+    });
+    json_to_py(py, &value)
+}
+
+/// Build a CARP request from the optional narrowing arguments of `resolve`
+fn build_request(
+    session_id: &str,
+    agent_id: &str,
+    goal: &str,
+    requested_actions: Option<Vec<String>>,
+    max_risk_tier: Option<&str>,
+    context_hints: Option<Vec<String>>,
+) -> PyResult<CoreCARPRequest> {
+    let mut builder = CoreCARPRequest::builder(session_id.to_string(), agent_id.to_string(), goal.to_string());
+    if let Some(actions) = requested_actions {
+        builder = builder.requested_actions(actions);
+    }
+    if let Some(tier) = max_risk_tier {
+        builder = builder.max_risk_tier(tier.parse().map_err(PyValueError::new_err)?);
+    }
+    if let Some(hints) = context_hints {
+        builder = builder.context_hints(hints);
+    }
+    Ok(builder.build())
+}
+
 // =============================================================================
 // Module Functions
 // =============================================================================
@@ -565,6 +995,23 @@ fn genesis_hash() -> &'static str {
     cra_core::trace::GENESIS_HASH
 }
 
+/// Verify an exported TRACE JSONL string without a resolver
+///
+/// Returns a dict with is_valid, first_invalid_index, error_type and a
+/// per-event timeline. Pass the hash preceding a chain segment as
+/// `genesis_hash` to verify a segment.
+#[pyfunction]
+#[pyo3(signature = (jsonl, genesis_hash=None))]
+fn verify_trace(py: Python, jsonl: &str, genesis_hash: Option<&str>) -> PyResult<PyObject> {
+    let verification = cra_trace_verify::verify_jsonl(
+        jsonl,
+        genesis_hash.unwrap_or(cra_trace_verify::GENESIS_HASH),
+    );
+    let value = serde_json::to_value(&verification)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize: {}", e)))?;
+    json_to_py(py, &value)
+}
+
 // =============================================================================
 // Python Module
 // =============================================================================
@@ -583,7 +1030,9 @@ fn cra(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<AllowedAction>()?;
     m.add_class::<DeniedAction>()?;
     m.add_class::<TRACEEvent>()?;
+    m.add_class::<TraceIterator>()?;
     m.add_class::<ChainVerification>()?;
+    wrapper::register(m)?;
 
     // Functions
     m.add_function(wrap_pyfunction!(version, m)?)?;
@@ -591,6 +1040,70 @@ fn cra(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(trace_version, m)?)?;
     m.add_function(wrap_pyfunction!(atlas_version, m)?)?;
     m.add_function(wrap_pyfunction!(genesis_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_trace, m)?)?;
+
+    // Constants
+    m.add("TRACE_PAYLOAD_COLUMNS", TRACE_PAYLOAD_COLUMNS.to_vec())?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cra_core::{CARPRequest, Resolver};
+
+    fn column<'a>(columns: &'a [(&str, TraceColumn)], name: &str) -> &'a TraceColumn {
+        &columns.iter().find(|(n, _)| *n == name).unwrap().1
+    }
+
+    #[test]
+    fn test_trace_columns_types_and_nulls() {
+        let mut resolver = Resolver::new();
+        resolver
+            .load_atlas(AtlasManifest::builder("test.atlas".to_string(), "Test".to_string()).version("1.0.0").build())
+            .unwrap();
+        let session_id = resolver.create_session("agent-1", "Export the trace").unwrap();
+        let request = CARPRequest::new(session_id.clone(), "agent-1".to_string(), "Export".to_string());
+        resolver.resolve(&request).unwrap();
+        resolver.end_session(&session_id).unwrap();
+        let events = resolver.get_trace(&session_id).unwrap();
+
+        let columns = trace_columns(&events);
+        let mut expected = vec![
+            "sequence", "timestamp", "event_type", "event_id", "trace_id", "span_id",
+            "parent_span_id", "event_hash", "previous_event_hash",
+        ];
+        expected.extend(TRACE_PAYLOAD_COLUMNS);
+        expected.push("payload");
+        assert_eq!(columns.iter().map(|(n, _)| *n).collect::<Vec<_>>(), expected);
+
+        for (name, column) in &columns {
+            let len = match column {
+                TraceColumn::UInt64(v) => v.len(),
+                TraceColumn::TimestampMicros(v) => v.len(),
+                TraceColumn::Utf8(v) => v.len(),
+            };
+            assert_eq!(len, events.len(), "column {}", name);
+        }
+        let sequences: Vec<_> = (0..events.len() as u64).map(Some).collect();
+        assert_eq!(column(&columns, "sequence"), &TraceColumn::UInt64(sequences));
+        assert!(matches!(column(&columns, "timestamp"), TraceColumn::TimestampMicros(_)));
+
+        // Payload fields are null where the event doesn't carry them
+        let TraceColumn::Utf8(goals) = column(&columns, "goal") else { panic!("goal is not a string column") };
+        assert_eq!(goals[0].as_deref(), Some("Export the trace"));
+        assert!(goals.iter().skip(1).any(Option::is_none));
+        let TraceColumn::UInt64(durations) = column(&columns, "duration_ms") else {
+            panic!("duration_ms is not a uint64 column")
+        };
+        assert!(durations[0].is_none());
+        assert!(durations.last().unwrap().is_some());
+        let TraceColumn::Utf8(parents) = column(&columns, "parent_span_id") else { panic!() };
+        assert_eq!(parents.len(), events.len());
+
+        let TraceColumn::Utf8(payloads) = column(&columns, "payload") else { panic!() };
+        let first: serde_json::Value = serde_json::from_str(payloads[0].as_deref().unwrap()).unwrap();
+        assert_eq!(first, events[0].payload);
+    }
+}
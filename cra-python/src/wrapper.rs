@@ -0,0 +1,443 @@
+//! Python bindings for the agent-side Wrapper
+//!
+//! Blocking methods release the GIL while the wrapper runs on its own tokio
+//! runtime. Each has an `*_async` twin that runs it in the event loop's
+//! default executor, so it can be awaited from asyncio code:
+//!
+//! ```python
+//! from cra import Wrapper, WrapperConfig
+//!
+//! config = WrapperConfig()
+//! config.redact_secrets = True
+//! wrapper = Wrapper(config)
+//!
+//! def no_deletes(ctx):
+//!     if ctx.get("action", "").startswith("delete_"):
+//!         return "deletes are not allowed"
+//!
+//! wrapper.add_hook("no-deletes", no_deletes, stages=["action"], priority=50)
+//!
+//! session_id = await wrapper.start_session_async("Help the user")
+//! processed = await wrapper.on_input_async("Deploy the app")
+//! decision = await wrapper.report_action_async("write_file", {"path": "a.txt"})
+//! summary = await wrapper.end_session_async()
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use pyo3::exceptions::{PyPermissionError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+use cra_wrapper::hooks::{HookContext, HookFlow, HookMiddleware, HookStage, ALL_STAGES};
+use cra_wrapper::{
+    TokenUsage, Wrapper as CoreWrapper, WrapperConfig as CoreWrapperConfig, WrapperError,
+    WrapperResult,
+};
+
+use crate::json_to_py;
+
+/// Map a wrapper error to a Python exception
+fn to_py_err(e: WrapperError) -> PyErr {
+    match e {
+        WrapperError::ActionDenied(_) | WrapperError::HookBlocked { .. } => {
+            PyPermissionError::new_err(e.to_string())
+        }
+        _ => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+/// Convert a Python object to JSON via the `json` module
+fn py_to_json(py: Python, obj: &PyAny) -> PyResult<serde_json::Value> {
+    let text: String = py.import("json")?.call_method1("dumps", (obj,))?.extract()?;
+    serde_json::from_str(&text).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Serialize a wrapper result into a Python object
+fn to_py<T: serde::Serialize>(py: Python, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
+
+/// Run `obj.method(*args)` in the running event loop's default executor
+fn in_executor<'py>(
+    py: Python<'py>,
+    obj: &'py PyAny,
+    method: &str,
+    args: Vec<PyObject>,
+) -> PyResult<&'py PyAny> {
+    let mut partial_args = vec![obj.getattr(method)?.into_py(py)];
+    partial_args.extend(args);
+    let call = py
+        .import("functools")?
+        .getattr("partial")?
+        .call1(PyTuple::new(py, partial_args))?;
+    py.import("asyncio")?
+        .call_method0("get_running_loop")?
+        .call_method1("run_in_executor", (py.None(), call))
+}
+
+fn parse_stage(name: &str) -> PyResult<HookStage> {
+    match name {
+        "input" => Ok(HookStage::Input),
+        "output" => Ok(HookStage::Output),
+        "action" => Ok(HookStage::Action),
+        other => Err(PyValueError::new_err(format!(
+            "Unknown hook stage '{}' (expected input, output or action)",
+            other
+        ))),
+    }
+}
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Wrapper configuration
+///
+/// Common switches are properties; use `from_json` for the full schema.
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct WrapperConfig {
+    inner: CoreWrapperConfig,
+}
+
+#[pymethods]
+impl WrapperConfig {
+    /// Create the default configuration
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a configuration from a JSON string
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = serde_json::from_str(json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid wrapper config: {}", e)))?;
+        Ok(Self { inner })
+    }
+
+    /// Serialize to a JSON string
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn checkpoints_enabled(&self) -> bool {
+        self.inner.checkpoints_enabled
+    }
+
+    #[setter]
+    fn set_checkpoints_enabled(&mut self, value: bool) {
+        self.inner.checkpoints_enabled = value;
+    }
+
+    #[getter]
+    fn redact_secrets(&self) -> bool {
+        self.inner.hooks.redact_secrets
+    }
+
+    #[setter]
+    fn set_redact_secrets(&mut self, value: bool) {
+        self.inner.hooks.redact_secrets = value;
+    }
+
+    #[getter]
+    fn cache_enabled(&self) -> bool {
+        self.inner.cache.enabled
+    }
+
+    #[setter]
+    fn set_cache_enabled(&mut self, value: bool) {
+        self.inner.cache.enabled = value;
+    }
+
+    #[getter]
+    fn offline_enabled(&self) -> bool {
+        self.inner.offline.enabled
+    }
+
+    #[setter]
+    fn set_offline_enabled(&mut self, value: bool) {
+        self.inner.offline.enabled = value;
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "WrapperConfig(checkpoints_enabled={}, redact_secrets={})",
+            self.inner.checkpoints_enabled, self.inner.hooks.redact_secrets
+        )
+    }
+}
+
+// =============================================================================
+// Python hooks
+// =============================================================================
+
+/// Hook middleware backed by a Python callable
+///
+/// The callable receives the hook context as a dict and may edit it in
+/// place (`text`, `params`, `injected_context`). Returning `None` or `True`
+/// continues; returning `False` or a string blocks, the string being the
+/// reason. Exceptions fail the wrapper call.
+struct PyHook {
+    name: String,
+    priority: i32,
+    stages: Vec<HookStage>,
+    callback: PyObject,
+}
+
+#[async_trait]
+impl HookMiddleware for PyHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn stages(&self) -> &[HookStage] {
+        &self.stages
+    }
+
+    async fn handle(&self, ctx: &mut HookContext) -> WrapperResult<HookFlow> {
+        Python::with_gil(|py| {
+            let hook_err = |e: PyErr| WrapperError::Internal(format!("Python hook '{}' failed: {}", self.name, e));
+
+            let dict = to_py(py, &*ctx).map_err(hook_err)?;
+            let result = self.callback.call1(py, (dict.clone_ref(py),)).map_err(hook_err)?;
+
+            let updated = py_to_json(py, dict.as_ref(py)).map_err(hook_err)?;
+            *ctx = serde_json::from_value(updated)?;
+
+            let result = result.as_ref(py);
+            if result.is_none() {
+                return Ok(HookFlow::Continue);
+            }
+            if let Ok(reason) = result.extract::<String>() {
+                return Ok(HookFlow::Block(reason));
+            }
+            match result.extract::<bool>() {
+                Ok(true) => Ok(HookFlow::Continue),
+                Ok(false) => Ok(HookFlow::Block(format!("blocked by {}", self.name))),
+                Err(_) => Err(WrapperError::Internal(format!(
+                    "Python hook '{}' must return None, a bool or a string",
+                    self.name
+                ))),
+            }
+        })
+    }
+}
+
+// =============================================================================
+// Wrapper
+// =============================================================================
+
+/// Agent-side CRA wrapper
+#[pyclass]
+pub struct Wrapper {
+    inner: Arc<CoreWrapper>,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Wrapper {
+    fn block_on<T, F>(&self, py: Python, f: F) -> PyResult<T>
+    where
+        T: Send,
+        F: std::future::Future<Output = WrapperResult<T>> + Send,
+    {
+        py.allow_threads(|| self.runtime.block_on(f)).map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl Wrapper {
+    /// Create a wrapper (default configuration if none is given)
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn new(config: Option<WrapperConfig>) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start runtime: {}", e)))?;
+
+        Ok(Self {
+            inner: Arc::new(CoreWrapper::new(config.unwrap_or_default().inner)),
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Start a governed session, returning the session ID
+    fn start_session(&self, py: Python, goal: &str) -> PyResult<String> {
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.start_session(goal).await })
+    }
+
+    /// End the current session, returning the summary as a dict
+    #[pyo3(signature = (summary=None))]
+    fn end_session(&self, py: Python, summary: Option<&str>) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let result = self.block_on(py, async move { inner.end_session(summary).await })?;
+        to_py(py, &result)
+    }
+
+    /// Run input through the hooks and checkpoint triggers
+    ///
+    /// Returns a dict with `original`, `processed` and `injected_context`
+    fn on_input(&self, py: Python, text: &str) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let result = self.block_on(py, async move { inner.on_input(text).await })?;
+        to_py(py, &result)
+    }
+
+    /// Run output through the hooks
+    ///
+    /// Returns a dict with `original` and `processed`
+    fn on_output(&self, py: Python, text: &str) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let result = self.block_on(py, async move { inner.on_output(text).await })?;
+        to_py(py, &result)
+    }
+
+    /// Report an action before executing it
+    ///
+    /// Returns a dict with `allowed` and `reason`
+    #[pyo3(signature = (action, params=None))]
+    fn report_action(&self, py: Python, action: &str, params: Option<&PyAny>) -> PyResult<PyObject> {
+        let params = match params {
+            Some(params) => py_to_json(py, params)?,
+            None => serde_json::json!({}),
+        };
+        let inner = self.inner.clone();
+        let result = self.block_on(py, async move { inner.report_action(action, params).await })?;
+        to_py(py, &result)
+    }
+
+    /// Report token usage for one model turn, returning session totals
+    #[pyo3(signature = (model, input_tokens, output_tokens, cost_usd=None))]
+    fn report_usage(
+        &self,
+        py: Python,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cost_usd: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let mut usage = TokenUsage::new(model, input_tokens, output_tokens);
+        usage.cost_usd = cost_usd;
+        let inner = self.inner.clone();
+        let result = self.block_on(py, async move { inner.report_usage(usage).await })?;
+        to_py(py, &result)
+    }
+
+    /// Current session as a dict, or None
+    fn current_session(&self, py: Python) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let session = py.allow_threads(|| self.runtime.block_on(inner.current_session()));
+        match session {
+            Some(session) => to_py(py, &session),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// TRACE queue statistics as a dict
+    fn queue_stats(&self, py: Python) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let stats = py.allow_threads(|| self.runtime.block_on(inner.queue_stats()));
+        to_py(py, &stats)
+    }
+
+    /// Context cache statistics as a dict
+    fn cache_stats(&self, py: Python) -> PyResult<PyObject> {
+        let inner = self.inner.clone();
+        let stats = py.allow_threads(|| self.runtime.block_on(inner.cache_stats()));
+        to_py(py, &stats)
+    }
+
+    /// Register a Python callable as a hook
+    ///
+    /// `stages` is a list of "input", "output" and "action" (all if omitted).
+    /// Higher priorities run first; a hook with the same name is replaced.
+    #[pyo3(signature = (name, callback, stages=None, priority=0))]
+    fn add_hook(
+        &self,
+        name: &str,
+        callback: PyObject,
+        stages: Option<Vec<String>>,
+        priority: i32,
+    ) -> PyResult<()> {
+        let stages = match stages {
+            Some(names) => names.iter().map(|s| parse_stage(s)).collect::<PyResult<Vec<_>>>()?,
+            None => ALL_STAGES.to_vec(),
+        };
+        self.inner.hooks().register(Arc::new(PyHook {
+            name: name.to_string(),
+            priority,
+            stages,
+            callback,
+        }));
+        Ok(())
+    }
+
+    /// Remove a hook by name, returning whether it existed
+    fn remove_hook(&self, name: &str) -> bool {
+        self.inner.hooks().unregister(name)
+    }
+
+    /// Names of registered hooks, in run order
+    fn hook_names(&self) -> Vec<String> {
+        self.inner.hooks().names()
+    }
+
+    /// Awaitable `start_session`
+    fn start_session_async<'py>(slf: &'py PyCell<Self>, py: Python<'py>, goal: String) -> PyResult<&'py PyAny> {
+        in_executor(py, slf, "start_session", vec![goal.into_py(py)])
+    }
+
+    /// Awaitable `end_session`
+    #[pyo3(signature = (summary=None))]
+    fn end_session_async<'py>(slf: &'py PyCell<Self>, py: Python<'py>, summary: Option<String>) -> PyResult<&'py PyAny> {
+        in_executor(py, slf, "end_session", vec![summary.into_py(py)])
+    }
+
+    /// Awaitable `on_input`
+    fn on_input_async<'py>(slf: &'py PyCell<Self>, py: Python<'py>, text: String) -> PyResult<&'py PyAny> {
+        in_executor(py, slf, "on_input", vec![text.into_py(py)])
+    }
+
+    /// Awaitable `on_output`
+    fn on_output_async<'py>(slf: &'py PyCell<Self>, py: Python<'py>, text: String) -> PyResult<&'py PyAny> {
+        in_executor(py, slf, "on_output", vec![text.into_py(py)])
+    }
+
+    /// Awaitable `report_action`
+    #[pyo3(signature = (action, params=None))]
+    fn report_action_async<'py>(
+        slf: &'py PyCell<Self>,
+        py: Python<'py>,
+        action: String,
+        params: Option<PyObject>,
+    ) -> PyResult<&'py PyAny> {
+        in_executor(py, slf, "report_action", vec![action.into_py(py), params.into_py(py)])
+    }
+
+    fn __repr__(&self, py: Python) -> String {
+        let inner = self.inner.clone();
+        let session = py.allow_threads(|| self.runtime.block_on(inner.current_session()));
+        match session {
+            Some(session) => format!("Wrapper(session_id='{}')", session.session_id),
+            None => "Wrapper(no session)".to_string(),
+        }
+    }
+}
+
+/// Register the wrapper classes on the module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_class::<Wrapper>()?;
+    m.add_class::<WrapperConfig>()?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Canonical JSON for TRACE hashing
+//!
+//! Event payloads are hashed in the JSON Canonicalization Scheme form
+//! ([RFC 8785](https://www.rfc-editor.org/rfc/rfc8785)), so verifiers in other
+//! languages can recompute hashes without depending on a serializer's field
+//! order or number formatting:
+//!
+//! - No whitespace
+//! - Object keys sorted by their UTF-16 code units
+//! - Strings escaped minimally: `"`, `\` and control characters only, using
+//!   `\b \t \n \f \r` where possible and lowercase `\u00xx` otherwise
+//! - Numbers as IEEE 754 doubles in ECMAScript `Number.prototype.toString`
+//!   form (`1.0` is `1`, `1e21` is `1e+21`, integers beyond 2^53 lose precision)
+//!
+//! Test vectors live in `specs/conformance/vectors/`.
+
+use std::fmt::Write;
+
+use serde_json::{Number, Value};
+
+/// Serialize a JSON value in canonical (RFC 8785) form
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&format_number(n)),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Format a number the way ECMAScript's `Number.prototype.toString` does
+fn format_number(n: &Number) -> String {
+    let value = n.as_f64().unwrap_or(0.0);
+    if value == 0.0 || !value.is_finite() {
+        // -0 serializes as 0; JSON has no NaN or Infinity
+        return "0".to_string();
+    }
+
+    // `{:e}` gives the shortest round-trip digits, e.g. "-1.2345e-7"
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+
+    let k = digits.len() as i32;
+    // Position of the decimal point relative to the digits
+    let n = exponent + 1;
+
+    let mut out = String::new();
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n - 1 < 0 { '-' } else { '+' }, (n - 1).abs());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn number(text: &str) -> String {
+        canonical_json(&serde_json::from_str(text).unwrap())
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(number("0"), "0");
+        assert_eq!(number("-0.0"), "0");
+        assert_eq!(number("1.0"), "1");
+        assert_eq!(number("-42"), "-42");
+        assert_eq!(number("0.1"), "0.1");
+        assert_eq!(number("1e20"), "100000000000000000000");
+        assert_eq!(number("1e21"), "1e+21");
+        assert_eq!(number("1.5e-7"), "1.5e-7");
+        assert_eq!(number("0.000001"), "0.000001");
+        assert_eq!(number("123.456e5"), "12345600");
+        assert_eq!(number("9007199254740993"), "9007199254740992");
+    }
+
+    #[test]
+    fn test_strings() {
+        let value = json!("quote\" slash\\ tab\t nul\u{0} del\u{7f} é €");
+        assert_eq!(
+            canonical_json(&value),
+            "\"quote\\\" slash\\\\ tab\\t nul\\u0000 del\u{7f} é €\""
+        );
+    }
+
+    #[test]
+    fn test_key_order_uses_utf16() {
+        // U+1F600 (surrogate pair D83D..) sorts before U+FB01 in UTF-16,
+        // but after it in UTF-8 / code point order
+        let value = json!({"\u{fb01}": 1, "\u{1f600}": 2, "b": 3, "a": {"z": null, "y": [true, false]}});
+        assert_eq!(
+            canonical_json(&value),
+            "{\"a\":{\"y\":[true,false],\"z\":null},\"b\":3,\"\u{1f600}\":2,\"\u{fb01}\":1}"
+        );
+    }
+}
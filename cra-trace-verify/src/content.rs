@@ -0,0 +1,53 @@
+//! Content records inlined into exported session traces
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// `record_type` of an inlined context block
+pub const CONTENT_RECORD_TYPE: &str = "context.content";
+
+/// Content address of a context block: `sha256:` and the hex digest
+pub fn content_address(content: &str) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(content.as_bytes())))
+}
+
+/// Context block content carried in an export
+///
+/// Events refer to context by `content_hash`; exports that inline context
+/// add one record per distinct block between the events and the trailer.
+/// Records aren't part of the hash chain, but each must match its hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentRecord {
+    /// Always [`CONTENT_RECORD_TYPE`]
+    pub record_type: String,
+    /// [`content_address`] of `content`
+    pub content_hash: String,
+    /// The block's content
+    pub content: String,
+}
+
+impl ContentRecord {
+    /// Create a record, addressing the content
+    pub fn new(content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self {
+            record_type: CONTENT_RECORD_TYPE.to_string(),
+            content_hash: content_address(&content),
+            content,
+        }
+    }
+
+    /// Read a content record from a parsed line, if it is one
+    pub fn from_json(value: &Value) -> Option<Self> {
+        if value.get("record_type").and_then(Value::as_str) != Some(CONTENT_RECORD_TYPE) {
+            return None;
+        }
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// Whether the content matches its hash
+    pub fn is_intact(&self) -> bool {
+        content_address(&self.content) == self.content_hash
+    }
+}
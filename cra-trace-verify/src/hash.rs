@@ -0,0 +1,74 @@
+//! TRACE/1.0 event hash
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::canonical::canonical_json;
+
+/// The fields covered by an event hash, in hash order
+///
+/// `timestamp` is the serialized RFC 3339 string, exactly as it appears in
+/// the event JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct HashInput<'a> {
+    pub trace_version: &'a str,
+    pub event_id: &'a str,
+    pub trace_id: &'a str,
+    pub span_id: &'a str,
+    pub parent_span_id: Option<&'a str>,
+    pub session_id: &'a str,
+    pub sequence: u64,
+    pub timestamp: &'a str,
+    pub event_type: &'a str,
+    pub payload: &'a Value,
+    pub previous_event_hash: &'a str,
+}
+
+impl<'a> HashInput<'a> {
+    /// Read the hashed fields from a serialized event
+    ///
+    /// Returns the name of the first missing or mistyped field on failure.
+    pub fn from_json(event: &'a Value) -> Result<Self, &'static str> {
+        let str_field = |name: &'static str| event.get(name).and_then(Value::as_str).ok_or(name);
+
+        Ok(Self {
+            trace_version: str_field("trace_version")?,
+            event_id: str_field("event_id")?,
+            trace_id: str_field("trace_id")?,
+            span_id: str_field("span_id")?,
+            parent_span_id: match event.get("parent_span_id") {
+                None | Some(Value::Null) => None,
+                Some(value) => Some(value.as_str().ok_or("parent_span_id")?),
+            },
+            session_id: str_field("session_id")?,
+            sequence: event.get("sequence").and_then(Value::as_u64).ok_or("sequence")?,
+            timestamp: str_field("timestamp")?,
+            event_type: str_field("event_type")?,
+            payload: event.get("payload").ok_or("payload")?,
+            previous_event_hash: str_field("previous_event_hash")?,
+        })
+    }
+
+    /// SHA-256 over the concatenated fields, as lowercase hex
+    ///
+    /// trace_version || event_id || trace_id || span_id || parent_span_id ||
+    /// session_id || sequence || timestamp || event_type || canonical_json(payload) ||
+    /// previous_event_hash
+    pub fn compute(&self) -> String {
+        let mut hasher = Sha256::new();
+
+        hasher.update(self.trace_version.as_bytes());
+        hasher.update(self.event_id.as_bytes());
+        hasher.update(self.trace_id.as_bytes());
+        hasher.update(self.span_id.as_bytes());
+        hasher.update(self.parent_span_id.unwrap_or("").as_bytes());
+        hasher.update(self.session_id.as_bytes());
+        hasher.update(self.sequence.to_string().as_bytes());
+        hasher.update(self.timestamp.as_bytes());
+        hasher.update(self.event_type.as_bytes());
+        hasher.update(canonical_json(self.payload).as_bytes());
+        hasher.update(self.previous_event_hash.as_bytes());
+
+        hex::encode(hasher.finalize())
+    }
+}
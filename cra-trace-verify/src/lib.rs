@@ -0,0 +1,38 @@
+//! Standalone TRACE/1.0 hash chain verifier
+//!
+//! Verifies exported TRACE JSONL without the CRA resolver: no atlases,
+//! policies or runtime, just `serde_json` and SHA-256. Intended for auditors
+//! and for embedding in other tools.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use cra_trace_verify::{verify_jsonl, GENESIS_HASH};
+//!
+//! let verification = verify_jsonl("", GENESIS_HASH);
+//! assert!(verification.is_valid);
+//! assert_eq!(verification.event_count, 0);
+//! ```
+//!
+//! Exports may end with an [`ExportTrailer`] line recording the event count
+//! and final hash; [`verify_jsonl`] checks it against the chain. Exports
+//! that inline context add [`ContentRecord`] lines, each checked against
+//! its content hash.
+//!
+//! The hashing rules are specified in `specs/PROTOCOL.md` (§4.4 and §6.1.1);
+//! `cra-core` uses this crate to compute its event hashes.
+
+mod canonical;
+mod content;
+mod hash;
+mod trailer;
+mod verify;
+
+pub use canonical::canonical_json;
+pub use content::{content_address, ContentRecord, CONTENT_RECORD_TYPE};
+pub use hash::HashInput;
+pub use trailer::{ExportTrailer, TRAILER_RECORD_TYPE};
+pub use verify::{verify_events, verify_jsonl, ErrorType, TimelineEntry, Verification};
+
+/// Genesis hash - previous_event_hash of the first event in a session
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
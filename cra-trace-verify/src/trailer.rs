@@ -0,0 +1,51 @@
+//! Trailer record closing an exported session trace
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `record_type` of an export trailer; events have no `record_type`
+pub const TRAILER_RECORD_TYPE: &str = "trace.trailer";
+
+/// Summary appended as the last line of an exported session trace
+///
+/// Lets a reader check that the export is complete: the trailer's
+/// `event_count` and `final_hash` must match the verified chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportTrailer {
+    /// Always [`TRAILER_RECORD_TYPE`]
+    pub record_type: String,
+    /// Session the events belong to
+    pub session_id: String,
+    /// Trace ID of the session
+    pub trace_id: String,
+    /// Number of events before the trailer
+    pub event_count: usize,
+    /// Hash of the last event (the genesis hash if there is none)
+    pub final_hash: String,
+}
+
+impl ExportTrailer {
+    /// Create a trailer for an exported chain
+    pub fn new(
+        session_id: impl Into<String>,
+        trace_id: impl Into<String>,
+        event_count: usize,
+        final_hash: impl Into<String>,
+    ) -> Self {
+        Self {
+            record_type: TRAILER_RECORD_TYPE.to_string(),
+            session_id: session_id.into(),
+            trace_id: trace_id.into(),
+            event_count,
+            final_hash: final_hash.into(),
+        }
+    }
+
+    /// Read a trailer from a parsed line, if it is one
+    pub fn from_json(value: &Value) -> Option<Self> {
+        if value.get("record_type").and_then(Value::as_str) != Some(TRAILER_RECORD_TYPE) {
+            return None;
+        }
+        serde_json::from_value(value.clone()).ok()
+    }
+}
@@ -0,0 +1,398 @@
+//! JSONL chain verification
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::content::ContentRecord;
+use crate::hash::HashInput;
+use crate::trailer::ExportTrailer;
+use crate::GENESIS_HASH;
+
+/// Payload fields used to summarize an event, in order of preference
+const SUBJECT_FIELDS: &[&str] = &[
+    "action_id",
+    "checkpoint_id",
+    "capability_id",
+    "policy_id",
+    "block_id",
+    "goal",
+    "reason",
+];
+
+/// Types of verification errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorType {
+    /// A line is not a JSON event with the hashed fields
+    ParseError,
+    /// Event's computed hash doesn't match stored hash
+    HashMismatch,
+    /// Event's previous_event_hash doesn't link to prior event
+    ChainBroken,
+    /// Sequence numbers are not consecutive
+    SequenceGap,
+    /// First event doesn't link to the genesis hash
+    InvalidGenesis,
+    /// Export trailer doesn't match the event count or final hash
+    TrailerMismatch,
+    /// Inlined context content doesn't match its content hash
+    ContentMismatch,
+}
+
+impl std::fmt::Display for ErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorType::ParseError => write!(f, "parse_error"),
+            ErrorType::HashMismatch => write!(f, "hash_mismatch"),
+            ErrorType::ChainBroken => write!(f, "chain_broken"),
+            ErrorType::SequenceGap => write!(f, "sequence_gap"),
+            ErrorType::InvalidGenesis => write!(f, "invalid_genesis"),
+            ErrorType::TrailerMismatch => write!(f, "trailer_mismatch"),
+            ErrorType::ContentMismatch => write!(f, "content_mismatch"),
+        }
+    }
+}
+
+/// One event in the verification timeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Position in the input
+    pub index: usize,
+    /// Event sequence number
+    pub sequence: u64,
+    /// Event timestamp, as serialized
+    pub timestamp: String,
+    /// Event type
+    pub event_type: String,
+    /// What the event is about (action, checkpoint, goal, ...), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Whether the chain is intact up to and including this event
+    pub valid: bool,
+}
+
+/// Result of verifying a JSONL trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+    /// Whether the chain is valid
+    pub is_valid: bool,
+    /// Number of events in the input
+    pub event_count: usize,
+    /// Index of the first invalid event (if any)
+    pub first_invalid_index: Option<usize>,
+    /// Type of error if the chain is invalid
+    pub error_type: Option<ErrorType>,
+    /// Human-readable error message
+    pub error_message: Option<String>,
+    /// Hash of the last valid event (the genesis hash if there is none)
+    pub last_valid_hash: String,
+    /// Summary of each parsed event
+    pub timeline: Vec<TimelineEntry>,
+    /// Export trailer, if the input ended with one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailer: Option<ExportTrailer>,
+    /// Number of inlined context content records
+    #[serde(default)]
+    pub content_count: usize,
+}
+
+impl Verification {
+    fn fail(&mut self, index: usize, error_type: ErrorType, message: String) {
+        self.is_valid = false;
+        self.first_invalid_index = Some(index);
+        self.error_type = Some(error_type);
+        self.error_message = Some(message);
+        for entry in self.timeline.iter_mut().skip(index) {
+            entry.valid = false;
+        }
+    }
+}
+
+/// Verify a JSONL trace against a genesis hash
+///
+/// Pass [`GENESIS_HASH`] for a full session trace, or the hash of the event
+/// before the segment to verify an exported chain segment. Full traces must
+/// start at sequence 0; segments may start anywhere.
+///
+/// Verification stops at the first invalid event, but the timeline covers
+/// every event up to the first line that isn't valid JSON. A final
+/// [`ExportTrailer`] line is not counted as an event; it must match the
+/// event count and the last event's hash. Neither are [`ContentRecord`]
+/// lines, which must match their content hash.
+pub fn verify_jsonl(jsonl: &str, genesis_hash: &str) -> Verification {
+    let lines: Vec<&str> = jsonl.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut events = Vec::new();
+    let mut parse_error = None;
+    for (index, line) in lines.iter().enumerate() {
+        match serde_json::from_str::<Value>(line) {
+            Ok(event) => events.push(event),
+            Err(e) => {
+                parse_error = Some((index, format!("Line {} is not valid JSON: {}", index, e)));
+                break;
+            }
+        }
+    }
+
+    let trailer = match (&parse_error, events.last()) {
+        (None, Some(last)) => ExportTrailer::from_json(last),
+        _ => None,
+    };
+    if trailer.is_some() {
+        events.pop();
+    }
+    let mut contents = Vec::new();
+    events.retain(|event| match ContentRecord::from_json(event) {
+        Some(record) => {
+            contents.push(record);
+            false
+        }
+        None => true,
+    });
+
+    let mut verification = verify_events(&events, genesis_hash);
+    verification.content_count = contents.len();
+    if let Some((index, message)) = parse_error {
+        verification.event_count = lines.len();
+        if verification.is_valid {
+            verification.fail(index, ErrorType::ParseError, message);
+        }
+    }
+    if let Some(trailer) = trailer {
+        if verification.is_valid
+            && (trailer.event_count != events.len() || trailer.final_hash != verification.last_valid_hash)
+        {
+            let message = format!(
+                "Trailer records {} events ending in {}, but the chain has {} ending in {}",
+                trailer.event_count,
+                trailer.final_hash,
+                events.len(),
+                verification.last_valid_hash
+            );
+            verification.fail(events.len(), ErrorType::TrailerMismatch, message);
+        }
+        verification.trailer = Some(trailer);
+    }
+    if let Some(record) = contents.iter().find(|r| !r.is_intact()) {
+        if verification.is_valid {
+            let message = format!("Inlined content doesn't match its hash {}", record.content_hash);
+            verification.fail(events.len(), ErrorType::ContentMismatch, message);
+        }
+    }
+    verification
+}
+
+/// Verify parsed events against a genesis hash (see [`verify_jsonl`])
+pub fn verify_events(events: &[Value], genesis_hash: &str) -> Verification {
+    let mut verification = Verification {
+        is_valid: true,
+        event_count: events.len(),
+        first_invalid_index: None,
+        error_type: None,
+        error_message: None,
+        last_valid_hash: genesis_hash.to_string(),
+        timeline: events.iter().enumerate().map(|(i, e)| timeline_entry(i, e)).collect(),
+        trailer: None,
+        content_count: 0,
+    };
+
+    let mut previous: Option<u64> = None;
+    for (i, event) in events.iter().enumerate() {
+        let input = match HashInput::from_json(event) {
+            Ok(input) => input,
+            Err(field) => {
+                verification.fail(i, ErrorType::ParseError, format!("Event {} is missing {}", i, field));
+                break;
+            }
+        };
+
+        if input.previous_event_hash != verification.last_valid_hash {
+            let error_type = if i == 0 { ErrorType::InvalidGenesis } else { ErrorType::ChainBroken };
+            let message = format!(
+                "Event {} previous_event_hash {} doesn't match {}",
+                i, input.previous_event_hash, verification.last_valid_hash
+            );
+            verification.fail(i, error_type, message);
+            break;
+        }
+
+        let expected_sequence = match previous {
+            Some(sequence) => Some(sequence + 1),
+            None if genesis_hash == GENESIS_HASH => Some(0),
+            None => None,
+        };
+        if expected_sequence.is_some_and(|s| s != input.sequence) {
+            let message = format!(
+                "Event {} sequence {} should be {}",
+                i,
+                input.sequence,
+                expected_sequence.unwrap_or_default()
+            );
+            verification.fail(i, ErrorType::SequenceGap, message);
+            break;
+        }
+
+        let stored = event.get("event_hash").and_then(Value::as_str).unwrap_or_default();
+        let computed = input.compute();
+        if stored != computed {
+            let message = format!("Event {} hash mismatch: stored {}, computed {}", i, stored, computed);
+            verification.fail(i, ErrorType::HashMismatch, message);
+            break;
+        }
+
+        verification.last_valid_hash = computed;
+        previous = Some(input.sequence);
+    }
+
+    verification
+}
+
+fn timeline_entry(index: usize, event: &Value) -> TimelineEntry {
+    let str_field = |name: &str| event.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+    let payload = event.get("payload");
+
+    TimelineEntry {
+        index,
+        sequence: event.get("sequence").and_then(Value::as_u64).unwrap_or_default(),
+        timestamp: str_field("timestamp"),
+        event_type: str_field("event_type"),
+        subject: SUBJECT_FIELDS.iter().find_map(|field| {
+            payload
+                .and_then(|p| p.get(*field))
+                .and_then(Value::as_str)
+                .map(|value| format!("{}={}", field, value))
+        }),
+        valid: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashInput;
+    use serde_json::json;
+
+    /// Build a valid chain of `n` events
+    fn chain(n: u64) -> Vec<Value> {
+        let mut previous = GENESIS_HASH.to_string();
+        (0..n)
+            .map(|sequence| {
+                let mut event = json!({
+                    "trace_version": "1.0",
+                    "event_id": format!("event-{}", sequence),
+                    "trace_id": "trace",
+                    "span_id": format!("span-{}", sequence),
+                    "session_id": "session",
+                    "sequence": sequence,
+                    "timestamp": "2023-11-14T22:13:20Z",
+                    "event_type": if sequence == 0 { "session.started" } else { "action.executed" },
+                    "payload": {"action_id": format!("action.{}", sequence)},
+                    "previous_event_hash": previous,
+                });
+                let hash = HashInput::from_json(&event).unwrap().compute();
+                event["event_hash"] = json!(hash);
+                previous = hash;
+                event
+            })
+            .collect()
+    }
+
+    fn to_jsonl(events: &[Value]) -> String {
+        events.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_valid_chain() {
+        let events = chain(3);
+        let verification = verify_jsonl(&to_jsonl(&events), GENESIS_HASH);
+
+        assert!(verification.is_valid);
+        assert_eq!(verification.event_count, 3);
+        assert_eq!(verification.last_valid_hash, events[2]["event_hash"]);
+        assert_eq!(verification.timeline[1].subject.as_deref(), Some("action_id=action.1"));
+        assert!(verification.timeline.iter().all(|e| e.valid));
+    }
+
+    #[test]
+    fn test_tampered_payload() {
+        let mut events = chain(3);
+        events[1]["payload"]["action_id"] = json!("action.evil");
+        let verification = verify_jsonl(&to_jsonl(&events), GENESIS_HASH);
+
+        assert!(!verification.is_valid);
+        assert_eq!(verification.first_invalid_index, Some(1));
+        assert_eq!(verification.error_type, Some(ErrorType::HashMismatch));
+        assert_eq!(verification.last_valid_hash, events[0]["event_hash"]);
+        assert!(verification.timeline[0].valid);
+        assert!(!verification.timeline[2].valid);
+    }
+
+    #[test]
+    fn test_segment_with_custom_genesis() {
+        let events = chain(4);
+        let genesis = events[1]["event_hash"].as_str().unwrap();
+
+        assert!(verify_events(&events[2..], genesis).is_valid);
+
+        let verification = verify_events(&events[2..], GENESIS_HASH);
+        assert_eq!(verification.error_type, Some(ErrorType::InvalidGenesis));
+    }
+
+    #[test]
+    fn test_invalid_json_line() {
+        let jsonl = format!("{}\nnot json\n", to_jsonl(&chain(1)));
+        let verification = verify_jsonl(&jsonl, GENESIS_HASH);
+
+        assert_eq!(verification.event_count, 2);
+        assert_eq!(verification.first_invalid_index, Some(1));
+        assert_eq!(verification.error_type, Some(ErrorType::ParseError));
+        assert_eq!(verification.timeline.len(), 1);
+    }
+
+    #[test]
+    fn test_export_trailer() {
+        let events = chain(2);
+        let trailer = ExportTrailer::new("session", "trace", 2, events[1]["event_hash"].as_str().unwrap());
+        let jsonl = format!("{}\n{}\n", to_jsonl(&events), serde_json::to_string(&trailer).unwrap());
+        let verification = verify_jsonl(&jsonl, GENESIS_HASH);
+
+        assert!(verification.is_valid);
+        assert_eq!(verification.event_count, 2);
+        assert_eq!(verification.trailer, Some(trailer));
+
+        let truncated = ExportTrailer::new("session", "trace", 2, events[0]["event_hash"].as_str().unwrap());
+        let jsonl = format!("{}\n{}\n", to_jsonl(&events[..1]), serde_json::to_string(&truncated).unwrap());
+        let verification = verify_jsonl(&jsonl, GENESIS_HASH);
+
+        assert!(!verification.is_valid);
+        assert_eq!(verification.first_invalid_index, Some(1));
+        assert_eq!(verification.error_type, Some(ErrorType::TrailerMismatch));
+    }
+
+    #[test]
+    fn test_inlined_content_records() {
+        let events = chain(2);
+        let trailer = ExportTrailer::new("session", "trace", 2, events[1]["event_hash"].as_str().unwrap());
+        let record = ContentRecord::new("Always cite sources");
+        let export = |record: &ContentRecord| {
+            format!(
+                "{}\n{}\n{}\n",
+                to_jsonl(&events),
+                serde_json::to_string(record).unwrap(),
+                serde_json::to_string(&trailer).unwrap()
+            )
+        };
+
+        let verification = verify_jsonl(&export(&record), GENESIS_HASH);
+        assert!(verification.is_valid);
+        assert_eq!(verification.event_count, 2);
+        assert_eq!(verification.content_count, 1);
+
+        let tampered = ContentRecord {
+            content: "Never cite sources".to_string(),
+            ..record
+        };
+        let verification = verify_jsonl(&export(&tampered), GENESIS_HASH);
+        assert!(!verification.is_valid);
+        assert_eq!(verification.error_type, Some(ErrorType::ContentMismatch));
+    }
+}
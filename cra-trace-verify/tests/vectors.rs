@@ -0,0 +1,38 @@
+//! Hash vectors and golden traces from specs/conformance/
+
+use cra_trace_verify::{canonical_json, verify_jsonl, HashInput, GENESIS_HASH};
+use serde_json::Value;
+
+fn load_vectors(json: &str) -> Vec<Value> {
+    let file: Value = serde_json::from_str(json).expect("Failed to parse vectors");
+    file["vectors"].as_array().expect("Missing vectors").clone()
+}
+
+#[test]
+fn canonical_json_vectors() {
+    for vector in load_vectors(include_str!("../../specs/conformance/vectors/canonical-json.json")) {
+        let input: Value = serde_json::from_str(vector["input"].as_str().unwrap()).unwrap();
+        assert_eq!(canonical_json(&input), vector["canonical"].as_str().unwrap(), "vector {}", vector["name"]);
+    }
+}
+
+#[test]
+fn event_hash_vectors() {
+    for vector in load_vectors(include_str!("../../specs/conformance/vectors/trace-hash.json")) {
+        let input = HashInput::from_json(&vector["event"]).unwrap();
+        assert_eq!(input.compute(), vector["event_hash"].as_str().unwrap(), "vector {}", vector["name"]);
+    }
+}
+
+#[test]
+fn golden_traces_verify() {
+    for jsonl in [
+        include_str!("../../specs/conformance/golden/simple-resolve/golden-trace.jsonl"),
+        include_str!("../../specs/conformance/golden/execute-deny/golden-trace.jsonl"),
+    ] {
+        let verification = verify_jsonl(jsonl, GENESIS_HASH);
+        assert!(verification.is_valid, "{:?}", verification.error_message);
+        assert_eq!(verification.timeline.first().unwrap().event_type, "session.started");
+        assert_eq!(verification.timeline.last().unwrap().event_type, "session.ended");
+    }
+}
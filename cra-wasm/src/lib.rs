@@ -61,16 +61,26 @@
 //!
 //! main();
 //! ```
+//!
+//! Heartbeats and session timeouts run on JS timers; see [`SessionTimers`].
 
 use wasm_bindgen::prelude::*;
 
-use cra_core::{AtlasManifest, CARPRequest, Resolver as CoreResolver};
+use cra_core::{AtlasManifest, CARPRequest, FixedClock, Resolver as CoreResolver, SequentialIdGen};
+
+mod timing;
 
-// Set up panic hook for better error messages
-#[cfg(feature = "console_error_panic_hook")]
+pub use timing::{install_js_clock, JsClock, SessionTimers, WasmTimerBackend};
+
+// Set up the panic hook for better error messages, and take time from the
+// JS host
 #[wasm_bindgen(start)]
 pub fn start() {
+    #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
+
+    #[cfg(target_arch = "wasm32")]
+    install_js_clock();
 }
 
 /// CRA Resolver for WebAssembly
@@ -89,6 +99,19 @@ impl Resolver {
         }
     }
 
+    /// Create a deterministic resolver
+    ///
+    /// Timestamps are fixed at `epoch_ms` and IDs are sequential from `seed`,
+    /// so the same calls always produce the same trace (golden trace tests)
+    #[wasm_bindgen]
+    pub fn deterministic(epoch_ms: f64, seed: Option<u32>) -> Resolver {
+        Resolver {
+            inner: CoreResolver::new()
+                .with_clock(FixedClock::from_millis(epoch_ms as i64))
+                .with_id_generator(SequentialIdGen::with_seed(seed.unwrap_or(0) as u64)),
+        }
+    }
+
     /// Load an atlas from a JSON string
     ///
     /// Returns the atlas ID on success
@@ -128,21 +151,63 @@ impl Resolver {
             .map_err(|e| JsError::new(&format!("Failed to end session: {}", e)))
     }
 
+    /// Hand a session off to another resolver
+    ///
+    /// Ends the session here and returns the snapshot as a JSON string
+    #[wasm_bindgen]
+    pub fn export_session(&mut self, session_id: &str, destination: &str) -> Result<String, JsError> {
+        let snapshot = self
+            .inner
+            .export_session(session_id, destination)
+            .map_err(|e| JsError::new(&format!("Failed to export session: {}", e)))?;
+
+        serde_json::to_string(&snapshot)
+            .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+    }
+
+    /// Continue a session from another resolver's snapshot JSON
+    ///
+    /// Returns the session ID
+    #[wasm_bindgen]
+    pub fn import_session(&mut self, snapshot_json: &str) -> Result<String, JsError> {
+        let snapshot = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsError::new(&format!("Invalid snapshot: {}", e)))?;
+
+        self.inner
+            .import_session(snapshot)
+            .map_err(|e| JsError::new(&format!("Failed to import session: {}", e)))
+    }
+
     /// Resolve a CARP request
     ///
-    /// Returns a JSON string containing the resolution
+    /// Returns a JSON string containing the resolution. Pass
+    /// `requested_actions` (IDs or patterns) and/or `max_risk_tier` to
+    /// narrow the allowed actions to what the agent needs.
     #[wasm_bindgen]
     pub fn resolve(
         &mut self,
         session_id: &str,
         agent_id: &str,
         goal: &str,
+        requested_actions: Option<Vec<String>>,
+        max_risk_tier: Option<String>,
+        context_hints: Option<Vec<String>>,
     ) -> Result<String, JsError> {
-        let request = CARPRequest::new(
+        let mut builder = CARPRequest::builder(
             session_id.to_string(),
             agent_id.to_string(),
             goal.to_string(),
         );
+        if let Some(actions) = requested_actions {
+            builder = builder.requested_actions(actions);
+        }
+        if let Some(tier) = max_risk_tier {
+            builder = builder.max_risk_tier(tier.parse().map_err(|e: String| JsError::new(&e))?);
+        }
+        if let Some(hints) = context_hints {
+            builder = builder.context_hints(hints);
+        }
+        let request = builder.build();
 
         let resolution = self
             .inner
@@ -245,3 +310,18 @@ pub fn trace_version() -> String {
 pub fn atlas_version() -> String {
     cra_core::ATLAS_VERSION.to_string()
 }
+
+/// Verify an exported TRACE JSONL string without a resolver
+///
+/// Returns a JSON string with `is_valid`, `first_invalid_index`, `error_type`
+/// and a per-event `timeline`. Pass the hash preceding a chain segment as
+/// `genesis_hash` to verify a segment.
+#[wasm_bindgen]
+pub fn verify_trace(jsonl: &str, genesis_hash: Option<String>) -> Result<String, JsError> {
+    let verification = cra_trace_verify::verify_jsonl(
+        jsonl,
+        genesis_hash.as_deref().unwrap_or(cra_trace_verify::GENESIS_HASH),
+    );
+    serde_json::to_string(&verification)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
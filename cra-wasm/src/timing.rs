@@ -0,0 +1,377 @@
+//! JS timers and clock for browser and edge deployments
+//!
+//! `std::thread` and `std::time::Instant` aren't available on
+//! `wasm32-unknown-unknown`, so the host supplies time instead:
+//!
+//! - [`WasmTimerBackend`] schedules CRA timers with `setTimeout` and
+//!   `setInterval`, and reads `performance.now()` for the time remaining
+//! - [`JsClock`] reads `Date.now()`; it's installed as the process clock on
+//!   startup, so resolution TTLs and session ages advance
+//! - [`SessionTimers`] gives JS heartbeats and session idle and lifetime
+//!   timeouts
+//!
+//! ```javascript
+//! const timers = new SessionTimers((event) => {
+//!   const { type, session_id } = JSON.parse(event);
+//!   if (type === "session_idle") resolver.end_session(session_id);
+//! }, 30_000, 15 * 60_000);
+//! timers.start();
+//! timers.track_session(sessionId);
+//! ```
+//!
+//! The bindings assume a JS host; on other targets they panic when called.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use cra_core::clock::Clock;
+use cra_core::timing::{
+    HeartbeatConfig, SessionTTLConfig, TimerBackend, TimerEvent, TimerManager,
+};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &JsValue, timeout_ms: f64) -> JsValue;
+
+    #[wasm_bindgen(js_name = setInterval)]
+    fn set_interval(handler: &JsValue, timeout_ms: f64) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: &JsValue);
+
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(handle: &JsValue);
+
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn date_now() -> f64;
+}
+
+/// Clock reading `Date.now()`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsClock;
+
+impl Clock for JsClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(date_now() as i64).unwrap_or_default()
+    }
+}
+
+/// Install [`JsClock`] as the process clock
+pub fn install_js_clock() {
+    cra_core::clock::set_clock(std::sync::Arc::new(JsClock));
+}
+
+/// A timer handed to the JS host
+struct JsTimer {
+    handle: JsValue,
+    /// `performance.now()` when scheduled
+    scheduled_at: f64,
+    /// Delay, or interval for repeating timers
+    period_ms: f64,
+    /// Keeps the interval handler alive; one-shot handlers free themselves
+    interval: Option<Closure<dyn FnMut()>>,
+}
+
+impl JsTimer {
+    fn clear(&self) {
+        if self.interval.is_some() {
+            clear_interval(&self.handle);
+        } else {
+            clear_timeout(&self.handle);
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        let elapsed = (performance_now() - self.scheduled_at).max(0.0);
+        let remaining = if self.interval.is_some() && self.period_ms > 0.0 {
+            self.period_ms - elapsed % self.period_ms
+        } else {
+            (self.period_ms - elapsed).max(0.0)
+        };
+        Duration::from_secs_f64(remaining / 1000.0)
+    }
+}
+
+type Callback = Rc<dyn Fn(TimerEvent)>;
+
+/// Timers and callbacks of every backend
+///
+/// JS values can't cross threads, so they live here rather than in the
+/// (`Send + Sync`) backends, which only hold their key.
+#[derive(Default)]
+struct Registry {
+    callbacks: HashMap<u64, Callback>,
+    timers: HashMap<(u64, String), JsTimer>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+static NEXT_BACKEND: AtomicU64 = AtomicU64::new(1);
+
+/// Timer backend using the JS host's timers
+///
+/// The wasm counterpart of `StdTimerBackend`: fired timers call the
+/// callback set with [`WasmTimerBackend::with_callback`].
+pub struct WasmTimerBackend {
+    key: u64,
+}
+
+impl WasmTimerBackend {
+    /// Create a backend without a callback
+    pub fn new() -> Self {
+        Self {
+            key: NEXT_BACKEND.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Create with a callback for timer events
+    pub fn with_callback<F>(callback: F) -> Self
+    where
+        F: Fn(TimerEvent) + 'static,
+    {
+        let backend = Self::new();
+        REGISTRY.with(|r| r.borrow_mut().callbacks.insert(backend.key, Rc::new(callback)));
+        backend
+    }
+
+    /// Fire a timer event (calls the callback if set)
+    ///
+    /// The registry is released first, so the callback may reschedule.
+    fn fire_event(key: u64, id: &str, event: TimerEvent, once: bool) {
+        let callback = REGISTRY.with(|r| {
+            let mut registry = r.borrow_mut();
+            if once {
+                registry.timers.remove(&(key, id.to_string()));
+            }
+            registry.callbacks.get(&key).cloned()
+        });
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+
+    fn insert(&self, id: &str, timer: JsTimer) {
+        let previous = REGISTRY.with(|r| r.borrow_mut().timers.insert((self.key, id.to_string()), timer));
+        if let Some(previous) = previous {
+            previous.clear();
+        }
+    }
+}
+
+impl Default for WasmTimerBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for WasmTimerBackend {
+    fn drop(&mut self) {
+        let timers: Vec<JsTimer> = REGISTRY.with(|r| {
+            let mut registry = r.borrow_mut();
+            registry.callbacks.remove(&self.key);
+            let keys: Vec<_> = registry.timers.keys().filter(|(k, _)| *k == self.key).cloned().collect();
+            keys.iter().filter_map(|k| registry.timers.remove(k)).collect()
+        });
+        timers.iter().for_each(JsTimer::clear);
+    }
+}
+
+impl TimerBackend for WasmTimerBackend {
+    fn schedule_once(&self, id: &str, delay: Duration, event: TimerEvent) -> cra_core::Result<()> {
+        let (key, timer_id) = (self.key, id.to_string());
+        let handler = Closure::once_into_js(move || Self::fire_event(key, &timer_id, event, true));
+        let period_ms = delay.as_secs_f64() * 1000.0;
+
+        self.insert(
+            id,
+            JsTimer {
+                handle: set_timeout(&handler, period_ms),
+                scheduled_at: performance_now(),
+                period_ms,
+                interval: None,
+            },
+        );
+        Ok(())
+    }
+
+    fn schedule_repeating(&self, id: &str, interval: Duration, event: TimerEvent) -> cra_core::Result<()> {
+        let (key, timer_id) = (self.key, id.to_string());
+        let handler = Closure::<dyn FnMut()>::new(move || {
+            Self::fire_event(key, &timer_id, event.clone(), false)
+        });
+        let period_ms = interval.as_secs_f64() * 1000.0;
+
+        self.insert(
+            id,
+            JsTimer {
+                handle: set_interval(handler.as_ref(), period_ms),
+                scheduled_at: performance_now(),
+                period_ms,
+                interval: Some(handler),
+            },
+        );
+        Ok(())
+    }
+
+    fn cancel(&self, id: &str) -> cra_core::Result<bool> {
+        let timer = REGISTRY.with(|r| r.borrow_mut().timers.remove(&(self.key, id.to_string())));
+        match timer {
+            Some(timer) => {
+                timer.clear();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn exists(&self, id: &str) -> bool {
+        REGISTRY.with(|r| r.borrow().timers.contains_key(&(self.key, id.to_string())))
+    }
+
+    fn time_remaining(&self, id: &str) -> Option<Duration> {
+        REGISTRY.with(|r| r.borrow().timers.get(&(self.key, id.to_string())).map(JsTimer::remaining))
+    }
+
+    fn name(&self) -> &'static str {
+        "wasm"
+    }
+}
+
+/// JSON form of a timer event passed to JS callbacks
+fn event_json(event: &TimerEvent) -> serde_json::Value {
+    use serde_json::json;
+
+    match event {
+        TimerEvent::Heartbeat { session_id } => json!({"type": "heartbeat", "session_id": session_id}),
+        TimerEvent::SessionIdle { session_id } => json!({"type": "session_idle", "session_id": session_id}),
+        TimerEvent::SessionExpired { session_id } => {
+            json!({"type": "session_expired", "session_id": session_id})
+        }
+        TimerEvent::ResolutionExpired { resolution_id } => {
+            json!({"type": "resolution_expired", "resolution_id": resolution_id})
+        }
+        TimerEvent::RateLimitReset { policy_id, action_id } => {
+            json!({"type": "rate_limit_reset", "policy_id": policy_id, "action_id": action_id})
+        }
+        TimerEvent::TraceBatchFlush => json!({"type": "trace_batch_flush"}),
+        TimerEvent::GarbageCollect => json!({"type": "garbage_collect"}),
+        TimerEvent::Custom { name, data } => json!({"type": "custom", "name": name, "data": data}),
+    }
+}
+
+/// Heartbeats and session timeouts on JS timers
+///
+/// `on_event` receives each timer event as a JSON string with a `type`
+/// (`heartbeat`, `session_idle`, `session_expired`, `trace_batch_flush`)
+/// and the `session_id` it concerns.
+#[wasm_bindgen]
+pub struct SessionTimers {
+    manager: TimerManager<WasmTimerBackend>,
+}
+
+#[wasm_bindgen]
+impl SessionTimers {
+    /// Create session timers
+    ///
+    /// Intervals are in milliseconds; omitted ones keep the core defaults
+    /// (30 s heartbeat, 1 h idle timeout, 24 h lifetime).
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        on_event: js_sys::Function,
+        heartbeat_ms: Option<f64>,
+        idle_timeout_ms: Option<f64>,
+        max_lifetime_ms: Option<f64>,
+    ) -> SessionTimers {
+        let backend = WasmTimerBackend::with_callback(move |event| {
+            let json = event_json(&event).to_string();
+            if let Err(e) = on_event.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+                web_sys::console::error_2(&JsValue::from_str("CRA timer callback failed:"), &e);
+            }
+        });
+
+        let mut heartbeat = HeartbeatConfig::new();
+        if let Some(ms) = heartbeat_ms {
+            heartbeat = heartbeat.interval(millis(ms));
+        }
+        let mut ttl = SessionTTLConfig::new();
+        if let Some(ms) = idle_timeout_ms {
+            ttl = ttl.idle_timeout(millis(ms));
+        }
+        if let Some(ms) = max_lifetime_ms {
+            ttl = ttl.max_lifetime(millis(ms));
+        }
+
+        SessionTimers {
+            manager: TimerManager::new(backend)
+                .with_heartbeat(heartbeat)
+                .with_session_ttl(ttl),
+        }
+    }
+
+    /// Start heartbeat and trace flush timers
+    #[wasm_bindgen]
+    pub fn start(&self) -> Result<(), JsError> {
+        self.manager
+            .start()
+            .map_err(|e| JsError::new(&format!("Failed to start timers: {}", e)))
+    }
+
+    /// Stop heartbeat and trace flush timers
+    #[wasm_bindgen]
+    pub fn stop(&self) -> Result<(), JsError> {
+        self.manager
+            .stop()
+            .map_err(|e| JsError::new(&format!("Failed to stop timers: {}", e)))
+    }
+
+    /// Start idle and lifetime timeouts for a session
+    #[wasm_bindgen]
+    pub fn track_session(&self, session_id: &str) -> Result<(), JsError> {
+        self.manager
+            .track_session(session_id)
+            .map_err(|e| JsError::new(&format!("Failed to track session: {}", e)))
+    }
+
+    /// Record session activity, restarting its idle timeout
+    #[wasm_bindgen]
+    pub fn touch_session(&self, session_id: &str) -> Result<(), JsError> {
+        self.manager
+            .touch_session(session_id)
+            .map_err(|e| JsError::new(&format!("Failed to touch session: {}", e)))
+    }
+
+    /// Cancel a session's timeouts (when it ends normally)
+    #[wasm_bindgen]
+    pub fn untrack_session(&self, session_id: &str) -> Result<(), JsError> {
+        self.manager
+            .untrack_session(session_id)
+            .map_err(|e| JsError::new(&format!("Failed to untrack session: {}", e)))
+    }
+
+    /// Milliseconds since the session was tracked
+    #[wasm_bindgen]
+    pub fn session_age_ms(&self, session_id: &str) -> Option<f64> {
+        self.manager.session_age(session_id).map(|ms| ms as f64)
+    }
+
+    /// Milliseconds since the session's last activity
+    #[wasm_bindgen]
+    pub fn session_idle_ms(&self, session_id: &str) -> Option<f64> {
+        self.manager.session_idle_time(session_id).map(|ms| ms as f64)
+    }
+}
+
+fn millis(ms: f64) -> Duration {
+    Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+}
@@ -1,9 +1,15 @@
 //! Context cache for avoiding redundant fetches
+//!
+//! Entries are bounded by count and total content size and evicted least
+//! recently used first. Each entry carries its own expiry, taken from the
+//! server's TTL when it sends one. Within the stale-while-revalidate window
+//! an expired entry is still served by [`ContextCache::lookup`], and exactly
+//! one caller is told to refresh it.
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
@@ -29,10 +35,61 @@ pub struct CachedContext {
 }
 
 impl CachedContext {
+    /// Create a context fetched now that expires after `ttl`
+    pub fn new(context_id: &str, content: &str, priority: i32, ttl: Duration) -> Self {
+        let fetched_at = Utc::now();
+        Self {
+            context_id: context_id.to_string(),
+            content: content.to_string(),
+            fetched_at,
+            expires_at: fetched_at + ttl,
+            priority,
+        }
+    }
+
     /// Check if the context is expired
     pub fn is_expired(&self) -> bool {
         Utc::now() > self.expires_at
     }
+
+    /// Approximate memory held by the entry
+    pub fn size_bytes(&self) -> usize {
+        self.context_id.len() + self.content.len()
+    }
+}
+
+/// Result of a cache lookup
+#[derive(Debug, Clone)]
+pub enum CacheLookup {
+    /// Entry is within its TTL
+    Fresh(CachedContext),
+
+    /// Entry expired but is inside the stale-while-revalidate window
+    Stale {
+        /// The stale entry, safe to use while a refresh runs
+        context: CachedContext,
+
+        /// Whether this caller should refresh it (only one caller is told to)
+        revalidate: bool,
+    },
+
+    /// Nothing usable in the cache
+    Miss,
+}
+
+impl CacheLookup {
+    /// The cached context, fresh or stale
+    pub fn context(&self) -> Option<&CachedContext> {
+        match self {
+            CacheLookup::Fresh(context) | CacheLookup::Stale { context, .. } => Some(context),
+            CacheLookup::Miss => None,
+        }
+    }
+
+    /// Whether the caller should refresh the entry
+    pub fn needs_revalidation(&self) -> bool {
+        matches!(self, CacheLookup::Stale { revalidate: true, .. })
+    }
 }
 
 /// Cache statistics
@@ -52,6 +109,61 @@ pub struct CacheStats {
 
     /// Number of evictions
     pub evictions: u64,
+
+    /// Stale entries served while being revalidated
+    pub stale_hits: u64,
+
+    /// Refreshes handed out to callers
+    pub revalidations: u64,
+
+    /// Total content size of cached entries
+    pub bytes: usize,
+
+    /// Configured size bound (0 = unbounded)
+    pub max_bytes: usize,
+}
+
+/// Cache slot with LRU bookkeeping
+struct CacheEntry {
+    context: CachedContext,
+    size: usize,
+    last_used: u64,
+    revalidating: bool,
+}
+
+/// Cache contents
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    bytes: usize,
+    tick: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.last_used = tick;
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) => {
+                self.bytes -= entry.size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn least_recently_used(&self, keep: &str) -> Option<String> {
+        self.entries.iter()
+            .filter(|(k, _)| k.as_str() != keep)
+            .min_by_key(|(_, v)| v.last_used)
+            .map(|(k, _)| k.clone())
+    }
 }
 
 /// Context cache
@@ -60,12 +172,14 @@ pub struct ContextCache {
     config: CacheConfig,
 
     /// Cached contexts by ID
-    entries: RwLock<HashMap<String, CachedContext>>,
+    state: RwLock<CacheState>,
 
     /// Statistics
     hits: AtomicU64,
     misses: AtomicU64,
     evictions: AtomicU64,
+    stale_hits: AtomicU64,
+    revalidations: AtomicU64,
 }
 
 impl ContextCache {
@@ -73,96 +187,174 @@ impl ContextCache {
     pub fn new(config: CacheConfig) -> Self {
         Self {
             config,
-            entries: RwLock::new(HashMap::new()),
+            state: RwLock::new(CacheState::default()),
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
             evictions: AtomicU64::new(0),
+            stale_hits: AtomicU64::new(0),
+            revalidations: AtomicU64::new(0),
         }
     }
 
-    /// Get a context from cache
+    /// TTL for a context, preferring the server's value over the default
+    pub fn ttl_for(&self, server_ttl_seconds: Option<u64>) -> Duration {
+        let seconds = server_ttl_seconds.unwrap_or(self.config.default_ttl_seconds);
+        Duration::seconds(seconds.min(i64::MAX as u64) as i64)
+    }
+
+    /// Get a fresh context from cache
     pub async fn get(&self, key: &str) -> Option<CachedContext> {
+        match self.lookup_inner(key, false).await {
+            CacheLookup::Fresh(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Look up a context, serving stale entries inside the revalidation window
+    ///
+    /// When this returns [`CacheLookup::Stale`] with `revalidate: true` the
+    /// caller owns the refresh and should [`set`](Self::set) the new content,
+    /// or call [`revalidation_failed`](Self::revalidation_failed).
+    pub async fn lookup(&self, key: &str) -> CacheLookup {
+        self.lookup_inner(key, true).await
+    }
+
+    async fn lookup_inner(&self, key: &str, allow_stale: bool) -> CacheLookup {
         if !self.config.enabled {
             self.misses.fetch_add(1, Ordering::SeqCst);
-            return None;
+            return CacheLookup::Miss;
         }
 
-        let entries = self.entries.read().await;
+        let mut state = self.state.write().await;
+        let now = Utc::now();
+        let window = Duration::seconds(self.config.stale_while_revalidate_seconds as i64);
 
-        if let Some(ctx) = entries.get(key) {
-            if !ctx.is_expired() {
+        let result = match state.entries.get_mut(key) {
+            Some(entry) if now <= entry.context.expires_at => {
                 self.hits.fetch_add(1, Ordering::SeqCst);
-                return Some(ctx.clone());
+                CacheLookup::Fresh(entry.context.clone())
             }
-        }
+            Some(entry) if allow_stale && now <= entry.context.expires_at + window => {
+                self.stale_hits.fetch_add(1, Ordering::SeqCst);
+                let revalidate = !entry.revalidating;
+                if revalidate {
+                    entry.revalidating = true;
+                    self.revalidations.fetch_add(1, Ordering::SeqCst);
+                }
+                CacheLookup::Stale {
+                    context: entry.context.clone(),
+                    revalidate,
+                }
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::SeqCst);
+                return CacheLookup::Miss;
+            }
+        };
 
-        self.misses.fetch_add(1, Ordering::SeqCst);
-        None
+        state.touch(key);
+        result
     }
 
     /// Store a context in cache
+    ///
+    /// Entries larger than `max_bytes` are not cached. Otherwise least
+    /// recently used entries are evicted until both bounds hold.
     pub async fn set(&self, key: &str, context: CachedContext) {
         if !self.config.enabled {
             return;
         }
 
-        let mut entries = self.entries.write().await;
-
-        // Evict if at capacity
-        if entries.len() >= self.config.max_entries && !entries.contains_key(key) {
-            // Find and remove oldest entry
-            if let Some(oldest_key) = entries.iter()
-                .min_by_key(|(_, v)| v.fetched_at)
-                .map(|(k, _)| k.clone())
-            {
-                entries.remove(&oldest_key);
-                self.evictions.fetch_add(1, Ordering::SeqCst);
+        let size = context.size_bytes();
+        let max_bytes = self.config.max_bytes;
+        let mut state = self.state.write().await;
+        state.remove(key);
+
+        if max_bytes > 0 && size > max_bytes {
+            return;
+        }
+
+        while !state.entries.is_empty()
+            && (state.entries.len() >= self.config.max_entries
+                || (max_bytes > 0 && state.bytes + size > max_bytes))
+        {
+            match state.least_recently_used(key) {
+                Some(lru) => {
+                    state.remove(&lru);
+                    self.evictions.fetch_add(1, Ordering::SeqCst);
+                }
+                None => break,
             }
         }
 
-        entries.insert(key.to_string(), context);
+        state.tick += 1;
+        let last_used = state.tick;
+        state.bytes += size;
+        state.entries.insert(key.to_string(), CacheEntry {
+            context,
+            size,
+            last_used,
+            revalidating: false,
+        });
+    }
+
+    /// Release a refresh claimed through [`lookup`](Self::lookup)
+    ///
+    /// The next lookup inside the window will be told to revalidate again.
+    pub async fn revalidation_failed(&self, key: &str) {
+        if let Some(entry) = self.state.write().await.entries.get_mut(key) {
+            entry.revalidating = false;
+        }
     }
 
     /// Invalidate a cache entry
     pub async fn invalidate(&self, key: &str) {
-        let mut entries = self.entries.write().await;
-        entries.remove(key);
+        self.state.write().await.remove(key);
     }
 
     /// Clear the entire cache
     pub async fn clear(&self) {
-        let mut entries = self.entries.write().await;
-        entries.clear();
+        let mut state = self.state.write().await;
+        state.entries.clear();
+        state.bytes = 0;
     }
 
     /// Get cache statistics
     pub async fn stats(&self) -> CacheStats {
-        let entry_count = self.entries.read().await.len();
+        let (entry_count, bytes) = {
+            let state = self.state.read().await;
+            (state.entries.len(), state.bytes)
+        };
         let hits = self.hits.load(Ordering::SeqCst);
         let misses = self.misses.load(Ordering::SeqCst);
-        let total = hits + misses;
+        let stale_hits = self.stale_hits.load(Ordering::SeqCst);
+        let total = hits + stale_hits + misses;
 
         CacheStats {
             entry_count,
             hits,
             misses,
-            hit_rate: if total > 0 { hits as f64 / total as f64 } else { 0.0 },
+            hit_rate: if total > 0 { (hits + stale_hits) as f64 / total as f64 } else { 0.0 },
             evictions: self.evictions.load(Ordering::SeqCst),
+            stale_hits,
+            revalidations: self.revalidations.load(Ordering::SeqCst),
+            bytes,
+            max_bytes: self.config.max_bytes,
         }
     }
 
-    /// Remove expired entries
+    /// Remove entries past their expiry and stale-while-revalidate window
     pub async fn evict_expired(&self) {
-        let mut entries = self.entries.write().await;
-        let now = Utc::now();
+        let mut state = self.state.write().await;
+        let cutoff = Utc::now() - Duration::seconds(self.config.stale_while_revalidate_seconds as i64);
 
-        let expired: Vec<String> = entries.iter()
-            .filter(|(_, v)| v.expires_at < now)
+        let expired: Vec<String> = state.entries.iter()
+            .filter(|(_, v)| v.context.expires_at < cutoff)
             .map(|(k, _)| k.clone())
             .collect();
 
         for key in expired {
-            entries.remove(&key);
+            state.remove(&key);
             self.evictions.fetch_add(1, Ordering::SeqCst);
         }
     }
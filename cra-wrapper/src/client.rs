@@ -3,14 +3,20 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+pub use cra_core::carp::{
+    BootstrapContext, BootstrapRequest, GovernanceRule, PolicySummary, SessionConstraint, BOOTSTRAP_PROTOCOL_VERSION,
+};
+
+use crate::content::ContentPolicy;
 use crate::error::WrapperResult;
+use crate::offline::OfflineDecision;
 use crate::ContextBlock;
 
 /// CRA Client interface
 #[async_trait]
 pub trait CRAClient: Send + Sync {
     /// Bootstrap/initialize with CRA
-    async fn bootstrap(&self, goal: &str) -> WrapperResult<BootstrapResult>;
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult>;
 
     /// Request context for a need
     async fn request_context(
@@ -42,45 +48,29 @@ pub trait CRAClient: Send + Sync {
 
     /// End session
     async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult>;
-}
-
-/// Result from bootstrap
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BootstrapResult {
-    /// Session ID
-    pub session_id: String,
-
-    /// Genesis hash
-    pub genesis_hash: String,
-
-    /// Current hash
-    pub current_hash: String,
-
-    /// Context IDs provided
-    pub context_ids: Vec<String>,
-
-    /// Contexts with content
-    pub contexts: Vec<BootstrapContext>,
 
-    /// Governance rules
-    pub rules: Vec<GovernanceRule>,
+    /// Replay decisions made offline and return the server's verdicts
+    ///
+    /// Reports are returned in the same order as `decisions`. The default
+    /// re-reports each action; servers with a reconciliation endpoint should
+    /// check them without recording new actions.
+    async fn reconcile(&self, decisions: &[OfflineDecision]) -> WrapperResult<Vec<ActionReport>> {
+        let mut reports = Vec::with_capacity(decisions.len());
+        for decision in decisions {
+            reports.push(
+                self.report_action(&decision.session_id, &decision.action, decision.params.clone())
+                    .await?,
+            );
+        }
+        Ok(reports)
+    }
 }
 
-/// Context provided during bootstrap
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BootstrapContext {
-    pub context_id: String,
-    pub content: String,
-    pub priority: i32,
-}
-
-/// Governance rule from bootstrap
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GovernanceRule {
-    pub rule_id: String,
-    pub description: String,
-    pub enforcement: String,
-}
+/// Result from bootstrap, in the shared bootstrap wire schema
+///
+/// Content policies are read straight into the hook chain's
+/// [`ContentPolicy`].
+pub type BootstrapResult = cra_core::carp::BootstrapResponse<ContentPolicy>;
 
 /// Result from action report
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -139,25 +129,12 @@ impl Default for DirectClient {
 
 #[async_trait]
 impl CRAClient for DirectClient {
-    async fn bootstrap(&self, goal: &str) -> WrapperResult<BootstrapResult> {
+    async fn bootstrap(&self, _request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
         // Direct mode - create mock bootstrap result
         let session_id = uuid::Uuid::new_v4().to_string();
         let genesis_hash = format!("genesis_{}", uuid::Uuid::new_v4());
 
-        Ok(BootstrapResult {
-            session_id: session_id.clone(),
-            genesis_hash: genesis_hash.clone(),
-            current_hash: genesis_hash,
-            context_ids: Vec::new(),
-            contexts: Vec::new(),
-            rules: vec![
-                GovernanceRule {
-                    rule_id: "trace.required".to_string(),
-                    description: "All actions must be reported".to_string(),
-                    enforcement: "hard".to_string(),
-                },
-            ],
-        })
+        Ok(BootstrapResult::new(session_id, genesis_hash).with_rules(GovernanceRule::standard()))
     }
 
     async fn request_context(
@@ -1,13 +1,22 @@
 //! Configuration for CRA Wrapper
 
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use crate::content::ContentPolicy;
+use crate::offline::OfflinePolicy;
+
 /// Main wrapper configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WrapperConfig {
     /// Version of the wrapper
     pub version: String,
 
+    /// Agent identifier sent at bootstrap
+    #[serde(default = "default_agent_id")]
+    pub agent_id: String,
+
     /// Whether checkpoints are enabled
     #[serde(default = "default_true")]
     pub checkpoints_enabled: bool,
@@ -27,19 +36,26 @@ pub struct WrapperConfig {
     /// Hook configuration
     #[serde(default)]
     pub hooks: HookConfig,
+
+    /// Offline mode configuration
+    #[serde(default)]
+    pub offline: OfflineConfig,
 }
 
 fn default_true() -> bool { true }
+fn default_agent_id() -> String { "cra-wrapper".to_string() }
 
 impl Default for WrapperConfig {
     fn default() -> Self {
         Self {
             version: "1.0.0".to_string(),
+            agent_id: default_agent_id(),
             checkpoints_enabled: true,
             queue: QueueConfig::default(),
             cache: CacheConfig::default(),
             transport: TransportConfig::default(),
             hooks: HookConfig::default(),
+            offline: OfflineConfig::default(),
         }
     }
 }
@@ -58,6 +74,11 @@ pub struct QueueConfig {
     /// Event types that require synchronous flush
     #[serde(default)]
     pub sync_events: Vec<String>,
+
+    /// Encrypt event payloads with a per-session key negotiated at
+    /// bootstrap (requires the `encryption` feature and a server key)
+    #[serde(default)]
+    pub encrypt_payloads: bool,
 }
 
 fn default_max_size() -> usize { 100 }
@@ -72,6 +93,7 @@ impl Default for QueueConfig {
                 "policy_check".to_string(),
                 "session_end".to_string(),
             ],
+            encrypt_payloads: false,
         }
     }
 }
@@ -91,6 +113,14 @@ pub struct CacheConfig {
     #[serde(default = "default_max_entries")]
     pub max_entries: usize,
 
+    /// Maximum total content size in bytes (0 = unbounded)
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+
+    /// How long an expired entry may still be served while it is refreshed
+    #[serde(default = "default_stale_while_revalidate")]
+    pub stale_while_revalidate_seconds: u64,
+
     /// Cache backend type
     #[serde(default)]
     pub backend: CacheBackendType,
@@ -98,6 +128,8 @@ pub struct CacheConfig {
 
 fn default_ttl() -> u64 { 300 }
 fn default_max_entries() -> usize { 1000 }
+fn default_max_bytes() -> usize { 16 * 1024 * 1024 }
+fn default_stale_while_revalidate() -> u64 { 60 }
 
 impl Default for CacheConfig {
     fn default() -> Self {
@@ -105,6 +137,8 @@ impl Default for CacheConfig {
             enabled: true,
             default_ttl_seconds: 300,
             max_entries: 1000,
+            max_bytes: default_max_bytes(),
+            stale_while_revalidate_seconds: 60,
             backend: CacheBackendType::Memory,
         }
     }
@@ -185,6 +219,18 @@ pub struct HookConfig {
     /// Keywords to trigger context injection
     #[serde(default)]
     pub trigger_keywords: Vec<String>,
+
+    /// Redact secret-looking tokens from input, output and action params
+    #[serde(default)]
+    pub redact_secrets: bool,
+
+    /// How to treat prompt-injection phrases in input
+    #[serde(default)]
+    pub injection_scan: InjectionScanMode,
+
+    /// Local content policies, enforced alongside those sent at bootstrap
+    #[serde(default)]
+    pub content_policies: Vec<ContentPolicy>,
 }
 
 impl Default for HookConfig {
@@ -194,6 +240,38 @@ impl Default for HookConfig {
             intercept_output: true,
             intercept_actions: true,
             trigger_keywords: Vec::new(),
+            redact_secrets: false,
+            injection_scan: InjectionScanMode::Flag,
+            content_policies: Vec::new(),
         }
     }
 }
+
+/// Prompt-injection scan mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectionScanMode {
+    /// Don't scan
+    Off,
+    /// Record findings but let input through
+    #[default]
+    Flag,
+    /// Block input containing injection phrases
+    Block,
+}
+
+/// Offline mode configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfflineConfig {
+    /// Fall back to local decisions when CRA is unreachable
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// JSONL spool for events buffered while offline (memory if unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spool_path: Option<PathBuf>,
+
+    /// Policy applied to actions while offline
+    #[serde(default)]
+    pub policy: OfflinePolicy,
+}
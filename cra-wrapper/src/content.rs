@@ -0,0 +1,309 @@
+//! Content policies for input and output text
+//!
+//! Atlases can declare `content` policies (PII, profanity, restricted
+//! topics) that govern text rather than actions. The resolver sends them at
+//! bootstrap; they can also be set locally in [`HookConfig`](crate::config::HookConfig).
+//! [`ContentPolicyMiddleware`] enforces them in the hook chain.
+//!
+//! Each policy has detectors and one action:
+//!
+//! | Action | Effect |
+//! |--------|--------|
+//! | `block` | Rejects the text |
+//! | `redact` | Replaces matched spans with [`REDACTED`] |
+//! | `flag` | Lets the text through |
+//!
+//! Every detection is recorded as a [`ContentDetection`] — policy,
+//! detector and match count, never the matched text — and ends up in the
+//! TRACE payload of the input or output event.
+//!
+//! Classifiers only score whole texts, so a classifier that fires under a
+//! `redact` policy blocks instead. Streamed output is checked one segment
+//! at a time (see [`crate::stream`]), so a pattern that spans whitespace
+//! can be missed when a chunk boundary falls inside it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{WrapperError, WrapperResult};
+use crate::hooks::{HookContext, HookFlow, HookMiddleware, HookStage, REDACTED};
+
+/// What happens when a content detector fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentAction {
+    /// Reject the text
+    Block,
+    /// Replace the matched spans
+    Redact,
+    /// Let the text through and record the detection
+    Flag,
+}
+
+/// A detector within a content policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentDetector {
+    /// Fires when any regular expression matches
+    Regex { name: String, patterns: Vec<String> },
+    /// Fires on any listed word or phrase (case-insensitive, whole words)
+    Wordlist { name: String, words: Vec<String> },
+    /// Fires when a registered classifier scores the text at or above `threshold`
+    Classifier {
+        name: String,
+        classifier: String,
+        #[serde(default = "default_threshold")]
+        threshold: f64,
+    },
+}
+
+fn default_threshold() -> f64 {
+    0.5
+}
+
+/// A content policy, as declared by an atlas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentPolicy {
+    /// Atlas policy ID
+    pub policy_id: String,
+
+    /// Stages the policy applies to (input and/or output)
+    #[serde(default = "default_stages")]
+    pub stages: Vec<HookStage>,
+
+    /// What to do on detection
+    pub action: ContentAction,
+
+    /// Detectors, any of which triggers the policy
+    pub detectors: Vec<ContentDetector>,
+
+    /// Reason given when text is blocked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+fn default_stages() -> Vec<HookStage> {
+    vec![HookStage::Input, HookStage::Output]
+}
+
+/// A content policy that fired, without the matched content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDetection {
+    /// Policy that fired
+    pub policy_id: String,
+
+    /// Detector that fired
+    pub detector: String,
+
+    /// Action taken
+    pub action: ContentAction,
+
+    /// Number of matches (1 for classifiers)
+    pub matches: usize,
+}
+
+/// A pluggable text classifier, referenced by name from `classifier` detectors
+#[async_trait]
+pub trait ContentClassifier: Send + Sync {
+    /// Score between 0 and 1 that `text` belongs to the classifier's category
+    async fn score(&self, text: &str) -> WrapperResult<f64>;
+}
+
+enum CompiledDetector {
+    /// Regex and wordlist detectors
+    Patterns { name: String, regexes: Vec<Regex> },
+    Classifier { name: String, classifier: String, threshold: f64 },
+}
+
+struct CompiledPolicy {
+    policy: ContentPolicy,
+    detectors: Vec<CompiledDetector>,
+}
+
+impl CompiledPolicy {
+    fn compile(policy: ContentPolicy) -> WrapperResult<Self> {
+        let invalid = |reason: String| {
+            WrapperError::InvalidContentPolicy(format!("'{}': {}", policy.policy_id, reason))
+        };
+
+        if policy.detectors.is_empty() {
+            return Err(invalid("no detectors".to_string()));
+        }
+        let mut detectors = Vec::with_capacity(policy.detectors.len());
+        for detector in &policy.detectors {
+            detectors.push(match detector {
+                ContentDetector::Regex { name, patterns } => CompiledDetector::Patterns {
+                    name: name.clone(),
+                    regexes: patterns
+                        .iter()
+                        .map(|p| Regex::new(p).map_err(|e| invalid(format!("detector '{}': {}", name, e))))
+                        .collect::<WrapperResult<_>>()?,
+                },
+                ContentDetector::Wordlist { name, words } => {
+                    let words: Vec<String> = words
+                        .iter()
+                        .map(|w| w.trim())
+                        .filter(|w| !w.is_empty())
+                        .map(regex::escape)
+                        .collect();
+                    if words.is_empty() {
+                        return Err(invalid(format!("detector '{}' has no words", name)));
+                    }
+                    let pattern = format!(r"(?i)\b(?:{})\b", words.join("|"));
+                    CompiledDetector::Patterns {
+                        name: name.clone(),
+                        regexes: vec![Regex::new(&pattern).map_err(|e| invalid(e.to_string()))?],
+                    }
+                }
+                ContentDetector::Classifier { name, classifier, threshold } => CompiledDetector::Classifier {
+                    name: name.clone(),
+                    classifier: classifier.clone(),
+                    threshold: *threshold,
+                },
+            });
+        }
+        Ok(Self { policy, detectors })
+    }
+}
+
+/// Enforces content policies on input and output text
+pub struct ContentPolicyMiddleware {
+    policies: RwLock<Vec<Arc<CompiledPolicy>>>,
+    classifiers: RwLock<HashMap<String, Arc<dyn ContentClassifier>>>,
+}
+
+impl ContentPolicyMiddleware {
+    /// Create with no policies
+    pub fn new() -> Self {
+        Self {
+            policies: RwLock::new(Vec::new()),
+            classifiers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add policies, replacing any with the same ID
+    ///
+    /// Nothing is added if any policy is invalid.
+    pub fn add(&self, policies: Vec<ContentPolicy>) -> WrapperResult<()> {
+        let compiled = policies
+            .into_iter()
+            .map(CompiledPolicy::compile)
+            .collect::<WrapperResult<Vec<_>>>()?;
+        if let Ok(mut current) = self.policies.write() {
+            for policy in compiled {
+                current.retain(|p| p.policy.policy_id != policy.policy.policy_id);
+                current.push(Arc::new(policy));
+            }
+        }
+        Ok(())
+    }
+
+    /// IDs of the loaded policies
+    pub fn policy_ids(&self) -> Vec<String> {
+        self.snapshot().iter().map(|p| p.policy.policy_id.clone()).collect()
+    }
+
+    /// Register a classifier under the name detectors refer to
+    pub fn register_classifier(&self, name: &str, classifier: Arc<dyn ContentClassifier>) {
+        if let Ok(mut classifiers) = self.classifiers.write() {
+            classifiers.insert(name.to_string(), classifier);
+        }
+    }
+
+    fn classifier(&self, name: &str) -> Option<Arc<dyn ContentClassifier>> {
+        self.classifiers.read().ok().and_then(|c| c.get(name).cloned())
+    }
+
+    fn snapshot(&self) -> Vec<Arc<CompiledPolicy>> {
+        self.policies.read().map(|p| p.clone()).unwrap_or_default()
+    }
+}
+
+impl Default for ContentPolicyMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HookMiddleware for ContentPolicyMiddleware {
+    fn name(&self) -> &str {
+        "content_policy"
+    }
+
+    fn priority(&self) -> i32 {
+        250
+    }
+
+    fn stages(&self) -> &[HookStage] {
+        &[HookStage::Input, HookStage::Output]
+    }
+
+    async fn handle(&self, ctx: &mut HookContext) -> WrapperResult<HookFlow> {
+        for compiled in self.snapshot() {
+            let policy = &compiled.policy;
+            if !policy.stages.contains(&ctx.stage) {
+                continue;
+            }
+
+            let mut block = None;
+            for detector in &compiled.detectors {
+                let (name, matches) = match detector {
+                    CompiledDetector::Patterns { name, regexes } => {
+                        let mut matches = 0;
+                        for regex in regexes {
+                            let found = regex.find_iter(&ctx.text).count();
+                            if found > 0 && policy.action == ContentAction::Redact {
+                                ctx.text = regex.replace_all(&ctx.text, REDACTED).into_owned();
+                                ctx.redactions += found;
+                            }
+                            matches += found;
+                        }
+                        (name, matches)
+                    }
+                    CompiledDetector::Classifier { name, classifier, threshold } => {
+                        let Some(model) = self.classifier(classifier) else {
+                            ctx.findings.push(format!(
+                                "content policy '{}': classifier '{}' is not registered",
+                                policy.policy_id, classifier
+                            ));
+                            continue;
+                        };
+                        let fired = model.score(&ctx.text).await? >= *threshold;
+                        if fired && policy.action == ContentAction::Redact {
+                            block = Some(name);
+                        }
+                        (name, usize::from(fired))
+                    }
+                };
+                if matches == 0 {
+                    continue;
+                }
+
+                let action = if block == Some(name) { ContentAction::Block } else { policy.action };
+                ctx.content_detections.push(ContentDetection {
+                    policy_id: policy.policy_id.clone(),
+                    detector: name.clone(),
+                    action,
+                    matches,
+                });
+                if policy.action == ContentAction::Block {
+                    block = Some(name);
+                }
+            }
+
+            if let Some(detector) = block {
+                let reason = policy
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| format!("content policy '{}' matched '{}'", policy.policy_id, detector));
+                return Ok(HookFlow::Block(reason));
+            }
+        }
+        Ok(HookFlow::Continue)
+    }
+}
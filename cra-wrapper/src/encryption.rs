@@ -0,0 +1,311 @@
+//! End-to-end encryption of queued TRACE payloads
+//!
+//! Events buffered on agent machines can carry sensitive payloads. When the
+//! server offers an X25519 key at bootstrap, the wrapper generates a fresh
+//! X25519 key for the session and derives a ChaCha20-Poly1305 key from the
+//! shared secret (HKDF-SHA256, salted with the session ID). Each payload is
+//! then replaced by a [`SealedPayload`]:
+//!
+//! ```json
+//! {"sealed": {"alg": "x25519-chacha20poly1305", "sender_key": "...",
+//!             "sequence": 3, "nonce": "...", "ciphertext": "...",
+//!             "previous_hash": "...", "event_hash": "..."}}
+//! ```
+//!
+//! The event type, session ID and timestamp stay in the clear and are bound
+//! to the ciphertext as associated data. `event_hash` covers that metadata,
+//! the ciphertext and `previous_hash`, so [`verify_sealed_chain`] can check
+//! the chain on an untrusted disk without any key. The server opens payloads
+//! with its [`TraceDecryptionKey`].
+
+use std::sync::Mutex;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, SecondsFormat, Utc};
+use hkdf::Hkdf;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::error::{WrapperError, WrapperResult};
+use crate::queue::QueuedEvent;
+
+/// Envelope algorithm identifier
+pub const ALGORITHM: &str = "x25519-chacha20poly1305";
+
+/// `previous_hash` of the first sealed event in a session
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const HKDF_INFO: &[u8] = b"cra-wrapper trace payload v1";
+
+/// Encrypted payload of a queued event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedPayload {
+    /// Always [`ALGORITHM`]
+    pub alg: String,
+
+    /// Wrapper's X25519 public key for the session (hex)
+    pub sender_key: String,
+
+    /// Position in the session's sealed chain, from 0
+    pub sequence: u64,
+
+    /// 96-bit nonce (hex)
+    pub nonce: String,
+
+    /// Ciphertext with Poly1305 tag (hex)
+    pub ciphertext: String,
+
+    /// `event_hash` of the previous sealed event
+    pub previous_hash: String,
+
+    /// SHA-256 over metadata, ciphertext and `previous_hash`
+    pub event_hash: String,
+}
+
+impl SealedPayload {
+    /// Read the envelope from an event, if its payload is sealed
+    pub fn from_event(event: &QueuedEvent) -> Option<Self> {
+        serde_json::from_value(event.payload.get("sealed")?.clone()).ok()
+    }
+
+    /// Recompute `event_hash` for this envelope on `event`
+    pub fn compute_hash(&self, event: &QueuedEvent) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(associated_data(event));
+        hasher.update(b"\n");
+        hasher.update(self.sequence.to_string());
+        hasher.update(b"\n");
+        hasher.update(&self.nonce);
+        hasher.update(b"\n");
+        hasher.update(&self.ciphertext);
+        hasher.update(b"\n");
+        hasher.update(&self.previous_hash);
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Whether an event's payload is sealed
+pub fn is_sealed(event: &QueuedEvent) -> bool {
+    SealedPayload::from_event(event).is_some()
+}
+
+/// Metadata that stays in the clear and is authenticated
+fn associated_data(event: &QueuedEvent) -> Vec<u8> {
+    format!(
+        "{}\n{}\n{}",
+        event.event_type,
+        event.session_id,
+        timestamp(&event.timestamp)
+    )
+    .into_bytes()
+}
+
+fn timestamp(at: &DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+fn derive_cipher(shared_secret: &[u8; 32], session_id: &str) -> ChaCha20Poly1305 {
+    let hkdf = Hkdf::<Sha256>::new(Some(session_id.as_bytes()), shared_secret);
+    let mut key = [0u8; 32];
+    // 32 bytes is always a valid HKDF-SHA256 output length
+    let _ = hkdf.expand(HKDF_INFO, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+fn parse_public_key(hex_key: &str) -> WrapperResult<PublicKey> {
+    let bytes: [u8; 32] = hex::decode(hex_key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| WrapperError::Encryption(format!("invalid X25519 public key '{}'", hex_key)))?;
+    Ok(PublicKey::from(bytes))
+}
+
+fn decode_hex(field: &str, value: &str) -> WrapperResult<Vec<u8>> {
+    hex::decode(value).map_err(|e| WrapperError::Encryption(format!("invalid {}: {}", field, e)))
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, event: &QueuedEvent, sealed: &SealedPayload) -> WrapperResult<serde_json::Value> {
+    let nonce = decode_hex("nonce", &sealed.nonce)?;
+    if nonce.len() != 12 {
+        return Err(WrapperError::Encryption("nonce must be 12 bytes".to_string()));
+    }
+    let ciphertext = decode_hex("ciphertext", &sealed.ciphertext)?;
+    let aad = associated_data(event);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+        .map_err(|_| WrapperError::Encryption("payload authentication failed".to_string()))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Wrapper-side sealing state for one session
+pub struct SessionCipher {
+    session_id: String,
+    public_key: PublicKey,
+    cipher: ChaCha20Poly1305,
+    /// (next sequence, hash of the last sealed event)
+    chain: Mutex<(u64, String)>,
+}
+
+impl std::fmt::Debug for SessionCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCipher")
+            .field("session_id", &self.session_id)
+            .field("public_key", &self.public_key_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionCipher {
+    /// Derive a session key with a fresh X25519 key and the server's public key
+    pub fn negotiate(session_id: &str, server_public_key: &str) -> WrapperResult<Self> {
+        let server_key = parse_public_key(server_public_key)?;
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public_key = PublicKey::from(&secret);
+        let shared = secret.diffie_hellman(&server_key);
+
+        Ok(Self {
+            session_id: session_id.to_string(),
+            public_key,
+            cipher: derive_cipher(shared.as_bytes(), session_id),
+            chain: Mutex::new((0, GENESIS_HASH.to_string())),
+        })
+    }
+
+    /// Session this cipher seals for
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Wrapper's public key for the session (hex)
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.public_key.as_bytes())
+    }
+
+    /// Replace the event's payload with a sealed envelope
+    ///
+    /// Events from other sessions and already sealed events pass through.
+    pub fn seal(&self, mut event: QueuedEvent) -> WrapperResult<QueuedEvent> {
+        if event.session_id != self.session_id || is_sealed(&event) {
+            return Ok(event);
+        }
+
+        let plaintext = serde_json::to_vec(&event.payload)?;
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let aad = associated_data(&event);
+        let ciphertext = self.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| WrapperError::Encryption("payload encryption failed".to_string()))?;
+
+        let mut chain = self.chain.lock()
+            .map_err(|_| WrapperError::Internal("cipher chain lock poisoned".to_string()))?;
+        let mut sealed = SealedPayload {
+            alg: ALGORITHM.to_string(),
+            sender_key: self.public_key_hex(),
+            sequence: chain.0,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            previous_hash: chain.1.clone(),
+            event_hash: String::new(),
+        };
+        sealed.event_hash = sealed.compute_hash(&event);
+        *chain = (chain.0 + 1, sealed.event_hash.clone());
+
+        event.payload = serde_json::json!({ "sealed": sealed });
+        Ok(event)
+    }
+
+    /// Decrypt a payload this cipher sealed
+    pub fn open(&self, event: &QueuedEvent) -> WrapperResult<serde_json::Value> {
+        let sealed = SealedPayload::from_event(event)
+            .ok_or_else(|| WrapperError::Encryption("payload is not sealed".to_string()))?;
+        decrypt(&self.cipher, event, &sealed)
+    }
+}
+
+/// Server-side X25519 key that opens sealed payloads
+pub struct TraceDecryptionKey {
+    secret: StaticSecret,
+}
+
+impl std::fmt::Debug for TraceDecryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraceDecryptionKey")
+            .field("public_key", &self.public_key_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TraceDecryptionKey {
+    /// Generate a new key
+    pub fn generate() -> Self {
+        Self {
+            secret: StaticSecret::random_from_rng(OsRng),
+        }
+    }
+
+    /// Load a key from its secret bytes
+    pub fn from_bytes(secret: [u8; 32]) -> Self {
+        Self {
+            secret: StaticSecret::from(secret),
+        }
+    }
+
+    /// Public key to offer at bootstrap (hex)
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(PublicKey::from(&self.secret).as_bytes())
+    }
+
+    /// Return the event with its payload decrypted
+    ///
+    /// Unsealed events are returned unchanged.
+    pub fn open(&self, event: &QueuedEvent) -> WrapperResult<QueuedEvent> {
+        let Some(sealed) = SealedPayload::from_event(event) else {
+            return Ok(event.clone());
+        };
+        if sealed.alg != ALGORITHM {
+            return Err(WrapperError::Encryption(format!("unsupported algorithm '{}'", sealed.alg)));
+        }
+        let sender = parse_public_key(&sealed.sender_key)?;
+        let shared = self.secret.diffie_hellman(&sender);
+        let cipher = derive_cipher(shared.as_bytes(), &event.session_id);
+
+        let mut opened = event.clone();
+        opened.payload = decrypt(&cipher, event, &sealed)?;
+        Ok(opened)
+    }
+}
+
+/// Check the hash chain of one session's sealed events without a key
+///
+/// Events are ordered by `sequence`, so events from the upload queue and
+/// the offline spool can be passed together. The chain must start at
+/// [`GENESIS_HASH`] with no gaps; unsealed events are rejected.
+pub fn verify_sealed_chain(events: &[QueuedEvent]) -> WrapperResult<()> {
+    let mut sealed = events.iter()
+        .map(|event| {
+            SealedPayload::from_event(event)
+                .map(|s| (s, event))
+                .ok_or_else(|| WrapperError::Encryption(format!("unsealed {} event", event.event_type)))
+        })
+        .collect::<WrapperResult<Vec<_>>>()?;
+    sealed.sort_by_key(|(s, _)| s.sequence);
+
+    let mut previous = GENESIS_HASH.to_string();
+    for (expected, (envelope, event)) in sealed.iter().enumerate() {
+        if envelope.sequence != expected as u64 {
+            return Err(WrapperError::Encryption(format!("missing sealed event {}", expected)));
+        }
+        if envelope.previous_hash != previous {
+            return Err(WrapperError::Encryption(format!("chain broken at sealed event {}", expected)));
+        }
+        if envelope.compute_hash(event) != envelope.event_hash {
+            return Err(WrapperError::Encryption(format!("hash mismatch at sealed event {}", expected)));
+        }
+        previous = envelope.event_hash.clone();
+    }
+    Ok(())
+}
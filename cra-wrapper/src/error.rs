@@ -24,6 +24,18 @@ pub enum WrapperError {
     #[error("Action denied: {0}")]
     ActionDenied(String),
 
+    /// A hook middleware blocked the input or output
+    #[error("Blocked by hook '{hook}': {reason}")]
+    HookBlocked { hook: String, reason: String },
+
+    /// A content policy could not be compiled
+    #[error("Invalid content policy: {0}")]
+    InvalidContentPolicy(String),
+
+    /// Session reached a token or cost ceiling
+    #[error("Usage limit exceeded: {0}")]
+    UsageLimitExceeded(String),
+
     /// Context not found
     #[error("Context not found: {0}")]
     ContextNotFound(String),
@@ -32,6 +44,14 @@ pub enum WrapperError {
     #[error("Transport error: {0}")]
     Transport(String),
 
+    /// Operation requires connectivity
+    #[error("Offline: {0}")]
+    Offline(String),
+
+    /// Payload encryption error
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
     /// Queue error
     #[error("Queue error: {0}")]
     Queue(String),
@@ -0,0 +1,91 @@
+//! MCP tool server adapter
+//!
+//! Governs `tools/call` requests. Denials and tool failures come back as
+//! results with `isError: true`, which is how MCP reports tool-level errors
+//! to the model; only wrapper failures are returned as `Err`.
+
+use std::fmt::Display;
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use super::{govern_tool_call, ToolCallError};
+use crate::error::WrapperResult;
+use crate::Wrapper;
+
+/// Params of an MCP `tools/call` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpToolCall {
+    /// Tool name
+    pub name: String,
+
+    /// Tool arguments
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// One content item of a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpContent {
+    /// Text content
+    Text {
+        /// The text
+        text: String,
+    },
+}
+
+/// Result of an MCP `tools/call` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolResult {
+    /// Result content
+    pub content: Vec<McpContent>,
+
+    /// Whether the tool call failed
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl McpToolResult {
+    /// Successful text result
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![McpContent::Text { text: text.into() }],
+            is_error: false,
+        }
+    }
+
+    /// Error result
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            content: vec![McpContent::Text { text: message.into() }],
+            is_error: true,
+        }
+    }
+}
+
+/// Run an MCP tool call under CRA governance
+///
+/// `f` returns the tool's JSON output, which is rendered as text content
+/// (strings as-is, anything else as pretty JSON).
+pub async fn govern_mcp_call<F, Fut, E>(
+    wrapper: &Wrapper,
+    call: McpToolCall,
+    f: F,
+) -> WrapperResult<McpToolResult>
+where
+    F: FnOnce(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value, E>>,
+    E: Display,
+{
+    match govern_tool_call(wrapper, &call.name, call.arguments, f).await {
+        Ok(serde_json::Value::String(text)) => Ok(McpToolResult::text(text)),
+        Ok(value) => Ok(McpToolResult::text(serde_json::to_string_pretty(&value)?)),
+        Err(ToolCallError::Denied(reason)) => {
+            Ok(McpToolResult::error(format!("Denied by CRA: {}", reason)))
+        }
+        Err(ToolCallError::Tool(e)) => Ok(McpToolResult::error(e.to_string())),
+        Err(ToolCallError::Wrapper(e)) => Err(e),
+    }
+}
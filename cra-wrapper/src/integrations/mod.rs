@@ -0,0 +1,95 @@
+//! Tool-call interception for agent frameworks
+//!
+//! [`govern_tool_call`] wraps a single tool invocation: it reports the call
+//! to CRA, enforces the decision, times the execution and records the result.
+//! The feature-gated adapters translate framework-specific call shapes:
+//!
+//! - `mcp`: MCP `tools/call` requests and results ([`mcp`])
+//! - `openai`: OpenAI function-calling tool calls ([`openai`])
+//!
+//! ```rust,ignore
+//! use cra_wrapper::integrations::govern_tool_call;
+//!
+//! let contents = govern_tool_call(&wrapper, "read_file", args, |args| async move {
+//!     tokio::fs::read_to_string(args["path"].as_str().unwrap_or_default()).await
+//! }).await?;
+//! ```
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Instant;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::error::WrapperError;
+use crate::hooks::ActionResult;
+use crate::Wrapper;
+
+#[cfg(feature = "mcp")]
+pub mod mcp;
+#[cfg(feature = "openai")]
+pub mod openai;
+
+/// Why a governed tool call did not produce a value
+#[derive(Error, Debug)]
+pub enum ToolCallError<E> {
+    /// CRA or a hook denied the call; the tool was not run
+    #[error("Tool call denied: {0}")]
+    Denied(String),
+
+    /// The wrapper failed before or after the call
+    #[error(transparent)]
+    Wrapper(#[from] WrapperError),
+
+    /// The tool ran and failed
+    #[error("Tool failed: {0}")]
+    Tool(E),
+}
+
+/// Run a tool call under CRA governance
+///
+/// The call is reported with [`Wrapper::report_action`]. If it is denied the
+/// tool is not run. Otherwise `f` runs with the original arguments and its
+/// outcome and duration are recorded with [`Wrapper::report_action_result`].
+pub async fn govern_tool_call<F, Fut, T, E>(
+    wrapper: &Wrapper,
+    name: &str,
+    args: serde_json::Value,
+    f: F,
+) -> Result<T, ToolCallError<E>>
+where
+    F: FnOnce(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    T: Serialize,
+    E: Display,
+{
+    let decision = wrapper.report_action(name, args.clone()).await?;
+    if !decision.allowed {
+        return Err(ToolCallError::Denied(
+            decision.reason.unwrap_or_else(|| "denied by policy".to_string()),
+        ));
+    }
+
+    let started = Instant::now();
+    let outcome = f(args.clone()).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let result = match &outcome {
+        Ok(value) => ActionResult {
+            success: true,
+            output: serde_json::to_value(value).ok(),
+            error: None,
+            duration_ms,
+        },
+        Err(e) => ActionResult {
+            success: false,
+            output: None,
+            error: Some(e.to_string()),
+            duration_ms,
+        },
+    };
+    wrapper.report_action_result(name, args, &result).await?;
+
+    outcome.map_err(ToolCallError::Tool)
+}
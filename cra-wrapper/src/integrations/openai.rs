@@ -0,0 +1,102 @@
+//! OpenAI function-calling adapter
+//!
+//! Governs the `tool_calls` of an assistant message and produces the
+//! matching `role: "tool"` messages. Denials and tool failures are returned
+//! to the model as the tool message content so it can adjust; only wrapper
+//! failures are returned as `Err`.
+
+use std::fmt::Display;
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use super::{govern_tool_call, ToolCallError};
+use crate::error::{WrapperError, WrapperResult};
+use crate::Wrapper;
+
+/// A tool call from an assistant message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Call ID, echoed in the tool message
+    pub id: String,
+
+    /// Always "function"
+    #[serde(rename = "type", default = "default_tool_type")]
+    pub call_type: String,
+
+    /// Function to call
+    pub function: FunctionCall,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
+}
+
+/// Function name and arguments of a tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// Function name
+    pub name: String,
+
+    /// Arguments as a JSON-encoded string
+    pub arguments: String,
+}
+
+/// A `role: "tool"` message answering a tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolMessage {
+    /// Always "tool"
+    pub role: String,
+
+    /// ID of the tool call this answers
+    pub tool_call_id: String,
+
+    /// Tool output, or the denial/error message
+    pub content: String,
+}
+
+impl ToolMessage {
+    fn new(tool_call_id: &str, content: String) -> Self {
+        Self {
+            role: "tool".to_string(),
+            tool_call_id: tool_call_id.to_string(),
+            content,
+        }
+    }
+}
+
+/// Run one tool call under CRA governance
+///
+/// Malformed `arguments` are reported to the model without running `f`.
+pub async fn govern_openai_tool_call<F, Fut, E>(
+    wrapper: &Wrapper,
+    call: &ToolCall,
+    f: F,
+) -> WrapperResult<ToolMessage>
+where
+    F: FnOnce(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value, E>>,
+    E: Display,
+{
+    let args = match parse_arguments(&call.function.arguments) {
+        Ok(args) => args,
+        Err(e) => return Ok(ToolMessage::new(&call.id, format!("Invalid arguments: {}", e))),
+    };
+
+    let content = match govern_tool_call(wrapper, &call.function.name, args, f).await {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(value) => serde_json::to_string(&value)?,
+        Err(ToolCallError::Denied(reason)) => format!("Denied by CRA: {}", reason),
+        Err(ToolCallError::Tool(e)) => format!("Error: {}", e),
+        Err(ToolCallError::Wrapper(e)) => return Err(e),
+    };
+    Ok(ToolMessage::new(&call.id, content))
+}
+
+/// Parse a tool call's argument string (empty means no arguments)
+pub fn parse_arguments(arguments: &str) -> WrapperResult<serde_json::Value> {
+    if arguments.trim().is_empty() {
+        return Ok(serde_json::json!({}));
+    }
+    serde_json::from_str(arguments).map_err(WrapperError::from)
+}
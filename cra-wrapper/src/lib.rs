@@ -5,6 +5,9 @@
 //! - Queues TRACE events locally for async upload
 //! - Caches context to avoid redundant fetches
 //! - Communicates with CRA server via transport backends
+//! - Falls back to a local policy while CRA is unreachable (see [`offline`])
+//! - Governs tool calls from agent frameworks (see [`integrations`])
+//! - Ships a scriptable client for testing wrappers (see [`mock`])
 //!
 //! ## Architecture
 //!
@@ -57,19 +60,33 @@
 //! ```
 
 pub mod hooks;
+pub mod content;
 pub mod queue;
 pub mod cache;
 pub mod client;
 pub mod transport;
 pub mod config;
 pub mod error;
-
-pub use config::{WrapperConfig, QueueConfig, CacheConfig};
+pub mod offline;
+pub mod integrations;
+pub mod usage;
+pub mod stream;
+pub mod mock;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+
+pub use config::{WrapperConfig, QueueConfig, CacheConfig, OfflineConfig, InjectionScanMode};
 pub use error::{WrapperError, WrapperResult};
-pub use hooks::{IOHooks, ActionDecision};
+pub use hooks::{IOHooks, ActionDecision, HookContext, HookFlow, HookMiddleware, HookStage};
+pub use content::{ContentAction, ContentClassifier, ContentDetection, ContentDetector, ContentPolicy};
 pub use queue::{TraceQueue, QueuedEvent};
-pub use cache::{ContextCache, CachedContext};
+pub use cache::{ContextCache, CachedContext, CacheLookup};
 pub use client::CRAClient;
+pub use transport::CORRELATION_HEADER;
+pub use offline::{DiskQueue, OfflineDecision, OfflinePolicy, ReconciliationReport};
+pub use usage::{TokenUsage, UsageLimits, UsageTotals};
+pub use stream::{OutputStream, StreamedOutput};
+pub use mock::{MockCRAClient, MockCall, MockMethod};
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -95,6 +112,15 @@ pub struct Wrapper {
 
     /// CRA client
     client: Arc<dyn client::CRAClient + Send + Sync>,
+
+    /// Buffer for events recorded while offline
+    spool: Arc<offline::DiskQueue>,
+
+    /// When offline mode started (`None` while online)
+    offline_since: Arc<RwLock<Option<DateTime<Utc>>>>,
+
+    /// Correlation ID stamped into emitted events
+    correlation_id: Arc<RwLock<Option<String>>>,
 }
 
 impl Wrapper {
@@ -103,14 +129,19 @@ impl Wrapper {
         let queue = Arc::new(queue::TraceQueue::new(config.queue.clone()));
         let cache = Arc::new(cache::ContextCache::new(config.cache.clone()));
         let client = Arc::new(client::DirectClient::new());
+        let spool = Arc::new(offline::DiskQueue::new(config.offline.spool_path.clone()));
+        let hooks = Arc::new(hooks::HookRegistry::from_config(&config.hooks));
 
         Self {
             config,
             session: Arc::new(RwLock::new(None)),
-            hooks: Arc::new(hooks::HookRegistry::new()),
+            hooks,
             queue,
             cache,
             client,
+            spool,
+            offline_since: Arc::new(RwLock::new(None)),
+            correlation_id: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -121,21 +152,32 @@ impl Wrapper {
     ) -> Self {
         let queue = Arc::new(queue::TraceQueue::new(config.queue.clone()));
         let cache = Arc::new(cache::ContextCache::new(config.cache.clone()));
+        let spool = Arc::new(offline::DiskQueue::new(config.offline.spool_path.clone()));
+        let hooks = Arc::new(hooks::HookRegistry::from_config(&config.hooks));
 
         Self {
             config,
             session: Arc::new(RwLock::new(None)),
-            hooks: Arc::new(hooks::HookRegistry::new()),
+            hooks,
             queue,
             cache,
             client: Arc::new(client),
+            spool,
+            offline_since: Arc::new(RwLock::new(None)),
+            correlation_id: Arc::new(RwLock::new(None)),
         }
     }
 
     /// Start a governed session
     pub async fn start_session(&self, goal: &str) -> WrapperResult<String> {
         // Bootstrap with CRA
-        let bootstrap_result = self.client.bootstrap(goal).await?;
+        let request = client::BootstrapRequest::new(self.config.agent_id.clone(), goal);
+        let bootstrap_result = self.client.bootstrap(&request).await?;
+        bootstrap_result
+            .check_version()
+            .map_err(|e| WrapperError::BootstrapFailed(e.to_string()))?;
+        let trace_key = self.negotiate_trace_key(&bootstrap_result).await?;
+        self.hooks.register_content_policies(bootstrap_result.content_policies.clone())?;
 
         // Create session
         let session = WrapperSession {
@@ -145,20 +187,21 @@ impl Wrapper {
             genesis_hash: bootstrap_result.genesis_hash.clone(),
             current_hash: bootstrap_result.current_hash.clone(),
             event_count: 1,
-            contexts_received: bootstrap_result.context_ids.clone(),
+            contexts_received: bootstrap_result.context_ids(),
+            usage: UsageTotals::default(),
+            usage_limits: UsageLimits::from_constraints(&bootstrap_result.constraints),
         };
 
         // Cache initial contexts
         for ctx in &bootstrap_result.contexts {
             self.cache.set(
                 &ctx.context_id,
-                CachedContext {
-                    context_id: ctx.context_id.clone(),
-                    content: ctx.content.clone(),
-                    fetched_at: Utc::now(),
-                    expires_at: Utc::now() + chrono::Duration::hours(1),
-                    priority: ctx.priority,
-                },
+                CachedContext::new(
+                    &ctx.context_id,
+                    &ctx.content,
+                    ctx.priority,
+                    self.cache.ttl_for(ctx.ttl_seconds),
+                ),
             ).await;
         }
 
@@ -166,15 +209,17 @@ impl Wrapper {
         *self.session.write().await = Some(session);
 
         // Emit session started event
-        self.queue.enqueue(QueuedEvent {
+        self.emit(QueuedEvent {
             event_type: "wrapper.session_started".to_string(),
             session_id: bootstrap_result.session_id.clone(),
             timestamp: Utc::now(),
             payload: serde_json::json!({
                 "goal": goal,
-                "genesis_hash": bootstrap_result.genesis_hash
+                "genesis_hash": bootstrap_result.genesis_hash,
+                "trace_encryption_key": trace_key,
+                "content_policies": self.hooks.content_policy_ids()
             }),
-        }).await;
+        }).await?;
 
         Ok(bootstrap_result.session_id)
     }
@@ -186,6 +231,12 @@ impl Wrapper {
             .ok_or(WrapperError::NoActiveSession)?
             .clone();
 
+        if self.is_offline().await {
+            return Err(WrapperError::Offline(
+                "reconnect before ending the session".to_string(),
+            ));
+        }
+
         // Flush trace queue
         self.queue.flush().await?;
 
@@ -194,6 +245,8 @@ impl Wrapper {
 
         // Clear session
         *self.session.write().await = None;
+        #[cfg(feature = "encryption")]
+        self.queue.set_cipher(None).await;
 
         Ok(SessionSummary {
             session_id: session.session_id,
@@ -201,6 +254,7 @@ impl Wrapper {
             event_count: session.event_count,
             chain_verified: result.chain_verified,
             final_hash: result.final_hash,
+            usage: session.usage,
         })
     }
 
@@ -212,12 +266,17 @@ impl Wrapper {
             .clone();
 
         // Run through input hooks
-        let processed = input.to_string();
-        let mut injected_context = Vec::new();
+        let outcome = self.run_hooks(
+            self.config.hooks.intercept_input,
+            hooks::HookContext::input(&session.session_id, input),
+        ).await?;
+        let hook_ctx = self.check_blocked(outcome).await?;
+        let processed = hook_ctx.text;
+        let mut injected_context = hook_ctx.injected_context;
 
         // Check for checkpoint triggers (keyword matching)
-        if self.config.checkpoints_enabled {
-            let keywords = self.hooks.check_keywords(&processed);
+        if self.config.checkpoints_enabled && !self.is_offline().await {
+            let keywords = hook_ctx.matched_keywords;
             if !keywords.is_empty() {
                 // Request context for matched keywords
                 let contexts = self.client.request_context(
@@ -228,27 +287,29 @@ impl Wrapper {
 
                 for ctx in contexts {
                     injected_context.push(ctx.content.clone());
-                    self.cache.set(&ctx.context_id, CachedContext {
-                        context_id: ctx.context_id.clone(),
-                        content: ctx.content,
-                        fetched_at: Utc::now(),
-                        expires_at: Utc::now() + chrono::Duration::hours(1),
-                        priority: ctx.priority,
-                    }).await;
+                    self.cache.set(&ctx.context_id, CachedContext::new(
+                        &ctx.context_id,
+                        &ctx.content,
+                        ctx.priority,
+                        self.cache.ttl_for(ctx.ttl_seconds),
+                    )).await;
                 }
             }
         }
 
         // Emit input event
-        self.queue.enqueue(QueuedEvent {
+        self.emit(QueuedEvent {
             event_type: "wrapper.input_received".to_string(),
             session_id: session.session_id.clone(),
             timestamp: Utc::now(),
             payload: serde_json::json!({
                 "input_length": input.len(),
-                "context_injected": !injected_context.is_empty()
+                "context_injected": !injected_context.is_empty(),
+                "redactions": hook_ctx.redactions,
+                "findings": hook_ctx.findings,
+                "content_detections": hook_ctx.content_detections
             }),
-        }).await;
+        }).await?;
 
         Ok(ProcessedInput {
             original: input.to_string(),
@@ -264,22 +325,89 @@ impl Wrapper {
             .ok_or(WrapperError::NoActiveSession)?
             .clone();
 
+        // Run through output hooks
+        let outcome = self.run_hooks(
+            self.config.hooks.intercept_output,
+            hooks::HookContext::output(&session.session_id, output),
+        ).await?;
+        let hook_ctx = self.check_blocked(outcome).await?;
+
         // Emit output event
-        self.queue.enqueue(QueuedEvent {
+        self.emit(QueuedEvent {
             event_type: "wrapper.output_produced".to_string(),
             session_id: session.session_id.clone(),
             timestamp: Utc::now(),
             payload: serde_json::json!({
-                "output_length": output.len()
+                "output_length": output.len(),
+                "redactions": hook_ctx.redactions,
+                "content_detections": hook_ctx.content_detections
             }),
-        }).await;
+        }).await?;
 
         Ok(ProcessedOutput {
             original: output.to_string(),
-            processed: output.to_string(),
+            processed: hook_ctx.text,
+        })
+    }
+
+    /// Start processing a streamed output
+    pub async fn output_stream(&self) -> WrapperResult<OutputStream> {
+        let session = self.session.read().await;
+        let session = session.as_ref().ok_or(WrapperError::NoActiveSession)?;
+        Ok(OutputStream::new(&session.session_id))
+    }
+
+    /// Process one chunk of a streamed output
+    ///
+    /// Returns the processed text that is safe to emit now, which may be
+    /// empty while a token is still incomplete.
+    pub async fn push_output_chunk(&self, stream: &mut OutputStream, chunk: &str) -> WrapperResult<String> {
+        let ready = stream.take_ready(chunk);
+        self.process_output_segment(stream, &ready).await
+    }
+
+    /// Finish a streamed output and record it
+    pub async fn finish_output_stream(&self, mut stream: OutputStream) -> WrapperResult<StreamedOutput> {
+        let rest = std::mem::take(&mut stream.pending);
+        let tail = self.process_output_segment(&mut stream, &rest).await?;
+
+        self.emit(QueuedEvent {
+            event_type: "wrapper.output_produced".to_string(),
+            session_id: stream.session_id.clone(),
+            timestamp: Utc::now(),
+            payload: serde_json::json!({
+                "output_length": stream.original.len(),
+                "redactions": stream.redactions,
+                "chunks": stream.chunks,
+                "content_detections": stream.content_detections
+            }),
+        }).await?;
+
+        Ok(StreamedOutput {
+            tail,
+            original: stream.original,
+            processed: stream.processed,
+            chunks: stream.chunks,
+            redactions: stream.redactions,
+            content_detections: stream.content_detections,
         })
     }
 
+    async fn process_output_segment(&self, stream: &mut OutputStream, text: &str) -> WrapperResult<String> {
+        if text.is_empty() {
+            return Ok(String::new());
+        }
+        let outcome = self.run_hooks(
+            self.config.hooks.intercept_output,
+            hooks::HookContext::output(&stream.session_id, text),
+        ).await?;
+        let hook_ctx = self.check_blocked(outcome).await?;
+        stream.redactions += hook_ctx.redactions;
+        stream.content_detections.extend(hook_ctx.content_detections);
+        stream.processed.push_str(&hook_ctx.text);
+        Ok(hook_ctx.text)
+    }
+
     /// Report an action before execution
     pub async fn report_action(
         &self,
@@ -291,15 +419,51 @@ impl Wrapper {
             .ok_or(WrapperError::NoActiveSession)?
             .clone();
 
+        if let Some(reason) = session.usage_limits.exceeded(&session.usage) {
+            return Ok(ActionDecision::deny(&format!("Usage limit: {}", reason)));
+        }
+
+        // Run through action hooks; a block short-circuits before CRA sees it
+        let outcome = self.run_hooks(
+            self.config.hooks.intercept_actions,
+            hooks::HookContext::action(&session.session_id, action, params),
+        ).await?;
+        if let Some(block) = &outcome.blocked {
+            self.emit_hook_block(&session.session_id, &outcome.context, block).await?;
+            return Ok(ActionDecision::deny(&format!(
+                "Blocked by hook '{}': {}", block.hook, block.reason
+            )));
+        }
+        let hook_ctx = outcome.context;
+        let params = hook_ctx.params.unwrap_or_default();
+        let hook_context = (!hook_ctx.injected_context.is_empty())
+            .then(|| hook_ctx.injected_context.join("\n\n"));
+
+        if self.is_offline().await {
+            let mut decision = self.decide_offline(&session.session_id, action, params).await?;
+            decision.injected_context = hook_context;
+            return Ok(decision);
+        }
+
         // Report to CRA and get decision
-        let report = self.client.report_action(
+        let report = match self.client.report_action(
             &session.session_id,
             action,
             params.clone(),
-        ).await?;
+        ).await {
+            Ok(report) => report,
+            Err(WrapperError::Transport(e)) if self.config.offline.enabled => {
+                tracing::warn!("CRA unreachable, switching to offline mode: {}", e);
+                self.go_offline().await;
+                let mut decision = self.decide_offline(&session.session_id, action, params).await?;
+                decision.injected_context = hook_context;
+                return Ok(decision);
+            }
+            Err(e) => return Err(e),
+        };
 
         // Emit action event
-        self.queue.enqueue(QueuedEvent {
+        self.emit(QueuedEvent {
             event_type: "wrapper.action_reported".to_string(),
             session_id: session.session_id.clone(),
             timestamp: Utc::now(),
@@ -307,12 +471,12 @@ impl Wrapper {
                 "action": action,
                 "decision": report.decision
             }),
-        }).await;
+        }).await?;
 
         Ok(ActionDecision {
             allowed: report.decision == "approved",
             reason: report.reason,
-            injected_context: None,
+            injected_context: hook_context,
         })
     }
 
@@ -336,7 +500,7 @@ impl Wrapper {
         ).await?;
 
         // Emit feedback event
-        self.queue.enqueue(QueuedEvent {
+        self.emit(QueuedEvent {
             event_type: "wrapper.feedback_submitted".to_string(),
             session_id: session.session_id.clone(),
             timestamp: Utc::now(),
@@ -344,7 +508,7 @@ impl Wrapper {
                 "context_id": context_id,
                 "helpful": helpful
             }),
-        }).await;
+        }).await?;
 
         Ok(())
     }
@@ -360,9 +524,6 @@ impl Wrapper {
             .ok_or(WrapperError::NoActiveSession)?
             .clone();
 
-        // Check cache first
-        // ... (cache lookup logic)
-
         // Request from CRA
         let contexts = self.client.request_context(
             &session.session_id,
@@ -372,18 +533,370 @@ impl Wrapper {
 
         // Cache results
         for ctx in &contexts {
-            self.cache.set(&ctx.context_id, CachedContext {
-                context_id: ctx.context_id.clone(),
-                content: ctx.content.clone(),
-                fetched_at: Utc::now(),
-                expires_at: Utc::now() + chrono::Duration::hours(1),
-                priority: ctx.priority,
-            }).await;
+            self.cache.set(&ctx.context_id, CachedContext::new(
+                &ctx.context_id,
+                &ctx.content,
+                ctx.priority,
+                self.cache.ttl_for(ctx.ttl_seconds),
+            )).await;
         }
 
         Ok(contexts)
     }
 
+    /// Get a previously received context from the cache
+    ///
+    /// Inside the stale-while-revalidate window the stale content is
+    /// returned immediately and a background refresh is started; while
+    /// offline, stale content is returned without a refresh.
+    pub async fn cached_context(&self, context_id: &str) -> WrapperResult<Option<CachedContext>> {
+        let lookup = self.cache.lookup(context_id).await;
+        let context = lookup.context().cloned();
+
+        if lookup.needs_revalidation() {
+            let session = self.session.read().await.clone();
+            match session {
+                Some(session) if !self.is_offline().await => {
+                    let cache = self.cache.clone();
+                    let client = self.client.clone();
+                    let context_id = context_id.to_string();
+                    tokio::spawn(async move {
+                        refresh_context(cache, client, session.session_id, context_id).await;
+                    });
+                }
+                _ => self.cache.revalidation_failed(context_id).await,
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// Switch to offline mode
+    ///
+    /// Actions are decided by the configured [`OfflinePolicy`] and events
+    /// buffer to the spool until [`Wrapper::reconnect`] succeeds.
+    pub async fn go_offline(&self) {
+        let mut offline_since = self.offline_since.write().await;
+        if offline_since.is_none() {
+            *offline_since = Some(Utc::now());
+        }
+    }
+
+    /// Whether the wrapper is in offline mode
+    pub async fn is_offline(&self) -> bool {
+        self.offline_since.read().await.is_some()
+    }
+
+    /// Number of events buffered while offline
+    pub async fn offline_pending(&self) -> WrapperResult<usize> {
+        self.spool.len().await
+    }
+
+    /// Upload the offline segment and reconcile offline decisions
+    ///
+    /// Buffered events are uploaded, then every offline decision is replayed
+    /// on the server. Local approvals the server would have denied come back
+    /// as conflicts. On failure the wrapper stays offline and keeps the
+    /// buffer. Also recovers a spool left behind by a previous process.
+    pub async fn reconnect(&self) -> WrapperResult<ReconciliationReport> {
+        let offline_since = *self.offline_since.read().await;
+        let events = self.spool.read_all().await?;
+
+        let mut uploaded_count = 0;
+        if !events.is_empty() {
+            let payload = events.iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()?;
+            let upload = self.client.upload_trace(payload).await?;
+            if !upload.success {
+                return Err(WrapperError::Transport("offline segment upload failed".to_string()));
+            }
+            uploaded_count = upload.uploaded_count;
+        }
+
+        let decision_events = events.iter()
+            .filter(|e| e.event_type == OFFLINE_DECISION_EVENT)
+            .collect::<Vec<_>>();
+        let mut decisions = Vec::with_capacity(decision_events.len());
+        for event in decision_events {
+            let payload = self.queue.open_payload(event).await?;
+            decisions.push(serde_json::from_value::<OfflineDecision>(payload)?);
+        }
+        let reports = if decisions.is_empty() {
+            Vec::new()
+        } else {
+            self.client.reconcile(&decisions).await?
+        };
+
+        let conflicts = decisions.iter()
+            .zip(reports)
+            .filter(|(decision, report)| decision.allowed && report.decision != "approved")
+            .map(|(decision, report)| offline::ReconciliationConflict {
+                decision: decision.clone(),
+                server_decision: report.decision,
+                server_reason: report.reason,
+            })
+            .collect::<Vec<_>>();
+
+        self.spool.clear().await?;
+        *self.offline_since.write().await = None;
+
+        let report = ReconciliationReport {
+            uploaded_count,
+            decisions_checked: decisions.len(),
+            conflicts,
+            offline_since,
+            reconciled_at: Utc::now(),
+        };
+
+        if let Some(session) = self.session.read().await.clone() {
+            self.emit(QueuedEvent {
+                event_type: "wrapper.reconciled".to_string(),
+                session_id: session.session_id,
+                timestamp: Utc::now(),
+                payload: serde_json::json!({
+                    "uploaded_count": report.uploaded_count,
+                    "decisions_checked": report.decisions_checked,
+                    "conflict_count": report.conflicts.len()
+                }),
+            }).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Report model usage for one turn
+    ///
+    /// Adds the turn to the session totals and emits `wrapper.usage`. If the
+    /// turn takes the session to a ceiling from its `budget_limit`
+    /// constraints, `wrapper.usage_limit_reached` is emitted and
+    /// [`WrapperError::UsageLimitExceeded`] returned; the usage is still
+    /// recorded and later actions are denied.
+    pub async fn report_usage(&self, usage: TokenUsage) -> WrapperResult<UsageTotals> {
+        let (session_id, totals, limits, was_exceeded) = {
+            let mut guard = self.session.write().await;
+            let session = guard.as_mut().ok_or(WrapperError::NoActiveSession)?;
+            let was_exceeded = session.usage_limits.exceeded(&session.usage).is_some();
+            session.usage.record(&usage);
+            (
+                session.session_id.clone(),
+                session.usage.clone(),
+                session.usage_limits.clone(),
+                was_exceeded,
+            )
+        };
+
+        self.emit(QueuedEvent {
+            event_type: "wrapper.usage".to_string(),
+            session_id: session_id.clone(),
+            timestamp: Utc::now(),
+            payload: serde_json::json!({
+                "model": usage.model,
+                "provider": usage.provider,
+                "input_tokens": usage.input_tokens,
+                "output_tokens": usage.output_tokens,
+                "cost_usd": usage.cost_usd,
+                "session_tokens": totals.total.total_tokens(),
+                "session_cost_usd": totals.total.cost_usd
+            }),
+        }).await?;
+
+        match limits.exceeded(&totals) {
+            Some(reason) => {
+                if !was_exceeded {
+                    self.emit(QueuedEvent {
+                        event_type: "wrapper.usage_limit_reached".to_string(),
+                        session_id,
+                        timestamp: Utc::now(),
+                        payload: serde_json::json!({
+                            "reason": reason,
+                            "limits": limits,
+                            "usage": totals.total
+                        }),
+                    }).await?;
+                }
+                Err(WrapperError::UsageLimitExceeded(reason))
+            }
+            None => Ok(totals),
+        }
+    }
+
+    /// Record the outcome of an executed action
+    ///
+    /// Runs the action-stage `after_action` hooks and emits
+    /// `wrapper.action_completed`.
+    pub async fn report_action_result(
+        &self,
+        action: &str,
+        params: serde_json::Value,
+        result: &hooks::ActionResult,
+    ) -> WrapperResult<()> {
+        let session = self.session.read().await
+            .as_ref()
+            .ok_or(WrapperError::NoActiveSession)?
+            .clone();
+
+        if self.config.hooks.intercept_actions {
+            let ctx = hooks::HookContext::action(&session.session_id, action, params);
+            self.hooks.run_after_action(&ctx, result).await;
+        }
+
+        self.emit(QueuedEvent {
+            event_type: "wrapper.action_completed".to_string(),
+            session_id: session.session_id.clone(),
+            timestamp: Utc::now(),
+            payload: serde_json::json!({
+                "action": action,
+                "success": result.success,
+                "duration_ms": result.duration_ms,
+                "error": result.error
+            }),
+        }).await
+    }
+
+    /// Hook registry, for registering middlewares at runtime
+    pub fn hooks(&self) -> &hooks::HookRegistry {
+        &self.hooks
+    }
+
+    /// Run the hook chain, or pass the context through when the stage is off
+    async fn run_hooks(&self, enabled: bool, ctx: hooks::HookContext) -> WrapperResult<hooks::HookOutcome> {
+        if enabled {
+            self.hooks.run(ctx).await
+        } else {
+            Ok(hooks::HookOutcome { context: ctx, blocked: None })
+        }
+    }
+
+    /// Unwrap a hook outcome, recording and returning an error if blocked
+    async fn check_blocked(&self, outcome: hooks::HookOutcome) -> WrapperResult<hooks::HookContext> {
+        match outcome.blocked {
+            Some(block) => {
+                self.emit_hook_block(&outcome.context.session_id, &outcome.context, &block).await?;
+                Err(WrapperError::HookBlocked {
+                    hook: block.hook,
+                    reason: block.reason,
+                })
+            }
+            None => Ok(outcome.context),
+        }
+    }
+
+    async fn emit_hook_block(
+        &self,
+        session_id: &str,
+        ctx: &hooks::HookContext,
+        block: &hooks::HookBlock,
+    ) -> WrapperResult<()> {
+        self.emit(QueuedEvent {
+            event_type: "wrapper.hook_blocked".to_string(),
+            session_id: session_id.to_string(),
+            timestamp: Utc::now(),
+            payload: serde_json::json!({
+                "stage": ctx.stage,
+                "action": ctx.action,
+                "hook": block.hook,
+                "reason": block.reason,
+                "findings": ctx.findings,
+                "content_detections": ctx.content_detections
+            }),
+        }).await
+    }
+
+    /// Decide an action with the offline policy and record the decision
+    async fn decide_offline(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> WrapperResult<ActionDecision> {
+        let local = self.config.offline.policy.decide(action);
+        let decision = OfflineDecision {
+            session_id: session_id.to_string(),
+            action: action.to_string(),
+            params,
+            allowed: local.allowed,
+            reason: local.reason(),
+            decided_at: Utc::now(),
+        };
+
+        self.emit(QueuedEvent {
+            event_type: OFFLINE_DECISION_EVENT.to_string(),
+            session_id: session_id.to_string(),
+            timestamp: decision.decided_at,
+            payload: serde_json::to_value(&decision)?,
+        }).await?;
+
+        Ok(ActionDecision {
+            allowed: decision.allowed,
+            reason: Some(decision.reason),
+            injected_context: None,
+        })
+    }
+
+    /// Set up payload sealing for a new session
+    ///
+    /// Returns the wrapper's session public key when payloads are sealed.
+    async fn negotiate_trace_key(&self, bootstrap: &client::BootstrapResult) -> WrapperResult<Option<String>> {
+        if !self.config.queue.encrypt_payloads {
+            return Ok(None);
+        }
+
+        #[cfg(feature = "encryption")]
+        {
+            let server_key = bootstrap.trace_encryption_key.as_deref().ok_or_else(|| {
+                WrapperError::BootstrapFailed(
+                    "payload encryption required but the server offered no key".to_string(),
+                )
+            })?;
+            let cipher = encryption::SessionCipher::negotiate(&bootstrap.session_id, server_key)?;
+            let public_key = cipher.public_key_hex();
+            self.queue.set_cipher(Some(Arc::new(cipher))).await;
+            Ok(Some(public_key))
+        }
+
+        #[cfg(not(feature = "encryption"))]
+        {
+            let _ = bootstrap;
+            Err(WrapperError::BootstrapFailed(
+                "payload encryption requires the `encryption` feature".to_string(),
+            ))
+        }
+    }
+
+    /// Stamp a correlation ID into every event emitted from now on
+    ///
+    /// Use the ID the caller sent in [`CORRELATION_HEADER`] and forward it
+    /// on outgoing requests, so the wrapper's events can be matched with
+    /// the server's. Pass `None` to stop stamping.
+    pub async fn set_correlation_id(&self, correlation_id: Option<String>) {
+        *self.correlation_id.write().await = correlation_id;
+    }
+
+    /// The correlation ID currently stamped into events, if any
+    pub async fn correlation_id(&self) -> Option<String> {
+        self.correlation_id.read().await.clone()
+    }
+
+    /// Queue an event, or buffer it to the spool while offline
+    async fn emit(&self, mut event: QueuedEvent) -> WrapperResult<()> {
+        if let (Some(id), Some(payload)) = (
+            self.correlation_id.read().await.clone(),
+            event.payload.as_object_mut(),
+        ) {
+            payload
+                .entry("correlation_id")
+                .or_insert_with(|| serde_json::Value::String(id));
+        }
+
+        if self.is_offline().await {
+            self.spool.push(&self.queue.seal(event).await?).await
+        } else {
+            self.queue.enqueue(event).await;
+            Ok(())
+        }
+    }
+
     /// Get current session info
     pub async fn current_session(&self) -> Option<WrapperSession> {
         self.session.read().await.clone()
@@ -394,12 +907,47 @@ impl Wrapper {
         self.queue.stats().await
     }
 
+    /// Events waiting for upload (payloads sealed if encryption is on)
+    pub async fn pending_events(&self) -> Vec<QueuedEvent> {
+        self.queue.pending().await
+    }
+
     /// Get cache statistics
     pub async fn cache_stats(&self) -> cache::CacheStats {
         self.cache.stats().await
     }
 }
 
+/// Refresh one cached context from CRA
+async fn refresh_context(
+    cache: Arc<cache::ContextCache>,
+    client: Arc<dyn client::CRAClient + Send + Sync>,
+    session_id: String,
+    context_id: String,
+) {
+    let need = format!("Refresh context {}", context_id);
+    let refreshed = client.request_context(&session_id, &need, Some(vec![context_id.clone()])).await;
+
+    match refreshed.map(|blocks| blocks.into_iter().find(|b| b.context_id == context_id)) {
+        Ok(Some(block)) => {
+            cache.set(&context_id, CachedContext::new(
+                &block.context_id,
+                &block.content,
+                block.priority,
+                cache.ttl_for(block.ttl_seconds),
+            )).await;
+        }
+        Ok(None) => cache.revalidation_failed(&context_id).await,
+        Err(e) => {
+            tracing::debug!("Context refresh for {} failed: {}", context_id, e);
+            cache.revalidation_failed(&context_id).await;
+        }
+    }
+}
+
+/// Event type recording an action decided while offline
+pub const OFFLINE_DECISION_EVENT: &str = "wrapper.offline_decision";
+
 /// Wrapper session state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WrapperSession {
@@ -410,6 +958,12 @@ pub struct WrapperSession {
     pub current_hash: String,
     pub event_count: u64,
     pub contexts_received: Vec<String>,
+    /// Model usage reported so far
+    #[serde(default)]
+    pub usage: UsageTotals,
+    /// Usage ceilings from `budget_limit` constraints
+    #[serde(default, skip_serializing_if = "UsageLimits::is_unlimited")]
+    pub usage_limits: UsageLimits,
 }
 
 /// Session summary after ending
@@ -420,6 +974,9 @@ pub struct SessionSummary {
     pub event_count: u64,
     pub chain_verified: bool,
     pub final_hash: String,
+    /// Model usage for the whole session
+    #[serde(default)]
+    pub usage: UsageTotals,
 }
 
 /// Processed input from on_input
@@ -443,4 +1000,7 @@ pub struct ContextBlock {
     pub context_id: String,
     pub content: String,
     pub priority: i32,
+    /// Server-assigned cache lifetime (wrapper default if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
 }
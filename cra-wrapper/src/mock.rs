@@ -0,0 +1,344 @@
+//! Programmable CRA client for tests
+//!
+//! [`MockCRAClient`] stands in for a CRA server when unit-testing code built
+//! on the wrapper. It answers from a script and records every call:
+//!
+//! ```rust,ignore
+//! use cra_wrapper::{MockCRAClient, MockMethod, Wrapper, WrapperConfig};
+//!
+//! let client = MockCRAClient::new()
+//!     .with_denial("delete_*", "deletes need approval")
+//!     .with_failure(MockMethod::UploadTrace, 1, "connection reset");
+//! let wrapper = Wrapper::with_client(WrapperConfig::default(), client.clone());
+//!
+//! wrapper.start_session("Tidy the repo").await?;
+//! assert!(wrapper.report_action("delete_branch", json!({})).await.is_err());
+//! assert_eq!(client.calls_to(MockMethod::ReportAction).len(), 1);
+//! ```
+//!
+//! Clones share their script and call log, so keep one to assert on after
+//! handing the other to the wrapper.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::client::{
+    ActionReport, BootstrapRequest, BootstrapResult, CRAClient, DirectClient, EndSessionResult, UploadResult,
+};
+use crate::error::{WrapperError, WrapperResult};
+use crate::offline::pattern_matches;
+use crate::ContextBlock;
+
+/// A [`CRAClient`] method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockMethod {
+    Bootstrap,
+    RequestContext,
+    ReportAction,
+    Feedback,
+    UploadTrace,
+    EndSession,
+}
+
+/// A call received by a [`MockCRAClient`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum MockCall {
+    Bootstrap {
+        goal: String,
+    },
+    RequestContext {
+        session_id: String,
+        need: String,
+        hints: Option<Vec<String>>,
+    },
+    ReportAction {
+        session_id: String,
+        action: String,
+        params: serde_json::Value,
+    },
+    Feedback {
+        session_id: String,
+        context_id: String,
+        helpful: bool,
+        reason: Option<String>,
+    },
+    UploadTrace {
+        events: Vec<serde_json::Value>,
+    },
+    EndSession {
+        session_id: String,
+        summary: Option<String>,
+    },
+}
+
+impl MockCall {
+    /// The method that was called
+    pub fn method(&self) -> MockMethod {
+        match self {
+            MockCall::Bootstrap { .. } => MockMethod::Bootstrap,
+            MockCall::RequestContext { .. } => MockMethod::RequestContext,
+            MockCall::ReportAction { .. } => MockMethod::ReportAction,
+            MockCall::Feedback { .. } => MockMethod::Feedback,
+            MockCall::UploadTrace { .. } => MockMethod::UploadTrace,
+            MockCall::EndSession { .. } => MockMethod::EndSession,
+        }
+    }
+}
+
+/// Scripted answer for actions matching a pattern
+#[derive(Debug, Clone)]
+struct DecisionRule {
+    pattern: String,
+    approved: bool,
+    reason: Option<String>,
+}
+
+/// Injected transport failures for one method
+#[derive(Debug, Clone)]
+struct Failure {
+    method: MockMethod,
+    remaining: usize,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    bootstraps: VecDeque<BootstrapResult>,
+    contexts: Vec<ContextBlock>,
+    decisions: Vec<DecisionRule>,
+    failures: Vec<Failure>,
+    down: Option<String>,
+    calls: Vec<MockCall>,
+}
+
+/// CRA client answering from a script, for tests
+///
+/// Unscripted calls behave like [`DirectClient`]: fresh sessions, no
+/// contexts, every action approved.
+#[derive(Debug, Clone, Default)]
+pub struct MockCRAClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockCRAClient {
+    /// Create a client with an empty script
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a bootstrap result; each `bootstrap` call takes the next one
+    pub fn with_bootstrap(self, result: BootstrapResult) -> Self {
+        self.state().bootstraps.push_back(result);
+        self
+    }
+
+    /// Return this context from every `request_context` call
+    pub fn with_context(self, context: ContextBlock) -> Self {
+        self.state().contexts.push(context);
+        self
+    }
+
+    /// Approve actions matching `pattern` (exact, or a prefix ending in `*`)
+    ///
+    /// Rules are checked in the order they were added; the first match wins.
+    pub fn with_approval(self, pattern: &str) -> Self {
+        self.push_rule(pattern, true, None)
+    }
+
+    /// Deny actions matching `pattern` with `reason`
+    pub fn with_denial(self, pattern: &str, reason: &str) -> Self {
+        self.push_rule(pattern, false, Some(reason.to_string()))
+    }
+
+    /// Fail the next `times` calls to `method` with a transport error
+    pub fn with_failure(self, method: MockMethod, times: usize, message: &str) -> Self {
+        self.fail(method, times, message);
+        self
+    }
+
+    /// Fail the next `times` calls to `method` with a transport error
+    pub fn fail(&self, method: MockMethod, times: usize, message: &str) {
+        self.state().failures.push(Failure {
+            method,
+            remaining: times,
+            message: message.to_string(),
+        });
+    }
+
+    /// Fail every call with a transport error until `None` is set
+    pub fn set_down(&self, message: Option<&str>) {
+        self.state().down = message.map(String::from);
+    }
+
+    /// Every call received, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state().calls.clone()
+    }
+
+    /// Calls received by one method, oldest first
+    pub fn calls_to(&self, method: MockMethod) -> Vec<MockCall> {
+        self.state()
+            .calls
+            .iter()
+            .filter(|c| c.method() == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Actions reported, in order
+    pub fn reported_actions(&self) -> Vec<String> {
+        self.state()
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                MockCall::ReportAction { action, .. } => Some(action.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every TRACE event uploaded, in order
+    pub fn uploaded_events(&self) -> Vec<serde_json::Value> {
+        self.state()
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                MockCall::UploadTrace { events } => Some(events.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Forget recorded calls, keeping the script
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    fn push_rule(self, pattern: &str, approved: bool, reason: Option<String>) -> Self {
+        self.state().decisions.push(DecisionRule {
+            pattern: pattern.to_string(),
+            approved,
+            reason,
+        });
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A panicking test thread must not hide the calls from the others
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a call, then apply any injected failure
+    fn record(&self, call: MockCall) -> WrapperResult<()> {
+        let method = call.method();
+        let mut state = self.state();
+        state.calls.push(call);
+
+        if let Some(message) = &state.down {
+            return Err(WrapperError::Transport(message.clone()));
+        }
+        if let Some(failure) = state
+            .failures
+            .iter_mut()
+            .find(|f| f.method == method && f.remaining > 0)
+        {
+            failure.remaining -= 1;
+            return Err(WrapperError::Transport(failure.message.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CRAClient for MockCRAClient {
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
+        self.record(MockCall::Bootstrap { goal: request.intent.clone() })?;
+        let scripted = self.state().bootstraps.pop_front();
+        match scripted {
+            Some(result) => Ok(result),
+            None => DirectClient::new().bootstrap(request).await,
+        }
+    }
+
+    async fn request_context(
+        &self,
+        session_id: &str,
+        need: &str,
+        hints: Option<Vec<String>>,
+    ) -> WrapperResult<Vec<ContextBlock>> {
+        self.record(MockCall::RequestContext {
+            session_id: session_id.to_string(),
+            need: need.to_string(),
+            hints,
+        })?;
+        Ok(self.state().contexts.clone())
+    }
+
+    async fn report_action(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> WrapperResult<ActionReport> {
+        self.record(MockCall::ReportAction {
+            session_id: session_id.to_string(),
+            action: action.to_string(),
+            params,
+        })?;
+        let rule = self
+            .state()
+            .decisions
+            .iter()
+            .find(|r| pattern_matches(&r.pattern, action))
+            .cloned();
+
+        let (approved, reason, note) = match rule {
+            Some(rule) => (rule.approved, rule.reason, format!("Matched mock rule '{}'", rule.pattern)),
+            None => (true, None, "Action permitted (mock default)".to_string()),
+        };
+        Ok(ActionReport {
+            decision: if approved { "approved" } else { "denied" }.to_string(),
+            trace_id: uuid::Uuid::new_v4().to_string(),
+            reason,
+            policy_notes: vec![note],
+        })
+    }
+
+    async fn feedback(
+        &self,
+        session_id: &str,
+        context_id: &str,
+        helpful: bool,
+        reason: Option<&str>,
+    ) -> WrapperResult<()> {
+        self.record(MockCall::Feedback {
+            session_id: session_id.to_string(),
+            context_id: context_id.to_string(),
+            helpful,
+            reason: reason.map(String::from),
+        })
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> WrapperResult<UploadResult> {
+        let uploaded_count = events.len();
+        self.record(MockCall::UploadTrace { events })?;
+        Ok(UploadResult {
+            uploaded_count,
+            success: true,
+        })
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult> {
+        self.record(MockCall::EndSession {
+            session_id: session_id.to_string(),
+            summary: summary.map(String::from),
+        })?;
+        DirectClient::new().end_session(session_id, summary).await
+    }
+}
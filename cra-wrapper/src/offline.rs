@@ -0,0 +1,274 @@
+//! Offline mode: local decisions, disk buffering and reconciliation
+//!
+//! When CRA is unreachable the wrapper keeps the agent running on a
+//! conservative local policy. Every event goes to the [`DiskQueue`] instead
+//! of the upload queue, and every local decision is recorded. On reconnect
+//! the buffered segment is uploaded and the server replays the decisions,
+//! reporting any it would have denied.
+//!
+//! ```text
+//! online ──(transport error)──► offline ──(reconnect)──► upload + reconcile
+//!                                  │
+//!                         OfflinePolicy decides,
+//!                         DiskQueue buffers events
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::error::{WrapperError, WrapperResult};
+use crate::queue::QueuedEvent;
+
+/// Conservative policy applied while offline
+///
+/// Patterns match action names exactly, or by prefix when they end in `*`.
+/// Denials win over allowances; unmatched actions get `default_allow`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflinePolicy {
+    /// Actions allowed while offline
+    #[serde(default = "default_allowed_actions")]
+    pub allowed_actions: Vec<String>,
+
+    /// Actions always denied while offline
+    #[serde(default)]
+    pub denied_actions: Vec<String>,
+
+    /// Decision for actions matching neither list
+    #[serde(default)]
+    pub default_allow: bool,
+}
+
+fn default_allowed_actions() -> Vec<String> {
+    ["read_*", "list_*", "get_*", "search_*"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+impl Default for OfflinePolicy {
+    fn default() -> Self {
+        Self {
+            allowed_actions: default_allowed_actions(),
+            denied_actions: Vec::new(),
+            default_allow: false,
+        }
+    }
+}
+
+impl OfflinePolicy {
+    /// Decide an action locally
+    pub fn decide(&self, action: &str) -> LocalDecision {
+        if let Some(pattern) = self.denied_actions.iter().find(|p| pattern_matches(p, action)) {
+            return LocalDecision::denied(action, Some(pattern.clone()));
+        }
+        if let Some(pattern) = self.allowed_actions.iter().find(|p| pattern_matches(p, action)) {
+            return LocalDecision::approved(action, Some(pattern.clone()));
+        }
+        if self.default_allow {
+            LocalDecision::approved(action, None)
+        } else {
+            LocalDecision::denied(action, None)
+        }
+    }
+}
+
+pub(crate) fn pattern_matches(pattern: &str, action: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => action.starts_with(prefix),
+        None => pattern == action,
+    }
+}
+
+/// Outcome of the offline policy for one action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalDecision {
+    /// Action name
+    pub action: String,
+
+    /// Whether the action was allowed
+    pub allowed: bool,
+
+    /// Pattern that matched (none means the policy default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched: Option<String>,
+}
+
+impl LocalDecision {
+    fn approved(action: &str, matched: Option<String>) -> Self {
+        Self { action: action.to_string(), allowed: true, matched }
+    }
+
+    fn denied(action: &str, matched: Option<String>) -> Self {
+        Self { action: action.to_string(), allowed: false, matched }
+    }
+
+    /// Human-readable reason for the decision
+    pub fn reason(&self) -> String {
+        let verdict = if self.allowed { "allowed" } else { "denied" };
+        match &self.matched {
+            Some(pattern) => format!("{} offline by policy '{}'", verdict, pattern),
+            None => format!("{} offline by default policy", verdict),
+        }
+    }
+}
+
+/// A decision made while offline, kept for reconciliation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineDecision {
+    /// Session the action belongs to
+    pub session_id: String,
+
+    /// Action name
+    pub action: String,
+
+    /// Action parameters
+    pub params: serde_json::Value,
+
+    /// Whether the action was allowed locally
+    pub allowed: bool,
+
+    /// Reason for the local decision
+    pub reason: String,
+
+    /// When the decision was made
+    pub decided_at: DateTime<Utc>,
+}
+
+/// A local approval the server would have denied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationConflict {
+    /// The decision made offline
+    pub decision: OfflineDecision,
+
+    /// Server decision: "approved" or "denied"
+    pub server_decision: String,
+
+    /// Server reason (for denials)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_reason: Option<String>,
+}
+
+/// Result of reconnecting after offline operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Buffered events uploaded
+    pub uploaded_count: usize,
+
+    /// Offline decisions replayed on the server
+    pub decisions_checked: usize,
+
+    /// Local approvals the server would have denied
+    pub conflicts: Vec<ReconciliationConflict>,
+
+    /// When offline mode started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offline_since: Option<DateTime<Utc>>,
+
+    /// When reconciliation finished
+    pub reconciled_at: DateTime<Utc>,
+}
+
+impl ReconciliationReport {
+    /// Whether every offline decision matched the server
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Append-only event buffer used while offline
+///
+/// Events are stored as JSON lines at `path`, so a crashed agent can recover
+/// them on restart. Without a path the buffer lives in memory.
+pub struct DiskQueue {
+    /// Spool file
+    path: Option<PathBuf>,
+
+    /// In-memory buffer (used when there is no spool file)
+    memory: Mutex<Vec<QueuedEvent>>,
+}
+
+impl DiskQueue {
+    /// Create a queue spooling to `path`, or in memory when `None`
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            memory: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Spool file, if any
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Append an event
+    pub async fn push(&self, event: &QueuedEvent) -> WrapperResult<()> {
+        let mut memory = self.memory.lock().await;
+        match &self.path {
+            Some(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", serde_json::to_string(event)?)?;
+            }
+            None => memory.push(event.clone()),
+        }
+        Ok(())
+    }
+
+    /// Read all buffered events without removing them
+    pub async fn read_all(&self) -> WrapperResult<Vec<QueuedEvent>> {
+        let memory = self.memory.lock().await;
+        match &self.path {
+            Some(path) => read_spool(path),
+            None => Ok(memory.clone()),
+        }
+    }
+
+    /// Remove all buffered events
+    pub async fn clear(&self) -> WrapperResult<()> {
+        let mut memory = self.memory.lock().await;
+        memory.clear();
+        if let Some(path) = &self.path {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of buffered events
+    pub async fn len(&self) -> WrapperResult<usize> {
+        Ok(self.read_all().await?.len())
+    }
+
+    /// Check if the buffer is empty
+    pub async fn is_empty(&self) -> WrapperResult<bool> {
+        Ok(self.len().await? == 0)
+    }
+}
+
+fn read_spool(path: &PathBuf) -> WrapperResult<Vec<QueuedEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = fs::File::open(path)?;
+    let mut events = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).map_err(|e| {
+            WrapperError::Queue(format!("{}:{}: {}", path.display(), index + 1, e))
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
@@ -1,12 +1,16 @@
 //! TRACE event queue for async upload
 
 use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "encryption")]
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
 use crate::config::QueueConfig;
+#[cfg(feature = "encryption")]
+use crate::encryption::SessionCipher;
 use crate::error::WrapperResult;
 
 /// A queued TRACE event
@@ -58,6 +62,10 @@ pub struct TraceQueue {
     total_flushed: AtomicU64,
     flush_count: AtomicU64,
     last_flush_at: RwLock<Option<DateTime<Utc>>>,
+
+    /// Payload cipher for the current session
+    #[cfg(feature = "encryption")]
+    cipher: RwLock<Option<Arc<SessionCipher>>>,
 }
 
 impl TraceQueue {
@@ -70,13 +78,60 @@ impl TraceQueue {
             total_flushed: AtomicU64::new(0),
             flush_count: AtomicU64::new(0),
             last_flush_at: RwLock::new(None),
+            #[cfg(feature = "encryption")]
+            cipher: RwLock::new(None),
         }
     }
 
+    /// Seal payloads with `cipher` from now on (`None` stops sealing)
+    #[cfg(feature = "encryption")]
+    pub async fn set_cipher(&self, cipher: Option<Arc<SessionCipher>>) {
+        *self.cipher.write().await = cipher;
+    }
+
+    /// Current payload cipher
+    #[cfg(feature = "encryption")]
+    pub async fn cipher(&self) -> Option<Arc<SessionCipher>> {
+        self.cipher.read().await.clone()
+    }
+
+    /// Seal the event's payload if a cipher is set
+    pub async fn seal(&self, event: QueuedEvent) -> WrapperResult<QueuedEvent> {
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = self.cipher.read().await.as_ref() {
+            return cipher.seal(event);
+        }
+        Ok(event)
+    }
+
+    /// Plaintext payload of an event, opening it if it was sealed
+    pub async fn open_payload(&self, event: &QueuedEvent) -> WrapperResult<serde_json::Value> {
+        #[cfg(feature = "encryption")]
+        if crate::encryption::is_sealed(event) {
+            let cipher = self.cipher.read().await.clone().ok_or_else(|| {
+                crate::error::WrapperError::Encryption("no session key to open payload".to_string())
+            })?;
+            return cipher.open(event);
+        }
+        Ok(event.payload.clone())
+    }
+
     /// Enqueue an event
+    ///
+    /// With a cipher set, the payload is sealed first. Events that fail to
+    /// seal are dropped rather than queued in plaintext.
     pub async fn enqueue(&self, event: QueuedEvent) {
         let should_flush = {
             let mut events = self.events.write().await;
+            // Seal under the events lock so sequence order matches queue order
+            let event_type = event.event_type.clone();
+            let event = match self.seal(event).await {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::error!("Dropping {} event that failed to seal: {}", event_type, e);
+                    return;
+                }
+            };
             events.push(event.clone());
             self.total_enqueued.fetch_add(1, Ordering::SeqCst);
 
@@ -133,6 +188,11 @@ impl TraceQueue {
         }
     }
 
+    /// Snapshot of pending events
+    pub async fn pending(&self) -> Vec<QueuedEvent> {
+        self.events.read().await.clone()
+    }
+
     /// Get pending event count
     pub async fn pending_count(&self) -> usize {
         self.events.read().await.len()
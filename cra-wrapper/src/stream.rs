@@ -0,0 +1,112 @@
+//! Chunk-by-chunk processing of streamed model output
+//!
+//! Streaming LLM responses arrive in arbitrary chunks, so a secret can be
+//! split across two of them. [`OutputStream`] holds back the trailing run of
+//! token characters (see [`is_token_char`]) and only passes text up to the
+//! last boundary through the output hooks. Everything it returns is final;
+//! the held-back tail is processed by the next chunk or by
+//! [`Wrapper::finish_output_stream`].
+//!
+//! ```ignore
+//! let mut stream = wrapper.output_stream().await?;
+//! while let Some(chunk) = llm.next_chunk().await {
+//!     print!("{}", wrapper.push_output_chunk(&mut stream, &chunk).await?);
+//! }
+//! let output = wrapper.finish_output_stream(stream).await?;
+//! print!("{}", output.tail);
+//! ```
+//!
+//! [`Wrapper::finish_output_stream`]: crate::Wrapper::finish_output_stream
+
+use serde::{Deserialize, Serialize};
+
+use crate::content::ContentDetection;
+use crate::hooks::is_token_char;
+
+/// State of one streamed output
+#[derive(Debug, Clone)]
+pub struct OutputStream {
+    /// Session the output belongs to
+    pub(crate) session_id: String,
+
+    /// Text received but not yet processed
+    pub(crate) pending: String,
+
+    /// All text received
+    pub(crate) original: String,
+
+    /// All processed text returned so far
+    pub(crate) processed: String,
+
+    /// Redactions made so far
+    pub(crate) redactions: usize,
+
+    /// Content policy detections so far
+    pub(crate) content_detections: Vec<ContentDetection>,
+
+    /// Chunks received
+    pub(crate) chunks: usize,
+}
+
+impl OutputStream {
+    pub(crate) fn new(session_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            pending: String::new(),
+            original: String::new(),
+            processed: String::new(),
+            redactions: 0,
+            content_detections: Vec::new(),
+            chunks: 0,
+        }
+    }
+
+    /// Session the output belongs to
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Text held back until the next boundary
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Add a chunk and take the text that is ready to process
+    pub(crate) fn take_ready(&mut self, chunk: &str) -> String {
+        self.chunks += 1;
+        self.original.push_str(chunk);
+        self.pending.push_str(chunk);
+
+        let split = self.pending
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_token_char(*c))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let tail = self.pending.split_off(split);
+        std::mem::replace(&mut self.pending, tail)
+    }
+}
+
+/// Result of finishing a streamed output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedOutput {
+    /// Processed text of the held-back tail, to emit after the last chunk
+    pub tail: String,
+
+    /// Full original output
+    pub original: String,
+
+    /// Full processed output
+    pub processed: String,
+
+    /// Chunks received
+    pub chunks: usize,
+
+    /// Values redacted across all chunks
+    pub redactions: usize,
+
+    /// Content policy detections across all chunks
+    #[serde(default)]
+    pub content_detections: Vec<ContentDetection>,
+}
@@ -4,6 +4,11 @@ use async_trait::async_trait;
 
 use crate::error::WrapperResult;
 
+/// Header carrying a correlation ID between CRA components
+///
+/// Matches the header the CRA server reads; forward it on every request.
+pub const CORRELATION_HEADER: &str = "X-CRA-Correlation-Id";
+
 /// Transport backend interface
 #[async_trait]
 pub trait TransportBackend: Send + Sync {
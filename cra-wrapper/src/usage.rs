@@ -0,0 +1,181 @@
+//! Model token usage and cost telemetry
+//!
+//! The host agent reports each model turn with [`Wrapper::report_usage`].
+//! The wrapper keeps per-session totals (overall and per model), emits a
+//! `wrapper.usage` event per turn, and enforces ceilings taken from
+//! `budget_limit` constraints in the bootstrap result:
+//!
+//! ```json
+//! {"constraint_id": "budget", "constraint_type": "budget_limit",
+//!  "description": "...", "parameters": {"max_tokens": 200000, "max_cost_usd": 5.0}}
+//! ```
+//!
+//! [`Wrapper::report_usage`]: crate::Wrapper::report_usage
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::SessionConstraint;
+
+/// Constraint type carrying usage ceilings
+pub const BUDGET_LIMIT: &str = "budget_limit";
+
+/// Usage reported for one model turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Model that served the turn
+    pub model: String,
+
+    /// Provider (informational)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    /// Prompt tokens
+    pub input_tokens: u64,
+
+    /// Completion tokens
+    pub output_tokens: u64,
+
+    /// Provider cost in USD, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    /// Usage for a turn with no cost information
+    pub fn new(model: &str, input_tokens: u64, output_tokens: u64) -> Self {
+        Self {
+            model: model.to_string(),
+            provider: None,
+            input_tokens,
+            output_tokens,
+            cost_usd: None,
+        }
+    }
+
+    /// Set the provider cost
+    pub fn with_cost(mut self, cost_usd: f64) -> Self {
+        self.cost_usd = Some(cost_usd);
+        self
+    }
+
+    /// Set the provider
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    /// Input plus output tokens
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Aggregated usage counters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageCounts {
+    /// Turns reported
+    pub turns: u64,
+
+    /// Prompt tokens
+    pub input_tokens: u64,
+
+    /// Completion tokens
+    pub output_tokens: u64,
+
+    /// Cost in USD
+    pub cost_usd: f64,
+}
+
+impl UsageCounts {
+    fn add(&mut self, usage: &TokenUsage) {
+        self.turns += 1;
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost_usd += usage.cost_usd.unwrap_or(0.0);
+    }
+
+    /// Input plus output tokens
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Session usage totals
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Totals across all models
+    #[serde(flatten)]
+    pub total: UsageCounts,
+
+    /// Totals per model
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_model: BTreeMap<String, UsageCounts>,
+}
+
+impl UsageTotals {
+    /// Add one turn
+    pub fn record(&mut self, usage: &TokenUsage) {
+        self.total.add(usage);
+        self.by_model.entry(usage.model.clone()).or_default().add(usage);
+    }
+}
+
+/// Per-session usage ceilings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageLimits {
+    /// Maximum input plus output tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    /// Maximum cost in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+}
+
+impl UsageLimits {
+    /// Collect ceilings from `budget_limit` constraints (the tightest wins)
+    pub fn from_constraints(constraints: &[SessionConstraint]) -> Self {
+        let mut limits = Self::default();
+        for params in constraints.iter()
+            .filter(|c| c.constraint_type == BUDGET_LIMIT)
+            .filter_map(|c| c.parameters.as_ref())
+        {
+            if let Some(max) = params.get("max_tokens").and_then(|v| v.as_u64()) {
+                limits.max_tokens = Some(limits.max_tokens.map_or(max, |m| m.min(max)));
+            }
+            if let Some(max) = params.get("max_cost_usd").and_then(|v| v.as_f64()) {
+                limits.max_cost_usd = Some(limits.max_cost_usd.map_or(max, |m| m.min(max)));
+            }
+        }
+        limits
+    }
+
+    /// Whether no ceiling is set
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost_usd.is_none()
+    }
+
+    /// Describe the first ceiling `totals` has reached, if any
+    pub fn exceeded(&self, totals: &UsageTotals) -> Option<String> {
+        if let Some(max) = self.max_tokens {
+            if totals.total.total_tokens() >= max {
+                return Some(format!(
+                    "token ceiling reached ({} of {})",
+                    totals.total.total_tokens(),
+                    max
+                ));
+            }
+        }
+        if let Some(max) = self.max_cost_usd {
+            if totals.total.cost_usd >= max {
+                return Some(format!(
+                    "cost ceiling reached (${:.4} of ${:.4})",
+                    totals.total.cost_usd, max
+                ));
+            }
+        }
+        None
+    }
+}
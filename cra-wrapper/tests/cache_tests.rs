@@ -1,6 +1,6 @@
 //! ContextCache tests
 
-use cra_wrapper::cache::{ContextCache, CachedContext};
+use cra_wrapper::cache::{CacheLookup, ContextCache, CachedContext};
 use cra_wrapper::config::{CacheConfig, CacheBackendType};
 use chrono::{Duration, Utc};
 
@@ -9,6 +9,8 @@ fn test_cache_config() -> CacheConfig {
         enabled: true,
         default_ttl_seconds: 3600,
         max_entries: 100,
+        max_bytes: 1024 * 1024,
+        stale_while_revalidate_seconds: 0,
         backend: CacheBackendType::Memory,
     }
 }
@@ -114,6 +116,8 @@ async fn test_cache_disabled() {
         enabled: false, // Cache disabled
         default_ttl_seconds: 3600,
         max_entries: 100,
+        max_bytes: 1024 * 1024,
+        stale_while_revalidate_seconds: 0,
         backend: CacheBackendType::Memory,
     };
     let cache = ContextCache::new(config);
@@ -191,6 +195,8 @@ async fn test_cache_eviction_at_capacity() {
         enabled: true,
         default_ttl_seconds: 3600,
         max_entries: 3, // Small capacity for testing
+        max_bytes: 1024 * 1024,
+        stale_while_revalidate_seconds: 0,
         backend: CacheBackendType::Memory,
     };
     let cache = ContextCache::new(config);
@@ -364,3 +370,124 @@ async fn test_cache_stats_serialization() {
     assert!(json.contains("hits"));
     assert!(json.contains("hit_rate"));
 }
+
+fn context(id: &str, content: &str, ttl: Duration) -> CachedContext {
+    CachedContext::new(id, content, 100, ttl)
+}
+
+#[tokio::test]
+async fn test_cache_lru_keeps_recently_used() {
+    let config = CacheConfig {
+        max_entries: 2,
+        ..test_cache_config()
+    };
+    let cache = ContextCache::new(config);
+
+    cache.set("a", context("a", "A", Duration::hours(1))).await;
+    cache.set("b", context("b", "B", Duration::hours(1))).await;
+
+    // Touch "a" so "b" becomes least recently used
+    assert!(cache.get("a").await.is_some());
+    cache.set("c", context("c", "C", Duration::hours(1))).await;
+
+    assert!(cache.get("a").await.is_some());
+    assert!(cache.get("b").await.is_none());
+    assert!(cache.get("c").await.is_some());
+    assert_eq!(cache.stats().await.evictions, 1);
+}
+
+#[tokio::test]
+async fn test_cache_byte_bound() {
+    let config = CacheConfig {
+        max_bytes: 30,
+        ..test_cache_config()
+    };
+    let cache = ContextCache::new(config);
+
+    // Each entry is 1 byte of id plus 10 bytes of content
+    cache.set("a", context("a", "0123456789", Duration::hours(1))).await;
+    cache.set("b", context("b", "0123456789", Duration::hours(1))).await;
+    assert_eq!(cache.stats().await.bytes, 22);
+
+    cache.set("c", context("c", "0123456789", Duration::hours(1))).await;
+
+    let stats = cache.stats().await;
+    assert_eq!(stats.entry_count, 2);
+    assert_eq!(stats.bytes, 22);
+    assert_eq!(stats.max_bytes, 30);
+    assert!(cache.get("a").await.is_none());
+
+    // Entries larger than the bound are not cached at all
+    cache.set("big", context("big", &"x".repeat(64), Duration::hours(1))).await;
+    assert!(cache.get("big").await.is_none());
+    assert_eq!(cache.stats().await.entry_count, 2);
+
+    cache.invalidate("b").await;
+    assert_eq!(cache.stats().await.bytes, 11);
+}
+
+#[tokio::test]
+async fn test_cache_stale_while_revalidate() {
+    let config = CacheConfig {
+        stale_while_revalidate_seconds: 3600,
+        ..test_cache_config()
+    };
+    let cache = ContextCache::new(config);
+
+    let mut stale = context("ctx-1", "Old", Duration::hours(1));
+    stale.expires_at = Utc::now() - Duration::seconds(30);
+    cache.set("ctx-1", stale).await;
+
+    // get() only returns fresh entries
+    assert!(cache.get("ctx-1").await.is_none());
+
+    // The first lookup owns the refresh, later ones just get the stale copy
+    let first = cache.lookup("ctx-1").await;
+    assert_eq!(first.context().unwrap().content, "Old");
+    assert!(first.needs_revalidation());
+
+    let second = cache.lookup("ctx-1").await;
+    assert!(second.context().is_some());
+    assert!(!second.needs_revalidation());
+
+    // A failed refresh hands the job to the next caller
+    cache.revalidation_failed("ctx-1").await;
+    assert!(cache.lookup("ctx-1").await.needs_revalidation());
+
+    // A successful refresh makes the entry fresh again
+    cache.set("ctx-1", context("ctx-1", "New", Duration::hours(1))).await;
+    match cache.lookup("ctx-1").await {
+        CacheLookup::Fresh(ctx) => assert_eq!(ctx.content, "New"),
+        other => panic!("expected fresh entry, got {:?}", other),
+    }
+
+    let stats = cache.stats().await;
+    assert_eq!(stats.stale_hits, 3);
+    assert_eq!(stats.revalidations, 2);
+}
+
+#[tokio::test]
+async fn test_cache_stale_window_expires() {
+    let config = CacheConfig {
+        stale_while_revalidate_seconds: 10,
+        ..test_cache_config()
+    };
+    let cache = ContextCache::new(config);
+
+    let mut expired = context("ctx-1", "Old", Duration::hours(1));
+    expired.expires_at = Utc::now() - Duration::seconds(60);
+    cache.set("ctx-1", expired).await;
+
+    assert!(matches!(cache.lookup("ctx-1").await, CacheLookup::Miss));
+
+    cache.evict_expired().await;
+    assert_eq!(cache.stats().await.entry_count, 0);
+}
+
+#[tokio::test]
+async fn test_cache_ttl_prefers_server_value() {
+    let cache = ContextCache::new(test_cache_config());
+
+    assert_eq!(cache.ttl_for(Some(30)), Duration::seconds(30));
+    assert_eq!(cache.ttl_for(None), Duration::seconds(3600));
+}
@@ -1,12 +1,12 @@
 //! CRA Client tests
 
-use cra_wrapper::client::{CRAClient, DirectClient, BootstrapResult, ActionReport};
+use cra_wrapper::client::{CRAClient, DirectClient, BootstrapRequest, BootstrapResult, ActionReport};
 
 #[tokio::test]
 async fn test_direct_client_bootstrap() {
     let client = DirectClient::new();
 
-    let result = client.bootstrap("Help with coding").await.unwrap();
+    let result = client.bootstrap(&BootstrapRequest::new("agent", "Help with coding")).await.unwrap();
 
     // Should have a session ID
     assert!(!result.session_id.is_empty());
@@ -101,7 +101,7 @@ async fn test_direct_client_end_session() {
 async fn test_direct_client_default() {
     let client = DirectClient::default();
 
-    let result = client.bootstrap("test").await.unwrap();
+    let result = client.bootstrap(&BootstrapRequest::new("agent", "test")).await.unwrap();
     assert!(!result.session_id.is_empty());
 }
 
@@ -109,20 +109,8 @@ async fn test_direct_client_default() {
 async fn test_bootstrap_result_serialization() {
     use cra_wrapper::client::BootstrapContext;
 
-    let result = BootstrapResult {
-        session_id: "session-123".to_string(),
-        genesis_hash: "genesis_abc".to_string(),
-        current_hash: "genesis_abc".to_string(),
-        context_ids: vec!["ctx-1".to_string()],
-        contexts: vec![
-            BootstrapContext {
-                context_id: "ctx-1".to_string(),
-                content: "You are a helpful assistant.".to_string(),
-                priority: 100,
-            }
-        ],
-        rules: vec![],
-    };
+    let result = BootstrapResult::new("session-123", "genesis_abc")
+        .with_context(BootstrapContext::new("ctx-1", "You are a helpful assistant.", 100).with_ttl(600));
 
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("session-123"));
@@ -163,3 +151,43 @@ async fn test_action_report_with_denial() {
     let parsed: ActionReport = serde_json::from_str(&json).unwrap();
     assert_eq!(parsed.reason, Some("Action not permitted by policy".to_string()));
 }
+
+#[tokio::test]
+async fn test_bootstrap_result_reads_resolver_response() {
+    use cra_core::{AtlasManifest, BootstrapRequest, Resolver};
+    use serde_json::json;
+
+    let atlas: AtlasManifest = serde_json::from_value(json!({
+        "atlas_version": "1.0",
+        "atlas_id": "com.test.bootstrap",
+        "version": "1.0.0",
+        "name": "Bootstrap Atlas",
+        "description": "Atlas for bootstrap tests",
+        "domains": ["test"],
+        "capabilities": [],
+        "policies": [
+            {"policy_id": "no-delete", "type": "deny", "actions": ["*.delete"], "reason": "No deletes"},
+            {"policy_id": "pii", "type": "content", "actions": [], "parameters": {
+                "action": "redact",
+                "detectors": [{"type": "regex", "name": "email", "patterns": ["[a-z]+@[a-z]+\\.com"]}]
+            }}
+        ],
+        "actions": [],
+        "context_blocks": [
+            {"context_id": "rules", "name": "Rules", "priority": 10, "content": "Be careful", "inject_mode": "always"}
+        ]
+    }))
+    .unwrap();
+    let mut resolver = Resolver::new();
+    resolver.load_atlas(atlas).unwrap();
+    let response = resolver.bootstrap(&BootstrapRequest::new("agent", "Help with coding")).unwrap();
+
+    // What the resolver sends is what the wrapper reads
+    let result: BootstrapResult = serde_json::from_value(serde_json::to_value(&response).unwrap()).unwrap();
+    assert!(result.check_version().is_ok());
+    assert_eq!(result.genesis_hash, response.genesis_hash);
+    assert_eq!(result.contexts, response.contexts);
+    assert_eq!(result.rules, response.rules);
+    assert_eq!(result.content_policies[0].policy_id, "pii");
+    assert_eq!(result.context_ids(), vec!["rules"]);
+}
@@ -0,0 +1,226 @@
+//! Content policy tests
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cra_wrapper::client::{
+    ActionReport, BootstrapRequest, BootstrapResult, CRAClient, DirectClient, EndSessionResult, UploadResult,
+};
+use cra_wrapper::config::HookConfig;
+use cra_wrapper::hooks::{HookContext, HookRegistry, REDACTED};
+use cra_wrapper::{
+    ContentAction, ContentClassifier, ContentPolicy, ContextBlock, Wrapper, WrapperConfig,
+    WrapperError, WrapperResult,
+};
+
+fn policy(value: serde_json::Value) -> ContentPolicy {
+    serde_json::from_value(value).unwrap()
+}
+
+fn pii_policy(action: &str) -> ContentPolicy {
+    policy(serde_json::json!({
+        "policy_id": "no-pii",
+        "action": action,
+        "detectors": [
+            { "type": "regex", "name": "email", "patterns": ["[\\w.+-]+@[\\w-]+\\.\\w+"] },
+            { "type": "regex", "name": "ssn", "patterns": ["\\b\\d{3}-\\d{2}-\\d{4}\\b"] }
+        ]
+    }))
+}
+
+/// Scores text by whether it mentions a keyword
+struct TopicClassifier(&'static str);
+
+#[async_trait]
+impl ContentClassifier for TopicClassifier {
+    async fn score(&self, text: &str) -> WrapperResult<f64> {
+        Ok(if text.to_lowercase().contains(self.0) { 0.9 } else { 0.1 })
+    }
+}
+
+#[tokio::test]
+async fn test_redact_policy_replaces_matches() {
+    let registry = HookRegistry::new();
+    registry.register_content_policies(vec![pii_policy("redact")]).unwrap();
+    assert!(registry.names().contains(&"content_policy".to_string()));
+
+    let outcome = registry
+        .run(HookContext::output("s1", "Mail jo@example.com or ann@example.org, SSN 123-45-6789"))
+        .await
+        .unwrap();
+
+    assert!(outcome.blocked.is_none());
+    let ctx = outcome.context;
+    assert_eq!(ctx.text, format!("Mail {r} or {r}, SSN {r}", r = REDACTED));
+    assert_eq!(ctx.redactions, 3);
+    assert_eq!(ctx.content_detections.len(), 2);
+    assert_eq!(ctx.content_detections[0].detector, "email");
+    assert_eq!(ctx.content_detections[0].matches, 2);
+    assert_eq!(ctx.content_detections[1].action, ContentAction::Redact);
+}
+
+#[tokio::test]
+async fn test_block_and_flag_policies() {
+    let registry = HookRegistry::new();
+    registry
+        .register_content_policies(vec![
+            policy(serde_json::json!({
+                "policy_id": "profanity",
+                "action": "flag",
+                "detectors": [{ "type": "wordlist", "name": "mild", "words": ["darn", "heck"] }]
+            })),
+            policy(serde_json::json!({
+                "policy_id": "no-secrets-in",
+                "stages": ["input"],
+                "action": "block",
+                "reason": "Do not paste credentials",
+                "detectors": [{ "type": "wordlist", "name": "credentials", "words": ["password"] }]
+            })),
+        ])
+        .unwrap();
+
+    // Wordlists match whole words, case-insensitively
+    let outcome = registry.run(HookContext::input("s1", "Darn, what the HECK. Darned.")).await.unwrap();
+    assert!(outcome.blocked.is_none());
+    assert_eq!(outcome.context.text, "Darn, what the HECK. Darned.");
+    assert_eq!(outcome.context.content_detections[0].matches, 2);
+
+    let outcome = registry.run(HookContext::input("s1", "my Password is hunter2")).await.unwrap();
+    let block = outcome.blocked.unwrap();
+    assert_eq!(block.hook, "content_policy");
+    assert_eq!(block.reason, "Do not paste credentials");
+
+    // Input-only policy does not apply to output
+    let outcome = registry.run(HookContext::output("s1", "reset your password")).await.unwrap();
+    assert!(outcome.blocked.is_none());
+}
+
+#[tokio::test]
+async fn test_classifier_detectors() {
+    let registry = HookRegistry::new();
+    registry
+        .register_content_policies(vec![policy(serde_json::json!({
+            "policy_id": "no-medical",
+            "action": "redact",
+            "detectors": [{ "type": "classifier", "name": "medical", "classifier": "topic.medical", "threshold": 0.8 }]
+        }))])
+        .unwrap();
+
+    // Unregistered classifiers are skipped with a finding
+    let outcome = registry.run(HookContext::output("s1", "Take two aspirin")).await.unwrap();
+    assert!(outcome.blocked.is_none());
+    assert!(outcome.context.findings[0].contains("topic.medical"));
+
+    registry.register_content_classifier("topic.medical", Arc::new(TopicClassifier("aspirin")));
+    let outcome = registry.run(HookContext::output("s1", "Take two aspirin")).await.unwrap();
+    // Classifiers cannot redact spans, so redact blocks
+    assert!(outcome.blocked.is_some());
+    assert_eq!(outcome.context.content_detections[0].action, ContentAction::Block);
+
+    let outcome = registry.run(HookContext::output("s1", "Take a walk")).await.unwrap();
+    assert!(outcome.blocked.is_none());
+    assert!(outcome.context.content_detections.is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_policies_are_rejected() {
+    let registry = HookRegistry::new();
+    let bad = policy(serde_json::json!({
+        "policy_id": "bad",
+        "action": "block",
+        "detectors": [{ "type": "regex", "name": "r", "patterns": ["(unclosed"] }]
+    }));
+    let result = registry.register_content_policies(vec![pii_policy("flag"), bad.clone()]);
+    assert!(matches!(result, Err(WrapperError::InvalidContentPolicy(_))));
+    assert!(registry.content_policy_ids().is_empty());
+    assert!(!registry.names().contains(&"content_policy".to_string()));
+
+    // A bad configured policy is ignored rather than failing the wrapper
+    let registry = HookRegistry::from_config(&HookConfig {
+        content_policies: vec![bad],
+        ..HookConfig::default()
+    });
+    assert!(registry.content_policy_ids().is_empty());
+}
+
+/// Direct client whose bootstrap sends content policies
+struct PolicyClient;
+
+#[async_trait]
+impl CRAClient for PolicyClient {
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
+        let mut result = DirectClient::new().bootstrap(request).await?;
+        result.content_policies = vec![pii_policy("redact")];
+        Ok(result)
+    }
+
+    async fn request_context(
+        &self,
+        session_id: &str,
+        need: &str,
+        hints: Option<Vec<String>>,
+    ) -> WrapperResult<Vec<ContextBlock>> {
+        DirectClient::new().request_context(session_id, need, hints).await
+    }
+
+    async fn report_action(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> WrapperResult<ActionReport> {
+        DirectClient::new().report_action(session_id, action, params).await
+    }
+
+    async fn feedback(
+        &self,
+        _session_id: &str,
+        _context_id: &str,
+        _helpful: bool,
+        _reason: Option<&str>,
+    ) -> WrapperResult<()> {
+        Ok(())
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> WrapperResult<UploadResult> {
+        DirectClient::new().upload_trace(events).await
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult> {
+        DirectClient::new().end_session(session_id, summary).await
+    }
+}
+
+#[tokio::test]
+async fn test_bootstrap_policies_are_enforced_and_traced() {
+    let mut config = WrapperConfig::default();
+    config.queue.sync_events.clear();
+    config.hooks.content_policies = vec![policy(serde_json::json!({
+        "policy_id": "no-competitors",
+        "stages": ["output"],
+        "action": "block",
+        "detectors": [{ "type": "wordlist", "name": "names", "words": ["Acme Corp"] }]
+    }))];
+    let wrapper = Wrapper::with_client(config, PolicyClient);
+    wrapper.start_session("Content policies").await.unwrap();
+    assert_eq!(wrapper.hooks().content_policy_ids(), vec!["no-competitors", "no-pii"]);
+
+    let input = wrapper.on_input("I'm jo@example.com").await.unwrap();
+    assert_eq!(input.processed, format!("I'm {}", REDACTED));
+
+    let result = wrapper.on_output("Try acme corp instead").await;
+    assert!(matches!(result, Err(WrapperError::HookBlocked { ref hook, .. }) if hook == "content_policy"));
+
+    let events = wrapper.pending_events().await;
+    let serialized = serde_json::to_string(&events).unwrap();
+    assert!(!serialized.contains("jo@example.com"));
+
+    let input_event = events.iter().find(|e| e.event_type == "wrapper.input_received").unwrap();
+    assert_eq!(input_event.payload["content_detections"][0]["policy_id"], "no-pii");
+    assert_eq!(input_event.payload["content_detections"][0]["detector"], "email");
+    assert_eq!(input_event.payload["content_detections"][0]["matches"], 1);
+
+    let blocked = events.iter().find(|e| e.event_type == "wrapper.hook_blocked").unwrap();
+    assert_eq!(blocked.payload["content_detections"][0]["policy_id"], "no-competitors");
+    assert_eq!(blocked.payload["content_detections"][0]["action"], "block");
+}
@@ -0,0 +1,224 @@
+//! TRACE payload encryption tests
+
+#![cfg(feature = "encryption")]
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use cra_wrapper::client::{
+    ActionReport, BootstrapRequest, BootstrapResult, CRAClient, DirectClient, EndSessionResult, UploadResult,
+};
+use cra_wrapper::encryption::{
+    is_sealed, verify_sealed_chain, SealedPayload, SessionCipher, TraceDecryptionKey,
+};
+use cra_wrapper::{
+    ContextBlock, OfflineConfig, QueueConfig, QueuedEvent, TraceQueue, Wrapper, WrapperConfig,
+    WrapperError, WrapperResult,
+};
+
+fn event(event_type: &str, session_id: &str, payload: serde_json::Value) -> QueuedEvent {
+    QueuedEvent {
+        event_type: event_type.to_string(),
+        session_id: session_id.to_string(),
+        timestamp: Utc::now(),
+        payload,
+    }
+}
+
+/// Direct client whose bootstrap offers a trace encryption key
+struct KeyedClient {
+    key: Option<String>,
+}
+
+#[async_trait]
+impl CRAClient for KeyedClient {
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
+        let mut result = DirectClient::new().bootstrap(request).await?;
+        result.trace_encryption_key = self.key.clone();
+        Ok(result)
+    }
+
+    async fn request_context(
+        &self,
+        session_id: &str,
+        need: &str,
+        hints: Option<Vec<String>>,
+    ) -> WrapperResult<Vec<ContextBlock>> {
+        DirectClient::new().request_context(session_id, need, hints).await
+    }
+
+    async fn report_action(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> WrapperResult<ActionReport> {
+        DirectClient::new().report_action(session_id, action, params).await
+    }
+
+    async fn feedback(
+        &self,
+        _session_id: &str,
+        _context_id: &str,
+        _helpful: bool,
+        _reason: Option<&str>,
+    ) -> WrapperResult<()> {
+        Ok(())
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> WrapperResult<UploadResult> {
+        DirectClient::new().upload_trace(events).await
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult> {
+        DirectClient::new().end_session(session_id, summary).await
+    }
+}
+
+fn encrypted_config() -> WrapperConfig {
+    let mut config = WrapperConfig::default();
+    config.queue.encrypt_payloads = true;
+    config.queue.sync_events.clear();
+    config
+}
+
+#[test]
+fn test_seal_and_open_roundtrip() {
+    let server = TraceDecryptionKey::generate();
+    let cipher = SessionCipher::negotiate("session-1", &server.public_key_hex()).unwrap();
+
+    let payload = serde_json::json!({"action": "write_file", "params": {"path": "/etc/secret"}});
+    let sealed = cipher.seal(event("wrapper.action_reported", "session-1", payload.clone())).unwrap();
+
+    assert!(is_sealed(&sealed));
+    assert!(!sealed.payload.to_string().contains("/etc/secret"));
+    let envelope = SealedPayload::from_event(&sealed).unwrap();
+    assert_eq!(envelope.sender_key, cipher.public_key_hex());
+    assert_eq!(envelope.sequence, 0);
+
+    assert_eq!(cipher.open(&sealed).unwrap(), payload);
+    let opened = server.open(&sealed).unwrap();
+    assert_eq!(opened.payload, payload);
+    assert_eq!(opened.event_type, "wrapper.action_reported");
+
+    // Debug output never carries key material
+    assert!(!format!("{:?}", server).contains("secret"));
+}
+
+#[test]
+fn test_open_rejects_tampering() {
+    let server = TraceDecryptionKey::generate();
+    let cipher = SessionCipher::negotiate("session-1", &server.public_key_hex()).unwrap();
+    let sealed = cipher.seal(event("wrapper.usage", "session-1", serde_json::json!({"tokens": 5}))).unwrap();
+
+    // Metadata is authenticated
+    let mut relabeled = sealed.clone();
+    relabeled.event_type = "wrapper.other".to_string();
+    assert!(matches!(server.open(&relabeled), Err(WrapperError::Encryption(_))));
+
+    // A different server key cannot open it
+    let other = TraceDecryptionKey::generate();
+    assert!(other.open(&sealed).is_err());
+
+    // Unsealed events pass through
+    let plain = event("wrapper.usage", "session-1", serde_json::json!({"tokens": 5}));
+    assert_eq!(server.open(&plain).unwrap().payload, plain.payload);
+}
+
+#[test]
+fn test_negotiate_rejects_bad_key() {
+    assert!(matches!(
+        SessionCipher::negotiate("session-1", "not-hex"),
+        Err(WrapperError::Encryption(_))
+    ));
+}
+
+#[test]
+fn test_sealed_chain_verifies_without_key() {
+    let server = TraceDecryptionKey::generate();
+    let cipher = SessionCipher::negotiate("session-1", &server.public_key_hex()).unwrap();
+    let events = (0..4)
+        .map(|i| cipher.seal(event("wrapper.usage", "session-1", serde_json::json!({"turn": i}))).unwrap())
+        .collect::<Vec<_>>();
+
+    verify_sealed_chain(&events).unwrap();
+
+    // Order on disk does not matter
+    let mut shuffled = events.clone();
+    shuffled.reverse();
+    verify_sealed_chain(&shuffled).unwrap();
+
+    // A dropped event breaks the chain
+    let mut missing = events.clone();
+    missing.remove(1);
+    assert!(verify_sealed_chain(&missing).is_err());
+
+    // So does altering ciphertext or metadata
+    let mut altered = events.clone();
+    altered[2].timestamp = Utc::now() + chrono::Duration::seconds(1);
+    assert!(verify_sealed_chain(&altered).is_err());
+}
+
+#[tokio::test]
+async fn test_queue_seals_with_cipher() {
+    let server = TraceDecryptionKey::generate();
+    let queue = TraceQueue::new(QueueConfig::default());
+    queue.enqueue(event("wrapper.usage", "session-1", serde_json::json!({"turn": 0}))).await;
+
+    let cipher = SessionCipher::negotiate("session-1", &server.public_key_hex()).unwrap();
+    queue.set_cipher(Some(Arc::new(cipher))).await;
+    queue.enqueue(event("wrapper.usage", "session-1", serde_json::json!({"turn": 1}))).await;
+
+    let pending = queue.pending().await;
+    assert!(!is_sealed(&pending[0]));
+    assert!(is_sealed(&pending[1]));
+    assert_eq!(queue.open_payload(&pending[1]).await.unwrap(), serde_json::json!({"turn": 1}));
+}
+
+#[tokio::test]
+async fn test_wrapper_seals_session_events() {
+    let server = TraceDecryptionKey::generate();
+    let wrapper = Wrapper::with_client(
+        encrypted_config(),
+        KeyedClient { key: Some(server.public_key_hex()) },
+    );
+    wrapper.start_session("Encrypted session").await.unwrap();
+    wrapper.report_action("write_file", serde_json::json!({"path": "notes.txt"})).await.unwrap();
+
+    let events = wrapper.pending_events().await;
+    assert!(events.len() >= 2);
+    assert!(events.iter().all(is_sealed));
+    verify_sealed_chain(&events).unwrap();
+
+    let started = server.open(&events[0]).unwrap();
+    assert_eq!(started.payload["goal"], "Encrypted session");
+    assert!(started.payload["trace_encryption_key"].is_string());
+
+    wrapper.end_session(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wrapper_requires_server_key() {
+    let wrapper = Wrapper::with_client(encrypted_config(), KeyedClient { key: None });
+
+    let result = wrapper.start_session("No key").await;
+    assert!(matches!(result, Err(WrapperError::BootstrapFailed(_))));
+}
+
+#[tokio::test]
+async fn test_offline_spool_is_sealed_and_reconciles() {
+    let server = TraceDecryptionKey::generate();
+    let mut config = encrypted_config();
+    config.offline = OfflineConfig { enabled: true, ..OfflineConfig::default() };
+    let wrapper = Wrapper::with_client(config, KeyedClient { key: Some(server.public_key_hex()) });
+    wrapper.start_session("Offline encrypted").await.unwrap();
+
+    wrapper.go_offline().await;
+    let decision = wrapper.report_action("read_file", serde_json::json!({"path": "a.txt"})).await.unwrap();
+    assert!(decision.allowed);
+
+    let report = wrapper.reconnect().await.unwrap();
+    assert_eq!(report.decisions_checked, 1);
+    assert!(report.is_clean());
+}
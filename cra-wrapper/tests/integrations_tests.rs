@@ -0,0 +1,186 @@
+//! Tool-call integration tests
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use cra_wrapper::hooks::{ActionResult, HookContext, HookFlow, HookMiddleware, HookStage};
+use cra_wrapper::integrations::{govern_tool_call, ToolCallError};
+use cra_wrapper::{Wrapper, WrapperConfig, WrapperResult};
+
+/// Denies `delete_*` and records completed actions
+#[derive(Default)]
+struct Recorder {
+    completed: Mutex<Vec<(String, ActionResult)>>,
+}
+
+#[async_trait]
+impl HookMiddleware for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    fn stages(&self) -> &[HookStage] {
+        &[HookStage::Action]
+    }
+
+    async fn handle(&self, ctx: &mut HookContext) -> WrapperResult<HookFlow> {
+        if ctx.action.as_deref().is_some_and(|a| a.starts_with("delete_")) {
+            return Ok(HookFlow::Block("deletes are not allowed".to_string()));
+        }
+        Ok(HookFlow::Continue)
+    }
+
+    async fn after_action(&self, ctx: &HookContext, result: &ActionResult) {
+        let action = ctx.action.clone().unwrap_or_default();
+        self.completed.lock().unwrap().push((action, result.clone()));
+    }
+}
+
+async fn governed_wrapper() -> (Wrapper, Arc<Recorder>) {
+    let wrapper = Wrapper::new(WrapperConfig::default());
+    let recorder = Arc::new(Recorder::default());
+    wrapper.hooks().register(recorder.clone());
+    wrapper.start_session("Integration test").await.unwrap();
+    (wrapper, recorder)
+}
+
+#[tokio::test]
+async fn test_govern_tool_call_runs_and_records() {
+    let (wrapper, recorder) = governed_wrapper().await;
+    let queued_before = wrapper.queue_stats().await.total_enqueued;
+
+    let value = govern_tool_call(&wrapper, "add", serde_json::json!({"a": 2, "b": 3}), |args| async move {
+        Ok::<_, String>(args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap())
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(value, 5);
+
+    {
+        let completed = recorder.completed.lock().unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].0, "add");
+        assert!(completed[0].1.success);
+        assert_eq!(completed[0].1.output, Some(serde_json::json!(5)));
+    }
+
+    // action_reported + action_completed
+    assert_eq!(wrapper.queue_stats().await.total_enqueued, queued_before + 2);
+}
+
+#[tokio::test]
+async fn test_govern_tool_call_denied_does_not_run() {
+    let (wrapper, recorder) = governed_wrapper().await;
+    let ran = Arc::new(Mutex::new(false));
+    let ran_in_tool = ran.clone();
+
+    let result = govern_tool_call(&wrapper, "delete_file", serde_json::json!({}), |_| async move {
+        *ran_in_tool.lock().unwrap() = true;
+        Ok::<_, String>(())
+    })
+    .await;
+
+    match result {
+        Err(ToolCallError::Denied(reason)) => assert!(reason.contains("deletes are not allowed")),
+        other => panic!("expected denial, got {:?}", other.map(|_| ())),
+    }
+    assert!(!*ran.lock().unwrap());
+    assert!(recorder.completed.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_govern_tool_call_records_tool_errors() {
+    let (wrapper, recorder) = governed_wrapper().await;
+
+    let result = govern_tool_call(&wrapper, "read_file", serde_json::json!({}), |_| async {
+        Err::<String, _>("file not found")
+    })
+    .await;
+
+    assert!(matches!(result, Err(ToolCallError::Tool("file not found"))));
+
+    let completed = recorder.completed.lock().unwrap();
+    assert!(!completed[0].1.success);
+    assert_eq!(completed[0].1.error.as_deref(), Some("file not found"));
+}
+
+#[tokio::test]
+async fn test_govern_tool_call_requires_session() {
+    let wrapper = Wrapper::new(WrapperConfig::default());
+
+    let result = govern_tool_call(&wrapper, "read_file", serde_json::json!({}), |_| async {
+        Ok::<_, String>(())
+    })
+    .await;
+
+    assert!(matches!(result, Err(ToolCallError::Wrapper(_))));
+}
+
+#[cfg(feature = "mcp")]
+#[tokio::test]
+async fn test_mcp_adapter() {
+    use cra_wrapper::integrations::mcp::{govern_mcp_call, McpContent, McpToolCall};
+
+    let (wrapper, _) = governed_wrapper().await;
+
+    let call: McpToolCall = serde_json::from_value(serde_json::json!({
+        "name": "echo",
+        "arguments": {"text": "hi"}
+    }))
+    .unwrap();
+    let result = govern_mcp_call(&wrapper, call, |args| async move {
+        Ok::<_, String>(args["text"].clone())
+    })
+    .await
+    .unwrap();
+    assert!(!result.is_error);
+    assert_eq!(result.content, vec![McpContent::Text { text: "hi".to_string() }]);
+
+    let call = McpToolCall { name: "delete_file".to_string(), arguments: serde_json::json!({}) };
+    let result = govern_mcp_call(&wrapper, call, |_| async { Ok::<_, String>(serde_json::json!(null)) })
+        .await
+        .unwrap();
+    assert!(result.is_error);
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["isError"], true);
+    assert_eq!(json["content"][0]["type"], "text");
+}
+
+#[cfg(feature = "openai")]
+#[tokio::test]
+async fn test_openai_adapter() {
+    use cra_wrapper::integrations::openai::{govern_openai_tool_call, ToolCall};
+
+    let (wrapper, _) = governed_wrapper().await;
+
+    let call: ToolCall = serde_json::from_value(serde_json::json!({
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "get_weather", "arguments": "{\"city\":\"Oslo\"}"}
+    }))
+    .unwrap();
+    let message = govern_openai_tool_call(&wrapper, &call, |args| async move {
+        Ok::<_, String>(serde_json::json!({"city": args["city"], "temp_c": 4}))
+    })
+    .await
+    .unwrap();
+    assert_eq!(message.role, "tool");
+    assert_eq!(message.tool_call_id, "call_1");
+    assert_eq!(message.content, r#"{"city":"Oslo","temp_c":4}"#);
+
+    let mut bad = call.clone();
+    bad.function.arguments = "{not json".to_string();
+    let message = govern_openai_tool_call(&wrapper, &bad, |_| async { Ok::<_, String>(serde_json::json!(null)) })
+        .await
+        .unwrap();
+    assert!(message.content.starts_with("Invalid arguments"));
+
+    let mut denied = call.clone();
+    denied.function.name = "delete_file".to_string();
+    let message = govern_openai_tool_call(&wrapper, &denied, |_| async { Ok::<_, String>(serde_json::json!(null)) })
+        .await
+        .unwrap();
+    assert!(message.content.starts_with("Denied by CRA"));
+}
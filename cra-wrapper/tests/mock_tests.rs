@@ -0,0 +1,80 @@
+//! MockCRAClient tests
+
+use cra_wrapper::client::{BootstrapRequest, BootstrapResult, CRAClient};
+use cra_wrapper::{
+    ContextBlock, MockCRAClient, MockCall, MockMethod, Wrapper, WrapperConfig, WrapperError,
+};
+use serde_json::json;
+
+fn bootstrap(session_id: &str) -> BootstrapResult {
+    BootstrapResult::new(session_id, "genesis")
+}
+
+#[tokio::test]
+async fn test_mock_scripts_wrapper_session() {
+    let client = MockCRAClient::new()
+        .with_bootstrap(bootstrap("session-1"))
+        .with_approval("delete_tmp")
+        .with_denial("delete_*", "deletes need approval");
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client.clone());
+
+    assert_eq!(wrapper.start_session("Tidy up").await.unwrap(), "session-1");
+    assert!(wrapper.report_action("delete_tmp", json!({})).await.unwrap().allowed);
+    let denied = wrapper.report_action("delete_repo", json!({"name": "core"})).await.unwrap();
+    assert!(!denied.allowed);
+    assert!(wrapper.report_action("read_file", json!({})).await.unwrap().allowed);
+    wrapper.end_session(Some("done")).await.unwrap();
+
+    assert_eq!(client.reported_actions(), vec!["delete_tmp", "delete_repo", "read_file"]);
+    assert_eq!(
+        client.calls_to(MockMethod::ReportAction)[1],
+        MockCall::ReportAction {
+            session_id: "session-1".to_string(),
+            action: "delete_repo".to_string(),
+            params: json!({"name": "core"}),
+        }
+    );
+    let methods: Vec<_> = client.calls().iter().map(MockCall::method).collect();
+    assert_eq!(methods.first(), Some(&MockMethod::Bootstrap));
+    assert_eq!(methods.last(), Some(&MockMethod::EndSession));
+
+    // The queue runs out; later sessions get fresh IDs
+    assert_ne!(wrapper.start_session("Again").await.unwrap(), "session-1");
+}
+
+#[tokio::test]
+async fn test_mock_failure_injection() {
+    let client = MockCRAClient::new().with_failure(MockMethod::ReportAction, 1, "connection reset");
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client.clone());
+    wrapper.start_session("Goal").await.unwrap();
+
+    let err = wrapper.report_action("read_file", json!({})).await.unwrap_err();
+    assert!(matches!(err, WrapperError::Transport(ref m) if m == "connection reset"));
+    assert!(wrapper.report_action("read_file", json!({})).await.is_ok());
+
+    client.set_down(Some("unreachable"));
+    assert!(matches!(client.bootstrap(&BootstrapRequest::new("agent", "x")).await, Err(WrapperError::Transport(_))));
+    client.set_down(None);
+
+    // Failed calls are recorded too
+    assert_eq!(client.calls_to(MockMethod::ReportAction).len(), 2);
+    client.clear_calls();
+    assert!(client.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_mock_contexts_and_uploads() {
+    let client = MockCRAClient::new().with_context(ContextBlock {
+        context_id: "style-guide".to_string(),
+        content: "Use tabs".to_string(),
+        priority: 10,
+        ttl_seconds: None,
+    });
+
+    let blocks = client.request_context("s1", "formatting", None).await.unwrap();
+    assert_eq!(blocks[0].context_id, "style-guide");
+
+    let uploaded = client.upload_trace(vec![json!({"n": 1}), json!({"n": 2})]).await.unwrap();
+    assert_eq!(uploaded.uploaded_count, 2);
+    assert_eq!(client.uploaded_events(), vec![json!({"n": 1}), json!({"n": 2})]);
+}
@@ -0,0 +1,281 @@
+//! Offline mode tests
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use cra_wrapper::client::{
+    ActionReport, BootstrapRequest, BootstrapResult, CRAClient, DirectClient, EndSessionResult, UploadResult,
+};
+use cra_wrapper::offline::OfflinePolicy;
+use cra_wrapper::queue::QueuedEvent;
+use cra_wrapper::{
+    ContextBlock, DiskQueue, Wrapper, WrapperConfig, WrapperError, WrapperResult,
+    OFFLINE_DECISION_EVENT,
+};
+
+/// Client that can be taken down and denies `delete_*` actions
+#[derive(Clone, Default)]
+struct FlakyClient {
+    inner: Arc<FlakyState>,
+}
+
+#[derive(Default)]
+struct FlakyState {
+    down: AtomicBool,
+    uploaded: AtomicUsize,
+}
+
+impl FlakyClient {
+    fn set_down(&self, down: bool) {
+        self.inner.down.store(down, Ordering::SeqCst);
+    }
+
+    fn check(&self) -> WrapperResult<()> {
+        if self.inner.down.load(Ordering::SeqCst) {
+            Err(WrapperError::Transport("connection refused".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait]
+impl CRAClient for FlakyClient {
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
+        self.check()?;
+        DirectClient::new().bootstrap(request).await
+    }
+
+    async fn request_context(
+        &self,
+        _session_id: &str,
+        _need: &str,
+        _hints: Option<Vec<String>>,
+    ) -> WrapperResult<Vec<ContextBlock>> {
+        self.check()?;
+        Ok(Vec::new())
+    }
+
+    async fn report_action(
+        &self,
+        _session_id: &str,
+        action: &str,
+        _params: serde_json::Value,
+    ) -> WrapperResult<ActionReport> {
+        self.check()?;
+        let denied = action.starts_with("delete_");
+        Ok(ActionReport {
+            decision: if denied { "denied" } else { "approved" }.to_string(),
+            trace_id: uuid::Uuid::new_v4().to_string(),
+            reason: denied.then(|| "deletes need approval".to_string()),
+            policy_notes: Vec::new(),
+        })
+    }
+
+    async fn feedback(
+        &self,
+        _session_id: &str,
+        _context_id: &str,
+        _helpful: bool,
+        _reason: Option<&str>,
+    ) -> WrapperResult<()> {
+        self.check()
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> WrapperResult<UploadResult> {
+        self.check()?;
+        self.inner.uploaded.fetch_add(events.len(), Ordering::SeqCst);
+        Ok(UploadResult {
+            uploaded_count: events.len(),
+            success: true,
+        })
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult> {
+        self.check()?;
+        DirectClient::new().end_session(session_id, summary).await
+    }
+}
+
+fn offline_config() -> WrapperConfig {
+    let mut config = WrapperConfig::default();
+    config.offline.enabled = true;
+    config
+}
+
+fn spool_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("cra-wrapper-offline-{}.jsonl", uuid::Uuid::new_v4()))
+}
+
+#[test]
+fn test_offline_policy_defaults_are_conservative() {
+    let policy = OfflinePolicy::default();
+
+    assert!(policy.decide("read_file").allowed);
+    assert!(policy.decide("list_dir").allowed);
+    assert!(!policy.decide("write_file").allowed);
+    assert!(!policy.decide("delete_file").allowed);
+}
+
+#[test]
+fn test_offline_policy_denials_win() {
+    let policy = OfflinePolicy {
+        allowed_actions: vec!["*".to_string()],
+        denied_actions: vec!["delete_*".to_string(), "deploy".to_string()],
+        default_allow: false,
+    };
+
+    assert!(policy.decide("write_file").allowed);
+    assert!(!policy.decide("deploy").allowed);
+    assert!(policy.decide("deploy_preview").allowed);
+
+    let decision = policy.decide("delete_file");
+    assert!(!decision.allowed);
+    assert_eq!(decision.matched.as_deref(), Some("delete_*"));
+    assert!(decision.reason().contains("delete_*"));
+}
+
+#[tokio::test]
+async fn test_disk_queue_persists_events() {
+    let path = spool_path();
+    let event = QueuedEvent {
+        event_type: "wrapper.input_received".to_string(),
+        session_id: "session-1".to_string(),
+        timestamp: Utc::now(),
+        payload: serde_json::json!({"input_length": 5}),
+    };
+
+    let queue = DiskQueue::new(Some(path.clone()));
+    queue.push(&event).await.unwrap();
+    queue.push(&event).await.unwrap();
+
+    // A fresh queue on the same file sees the buffered events
+    let reopened = DiskQueue::new(Some(path.clone()));
+    let events = reopened.read_all().await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].session_id, "session-1");
+
+    reopened.clear().await.unwrap();
+    assert!(reopened.is_empty().await.unwrap());
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_transport_error_switches_to_offline() {
+    let client = FlakyClient::default();
+    let wrapper = Wrapper::with_client(offline_config(), client.clone());
+    wrapper.start_session("Offline test").await.unwrap();
+
+    client.set_down(true);
+    let decision = wrapper.report_action("write_file", serde_json::json!({})).await.unwrap();
+
+    assert!(wrapper.is_offline().await);
+    assert!(!decision.allowed);
+    assert!(decision.reason.unwrap().contains("offline"));
+
+    let decision = wrapper.report_action("read_file", serde_json::json!({})).await.unwrap();
+    assert!(decision.allowed);
+}
+
+#[tokio::test]
+async fn test_transport_error_without_offline_mode() {
+    let client = FlakyClient::default();
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client.clone());
+    wrapper.start_session("Offline disabled").await.unwrap();
+
+    client.set_down(true);
+    let result = wrapper.report_action("read_file", serde_json::json!({})).await;
+
+    assert!(matches!(result, Err(WrapperError::Transport(_))));
+    assert!(!wrapper.is_offline().await);
+}
+
+#[tokio::test]
+async fn test_offline_events_buffer_to_spool() {
+    let path = spool_path();
+    let mut config = offline_config();
+    config.offline.spool_path = Some(path.clone());
+
+    let wrapper = Wrapper::new(config);
+    wrapper.start_session("Spool test").await.unwrap();
+    let queued_before = wrapper.queue_stats().await.total_enqueued;
+
+    wrapper.go_offline().await;
+    wrapper.on_input("hello").await.unwrap();
+    wrapper.report_action("read_file", serde_json::json!({"path": "a.txt"})).await.unwrap();
+
+    assert_eq!(wrapper.queue_stats().await.total_enqueued, queued_before);
+    assert_eq!(wrapper.offline_pending().await.unwrap(), 2);
+
+    let events = DiskQueue::new(Some(path.clone())).read_all().await.unwrap();
+    assert_eq!(events[1].event_type, OFFLINE_DECISION_EVENT);
+
+    wrapper.reconnect().await.unwrap();
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_end_session_requires_reconnect() {
+    let wrapper = Wrapper::new(offline_config());
+    wrapper.start_session("End offline").await.unwrap();
+
+    wrapper.go_offline().await;
+    let result = wrapper.end_session(None).await;
+    assert!(matches!(result, Err(WrapperError::Offline(_))));
+
+    wrapper.reconnect().await.unwrap();
+    assert!(wrapper.end_session(None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_reconnect_surfaces_server_denials() {
+    let client = FlakyClient::default();
+    let mut config = offline_config();
+    config.offline.policy.allowed_actions.push("delete_*".to_string());
+
+    let wrapper = Wrapper::with_client(config, client.clone());
+    wrapper.start_session("Reconcile test").await.unwrap();
+
+    client.set_down(true);
+    wrapper.report_action("read_file", serde_json::json!({})).await.unwrap();
+    wrapper.report_action("delete_file", serde_json::json!({"path": "a.txt"})).await.unwrap();
+    wrapper.report_action("write_file", serde_json::json!({})).await.unwrap();
+
+    // Still down: reconnect fails and the buffer is kept
+    assert!(wrapper.reconnect().await.is_err());
+    assert!(wrapper.is_offline().await);
+    assert_eq!(wrapper.offline_pending().await.unwrap(), 3);
+
+    client.set_down(false);
+    let report = wrapper.reconnect().await.unwrap();
+
+    assert!(!wrapper.is_offline().await);
+    assert_eq!(report.uploaded_count, 3);
+    assert_eq!(report.decisions_checked, 3);
+    assert!(report.offline_since.is_some());
+    assert!(!report.is_clean());
+
+    // Only the local approval the server denies is a conflict; the local
+    // denial of write_file is stricter than the server and not reported
+    assert_eq!(report.conflicts.len(), 1);
+    let conflict = &report.conflicts[0];
+    assert_eq!(conflict.decision.action, "delete_file");
+    assert_eq!(conflict.server_decision, "denied");
+    assert_eq!(conflict.server_reason.as_deref(), Some("deletes need approval"));
+
+    assert_eq!(wrapper.offline_pending().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_reconnect_when_online_is_noop() {
+    let wrapper = Wrapper::new(offline_config());
+    wrapper.start_session("Online").await.unwrap();
+
+    let report = wrapper.reconnect().await.unwrap();
+
+    assert!(report.is_clean());
+    assert_eq!(report.uploaded_count, 0);
+    assert!(report.offline_since.is_none());
+}
@@ -19,6 +19,7 @@ async fn test_enqueue_event() {
         max_size: 100,
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -41,6 +42,7 @@ async fn test_enqueue_multiple_events() {
         max_size: 100,
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -63,6 +65,7 @@ async fn test_flush_clears_queue() {
         max_size: 100,
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -105,6 +108,7 @@ async fn test_auto_flush_at_max_size() {
         max_size: 3, // Auto-flush at 3 events
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -129,6 +133,7 @@ async fn test_sync_event_triggers_flush() {
         max_size: 100,
         sync_events: vec!["session.end".to_string()],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -160,6 +165,7 @@ async fn test_queue_stats() {
         max_size: 100,
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = TraceQueue::new(config);
 
@@ -225,6 +231,7 @@ async fn test_concurrent_enqueue() {
         max_size: 1000,
         sync_events: vec![],
         flush_interval_ms: 5000,
+        encrypt_payloads: false,
     };
     let queue = Arc::new(TraceQueue::new(config));
 
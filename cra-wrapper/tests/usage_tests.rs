@@ -0,0 +1,181 @@
+//! Token usage and cost telemetry tests
+
+use async_trait::async_trait;
+use cra_wrapper::client::{
+    ActionReport, BootstrapRequest, BootstrapResult, CRAClient, DirectClient, EndSessionResult, SessionConstraint,
+    UploadResult,
+};
+use cra_wrapper::{
+    ContextBlock, TokenUsage, UsageLimits, UsageTotals, Wrapper, WrapperConfig, WrapperError,
+    WrapperResult,
+};
+
+fn budget(id: &str, parameters: serde_json::Value) -> SessionConstraint {
+    SessionConstraint {
+        constraint_id: id.to_string(),
+        constraint_type: "budget_limit".to_string(),
+        description: "Session budget".to_string(),
+        parameters: Some(parameters),
+    }
+}
+
+/// Direct client whose bootstrap carries budget constraints
+struct BudgetClient {
+    constraints: Vec<SessionConstraint>,
+}
+
+#[async_trait]
+impl CRAClient for BudgetClient {
+    async fn bootstrap(&self, request: &BootstrapRequest) -> WrapperResult<BootstrapResult> {
+        let mut result = DirectClient::new().bootstrap(request).await?;
+        result.constraints = self.constraints.clone();
+        Ok(result)
+    }
+
+    async fn request_context(
+        &self,
+        session_id: &str,
+        need: &str,
+        hints: Option<Vec<String>>,
+    ) -> WrapperResult<Vec<ContextBlock>> {
+        DirectClient::new().request_context(session_id, need, hints).await
+    }
+
+    async fn report_action(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> WrapperResult<ActionReport> {
+        DirectClient::new().report_action(session_id, action, params).await
+    }
+
+    async fn feedback(
+        &self,
+        _session_id: &str,
+        _context_id: &str,
+        _helpful: bool,
+        _reason: Option<&str>,
+    ) -> WrapperResult<()> {
+        Ok(())
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> WrapperResult<UploadResult> {
+        DirectClient::new().upload_trace(events).await
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> WrapperResult<EndSessionResult> {
+        DirectClient::new().end_session(session_id, summary).await
+    }
+}
+
+#[test]
+fn test_limits_from_constraints_take_tightest() {
+    let limits = UsageLimits::from_constraints(&[
+        budget("org", serde_json::json!({"max_tokens": 10_000, "max_cost_usd": 5.0})),
+        budget("team", serde_json::json!({"max_tokens": 2_000})),
+        SessionConstraint {
+            constraint_id: "rate".to_string(),
+            constraint_type: "rate_limit".to_string(),
+            description: "Not a budget".to_string(),
+            parameters: Some(serde_json::json!({"max_tokens": 1})),
+        },
+    ]);
+
+    assert_eq!(limits.max_tokens, Some(2_000));
+    assert_eq!(limits.max_cost_usd, Some(5.0));
+    assert!(UsageLimits::from_constraints(&[]).is_unlimited());
+}
+
+#[test]
+fn test_usage_totals_by_model() {
+    let mut totals = UsageTotals::default();
+    totals.record(&TokenUsage::new("model-a", 100, 50).with_cost(0.01));
+    totals.record(&TokenUsage::new("model-b", 10, 5));
+    totals.record(&TokenUsage::new("model-a", 200, 25).with_cost(0.02));
+
+    assert_eq!(totals.total.turns, 3);
+    assert_eq!(totals.total.total_tokens(), 390);
+    assert!((totals.total.cost_usd - 0.03).abs() < 1e-9);
+    assert_eq!(totals.by_model["model-a"].turns, 2);
+    assert_eq!(totals.by_model["model-b"].total_tokens(), 15);
+
+    // Totals flatten into the top-level object
+    let json = serde_json::to_value(&totals).unwrap();
+    assert_eq!(json["input_tokens"], 310);
+    assert_eq!(json["by_model"]["model-b"]["output_tokens"], 5);
+}
+
+#[tokio::test]
+async fn test_report_usage_aggregates_and_summarizes() {
+    let wrapper = Wrapper::new(WrapperConfig::default());
+    wrapper.start_session("Usage test").await.unwrap();
+    let queued_before = wrapper.queue_stats().await.total_enqueued;
+
+    wrapper
+        .report_usage(TokenUsage::new("model-a", 1_000, 200).with_provider("acme").with_cost(0.5))
+        .await
+        .unwrap();
+    let totals = wrapper.report_usage(TokenUsage::new("model-a", 500, 100)).await.unwrap();
+
+    assert_eq!(totals.total.total_tokens(), 1_800);
+    assert_eq!(wrapper.queue_stats().await.total_enqueued, queued_before + 2);
+
+    let summary = wrapper.end_session(None).await.unwrap();
+    assert_eq!(summary.usage.total.turns, 2);
+    assert_eq!(summary.usage.total.input_tokens, 1_500);
+    assert!((summary.usage.total.cost_usd - 0.5).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_report_usage_requires_session() {
+    let wrapper = Wrapper::new(WrapperConfig::default());
+
+    let result = wrapper.report_usage(TokenUsage::new("model-a", 1, 1)).await;
+    assert!(matches!(result, Err(WrapperError::NoActiveSession)));
+}
+
+#[tokio::test]
+async fn test_token_ceiling_denies_further_actions() {
+    let client = BudgetClient {
+        constraints: vec![budget("budget", serde_json::json!({"max_tokens": 1_000}))],
+    };
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client);
+    wrapper.start_session("Budget test").await.unwrap();
+
+    let session = wrapper.current_session().await.unwrap();
+    assert_eq!(session.usage_limits.max_tokens, Some(1_000));
+
+    wrapper.report_usage(TokenUsage::new("model-a", 600, 100)).await.unwrap();
+    assert!(wrapper.report_action("write_file", serde_json::json!({})).await.unwrap().allowed);
+
+    // Crossing the ceiling records the usage but reports the breach
+    let result = wrapper.report_usage(TokenUsage::new("model-a", 300, 100)).await;
+    assert!(matches!(result, Err(WrapperError::UsageLimitExceeded(_))));
+    assert_eq!(wrapper.current_session().await.unwrap().usage.total.total_tokens(), 1_100);
+
+    let decision = wrapper.report_action("write_file", serde_json::json!({})).await.unwrap();
+    assert!(!decision.allowed);
+    assert!(decision.reason.unwrap().contains("token ceiling"));
+
+    // The summary still carries the totals
+    let summary = wrapper.end_session(None).await.unwrap();
+    assert_eq!(summary.usage.total.total_tokens(), 1_100);
+}
+
+#[tokio::test]
+async fn test_cost_ceiling() {
+    let client = BudgetClient {
+        constraints: vec![budget("budget", serde_json::json!({"max_cost_usd": 1.0}))],
+    };
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client);
+    wrapper.start_session("Cost test").await.unwrap();
+
+    wrapper.report_usage(TokenUsage::new("model-a", 10, 10).with_cost(0.6)).await.unwrap();
+    let result = wrapper.report_usage(TokenUsage::new("model-a", 10, 10).with_cost(0.6)).await;
+
+    match result {
+        Err(WrapperError::UsageLimitExceeded(reason)) => assert!(reason.contains("cost ceiling")),
+        other => panic!("expected cost ceiling, got {:?}", other),
+    }
+}
@@ -159,6 +159,25 @@ async fn test_wrapper_queue_stats() {
     assert!(stats.total_enqueued >= 3);
 }
 
+#[tokio::test]
+async fn test_wrapper_correlation_id() {
+    let wrapper = Wrapper::new(WrapperConfig::default());
+    wrapper.set_correlation_id(Some("req-7".to_string())).await;
+    assert_eq!(wrapper.correlation_id().await.as_deref(), Some("req-7"));
+
+    wrapper.start_session("Test goal").await.unwrap();
+    wrapper.on_input("input 1").await.unwrap();
+    wrapper.set_correlation_id(None).await;
+    wrapper.on_output("output 1").await.unwrap();
+
+    let events = wrapper.pending_events().await;
+    let (stamped, rest) = events.split_at(events.len() - 1);
+    assert!(stamped.len() >= 2);
+    assert!(stamped.iter().all(|e| e.payload["correlation_id"] == "req-7"));
+    assert!(rest[0].payload.get("correlation_id").is_none());
+    assert_eq!(cra_wrapper::CORRELATION_HEADER, "X-CRA-Correlation-Id");
+}
+
 #[tokio::test]
 async fn test_wrapper_cache_stats() {
     let config = WrapperConfig::default();
@@ -258,3 +277,83 @@ async fn test_wrapper_config_serialization() {
     assert_eq!(parsed.version, config.version);
     assert_eq!(parsed.checkpoints_enabled, config.checkpoints_enabled);
 }
+
+/// Client whose contexts expire immediately and change on every fetch
+struct RefreshingClient {
+    fetches: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl cra_wrapper::client::CRAClient for RefreshingClient {
+    async fn bootstrap(
+        &self,
+        request: &cra_wrapper::client::BootstrapRequest,
+    ) -> cra_wrapper::WrapperResult<cra_wrapper::client::BootstrapResult> {
+        let mut result = cra_wrapper::client::DirectClient::new().bootstrap(request).await?;
+        result.contexts.push(cra_wrapper::client::BootstrapContext::new("ctx-1", "v0", 100).with_ttl(0));
+        Ok(result)
+    }
+
+    async fn request_context(
+        &self,
+        _session_id: &str,
+        _need: &str,
+        _hints: Option<Vec<String>>,
+    ) -> cra_wrapper::WrapperResult<Vec<cra_wrapper::ContextBlock>> {
+        let n = self.fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        Ok(vec![cra_wrapper::ContextBlock {
+            context_id: "ctx-1".to_string(),
+            content: format!("v{}", n),
+            priority: 100,
+            ttl_seconds: Some(3600),
+        }])
+    }
+
+    async fn report_action(
+        &self,
+        session_id: &str,
+        action: &str,
+        params: serde_json::Value,
+    ) -> cra_wrapper::WrapperResult<cra_wrapper::client::ActionReport> {
+        cra_wrapper::client::DirectClient::new().report_action(session_id, action, params).await
+    }
+
+    async fn feedback(
+        &self,
+        _session_id: &str,
+        _context_id: &str,
+        _helpful: bool,
+        _reason: Option<&str>,
+    ) -> cra_wrapper::WrapperResult<()> {
+        Ok(())
+    }
+
+    async fn upload_trace(&self, events: Vec<serde_json::Value>) -> cra_wrapper::WrapperResult<cra_wrapper::client::UploadResult> {
+        cra_wrapper::client::DirectClient::new().upload_trace(events).await
+    }
+
+    async fn end_session(&self, session_id: &str, summary: Option<&str>) -> cra_wrapper::WrapperResult<cra_wrapper::client::EndSessionResult> {
+        cra_wrapper::client::DirectClient::new().end_session(session_id, summary).await
+    }
+}
+
+#[tokio::test]
+async fn test_wrapper_cached_context_revalidates_in_background() {
+    let client = RefreshingClient { fetches: std::sync::atomic::AtomicUsize::new(0) };
+    let wrapper = Wrapper::with_client(WrapperConfig::default(), client);
+    wrapper.start_session("Revalidate test").await.unwrap();
+
+    // Bootstrap context had a zero TTL, so it is already stale
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let stale = wrapper.cached_context("ctx-1").await.unwrap().unwrap();
+    assert_eq!(stale.content, "v0");
+
+    // The background refresh replaces it with the server's copy
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let fresh = wrapper.cached_context("ctx-1").await.unwrap().unwrap();
+    assert_eq!(fresh.content, "v1");
+
+    let stats = wrapper.cache_stats().await;
+    assert_eq!(stats.stale_hits, 1);
+    assert_eq!(stats.revalidations, 1);
+}
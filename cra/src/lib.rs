@@ -0,0 +1,36 @@
+//! # CRA - Context Registry Agents
+//!
+//! One dependency for embedding CRA: re-exports [`cra_core`] and the
+//! standalone TRACE verifier, with a feature per subsystem so lean builds
+//! only compile what they use.
+//!
+//! ```toml
+//! [dependencies]
+//! cra = { version = "0.1", default-features = false, features = ["runtime"] }
+//! ```
+//!
+//! | Feature | Subsystem |
+//! |---------|-----------|
+//! | `signing` (default) | Ed25519 event signatures |
+//! | `runtime` | `AsyncRuntime` on tokio |
+//! | `gzip` | Gzip-compressed trace exports |
+//! | `object-storage` | Archiving sessions to S3-compatible storage |
+//! | `encryption` | AES-256-GCM encrypted event payloads |
+//! | `redb-store` | Session store on redb |
+//! | `oidc` | OIDC bearer token identity |
+//! | `conformance` | Conformance test helpers |
+//! | `chaos` | Fault injection for chaos tests |
+//! | `minimal` | Constrained runtimes (no file storage or threads) |
+//!
+//! ```rust
+//! use cra::prelude::*;
+//!
+//! let mut resolver = Resolver::new();
+//! let session_id = resolver.create_session("agent", "Triage the inbox").unwrap();
+//! assert!(resolver.verify_chain(&session_id).unwrap().is_valid);
+//! ```
+
+pub use cra_core::*;
+
+/// Standalone TRACE/1.0 hash chain verification
+pub use cra_trace_verify as verify;
@@ -0,0 +1,67 @@
+// Shared golden trace runner for the JavaScript bindings.
+//
+// `adapter` maps the suite's operations onto a binding's Resolver, since the
+// Node binding uses camelCase methods and the WASM binding snake_case.
+
+import { readFileSync, readdirSync, existsSync } from 'node:fs';
+import { join, dirname } from 'node:path';
+import { fileURLToPath } from 'node:url';
+import assert from 'node:assert/strict';
+
+export const GOLDEN_ROOT = join(dirname(fileURLToPath(import.meta.url)), '..', '..', 'golden');
+
+export function scenarios() {
+  return readdirSync(GOLDEN_ROOT)
+    .map((name) => join(GOLDEN_ROOT, name))
+    .filter((dir) => existsSync(join(dir, 'script.json')))
+    .sort();
+}
+
+export function runScript(scenario, adapter) {
+  const script = JSON.parse(readFileSync(join(scenario, 'script.json'), 'utf8'));
+  const resolver = adapter.deterministic(script.epoch_ms, script.id_seed);
+
+  for (const atlas of script.atlases) {
+    adapter.loadAtlasJson(resolver, readFileSync(join(scenario, atlas), 'utf8'));
+  }
+
+  let sessionId, agentId, resolutionId;
+  script.steps.forEach((step, i) => {
+    switch (step.op) {
+      case 'create_session':
+        agentId = step.agent_id;
+        sessionId = adapter.createSession(resolver, agentId, step.goal);
+        break;
+      case 'resolve':
+        resolutionId = JSON.parse(adapter.resolve(resolver, sessionId, agentId, step.goal)).trace_id;
+        break;
+      case 'execute': {
+        let failed = false;
+        try {
+          adapter.execute(resolver, sessionId, resolutionId, step.action_id, JSON.stringify(step.parameters ?? {}));
+        } catch {
+          failed = true;
+        }
+        assert.equal(failed, step.expect_error ?? false, `step ${i}: execute ${step.action_id}`);
+        break;
+      }
+      case 'end_session':
+        adapter.endSession(resolver, sessionId);
+        break;
+      default:
+        throw new Error(`step ${i}: unknown op ${step.op}`);
+    }
+  });
+
+  return adapter.getTrace(resolver, sessionId);
+}
+
+const parseJsonl = (text) => text.split('\n').filter((l) => l.trim()).map((l) => JSON.parse(l));
+
+export function checkGolden(scenario, trace) {
+  const actual = parseJsonl(trace);
+  const expected = parseJsonl(readFileSync(join(scenario, 'golden-trace.jsonl'), 'utf8'));
+
+  expected.forEach((event, i) => assert.deepStrictEqual(actual[i], event, `event ${i} differs from golden`));
+  assert.equal(actual.length, expected.length);
+}
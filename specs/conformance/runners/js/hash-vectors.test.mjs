@@ -0,0 +1,74 @@
+// Reference TRACE/1.0 hash computation in JavaScript.
+//
+// Recomputes the canonical JSON and event hash vectors in
+// specs/conformance/vectors/ and re-verifies every golden trace, without any
+// CRA binding:
+//
+//   node --test specs/conformance/runners/js/hash-vectors.test.mjs
+
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { createHash } from 'node:crypto';
+import { readFileSync } from 'node:fs';
+import { join } from 'node:path';
+
+import { GOLDEN_ROOT, scenarios } from './golden.mjs';
+
+const VECTORS = join(GOLDEN_ROOT, '..', 'vectors');
+
+// RFC 8785: ECMAScript number and string serialization, keys sorted by
+// UTF-16 code units (the default Array.prototype.sort order)
+export function canonicalJson(value) {
+  if (Array.isArray(value)) {
+    return `[${value.map(canonicalJson).join(',')}]`;
+  }
+  if (value !== null && typeof value === 'object') {
+    const keys = Object.keys(value).sort();
+    return `{${keys.map((k) => `${JSON.stringify(k)}:${canonicalJson(value[k])}`).join(',')}}`;
+  }
+  return JSON.stringify(value);
+}
+
+export function computeEventHash(event) {
+  const data =
+    event.trace_version +
+    event.event_id +
+    event.trace_id +
+    event.span_id +
+    (event.parent_span_id ?? '') +
+    event.session_id +
+    String(event.sequence) +
+    event.timestamp +
+    event.event_type +
+    canonicalJson(event.payload) +
+    event.previous_event_hash;
+  return createHash('sha256').update(data, 'utf8').digest('hex');
+}
+
+const loadVectors = (name) => JSON.parse(readFileSync(join(VECTORS, name), 'utf8')).vectors;
+
+test('canonical JSON vectors', () => {
+  for (const vector of loadVectors('canonical-json.json')) {
+    assert.equal(canonicalJson(JSON.parse(vector.input)), vector.canonical, vector.name);
+  }
+});
+
+test('event hash vectors', () => {
+  for (const vector of loadVectors('trace-hash.json')) {
+    assert.equal(canonicalJson(vector.event.payload), vector.canonical_payload, vector.name);
+    assert.equal(computeEventHash(vector.event), vector.event_hash, vector.name);
+  }
+});
+
+test('golden trace hashes', () => {
+  for (const scenario of scenarios()) {
+    let previous = '0'.repeat(64);
+    const lines = readFileSync(join(scenario, 'golden-trace.jsonl'), 'utf8').split('\n');
+    for (const line of lines.filter((l) => l.trim())) {
+      const event = JSON.parse(line);
+      assert.equal(event.previous_event_hash, previous, scenario);
+      assert.equal(computeEventHash(event), event.event_hash, scenario);
+      previous = event.event_hash;
+    }
+  }
+});
@@ -0,0 +1,28 @@
+// Golden trace conformance tests for the Node binding.
+//
+//   (cd cra-node && napi build --release)
+//   CRA_NODE_MODULE=./cra-node node --test specs/conformance/runners/js/node.test.mjs
+
+import { test } from 'node:test';
+import { createRequire } from 'node:module';
+import { basename, resolve } from 'node:path';
+
+import { scenarios, runScript, checkGolden } from './golden.mjs';
+
+const require = createRequire(import.meta.url);
+const modulePath = process.env.CRA_NODE_MODULE;
+const { Resolver } = require(modulePath ? resolve(modulePath) : '@cra/core');
+
+const adapter = {
+  deterministic: (epochMs, seed) => Resolver.deterministic(epochMs, seed),
+  loadAtlasJson: (r, json) => r.loadAtlasJson(json),
+  createSession: (r, agentId, goal) => r.createSession(agentId, goal),
+  resolve: (r, sessionId, agentId, goal) => r.resolve(sessionId, agentId, goal),
+  execute: (r, sessionId, resolutionId, actionId, params) => r.execute(sessionId, resolutionId, actionId, params),
+  endSession: (r, sessionId) => r.endSession(sessionId),
+  getTrace: (r, sessionId) => r.getTrace(sessionId),
+};
+
+for (const scenario of scenarios()) {
+  test(`node: ${basename(scenario)}`, () => checkGolden(scenario, runScript(scenario, adapter)));
+}
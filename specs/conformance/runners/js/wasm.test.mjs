@@ -0,0 +1,28 @@
+// Golden trace conformance tests for the WASM binding.
+//
+//   wasm-pack build cra-wasm --target nodejs
+//   CRA_WASM_MODULE=./cra-wasm/pkg/cra_wasm.js node --test specs/conformance/runners/js/wasm.test.mjs
+
+import { test } from 'node:test';
+import { basename, resolve } from 'node:path';
+import { pathToFileURL } from 'node:url';
+
+import { scenarios, runScript, checkGolden } from './golden.mjs';
+
+const modulePath = process.env.CRA_WASM_MODULE;
+const wasm = await import(modulePath ? pathToFileURL(resolve(modulePath)).href : '@cra/wasm');
+const { Resolver } = wasm;
+
+const adapter = {
+  deterministic: (epochMs, seed) => Resolver.deterministic(epochMs, seed),
+  loadAtlasJson: (r, json) => r.load_atlas_json(json),
+  createSession: (r, agentId, goal) => r.create_session(agentId, goal),
+  resolve: (r, sessionId, agentId, goal) => r.resolve(sessionId, agentId, goal),
+  execute: (r, sessionId, resolutionId, actionId, params) => r.execute(sessionId, resolutionId, actionId, params),
+  endSession: (r, sessionId) => r.end_session(sessionId),
+  getTrace: (r, sessionId) => r.get_trace(sessionId),
+};
+
+for (const scenario of scenarios()) {
+  test(`wasm: ${basename(scenario)}`, () => checkGolden(scenario, runScript(scenario, adapter)));
+}
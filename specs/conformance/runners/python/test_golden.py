@@ -0,0 +1,65 @@
+"""Golden trace conformance tests for the Python binding.
+
+Runs every scenario in specs/conformance/golden/ through `cra.Resolver` in
+deterministic mode and compares the trace with golden-trace.jsonl, hashes
+included.
+
+    maturin develop -m cra-python/Cargo.toml
+    pytest specs/conformance/runners/python
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+import cra
+
+GOLDEN_ROOT = Path(__file__).resolve().parents[2] / "golden"
+SCENARIOS = sorted(p.parent for p in GOLDEN_ROOT.glob("*/script.json"))
+
+
+def run_script(scenario: Path) -> str:
+    script = json.loads((scenario / "script.json").read_text())
+    resolver = cra.Resolver.deterministic(script["epoch_ms"], script["id_seed"])
+
+    for atlas in script["atlases"]:
+        resolver.load_atlas_json((scenario / atlas).read_text())
+
+    session_id = agent_id = resolution_id = None
+    for i, step in enumerate(script["steps"]):
+        op = step["op"]
+        if op == "create_session":
+            agent_id = step["agent_id"]
+            session_id = resolver.create_session(agent_id, step["goal"])
+        elif op == "resolve":
+            resolution = json.loads(resolver.resolve_json(session_id, agent_id, step["goal"]))
+            resolution_id = resolution["trace_id"]
+        elif op == "execute":
+            params = json.dumps(step.get("parameters", {}))
+            try:
+                resolver.execute(session_id, resolution_id, step["action_id"], params)
+                failed = False
+            except RuntimeError:
+                failed = True
+            assert failed == step.get("expect_error", False), f"step {i}: execute {step['action_id']}"
+        elif op == "end_session":
+            resolver.end_session(session_id)
+        else:
+            raise ValueError(f"step {i}: unknown op {op!r}")
+
+    return resolver.get_trace(session_id)
+
+
+def parse_jsonl(text: str) -> list:
+    return [json.loads(line) for line in text.splitlines() if line.strip()]
+
+
+@pytest.mark.parametrize("scenario", SCENARIOS, ids=[p.name for p in SCENARIOS])
+def test_golden_trace(scenario: Path):
+    actual = parse_jsonl(run_script(scenario))
+    expected = parse_jsonl((scenario / "golden-trace.jsonl").read_text())
+
+    for i, (a, e) in enumerate(zip(actual, expected)):
+        assert a == e, f"event {i} differs from golden"
+    assert len(actual) == len(expected)
@@ -0,0 +1,109 @@
+"""Reference TRACE/1.0 hash computation in Python.
+
+Recomputes the canonical JSON and event hash vectors in
+specs/conformance/vectors/ and re-verifies every golden trace, without the
+Rust core. Runs under pytest or directly:
+
+    python specs/conformance/runners/python/test_hash_vectors.py
+"""
+
+import hashlib
+import json
+from decimal import Decimal
+from pathlib import Path
+
+SPECS = Path(__file__).resolve().parents[2]
+VECTORS = SPECS / "vectors"
+GOLDEN_ROOT = SPECS / "golden"
+
+
+def canonical_number(value) -> str:
+    """ECMAScript Number.prototype.toString for an IEEE 754 double."""
+    value = float(value)
+    if value == 0:
+        return "0"
+    sign, digits, exponent = Decimal(repr(abs(value))).normalize().as_tuple()
+    digits = "".join(map(str, digits))
+    k = len(digits)
+    n = exponent + k  # position of the decimal point
+    prefix = "-" if value < 0 else ""
+
+    if k <= n <= 21:
+        return prefix + digits + "0" * (n - k)
+    if 0 < n <= 21:
+        return prefix + digits[:n] + "." + digits[n:]
+    if -6 < n <= 0:
+        return prefix + "0." + "0" * -n + digits
+    mantissa = digits[0] + ("." + digits[1:] if k > 1 else "")
+    return f"{prefix}{mantissa}e{'+' if n - 1 >= 0 else '-'}{abs(n - 1)}"
+
+
+def canonical_json(value) -> str:
+    """RFC 8785 canonical JSON."""
+    if value is None:
+        return "null"
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, (int, float)):
+        return canonical_number(value)
+    if isinstance(value, str):
+        return json.dumps(value, ensure_ascii=False)
+    if isinstance(value, list):
+        return "[" + ",".join(canonical_json(v) for v in value) + "]"
+    if isinstance(value, dict):
+        keys = sorted(value, key=lambda k: k.encode("utf-16-be"))
+        return "{" + ",".join(json.dumps(k, ensure_ascii=False) + ":" + canonical_json(value[k]) for k in keys) + "}"
+    raise TypeError(f"not a JSON value: {value!r}")
+
+
+def compute_event_hash(event: dict) -> str:
+    data = (
+        event["trace_version"]
+        + event["event_id"]
+        + event["trace_id"]
+        + event["span_id"]
+        + (event.get("parent_span_id") or "")
+        + event["session_id"]
+        + str(event["sequence"])
+        + event["timestamp"]
+        + event["event_type"]
+        + canonical_json(event["payload"])
+        + event["previous_event_hash"]
+    )
+    return hashlib.sha256(data.encode("utf-8")).hexdigest()
+
+
+def load_vectors(name: str) -> list:
+    return json.loads((VECTORS / name).read_text(encoding="utf-8"))["vectors"]
+
+
+def test_canonical_json_vectors():
+    for vector in load_vectors("canonical-json.json"):
+        actual = canonical_json(json.loads(vector["input"]))
+        assert actual == vector["canonical"], vector["name"]
+
+
+def test_event_hash_vectors():
+    for vector in load_vectors("trace-hash.json"):
+        event = vector["event"]
+        assert canonical_json(event["payload"]) == vector["canonical_payload"], vector["name"]
+        assert compute_event_hash(event) == vector["event_hash"], vector["name"]
+
+
+def test_golden_trace_hashes():
+    for path in sorted(GOLDEN_ROOT.glob("*/golden-trace.jsonl")):
+        previous = "0" * 64
+        for line in path.read_text(encoding="utf-8").splitlines():
+            if not line.strip():
+                continue
+            event = json.loads(line)
+            assert event["previous_event_hash"] == previous, path
+            assert compute_event_hash(event) == event["event_hash"], path
+            previous = event["event_hash"]
+
+
+if __name__ == "__main__":
+    for name, test in list(globals().items()):
+        if name.startswith("test_"):
+            test()
+            print(f"ok {name}")